@@ -0,0 +1,63 @@
+//! Compares spawning each batch entity by entity against `process_batch`, which reuses the
+//! previous batch's entities and falls back to `spawn_batch`, and against `process_batch` with
+//! the systems running on parallel chunks (`--chunk-size`).
+//!
+//! Run with `cargo bench --bench batch_pipeline`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ecs_ai_compliance::components::{ComplianceStatus, Event, RiskAssessment};
+use ecs_ai_compliance::ecs::*;
+use ecs_ai_compliance::policy::Policy;
+use hecs::World;
+use std::hint::black_box;
+
+/// Batch sizes around the default of 1,000,000 events/s spread over 4 to 16 threads.
+const BATCH_SIZES: [usize; 3] = [500, 2_500, 10_000];
+
+/// Chunk size for the parallel variant.
+const CHUNK_SIZE: u32 = 1_024;
+
+/// The pipeline as it ran before batch spawning: one `spawn` per event and a clear afterwards.
+fn per_entity_spawn(world: &mut World, events: &[Event], policy: &Policy) -> u64 {
+    let compliance = ComplianceStatus {
+        flags: policy.compliance.all_compliant(),
+        waived: 0,
+    };
+    for &(service, usage, user, purpose, region, basis, pii, timestamp) in events {
+        world.spawn((service, usage, user, purpose, region, basis, pii, timestamp, compliance, RiskAssessment::default()));
+    }
+    eu_ai_act_system(world, Parallelism::Sequential);
+    gdpr_system(world, &policy.vendors, Parallelism::Sequential);
+    internal_policy_system(world, &policy.allowlist, Parallelism::Sequential);
+    purpose_limitation_system(world, Parallelism::Sequential);
+    data_residency_system(world, Parallelism::Sequential);
+    let metrics = risk_assessment_system(world, policy, Parallelism::Sequential);
+    world.clear();
+    metrics.total_events
+}
+
+fn batch_pipeline(c: &mut Criterion) {
+    let policy = Policy::builtin();
+    let mut group = c.benchmark_group("batch_pipeline");
+    for size in BATCH_SIZES {
+        let events = generate_ai_events(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("per_entity_spawn", size), &events, |b, events| {
+            let mut world = World::new();
+            b.iter(|| black_box(per_entity_spawn(&mut world, events, &policy)));
+        });
+        group.bench_with_input(BenchmarkId::new("process_batch", size), &events, |b, events| {
+            let mut world = World::new();
+            b.iter(|| black_box(process_batch(&mut world, events, &policy, Parallelism::Sequential, None).total_events));
+        });
+        group.bench_with_input(BenchmarkId::new("process_batch_chunked", size), &events, |b, events| {
+            let mut world = World::new();
+            let parallelism = Parallelism::Chunked(CHUNK_SIZE);
+            b.iter(|| black_box(process_batch(&mut world, events, &policy, parallelism, None).total_events));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, batch_pipeline);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+/// Compiles the gRPC schema when the `grpc` feature is enabled, using a vendored `protoc` so
+/// that no system installation is needed.
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/compliance.proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available for this platform");
+        // SAFETY: build scripts are single-threaded.
+        unsafe { std::env::set_var("PROTOC", protoc) };
+        tonic_build::compile_protos("proto/compliance.proto").expect("proto/compliance.proto compiles");
+    }
+}
@@ -0,0 +1,343 @@
+use crate::batching::RateControl;
+use crate::components::AI_ACT_TIERS;
+use crate::constants::{DEPARTMENT_NAMES, LAWFUL_BASIS_NAMES, PII_TYPE_NAMES, REGION_NAMES, SERVICE_NAMES, VENDOR_NAMES};
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError};
+use crate::frontend::{ControlCommand, FrontendCommand};
+use crate::metrics::{ComplianceMetrics, LatestMetrics, SharedLatestMetrics};
+use crate::policy::{LivePolicy, Policy};
+use crate::silence::MAX_SILENCE_MINUTES;
+use crossbeam_channel::{bounded, Sender};
+use serde_json::{json, Map, Value};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// How long the admin server waits for a request before dropping the connection.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest request head, and longest body, the admin server reads.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// How often the admin server checks the stop signal while no client is connecting.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a policy reload may take before the request fails.
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Everything the admin API inspects and controls.
+pub struct AdminContext {
+    /// Target rate and pause state shared with the local workers.
+    pub rate: Arc<RateControl>,
+    /// The active policy.
+    pub policy: Arc<LivePolicy>,
+    /// Metrics returned by `/metrics.json`, updated by the aggregator every reporting interval.
+    pub latest: SharedLatestMetrics,
+    /// Channel sender for requests to the monitor loop, which reloads the policy and queues
+    /// violation spikes.
+    pub control: Sender<ControlCommand>,
+    /// Channel sender for notices shown on the frontend.
+    pub notices: Sender<FrontendCommand>,
+    /// When the monitor started, for reporting its uptime.
+    pub started: Instant,
+}
+
+/// Serves the admin API over HTTP until the stop signal is set.
+///
+/// `GET /status` and `GET /metrics.json` inspect the running monitor; `POST /pause`,
+/// `POST /resume`, `POST /rate?events_per_second=N`, `POST /policy/reload`,
+/// `POST /spike[?events=N]`, `POST /silence?minutes=N`, and `POST /unsilence` control it and
+/// answer with the resulting status. Every response is JSON; failed requests carry an `error`
+/// message. Requests are handled one at a time on this thread.
+///
+/// # Arguments
+///
+/// * `listener` - Socket bound to the `--admin-listen` address.
+/// * `context` - Rate control, policy, metrics, and channels shared with the monitor.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn serve_admin(listener: TcpListener, context: AdminContext, stop_signal: Arc<AtomicBool>, errors: Sender<ErrorReport>) {
+    if let Err(e) = listener.set_nonblocking(true) {
+        report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade);
+        return;
+    }
+    while !stop_signal.load(Ordering::Relaxed) {
+        match listener.accept() {
+            // A client that disconnects or stalls only affects its own request.
+            Ok((stream, _peer)) => {
+                let _ = respond(stream, &context);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => report(&errors, MonitorError::Io(e), ErrorPolicy::Retry),
+        }
+    }
+}
+
+/// Reads a single request and answers it.
+fn respond(mut stream: TcpStream, context: &AdminContext) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    // Parameters come in the query string, but the headers and any body are read anyway so that
+    // closing the connection does not reset it before the client has read the response.
+    let mut reader = BufReader::new(&stream);
+    let mut head = (&mut reader).take(MAX_REQUEST_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let mut header = String::new();
+    while head.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+        header.clear();
+    }
+    io::copy(&mut reader.take(content_length.min(MAX_REQUEST_BYTES)), &mut io::sink())?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, body) = handle(method, path, query, context);
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Routes a request and returns the response status and body.
+fn handle(method: &str, path: &str, query: &str, context: &AdminContext) -> (&'static str, Value) {
+    let method_allowed = match path {
+        "/status" | "/metrics.json" => method == "GET",
+        "/pause" | "/resume" | "/rate" | "/policy/reload" | "/spike" | "/silence" | "/unsilence" => method == "POST",
+        _ => return ("404 Not Found", error_body("not found")),
+    };
+    if !method_allowed {
+        return ("405 Method Not Allowed", error_body(&format!("{} does not accept {}", path, method)));
+    }
+    match path {
+        "/metrics.json" => {
+            let latest = context.latest.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            return ("200 OK", metrics_json(&latest, &context.policy.current()));
+        }
+        "/pause" | "/resume" => {
+            let paused = path == "/pause";
+            if context.rate.set_paused(paused) != paused {
+                let message = if paused { "Workers paused by the admin API" } else { "Workers resumed by the admin API" };
+                info!("{}", message);
+                let _ = context.notices.send(FrontendCommand::ShowNotice(message.to_string()));
+            }
+        }
+        "/rate" => {
+            let rate = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("events_per_second="))
+                .map(|value| value.parse::<u32>());
+            match rate {
+                Some(Ok(rate)) if rate > 0 => {
+                    context.rate.set_rate(rate);
+                    let message = format!("Target rate set to {} events/second by the admin API", rate);
+                    info!("{}", message);
+                    let _ = context.notices.send(FrontendCommand::ShowNotice(message));
+                }
+                _ => return ("400 Bad Request", error_body("expected ?events_per_second=N with N at least 1")),
+            }
+        }
+        "/policy/reload" => {
+            let (reply, result) = bounded(1);
+            if context.control.send(ControlCommand::ReloadPolicy { reply }).is_err() {
+                return ("503 Service Unavailable", error_body("the monitor is shutting down"));
+            }
+            match result.recv_timeout(RELOAD_TIMEOUT) {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => return ("422 Unprocessable Entity", error_body(&e.to_string())),
+                Err(_) => return ("503 Service Unavailable", error_body("the monitor did not reload the policy in time")),
+            }
+        }
+        "/spike" => {
+            let events = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("events="))
+                .map(|value| value.parse::<usize>());
+            let events = match events {
+                None => None,
+                Some(Ok(events)) if events > 0 => Some(events),
+                _ => return ("400 Bad Request", error_body("expected no query or ?events=N with N at least 1")),
+            };
+            if context.control.send(ControlCommand::InjectSpike { events }).is_err() {
+                return ("503 Service Unavailable", error_body("the monitor is shutting down"));
+            }
+        }
+        "/silence" | "/unsilence" => {
+            let minutes = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("minutes="))
+                .map(|value| value.parse::<u64>());
+            let duration = match minutes {
+                // Unsilencing ends the silence requested last, by silencing for no time at all.
+                None if path == "/unsilence" => Duration::ZERO,
+                Some(Ok(minutes)) if path == "/silence" && (1..=MAX_SILENCE_MINUTES).contains(&minutes) => {
+                    Duration::from_secs(minutes * 60)
+                }
+                _ if path == "/unsilence" => return ("400 Bad Request", error_body("expected no query")),
+                _ => return ("400 Bad Request", error_body(&format!("expected ?minutes=N with N from 1 to {}", MAX_SILENCE_MINUTES))),
+            };
+            if context.control.send(ControlCommand::SilenceAlerts { duration }).is_err() {
+                return ("503 Service Unavailable", error_body("the monitor is shutting down"));
+            }
+        }
+        _ => {}
+    }
+    let latest = context.latest.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let status = status_json(&context.rate, &context.policy, &latest, context.started.elapsed());
+    ("200 OK", status)
+}
+
+/// The body of a failed request.
+fn error_body(message: &str) -> Value {
+    json!({ "error": message })
+}
+
+/// Builds the `/status` response: whether the workers run and at what rate, the policy in
+/// effect, and the headline metrics.
+///
+/// # Arguments
+///
+/// * `rate` - Target rate and pause state of the local workers.
+/// * `live_policy` - The active policy.
+/// * `latest` - The latest cumulative metrics and when they were updated.
+/// * `uptime` - Time since the monitor started.
+pub fn status_json(rate: &RateControl, live_policy: &LivePolicy, latest: &LatestMetrics, uptime: Duration) -> Value {
+    let policy = live_policy.current();
+    let metrics = &latest.metrics;
+    json!({
+        "state": if rate.is_paused() { "paused" } else { "running" },
+        "target_rate": rate.rate(),
+        "workers": rate.workers(),
+        "uptime_secs": uptime.as_secs(),
+        "events": metrics.total_events,
+        "rate": metrics.processing_rate(),
+        "compliance_percentage": metrics.compliance_percentage(),
+        "metrics_updated_at_ms": latest.updated_at_ms,
+        "policy": {
+            "version": policy.version,
+            "reloads": live_policy.reloads(),
+            "checks": policy.compliance.iter().map(|(_, info)| info.key).collect::<Vec<_>>(),
+            "hipaa": policy.hipaa.is_some(),
+            "slo_targets": policy.slo.as_ref().map_or(0, |slo| slo.targets.len()),
+            "waivers": policy.waivers.len(),
+        },
+    })
+}
+
+/// Builds the `/metrics.json` response from the latest cumulative metrics, with checks,
+/// departments, and breakdowns keyed by name.
+///
+/// # Arguments
+///
+/// * `latest` - The latest cumulative metrics and when they were updated.
+/// * `policy` - The active policy, used to name the compliance checks and score compliance.
+pub fn metrics_json(latest: &LatestMetrics, policy: &Policy) -> Value {
+    let metrics = &latest.metrics;
+    let counts = |names: &[&str], counts: &[u64]| -> Map<String, Value> {
+        names.iter().zip(counts).map(|(name, count)| (name.to_string(), (*count).into())).collect()
+    };
+    let by_check = |counts: &dyn Fn(usize) -> u64| -> Map<String, Value> {
+        policy
+            .compliance
+            .iter()
+            .map(|(flag, info)| (info.key.to_string(), counts(flag.0 as usize).into()))
+            .collect()
+    };
+    let empty = ComplianceMetrics::default();
+    let departments: Map<String, Value> = DEPARTMENT_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let department = metrics.departments.get(index).unwrap_or(&empty);
+            let summary = json!({
+                "events": department.total_events,
+                "violations": department.total_violations(),
+                "compliance_percentage": policy.scoring.score(department),
+            });
+            (name.to_string(), summary)
+        })
+        .collect();
+    let tier_names: Vec<&str> = AI_ACT_TIERS.iter().map(|tier| tier.name()).collect();
+    json!({
+        "updated_at_ms": latest.updated_at_ms,
+        "events": metrics.total_events,
+        "rate": metrics.processing_rate(),
+        "compliance_percentage": policy.scoring.score(metrics),
+        "compliance_scoring": policy.scoring.mode.name(),
+        "violations": by_check(&|index| metrics.violation_counts.get(index).copied().unwrap_or(0)),
+        "waived": by_check(&|index| metrics.waived_counts.get(index).copied().unwrap_or(0)),
+        "waived_events": metrics.waived_events,
+        "shadow_ai_events": metrics.shadow_ai_events,
+        "risk": {
+            "high": metrics.high_risk_count,
+            "medium": metrics.medium_risk_count,
+            "low": metrics.low_risk_count,
+        },
+        "avg_data_sensitivity": metrics.avg_data_sensitivity,
+        "departments": departments,
+        "services": counts(&SERVICE_NAMES, &metrics.service_counts),
+        "vendors": counts(&VENDOR_NAMES, &metrics.vendor_counts),
+        "regions": counts(&REGION_NAMES, &metrics.region_counts),
+        "lawful_bases": counts(&LAWFUL_BASIS_NAMES, &metrics.lawful_basis_counts),
+        "pii_types": counts(&PII_TYPE_NAMES, &metrics.pii_type_counts),
+        "ai_act_tiers": counts(&tier_names, &metrics.ai_act_tier_counts),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::CumulativeMetrics;
+
+    #[test]
+    fn status_and_metrics_name_checks_and_reflect_the_rate_control() {
+        let policy = Arc::new(Policy::builtin());
+        let live_policy = LivePolicy::new(policy.clone());
+        let rate = RateControl::new(5_000, 4);
+        let mut metrics = ComplianceMetrics {
+            total_events: 200,
+            violation_counts: vec![0, 10, 0, 0, 0],
+            service_counts: [200, 0, 0, 0, 0],
+            ..ComplianceMetrics::default()
+        };
+        metrics.departments = vec![ComplianceMetrics::default(); DEPARTMENT_NAMES.len()];
+        metrics.departments[1] = ComplianceMetrics {
+            total_events: 50,
+            violation_counts: vec![0, 10, 0, 0, 0],
+            ..ComplianceMetrics::default()
+        };
+        let latest = LatestMetrics { metrics: CumulativeMetrics::resume(metrics), updated_at_ms: 42 };
+
+        rate.set_paused(true);
+        let status = status_json(&rate, &live_policy, &latest, Duration::from_secs(90));
+        assert_eq!(status["state"], "paused");
+        assert_eq!(status["target_rate"], 5_000);
+        assert_eq!(status["workers"], 4);
+        assert_eq!(status["events"], 200);
+        assert_eq!(status["policy"]["checks"][1], "gdpr");
+        assert_eq!(status["policy"]["reloads"], 0);
+
+        let metrics = metrics_json(&latest, &policy);
+        assert_eq!(metrics["updated_at_ms"], 42);
+        assert_eq!(metrics["violations"]["gdpr"], 10);
+        assert_eq!(metrics["waived"]["gdpr"], 0);
+        assert_eq!(metrics["services"][SERVICE_NAMES[0]], 200);
+        assert_eq!(metrics["departments"][DEPARTMENT_NAMES[1]]["violations"], 10);
+        let department_compliance = metrics["departments"][DEPARTMENT_NAMES[1]]["compliance_percentage"].as_f64().unwrap();
+        assert!((department_compliance - 96.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+/// Most alerts kept in the history; the oldest cleared ones are dropped first.
+pub const MAX_ALERTS: usize = 200;
+
+/// How urgent an alert is, least urgent first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    /// Returns the display name of the severity.
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "Info",
+            AlertSeverity::Warning => "Warning",
+            AlertSeverity::Critical => "Critical",
+        }
+    }
+}
+
+/// An alert raised by one of the monitor's alert rules.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Alert {
+    /// Identifies the alert for acknowledgement, unique within the run.
+    pub id: u64,
+    /// Key of the rule that raised the alert.
+    pub rule: String,
+    /// What the alert is about, e.g. a department or waiver; the rule raises at most one active
+    /// alert per subject.
+    pub subject: String,
+    pub message: String,
+    /// Measurement that tripped the rule, formatted for display.
+    pub value: String,
+    pub severity: AlertSeverity,
+    pub raised_at_ms: u64,
+    /// Time the condition stopped holding; `None` while the alert is active.
+    pub cleared_at_ms: Option<u64>,
+    /// Whether someone has seen the alert; acknowledged alerts no longer flash.
+    pub acknowledged: bool,
+    /// Whether the alert was raised during a silence, so it was recorded but not dispatched.
+    pub silenced: bool,
+}
+
+impl Alert {
+    /// Returns true while the alert's condition holds.
+    pub fn is_active(&self) -> bool {
+        self.cleared_at_ms.is_none()
+    }
+}
+
+/// The alerts of the monitor, oldest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AlertLog {
+    alerts: Vec<Alert>,
+    next_id: u64,
+    /// Description of the silence in effect, if alerts are not being dispatched.
+    silence: Option<String>,
+}
+
+impl AlertLog {
+    /// Returns the alerts in the order they were raised.
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Returns a description of the silence in effect, or `None` if alerts are dispatched.
+    pub fn silence(&self) -> Option<&str> {
+        self.silence.as_deref()
+    }
+
+    /// Sets the silence in effect, marking the alerts raised from now on as silenced.
+    ///
+    /// # Returns
+    ///
+    /// True if the silence changed.
+    pub fn set_silence(&mut self, silence: Option<String>) -> bool {
+        let changed = self.silence != silence;
+        self.silence = silence;
+        changed
+    }
+
+    /// Raises an alert, unless the rule already has an active one for the subject.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - Key of the rule raising the alert.
+    /// * `subject` - What the alert is about.
+    /// * `severity` - How urgent the alert is.
+    /// * `message` - Description of the alert.
+    /// * `value` - Measurement that tripped the rule.
+    /// * `now_ms` - Current wall-clock time, in milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// True if a new alert was raised.
+    pub fn raise(&mut self, rule: &str, subject: &str, severity: AlertSeverity, message: &str, value: String, now_ms: u64) -> bool {
+        if self.find_active(rule, subject).is_some() {
+            return false;
+        }
+        if self.alerts.len() == MAX_ALERTS {
+            let oldest = self.alerts.iter().position(|alert| !alert.is_active()).unwrap_or(0);
+            self.alerts.remove(oldest);
+        }
+        self.alerts.push(Alert {
+            id: self.next_id,
+            rule: rule.to_string(),
+            subject: subject.to_string(),
+            message: message.to_string(),
+            value,
+            severity,
+            raised_at_ms: now_ms,
+            cleared_at_ms: None,
+            acknowledged: false,
+            silenced: self.silence.is_some(),
+        });
+        self.next_id += 1;
+        true
+    }
+
+    /// Clears the rule's active alert for the subject, if any.
+    ///
+    /// # Returns
+    ///
+    /// True if an alert was cleared.
+    pub fn clear(&mut self, rule: &str, subject: &str, now_ms: u64) -> bool {
+        match self.find_active(rule, subject) {
+            Some(index) => {
+                self.alerts[index].cleared_at_ms = Some(now_ms);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clears every active alert of the given rules, e.g. when the policy they check is reloaded.
+    pub fn clear_rules(&mut self, rules: &[&str], now_ms: u64) {
+        for alert in &mut self.alerts {
+            if alert.is_active() && rules.contains(&alert.rule.as_str()) {
+                alert.cleared_at_ms = Some(now_ms);
+            }
+        }
+    }
+
+    /// Marks the alert with the given id as acknowledged.
+    ///
+    /// # Returns
+    ///
+    /// The acknowledged alert, or `None` if there is no alert with that id any more.
+    pub fn acknowledge(&mut self, id: u64) -> Option<&Alert> {
+        let alert = self.alerts.iter_mut().find(|alert| alert.id == id)?;
+        alert.acknowledged = true;
+        Some(alert)
+    }
+
+    /// Returns the number of active alerts.
+    pub fn active_count(&self) -> usize {
+        self.alerts.iter().filter(|alert| alert.is_active()).count()
+    }
+
+    /// Returns the most severe active alert, preferring unacknowledged and then newer ones
+    /// among equally severe alerts.
+    pub fn most_severe_active(&self) -> Option<&Alert> {
+        self.alerts
+            .iter()
+            .filter(|alert| alert.is_active())
+            .max_by_key(|alert| (alert.severity, !alert.acknowledged, alert.id))
+    }
+
+    /// Returns the index of the rule's active alert for the subject.
+    fn find_active(&self, rule: &str, subject: &str) -> Option<usize> {
+        self.alerts
+            .iter()
+            .position(|alert| alert.is_active() && alert.rule == rule && alert.subject == subject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_are_raised_once_until_cleared_and_the_most_severe_is_shown() {
+        let mut log = AlertLog::default();
+        assert!(log.raise("slo_burn", "HR", AlertSeverity::Critical, "HR is burning", "4.0x".to_string(), 1));
+        assert!(!log.raise("slo_burn", "HR", AlertSeverity::Critical, "HR is burning", "5.0x".to_string(), 2));
+        assert!(log.set_silence(Some("load test".to_string())));
+        assert!(!log.set_silence(Some("load test".to_string())));
+        assert!(log.raise("waiver_expiring", "waiver RISK-1", AlertSeverity::Warning, "RISK-1 expires", "2026-01-01".to_string(), 3));
+        assert_eq!(log.alerts().iter().map(|alert| alert.silenced).collect::<Vec<_>>(), [false, true]);
+        assert_eq!(log.alerts().len(), 2);
+        assert_eq!(log.most_severe_active().map(|alert| alert.id), Some(0));
+
+        assert_eq!(log.acknowledge(0).map(|alert| alert.acknowledged), Some(true));
+        assert!(log.clear("slo_burn", "HR", 4));
+        assert!(!log.clear("slo_burn", "HR", 5));
+        assert_eq!(log.alerts()[0].cleared_at_ms, Some(4));
+        assert_eq!(log.most_severe_active().map(|alert| alert.rule.as_str()), Some("waiver_expiring"));
+
+        // Once cleared, the condition raises a new alert.
+        assert!(log.raise("slo_burn", "HR", AlertSeverity::Critical, "HR is burning", "3.0x".to_string(), 6));
+        assert_eq!(log.active_count(), 2);
+        log.clear_rules(&["slo_burn", "waiver_expiring"], 7);
+        assert!(log.most_severe_active().is_none());
+        assert!(log.acknowledge(99).is_none());
+    }
+}
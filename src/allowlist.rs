@@ -0,0 +1,155 @@
+use crate::components::AIService;
+use crate::constants::{DEPARTMENT_NAMES, MODELS_PER_SERVICE, MODEL_NAMES, SERVICE_NAMES, VENDOR_NAMES};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Index into `DEPARTMENT_NAMES` of the department restricted by the built-in allowlist.
+const FINANCE_DEPARTMENT: usize = 2;
+
+/// Services approved for Finance by the built-in allowlist: Claude and Copilot.
+const FINANCE_SERVICES: [u8; 2] = [1, 3];
+
+/// A service, vendor, and model combination approved for a department. A part that is `None`
+/// matches any service, vendor, or model.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ApprovedUse {
+    /// Index into `SERVICE_NAMES`.
+    pub service: Option<u8>,
+    /// Index into `VENDOR_NAMES`.
+    pub vendor: Option<u8>,
+    /// Index into `MODEL_NAMES`.
+    pub model: Option<u8>,
+}
+
+impl ApprovedUse {
+    /// Returns true if the event's service, vendor, and model match this approved use.
+    pub fn matches(&self, service: &AIService) -> bool {
+        self.service.is_none_or(|index| index == service.name_idx)
+            && self.vendor.is_none_or(|index| index == service.vendor_idx)
+            && self.model.is_none_or(|index| index == service.model_idx)
+    }
+}
+
+/// An approved use as written in a policy file, naming its parts.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ApprovedUseSpec {
+    service: Option<String>,
+    vendor: Option<String>,
+    model: Option<String>,
+}
+
+/// The departments restricted to approved uses, and the uses approved for each. Departments
+/// without an entry may use any service, vendor, and model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allowlist {
+    /// Approved uses indexed like `DEPARTMENT_NAMES`, or `None` for unrestricted departments.
+    departments: [Option<Vec<ApprovedUse>>; DEPARTMENT_NAMES.len()],
+}
+
+impl Allowlist {
+    /// Creates the built-in allowlist, which only restricts Finance, to Claude and Copilot.
+    pub fn builtin() -> Self {
+        let mut allowlist = Allowlist { departments: Default::default() };
+        allowlist.departments[FINANCE_DEPARTMENT] =
+            Some(FINANCE_SERVICES.iter().map(|&service| ApprovedUse { service: Some(service), ..ApprovedUse::default() }).collect());
+        allowlist
+    }
+
+    /// Builds the allowlist from the approved uses of each restricted department.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - Approved uses keyed by department name as in `DEPARTMENT_NAMES`.
+    pub fn from_spec(spec: BTreeMap<String, Vec<ApprovedUseSpec>>) -> Result<Self, String> {
+        let mut allowlist = Allowlist { departments: Default::default() };
+        for (department, uses) in spec {
+            let index = lookup(&DEPARTMENT_NAMES, "department", &department)?;
+            let mut approved = Vec::new();
+            for spec in uses {
+                let approved_use = ApprovedUse {
+                    service: spec.service.as_deref().map(|name| lookup(&SERVICE_NAMES, "service", name)).transpose()?,
+                    vendor: spec.vendor.as_deref().map(|name| lookup(&VENDOR_NAMES, "vendor", name)).transpose()?,
+                    model: spec.model.as_deref().map(|name| lookup(&MODEL_NAMES, "model", name)).transpose()?,
+                };
+                if approved_use == ApprovedUse::default() {
+                    return Err(format!("allowlist entry for {} must name a service, vendor, or model", department));
+                }
+                if let (Some(service), Some(model)) = (approved_use.service, approved_use.model)
+                    && model as usize / MODELS_PER_SERVICE != service as usize
+                {
+                    return Err(format!(
+                        "allowlist model '{}' is not offered by {}",
+                        MODEL_NAMES[model as usize], SERVICE_NAMES[service as usize]
+                    ));
+                }
+                approved.push(approved_use);
+            }
+            allowlist.departments[index as usize] = Some(approved);
+        }
+        Ok(allowlist)
+    }
+
+    /// Iterates over the restricted departments' names and approved uses, in `DEPARTMENT_NAMES` order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &[ApprovedUse])> {
+        DEPARTMENT_NAMES
+            .iter()
+            .zip(&self.departments)
+            .filter_map(|(name, approved)| approved.as_deref().map(|approved| (*name, approved)))
+    }
+
+    /// Returns true if the department may use the event's service, vendor, and model.
+    pub fn permits(&self, department_idx: u8, service: &AIService) -> bool {
+        match &self.departments[department_idx as usize] {
+            Some(approved) => approved.iter().any(|approved_use| approved_use.matches(service)),
+            None => true,
+        }
+    }
+}
+
+/// Returns the index of `name` in `names`, or an error naming the kind of thing looked up.
+fn lookup(names: &[&str], kind: &str, name: &str) -> Result<u8, String> {
+    names
+        .iter()
+        .position(|candidate| *candidate == name)
+        .map(|index| index as u8)
+        .ok_or_else(|| format!("unknown allowlist {} '{}'", kind, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn departments_are_limited_to_their_approved_uses() {
+        let service = |name_idx: u8, vendor_idx, model_idx| AIService { name_idx, vendor_idx, model_idx };
+        let builtin = Allowlist::builtin();
+        assert!(builtin.permits(2, &service(1, 4, 3)));
+        assert!(!builtin.permits(2, &service(0, 0, 0)));
+        assert!(builtin.permits(0, &service(0, 0, 0)));
+
+        let spec: BTreeMap<String, Vec<ApprovedUseSpec>> = serde_json::from_str(
+            r#"{"Legal": [{"service": "Claude", "vendor": "Anthropic", "model": "Claude Opus"}, {"vendor": "Microsoft"}], "HR": []}"#,
+        )
+        .unwrap();
+        let allowlist = Allowlist::from_spec(spec).unwrap();
+        assert!(allowlist.permits(4, &service(1, 1, 3)));
+        assert!(!allowlist.permits(4, &service(1, 1, 2)));
+        assert!(!allowlist.permits(4, &service(1, 0, 3)));
+        assert!(allowlist.permits(4, &service(2, 3, 4)));
+        // HR has no approved uses, and Finance is no longer restricted once the policy file lists departments.
+        assert!(!allowlist.permits(3, &service(1, 1, 3)));
+        assert!(allowlist.permits(2, &service(0, 0, 0)));
+
+        for (spec, error) in [
+            (r#"{"Sales": []}"#, "unknown allowlist department 'Sales'"),
+            (r#"{"Legal": [{"model": "GPT-5"}]}"#, "unknown allowlist model 'GPT-5'"),
+            (r#"{"Legal": [{}]}"#, "must name a service, vendor, or model"),
+            (r#"{"Legal": [{"service": "Claude", "model": "o3"}]}"#, "allowlist model 'o3' is not offered by Claude"),
+        ] {
+            let spec = serde_json::from_str(spec).unwrap();
+            let message = Allowlist::from_spec(spec).unwrap_err();
+            assert!(message.contains(error), "{}", message);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use crate::compliance_report::ReportFormat;
+use crate::components::{ComplianceStatus, Event};
+use crate::ecs::{process_batch, Parallelism};
+use crate::error::{MonitorError, Result};
+use crate::policy::Policy;
+use crate::replay::EventRecord;
+use hecs::World;
+use serde::Serialize;
+use std::fmt::Write;
+use std::io::BufRead;
+
+/// Events evaluated per batch by the `analyze` subcommand.
+const ANALYZE_BATCH_SIZE: usize = 10_000;
+
+/// A policy to analyze, and the name it is reported under.
+pub struct NamedPolicy {
+    pub name: String,
+    pub policy: Policy,
+}
+
+/// What each policy would flag in an event log, from the `analyze` subcommand.
+#[derive(Debug, Serialize)]
+pub struct ImpactReport {
+    pub source: String,
+    /// Events read from the log and evaluated under every policy.
+    pub events: u64,
+    /// Lines that could not be parsed or named something unknown to this build.
+    pub skipped_records: u64,
+    pub policies: Vec<PolicyImpact>,
+}
+
+/// What one policy would flag in an event log.
+#[derive(Debug, Serialize)]
+pub struct PolicyImpact {
+    pub name: String,
+    /// Events flagged by at least one check, after waivers.
+    pub flagged_events: u64,
+    /// Number of events flagged by exactly as many checks as the index, from none up to all.
+    pub events_by_checks_failed: Vec<u64>,
+    pub checks: Vec<CheckImpact>,
+    /// Events flagged by both checks of every pair, in check order.
+    pub overlaps: Vec<CheckOverlap>,
+}
+
+/// What a single check would flag in an event log.
+#[derive(Debug, Serialize)]
+pub struct CheckImpact {
+    pub key: &'static str,
+    pub name: &'static str,
+    /// Events the check flags, after waivers.
+    pub flagged: u64,
+    /// Events the check flags and no other check does.
+    pub only_check: u64,
+    /// Events whose violation of the check a waiver exempted.
+    pub waived: u64,
+}
+
+/// Events flagged by both of two checks.
+#[derive(Debug, Serialize)]
+pub struct CheckOverlap {
+    pub first: &'static str,
+    pub second: &'static str,
+    pub events: u64,
+}
+
+/// Accumulates the impact of one policy over the batches of an event log.
+struct ImpactAnalysis {
+    name: String,
+    policy: Policy,
+    world: World,
+    flagged: Vec<u64>,
+    only_check: Vec<u64>,
+    waived: Vec<u64>,
+    /// Events flagged by both checks, indexed by the first and then the second check's flag.
+    overlaps: Vec<Vec<u64>>,
+    events_by_checks_failed: Vec<u64>,
+}
+
+impl ImpactAnalysis {
+    fn new(NamedPolicy { name, policy }: NamedPolicy) -> Self {
+        let checks = policy.compliance.len();
+        ImpactAnalysis {
+            name,
+            policy,
+            world: World::new(),
+            flagged: vec![0; checks],
+            only_check: vec![0; checks],
+            waived: vec![0; checks],
+            overlaps: vec![vec![0; checks]; checks],
+            events_by_checks_failed: vec![0; checks + 1],
+        }
+    }
+
+    /// Evaluates a batch and counts the checks each event fails.
+    fn add_batch(&mut self, events: &[Event], parallelism: Parallelism) {
+        process_batch(&mut self.world, events, &self.policy, parallelism, None);
+        let all_checks = self.policy.compliance.all_compliant();
+        // The world holds exactly the entities of the batch just evaluated.
+        for (_, status) in self.world.query_mut::<&ComplianceStatus>() {
+            let failed = all_checks & !status.flags;
+            self.events_by_checks_failed[failed.count_ones() as usize] += 1;
+            for check in set_bits(status.waived) {
+                self.waived[check] += 1;
+            }
+            for check in set_bits(failed) {
+                self.flagged[check] += 1;
+                if failed.count_ones() == 1 {
+                    self.only_check[check] += 1;
+                }
+                for other in set_bits(failed >> (check + 1)) {
+                    self.overlaps[check][check + 1 + other] += 1;
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> PolicyImpact {
+        let infos: Vec<_> = self.policy.compliance.iter().map(|(_, info)| info).collect();
+        let checks = infos
+            .iter()
+            .enumerate()
+            .map(|(index, info)| CheckImpact {
+                key: info.key,
+                name: info.name,
+                flagged: self.flagged[index],
+                only_check: self.only_check[index],
+                waived: self.waived[index],
+            })
+            .collect();
+        let mut overlaps = Vec::new();
+        for (first, row) in self.overlaps.iter().enumerate() {
+            for (second, events) in row.iter().enumerate().skip(first + 1) {
+                overlaps.push(CheckOverlap { first: infos[first].key, second: infos[second].key, events: *events });
+            }
+        }
+        PolicyImpact {
+            name: self.name,
+            flagged_events: self.events_by_checks_failed.iter().skip(1).sum(),
+            events_by_checks_failed: self.events_by_checks_failed,
+            checks,
+            overlaps,
+        }
+    }
+}
+
+/// Iterates over the positions of the set bits of `bits`, lowest first.
+fn set_bits(mut bits: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (bits != 0).then(|| {
+            let position = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            position
+        })
+    })
+}
+
+/// Evaluates every event of an NDJSON event log under each policy and reports what each check
+/// would flag.
+///
+/// Events are evaluated in batches; waivers are checked against the recorded time of each event.
+///
+/// # Arguments
+///
+/// * `source` - Name of the event log, shown in the report.
+/// * `reader` - The event log, in the format written by `--record`.
+/// * `policies` - The policies to evaluate the events under.
+/// * `parallelism` - How the systems iterate over each batch.
+pub fn analyze_events(source: &str, reader: impl BufRead, policies: Vec<NamedPolicy>, parallelism: Parallelism) -> Result<ImpactReport> {
+    let mut analyses: Vec<_> = policies.into_iter().map(ImpactAnalysis::new).collect();
+    let mut batch = Vec::with_capacity(ANALYZE_BATCH_SIZE);
+    let mut report = ImpactReport { source: source.to_string(), events: 0, skipped_records: 0, policies: Vec::new() };
+    let mut flush = |batch: &mut Vec<_>| {
+        for analysis in &mut analyses {
+            analysis.add_batch(batch, parallelism);
+        }
+        batch.clear();
+    };
+    for line in reader.lines() {
+        let line = line.map_err(MonitorError::EventLog)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(event) = serde_json::from_str::<EventRecord>(&line).ok().and_then(|record| record.to_components()) else {
+            report.skipped_records += 1;
+            continue;
+        };
+        batch.push(event);
+        report.events += 1;
+        if batch.len() == ANALYZE_BATCH_SIZE {
+            flush(&mut batch);
+        }
+    }
+    if !batch.is_empty() {
+        flush(&mut batch);
+    }
+    report.policies = analyses.into_iter().map(ImpactAnalysis::finish).collect();
+    Ok(report)
+}
+
+impl ImpactReport {
+    /// Renders the report in the given format, ending with a newline.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.render_text(),
+            ReportFormat::Json => serde_json::to_string_pretty(self).expect("reports serialize") + "\n",
+            ReportFormat::Markdown => self.render_markdown(),
+        }
+    }
+
+    /// Returns the share of the analyzed events, in percent.
+    fn share(&self, events: u64) -> f64 {
+        100.0 * events as f64 / self.events.max(1) as f64
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Impact analysis of {}", self.source);
+        let _ = writeln!(out, "  Events: {} ({} records skipped)", self.events, self.skipped_records);
+        for policy in &self.policies {
+            let _ = writeln!(out, "\nPolicy {}:", policy.name);
+            let _ = writeln!(
+                out,
+                "  Flagged events: {} ({:.2}%)",
+                policy.flagged_events,
+                self.share(policy.flagged_events)
+            );
+            let _ = writeln!(out, "  {:<24} {:>10} {:>8} {:>10} {:>10}", "Check", "Flagged", "Share", "Only check", "Waived");
+            for check in &policy.checks {
+                let _ = writeln!(
+                    out,
+                    "  {:<24} {:>10} {:>7.2}% {:>10} {:>10}",
+                    check.name,
+                    check.flagged,
+                    self.share(check.flagged),
+                    check.only_check,
+                    check.waived
+                );
+            }
+            let _ = writeln!(out, "  Overlaps (events flagged by both):");
+            let _ = write!(out, "  {:<24}", "");
+            for check in &policy.checks {
+                let _ = write!(out, " {:>10}", check.name);
+            }
+            let _ = writeln!(out);
+            for (row, check) in policy.checks.iter().enumerate() {
+                let _ = write!(out, "  {:<24}", check.name);
+                for column in 0..policy.checks.len() {
+                    let _ = write!(out, " {:>10}", policy.overlap(row, column).map_or_else(|| "-".to_string(), |events| events.to_string()));
+                }
+                let _ = writeln!(out);
+            }
+            let counts: Vec<_> = policy
+                .events_by_checks_failed
+                .iter()
+                .enumerate()
+                .map(|(failed, events)| format!("{} failed {}", events, failed))
+                .collect();
+            let _ = writeln!(out, "  Events by checks failed: {}", counts.join(", "));
+        }
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Impact analysis of {}\n", self.source);
+        let _ = writeln!(out, "{} events, {} records skipped.", self.events, self.skipped_records);
+        for policy in &self.policies {
+            let _ = writeln!(out, "\n## Policy {}\n", policy.name);
+            let _ = writeln!(
+                out,
+                "{} events flagged ({:.2}%).\n",
+                policy.flagged_events,
+                self.share(policy.flagged_events)
+            );
+            let _ = writeln!(out, "| Check | Flagged | Share | Only check | Waived |\n| --- | ---: | ---: | ---: | ---: |");
+            for check in &policy.checks {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {:.2}% | {} | {} |",
+                    check.name,
+                    check.flagged,
+                    self.share(check.flagged),
+                    check.only_check,
+                    check.waived
+                );
+            }
+            let _ = writeln!(out, "\n| Overlap | Events |\n| --- | ---: |");
+            for (row, first) in policy.checks.iter().enumerate() {
+                for (column, second) in policy.checks.iter().enumerate().skip(row + 1) {
+                    let events = policy.overlap(row, column).unwrap_or(0);
+                    let _ = writeln!(out, "| {} and {} | {} |", first.name, second.name, events);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl PolicyImpact {
+    /// Returns the events flagged by both of two checks, by their index; the events flagged by a
+    /// check if both are the same, or `None` if an index is out of range.
+    pub fn overlap(&self, first: usize, second: usize) -> Option<u64> {
+        let checks = self.checks.len();
+        if first >= checks || second >= checks {
+            return None;
+        }
+        if first == second {
+            return Some(self.checks[first].flagged);
+        }
+        let (low, high) = (first.min(second), first.max(second));
+        // Pairs are listed row by row of the upper triangle.
+        let index = low * checks - low * (low + 1) / 2 + (high - low - 1);
+        self.overlaps.get(index).map(|overlap| overlap.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{AIService, LawfulBasis, PiiTypes, Purpose, Region, Timestamp, Usage, UserId};
+    use crate::compliance::{DATA_RESIDENCY, GDPR, INTERNAL_POLICY};
+    use crate::constants::{HEALTHCARE_DEPARTMENT, MODELS_PER_SERVICE};
+    use crate::policy::HipaaRules;
+
+    #[test]
+    fn analysis_counts_flagged_events_and_overlaps_per_policy() {
+        let event = |name_idx: u8, department_idx, data_sensitivity, region| {
+            let service = AIService { name_idx, vendor_idx: name_idx, model_idx: name_idx * MODELS_PER_SERVICE as u8 };
+            (
+                service,
+                Usage { department_idx, data_sensitivity },
+                UserId::UNKNOWN,
+                Purpose(5),
+                Region(region),
+                LawfulBasis(1),
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let (chatgpt, stability, finance, eu, us) = (0, 4, 2, 0, 3);
+        let events = [
+            // GDPR and data residency.
+            event(1, 0, 60, us),
+            // GDPR only.
+            event(1, 0, 60, eu),
+            // Internal policy only.
+            event(chatgpt, finance, 20, eu),
+            // GDPR, and HIPAA where it is enabled.
+            event(stability, HEALTHCARE_DEPARTMENT, 80, eu),
+            // Nothing.
+            event(1, 0, 10, eu),
+        ];
+        let mut log: String = events
+            .iter()
+            .map(|event| serde_json::to_string(&EventRecord::from_components(event)).unwrap() + "\n")
+            .collect();
+        log.push_str("not json\n\n");
+
+        let mut hipaa = Policy::builtin();
+        let flag = hipaa.compliance.claim("hipaa", "HIPAA").unwrap();
+        hipaa.hipaa = Some(HipaaRules { flag, baa_vendors: 0b0_1111 });
+        let policies = vec![
+            NamedPolicy { name: "built-in".to_string(), policy: Policy::builtin() },
+            NamedPolicy { name: "hipaa".to_string(), policy: hipaa },
+        ];
+        let report = analyze_events("events.ndjson", log.as_bytes(), policies, Parallelism::Sequential).unwrap();
+        assert_eq!((report.events, report.skipped_records), (5, 1));
+
+        let builtin = &report.policies[0];
+        assert_eq!(builtin.flagged_events, 4);
+        assert_eq!(builtin.events_by_checks_failed, [1, 3, 1, 0, 0, 0]);
+        let gdpr = &builtin.checks[GDPR.0 as usize];
+        assert_eq!((gdpr.flagged, gdpr.only_check), (3, 2));
+        assert_eq!(builtin.checks[INTERNAL_POLICY.0 as usize].only_check, 1);
+        assert_eq!(builtin.overlap(GDPR.0 as usize, DATA_RESIDENCY.0 as usize), Some(1));
+        assert_eq!(builtin.overlap(DATA_RESIDENCY.0 as usize, GDPR.0 as usize), Some(1));
+        assert_eq!(builtin.overlap(GDPR.0 as usize, INTERNAL_POLICY.0 as usize), Some(0));
+        assert_eq!(builtin.overlap(GDPR.0 as usize, GDPR.0 as usize), Some(3));
+
+        let with_hipaa = &report.policies[1];
+        assert_eq!(with_hipaa.checks.last().unwrap().flagged, 1);
+        assert_eq!(with_hipaa.overlap(GDPR.0 as usize, flag.0 as usize), Some(1));
+        assert_eq!(with_hipaa.events_by_checks_failed, [1, 2, 2, 0, 0, 0, 0]);
+
+        assert!(report.render(ReportFormat::Text).contains("Policy hipaa:"));
+        assert!(report.render(ReportFormat::Markdown).contains("| GDPR and Residency | 1 |"));
+        assert!(report.render(ReportFormat::Json).contains("\"only_check\""));
+    }
+}
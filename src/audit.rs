@@ -0,0 +1,480 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::error::{report, with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use crate::nist;
+use crate::policy::Policy;
+use crate::provenance::Provenance;
+use crate::signing::{from_hex, parse_public_key, to_hex};
+use crossbeam_channel::{Receiver, Sender};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Sink name used in error reports from the audit log writer.
+pub const AUDIT_SINK: &str = "audit log";
+
+/// Size of the audit writer's output buffer.
+const AUDIT_WRITE_BUFFER: usize = 1 << 20;
+
+/// Hash standing in for the line before the first record of an audit log.
+const GENESIS_HASH: [u8; 32] = [0; 32];
+
+/// Minimum time between signatures of the chain head while the audit log is written.
+const SIGNATURE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Source of event ids, unique across all workers for the lifetime of the process.
+static NEXT_EVENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Reserves `count` consecutive event ids and returns the first one.
+pub fn reserve_event_ids(count: usize) -> u64 {
+    NEXT_EVENT_ID.fetch_add(count as u64, Ordering::Relaxed)
+}
+
+/// Compact decision record emitted for every evaluated event in audit mode.
+///
+/// Carries the same event fields as [`crate::replay::EventRecord`], so audit logs can be
+/// replayed directly.
+#[derive(Serialize, Debug, Clone)]
+pub struct AuditRecord {
+    pub event_id: u64,
+    pub timestamp_ms: u64,
+    pub policy_version: &'static str,
+    pub service: &'static str,
+    pub model: &'static str,
+    pub vendor: &'static str,
+    pub department: &'static str,
+    pub data_sensitivity: u8,
+    /// Id of the user behind the event, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u32>,
+    /// What the event's prompt is used for, if declared.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<&'static str>,
+    /// Region the request is processed in, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<&'static str>,
+    /// GDPR lawful basis the event's data is processed on, if recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lawful_basis: Option<&'static str>,
+    /// Types of personally identifiable information in the event's data.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pii_types: Vec<&'static str>,
+    /// The event's EU AI Act risk tier and the provisions defining it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_act_tier: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_act_article: Option<&'static str>,
+    /// Keys of the compliance checks the event failed.
+    pub violations: Vec<&'static str>,
+    /// NIST AI RMF categories addressed by the failed checks.
+    pub nist_categories: Vec<&'static str>,
+    /// Keys of the failed checks exempted by a waiver; they are not listed in `violations`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub waived: Vec<&'static str>,
+    pub risk_score: u8,
+    pub risk_factors: u64,
+    /// Ids of the pipeline steps that produced the verdict, in the order they ran.
+    pub provenance: Provenance,
+}
+
+impl AuditRecord {
+    /// Builds an audit record from an evaluated entity's components, with an empty provenance and no user, purpose, region, lawful basis, PII types, or AI Act tier.
+    pub fn new(
+        event_id: EventId,
+        policy: &Policy,
+        timestamp_ms: u64,
+        service: &AIService,
+        usage: &Usage,
+        status: &ComplianceStatus,
+        risk: &RiskAssessment,
+    ) -> Self {
+        let violations = policy.compliance.violated_keys(status.flags);
+        AuditRecord {
+            event_id: event_id.0,
+            timestamp_ms,
+            policy_version: policy.version,
+            service: SERVICE_NAMES[service.name_idx as usize],
+            model: MODEL_NAMES[service.model_idx as usize],
+            vendor: VENDOR_NAMES[service.vendor_idx as usize],
+            department: DEPARTMENT_NAMES[usage.department_idx as usize],
+            data_sensitivity: usage.data_sensitivity,
+            user_id: None,
+            purpose: None,
+            region: None,
+            lawful_basis: None,
+            pii_types: Vec::new(),
+            ai_act_tier: None,
+            ai_act_article: None,
+            nist_categories: nist::violated_categories(&violations),
+            violations,
+            waived: policy.compliance.violated_keys(!status.waived),
+            risk_score: risk.score,
+            risk_factors: risk.factor_flags,
+            provenance: Provenance::default(),
+        }
+    }
+
+    /// Sets the steps that produced the verdict.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Sets the user behind the event.
+    pub fn with_user(mut self, user: UserId) -> Self {
+        self.user_id = user.known();
+        self
+    }
+
+    /// Sets what the event's prompt is used for.
+    pub fn with_purpose(mut self, purpose: Purpose) -> Self {
+        self.purpose = purpose.known().map(|purpose| PURPOSE_NAMES[purpose]);
+        self
+    }
+
+    /// Sets the event's EU AI Act risk tier.
+    pub fn with_ai_act_tier(mut self, tier: AiActTier) -> Self {
+        self.ai_act_tier = Some(tier.name());
+        self.ai_act_article = Some(tier.article());
+        self
+    }
+
+    /// Sets the region the request is processed in.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.region = region.known().map(|region| REGION_NAMES[region]);
+        self
+    }
+
+    /// Sets the lawful basis the event's data is processed on.
+    pub fn with_lawful_basis(mut self, basis: LawfulBasis) -> Self {
+        self.lawful_basis = basis.known().map(|basis| LAWFUL_BASIS_NAMES[basis]);
+        self
+    }
+
+    /// Sets the types of personally identifiable information in the event's data.
+    pub fn with_pii_types(mut self, pii: PiiTypes) -> Self {
+        self.pii_types = pii.indices().map(|index| PII_TYPE_NAMES[index]).collect();
+        self
+    }
+}
+
+/// An audit record as written to the log, linked to the SHA-256 hash of the previous line.
+#[derive(Serialize)]
+struct ChainedRecord<'a> {
+    #[serde(flatten)]
+    record: &'a AuditRecord,
+    prev_hash: &'a str,
+}
+
+/// The chain link of a logged record, the only field read when verifying.
+#[derive(Deserialize)]
+struct ChainLink {
+    prev_hash: Option<String>,
+}
+
+/// Signed statement that the first `records` lines of an audit log hash to `head`.
+///
+/// Stored next to the log (see [`signature_path`]), so removing or rewriting signed records
+/// is detected even though the chain itself can be recomputed by anyone.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChainSignature {
+    pub records: u64,
+    /// Hex-encoded SHA-256 hash of the last signed line.
+    pub head: String,
+    /// Hex-encoded Ed25519 public key of the signer.
+    pub public_key: String,
+    /// Hex-encoded Ed25519 signature of `"<records>:<head>"`.
+    pub signature: String,
+}
+
+impl ChainSignature {
+    /// Returns the signed message for a chain of `records` lines ending at `head`.
+    fn message(records: u64, head: &str) -> Vec<u8> {
+        format!("{}:{}", records, head).into_bytes()
+    }
+}
+
+/// Returns the path of the file holding the chain signature of the audit log at `path`.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".sig");
+    path.with_file_name(file_name)
+}
+
+/// Writes audit record batches to an NDJSON log until all senders are dropped.
+///
+/// Every line carries the SHA-256 hash of the previous line in `prev_hash`, so altering,
+/// removing, or reordering records breaks the chain. With a signing key, the chain head is also
+/// signed into the file at [`signature_path`] at most once per second and on close. Workers send
+/// through a bounded channel, so a slow disk applies backpressure to event processing instead
+/// of dropping decisions or buffering without limit. Write failures are handled according to
+/// `policy`.
+///
+/// # Arguments
+///
+/// * `path` - The file to write the audit log to.
+/// * `receiver` - Channel receiving batches of audit records from the workers.
+/// * `signing_key` - Key signing the chain head, if any.
+/// * `policy` - How to handle write failures.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn audit_writer(
+    path: &Path,
+    receiver: Receiver<Vec<AuditRecord>>,
+    signing_key: Option<SigningKey>,
+    policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+) {
+    if let Err(e) = write_audit_log(path, receiver, signing_key.as_ref(), policy, &errors) {
+        let action = if policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+        report(&errors, e, action);
+    }
+}
+
+/// Serializes each batch into the buffered writer, flushing whenever the channel runs dry.
+fn write_audit_log(
+    path: &Path,
+    receiver: Receiver<Vec<AuditRecord>>,
+    signing_key: Option<&SigningKey>,
+    policy: ErrorPolicy,
+    errors: &Sender<ErrorReport>,
+) -> Result<()> {
+    let file = with_retry(policy, AUDIT_SINK, errors, || File::create(path))?;
+    let mut writer = AuditWriter::new(file);
+    let mut line = Vec::with_capacity(256);
+    let mut prev_hash = to_hex(&GENESIS_HASH);
+    let mut records = 0u64;
+    let mut last_signed: Option<Instant> = None;
+    let sign = |records: u64, head: &str| -> Result<()> {
+        match signing_key {
+            Some(key) => with_retry(policy, AUDIT_SINK, errors, || write_chain_signature(path, key, records, head)),
+            None => Ok(()),
+        }
+    };
+    for batch in receiver.iter() {
+        for record in &batch {
+            line.clear();
+            serde_json::to_writer(&mut line, &ChainedRecord { record, prev_hash: &prev_hash }).map_err(|e| {
+                MonitorError::Sink {
+                    sink: AUDIT_SINK,
+                    source: e.into(),
+                }
+            })?;
+            prev_hash = to_hex(&Sha256::digest(&line));
+            records += 1;
+            line.push(b'\n');
+            writer.buffer.extend_from_slice(&line);
+            if writer.buffer.len() >= AUDIT_WRITE_BUFFER {
+                with_retry(policy, AUDIT_SINK, errors, || writer.flush())?;
+            }
+        }
+        if receiver.is_empty() {
+            with_retry(policy, AUDIT_SINK, errors, || writer.flush())?;
+            // The signature only ever covers flushed lines.
+            if last_signed.is_none_or(|signed| signed.elapsed() >= SIGNATURE_INTERVAL) {
+                sign(records, &prev_hash)?;
+                last_signed = Some(Instant::now());
+            }
+        }
+    }
+    with_retry(policy, AUDIT_SINK, errors, || writer.flush())?;
+    sign(records, &prev_hash)
+}
+
+/// Buffers audit log lines and writes them out so that a failed write can be retried without
+/// duplicating or splitting a record in the hash chain.
+struct AuditWriter {
+    file: File,
+    /// Length of the log up to the end of the last write that succeeded.
+    written: u64,
+    /// Complete lines not yet written.
+    buffer: Vec<u8>,
+}
+
+impl AuditWriter {
+    /// Wraps a newly created, empty log file.
+    fn new(file: File) -> Self {
+        AuditWriter { file, written: 0, buffer: Vec::with_capacity(AUDIT_WRITE_BUFFER) }
+    }
+
+    /// Writes the buffered lines after the last successful write, first cutting off whatever a
+    /// failed attempt left behind.
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.file.set_len(self.written)?;
+        self.file.seek(SeekFrom::Start(self.written))?;
+        self.file.write_all(&self.buffer)?;
+        self.written += self.buffer.len() as u64;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+/// Signs the chain head and replaces the log's signature file with it.
+fn write_chain_signature(path: &Path, key: &SigningKey, records: u64, head: &str) -> io::Result<()> {
+    let signature = ChainSignature {
+        records,
+        head: head.to_string(),
+        public_key: to_hex(key.verifying_key().as_bytes()),
+        signature: to_hex(&key.sign(&ChainSignature::message(records, head)).to_bytes()),
+    };
+    let path = signature_path(path);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_vec(&signature)?)?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Result of verifying an audit log.
+#[derive(Debug)]
+pub struct AuditVerification {
+    /// Number of records in the log.
+    pub records: u64,
+    /// Hex-encoded hash of the last line.
+    pub head: String,
+    /// The verified chain signature, if the log is signed.
+    pub signature: Option<ChainSignature>,
+}
+
+/// Checks the hash chain of an audit log and, if present, the signature of its head.
+///
+/// Records written after the last signature are chained but not signed; they are counted in
+/// [`AuditVerification::records`] but not in the signature.
+///
+/// # Arguments
+///
+/// * `path` - The audit log to verify.
+/// * `public_key` - Key the chain must be signed with. Without one, a signature is checked
+///   against the public key stored with it, which shows consistency but not who signed.
+pub fn verify_audit_log(path: &Path, public_key: Option<&VerifyingKey>) -> Result<AuditVerification> {
+    let failed = |message: String| MonitorError::AuditLog(format!("{}: {}", path.display(), message));
+    let signature = match fs::read(signature_path(path)) {
+        Ok(contents) => Some(serde_json::from_slice::<ChainSignature>(&contents).map_err(|e| failed(format!("invalid signature file: {}", e)))?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    if public_key.is_some() && signature.is_none() {
+        return Err(failed(format!("no signature file {}", signature_path(path).display())));
+    }
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut line = Vec::new();
+    let mut head = to_hex(&GENESIS_HASH);
+    let mut records = 0u64;
+    let mut signed_head = None;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        records += 1;
+        let link: ChainLink = serde_json::from_slice(&line).map_err(|e| failed(format!("record {}: {}", records, e)))?;
+        match link.prev_hash {
+            Some(prev_hash) if prev_hash == head => {}
+            Some(_) => {
+                return Err(failed(format!(
+                    "record {} does not follow record {}; records were altered, removed, or reordered",
+                    records,
+                    records - 1
+                )));
+            }
+            None => return Err(failed(format!("record {} is not chained", records))),
+        }
+        head = to_hex(&Sha256::digest(&line));
+        if signature.as_ref().is_some_and(|signature| signature.records == records) {
+            signed_head = Some(head.clone());
+        }
+    }
+
+    if let Some(signature) = &signature {
+        let signed_head = match signed_head {
+            Some(signed_head) => signed_head,
+            None if signature.records == 0 => to_hex(&GENESIS_HASH),
+            None => {
+                return Err(failed(format!(
+                    "the signature covers {} records but the log has only {}",
+                    signature.records, records
+                )));
+            }
+        };
+        if signed_head != signature.head {
+            return Err(failed(format!("the first {} records do not match the signed chain head", signature.records)));
+        }
+        let key = match public_key {
+            Some(key) => *key,
+            None => parse_public_key(&signature.public_key)?,
+        };
+        let bytes = from_hex(&signature.signature).and_then(|bytes| Signature::from_slice(&bytes).ok());
+        let valid = bytes.is_some_and(|bytes| key.verify(&ChainSignature::message(signature.records, &signature.head), &bytes).is_ok());
+        if !valid {
+            return Err(failed("invalid chain signature".to_string()));
+        }
+    }
+    Ok(AuditVerification { records, head, signature })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+
+    #[test]
+    fn verification_detects_tampering_and_truncation() {
+        let dir = std::env::temp_dir().join(format!("audit-chain-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.ndjson");
+        let policy = Policy::builtin();
+        let service = AIService { name_idx: 0, vendor_idx: 0, model_idx: 0 };
+        let status = ComplianceStatus { flags: policy.compliance.all_compliant(), waived: 0 };
+        let records = (1..=4)
+            .map(|id| {
+                let usage = Usage { department_idx: 0, data_sensitivity: id as u8 };
+                AuditRecord::new(EventId(id), &policy, 0, &service, &usage, &status, &RiskAssessment { score: 0, factor_flags: 0 })
+            })
+            .collect();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let (sender, receiver) = unbounded();
+        let (errors, _) = unbounded();
+        sender.send(records).unwrap();
+        drop(sender);
+        audit_writer(&path, receiver, Some(key.clone()), ErrorPolicy::Shutdown, errors);
+
+        let verified = verify_audit_log(&path, Some(&key.verifying_key())).unwrap();
+        assert_eq!((verified.records, verified.signature.map(|s| s.records)), (4, Some(4)));
+        let other_key = SigningKey::from_bytes(&[8; 32]).verifying_key();
+        assert!(verify_audit_log(&path, Some(&other_key)).is_err());
+
+        let log = fs::read_to_string(&path).unwrap();
+        fs::write(&path, log.replacen("\"data_sensitivity\":2", "\"data_sensitivity\":9", 1)).unwrap();
+        assert!(verify_audit_log(&path, None).is_err());
+        let truncated: Vec<&str> = log.lines().take(3).collect();
+        fs::write(&path, truncated.join("\n") + "\n").unwrap();
+        let result = verify_audit_log(&path, None);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn retried_writes_discard_what_a_failed_attempt_left_behind() {
+        let path = std::env::temp_dir().join(format!("audit-retry-test-{}.ndjson", std::process::id()));
+        let mut writer = AuditWriter::new(File::create(&path).unwrap());
+        writer.buffer.extend_from_slice(b"{\"first\":1}\n");
+        writer.flush().unwrap();
+        writer.buffer.extend_from_slice(b"{\"second\":2}\n");
+        // A failed attempt wrote part of the second line before erroring.
+        writer.file.write_all(b"{\"sec").unwrap();
+        writer.flush().unwrap();
+        writer.flush().unwrap();
+        let log = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(log, "{\"first\":1}\n{\"second\":2}\n");
+    }
+}
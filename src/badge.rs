@@ -0,0 +1,236 @@
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError};
+use crate::metrics::{ComplianceMetrics, CumulativeMetrics, IntervalMetrics};
+use crate::policy::Policy;
+use crate::profile::prometheus_text;
+use crate::sink::Sink;
+use crossbeam_channel::Sender;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Sink name used in error reports when writing the badge file.
+pub const BADGE_SINK: &str = "compliance badge";
+
+/// Label shown on the left-hand side of the badge and at the start of the status line.
+const BADGE_LABEL: &str = "AI Compliance";
+
+/// Compliance percentage at or above which the posture is OK.
+const OK_THRESHOLD: f64 = 95.0;
+
+/// Compliance percentage at or above which the posture is a warning rather than failing.
+const WARN_THRESHOLD: f64 = 80.0;
+
+/// How long the badge server waits for a request line before dropping the connection.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest request line the badge server reads.
+const MAX_REQUEST_LINE: u64 = 8 * 1024;
+
+/// How often the badge server checks the stop signal while no client is connecting.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Overall compliance posture, derived from the compliance percentage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Posture {
+    Ok,
+    Warn,
+    Fail,
+    /// No events have been evaluated yet.
+    Unknown,
+}
+
+impl Posture {
+    /// Classifies the cumulative metrics.
+    pub fn of(metrics: &ComplianceMetrics) -> Self {
+        if metrics.total_events == 0 {
+            return Posture::Unknown;
+        }
+        let percentage = metrics.compliance_percentage();
+        if percentage >= OK_THRESHOLD {
+            Posture::Ok
+        } else if percentage >= WARN_THRESHOLD {
+            Posture::Warn
+        } else {
+            Posture::Fail
+        }
+    }
+
+    /// Returns the word used in the status line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Posture::Ok => "OK",
+            Posture::Warn => "WARN",
+            Posture::Fail => "FAIL",
+            Posture::Unknown => "NO DATA",
+        }
+    }
+
+    /// Returns the shields.io color of the badge.
+    fn color(self) -> &'static str {
+        match self {
+            Posture::Ok => "brightgreen",
+            Posture::Warn => "yellow",
+            Posture::Fail => "red",
+            Posture::Unknown => "lightgrey",
+        }
+    }
+}
+
+/// A badge in the shields.io endpoint format, plus the equivalent one-line status.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Badge {
+    schema_version: u8,
+    label: &'static str,
+    message: String,
+    color: &'static str,
+    /// Not part of the shields.io schema, which ignores unknown fields.
+    status: String,
+}
+
+impl Badge {
+    /// Builds the badge for the cumulative metrics.
+    pub fn new(metrics: &ComplianceMetrics) -> Self {
+        let posture = Posture::of(metrics);
+        let message = match posture {
+            Posture::Unknown => posture.name().to_lowercase(),
+            _ => format!("{:.1}%", metrics.compliance_percentage()),
+        };
+        Badge {
+            schema_version: 1,
+            label: BADGE_LABEL,
+            status: format!("{}: {} \u{2013} {}", BADGE_LABEL, message, posture.name()),
+            message,
+            color: posture.color(),
+        }
+    }
+
+    /// Returns the one-line status, e.g. "AI Compliance: 96.2% – OK".
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+}
+
+/// The latest badge, shared between the aggregator and the badge server.
+pub type SharedBadge = Arc<Mutex<Badge>>;
+
+/// Writes the badge as JSON to `path`, replacing it atomically so readers never see a partial file.
+pub fn write_badge(path: &Path, badge: &Badge) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer(&mut writer, badge)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    drop(writer);
+    fs::rename(&tmp_path, path)
+}
+
+/// Sink rewriting the badge file with the cumulative compliance every reporting interval.
+pub struct BadgeFileSink {
+    pub path: PathBuf,
+}
+
+impl Sink for BadgeFileSink {
+    fn name(&self) -> &'static str {
+        BADGE_SINK
+    }
+
+    fn write(&self, _interval: &IntervalMetrics, total: &CumulativeMetrics, _policy: &Policy) -> io::Result<()> {
+        write_badge(&self.path, &Badge::new(total))
+    }
+}
+
+/// Serves the latest badge over HTTP until the stop signal is set.
+///
+/// `GET /badge.json` returns the shields.io endpoint JSON and `GET /status` the one-line status
+/// as plain text. `GET /metrics` additionally exposes the per-system latency histograms and the
+/// workers' batch latency summary for Prometheus. Requests are handled one at a time on this thread; the responses are small.
+///
+/// # Arguments
+///
+/// * `listener` - Socket bound to the `--badge-listen` address.
+/// * `badge` - The latest badge, updated by the aggregator every interval.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn serve_badge(listener: TcpListener, badge: SharedBadge, stop_signal: Arc<AtomicBool>, errors: Sender<ErrorReport>) {
+    if let Err(e) = listener.set_nonblocking(true) {
+        report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade);
+        return;
+    }
+    while !stop_signal.load(Ordering::Relaxed) {
+        match listener.accept() {
+            // A client that disconnects or stalls only affects its own request.
+            Ok((stream, _peer)) => {
+                let _ = respond(stream, &badge);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => report(&errors, MonitorError::Io(e), ErrorPolicy::Retry),
+        }
+    }
+}
+
+/// Reads a single request and answers it.
+fn respond(mut stream: TcpStream, badge: &SharedBadge) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    // Only the request line matters; it is bounded so a client cannot make the server buffer
+    // without limit.
+    let mut request_line = String::new();
+    BufReader::new(&stream).take(MAX_REQUEST_LINE).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let badge = badge.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let (status, content_type, body) = match (method, target) {
+        ("GET", "/badge.json") => ("200 OK", "application/json", serde_json::to_string(&badge)?),
+        ("GET", "/status") => ("200 OK", "text/plain; charset=utf-8", badge.status),
+        ("GET", "/metrics") => ("200 OK", "text/plain; version=0.0.4", prometheus_text()),
+        _ => ("404 Not Found", "text/plain; charset=utf-8", "not found".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(total_events: u64, violations: u64) -> ComplianceMetrics {
+        ComplianceMetrics {
+            total_events,
+            violation_counts: vec![violations],
+            ..ComplianceMetrics::default()
+        }
+    }
+
+    #[test]
+    fn badge_reflects_posture() {
+        let badge = Badge::new(&metrics(1000, 38));
+        assert_eq!(badge.status(), "AI Compliance: 96.2% \u{2013} OK");
+        assert_eq!(badge.color, "brightgreen");
+        assert_eq!(Posture::of(&metrics(1000, 100)), Posture::Warn);
+        assert_eq!(Posture::of(&metrics(1000, 500)), Posture::Fail);
+        assert_eq!(Badge::new(&ComplianceMetrics::default()).message, "no data");
+    }
+
+    #[test]
+    fn badge_json_uses_the_shields_endpoint_schema() {
+        let json = serde_json::to_value(Badge::new(&metrics(10, 0))).unwrap();
+        assert_eq!(json["schemaVersion"], 1);
+        assert_eq!(json["label"], "AI Compliance");
+        assert_eq!(json["message"], "100.0%");
+        assert_eq!(json["color"], "brightgreen");
+    }
+}
@@ -0,0 +1,279 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Time between batches a worker aims for when it keeps up with its target rate.
+const BATCH_PERIOD: Duration = Duration::from_millis(10);
+
+/// Largest backlog a worker tries to catch up on after falling behind; older events are skipped.
+const MAX_BACKLOG: Duration = Duration::from_secs(1);
+
+/// Longest wait returned at once, so workers keep checking their stop signal.
+const MAX_WAIT: Duration = Duration::from_millis(100);
+
+/// Upper bound on a single batch, whatever the measured cost per event.
+const MAX_BATCH_SIZE: usize = 1_000_000;
+
+/// Weight of the latest batch in the smoothed cost per event.
+const COST_SMOOTHING: f64 = 0.2;
+
+/// Smallest share of the target rate worth giving a worker of its own when the thread count is
+/// chosen automatically.
+pub const MIN_RATE_PER_WORKER: u32 = 1_000;
+
+/// Returns the default number of workers for a target rate: one per core, but no more than the
+/// rate needs, so tiny rates are not spread across idle workers.
+///
+/// # Arguments
+///
+/// * `rate` - Target events per second across all workers.
+/// * `cores` - Number of logical cores available.
+pub fn default_worker_count(rate: u32, cores: usize) -> usize {
+    (rate.div_ceil(MIN_RATE_PER_WORKER) as usize).clamp(1, cores.max(1))
+}
+
+/// What a worker should do next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BatchPlan {
+    /// Generate and evaluate a batch of this many events.
+    Run(usize),
+    /// The worker is ahead of its target rate; wait this long before asking again.
+    Wait(Duration),
+}
+
+/// Chooses each worker's batch sizes to hit its share of the target rate while keeping every
+/// batch under a latency bound.
+///
+/// The worker is paced against the clock: it waits while it is ahead of schedule and runs
+/// batches of about [`BATCH_PERIOD`] worth of events when on schedule. When it falls behind, it
+/// catches up with larger batches, but never larger than the bound allows given the measured
+/// cost per event. Events become due at whatever rate is in effect at the time, so the rate may
+/// change continuously, as it does under a traffic pattern.
+#[derive(Clone, Debug)]
+pub struct BatchController {
+    /// Events per second this worker should produce, unless it follows a shared rate.
+    target_rate: f64,
+    /// Factor the target rate is scaled by, such as the load of a traffic pattern.
+    scale: f64,
+    max_latency: Duration,
+    /// Clock time of the last call to `next_batch`.
+    last_call: Option<Duration>,
+    /// Events per second in effect since the last call.
+    rate: f64,
+    /// Events due but not yet run, at most [`MAX_BACKLOG`] worth; older events are skipped.
+    backlog: f64,
+    /// Smoothed processing time per event, in seconds.
+    cost_per_event: Option<f64>,
+    /// Shared rate this worker follows, if it can be changed at runtime.
+    control: Option<Arc<RateControl>>,
+}
+
+impl BatchController {
+    /// Creates a controller for a single worker.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_rate` - Events per second this worker should produce.
+    /// * `max_latency` - Upper bound on the time a single batch may take.
+    pub fn new(target_rate: f64, max_latency: Duration) -> Self {
+        BatchController {
+            target_rate: target_rate.max(0.0),
+            scale: 1.0,
+            max_latency,
+            last_call: None,
+            rate: 0.0,
+            backlog: 0.0,
+            cost_per_event: None,
+            control: None,
+        }
+    }
+
+    /// Creates a controller for one of the workers sharing a rate that can be changed or paused
+    /// while they run.
+    ///
+    /// # Arguments
+    ///
+    /// * `control` - The shared target rate.
+    /// * `max_latency` - Upper bound on the time a single batch may take.
+    pub fn controlled(control: Arc<RateControl>, max_latency: Duration) -> Self {
+        BatchController {
+            control: Some(control.clone()),
+            ..Self::new(control.rate_per_worker(), max_latency)
+        }
+    }
+
+    /// Scales the target rate from the next step on.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - Factor applied to the target rate, e.g. the current load of a traffic pattern.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.max(0.0);
+    }
+
+    /// Decides the next step at clock time `now`.
+    pub fn next_batch(&mut self, now: Duration) -> BatchPlan {
+        // The time since the last call counts at the rate in effect during it, so a new rate is
+        // paced from now on and neither a pause nor a rate change is followed by a burst of
+        // catch-up batches.
+        let elapsed = self.last_call.replace(now).map_or(Duration::ZERO, |last| now.saturating_sub(last));
+        self.backlog += elapsed.as_secs_f64() * self.rate;
+        let target_rate = match &self.control {
+            Some(control) if control.is_paused() => 0.0,
+            Some(control) => control.rate_per_worker(),
+            None => self.target_rate,
+        };
+        self.rate = target_rate * self.scale;
+        if self.rate == 0.0 {
+            self.backlog = 0.0;
+            return BatchPlan::Wait(MAX_WAIT);
+        }
+        let steady_size = (self.rate * BATCH_PERIOD.as_secs_f64()).max(1.0);
+        // Below one event per MAX_BACKLOG, the backlog must still be able to reach a whole event.
+        self.backlog = self.backlog.min((MAX_BACKLOG.as_secs_f64() * self.rate).max(steady_size));
+        if self.backlog < steady_size {
+            let wait = Duration::from_secs_f64((steady_size - self.backlog) / self.rate);
+            return BatchPlan::Wait(wait.min(MAX_WAIT));
+        }
+        let size = (self.backlog as usize).min(self.latency_limit());
+        self.backlog -= size as f64;
+        BatchPlan::Run(size)
+    }
+
+    /// Records how long a batch of `events` took, refining the cost per event.
+    pub fn observe(&mut self, events: usize, latency: Duration) {
+        if events == 0 {
+            return;
+        }
+        let cost = latency.as_secs_f64() / events as f64;
+        self.cost_per_event = Some(match self.cost_per_event {
+            Some(previous) => previous + COST_SMOOTHING * (cost - previous),
+            None => cost,
+        });
+    }
+
+    /// Returns the largest batch expected to finish within the latency bound.
+    fn latency_limit(&self) -> usize {
+        match self.cost_per_event {
+            Some(cost) if cost > 0.0 => ((self.max_latency.as_secs_f64() / cost).round() as usize).clamp(1, MAX_BATCH_SIZE),
+            _ => MAX_BATCH_SIZE,
+        }
+    }
+}
+
+/// The target rate shared by a group of local workers, which can be changed or paused while
+/// they run.
+#[derive(Debug)]
+pub struct RateControl {
+    /// Events per second across all the workers.
+    rate: AtomicU32,
+    paused: AtomicBool,
+    workers: usize,
+}
+
+impl RateControl {
+    /// Creates a running control.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - Target events per second across all workers.
+    /// * `workers` - Number of workers sharing the rate.
+    pub fn new(rate: u32, workers: usize) -> Self {
+        RateControl {
+            rate: AtomicU32::new(rate),
+            paused: AtomicBool::new(false),
+            workers: workers.max(1),
+        }
+    }
+
+    /// Returns the target events per second across all workers.
+    pub fn rate(&self) -> u32 {
+        self.rate.load(Ordering::Relaxed)
+    }
+
+    /// Changes the target rate; workers pick it up before their next batch.
+    pub fn set_rate(&self, rate: u32) {
+        self.rate.store(rate, Ordering::Relaxed);
+    }
+
+    /// Returns true if the workers are paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Pauses or resumes the workers and returns whether they were paused before.
+    pub fn set_paused(&self, paused: bool) -> bool {
+        self.paused.swap(paused, Ordering::Relaxed)
+    }
+
+    /// Returns the number of workers sharing the rate.
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Returns each worker's share of the target rate.
+    fn rate_per_worker(&self) -> f64 {
+        self.rate() as f64 / self.workers as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paces_batches_to_the_target_rate() {
+        let mut controller = BatchController::new(10_000.0, Duration::from_millis(20));
+        // Nothing is due at the start; one batch period's worth becomes due after 10 ms.
+        assert_eq!(controller.next_batch(Duration::ZERO), BatchPlan::Wait(Duration::from_millis(10)));
+        assert_eq!(controller.next_batch(Duration::from_millis(10)), BatchPlan::Run(100));
+        assert!(matches!(controller.next_batch(Duration::from_millis(15)), BatchPlan::Wait(_)));
+        // Falling behind is caught up in one larger batch.
+        assert_eq!(controller.next_batch(Duration::from_millis(60)), BatchPlan::Run(500));
+    }
+
+    #[test]
+    fn low_rates_still_produce_whole_events() {
+        let mut controller = BatchController::new(2.0, Duration::from_millis(20));
+        // One event is due every 500 ms; waits are capped so the stop signal is still checked.
+        assert_eq!(controller.next_batch(Duration::ZERO), BatchPlan::Wait(Duration::from_millis(100)));
+        assert!(matches!(controller.next_batch(Duration::from_millis(400)), BatchPlan::Wait(_)));
+        assert_eq!(controller.next_batch(Duration::from_millis(500)), BatchPlan::Run(1));
+        // Rates below one event per second still produce events, however long the wait.
+        let mut controller = BatchController::new(0.5, Duration::from_millis(20));
+        controller.next_batch(Duration::ZERO);
+        assert_eq!(controller.next_batch(Duration::from_secs(2)), BatchPlan::Run(1));
+    }
+
+    #[test]
+    fn tiny_rates_use_fewer_workers_than_cores() {
+        assert_eq!(default_worker_count(50, 16), 1);
+        assert_eq!(default_worker_count(0, 16), 1);
+        assert_eq!(default_worker_count(2_500, 16), 3);
+        assert_eq!(default_worker_count(100_000, 16), 16);
+    }
+
+    #[test]
+    fn batches_stay_under_the_latency_bound() {
+        let mut controller = BatchController::new(1_000_000.0, Duration::from_millis(5));
+        controller.next_batch(Duration::ZERO);
+        // 10 µs per event allows 500 events in 5 ms.
+        controller.observe(1_000, Duration::from_millis(10));
+        assert_eq!(controller.next_batch(Duration::from_millis(100)), BatchPlan::Run(500));
+    }
+
+    #[test]
+    fn controlled_workers_follow_pauses_and_rate_changes() {
+        let control = Arc::new(RateControl::new(20_000, 2));
+        let mut controller = BatchController::controlled(control.clone(), Duration::from_millis(20));
+        controller.next_batch(Duration::ZERO);
+        assert_eq!(controller.next_batch(Duration::from_millis(10)), BatchPlan::Run(100));
+        assert!(!control.set_paused(true));
+        assert_eq!(controller.next_batch(Duration::from_secs(5)), BatchPlan::Wait(Duration::from_millis(100)));
+        // Resuming at a new rate starts a fresh schedule instead of catching up on the pause.
+        control.set_paused(false);
+        control.set_rate(40_000);
+        assert_eq!(controller.next_batch(Duration::from_secs(6)), BatchPlan::Wait(Duration::from_millis(10)));
+        assert_eq!(controller.next_batch(Duration::from_millis(6_010)), BatchPlan::Run(200));
+    }
+}
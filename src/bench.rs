@@ -0,0 +1,323 @@
+use crate::components::Event;
+use crate::ecs::{generate_ai_events, process_batch, Parallelism};
+use crate::error::{MonitorError, Result};
+use crate::policy::Policy;
+use crate::tail_latency::{self, merge, new_histogram, quantile};
+use hdrhistogram::Histogram;
+use hecs::World;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Sink name used in error reports when saving a baseline.
+pub const BASELINE_SINK: &str = "benchmark baseline";
+
+/// Baseline format version; bumped whenever `WorkloadStats` changes incompatibly.
+const BASELINE_VERSION: u32 = 1;
+
+/// Two-sided 95% quantile of the normal distribution, for confidence intervals of the mean.
+const Z_95: f64 = 1.96;
+
+/// Width of the column that criterion-style output aligns the statistics at.
+const LABEL_WIDTH: usize = 24;
+
+/// A fixed amount of work pushed through the ECS pipeline in every sample.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Workload {
+    /// Events evaluated per sample, split evenly across the threads.
+    pub entities: usize,
+    /// Threads evaluating events, each with its own world.
+    pub threads: usize,
+    /// Events per `process_batch` call.
+    pub batch_size: usize,
+}
+
+impl Workload {
+    /// Returns every combination of the given entity counts, thread counts, and batch sizes.
+    pub fn matrix(entities: &[usize], threads: &[usize], batch_sizes: &[usize]) -> Vec<Workload> {
+        let mut workloads = Vec::new();
+        for &entities in entities {
+            for &threads in threads {
+                for &batch_size in batch_sizes {
+                    workloads.push(Workload { entities, threads: threads.max(1), batch_size: batch_size.max(1) });
+                }
+            }
+        }
+        workloads
+    }
+
+    /// Returns the name the workload is reported and stored in baselines under.
+    pub fn id(&self) -> String {
+        format!("pipeline/entities={}/threads={}/batch={}", self.entities, self.threads, self.batch_size)
+    }
+
+    /// Runs the workload once, one thread per entry of `threads`, and returns its wall-clock time.
+    /// Each thread records its batch latencies in its own histogram.
+    fn run_once(&self, threads: &mut [BenchThread], policy: &Policy, parallelism: Parallelism) -> Duration {
+        let per_thread = self.entities / self.threads;
+        let remainder = self.entities % self.threads;
+        let started = Instant::now();
+        thread::scope(|scope| {
+            for (index, thread) in threads.iter_mut().enumerate() {
+                let mut remaining = per_thread + usize::from(index < remainder);
+                scope.spawn(move || {
+                    while remaining > 0 {
+                        let batch = &thread.events[..remaining.min(thread.events.len())];
+                        let batch_started = Instant::now();
+                        process_batch(&mut thread.world, batch, policy, parallelism, None);
+                        tail_latency::record(&mut thread.latencies, batch_started.elapsed());
+                        remaining -= batch.len();
+                    }
+                });
+            }
+        });
+        started.elapsed()
+    }
+}
+
+/// The world of a benchmark thread, the batch of events it evaluates over and over, and the
+/// latencies of its batches.
+struct BenchThread {
+    world: World,
+    events: Vec<Event>,
+    latencies: Histogram<u64>,
+}
+
+/// Timing statistics of a workload over all its samples.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadStats {
+    pub id: String,
+    /// Events evaluated per sample.
+    pub entities: usize,
+    pub samples: usize,
+    /// Lower bound, estimate, and upper bound of the mean time per sample, in seconds, as a 95%
+    /// confidence interval.
+    pub time_secs: [f64; 3],
+    /// Per-batch latency percentiles, to three significant digits.
+    pub batch_p50_ms: f64,
+    pub batch_p99_ms: f64,
+}
+
+impl WorkloadStats {
+    /// Returns the lower bound, estimate, and upper bound of the throughput in events per
+    /// second, matching the bounds of the time.
+    pub fn throughput(&self) -> [f64; 3] {
+        let rate = |secs: f64| self.entities as f64 / secs.max(f64::EPSILON);
+        [rate(self.time_secs[2]), rate(self.time_secs[1]), rate(self.time_secs[0])]
+    }
+}
+
+/// Runs a workload `samples` times after one warm-up run and summarizes the timings.
+///
+/// # Arguments
+///
+/// * `workload` - The work done in each sample.
+/// * `policy` - The policy events are evaluated against.
+/// * `parallelism` - How the systems iterate over each batch.
+/// * `samples` - Number of timed runs.
+pub fn measure(workload: &Workload, policy: &Policy, parallelism: Parallelism, samples: usize) -> WorkloadStats {
+    let mut threads: Vec<BenchThread> = (0..workload.threads)
+        .map(|_| BenchThread {
+            world: World::new(),
+            events: generate_ai_events(workload.batch_size),
+            latencies: new_histogram(),
+        })
+        .collect();
+    // The warm-up run spawns the entities that later batches reuse.
+    workload.run_once(&mut threads, policy, parallelism);
+    threads.iter_mut().for_each(|thread| thread.latencies.reset());
+    let times: Vec<f64> = (0..samples.max(1))
+        .map(|_| workload.run_once(&mut threads, policy, parallelism).as_secs_f64())
+        .collect();
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    let variance = if times.len() > 1 {
+        times.iter().map(|time| (time - mean).powi(2)).sum::<f64>() / (times.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let margin = Z_95 * variance.sqrt() / (times.len() as f64).sqrt();
+    let mut latencies = new_histogram();
+    for thread in &threads {
+        merge(&mut latencies, &thread.latencies);
+    }
+    let percentile_ms = |q: f64| if latencies.is_empty() { 0.0 } else { quantile(&latencies, q).as_secs_f64() * 1000.0 };
+    WorkloadStats {
+        id: workload.id(),
+        entities: workload.entities,
+        samples: times.len(),
+        time_secs: [(mean - margin).max(0.0), mean, mean + margin],
+        batch_p50_ms: percentile_ms(0.5),
+        batch_p99_ms: percentile_ms(0.99),
+    }
+}
+
+/// How a workload's time compares with its baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Improved,
+    Regressed,
+    /// The change is significant but smaller than the noise threshold.
+    WithinNoise,
+    NoChange,
+}
+
+impl Verdict {
+    /// Returns the sentence the comparison ends with.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Verdict::Improved => "Performance has improved.",
+            Verdict::Regressed => "Performance has regressed.",
+            Verdict::WithinNoise => "Change within noise threshold.",
+            Verdict::NoChange => "No change in performance detected.",
+        }
+    }
+}
+
+/// Change of a workload's mean time relative to a baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Change {
+    /// Lower bound, estimate, and upper bound of the relative change in time, in percent.
+    pub percent: [f64; 3],
+    pub verdict: Verdict,
+}
+
+/// Compares a workload's timings with those saved in a baseline.
+///
+/// The change counts only if the confidence intervals of the two means do not overlap, and as
+/// an improvement or regression only if the estimate also exceeds the noise threshold.
+///
+/// # Arguments
+///
+/// * `current` - Timings of this run.
+/// * `baseline` - Timings of the same workload in the baseline.
+/// * `noise_threshold` - Smallest change in percent reported as an improvement or regression.
+pub fn compare(current: &WorkloadStats, baseline: &WorkloadStats, noise_threshold: f64) -> Change {
+    let relative = |now: f64, then: f64| (now / then.max(f64::EPSILON) - 1.0) * 100.0;
+    let [now_low, now_mean, now_high] = current.time_secs;
+    let [then_low, then_mean, then_high] = baseline.time_secs;
+    let percent = [relative(now_low, then_high), relative(now_mean, then_mean), relative(now_high, then_low)];
+    let verdict = if percent[0] <= 0.0 && percent[2] >= 0.0 {
+        Verdict::NoChange
+    } else if percent[1].abs() < noise_threshold {
+        Verdict::WithinNoise
+    } else if percent[1] > 0.0 {
+        Verdict::Regressed
+    } else {
+        Verdict::Improved
+    };
+    Change { percent, verdict }
+}
+
+/// Renders a workload's statistics, and its change against a baseline if there is one, the way
+/// criterion prints them.
+pub fn render(stats: &WorkloadStats, change: Option<&Change>) -> String {
+    let mut out = String::new();
+    let indent = " ".repeat(LABEL_WIDTH);
+    let _ = writeln!(out, "{}", stats.id);
+    let [low, mean, high] = stats.time_secs;
+    let _ = writeln!(out, "{}time:   [{} {} {}]", indent, format_time(low), format_time(mean), format_time(high));
+    let [low, mean, high] = stats.throughput();
+    let _ = writeln!(out, "{}thrpt:  [{} {} {}]", indent, format_rate(low), format_rate(mean), format_rate(high));
+    let _ = writeln!(out, "{}batch:  p50 {:.3} ms, p99 {:.3} ms", indent, stats.batch_p50_ms, stats.batch_p99_ms);
+    if let Some(change) = change {
+        let _ = writeln!(out, "{:>width$}", "change:", width = LABEL_WIDTH);
+        let [low, mean, high] = change.percent;
+        let _ = writeln!(out, "{}time:   [{:+.4}% {:+.4}% {:+.4}%]", indent, low, mean, high);
+        let _ = writeln!(out, "{}{}", indent, change.verdict.describe());
+    }
+    out
+}
+
+/// Formats a duration in seconds with four decimals in the largest unit below it.
+fn format_time(secs: f64) -> String {
+    let (value, unit) = match secs {
+        secs if secs >= 1.0 => (secs, "s"),
+        secs if secs >= 1e-3 => (secs * 1e3, "ms"),
+        secs if secs >= 1e-6 => (secs * 1e6, "µs"),
+        secs => (secs * 1e9, "ns"),
+    };
+    format!("{:.4} {}", value, unit)
+}
+
+/// Formats a rate in events per second with four decimals and a metric prefix.
+fn format_rate(rate: f64) -> String {
+    let (value, unit) = match rate {
+        rate if rate >= 1e9 => (rate / 1e9, "Gelem/s"),
+        rate if rate >= 1e6 => (rate / 1e6, "Melem/s"),
+        rate if rate >= 1e3 => (rate / 1e3, "Kelem/s"),
+        rate => (rate, "elem/s"),
+    };
+    format!("{:.4} {}", value, unit)
+}
+
+/// Results of a benchmark run, saved to compare later runs against.
+#[derive(Serialize, Deserialize)]
+struct Baseline {
+    version: u32,
+    workloads: Vec<WorkloadStats>,
+}
+
+/// Writes the statistics of a run to `path` as a baseline.
+pub fn save_baseline(path: &Path, workloads: &[WorkloadStats]) -> io::Result<()> {
+    let baseline = Baseline {
+        version: BASELINE_VERSION,
+        workloads: workloads.to_vec(),
+    };
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, &baseline)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Loads the workload statistics of a baseline saved with [`save_baseline`].
+pub fn load_baseline(path: &Path) -> Result<Vec<WorkloadStats>> {
+    let invalid = |message: String| MonitorError::Baseline(format!("{}: {}", path.display(), message));
+    let file = File::open(path).map_err(|e| invalid(e.to_string()))?;
+    let baseline: Baseline = serde_json::from_reader(BufReader::new(file)).map_err(|e| invalid(e.to_string()))?;
+    if baseline.version != BASELINE_VERSION {
+        return Err(invalid(format!(
+            "unsupported baseline version {} (expected {})",
+            baseline.version, BASELINE_VERSION
+        )));
+    }
+    Ok(baseline.workloads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn workloads_are_measured_and_compared_with_saved_baselines() {
+        let policy = Policy::builtin();
+        let workloads = Workload::matrix(&[2_000], &[1, 2], &[300]);
+        assert_eq!(workloads.len(), 2);
+        let stats = measure(&workloads[1], &policy, Parallelism::Sequential, 3);
+        assert_eq!(stats.id, "pipeline/entities=2000/threads=2/batch=300");
+        assert_eq!(stats.samples, 3);
+        assert!(stats.time_secs[0] <= stats.time_secs[1] && stats.time_secs[1] <= stats.time_secs[2]);
+        assert!(stats.batch_p50_ms > 0.0 && stats.batch_p50_ms <= stats.batch_p99_ms);
+
+        let path = std::env::temp_dir().join(format!("bench-baseline-{}.json", std::process::id()));
+        save_baseline(&path, std::slice::from_ref(&stats)).unwrap();
+        let baseline = load_baseline(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(baseline, vec![stats.clone()]);
+
+        let with_time = |time_secs: [f64; 3]| WorkloadStats { time_secs, ..stats.clone() };
+        let before = with_time([0.009, 0.010, 0.011]);
+        assert_eq!(compare(&with_time([0.0095, 0.0102, 0.0108]), &before, 5.0).verdict, Verdict::NoChange);
+        let narrow = with_time([0.0099, 0.0100, 0.0101]);
+        assert_eq!(compare(&with_time([0.0102, 0.0103, 0.0104]), &narrow, 5.0).verdict, Verdict::WithinNoise);
+        let regressed = compare(&with_time([0.0118, 0.012, 0.0122]), &before, 5.0);
+        assert_eq!(regressed.verdict, Verdict::Regressed);
+        assert!((regressed.percent[1] - 20.0).abs() < 1e-9);
+        assert_eq!(compare(&with_time([0.0048, 0.005, 0.0052]), &before, 5.0).verdict, Verdict::Improved);
+        assert!(render(&with_time([0.0048, 0.005, 0.0052]), Some(&regressed)).contains("thrpt:  [384.6154 Kelem/s 400.0000 Kelem/s 416.6667 Kelem/s]"));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::compliance::ComplianceRegistry;
+use crate::constants::DEPARTMENT_NAMES;
+use crate::error::{MonitorError, Result};
+use crate::metrics::ComplianceMetrics;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+
+/// Sink name used in error reports when saving cases.
+pub const CASES_SINK: &str = "case file";
+
+/// Where a case stands in its review.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseState {
+    /// Not yet looked at.
+    Open,
+    /// Seen by the owner and being worked on.
+    Acknowledged,
+    /// Accepted as a known risk.
+    Waived,
+    /// Fixed; reopened if the violations recur.
+    Remediated,
+}
+
+/// All case states, in review order.
+pub const CASE_STATES: [CaseState; 4] = [CaseState::Open, CaseState::Acknowledged, CaseState::Waived, CaseState::Remediated];
+
+impl CaseState {
+    /// Returns the display name of the state.
+    pub fn name(self) -> &'static str {
+        match self {
+            CaseState::Open => "Open",
+            CaseState::Acknowledged => "Acknowledged",
+            CaseState::Waived => "Waived",
+            CaseState::Remediated => "Remediated",
+        }
+    }
+}
+
+/// The high-risk violations of one compliance check within one department.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Case {
+    /// Key of the violated compliance flag.
+    pub check: String,
+    pub department: String,
+    pub state: CaseState,
+    /// High-risk violations counted against the case since it was opened.
+    pub violations: u64,
+    pub opened_at_ms: u64,
+    /// Time of the last state change or new violation.
+    pub updated_at_ms: u64,
+}
+
+/// All cases of the monitor, in the order they were opened.
+///
+/// Cases are identified by their check and department names rather than indices, so a case
+/// file stays valid when rule packs or departments are added.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CaseBook {
+    cases: Vec<Case>,
+}
+
+impl CaseBook {
+    /// Loads the cases saved at `path`, or starts an empty book if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+        match File::open(path) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(|e| invalid(e.to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(CaseBook::default()),
+            Err(e) => Err(invalid(e.to_string())),
+        }
+    }
+
+    /// Writes the cases to `path`, replacing the file atomically.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Returns the cases in the order they were opened.
+    pub fn cases(&self) -> &[Case] {
+        &self.cases
+    }
+
+    /// Counts new high-risk violations against their cases, opening a case for each check and
+    /// department seen for the first time and reopening remediated cases.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - Metrics accumulated since the last call, with per-department counters.
+    /// * `compliance` - The compliance flags the counters are indexed by.
+    /// * `now_ms` - Current wall-clock time, in milliseconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// True if any case changed.
+    pub fn record(&mut self, delta: &ComplianceMetrics, compliance: &ComplianceRegistry, now_ms: u64) -> bool {
+        let mut changed = false;
+        for (department, metrics) in DEPARTMENT_NAMES.iter().zip(&delta.departments) {
+            for (flag, info) in compliance.iter() {
+                let count = metrics.high_risk_violations.get(flag.0 as usize).copied().unwrap_or(0);
+                if count == 0 {
+                    continue;
+                }
+                let index = match self.find(info.key, department) {
+                    Some(index) => index,
+                    None => {
+                        self.cases.push(Case {
+                            check: info.key.to_string(),
+                            department: department.to_string(),
+                            state: CaseState::Open,
+                            violations: 0,
+                            opened_at_ms: now_ms,
+                            updated_at_ms: now_ms,
+                        });
+                        self.cases.len() - 1
+                    }
+                };
+                let case = &mut self.cases[index];
+                case.violations = case.violations.saturating_add(count);
+                case.updated_at_ms = now_ms;
+                if case.state == CaseState::Remediated {
+                    case.state = CaseState::Open;
+                }
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Moves the case at `index` to `state`.
+    ///
+    /// # Returns
+    ///
+    /// The updated case, or `None` if there is no case at `index`.
+    pub fn set_state(&mut self, index: usize, state: CaseState, now_ms: u64) -> Option<&Case> {
+        let case = self.cases.get_mut(index)?;
+        case.state = state;
+        case.updated_at_ms = now_ms;
+        Some(case)
+    }
+
+    /// Returns the high-risk violations of the cases in each state, indexed like `CASE_STATES`.
+    pub fn violations_by_state(&self) -> [u64; 4] {
+        let mut totals = [0u64; 4];
+        for case in &self.cases {
+            totals[case.state as usize] = totals[case.state as usize].saturating_add(case.violations);
+        }
+        totals
+    }
+
+    /// Returns the index of the case for a check and department.
+    fn find(&self, check: &str, department: &str) -> Option<usize> {
+        self.cases.iter().position(|case| case.check == check && case.department == department)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::GDPR;
+
+    #[test]
+    fn violations_open_cases_and_reopen_remediated_ones() {
+        let compliance = ComplianceRegistry::builtin();
+        let mut delta = ComplianceMetrics {
+            departments: DEPARTMENT_NAMES.iter().map(|_| ComplianceMetrics::default()).collect(),
+            ..ComplianceMetrics::default()
+        };
+        delta.departments[1].high_risk_violations = vec![0; compliance.len()];
+        delta.departments[1].high_risk_violations[GDPR.0 as usize] = 3;
+
+        let mut book = CaseBook::default();
+        assert!(book.record(&delta, &compliance, 1));
+        assert!(book.record(&delta, &compliance, 2));
+        assert_eq!(book.cases().len(), 1);
+        assert_eq!((book.cases()[0].check.as_str(), book.cases()[0].department.as_str()), ("gdpr", DEPARTMENT_NAMES[1]));
+        assert_eq!(book.violations_by_state(), [6, 0, 0, 0]);
+
+        book.set_state(0, CaseState::Acknowledged, 3);
+        assert_eq!(book.violations_by_state(), [0, 6, 0, 0]);
+        book.set_state(0, CaseState::Remediated, 4);
+        book.record(&delta, &compliance, 5);
+        assert_eq!(book.violations_by_state(), [9, 0, 0, 0]);
+        assert!(book.set_state(1, CaseState::Waived, 6).is_none());
+        assert!(!book.record(&ComplianceMetrics::default(), &compliance, 7));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::error::{MonitorError, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Process-wide failure injection, enabled once at startup by `--chaos`.
+static CHAOS: OnceLock<Chaos> = OnceLock::new();
+
+/// Settings for injecting sink failures.
+#[derive(Clone, Copy, Debug)]
+pub struct ChaosConfig {
+    /// Probability that a sink operation starts an outage of that sink.
+    pub failure_rate: f64,
+    /// Upper bound on the length of a single outage.
+    pub max_outage: Duration,
+}
+
+impl ChaosConfig {
+    /// Validates the chaos settings given on the command line.
+    ///
+    /// # Arguments
+    ///
+    /// * `failure_rate` - Probability in `0.0..=1.0` that a sink operation starts an outage.
+    /// * `max_outage` - Upper bound on the length of a single outage.
+    pub fn new(failure_rate: f64, max_outage: Duration) -> Result<Self> {
+        if !(0.0..=1.0).contains(&failure_rate) {
+            return Err(MonitorError::Config(format!(
+                "chaos failure rate must be between 0 and 1, got {}",
+                failure_rate
+            )));
+        }
+        Ok(ChaosConfig { failure_rate, max_outage })
+    }
+}
+
+/// Injection state of a single sink.
+struct SinkChaos {
+    sink: &'static str,
+    outage_until: Option<Instant>,
+    injected: u64,
+}
+
+struct Chaos {
+    config: ChaosConfig,
+    sinks: Mutex<Vec<SinkChaos>>,
+    rng: Mutex<StdRng>,
+}
+
+impl Chaos {
+    /// Creates the injector, drawing outages from `rng`.
+    fn new(config: ChaosConfig, rng: StdRng) -> Self {
+        Chaos {
+            config,
+            sinks: Mutex::new(Vec::new()),
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// Decides whether an operation on `sink` at `now` fails; see [`inject`].
+    fn inject(&self, sink: &'static str, now: Instant) -> io::Result<()> {
+        let mut sinks = self.sinks.lock().unwrap_or_else(|e| e.into_inner());
+        let index = match sinks.iter().position(|s| s.sink == sink) {
+            Some(index) => index,
+            None => {
+                sinks.push(SinkChaos { sink, outage_until: None, injected: 0 });
+                sinks.len() - 1
+            }
+        };
+        let state = &mut sinks[index];
+        let in_outage = state.outage_until.is_some_and(|until| now < until);
+        if !in_outage {
+            state.outage_until = None;
+            let mut rng = self.rng.lock().unwrap_or_else(|e| e.into_inner());
+            if !rng.random_bool(self.config.failure_rate) {
+                return Ok(());
+            }
+            let outage_ms = rng.random_range(0..=self.config.max_outage.as_millis() as u64);
+            state.outage_until = Some(now + Duration::from_millis(outage_ms));
+        }
+        state.injected += 1;
+        Err(io::Error::other("failure injected by --chaos"))
+    }
+
+    /// Returns the number of injected failures per sink, in order of first use.
+    fn injected_failures(&self) -> Vec<(&'static str, u64)> {
+        let sinks = self.sinks.lock().unwrap_or_else(|e| e.into_inner());
+        sinks.iter().map(|s| (s.sink, s.injected)).collect()
+    }
+}
+
+/// Enables failure injection for the rest of the process. Later calls have no effect.
+pub fn enable(config: ChaosConfig) {
+    let _ = CHAOS.set(Chaos::new(config, StdRng::from_os_rng()));
+}
+
+/// Returns true if failure injection is enabled.
+pub fn is_enabled() -> bool {
+    CHAOS.get().is_some()
+}
+
+/// Decides whether the next operation on `sink` fails.
+///
+/// Each operation may start an outage with the configured probability. Every operation during
+/// an outage fails, so the retry policy sees a sink that is down for a while rather than
+/// isolated errors. Does nothing unless [`enable`] has been called.
+///
+/// # Arguments
+///
+/// * `sink` - Name of the sink about to be written to.
+pub fn inject(sink: &'static str) -> io::Result<()> {
+    match CHAOS.get() {
+        Some(chaos) => chaos.inject(sink, Instant::now()),
+        None => Ok(()),
+    }
+}
+
+/// Returns the number of injected failures per sink, in order of first use.
+pub fn injected_failures() -> Vec<(&'static str, u64)> {
+    CHAOS.get().map(Chaos::injected_failures).unwrap_or_default()
+}
+
+/// Returns the total number of injected failures across all sinks.
+pub fn total_injected() -> u64 {
+    injected_failures().iter().map(|&(_, count)| count).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failure_rates_outside_zero_to_one_are_rejected() {
+        let outage = Duration::from_secs(1);
+        assert!(ChaosConfig::new(0.0, outage).is_ok());
+        assert!(ChaosConfig::new(1.0, outage).is_ok());
+        for rate in [-0.1, 1.5, f64::NAN] {
+            let Err(e) = ChaosConfig::new(rate, outage) else {
+                panic!("failure rate {} was accepted", rate);
+            };
+            assert!(e.to_string().contains("chaos failure rate must be between 0 and 1"));
+        }
+    }
+
+    #[test]
+    fn injected_outages_fail_every_operation_until_they_end() {
+        let now = Instant::now();
+        let never = Chaos::new(ChaosConfig::new(0.0, Duration::from_secs(1)).unwrap(), StdRng::seed_from_u64(7));
+        assert!((0..100).all(|_| never.inject("CSV export", now).is_ok()));
+        assert_eq!(never.injected_failures(), [("CSV export", 0)]);
+
+        let mut always = Chaos::new(ChaosConfig::new(1.0, Duration::from_secs(1)).unwrap(), StdRng::seed_from_u64(7));
+        let error = always.inject("CSV export", now).unwrap_err();
+        assert_eq!(error.to_string(), "failure injected by --chaos");
+        let until = always.sinks.lock().unwrap()[0].outage_until.unwrap();
+        assert!(until > now && until <= now + Duration::from_secs(1));
+
+        // Once no new outage can start, the current one still fails operations until it ends.
+        always.config.failure_rate = 0.0;
+        assert!(always.inject("CSV export", now).is_err());
+        assert!(always.inject("CSV export", until - Duration::from_millis(1)).is_err());
+        assert!(always.inject("audit log", now).is_ok());
+        assert!(always.inject("CSV export", until).is_ok());
+        assert_eq!(always.injected_failures(), [("CSV export", 3), ("audit log", 0)]);
+    }
+}
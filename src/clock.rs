@@ -0,0 +1,319 @@
+use crate::error::{MonitorError, Result};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of time for event timestamps, reporting intervals, and pacing.
+///
+/// Every clock starts at zero when it is created. Components measure intervals with
+/// [`Clock::now`] and wait with [`Clock::sleep`] instead of using `Instant` and
+/// `thread::sleep` directly, so a run can be accelerated or stepped deterministically in tests.
+pub trait Clock: Send + Sync {
+    /// Returns the time that has passed on this clock since it was created.
+    fn now(&self) -> Duration;
+
+    /// Returns the current time on this clock in milliseconds since the Unix epoch.
+    fn unix_millis(&self) -> u64;
+
+    /// Blocks until `duration` has passed on this clock.
+    fn sleep(&self, duration: Duration);
+}
+
+/// A clock shared between threads.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Creates the clock for a run from `--time-scale`.
+///
+/// # Arguments
+///
+/// * `scale` - How many times faster than real time the clock runs; `1.0` is the real clock.
+pub fn from_time_scale(scale: f64) -> Result<SharedClock> {
+    if scale == 1.0 {
+        Ok(Arc::new(SystemClock::new()))
+    } else {
+        Ok(Arc::new(ScaledClock::new(scale)?))
+    }
+}
+
+/// Returns the current wall-clock time in milliseconds since the Unix epoch.
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Formats a time of day in UTC as `HH:MM:SS`.
+///
+/// # Arguments
+///
+/// * `unix_secs` - Seconds since the Unix epoch.
+pub fn format_utc_time(unix_secs: u64) -> String {
+    let secs_of_day = unix_secs % 86_400;
+    format!("{:02}:{:02}:{:02}", secs_of_day / 3_600, (secs_of_day / 60) % 60, secs_of_day % 60)
+}
+
+/// Formats a time in UTC as an RFC 3339 timestamp with milliseconds, such as
+/// `2025-01-31T13:05:09.250Z`.
+///
+/// # Arguments
+///
+/// * `unix_ms` - Milliseconds since the Unix epoch.
+pub fn format_utc_timestamp(unix_ms: u64) -> String {
+    let (year, month, day) = civil_from_days(unix_ms / 86_400_000);
+    format!(
+        "{:04}-{:02}-{:02}T{}.{:03}Z",
+        year,
+        month,
+        day,
+        format_utc_time(unix_ms / 1000),
+        unix_ms % 1000
+    )
+}
+
+/// Converts days since the Unix epoch into a year, month (1-12), and day of the month (1-31)
+/// in the proleptic Gregorian calendar; the inverse of [`parse_utc_date`].
+pub fn civil_from_days(days: u64) -> (i64, i64, i64) {
+    // Civil from days, with years starting in March.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Parses a `YYYY-MM-DD` date in the proleptic Gregorian calendar into days since the Unix epoch.
+///
+/// Returns `None` if the date is malformed, does not exist, or lies before the epoch.
+pub fn parse_utc_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let mut field = |digits: usize| parts.next().filter(|part| part.len() == digits)?.parse::<i64>().ok();
+    let (year, month, day) = (field(4)?, field(2)?, field(2)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if !(1..=12).contains(&month) || !(1..=month_days[month as usize - 1]).contains(&day) {
+        return None;
+    }
+    // Days from civil, counting years from March so the leap day ends the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    u64::try_from(era * 146_097 + doe - 719_468).ok()
+}
+
+/// Parses an RFC 3339 timestamp, such as `2025-01-31T13:05:09.250Z` or
+/// `2025-01-31T14:05:09+01:00`, or a `YYYY-MM-DD HH:MM:SS` time in UTC, into milliseconds since
+/// the Unix epoch.
+///
+/// Fractions beyond milliseconds are truncated. Returns `None` if the timestamp is malformed or
+/// lies before the epoch.
+pub fn parse_utc_timestamp(timestamp: &str) -> Option<u64> {
+    let days = parse_utc_date(timestamp.get(..10)?)?;
+    let time = timestamp.get(10..)?.strip_prefix(['T', 't', ' '])?;
+    let mut parts = time.get(..8)?.splitn(3, ':');
+    let mut field = |max: u64| parts.next().filter(|part| part.len() == 2)?.parse::<u64>().ok().filter(|value| *value < max);
+    let (hour, minute, second) = (field(24)?, field(60)?, field(60)?);
+    let mut rest = &time[8..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.find(|c: char| !c.is_ascii_digit()).unwrap_or(fraction.len());
+        if digits == 0 {
+            return None;
+        }
+        millis = format!("{:0<3}", &fraction[..digits.min(3)]).parse::<u64>().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset_ms = match rest {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.get(..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = rest[1..].split_once(':').filter(|(h, m)| h.len() == 2 && m.len() == 2)?;
+            sign * (hours.parse::<i64>().ok()? * 60 + minutes.parse::<i64>().ok()?) * 60_000
+        }
+    };
+    let local_ms = ((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis;
+    u64::try_from(local_ms as i64 - offset_ms).ok()
+}
+
+/// The real clock.
+pub struct SystemClock {
+    started: Instant,
+}
+
+impl SystemClock {
+    /// Creates a real clock starting now.
+    pub fn new() -> Self {
+        SystemClock { started: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    fn unix_millis(&self) -> u64 {
+        now_millis()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A simulated clock running a fixed number of times faster (or slower) than real time.
+///
+/// Its Unix time starts at the real time it was created and then advances at the scaled rate.
+pub struct ScaledClock {
+    started: Instant,
+    origin_unix_ms: u64,
+    scale: f64,
+}
+
+impl ScaledClock {
+    /// Creates a scaled clock starting now.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - Simulated seconds per real second; must be positive and finite.
+    pub fn new(scale: f64) -> Result<Self> {
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(MonitorError::Config(format!("time scale must be a positive number, got {}", scale)));
+        }
+        Ok(ScaledClock {
+            started: Instant::now(),
+            origin_unix_ms: now_millis(),
+            scale,
+        })
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Duration {
+        self.started.elapsed().mul_f64(self.scale)
+    }
+
+    fn unix_millis(&self) -> u64 {
+        self.origin_unix_ms + self.now().as_millis() as u64
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration.div_f64(self.scale));
+    }
+}
+
+/// A clock that only moves when it is advanced, for deterministic tests.
+///
+/// Sleeping advances the clock by the requested duration and returns immediately, so a loop
+/// that sleeps between iterations steps through simulated time as fast as it can run.
+pub struct ManualClock {
+    origin_unix_ms: u64,
+    elapsed: Mutex<Duration>,
+}
+
+impl ManualClock {
+    /// Creates a stopped clock at zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin_unix_ms` - Unix time of the clock's zero, in milliseconds.
+    pub fn new(origin_unix_ms: u64) -> Self {
+        ManualClock {
+            origin_unix_ms,
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn unix_millis(&self) -> u64 {
+        self.origin_unix_ms + self.now().as_millis() as u64
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+        // Let other threads observe the new time before this one carries on.
+        thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_moves_only_when_advanced() {
+        let clock = ManualClock::new(1_000);
+        assert_eq!(clock.now(), Duration::ZERO);
+        clock.advance(Duration::from_millis(250));
+        clock.sleep(Duration::from_secs(2));
+        assert_eq!(clock.now(), Duration::from_millis(2_250));
+        assert_eq!(clock.unix_millis(), 3_250);
+    }
+
+    #[test]
+    fn formats_time_of_day_in_utc() {
+        assert_eq!(format_utc_time(0), "00:00:00");
+        // 2025-01-31 13:05:09 UTC.
+        assert_eq!(format_utc_time(1_738_328_709), "13:05:09");
+        assert_eq!(format_utc_timestamp(1_738_328_709_250), "2025-01-31T13:05:09.250Z");
+        assert_eq!(format_utc_timestamp(1_709_164_800_000), "2024-02-29T00:00:00.000Z");
+    }
+
+    #[test]
+    fn parses_utc_dates() {
+        assert_eq!(parse_utc_date("1970-01-01"), Some(0));
+        assert_eq!(parse_utc_date("2025-01-31"), Some(1_738_328_709 / 86_400));
+        assert_eq!(parse_utc_date("2024-02-29"), Some(19_782));
+        assert_eq!(parse_utc_date("2025-02-29"), None);
+        assert_eq!(parse_utc_date("2025-12-1"), None);
+        assert_eq!(parse_utc_date("1969-12-31"), None);
+    }
+
+    #[test]
+    fn parses_utc_timestamps() {
+        assert_eq!(parse_utc_timestamp("2025-01-31T13:05:09.250Z"), Some(1_738_328_709_250));
+        assert_eq!(parse_utc_timestamp("2025-01-31T14:05:09.25+01:00"), Some(1_738_328_709_250));
+        assert_eq!(parse_utc_timestamp("2025-01-31T08:35:09.250999-04:30"), Some(1_738_328_709_250));
+        assert_eq!(parse_utc_timestamp("2025-01-31 13:05:09"), Some(1_738_328_709_000));
+        assert_eq!(parse_utc_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_utc_timestamp("1970-01-01T00:00:00+00:01"), None);
+        assert_eq!(parse_utc_timestamp("2025-01-31T24:00:00Z"), None);
+        assert_eq!(parse_utc_timestamp("2025-01-31T13:05Z"), None);
+        assert_eq!(parse_utc_timestamp("2025-01-31T13:05:09.Z"), None);
+        assert_eq!(parse_utc_timestamp("2025-01-31T13:05:09 UTC"), None);
+    }
+
+    #[test]
+    fn scaled_clock_rejects_non_positive_scales() {
+        assert!(ScaledClock::new(0.0).is_err());
+        assert!(ScaledClock::new(-2.0).is_err());
+        assert!(ScaledClock::new(f64::NAN).is_err());
+        assert!(ScaledClock::new(10.0).is_ok());
+    }
+}
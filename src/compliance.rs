@@ -0,0 +1,120 @@
+/// Maximum number of compliance flags; each flag claims one bit of `ComplianceStatus::flags`.
+pub const MAX_COMPLIANCE_FLAGS: usize = 64;
+
+/// Identifier of a claimed compliance flag, i.e. its bit position in the status flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComplianceFlag(pub u8);
+
+impl ComplianceFlag {
+    /// Returns the bit mask for this flag.
+    pub fn bit(self) -> u64 {
+        1 << self.0
+    }
+}
+
+/// Flags claimed by the built-in rule packs, always claimed first and in this order.
+pub const EU_AI_ACT: ComplianceFlag = ComplianceFlag(0);
+pub const GDPR: ComplianceFlag = ComplianceFlag(1);
+pub const INTERNAL_POLICY: ComplianceFlag = ComplianceFlag(2);
+pub const PURPOSE_LIMITATION: ComplianceFlag = ComplianceFlag(3);
+pub const DATA_RESIDENCY: ComplianceFlag = ComplianceFlag(4);
+
+/// Description of a compliance flag claimed by a rule pack.
+#[derive(Clone, Debug)]
+pub struct ComplianceFlagInfo {
+    /// Stable identifier used in audit logs and exports.
+    pub key: &'static str,
+    /// Display name used on the dashboard.
+    pub name: &'static str,
+}
+
+/// Registry of the compliance flags claimed by the enabled rule packs.
+///
+/// A set bit in `ComplianceStatus::flags` means the event passed that rule pack's check.
+#[derive(Clone, Debug)]
+pub struct ComplianceRegistry {
+    flags: Vec<ComplianceFlagInfo>,
+}
+
+impl ComplianceRegistry {
+    /// Creates a registry in which each built-in rule pack has claimed its flag.
+    pub fn builtin() -> Self {
+        let mut registry = ComplianceRegistry { flags: Vec::new() };
+        for (flag, key, name) in [
+            (EU_AI_ACT, "eu_ai_act", "EU AI Act"),
+            (GDPR, "gdpr", "GDPR"),
+            (INTERNAL_POLICY, "internal_policy", "Internal"),
+            (PURPOSE_LIMITATION, "purpose_limitation", "Purpose"),
+            (DATA_RESIDENCY, "data_residency", "Residency"),
+        ] {
+            let claimed = registry.claim(key, name).expect("built-in compliance flags are unique");
+            debug_assert_eq!(claimed, flag);
+        }
+        registry
+    }
+
+    /// Claims a new flag for a rule pack and returns it.
+    pub fn claim(&mut self, key: &'static str, name: &'static str) -> Result<ComplianceFlag, String> {
+        if self.flags.len() >= MAX_COMPLIANCE_FLAGS {
+            return Err(format!("at most {} compliance flags are supported", MAX_COMPLIANCE_FLAGS));
+        }
+        if self.flags.iter().any(|f| f.key == key) {
+            return Err(format!("compliance flag '{}' is already claimed", key));
+        }
+        self.flags.push(ComplianceFlagInfo { key, name });
+        Ok(ComplianceFlag((self.flags.len() - 1) as u8))
+    }
+
+    /// Returns the status flags of an event that passed every check.
+    pub fn all_compliant(&self) -> u64 {
+        if self.flags.len() == MAX_COMPLIANCE_FLAGS { u64::MAX } else { (1 << self.flags.len()) - 1 }
+    }
+
+    /// Decodes the keys of the checks an event failed.
+    pub fn violated_keys(&self, status_flags: u64) -> Vec<&'static str> {
+        self.iter()
+            .filter(|(flag, _)| status_flags & flag.bit() == 0)
+            .map(|(_, info)| info.key)
+            .collect()
+    }
+
+    /// Iterates over all claimed flags, in claim order.
+    pub fn iter(&self) -> impl Iterator<Item = (ComplianceFlag, &ComplianceFlagInfo)> {
+        self.flags.iter().enumerate().map(|(i, f)| (ComplianceFlag(i as u8), f))
+    }
+
+    /// Returns the number of claimed flags.
+    pub fn len(&self) -> usize {
+        self.flags.len()
+    }
+
+    /// Returns true if there are no claimed flags.
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_pack_into_64_bits_in_claim_order() {
+        let mut registry = ComplianceRegistry::builtin();
+        assert_eq!(registry.all_compliant(), 0b11111);
+        assert_eq!(registry.claim("gdpr", "GDPR").unwrap_err(), "compliance flag 'gdpr' is already claimed");
+
+        let status = registry.all_compliant() & !DATA_RESIDENCY.bit() & !GDPR.bit();
+        assert_eq!(registry.violated_keys(status), ["gdpr", "data_residency"]);
+        assert!(registry.violated_keys(registry.all_compliant()).is_empty());
+
+        for i in registry.len()..MAX_COMPLIANCE_FLAGS {
+            let key: &'static str = Box::leak(format!("check_{}", i).into_boxed_str());
+            registry.claim(key, key).unwrap();
+        }
+        assert_eq!(registry.all_compliant(), u64::MAX);
+        assert_eq!(registry.violated_keys(u64::MAX >> 1), ["check_63"]);
+        assert_eq!(registry.violated_keys(0).len(), MAX_COMPLIANCE_FLAGS);
+        assert_eq!(registry.claim("check_64", "Check").unwrap_err(), "at most 64 compliance flags are supported");
+    }
+}
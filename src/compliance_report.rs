@@ -0,0 +1,291 @@
+use crate::constants::{DEPARTMENT_NAMES, SERVICE_NAMES};
+use crate::metrics::ComplianceMetrics;
+use crate::policy::Policy;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Number of users listed in a report, ranked by their summed risk scores.
+const REPORT_USERS: usize = 10;
+
+/// Output formats of the `report` subcommand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Aligned plain text for the terminal.
+    Text,
+    /// Pretty-printed JSON for scripts.
+    Json,
+    /// Markdown tables for tickets and audit write-ups.
+    Markdown,
+}
+
+/// Compliance posture of a set of cumulative metrics, such as a saved snapshot.
+#[derive(Debug, Serialize)]
+pub struct ComplianceReport {
+    pub policy_version: &'static str,
+    pub events: u64,
+    pub compliance_percentage: f64,
+    /// Failed checks, summed over all events.
+    pub violations: u64,
+    /// Events with at least one violation exempted by a waiver.
+    pub waived_events: u64,
+    /// Events carrying protected health information.
+    pub phi_events: u64,
+    pub high_risk_events: u64,
+    pub medium_risk_events: u64,
+    pub low_risk_events: u64,
+    pub avg_data_sensitivity: f64,
+    pub checks: Vec<CheckSummary>,
+    pub departments: Vec<GroupSummary>,
+    pub services: Vec<GroupSummary>,
+    pub risk_factors: Vec<RiskFactorSummary>,
+    pub top_users: Vec<UserSummary>,
+}
+
+/// Violations of a single compliance check.
+#[derive(Debug, Serialize)]
+pub struct CheckSummary {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub violations: u64,
+    /// Violations by high-risk events.
+    pub high_risk_violations: u64,
+    /// Violations exempted by a waiver, not included in `violations`.
+    pub waived: u64,
+}
+
+/// Events and violations of a single department or service.
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+    pub name: &'static str,
+    pub events: u64,
+    pub violations: u64,
+    pub compliance_percentage: f64,
+}
+
+/// Events a single risk factor applied to.
+#[derive(Debug, Serialize)]
+pub struct RiskFactorSummary {
+    pub key: String,
+    pub name: String,
+    pub events: u64,
+}
+
+/// Counts of one of the riskiest users.
+#[derive(Debug, Serialize)]
+pub struct UserSummary {
+    pub user: u32,
+    pub events: u64,
+    pub violations: u64,
+    pub high_risk_events: u64,
+    pub total_risk_score: u64,
+}
+
+impl ComplianceReport {
+    /// Builds the report of cumulative metrics, naming checks and risk factors after the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - Cumulative metrics, such as those of a snapshot.
+    /// * `policy` - Policy the metrics were collected under.
+    pub fn new(metrics: &ComplianceMetrics, policy: &Policy) -> Self {
+        let empty = ComplianceMetrics::default();
+        let groups = |names: &[&'static str], groups: &[ComplianceMetrics]| -> Vec<GroupSummary> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(index, &name)| {
+                    let group = groups.get(index).unwrap_or(&empty);
+                    GroupSummary {
+                        name,
+                        events: group.total_events,
+                        violations: group.total_violations(),
+                        compliance_percentage: group.compliance_percentage(),
+                    }
+                })
+                .collect()
+        };
+        ComplianceReport {
+            policy_version: policy.version,
+            events: metrics.total_events,
+            compliance_percentage: metrics.compliance_percentage(),
+            violations: metrics.total_violations(),
+            waived_events: metrics.waived_events,
+            phi_events: metrics.phi_events,
+            high_risk_events: metrics.high_risk_count,
+            medium_risk_events: metrics.medium_risk_count,
+            low_risk_events: metrics.low_risk_count,
+            avg_data_sensitivity: metrics.avg_data_sensitivity,
+            checks: policy
+                .compliance
+                .iter()
+                .map(|(flag, info)| CheckSummary {
+                    key: info.key,
+                    name: info.name,
+                    violations: metrics.violations(flag),
+                    high_risk_violations: metrics.high_risk_violations.get(flag.0 as usize).copied().unwrap_or(0),
+                    waived: metrics.waived(flag),
+                })
+                .collect(),
+            departments: groups(&DEPARTMENT_NAMES, &metrics.departments),
+            services: groups(&SERVICE_NAMES, &metrics.services),
+            risk_factors: policy
+                .risk_factors
+                .iter()
+                .map(|(id, factor)| RiskFactorSummary {
+                    key: factor.key.clone(),
+                    name: factor.name.clone(),
+                    events: metrics.risk_factor_counts.get(id.0 as usize).copied().unwrap_or(0),
+                })
+                .collect(),
+            top_users: metrics
+                .top_users(REPORT_USERS)
+                .into_iter()
+                .map(|(user, counts)| UserSummary {
+                    user,
+                    events: counts.events,
+                    violations: counts.violations,
+                    high_risk_events: counts.high_risk_events,
+                    total_risk_score: counts.total_risk_score,
+                })
+                .collect(),
+        }
+    }
+
+    /// Renders the report in the given format, ending with a newline.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.render_text(),
+            ReportFormat::Json => serde_json::to_string_pretty(self).expect("reports serialize") + "\n",
+            ReportFormat::Markdown => self.render_markdown(),
+        }
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Compliance report (policy {})", self.policy_version);
+        let _ = writeln!(out, "  Events: {}", self.events);
+        let _ = writeln!(out, "  Compliance: {:.2}%", self.compliance_percentage);
+        let _ = writeln!(out, "  Violations: {} ({} events waived)", self.violations, self.waived_events);
+        let _ = writeln!(
+            out,
+            "  Risk: {} high, {} medium, {} low",
+            self.high_risk_events, self.medium_risk_events, self.low_risk_events
+        );
+        let _ = writeln!(out, "  Average data sensitivity: {:.2}", self.avg_data_sensitivity);
+        let _ = writeln!(out, "  PHI events: {}", self.phi_events);
+        let _ = writeln!(out, "\nViolations by check:");
+        for check in &self.checks {
+            let _ = writeln!(
+                out,
+                "  {:<24} {:>10} ({} high risk, {} waived)",
+                check.name, check.violations, check.high_risk_violations, check.waived
+            );
+        }
+        for (title, groups) in [("Departments", &self.departments), ("Services", &self.services)] {
+            let _ = writeln!(out, "\n{}:", title);
+            for group in groups {
+                let _ = writeln!(
+                    out,
+                    "  {:<24} {:>10} events {:>10} violations {:>7.2}%",
+                    group.name, group.events, group.violations, group.compliance_percentage
+                );
+            }
+        }
+        let _ = writeln!(out, "\nRisk factors:");
+        for factor in &self.risk_factors {
+            let _ = writeln!(out, "  {:<24} {:>10} events", factor.name, factor.events);
+        }
+        if !self.top_users.is_empty() {
+            let _ = writeln!(out, "\nRiskiest users:");
+            for user in &self.top_users {
+                let _ = writeln!(
+                    out,
+                    "  user {:<19} {:>10} events {:>10} violations {:>7} high risk",
+                    user.user, user.events, user.violations, user.high_risk_events
+                );
+            }
+        }
+        out
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Compliance report\n");
+        let _ = writeln!(out, "| Metric | Value |\n| --- | ---: |");
+        let _ = writeln!(out, "| Policy version | {} |", self.policy_version);
+        let _ = writeln!(out, "| Events | {} |", self.events);
+        let _ = writeln!(out, "| Compliance | {:.2}% |", self.compliance_percentage);
+        let _ = writeln!(out, "| Violations | {} |", self.violations);
+        let _ = writeln!(out, "| Waived events | {} |", self.waived_events);
+        let _ = writeln!(out, "| High / medium / low risk events | {} / {} / {} |", self.high_risk_events, self.medium_risk_events, self.low_risk_events);
+        let _ = writeln!(out, "| Average data sensitivity | {:.2} |", self.avg_data_sensitivity);
+        let _ = writeln!(out, "| PHI events | {} |", self.phi_events);
+        let _ = writeln!(out, "\n## Violations by check\n");
+        let _ = writeln!(out, "| Check | Violations | High risk | Waived |\n| --- | ---: | ---: | ---: |");
+        for check in &self.checks {
+            let _ = writeln!(out, "| {} | {} | {} | {} |", check.name, check.violations, check.high_risk_violations, check.waived);
+        }
+        for (title, heading, groups) in [("Departments", "Department", &self.departments), ("Services", "Service", &self.services)] {
+            let _ = writeln!(out, "\n## {}\n", title);
+            let _ = writeln!(out, "| {} | Events | Violations | Compliance |\n| --- | ---: | ---: | ---: |", heading);
+            for group in groups {
+                let _ = writeln!(out, "| {} | {} | {} | {:.2}% |", group.name, group.events, group.violations, group.compliance_percentage);
+            }
+        }
+        let _ = writeln!(out, "\n## Risk factors\n");
+        let _ = writeln!(out, "| Factor | Events |\n| --- | ---: |");
+        for factor in &self.risk_factors {
+            let _ = writeln!(out, "| {} | {} |", factor.name, factor.events);
+        }
+        if !self.top_users.is_empty() {
+            let _ = writeln!(out, "\n## Riskiest users\n");
+            let _ = writeln!(out, "| User | Events | Violations | High risk | Risk score |\n| --- | ---: | ---: | ---: | ---: |");
+            for user in &self.top_users {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} |",
+                    user.user, user.events, user.violations, user.high_risk_events, user.total_risk_score
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::GDPR;
+    use crate::metrics::UserCounts;
+
+    #[test]
+    fn reports_name_checks_and_render_in_every_format() {
+        let policy = Policy::builtin();
+        let mut violation_counts = vec![0; policy.compliance.len()];
+        violation_counts[GDPR.0 as usize] = 25;
+        let mut metrics = ComplianceMetrics {
+            total_events: 100,
+            violation_counts,
+            high_risk_count: 10,
+            ..ComplianceMetrics::default()
+        };
+        metrics.users.insert(7, UserCounts { events: 5, violations: 3, high_risk_events: 1, total_risk_score: 40 });
+
+        let report = ComplianceReport::new(&metrics, &policy);
+        assert_eq!(report.events, 100);
+        assert_eq!(report.violations, 25);
+        let gdpr = report.checks.iter().find(|check| check.key == "gdpr").unwrap();
+        assert_eq!(gdpr.violations, 25);
+        assert_eq!(report.departments.len(), DEPARTMENT_NAMES.len());
+        assert_eq!(report.top_users[0].user, 7);
+
+        let text = report.render(ReportFormat::Text);
+        assert!(text.contains("Events: 100"), "{}", text);
+        assert!(text.contains(gdpr.name), "{}", text);
+        let json: serde_json::Value = serde_json::from_str(&report.render(ReportFormat::Json)).unwrap();
+        assert_eq!(json["high_risk_events"], 10);
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains(&format!("| {} | 25 | 0 | 0 |", gdpr.name)), "{}", markdown);
+    }
+}
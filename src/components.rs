@@ -1,28 +1,562 @@
-use clap::Parser;
+use crate::error::ErrorPolicy;
+use crate::frontend::FrontendKind;
+use crate::compliance_report::ReportFormat;
+use crate::constants::PII_TYPE_NAMES;
+use crate::metrics::DEFAULT_HISTORY_POINTS;
+#[cfg(feature = "siem")]
+use crate::siem::SiemFormat;
+use crate::source::SourceSpec;
+use crate::traffic::TrafficPattern;
+use crate::ui::ascii::AsciiMode;
+use crate::ui::keys::{KeyBinding, KeyPreset};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 /// Command line arguments for the application.
+///
+/// The options here apply to every subcommand; each subcommand adds its own.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    /// Mode to run in; without one, synthetic events are generated and monitored live as with
+    /// `run`.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// TOML file of option values used where neither the command line nor an
+    /// `ECS_AI_COMPLIANCE_*` environment variable sets them (defaults to
+    /// `~/.config/ecs_ai_compliance/config.toml` if it exists).
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
     /// Number of AI events to process per second.
-    #[arg(short, long, default_value_t = 100000)]
+    #[arg(short, long, default_value_t = 100000, global = true, help_heading = "Pipeline")]
     pub rate: u32,
 
     /// Reporting interval in seconds.
-    #[arg(short, long, default_value_t = 5)]
+    #[arg(short, long, default_value_t = 5, global = true, help_heading = "Pipeline")]
     pub interval: u64,
 
+    /// How long replays and imports keep an event-time window open for events arriving out of order, in seconds.
+    #[arg(long, value_name = "SECONDS", default_value_t = 0, global = true, help_heading = "Pipeline")]
+    pub allowed_lateness: u64,
+
+    /// Run on a simulated clock this many times faster than real time (timestamps and intervals).
+    #[arg(long, default_value_t = 1.0, global = true, help_heading = "Pipeline")]
+    pub time_scale: f64,
+
     /// Number of worker threads (defaults to number of logical cores).
-    #[arg(short, long)]
+    #[arg(short, long, global = true, help_heading = "Pipeline")]
     pub threads: Option<usize>,
+
+    /// Split each batch into chunks of this many events and evaluate them in parallel.
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..), help_heading = "Pipeline")]
+    pub chunk_size: Option<u32>,
+
+    /// Upper bound on the time a worker spends on a single batch, in milliseconds.
+    #[arg(long, default_value_t = 20, global = true, value_parser = clap::value_parser!(u64).range(1..), help_heading = "Pipeline")]
+    pub max_batch_latency: u64,
+
+    /// Pin worker threads to these cores (comma-separated; empty = all cores round-robin).
+    #[arg(long, num_args = 0.., value_delimiter = ',', global = true, help_heading = "Pipeline")]
+    pub pin_cores: Option<Vec<usize>>,
+
+    /// Distributions of generated events: uniform, enterprise, or the path of a JSON profile file.
+    #[arg(long, value_name = "NAME|FILE", default_value = "uniform", global = true, help_heading = "Pipeline")]
+    pub event_profile: String,
+
+    /// Where `run` and `worker` take events from: synthetic, or file:<PATH> to read an event log
+    /// at its original pace on a single worker thread.
+    #[arg(long, value_name = "SOURCE", default_value = "synthetic", value_parser = SourceSpec::parse, global = true, help_heading = "Pipeline")]
+    pub source: SourceSpec,
+
+    /// How the rate and regional mix of generated events vary with the time of day and week.
+    #[arg(long, value_enum, default_value_t = TrafficPattern::Flat, global = true, help_heading = "Pipeline")]
+    pub traffic: TrafficPattern,
+
+    /// Number of metrics reports buffered between the replay thread or remote workers and the aggregator.
+    #[arg(long, default_value_t = 64, global = true, help_heading = "Pipeline")]
+    pub channel_capacity: usize,
+
+    /// Log more detail to the log file and the Logs tab (-v debug, -vv trace).
+    #[arg(short, long, action = clap::ArgAction::Count, global = true, help_heading = "Dashboard")]
+    pub verbose: u8,
+
+    /// Directory for the daily rotating log files.
+    #[arg(long, default_value = "logs", global = true, help_heading = "Dashboard")]
+    pub log_dir: PathBuf,
+
+    /// User interface to present the monitor with.
+    #[arg(long, value_enum, default_value_t = FrontendKind::Tui, global = true, help_heading = "Dashboard")]
+    pub frontend: FrontendKind,
+
+    /// Address the `remote` frontend serves `connect` dashboards on.
+    #[arg(long, default_value = "127.0.0.1:7171", global = true, help_heading = "Dashboard")]
+    pub dashboard_listen: String,
+
+    /// Also serve a read-only web dashboard on this address, e.g. 127.0.0.1:8080.
+    #[cfg(feature = "web")]
+    #[arg(long, global = true, help_heading = "Dashboard")]
+    pub web: Option<String>,
+
+    /// Most points kept in each metrics history; older points are merged pairwise beyond this.
+    #[arg(long, default_value_t = DEFAULT_HISTORY_POINTS, global = true, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..), help_heading = "Dashboard")]
+    pub history_points: usize,
+
+    /// Dashboard colors: default, high-contrast, color-blind, or the path of a JSON theme file.
+    #[arg(long, value_name = "NAME|FILE", default_value = "default", global = true, help_heading = "Dashboard")]
+    pub theme: String,
+
+    /// Draw the dashboard with ASCII characters only, for terminals that mangle Braille and box
+    /// drawing; `auto` does so for the Linux console, non-UTF-8 locales, and legacy Windows consoles.
+    #[arg(long, value_enum, value_name = "WHEN", num_args = 0..=1, default_value_t = AsciiMode::Auto, default_missing_value = "always", global = true, help_heading = "Dashboard")]
+    pub ascii: AsciiMode,
+
+    /// Built-in dashboard keybindings: `default`, or `vim` for h/l to switch tabs, j/k to move
+    /// through tables, and gg/G to jump to the top or bottom of a list.
+    #[arg(long, value_enum, value_name = "PRESET", default_value_t = KeyPreset::Default, global = true, help_heading = "Dashboard")]
+    pub keybindings: KeyPreset,
+
+    /// Bind a dashboard action to a key sequence in Vim's notation, such as `quit=<C-c>` or
+    /// `top=gg`, replacing the preset's keys for it; repeat to bind several keys.
+    #[arg(long, value_name = "ACTION=KEYS", value_parser = KeyBinding::parse, global = true, help_heading = "Dashboard")]
+    pub bind: Vec<KeyBinding>,
+
+    /// JSON file of custom dashboard tab layouts: rows of panels with their relative sizes.
+    #[arg(long, value_name = "FILE", global = true, help_heading = "Dashboard")]
+    pub layout: Option<PathBuf>,
+
+    /// Draw a reference line at this overall compliance, in percent, on the compliance charts.
+    #[arg(long, value_name = "PERCENT", global = true, help_heading = "Dashboard")]
+    pub compliance_target: Option<f64>,
+
+    /// Draw a reference line at this processing rate, in events per second, on the rate charts.
+    #[arg(long, value_name = "EVENTS_PER_SECOND", global = true, help_heading = "Dashboard")]
+    pub max_rate: Option<f64>,
+
+    /// Track cases of high-risk violations in this JSON file, restoring their review states on startup.
+    #[arg(long, global = true, help_heading = "Policy")]
+    pub case_file: Option<PathBuf>,
+
+    /// JSON file defining additional risk factors on top of the built-in ones.
+    #[arg(long, global = true, help_heading = "Policy")]
+    pub risk_factors: Option<PathBuf>,
+
+    /// JSON policy file enabling optional rule packs, such as HIPAA.
+    #[arg(long, global = true, help_heading = "Policy")]
+    pub policy: Option<PathBuf>,
+
+    /// JSON file of waivers exempting departments' use of services from compliance checks until they expire.
+    #[arg(long, global = true, help_heading = "Policy")]
+    pub waivers: Option<PathBuf>,
+
+    /// JSON policy file of a candidate policy evaluated in shadow mode next to `--policy`, whose
+    /// violations per check are compared with the active policy's.
+    #[arg(long, global = true, help_heading = "Policy")]
+    pub candidate_policy: Option<PathBuf>,
+
+    /// JSON waivers file of the candidate policy evaluated in shadow mode; defaults to `--waivers`.
+    #[arg(long, global = true, help_heading = "Policy")]
+    pub candidate_waivers: Option<PathBuf>,
+
+    /// Write the run summary (rates, peak RSS, CPU and per-stage time) to this JSON file on shutdown.
+    #[arg(long, global = true, help_heading = "Exports")]
+    pub run_result: Option<PathBuf>,
+
+    /// How to handle sink failures such as a failing event log write.
+    #[arg(long, value_enum, default_value_t = ErrorPolicy::Retry, global = true, help_heading = "Reliability")]
+    pub sink_error_policy: ErrorPolicy,
 }
 
+/// Subcommands supported by the application.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate synthetic events and monitor them live (the default without a subcommand).
+    Run(RunArgs),
+    /// Replay a recorded NDJSON event log through the compliance systems.
+    Replay(ReplayArgs),
+    /// Print a compliance report of a metrics snapshot written by `--snapshot-file`.
+    Report(ReportArgs),
+    /// Measure the throughput and batch latency of the compliance pipeline on fixed workloads.
+    Bench(BenchArgs),
+    /// Report how many events each check of one or more policies would flag in an NDJSON event
+    /// log, and how the checks overlap, without a dashboard.
+    Analyze(AnalyzeArgs),
+    /// Evaluate a directory of historical CSV and JSONL usage logs as fast as possible and print
+    /// the aggregate compliance report, without a dashboard.
+    Import(ImportArgs),
+    /// Check the options, config file, and the policy files they name without running.
+    #[command(alias = "validate-config")]
+    Check(RunArgs),
+    /// Run headless workers that report their metrics to a remote aggregator.
+    Worker(WorkerArgs),
+    /// Package the audit log, metrics snapshots, and active policy into a signed evidence bundle.
+    ExportEvidence(EvidenceArgs),
+    /// Verify the hash chain and signature of an audit log.
+    VerifyAudit(VerifyAuditArgs),
+    /// Show the dashboard of a monitor running with `--frontend remote` elsewhere.
+    Connect(ConnectArgs),
+}
+
+/// Arguments for the `run` and `check` subcommands: the ingestion endpoints and sinks of the
+/// live monitor.
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    /// Accept metrics from remote `worker` instances on this address, e.g. 0.0.0.0:7070.
+    #[arg(long, help_heading = "Ingestion")]
+    pub listen: Option<String>,
+
+    /// Serve the HTTP admin API for pausing, resuming, rate changes, and policy reloads on this
+    /// address, e.g. 127.0.0.1:9091.
+    #[arg(long, help_heading = "Ingestion")]
+    pub admin_listen: Option<String>,
+
+    /// Serve gRPC event ingestion and metric queries on this address, e.g. 0.0.0.0:50051.
+    #[cfg(feature = "grpc")]
+    #[arg(long, help_heading = "Ingestion")]
+    pub grpc_listen: Option<String>,
+
+    /// Record every generated event to an NDJSON log that can later be replayed.
+    #[arg(long, help_heading = "Persistence")]
+    pub record: Option<PathBuf>,
+
+    /// Persist cumulative metrics to this file on shutdown and restore them on startup.
+    #[arg(long, help_heading = "Persistence")]
+    pub snapshot_file: Option<PathBuf>,
+
+    /// Interval in seconds between periodic snapshots (0 = only on shutdown).
+    #[arg(long, default_value_t = 60, help_heading = "Persistence")]
+    pub snapshot_interval: u64,
+
+    /// Write a structured audit record for every evaluated event to this NDJSON file.
+    #[arg(long, help_heading = "Persistence")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Sign the audit log's hash chain with the Ed25519 key in this file (64 hex digits).
+    #[arg(long, requires = "audit_log", help_heading = "Persistence")]
+    pub audit_signing_key: Option<PathBuf>,
+
+    /// Number of audit batches buffered before workers block on the audit writer.
+    #[arg(long, default_value_t = 64, help_heading = "Persistence")]
+    pub audit_buffer: usize,
+
+    /// Stream every evaluated event to hourly partitioned Parquet files in this directory.
+    #[cfg(feature = "parquet")]
+    #[arg(long, help_heading = "Persistence")]
+    pub parquet_dir: Option<PathBuf>,
+
+    /// Forward every violation record to this Splunk HEC or Elasticsearch bulk endpoint.
+    #[cfg(feature = "siem")]
+    #[arg(long, help_heading = "SIEM")]
+    pub siem_url: Option<String>,
+
+    /// Ingestion API of the `--siem-url` endpoint.
+    #[cfg(feature = "siem")]
+    #[arg(long, value_enum, default_value_t = SiemFormat::SplunkHec, requires = "siem_url", help_heading = "SIEM")]
+    pub siem_format: SiemFormat,
+
+    /// File holding the Splunk HEC token or Elasticsearch API key.
+    #[cfg(feature = "siem")]
+    #[arg(long, requires = "siem_url", help_heading = "SIEM")]
+    pub siem_token_file: Option<PathBuf>,
+
+    /// Index to write violations to (Elasticsearch default: ai-compliance-violations).
+    #[cfg(feature = "siem")]
+    #[arg(long, requires = "siem_url", help_heading = "SIEM")]
+    pub siem_index: Option<String>,
+
+    /// Most violations forwarded to the SIEM in one request.
+    #[cfg(feature = "siem")]
+    #[arg(long, default_value_t = 500, help_heading = "SIEM")]
+    pub siem_batch_size: usize,
+
+    /// NDJSON file that violations the SIEM does not accept are appended to.
+    #[cfg(feature = "siem")]
+    #[arg(long, default_value = "siem-dead-letter.ndjson", help_heading = "SIEM")]
+    pub siem_dead_letter: PathBuf,
+
+    /// Run for this many hours while tracking memory growth, then fail if it exceeded `--soak-max-slope`.
+    #[arg(long, value_name = "HOURS", help_heading = "Soak Testing")]
+    pub soak: Option<f64>,
+
+    /// Largest growth of resident memory a soak run tolerates, in MiB per hour.
+    #[arg(long, default_value_t = 8.0, requires = "soak", help_heading = "Soak Testing")]
+    pub soak_max_slope: f64,
+
+    /// Evaluated events sampled per second across the local workers for the event inspector
+    /// (0 = no sampling).
+    #[arg(long, default_value_t = 20, help_heading = "Event Sampling")]
+    pub event_sample_rate: u32,
+
+    /// Number of non-compliant events injected by the `i` key or `POST /spike` without a count.
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..), help_heading = "Violation Spikes")]
+    pub spike_size: u64,
+
+    #[command(flatten)]
+    pub outputs: LiveOutputArgs,
+
+    /// Inject sink failures: probability (0-1) that a sink operation starts an outage.
+    #[arg(long, help_heading = "Reliability")]
+    pub chaos: Option<f64>,
+
+    /// Maximum length of an injected sink outage, in milliseconds.
+    #[arg(long, default_value_t = 500, help_heading = "Reliability")]
+    pub chaos_max_outage: u64,
+}
+
+/// Arguments for the sinks written every reporting interval, taken by the `run`, `check`,
+/// `replay`, and `import` subcommands.
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// Append per-interval metrics to CSV time series files in this directory.
+    #[arg(long, help_heading = "Exports")]
+    pub csv_export: Option<PathBuf>,
+
+    /// Write a shields.io endpoint badge of the compliance posture to this JSON file every interval.
+    #[arg(long, help_heading = "Exports")]
+    pub badge_file: Option<PathBuf>,
+
+    /// Write the shadow AI report, the unapproved models in use with their departments, events, and average sensitivity, to this CSV file every interval.
+    #[arg(long, help_heading = "Exports")]
+    pub shadow_ai_report: Option<PathBuf>,
+
+    /// Push per-interval counters and gauges to this StatsD server (`host:port`) over UDP.
+    #[arg(long, help_heading = "Exports")]
+    pub statsd: Option<String>,
+
+    /// Prefix of the metric names sent to StatsD.
+    #[arg(long, default_value = "ecs_ai_compliance", help_heading = "Exports")]
+    pub statsd_prefix: String,
+
+    /// Send checks, departments, and risk levels to StatsD as DogStatsD tags instead of in metric names.
+    #[arg(long, requires = "statsd", help_heading = "Exports")]
+    pub statsd_tags: bool,
+}
+
+/// Arguments for the outputs of a monitor running with a frontend, taken by the `run`, `check`,
+/// and `replay` subcommands: the interval sinks, the badge server, federation, and alerts.
+#[derive(clap::Args, Debug)]
+pub struct LiveOutputArgs {
+    #[command(flatten)]
+    pub exports: ExportArgs,
+
+    /// Serve the compliance badge (`/badge.json`), a one-line status (`/status`), and Prometheus metrics (`/metrics`) over HTTP on this address.
+    #[arg(long, help_heading = "Exports")]
+    pub badge_listen: Option<String>,
+
+    /// Push each reporting interval's metrics to the `--listen` socket of a federation leader,
+    /// e.g. leader-host:7070.
+    #[arg(long, help_heading = "Federation")]
+    pub federate: Option<String>,
+
+    /// Name this instance reports to a federation leader under (defaults to the host name).
+    #[arg(long, help_heading = "Federation")]
+    pub instance_name: Option<String>,
+
+    /// Post alerts, such as SLO burn alerts, to this Slack incoming webhook URL.
+    #[cfg(feature = "slack")]
+    #[arg(long, help_heading = "Alerts")]
+    pub slack_webhook: Option<String>,
+
+    /// Slack channel to post alerts to instead of the webhook's default channel.
+    #[cfg(feature = "slack")]
+    #[arg(long, requires = "slack_webhook", help_heading = "Alerts")]
+    pub slack_channel: Option<String>,
+
+    /// Seconds to collect alerts for after the first one, before posting them to Slack as one message.
+    #[cfg(feature = "slack")]
+    #[arg(long, default_value_t = 60, help_heading = "Alerts")]
+    pub slack_batch_secs: u64,
+
+    /// JSON file with cron schedules of windows during which alerts are recorded but not sent to
+    /// Slack or PagerDuty, e.g. for planned load tests.
+    #[arg(long, help_heading = "Alerts")]
+    pub silence_windows: Option<PathBuf>,
+
+    /// JSON file with a PagerDuty routing key and the alert rules that open incidents.
+    #[cfg(feature = "pagerduty")]
+    #[arg(long, help_heading = "Alerts")]
+    pub pagerduty: Option<PathBuf>,
+}
+
+/// Arguments for the `replay` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ReplayArgs {
+    /// Path to the event log to replay: NDJSON, or CSV with a header row if it ends in `.csv`.
+    pub file: PathBuf,
+
+    /// Playback speed multiplier (1.0 = original timing, 0 = as fast as possible).
+    #[arg(short, long, default_value_t = 1.0)]
+    pub speed: f64,
+
+    /// JSON file mapping event fields to the columns holding them, e.g. `{"service": "tool"}`;
+    /// unmapped fields are read from columns named like the field.
+    #[arg(long, value_name = "FILE")]
+    pub mapping: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub outputs: LiveOutputArgs,
+}
+
+/// Arguments for the `report` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ReportArgs {
+    /// Path to the metrics snapshot to report on.
+    pub snapshot: PathBuf,
+
+    /// Output format of the report.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+}
+
+/// Arguments for the `analyze` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct AnalyzeArgs {
+    /// Path to the NDJSON event log written by `--record`.
+    pub file: PathBuf,
+
+    /// Rule pack files to analyze, each combined with the `--risk-factors` and `--waivers` in
+    /// effect; defaults to the policy given by `--policy`.
+    pub policies: Vec<PathBuf>,
+
+    /// Output format of the report.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+}
+
+/// Arguments for the `import` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Directory of `.csv`, `.jsonl`, and `.ndjson` usage logs, imported in name order.
+    pub dir: PathBuf,
+
+    /// JSON file mapping event fields to the columns holding them, e.g. `{"service": "tool"}`;
+    /// unmapped fields are read from columns named like the field.
+    #[arg(long, value_name = "FILE")]
+    pub mapping: Option<PathBuf>,
+
+    /// Write a structured audit record for every imported event to this NDJSON file.
+    #[arg(long)]
+    pub audit_log: Option<PathBuf>,
+
+    /// Sign the audit log's hash chain with the Ed25519 key in this file (64 hex digits).
+    #[arg(long, requires = "audit_log")]
+    pub audit_signing_key: Option<PathBuf>,
+
+    /// Number of audit batches buffered before the import blocks on the audit writer.
+    #[arg(long, default_value_t = 64)]
+    pub audit_buffer: usize,
+
+    /// Output format of the report.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+
+    /// Write the report to this file instead of standard output.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub exports: ExportArgs,
+}
+
+/// Arguments for the `bench` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct BenchArgs {
+    /// Events evaluated per sample, split across the threads (comma-separated).
+    #[arg(long, value_delimiter = ',', default_value = "10000,100000")]
+    pub entities: Vec<usize>,
+
+    /// Numbers of threads evaluating events, each with its own world (comma-separated).
+    #[arg(long, value_delimiter = ',', default_value = "1,4")]
+    pub thread_counts: Vec<usize>,
+
+    /// Events per batch (comma-separated).
+    #[arg(long, value_delimiter = ',', default_value = "500,2500,10000")]
+    pub batch_sizes: Vec<usize>,
+
+    /// Timed runs of each workload, after one warm-up run.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(2..))]
+    pub samples: u64,
+
+    /// Compare the results with the baseline saved in this file.
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Save the results as a baseline to this file.
+    #[arg(long, value_name = "FILE")]
+    pub save_baseline: Option<PathBuf>,
+
+    /// Smallest change in mean time, in percent, reported as an improvement or regression.
+    #[arg(long, default_value_t = 5.0)]
+    pub noise_threshold: f64,
+}
+
+/// Arguments for the `worker` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct WorkerArgs {
+    /// Address of the aggregator's `--listen` socket.
+    #[arg(long)]
+    pub aggregator: String,
+
+    /// LZ4-compress metric frames before sending them.
+    #[arg(long)]
+    pub compress: bool,
+
+    /// Name this instance reports to the aggregator under (defaults to the host name).
+    #[arg(long)]
+    pub instance_name: Option<String>,
+}
+
+/// Arguments for the `connect` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ConnectArgs {
+    /// Address of the monitor's `--dashboard-listen` socket, e.g. localhost:7171 through an SSH
+    /// tunnel.
+    pub addr: String,
+}
+
+/// Arguments for the `export-evidence` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct EvidenceArgs {
+    /// Path of the zip archive to create.
+    pub output: PathBuf,
+
+    /// File holding the Ed25519 key that signs the manifest, as 64 hex digits.
+    #[arg(long)]
+    pub signing_key: PathBuf,
+
+    /// NDJSON audit log to include, as written by `--audit-log`.
+    #[arg(long)]
+    pub audit_log: Option<PathBuf>,
+
+    /// Metrics snapshot to include, as written by `--snapshot-file`; may be repeated.
+    #[arg(long = "snapshot")]
+    pub snapshots: Vec<PathBuf>,
+}
+
+/// Arguments for the `verify-audit` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct VerifyAuditArgs {
+    /// Path to the NDJSON audit log to verify.
+    pub file: PathBuf,
+
+    /// Ed25519 public key (64 hex digits) the log must be signed with.
+    #[arg(long)]
+    pub public_key: Option<String>,
+}
+
+/// Component carrying a process-unique event id, attached only when audit records are emitted.
+#[derive(Clone, Copy, Debug)]
+pub struct EventId(pub u64);
+
 /// Component representing an AI service event.
-/// Uses indices into static arrays for name and vendor to reduce memory footprint.
+/// Uses indices into static arrays for name, vendor, and model to reduce memory footprint.
 #[derive(Clone, Copy)]
 pub struct AIService {
     pub name_idx: u8,
     pub vendor_idx: u8,
+    /// Index into `MODEL_NAMES`; always one of the models of the service at `name_idx`.
+    pub model_idx: u8,
 }
 
 /// Component representing the usage details of an AI event.
@@ -32,15 +566,168 @@ pub struct Usage {
     pub data_sensitivity: u8, // Scale from 0 to 100.
 }
 
+/// Component identifying the user behind an AI event by their numeric id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserId(pub u32);
+
+impl UserId {
+    /// Placeholder for events without a user id, such as those from older event logs. They are
+    /// not counted per user.
+    pub const UNKNOWN: UserId = UserId(u32::MAX);
+
+    /// Returns the id, or `None` for [`UserId::UNKNOWN`].
+    pub fn known(self) -> Option<u32> {
+        (self != Self::UNKNOWN).then_some(self.0)
+    }
+}
+
+/// Component recording what an AI event's prompt is used for, as an index into `PURPOSE_NAMES`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Purpose(pub u8);
+
+impl Purpose {
+    /// Placeholder for events without a declared purpose, such as those from older event logs.
+    /// They are not checked for purpose limitation and not counted per purpose.
+    pub const UNSPECIFIED: Purpose = Purpose(u8::MAX);
+
+    /// Returns the index into `PURPOSE_NAMES`, or `None` for [`Purpose::UNSPECIFIED`].
+    pub fn known(self) -> Option<usize> {
+        (self != Self::UNSPECIFIED).then_some(self.0 as usize)
+    }
+}
+
+/// Component recording where an AI request is processed, as an index into `REGION_NAMES`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region(pub u8);
+
+impl Region {
+    /// Placeholder for events without a known processing region, such as those from older event
+    /// logs. They are not checked for data residency and not counted per region.
+    pub const UNKNOWN: Region = Region(u8::MAX);
+
+    /// Returns the index into `REGION_NAMES`, or `None` for [`Region::UNKNOWN`].
+    pub fn known(self) -> Option<usize> {
+        (self != Self::UNKNOWN).then_some(self.0 as usize)
+    }
+}
+
+/// Component recording the GDPR lawful basis an AI event's data is processed on, as an index
+/// into `LAWFUL_BASIS_NAMES`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LawfulBasis(pub u8);
+
+impl LawfulBasis {
+    /// Consent of the data subject, the only basis accepted for special-category data.
+    pub const CONSENT: LawfulBasis = LawfulBasis(0);
+
+    /// Processing without any lawful basis, which GDPR forbids for personal data.
+    pub const NONE: LawfulBasis = LawfulBasis(3);
+
+    /// Placeholder for events without a recorded basis, such as those from older event logs.
+    /// They are not checked for a lawful basis and not counted per basis.
+    pub const UNKNOWN: LawfulBasis = LawfulBasis(u8::MAX);
+
+    /// Returns the index into `LAWFUL_BASIS_NAMES`, or `None` for [`LawfulBasis::UNKNOWN`].
+    pub fn known(self) -> Option<usize> {
+        (self != Self::UNKNOWN).then_some(self.0 as usize)
+    }
+}
+
+/// Component recording the types of personally identifiable information in an AI event's data, as
+/// bit flags in the order of `PII_TYPE_NAMES`. It supplements `Usage::data_sensitivity`, which
+/// says how sensitive the data is but not what it is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PiiTypes(pub u8);
+
+impl PiiTypes {
+    /// No personally identifiable information, or none declared, as in older event logs.
+    pub const NONE: PiiTypes = PiiTypes(0);
+    pub const CONTACT: PiiTypes = PiiTypes(1 << 0);
+    pub const FINANCIAL: PiiTypes = PiiTypes(1 << 1);
+    pub const HEALTH: PiiTypes = PiiTypes(1 << 2);
+    pub const BIOMETRIC: PiiTypes = PiiTypes(1 << 3);
+    pub const CHILDREN: PiiTypes = PiiTypes(1 << 4);
+
+    /// Special categories of personal data under GDPR Art. 9.
+    pub const SPECIAL_CATEGORY: PiiTypes = PiiTypes(Self::HEALTH.0 | Self::BIOMETRIC.0);
+
+    /// Returns true if the data contains any of the types in `types`.
+    pub fn contains_any(self, types: PiiTypes) -> bool {
+        self.0 & types.0 != 0
+    }
+
+    /// Returns true if the data contains special-category personal data.
+    pub fn is_special_category(self) -> bool {
+        self.contains_any(Self::SPECIAL_CATEGORY)
+    }
+
+    /// Iterates over the indices into `PII_TYPE_NAMES` of the types the data contains.
+    pub fn indices(self) -> impl Iterator<Item = usize> {
+        (0..PII_TYPE_NAMES.len()).filter(move |&index| self.0 & (1 << index) != 0)
+    }
+}
+
+/// Component recording when an AI event happened, in milliseconds since the Unix epoch: the
+/// time recorded at ingestion, or the clock reading when a synthetic event is generated. Waivers
+/// and audit records go by it, and replays and imports window their metrics by it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp(pub u64);
+
+/// The components of an AI event, as generated, read from a log, or taken from a source, before
+/// they are spawned into a world.
+pub type Event = (AIService, Usage, UserId, Purpose, Region, LawfulBasis, PiiTypes, Timestamp);
+
+/// Component holding the risk tier an AI event falls into under the EU AI Act.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AiActTier {
+    /// Prohibited practices.
+    Unacceptable,
+    /// High-risk uses, subject to conformity assessment.
+    High,
+    /// Uses with transparency obligations.
+    Limited,
+    /// Everything else, covered by voluntary codes of conduct.
+    #[default]
+    Minimal,
+}
+
+/// Every AI Act risk tier, from the most to the least restricted.
+pub const AI_ACT_TIERS: [AiActTier; 4] = [AiActTier::Unacceptable, AiActTier::High, AiActTier::Limited, AiActTier::Minimal];
+
+impl AiActTier {
+    /// Returns the display name of the tier.
+    pub fn name(self) -> &'static str {
+        match self {
+            AiActTier::Unacceptable => "Unacceptable",
+            AiActTier::High => "High",
+            AiActTier::Limited => "Limited",
+            AiActTier::Minimal => "Minimal",
+        }
+    }
+
+    /// Returns the provisions of the AI Act that define the tier, as cited in audit logs.
+    pub fn article(self) -> &'static str {
+        match self {
+            AiActTier::Unacceptable => "Art. 5",
+            AiActTier::High => "Art. 6, Annex III",
+            AiActTier::Limited => "Art. 50",
+            AiActTier::Minimal => "Art. 95",
+        }
+    }
+}
+
 /// Component representing compliance status using bit flags.
+/// Each bit is claimed by a rule pack in the `ComplianceRegistry`; a set bit means compliant.
 #[derive(Clone, Copy)]
 pub struct ComplianceStatus {
-    pub flags: u8,
+    pub flags: u64,
+    /// Bit flags of the violations exempted by a waiver; their bits in `flags` are set again.
+    pub waived: u64,
 }
 
 /// Component representing a risk assessment for an AI event.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct RiskAssessment {
     pub score: u8,      // Risk score on a 0-100 scale.
-    pub factor_flags: u16, // Bit flags indicating which risk factors apply.
+    pub factor_flags: u64, // Bit flags indicating which registered risk factors apply.
 }
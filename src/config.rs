@@ -0,0 +1,520 @@
+use crate::components::Args;
+use crate::error::{MonitorError, Result};
+use clap::parser::ValueSource;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{Arg, ArgAction, CommandFactory, FromArgMatches};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the environment variables setting options, e.g. `ECS_AI_COMPLIANCE_RATE` for
+/// `--rate` and `ECS_AI_COMPLIANCE_REPLAY_SPEED` for `replay --speed`.
+pub const ENV_PREFIX: &str = "ECS_AI_COMPLIANCE_";
+
+/// Id of the option naming the config file, which cannot itself be set from the file.
+const CONFIG_ARG: &str = "config";
+
+/// Config file table of dashboard keybindings: its `preset` sets `--keybindings`, and every
+/// other key binds the action of that name with `--bind`.
+const KEYBINDINGS_TABLE: &str = "keybindings";
+
+/// Subcommand run when none is given.
+const DEFAULT_COMMAND: &str = "run";
+
+/// Subcommands taking the live monitor's options. Since running live is the default, their
+/// environment variables and config file keys are named like the program's own, e.g.
+/// `ECS_AI_COMPLIANCE_RECORD` and `record` for `run --record`.
+const LIVE_COMMANDS: [&str; 2] = [DEFAULT_COMMAND, "check"];
+
+/// Where an option's value came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingSource {
+    CommandLine,
+    Environment,
+    ConfigFile,
+}
+
+impl SettingSource {
+    /// Returns the display name of the source.
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingSource::CommandLine => "command line",
+            SettingSource::Environment => "environment",
+            SettingSource::ConfigFile => "config file",
+        }
+    }
+}
+
+/// The config file that was read and the options set on top of their defaults.
+#[derive(Debug, Default)]
+pub struct ConfigLayers {
+    pub config_file: Option<PathBuf>,
+    /// Options that were set, such as `--rate` or `replay --speed`, with where their values came
+    /// from, in the order `--help` lists them.
+    pub settings: Vec<(String, SettingSource)>,
+}
+
+/// Returns the command line parser, with an `ECS_AI_COMPLIANCE_*` environment variable for every
+/// option of the program and its subcommands.
+///
+/// Variable values are hidden from `--help`, since some options carry secrets such as webhook
+/// URLs.
+pub fn command() -> clap::Command {
+    let command = Args::command().mut_args(|arg| with_env(arg, None));
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    subcommands.iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |sub| sub.mut_args(|arg| with_env(arg, option_scope(name))))
+    })
+}
+
+/// Returns the scope an option of `subcommand` is named in outside the command line: the
+/// subcommand itself, or `None` for the live monitor's subcommands.
+fn option_scope(subcommand: &str) -> Option<&str> {
+    (!LIVE_COMMANDS.contains(&subcommand)).then_some(subcommand)
+}
+
+/// Attaches the environment variable of an option; positional arguments have none.
+fn with_env(arg: Arg, scope: Option<&str>) -> Arg {
+    match arg.get_long() {
+        Some(long) => {
+            let name = env_name(scope, long);
+            arg.env(name).hide_env_values(true)
+        }
+        None => arg,
+    }
+}
+
+/// Parses the command line, filling in options it does not set from the environment and then
+/// from the config file. Without a subcommand, the command line is parsed as `run`.
+///
+/// The precedence is command line, then `ECS_AI_COMPLIANCE_*` environment variables, then the
+/// config file, then the built-in defaults. The config file is `--config`, or
+/// `ECS_AI_COMPLIANCE_CONFIG`, or `config.toml` in the user's config directory if it exists. It
+/// is a TOML table keyed by long option names, such as `rate = 50000`. Other tables group
+/// options by prefix, so `[slack]` with `webhook` sets `--slack-webhook`, except that tables
+/// named after a subcommand, such as `[replay]`, hold that subcommand's options. The options of
+/// `run` are set at the top level, like the program's own. The `[keybindings]` table picks a
+/// preset and binds actions to keys, such as `preset = "vim"` and `quit = ["q", "<C-c>"]`.
+///
+/// # Arguments
+///
+/// * `argv` - The command line, starting with the program name.
+///
+/// # Errors
+///
+/// [`MonitorError::Usage`] if the command line is invalid, or asks for help or the version;
+/// [`MonitorError::Config`] if an environment variable has an invalid value, or the config file
+/// sets an unknown option or an invalid value.
+pub fn parse_layered(mut argv: Vec<OsString>) -> Result<(Args, ConfigLayers)> {
+    let command = command();
+    // Missing required options may be filled in from the config file, so they are checked only
+    // once every layer has been applied.
+    let lenient = command.clone().ignore_errors(true);
+    let mut given = lenient.clone().try_get_matches_from(&argv).map_err(MonitorError::Usage)?;
+    if given.subcommand().is_none() {
+        // A mistyped subcommand is reported as such rather than as an argument of `run`.
+        if let Err(e) = command.clone().try_get_matches_from(&argv)
+            && e.kind() == ErrorKind::InvalidSubcommand
+        {
+            return Err(MonitorError::Usage(e));
+        }
+        argv.insert(argv.len().min(1), OsString::from(DEFAULT_COMMAND));
+        given = lenient.try_get_matches_from(&argv).map_err(MonitorError::Usage)?;
+    }
+
+    let config_file = match given.get_one::<PathBuf>(CONFIG_ARG) {
+        Some(path) => Some(path.clone()),
+        None => default_config_path().filter(|path| path.is_file()),
+    };
+    let file_settings = match &config_file {
+        Some(path) => read_config_file(path)?,
+        None => Vec::new(),
+    };
+
+    let subcommand = given.subcommand();
+    let mut layers = ConfigLayers { config_file: config_file.clone(), settings: Vec::new() };
+    let mut leading = Vec::new();
+    let mut trailing = Vec::new();
+    // Variables setting an option, with the option they set, to name them in errors.
+    let mut variables = Vec::new();
+    let mut scopes = vec![(None, &command, &given)];
+    if let Some((name, matches)) = subcommand {
+        scopes.extend(command.find_subcommand(name).map(|sub| (Some(name), sub, matches)));
+    }
+    for (scope, scope_command, matches) in scopes {
+        for arg in scope_command.get_arguments() {
+            let id = arg.get_id().as_str();
+            let Some(long) = arg.get_long() else {
+                continue;
+            };
+            if arg.is_global_set() && scope.is_some() || id == CONFIG_ARG {
+                continue;
+            }
+            let key_scope = scope.and_then(option_scope);
+            let display = match key_scope {
+                Some(name) => format!("{} --{}", name, long),
+                None => format!("--{}", long),
+            };
+            match matches.value_source(id) {
+                Some(ValueSource::CommandLine) => {
+                    layers.settings.push((display, SettingSource::CommandLine));
+                    continue;
+                }
+                Some(ValueSource::EnvVariable) => {
+                    variables.extend(arg.get_env().map(|name| (name.to_string_lossy().into_owned(), format!("--{}", long))));
+                    layers.settings.push((display, SettingSource::Environment));
+                    continue;
+                }
+                _ => {}
+            }
+            let Some(setting) = file_settings.iter().find(|setting| setting.scope.as_deref() == key_scope && setting.key == long) else {
+                continue;
+            };
+            let invalid = |message: String| MonitorError::Config(format!("{}: {}", setting.describe(config_file.as_deref()), message));
+            let tokens = arg_tokens(arg, long, &setting.values).map_err(invalid)?;
+            check_tokens(&command, scope, &tokens).map_err(invalid)?;
+            if tokens.is_empty() {
+                continue;
+            }
+            layers.settings.push((display, SettingSource::ConfigFile));
+            match scope {
+                Some(_) => trailing.extend(tokens),
+                None => leading.extend(tokens),
+            }
+        }
+    }
+
+    // Options of the program go before anything on the command line, and options of the
+    // subcommand after it; neither repeats an option given on the command line.
+    let mut layered: Vec<OsString> = argv.iter().take(1).cloned().collect();
+    layered.extend(leading.into_iter().map(OsString::from));
+    layered.extend(argv.into_iter().skip(1));
+    layered.extend(trailing.into_iter().map(OsString::from));
+    let matches = command.try_get_matches_from(layered).map_err(|e| blame_variable(e, &variables))?;
+    let args = Args::from_arg_matches(&matches).map_err(MonitorError::Usage)?;
+    Ok((args, layers))
+}
+
+/// Names the environment variable in an error about an option's value, if the value came from
+/// one, since clap reports it as if it were given on the command line.
+///
+/// # Arguments
+///
+/// * `error` - The error parsing every layer.
+/// * `variables` - Environment variables that set an option, with the option they set.
+fn blame_variable(error: clap::Error, variables: &[(String, String)]) -> MonitorError {
+    let Some(ContextValue::String(invalid)) = error.get(ContextKind::InvalidArg) else {
+        return MonitorError::Usage(error);
+    };
+    // The option is shown with its value name, as in `--rate <RATE>`.
+    let option = invalid.split(['=', ' ']).next().unwrap_or_default();
+    match variables.iter().find(|(_, long)| long == option) {
+        Some((name, _)) => {
+            let rendered = error.render().to_string();
+            let message = rendered.lines().next().unwrap_or_default();
+            MonitorError::Config(format!("{}: {}", name, message.trim_start_matches("error: ")))
+        }
+        None => MonitorError::Usage(error),
+    }
+}
+
+/// Returns the environment variable setting an option.
+fn env_name(scope: Option<&str>, long: &str) -> String {
+    let name = match scope {
+        Some(subcommand) => format!("{}_{}", subcommand, long),
+        None => long.to_string(),
+    };
+    format!("{}{}", ENV_PREFIX, name.to_ascii_uppercase().replace('-', "_"))
+}
+
+/// Returns the config file read when neither `--config` nor `ECS_AI_COMPLIANCE_CONFIG` is given:
+/// `ecs_ai_compliance/config.toml` under `$XDG_CONFIG_HOME`, or under `~/.config` if it is unset.
+fn default_config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(base.join("ecs_ai_compliance").join("config.toml"))
+}
+
+/// An option set in the config file, with its values as they would be written on the command line.
+#[derive(Debug)]
+struct FileSetting {
+    /// Subcommand whose option it is, or `None` for the program's own options.
+    scope: Option<String>,
+    /// Long option name.
+    key: String,
+    values: Vec<String>,
+}
+
+impl FileSetting {
+    /// Describes where the setting is, for error messages.
+    fn describe(&self, file: Option<&Path>) -> String {
+        let file = file.map_or_else(String::new, |path| format!("{}: ", path.display()));
+        match &self.scope {
+            Some(scope) => format!("{}[{}] {}", file, scope, self.key),
+            None => format!("{}{}", file, self.key),
+        }
+    }
+}
+
+/// Reads a config file and checks that every option it sets exists.
+fn read_config_file(path: &Path) -> Result<Vec<FileSetting>> {
+    let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+    let text = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| invalid(e.message().to_string()))?;
+    let settings = flatten_config(&table).map_err(invalid)?;
+    let command = Args::command();
+    for setting in &settings {
+        let scope_commands: Vec<&clap::Command> = match &setting.scope {
+            Some(name) => command.find_subcommand(name).into_iter().collect(),
+            None => std::iter::once(&command).chain(LIVE_COMMANDS.iter().filter_map(|name| command.find_subcommand(name))).collect(),
+        };
+        let known = scope_commands
+            .iter()
+            .flat_map(|scope_command| scope_command.get_arguments())
+            .any(|arg| arg.get_long() == Some(setting.key.as_str()) && arg.get_id() != CONFIG_ARG);
+        if !known {
+            return Err(invalid(format!("unknown option `{}`", setting.describe(None))));
+        }
+    }
+    Ok(settings)
+}
+
+/// Flattens a parsed config file into the options it sets.
+fn flatten_config(table: &toml::Table) -> std::result::Result<Vec<FileSetting>, String> {
+    let command = Args::command();
+    let mut settings = Vec::new();
+    for (key, value) in table {
+        let key = key.replace('_', "-");
+        match value {
+            toml::Value::Table(bindings) if key == KEYBINDINGS_TABLE => flatten_keybindings(bindings, &mut settings)?,
+            toml::Value::Table(options) if command.find_subcommand(&key).is_some() => {
+                flatten_table(options, option_scope(&key), "", &mut settings)?;
+            }
+            toml::Value::Table(options) => flatten_table(options, None, &format!("{}-", key), &mut settings)?,
+            value => settings.push(FileSetting { scope: None, key, values: option_values(value)? }),
+        }
+    }
+    Ok(settings)
+}
+
+/// Adds the options of a table, whose keys are prefixed with `prefix`.
+fn flatten_table(table: &toml::Table, scope: Option<&str>, prefix: &str, settings: &mut Vec<FileSetting>) -> std::result::Result<(), String> {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key.replace('_', "-"));
+        match value {
+            toml::Value::Table(options) => flatten_table(options, scope, &format!("{}-", key), settings)?,
+            value => settings.push(FileSetting {
+                scope: scope.map(str::to_string),
+                key,
+                values: option_values(value)?,
+            }),
+        }
+    }
+    Ok(())
+}
+
+/// Adds the options set by the `[keybindings]` table: `--keybindings` for its preset, and one
+/// `--bind` for every key of every other action, or an empty one if it is unbound.
+fn flatten_keybindings(table: &toml::Table, settings: &mut Vec<FileSetting>) -> std::result::Result<(), String> {
+    let mut binds = Vec::new();
+    for (key, value) in table {
+        if key == "preset" {
+            settings.push(FileSetting { scope: None, key: KEYBINDINGS_TABLE.to_string(), values: option_values(value)? });
+            continue;
+        }
+        let keys = option_values(value)?;
+        if keys.is_empty() {
+            binds.push(format!("{}=", key));
+        }
+        binds.extend(keys.iter().map(|keys| format!("{}={}", key, keys)));
+    }
+    if !binds.is_empty() {
+        settings.push(FileSetting { scope: None, key: "bind".to_string(), values: binds });
+    }
+    Ok(())
+}
+
+/// Converts a TOML value into option values; arrays give one value per element.
+fn option_values(value: &toml::Value) -> std::result::Result<Vec<String>, String> {
+    match value {
+        toml::Value::String(value) => Ok(vec![value.clone()]),
+        toml::Value::Array(items) => items.iter().map(scalar).collect(),
+        value => scalar(value).map(|value| vec![value]),
+    }
+}
+
+/// Converts a TOML scalar into an option value.
+fn scalar(value: &toml::Value) -> std::result::Result<String, String> {
+    match value {
+        toml::Value::String(value) => Ok(value.clone()),
+        toml::Value::Integer(value) => Ok(value.to_string()),
+        toml::Value::Float(value) => Ok(value.to_string()),
+        toml::Value::Boolean(value) => Ok(value.to_string()),
+        toml::Value::Datetime(value) => Ok(value.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => Err("nested arrays and tables of values are not supported".to_string()),
+    }
+}
+
+/// Writes an option's values as command line arguments.
+///
+/// Switches take `true` or `false`, counted options such as `--verbose` a number of
+/// repetitions, and options taking several values a list.
+fn arg_tokens(arg: &Arg, long: &str, values: &[String]) -> std::result::Result<Vec<String>, String> {
+    let single = || match values {
+        [value] => Ok(value.as_str()),
+        _ => Err(format!("--{} takes a single value", long)),
+    };
+    match arg.get_action() {
+        ArgAction::SetTrue => {
+            let enabled = match single()?.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => true,
+                "false" | "0" | "no" | "off" | "" => false,
+                value => return Err(format!("--{} is a switch; expected true or false, not `{}`", long, value)),
+            };
+            Ok(if enabled { vec![format!("--{}", long)] } else { Vec::new() })
+        }
+        ArgAction::Count => {
+            let count: u8 = single()?
+                .parse()
+                .map_err(|_| format!("--{} is counted; expected a number of repetitions", long))?;
+            Ok(vec![format!("--{}", long); count as usize])
+        }
+        ArgAction::Append => Ok(values.iter().map(|value| format!("--{}={}", long, value)).collect()),
+        _ => match arg.get_value_delimiter() {
+            Some(_) if values.is_empty() => Ok(vec![format!("--{}", long)]),
+            Some(delimiter) => Ok(vec![format!("--{}={}", long, values.join(&delimiter.to_string()))]),
+            None => Ok(vec![format!("--{}={}", long, single()?)]),
+        },
+    }
+}
+
+/// Checks that the arguments written for one option are valid on their own, so that a bad value
+/// is blamed on the file that set it.
+fn check_tokens(command: &clap::Command, scope: Option<&str>, tokens: &[String]) -> std::result::Result<(), String> {
+    let program = std::iter::once("ecs_ai_compliance").chain(scope);
+    match command.clone().try_get_matches_from(program.chain(tokens.iter().map(String::as_str))) {
+        Ok(_) => Ok(()),
+        // Options requiring others, and the subcommand's positional arguments, are checked
+        // once everything is put together.
+        Err(e) if e.kind() == ErrorKind::MissingRequiredArgument => Ok(()),
+        Err(e) => {
+            let rendered = e.render().to_string();
+            let message = rendered.lines().next().unwrap_or_default();
+            Err(message.trim_start_matches("error: ").to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Command;
+    use crate::frontend::FrontendKind;
+    use crate::ui::keys::{KeyBinding, KeyPreset};
+
+    /// Marks the child process running a test with its own environment.
+    const CHILD_VAR: &str = "CONFIG_TEST_CHILD";
+
+    /// Runs a test again in a child process with `vars` set, since every test of the binary
+    /// shares the process environment that clap reads. Returns true in the child, which runs
+    /// the test's assertions.
+    fn in_child_process(test: &str, vars: &[(&str, &str)]) -> bool {
+        if env::var_os(CHILD_VAR).is_some() {
+            return true;
+        }
+        let output = std::process::Command::new(env::current_exe().unwrap())
+            .args(["--exact", &format!("config::tests::{}", test), "--nocapture"])
+            .env(CHILD_VAR, "1")
+            .envs(vars.iter().copied())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success() && stdout.contains("1 passed"), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+        false
+    }
+
+    #[test]
+    fn command_line_beats_environment_beats_config_file_beats_defaults() {
+        if !in_child_process("command_line_beats_environment_beats_config_file_beats_defaults", &[("ECS_AI_COMPLIANCE_INTERVAL", "3")]) {
+            return;
+        }
+        let dir = env::temp_dir().join(format!("config-layers-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            "rate = 500\ninterval = 2\nverbose = 2\nfrontend = \"json\"\npin-cores = [0, 1]\n\n[snapshot]\ninterval = 30\n\n[statsd]\ntags = false\n\n[replay]\nspeed = 4.0\nstatsd-prefix = \"ci\"\n\n[keybindings]\npreset = \"vim\"\nquit = [\"q\", \"<C-c>\"]\ninject = []\n",
+        )
+        .unwrap();
+        let config = path.to_str().unwrap();
+        let argv = |args: &[&str]| -> Vec<OsString> { ["ecs_ai_compliance", "--config", config].iter().chain(args).map(OsString::from).collect() };
+
+        let (args, layers) = parse_layered(argv(&["--rate", "900", "replay", "events.ndjson"])).unwrap();
+        assert_eq!(args.rate, 900);
+        assert_eq!(args.interval, 3);
+        assert_eq!(args.verbose, 2);
+        assert_eq!(args.frontend, FrontendKind::Json);
+        assert_eq!(args.pin_cores, Some(vec![0, 1]));
+        assert_eq!(args.keybindings, KeyPreset::Vim);
+        let binds = ["inject=", "quit=q", "quit=<C-c>"].map(|spec| KeyBinding::parse(spec).unwrap());
+        assert_eq!(args.bind, binds);
+        let Some(Command::Replay(replay)) = &args.command else {
+            panic!("expected the replay subcommand");
+        };
+        assert_eq!(replay.speed, 4.0);
+        assert_eq!(replay.outputs.exports.statsd_prefix, "ci");
+        assert_eq!(layers.config_file.as_deref(), Some(path.as_path()));
+        let source = |name: &str| layers.settings.iter().find(|(option, _)| option == name).map(|(_, source)| *source);
+        assert_eq!(source("--rate"), Some(SettingSource::CommandLine));
+        assert_eq!(source("--interval"), Some(SettingSource::Environment));
+        assert_eq!(source("--frontend"), Some(SettingSource::ConfigFile));
+        assert_eq!(source("replay --speed"), Some(SettingSource::ConfigFile));
+        assert_eq!(source("replay --statsd-prefix"), Some(SettingSource::ConfigFile));
+        assert_eq!(source("--statsd-tags"), None);
+        assert_eq!(source("--bind"), Some(SettingSource::ConfigFile));
+
+        // Without a subcommand the monitor runs live, taking `run` options from the top level.
+        let (args, layers) = parse_layered(argv(&["--audit-buffer", "8"])).unwrap();
+        let Some(Command::Run(run)) = &args.command else {
+            panic!("expected the run subcommand");
+        };
+        assert_eq!(run.snapshot_interval, 30);
+        assert_eq!(run.audit_buffer, 8);
+        assert!(!run.outputs.exports.statsd_tags);
+        assert_eq!(run.outputs.exports.statsd_prefix, "ecs_ai_compliance");
+        assert!(layers.settings.contains(&("--snapshot-interval".to_string(), SettingSource::ConfigFile)));
+        let error = parse_layered(argv(&["repaly"])).unwrap_err().to_string();
+        assert!(error.contains("unrecognized subcommand 'repaly'"), "{}", error);
+        // Options of the live monitor's outputs are not accepted by subcommands without them.
+        let error = parse_layered(argv(&["import", "logs", "--chaos", "0.1"])).unwrap_err().to_string();
+        assert!(error.contains("unexpected argument '--chaos'"), "{}", error);
+        let error = parse_layered(argv(&["analyze", "events.ndjson", "--csv-export", "csv"])).unwrap_err().to_string();
+        assert!(error.contains("unexpected argument '--csv-export'"), "{}", error);
+
+        let help = command().render_help().to_string();
+        assert!(help.contains("[env: ECS_AI_COMPLIANCE_RATE]"), "{}", help);
+        let replay_help = command().find_subcommand_mut("replay").unwrap().render_help().to_string();
+        assert!(replay_help.contains("[env: ECS_AI_COMPLIANCE_REPLAY_SPEED]"), "{}", replay_help);
+
+        fs::write(&path, "rate = \"fast\"\n").unwrap();
+        let error = parse_layered(argv(&[])).unwrap_err().to_string();
+        assert!(error.contains("config.toml: rate: invalid value 'fast'"), "{}", error);
+        fs::write(&path, "[snapshot]\nfiel = \"metrics.json\"\n").unwrap();
+        let error = parse_layered(argv(&[])).unwrap_err().to_string();
+        assert!(error.contains("unknown option `snapshot-fiel`"), "{}", error);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_environment_variables_are_named() {
+        if !in_child_process("invalid_environment_variables_are_named", &[("ECS_AI_COMPLIANCE_RATE", "fast")]) {
+            return;
+        }
+        let argv = |args: &[&str]| -> Vec<OsString> { ["ecs_ai_compliance"].iter().chain(args).map(OsString::from).collect() };
+        let error = parse_layered(argv(&[])).unwrap_err().to_string();
+        assert!(error.contains("ECS_AI_COMPLIANCE_RATE: invalid value 'fast' for '--rate <RATE>'"), "{}", error);
+    }
+}
@@ -1,29 +1,79 @@
 /// Service names for AI events.
 pub const SERVICE_NAMES: [&str; 5] = ["ChatGPT", "Claude", "Gemini", "Copilot", "Stable Diffusion"];
 
+/// Vendor names for AI events.
+pub const VENDOR_NAMES: [&str; 5] = ["OpenAI", "Anthropic", "Google", "Microsoft", "Stability AI"];
+
+/// Model versions of the AI services, `MODELS_PER_SERVICE` per service in `SERVICE_NAMES` order.
+pub const MODEL_NAMES: [&str; 10] = [
+    "GPT-4o",
+    "o3",
+    "Claude Sonnet",
+    "Claude Opus",
+    "Gemini Flash",
+    "Gemini Pro",
+    "Copilot Chat",
+    "Copilot Enterprise",
+    "SD 3.5",
+    "SDXL",
+];
+
+/// Number of models each service offers in `MODEL_NAMES`.
+pub const MODELS_PER_SERVICE: usize = 2;
+
+/// Ways a model is deployed.
+pub const DEPLOYMENT_NAMES: [&str; 2] = ["API", "On-prem"];
+
+/// Index into `DEPLOYMENT_NAMES` of each model in `MODEL_NAMES`.
+pub const MODEL_DEPLOYMENTS: [u8; 10] = [0, 0, 0, 0, 0, 0, 0, 1, 0, 1];
+
 /// Department names used in usage events.
-pub const DEPARTMENT_NAMES: [&str; 5] = ["Engineering", "Marketing", "Finance", "HR", "Legal"];
-
-/// Bit flags for compliance statuses.
-pub const EU_ACT_COMPLIANT: u8 = 0b00000001;
-pub const GDPR_COMPLIANT: u8 = 0b00000010;
-pub const INTERNAL_POLICY_COMPLIANT: u8 = 0b00000100;
-
-/// Bit flags for risk factors.
-pub const RISK_EU_ACT: u16 = 0b0000000000000001;
-pub const RISK_GDPR: u16 = 0b0000000000000010;
-pub const RISK_INTERNAL: u16 = 0b0000000000000100;
-pub const RISK_SENSITIVE_DATA: u16 = 0b0000000000001000;
-pub const RISK_PUBLIC_MODEL: u16 = 0b0000000000010000;
-
-/// Mapping of risk factor flags to descriptive names.
-pub const RISK_FACTOR_NAMES: [(u16, &str); 5] = [
-    (RISK_EU_ACT, "EU AI Act non-compliance"),
-    (RISK_GDPR, "GDPR non-compliance"),
-    (RISK_INTERNAL, "Internal policy violation"),
-    (RISK_SENSITIVE_DATA, "High sensitivity data"),
-    (RISK_PUBLIC_MODEL, "Public model usage"),
+pub const DEPARTMENT_NAMES: [&str; 6] = ["Engineering", "Marketing", "Finance", "HR", "Legal", "Healthcare"];
+
+/// Index into `DEPARTMENT_NAMES` of the department handling patient data.
+pub const HEALTHCARE_DEPARTMENT: u8 = 5;
+
+/// Data sensitivity from which healthcare data counts as protected health information (PHI).
+pub const PHI_SENSITIVITY: u8 = 70;
+
+/// Purposes an AI service is used for, as declared with each prompt.
+pub const PURPOSE_NAMES: [&str; 6] = [
+    "Code generation",
+    "HR screening",
+    "Marketing copy",
+    "Legal review",
+    "Customer support",
+    "Data analysis",
 ];
 
+/// Regions an AI request can be processed in.
+pub const REGION_NAMES: [&str; 6] = ["EU", "UK", "Japan", "US", "India", "Brazil"];
+
+/// Whether each region in `REGION_NAMES` may receive EU personal data: the EU itself, or a
+/// country with an EU adequacy decision.
+pub const REGION_ADEQUATE: [bool; 6] = [true, true, true, false, false, false];
+
+/// GDPR Art. 6 lawful bases personal data can be processed on, the last being none at all.
+pub const LAWFUL_BASIS_NAMES: [&str; 4] = ["Consent", "Contract", "Legitimate interest", "None"];
+
+/// Relative weights of the lawful bases in `LAWFUL_BASIS_NAMES` of generated events.
+pub const LAWFUL_BASIS_WEIGHTS: [f64; 4] = [3.0, 4.0, 2.0, 1.0];
+
+/// Types of personally identifiable information an event's data can contain, in bit order of
+/// the `PiiTypes` component. Health and biometric data are special categories under GDPR Art. 9.
+pub const PII_TYPE_NAMES: [&str; 5] = ["Contact details", "Financial", "Health", "Biometric", "Children's data"];
+
+/// Relative weights of the PII types in `PII_TYPE_NAMES` carried by generated events with
+/// personal data.
+pub const PII_TYPE_WEIGHTS: [f64; 5] = [5.0, 3.0, 2.0, 1.0, 1.0];
+
+/// Number of distinct users the synthetic event generator draws from.
+pub const SYNTHETIC_USERS: u32 = 500;
+
+/// Version of the compliance rules implemented by the ECS systems, recorded in audit logs.
+pub const POLICY_VERSION: &str = "2025.1";
+
 /// Tab names for the dashboard UI.
-pub const TAB_NAMES: [&str; 4] = ["Overview", "Services", "Compliance", "Risk"];
+pub const TAB_NAMES: [&str; 11] = [
+    "Overview", "Services", "Compliance", "Risk", "System", "Logs", "Cases", "Fleet", "Events", "Vendors", "Alerts",
+];
@@ -0,0 +1,176 @@
+use crate::components::PiiTypes;
+use crate::constants::PII_TYPE_NAMES;
+use crate::replay::EventRecord;
+use regex::Regex;
+use serde::Deserialize;
+
+/// A rule of the content scanner: text to look for in prompts, and what a match reveals.
+#[derive(Clone, Debug)]
+pub struct ScanRule {
+    pub name: String,
+    pub pattern: Regex,
+    /// Index into `PII_TYPE_NAMES` of the PII type a match reveals, if any.
+    pub pii_type: Option<u8>,
+    /// Data sensitivity of prompts the rule matches, from 0 to 100.
+    pub sensitivity: u8,
+}
+
+/// A scan rule as written in a policy file, with either a regular expression `pattern` or a
+/// list of `keywords` matched as whole words regardless of case.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ScanRuleSpec {
+    name: String,
+    pattern: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    pii_type: Option<String>,
+    sensitivity: u8,
+}
+
+/// What the content scanner found in a prompt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScanFindings {
+    /// Highest sensitivity of the matching rules, or `None` if no rule matched.
+    pub sensitivity: Option<u8>,
+    pub pii_types: PiiTypes,
+}
+
+/// Classifies the raw prompt text of ingested events with regex and keyword rules, deriving
+/// their data sensitivity and PII types.
+#[derive(Clone, Debug)]
+pub struct ContentScanner {
+    rules: Vec<ScanRule>,
+}
+
+impl ContentScanner {
+    /// Creates the scanner with the built-in rules: email addresses, IBANs, UK National
+    /// Insurance numbers, and medical terms.
+    pub fn builtin() -> Self {
+        let rule = |name: &str, pattern: &str, pii_type: Option<PiiTypes>, sensitivity| ScanRule {
+            name: name.to_string(),
+            pattern: Regex::new(pattern).expect("built-in scan rules are valid"),
+            pii_type: pii_type.map(|pii| pii.0.trailing_zeros() as u8),
+            sensitivity,
+        };
+        ContentScanner {
+            rules: vec![
+                rule("email", r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b", Some(PiiTypes::CONTACT), 60),
+                rule("iban", r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b", Some(PiiTypes::FINANCIAL), 80),
+                rule("ni_number", r"\b[A-CEGHJ-PR-TW-Z]{2} ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b", None, 80),
+                rule("medical_terms", r"(?i)\b(?:diagnosis|prescription|patient|medical record)s?\b", Some(PiiTypes::HEALTH), 70),
+            ],
+        }
+    }
+
+    /// Builds the scanner from the rules of a policy file, which replace the built-in ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `specs` - The rules, each with a pattern or keywords, an optional PII type named as in
+    ///   `PII_TYPE_NAMES`, and a sensitivity from 0 to 100.
+    pub fn from_specs(specs: Vec<ScanRuleSpec>) -> Result<Self, String> {
+        let mut rules = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let pattern = match (spec.pattern, spec.keywords.is_empty()) {
+                (Some(pattern), true) => pattern,
+                (None, false) => {
+                    let keywords: Vec<String> = spec.keywords.iter().map(|keyword| regex::escape(keyword)).collect();
+                    format!(r"(?i)\b(?:{})\b", keywords.join("|"))
+                }
+                _ => return Err(format!("scan rule '{}' must have either a pattern or keywords", spec.name)),
+            };
+            let pattern = Regex::new(&pattern).map_err(|e| format!("invalid pattern of scan rule '{}': {}", spec.name, e))?;
+            let pii_type = match &spec.pii_type {
+                Some(name) => Some(
+                    PII_TYPE_NAMES
+                        .iter()
+                        .position(|candidate| candidate == name)
+                        .ok_or_else(|| format!("unknown PII type '{}' in scan rule '{}'", name, spec.name))? as u8,
+                ),
+                None => None,
+            };
+            if spec.sensitivity > 100 {
+                return Err(format!("sensitivity of scan rule '{}' must be at most 100, got {}", spec.name, spec.sensitivity));
+            }
+            rules.push(ScanRule { name: spec.name, pattern, pii_type, sensitivity: spec.sensitivity });
+        }
+        Ok(ContentScanner { rules })
+    }
+
+    /// Returns the rules, in the order they were configured.
+    pub fn rules(&self) -> &[ScanRule] {
+        &self.rules
+    }
+
+    /// Scans a prompt with every rule.
+    pub fn scan(&self, text: &str) -> ScanFindings {
+        let mut findings = ScanFindings::default();
+        for rule in self.rules.iter().filter(|rule| rule.pattern.is_match(text)) {
+            findings.sensitivity = findings.sensitivity.max(Some(rule.sensitivity));
+            if let Some(pii_type) = rule.pii_type {
+                findings.pii_types.0 |= 1 << pii_type;
+            }
+        }
+        findings
+    }
+
+    /// Classifies an ingested event by its prompt, if it has one. The findings raise, but never
+    /// lower, the sensitivity the record states, and add to the PII types it lists.
+    pub fn classify(&self, record: &mut EventRecord) {
+        let Some(prompt) = &record.prompt else {
+            return;
+        };
+        let findings = self.scan(prompt);
+        if let Some(sensitivity) = findings.sensitivity {
+            record.data_sensitivity = record.data_sensitivity.max(sensitivity);
+        }
+        for index in findings.pii_types.indices() {
+            if !record.pii_types.iter().any(|name| name == PII_TYPE_NAMES[index]) {
+                record.pii_types.push(PII_TYPE_NAMES[index].to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompts_are_scored_by_the_rules_they_match() {
+        let scanner = ContentScanner::builtin();
+        let findings = scanner.scan("Refund jane.doe@example.com to GB82 WEST 1234 5698 7654 32");
+        assert_eq!(findings.sensitivity, Some(80));
+        assert_eq!(findings.pii_types, PiiTypes(PiiTypes::CONTACT.0 | PiiTypes::FINANCIAL.0));
+        assert_eq!(scanner.scan("NI number AB 12 34 56 C").sensitivity, Some(80));
+        assert_eq!(scanner.scan("Summarise this Diagnosis").pii_types, PiiTypes::HEALTH);
+        assert_eq!(scanner.scan("Write a haiku about spring"), ScanFindings::default());
+
+        let mut record: EventRecord = serde_json::from_str(
+            r#"{"timestamp_ms": 0, "service": "Claude", "vendor": "Anthropic", "department": "HR",
+                "prompt": "Email bob@example.com", "pii_types": ["Contact details"]}"#,
+        )
+        .unwrap();
+        scanner.classify(&mut record);
+        assert_eq!((record.data_sensitivity, record.pii_types.as_slice()), (60, ["Contact details".to_string()].as_slice()));
+
+        let specs: Vec<ScanRuleSpec> = serde_json::from_str(
+            r#"[{"name": "children", "keywords": ["pupil", "year 6"], "pii_type": "Children's data", "sensitivity": 90}]"#,
+        )
+        .unwrap();
+        let custom = ContentScanner::from_specs(specs).unwrap();
+        assert_eq!(custom.scan("Grades of every PUPIL in Year 6").pii_types, PiiTypes::CHILDREN);
+        assert_eq!(custom.scan("jane.doe@example.com").sensitivity, None);
+
+        for (spec, error) in [
+            (r#"[{"name": "x", "sensitivity": 10}]"#, "must have either a pattern or keywords"),
+            (r#"[{"name": "x", "pattern": "(", "sensitivity": 10}]"#, "invalid pattern of scan rule 'x'"),
+            (r#"[{"name": "x", "pattern": "a", "pii_type": "Genetic", "sensitivity": 10}]"#, "unknown PII type 'Genetic'"),
+            (r#"[{"name": "x", "pattern": "a", "sensitivity": 101}]"#, "must be at most 100"),
+        ] {
+            let message = ContentScanner::from_specs(serde_json::from_str(spec).unwrap()).unwrap_err();
+            assert!(message.contains(error), "{}", message);
+        }
+    }
+}
@@ -1,13 +1,39 @@
+use crate::allowlist::Allowlist;
+use crate::audit::{reserve_event_ids, AuditRecord};
+use crate::event_sample::{EventSampler, SampledEvent};
+use crate::batching::{BatchController, BatchPlan};
+use crate::clock::SharedClock;
+use crate::compliance::{DATA_RESIDENCY, EU_AI_ACT, GDPR, INTERNAL_POLICY, PURPOSE_LIMITATION};
 use crate::components::*;
-use crate::constants::*;
-use crate::metrics::ComplianceMetrics;
+use crate::constants::{DEPARTMENT_NAMES, HEALTHCARE_DEPARTMENT, MODEL_NAMES, PHI_SENSITIVITY, REGION_ADEQUATE, SERVICE_NAMES};
+use crate::error::{report, ErrorPolicy, ErrorReport};
+use crate::event_profile::EventProfile;
+use crate::metrics::{ComplianceMetrics, MetricsShard};
+#[cfg(feature = "parquet")]
+use crate::parquet_export::ParquetEventWriter;
+use crate::policy::{HipaaRules, LivePolicy, Policy};
+use crate::profile::{time_system, EcsSystem};
+use crate::provenance::{
+    Provenance, DATA_RESIDENCY_STEP, EU_AI_ACT_BIOMETRIC_STEP, GDPR_SPECIAL_CATEGORY_STEP, HIPAA_STEP,
+    INTERNAL_POLICY_ALLOWLIST_STEP, PURPOSE_LIMITATION_STEP, RISK_ASSESSMENT_STEP, WAIVER_STEP,
+};
+use crate::replay::EventRecord;
+use crate::run_report::{record_stage, time_stage, Stage};
+use crate::shadow::ShadowPolicy;
+use crate::source::SourceSpec;
+use crate::spike::ViolationSpikes;
+use crate::traffic::TrafficPattern;
+use crate::vendors::VendorRegistry;
+use crate::waivers::{waived_checks, Waiver};
 use crossbeam_channel::Sender;
-use hecs::World;
-use rand::{rng, Rng};
+use hecs::{Entity, Query, World};
+use rayon::iter::{ParallelBridge, ParallelIterator};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
+use tracing::debug;
 
-/// Generates AI events as a vector of (AIService, Usage) tuples.
+/// Generates AI events with uniformly distributed attributes.
 ///
 /// # Arguments
 ///
@@ -16,214 +42,1217 @@ use std::sync::Arc;
 /// # Returns
 ///
 /// A vector containing AI events.
-pub fn generate_ai_events(count: usize) -> Vec<(AIService, Usage)> {
-    let mut events = Vec::with_capacity(count);
-    let mut rng = rng();
-    for _ in 0..count {
-        let ai_service = AIService {
-            name_idx: rng.random_range(0..5) as u8,
-            vendor_idx: rng.random_range(0..5) as u8,
-        };
-        let usage = Usage {
-            department_idx: rng.random_range(0..5) as u8,
-            data_sensitivity: rng.random_range(0..100),
-        };
-        events.push((ai_service, usage));
+pub fn generate_ai_events(count: usize) -> Vec<Event> {
+    EventProfile::default().generate(count, None, 0)
+}
+
+/// How the systems iterate over the entities of a single world.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Parallelism {
+    /// Visit every entity on the calling thread.
+    #[default]
+    Sequential,
+    /// Split the entities into chunks of this many and process the chunks on the rayon thread pool.
+    Chunked(u32),
+}
+
+impl Parallelism {
+    /// Returns the parallelism selected by `--chunk-size`, sequential if it is not given.
+    pub fn from_chunk_size(chunk_size: Option<u32>) -> Self {
+        chunk_size.map_or(Parallelism::Sequential, Parallelism::Chunked)
     }
-    events
 }
 
-/// Applies the EU AI Act compliance rule to all relevant entities.
+/// Settings shared by every thread that evaluates batches.
+#[derive(Clone)]
+pub struct PipelineConfig {
+    /// How the systems iterate over each batch.
+    pub parallelism: Parallelism,
+    /// Time source for event timestamps and pacing.
+    pub clock: SharedClock,
+    /// Distributions the generated events are drawn from.
+    pub event_profile: Arc<EventProfile>,
+    /// How the rate and regional mix of generated events vary over time.
+    pub traffic: TrafficPattern,
+    /// Non-compliant events queued by `i` or the admin API, added to the generated batches.
+    pub spikes: Arc<ViolationSpikes>,
+    /// Where the workers take their events from.
+    pub source: SourceSpec,
+    /// Candidate policy evaluated next to the active one, if shadow mode is enabled.
+    pub shadow: Option<Arc<ShadowPolicy>>,
+}
+
+/// Runs `f` on every entity matching the query `Q`, in parallel chunks if requested.
+///
+/// # Arguments
 ///
-/// High-risk services with sensitive data have their compliant bit cleared.
+/// * `world` - A mutable reference to the ECS world.
+/// * `parallelism` - How to iterate over the entities.
+/// * `f` - The per-entity work.
+fn for_each_entity<Q>(world: &mut World, parallelism: Parallelism, f: impl Fn(Q::Item<'_>) + Send + Sync)
+where
+    Q: Query,
+    for<'a> Q::Item<'a>: Send,
+{
+    fold_entities::<Q, ()>(world, parallelism, || (), |(), item| f(item), |(), ()| ());
+}
+
+/// Folds every entity matching the query `Q` into an accumulator, in parallel chunks if requested.
+///
+/// Each chunk is folded into its own accumulator from `init`; the chunk results are then
+/// combined with `merge`.
 ///
 /// # Arguments
 ///
 /// * `world` - A mutable reference to the ECS world.
-pub fn eu_ai_act_system(world: &mut World) {
-    let high_risk_vendor_idx = 0u8; // Assume vendor at index 0 is high risk.
-    for (_id, (service, usage, status)) in world.query_mut::<(&AIService, &Usage, &mut ComplianceStatus)>() {
-        let is_high_risk = service.vendor_idx == high_risk_vendor_idx;
-        if is_high_risk && usage.data_sensitivity > 70 {
-            status.flags &= !EU_ACT_COMPLIANT;
+/// * `parallelism` - How to iterate over the entities.
+/// * `init` - Creates an empty accumulator.
+/// * `f` - Adds a single entity to an accumulator.
+/// * `merge` - Combines the accumulators of two chunks.
+fn fold_entities<Q, T>(
+    world: &mut World,
+    parallelism: Parallelism,
+    init: impl Fn() -> T + Send + Sync,
+    f: impl Fn(&mut T, Q::Item<'_>) + Send + Sync,
+    merge: impl Fn(T, T) -> T + Send + Sync,
+) -> T
+where
+    Q: Query,
+    for<'a> Q::Item<'a>: Send,
+    T: Send,
+{
+    let fold_chunk = |entities: &mut dyn Iterator<Item = (Entity, Q::Item<'_>)>| {
+        let mut acc = init();
+        entities.for_each(|(_id, item)| f(&mut acc, item));
+        acc
+    };
+    match parallelism {
+        Parallelism::Sequential => fold_chunk(&mut world.query_mut::<Q>().into_iter()),
+        Parallelism::Chunked(chunk_size) => world
+            .query_mut::<Q>()
+            .into_iter_batched(chunk_size)
+            .par_bridge()
+            .map(|mut chunk| fold_chunk(&mut chunk))
+            .reduce(&init, merge),
+    }
+}
+
+/// Classifies each entity into its EU AI Act risk tier and applies the rules of that tier.
+///
+/// Screening candidates with highly sensitive data, such as recognising their emotions, is
+/// prohibited. Screening candidates otherwise, and analysing highly sensitive data, are
+/// high-risk uses, as is any processing of biometric data; high-risk uses require a vendor with
+/// a conformity assessment. Generated images and
+/// text shown to customers or the public carry transparency obligations only, and everything
+/// else is minimal risk. Prohibited uses, and high-risk uses without a conformity assessment,
+/// have their compliant bit cleared.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world.
+/// * `parallelism` - How to iterate over the entities.
+pub fn eu_ai_act_system(world: &mut World, parallelism: Parallelism) {
+    let unassessed_vendor_idx = 0u8; // Assume vendor at index 0 has no conformity assessment.
+    let image_service_idx = 4u8;
+    let (hr_screening, marketing_copy, customer_support, data_analysis) = (1, 2, 4, 5);
+    type Checked<'a> = (
+        &'a AIService,
+        &'a Usage,
+        &'a Purpose,
+        &'a PiiTypes,
+        &'a mut AiActTier,
+        &'a mut ComplianceStatus,
+        Option<&'a mut Provenance>,
+    );
+    for_each_entity::<Checked>(world, parallelism, |(service, usage, purpose, pii, tier, status, provenance)| {
+        *tier = match purpose.known() {
+            Some(purpose) if purpose == hr_screening && usage.data_sensitivity >= 90 => AiActTier::Unacceptable,
+            Some(purpose) if purpose == hr_screening => AiActTier::High,
+            Some(purpose) if purpose == data_analysis && usage.data_sensitivity > 70 => AiActTier::High,
+            _ if pii.contains_any(PiiTypes::BIOMETRIC) => AiActTier::High,
+            Some(purpose) if purpose == marketing_copy || purpose == customer_support => AiActTier::Limited,
+            _ if service.name_idx == image_service_idx => AiActTier::Limited,
+            _ => AiActTier::Minimal,
+        };
+        let compliant = match *tier {
+            AiActTier::Unacceptable => false,
+            AiActTier::High => service.vendor_idx != unassessed_vendor_idx,
+            AiActTier::Limited | AiActTier::Minimal => true,
+        };
+        if compliant {
+            status.flags |= EU_AI_ACT.bit();
         } else {
-            status.flags |= EU_ACT_COMPLIANT;
+            status.flags &= !EU_AI_ACT.bit();
         }
-    }
+        if let Some(provenance) = provenance {
+            provenance.record(EU_AI_ACT_BIOMETRIC_STEP);
+        }
+    });
 }
 
 /// Applies GDPR compliance rules to each entity.
 ///
-/// Usage with data sensitivity below 50 is marked as GDPR compliant.
+/// Personal data, i.e. data sensitivity of 50 or more or any special-category data, may only be
+/// sent to vendors that signed a data processing agreement in the vendor registry, and only
+/// processed on a lawful basis. Special-category data, such as health or biometric data, also
+/// needs explicit consent (GDPR Art. 9). Without a configured registry no vendor has a DPA, so
+/// only usage without personal data is compliant. Events without a recorded basis are not
+/// checked for one.
 ///
 /// # Arguments
 ///
 /// * `world` - A mutable reference to the ECS world.
-pub fn gdpr_system(world: &mut World) {
-    for (_id, (usage, status)) in world.query_mut::<(&Usage, &mut ComplianceStatus)>() {
-        if usage.data_sensitivity < 50 {
-            status.flags |= GDPR_COMPLIANT;
+/// * `vendors` - The vendor registry of the policy.
+/// * `parallelism` - How to iterate over the entities.
+pub fn gdpr_system(world: &mut World, vendors: &VendorRegistry, parallelism: Parallelism) {
+    let dpa_vendors = vendors.dpa_vendors();
+    type Checked<'a> = (
+        &'a AIService,
+        &'a Usage,
+        &'a LawfulBasis,
+        &'a PiiTypes,
+        &'a mut ComplianceStatus,
+        Option<&'a mut Provenance>,
+    );
+    for_each_entity::<Checked>(world, parallelism, |(service, usage, basis, pii, status, provenance)| {
+        let consented = !pii.is_special_category() || matches!(*basis, LawfulBasis::CONSENT | LawfulBasis::UNKNOWN);
+        let lawful = dpa_vendors & (1 << service.vendor_idx) != 0 && *basis != LawfulBasis::NONE && consented;
+        if !is_personal(usage, pii) || lawful {
+            status.flags |= GDPR.bit();
         } else {
-            status.flags &= !GDPR_COMPLIANT;
+            status.flags &= !GDPR.bit();
         }
-    }
+        if let Some(provenance) = provenance {
+            provenance.record(GDPR_SPECIAL_CATEGORY_STEP);
+        }
+    });
+}
+
+/// Applies internal policy compliance rules: the allowlist of approved AI usage.
+///
+/// Departments restricted by the allowlist may only use the service, vendor, and model
+/// combinations approved for them; any other combination is shadow AI. The built-in allowlist
+/// only approves Claude and Copilot for Finance.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world.
+/// * `allowlist` - The allowlist of the policy.
+/// * `parallelism` - How to iterate over the entities.
+pub fn internal_policy_system(world: &mut World, allowlist: &Allowlist, parallelism: Parallelism) {
+    type Checked<'a> = (&'a AIService, &'a Usage, &'a mut ComplianceStatus, Option<&'a mut Provenance>);
+    for_each_entity::<Checked>(world, parallelism, |(service, usage, status, provenance)| {
+        if allowlist.permits(usage.department_idx, service) {
+            status.flags |= INTERNAL_POLICY.bit();
+        } else {
+            status.flags &= !INTERNAL_POLICY.bit();
+        }
+        if let Some(provenance) = provenance {
+            provenance.record(INTERNAL_POLICY_ALLOWLIST_STEP);
+        }
+    });
+}
+
+/// Applies the GDPR purpose-limitation rule to each entity.
+///
+/// Personal data, i.e. data sensitivity of 50 or more, may only be used for the purposes
+/// approved for the event's department. Events without a declared purpose are not checked.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world.
+/// * `parallelism` - How to iterate over the entities.
+pub fn purpose_limitation_system(world: &mut World, parallelism: Parallelism) {
+    // Bit masks over `PURPOSE_NAMES` of the purposes each department may use personal data for:
+    // Engineering code generation and data analysis, Marketing marketing copy, customer support
+    // and data analysis, Finance data analysis, HR screening, Legal legal review, and Healthcare
+    // customer support and data analysis.
+    let approved_purposes: [u8; 6] = [0b10_0001, 0b11_0100, 0b10_0000, 0b00_0010, 0b00_1000, 0b11_0000];
+    type Checked<'a> = (&'a Usage, &'a Purpose, &'a mut ComplianceStatus, Option<&'a mut Provenance>);
+    for_each_entity::<Checked>(world, parallelism, |(usage, purpose, status, provenance)| {
+        let approved = |purpose: usize| approved_purposes[usage.department_idx as usize] & (1 << purpose) != 0;
+        match purpose.known() {
+            Some(purpose) if usage.data_sensitivity >= 50 && !approved(purpose) => {
+                status.flags &= !PURPOSE_LIMITATION.bit();
+            }
+            _ => status.flags |= PURPOSE_LIMITATION.bit(),
+        }
+        if let Some(provenance) = provenance {
+            provenance.record(PURPOSE_LIMITATION_STEP);
+        }
+    });
 }
 
-/// Applies internal policy compliance rules, especially for finance.
+/// Applies the data residency rule to each entity.
 ///
-/// For finance, only specific services are approved.
+/// The monitored organisation is established in the EU, so all personal data it handles, i.e.
+/// data sensitivity of 50 or more, is EU personal data and may only be processed in the EU or a
+/// country with an adequacy decision. Events without a known region are not checked.
 ///
 /// # Arguments
 ///
 /// * `world` - A mutable reference to the ECS world.
-pub fn internal_policy_system(world: &mut World) {
-    let finance_idx = 2u8;
-    let approved_services: [u8; 2] = [1, 3];
-    for (_id, (service, usage, status)) in world.query_mut::<(&AIService, &Usage, &mut ComplianceStatus)>() {
-        if usage.department_idx == finance_idx {
-            if approved_services.contains(&service.name_idx) {
-                status.flags |= INTERNAL_POLICY_COMPLIANT;
-            } else {
-                status.flags &= !INTERNAL_POLICY_COMPLIANT;
+/// * `parallelism` - How to iterate over the entities.
+pub fn data_residency_system(world: &mut World, parallelism: Parallelism) {
+    type Checked<'a> = (&'a Usage, &'a Region, &'a mut ComplianceStatus, Option<&'a mut Provenance>);
+    for_each_entity::<Checked>(world, parallelism, |(usage, region, status, provenance)| {
+        match region.known() {
+            Some(region) if usage.data_sensitivity >= 50 && !REGION_ADEQUATE[region] => {
+                status.flags &= !DATA_RESIDENCY.bit();
             }
+            _ => status.flags |= DATA_RESIDENCY.bit(),
+        }
+        if let Some(provenance) = provenance {
+            provenance.record(DATA_RESIDENCY_STEP);
+        }
+    });
+}
+
+/// Applies the optional HIPAA rule pack to each entity.
+///
+/// Protected health information, i.e. healthcare data with a sensitivity of at least
+/// `PHI_SENSITIVITY`, may only be sent to vendors that signed a business associate agreement.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world.
+/// * `rules` - The HIPAA settings of the active policy.
+/// * `parallelism` - How to iterate over the entities.
+pub fn hipaa_system(world: &mut World, rules: &HipaaRules, parallelism: Parallelism) {
+    type Checked<'a> = (&'a AIService, &'a Usage, &'a mut ComplianceStatus, Option<&'a mut Provenance>);
+    for_each_entity::<Checked>(world, parallelism, |(service, usage, status, provenance)| {
+        let has_baa = rules.baa_vendors & (1 << service.vendor_idx) != 0;
+        if is_phi(usage) && !has_baa {
+            status.flags &= !rules.flag.bit();
         } else {
-            status.flags |= INTERNAL_POLICY_COMPLIANT;
+            status.flags |= rules.flag.bit();
+        }
+        if let Some(provenance) = provenance {
+            provenance.record(HIPAA_STEP);
+        }
+    });
+}
+
+/// Exempts violations covered by a waiver for the event's department and service.
+///
+/// Runs after every compliance check. Violations of waived checks have their compliant bit set
+/// again and are recorded in the status's `waived` flags instead, so they are counted
+/// separately. Waivers that had expired by the event's timestamp are ignored.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world.
+/// * `waivers` - The waivers of the active policy.
+/// * `parallelism` - How to iterate over the entities.
+pub fn waiver_system(world: &mut World, waivers: &[Waiver], parallelism: Parallelism) {
+    type Checked<'a> = (&'a AIService, &'a Usage, &'a Timestamp, &'a mut ComplianceStatus, Option<&'a mut Provenance>);
+    for_each_entity::<Checked>(world, parallelism, |(service, usage, timestamp, status, provenance)| {
+        let waived = !status.flags & waived_checks(waivers, usage.department_idx, service.name_idx, timestamp.0);
+        status.flags |= waived;
+        status.waived = waived;
+        if let Some(provenance) = provenance {
+            provenance.record(WAIVER_STEP);
         }
+    });
+}
+
+/// Returns true if the event carries personal data: data sensitivity of 50 or more, or any
+/// special-category data.
+fn is_personal(usage: &Usage, pii: &PiiTypes) -> bool {
+    usage.data_sensitivity >= 50 || pii.is_special_category()
+}
+
+/// Returns true if the event carries protected health information.
+fn is_phi(usage: &Usage) -> bool {
+    usage.department_idx == HEALTHCARE_DEPARTMENT && usage.data_sensitivity >= PHI_SENSITIVITY
+}
+
+/// Assesses risk based on compliance and usage data and aggregates the batch metrics.
+///
+/// Every registered risk factor whose condition holds sets its flag and adds its weight. The
+/// result is written into each entity's `RiskAssessment` component, which must already be
+/// present. Since this is the last system to visit every entity, the metrics are accumulated in
+/// the same pass instead of rescanning the world afterwards. Each event is counted in its
+/// department's, its service's, and its model's metrics, and against its user in the
+/// department's metrics; the batch totals are summed from the departments at the end.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world.
+/// * `policy` - The active policy, providing the risk factors and compliance flags.
+/// * `parallelism` - How to iterate over the entities.
+///
+/// # Returns
+///
+/// A `ComplianceMetrics` structure with aggregated values.
+pub fn risk_assessment_system(world: &mut World, policy: &Policy, parallelism: Parallelism) -> ComplianceMetrics {
+    let all_compliant = policy.compliance.all_compliant();
+    let empty_counters = || ComplianceMetrics {
+        violation_counts: vec![0; policy.compliance.len()],
+        high_risk_violations: vec![0; policy.compliance.len()],
+        waived_counts: vec![0; policy.compliance.len()],
+        risk_factor_counts: vec![0; policy.risk_factors.len()],
+        ..ComplianceMetrics::default()
+    };
+    let empty_metrics = || ComplianceMetrics {
+        departments: DEPARTMENT_NAMES.iter().map(|_| empty_counters()).collect(),
+        services: SERVICE_NAMES.iter().map(|_| empty_counters()).collect(),
+        models: MODEL_NAMES.iter().map(|_| empty_counters()).collect(),
+        ..empty_counters()
+    };
+    let merge = |mut metrics: ComplianceMetrics, other: ComplianceMetrics| {
+        metrics.merge(&other);
+        metrics
+    };
+    type Assessed<'a> = (
+        &'a AIService,
+        &'a Usage,
+        &'a UserId,
+        &'a Purpose,
+        &'a Region,
+        &'a LawfulBasis,
+        &'a PiiTypes,
+        &'a AiActTier,
+        &'a ComplianceStatus,
+        &'a mut RiskAssessment,
+        Option<&'a mut Provenance>,
+    );
+    let mut metrics = fold_entities::<Assessed, _>(
+        world,
+        parallelism,
+        empty_metrics,
+        |metrics, (service, usage, user, purpose, region, basis, pii, tier, status, risk, provenance)| {
+            let mut factor_flags = 0u64;
+            let mut score = 0u8;
+            for (factor_id, factor) in policy.risk_factors.iter() {
+                if factor.applies(service, usage, status) {
+                    factor_flags |= factor_id.bit();
+                    score = score.saturating_add(factor.weight);
+                }
+            }
+            *risk = RiskAssessment {
+                score: score.min(100),
+                factor_flags,
+            };
+            if let Some(provenance) = provenance {
+                provenance.record(RISK_ASSESSMENT_STEP);
+            }
+            let violated = !status.flags & all_compliant;
+            let event = (service, usage, *purpose, *region, *basis, *pii, *tier);
+            let department = &mut metrics.departments[usage.department_idx as usize];
+            tally_event(department, event, violated, status.waived, risk);
+            if let Some(user) = user.known() {
+                tally_user(department, user, violated, risk);
+            }
+            tally_event(&mut metrics.services[service.name_idx as usize], event, violated, status.waived, risk);
+            tally_event(&mut metrics.models[service.model_idx as usize], event, violated, status.waived, risk);
+        },
+        merge,
+    );
+    metrics.add_department_totals();
+    if metrics.data_sensitivity_samples > 0 {
+        metrics.avg_data_sensitivity = metrics.total_data_sensitivity as f64 / metrics.data_sensitivity_samples as f64;
     }
+    metrics
 }
 
-/// Assesses risk based on compliance and usage data, then attaches a RiskAssessment component.
+/// Adds a single evaluated event to the batch metrics.
+///
+/// # Arguments
+///
+/// * `metrics` - The batch metrics to update.
+/// * `event` - The event's AI service, usage data, purpose, processing region, lawful basis, PII
+///   types, and AI Act tier.
+/// * `violated` - Bit flags of the compliance rules the event violates.
+/// * `waived` - Bit flags of the violations exempted by a waiver.
+/// * `risk` - The event's risk assessment.
+fn tally_event(
+    metrics: &mut ComplianceMetrics,
+    (service, usage, purpose, region, basis, pii, tier): (&AIService, &Usage, Purpose, Region, LawfulBasis, PiiTypes, AiActTier),
+    mut violated: u64,
+    mut waived: u64,
+    risk: &RiskAssessment,
+) {
+    metrics.total_events += 1;
+    metrics.service_counts[service.name_idx as usize] += 1;
+    metrics.vendor_counts[service.vendor_idx as usize] += 1;
+    metrics.department_counts[usage.department_idx as usize] += 1;
+    metrics.ai_act_tier_counts[tier as usize] += 1;
+    if is_phi(usage) {
+        metrics.phi_events += 1;
+    }
+    if let Some(purpose) = purpose.known() {
+        metrics.purpose_counts[purpose] += 1;
+        if violated & PURPOSE_LIMITATION.bit() != 0 {
+            metrics.purpose_violations[purpose] += 1;
+        }
+    }
+    if let Some(region) = region.known() {
+        metrics.region_counts[region] += 1;
+        if violated & DATA_RESIDENCY.bit() != 0 {
+            metrics.region_violations[region] += 1;
+        }
+    }
+    if let Some(basis) = basis.known() {
+        metrics.lawful_basis_counts[basis] += 1;
+        if is_personal(usage, &pii) {
+            metrics.lawful_basis_personal[basis] += 1;
+        }
+    }
+    for pii_type in pii.indices() {
+        metrics.pii_type_counts[pii_type] += 1;
+    }
+    metrics.total_data_sensitivity += usage.data_sensitivity as u64;
+    metrics.data_sensitivity_samples += 1;
+    let high_risk = risk.score > 70;
+    // Waived shadow AI is still shadow AI: the waiver only clears the violation.
+    if (violated | waived) & INTERNAL_POLICY.bit() != 0 {
+        metrics.shadow_ai_events += 1;
+        metrics.shadow_ai_department_counts[usage.department_idx as usize] += 1;
+        metrics.shadow_ai_sensitivity += usage.data_sensitivity as u64;
+    }
+    if violated != 0 {
+        metrics.violating_events += 1;
+        metrics.vendor_violations[service.vendor_idx as usize] += 1;
+    }
+    while violated != 0 {
+        let flag = violated.trailing_zeros() as usize;
+        metrics.violation_counts[flag] += 1;
+        if high_risk {
+            metrics.high_risk_violations[flag] += 1;
+        }
+        violated &= violated - 1;
+    }
+    if waived != 0 {
+        metrics.waived_events += 1;
+    }
+    while waived != 0 {
+        metrics.waived_counts[waived.trailing_zeros() as usize] += 1;
+        waived &= waived - 1;
+    }
+    let mut flags = risk.factor_flags;
+    while flags != 0 {
+        metrics.risk_factor_counts[flags.trailing_zeros() as usize] += 1;
+        flags &= flags - 1;
+    }
+    if high_risk {
+        metrics.high_risk_count += 1;
+    } else if risk.score > 30 {
+        metrics.medium_risk_count += 1;
+    } else {
+        metrics.low_risk_count += 1;
+    }
+}
+
+/// Adds a single evaluated event to the counts of its user.
+///
+/// # Arguments
+///
+/// * `metrics` - The batch metrics to update.
+/// * `user` - Id of the event's user.
+/// * `violated` - Bit flags of the compliance rules the event violates.
+/// * `risk` - The event's risk assessment.
+fn tally_user(metrics: &mut ComplianceMetrics, user: u32, violated: u64, risk: &RiskAssessment) {
+    let counts = metrics.users.entry(user).or_default();
+    counts.events += 1;
+    counts.violations += violated.count_ones() as u64;
+    counts.total_risk_score += risk.score as u64;
+    if risk.score > 70 {
+        counts.high_risk_events += 1;
+    }
+}
+
+/// Runs the full compliance pipeline over a batch of events and returns the batch metrics.
+///
+/// When `audit` is provided, each event is tagged with an [`EventId`] and an audit record is
+/// appended for it, timestamped with `timestamp_ms`. The batch's entities stay in the world afterwards so the next batch can
+/// reuse them.
+///
+/// # Arguments
+///
+/// * `world` - A mutable reference to the ECS world, holding only entities from this function.
+/// * `events` - The events to evaluate.
+/// * `policy` - The active policy.
+/// * `parallelism` - How the systems iterate over the batch.
+/// * `audit` - Optional buffer receiving one audit record per evaluated event.
+pub fn process_batch(
+    world: &mut World,
+    events: &[Event],
+    policy: &Policy,
+    parallelism: Parallelism,
+    audit: Option<&mut Vec<AuditRecord>>,
+) -> ComplianceMetrics {
+    let compliance = ComplianceStatus {
+        flags: policy.compliance.all_compliant(),
+        waived: 0,
+    };
+    let first_id = audit.is_some().then(|| reserve_event_ids(events.len()));
+    load_batch(world, events, compliance, first_id);
+    time_system(EcsSystem::EuAiAct, || eu_ai_act_system(world, parallelism));
+    time_system(EcsSystem::Gdpr, || gdpr_system(world, &policy.vendors, parallelism));
+    time_system(EcsSystem::InternalPolicy, || internal_policy_system(world, &policy.allowlist, parallelism));
+    time_system(EcsSystem::PurposeLimitation, || purpose_limitation_system(world, parallelism));
+    time_system(EcsSystem::DataResidency, || data_residency_system(world, parallelism));
+    if let Some(hipaa) = &policy.hipaa {
+        time_system(EcsSystem::Hipaa, || hipaa_system(world, hipaa, parallelism));
+    }
+    if !policy.waivers.is_empty() {
+        time_system(EcsSystem::Waivers, || waiver_system(world, &policy.waivers, parallelism));
+    }
+    let metrics = time_system(EcsSystem::RiskAssessment, || risk_assessment_system(world, policy, parallelism));
+    if let Some(records) = audit {
+        time_system(EcsSystem::AuditRecords, || collect_audit_records(world, policy, records));
+    }
+    metrics
+}
+
+/// Loads a batch of events into the world, ready for the compliance systems.
+///
+/// If the world holds a previous batch of the same size and shape, its entities are overwritten
+/// in place, so steady-state batches allocate and free no entities at all. Otherwise the world
+/// is cleared, keeping its archetype storage, and the batch is spawned with `spawn_batch`,
+/// which reserves room for every entity up front. Entities carry every component the systems
+/// write, so no system has to move them between archetypes.
 ///
 /// # Arguments
 ///
 /// * `world` - A mutable reference to the ECS world.
-pub fn risk_assessment_system(world: &mut World) {
-    let openai_idx = 0u8;
-    let mut insertions = Vec::new();
-    for (id, (service, usage, status)) in world.query_mut::<(&AIService, &Usage, &ComplianceStatus)>() {
-        let mut factor_flags = 0u16;
-        let mut score = 0u8;
-        if status.flags & EU_ACT_COMPLIANT == 0 {
-            factor_flags |= RISK_EU_ACT;
-            score += 40;
-        }
-        if status.flags & GDPR_COMPLIANT == 0 {
-            factor_flags |= RISK_GDPR;
-            score += 30;
-        }
-        if status.flags & INTERNAL_POLICY_COMPLIANT == 0 {
-            factor_flags |= RISK_INTERNAL;
-            score += 20;
-        }
-        if usage.data_sensitivity > 80 {
-            factor_flags |= RISK_SENSITIVE_DATA;
-            score += 10;
-        }
-        if service.vendor_idx == openai_idx {
-            factor_flags |= RISK_PUBLIC_MODEL;
-            score += 5;
-        }
-        score = score.min(100);
-        let risk = RiskAssessment {
-            score,
-            factor_flags,
-        };
-        insertions.push((id, risk));
+/// * `events` - The events to load.
+/// * `compliance` - Initial compliance status, with every flag set.
+/// * `first_id` - Event id of the first event, if the events should be tagged with ids.
+fn load_batch(
+    world: &mut World,
+    events: &[Event],
+    compliance: ComplianceStatus,
+    first_id: Option<u64>,
+) {
+    let tagged = world.query_mut::<&EventId>().into_iter().len();
+    let expected_tagged = if first_id.is_some() { events.len() } else { 0 };
+    if world.len() as usize == events.len() && tagged == expected_tagged {
+        type Loaded<'a> = (
+            &'a mut AIService,
+            &'a mut Usage,
+            &'a mut UserId,
+            &'a mut Purpose,
+            &'a mut Region,
+            &'a mut LawfulBasis,
+            &'a mut PiiTypes,
+            &'a mut Timestamp,
+            &'a mut ComplianceStatus,
+            Option<(&'a mut EventId, &'a mut Provenance)>,
+        );
+        let query = world.query_mut::<Loaded>();
+        for (i, ((_id, (service, usage, user, purpose, region, basis, pii, timestamp, status, tags)), event)) in query.into_iter().zip(events).enumerate() {
+            (*service, *usage, *user, *purpose, *region, *basis, *pii, *timestamp) = *event;
+            *status = compliance;
+            if let (Some((event_id, provenance)), Some(first_id)) = (tags, first_id) {
+                *event_id = EventId(first_id + i as u64);
+                *provenance = Provenance::default();
+            }
+        }
+        return;
     }
-    for (id, risk) in insertions {
-        let _ = world.insert_one(id, risk);
+    world.clear();
+    let (risk, tier) = (RiskAssessment::default(), AiActTier::default());
+    match first_id {
+        Some(first_id) => {
+            let tagged_events = events.iter().enumerate();
+            world.spawn_batch(tagged_events.map(|(i, &(service, usage, user, purpose, region, basis, pii, timestamp))| {
+                let id = EventId(first_id + i as u64);
+                (service, usage, user, purpose, region, basis, pii, timestamp, tier, compliance, risk, id, Provenance::default())
+            }));
+        }
+        None => {
+            world.spawn_batch(events.iter().map(|&(service, usage, user, purpose, region, basis, pii, timestamp)| {
+                (service, usage, user, purpose, region, basis, pii, timestamp, tier, compliance, risk)
+            }));
+        }
     }
 }
 
-/// Aggregates compliance metrics from all entities in the world.
+/// Appends an audit record for every evaluated entity that carries an event id, stamped with
+/// the event's timestamp.
 ///
 /// # Arguments
 ///
 /// * `world` - A reference to the ECS world.
+/// * `policy` - The active policy, used to version and decode the verdicts.
+/// * `records` - Buffer receiving the audit records.
+pub fn collect_audit_records(world: &World, policy: &Policy, records: &mut Vec<AuditRecord>) {
+    type Audited<'a> = (
+        &'a EventId,
+        &'a Provenance,
+        &'a AIService,
+        &'a Usage,
+        &'a UserId,
+        &'a Purpose,
+        &'a Region,
+        &'a LawfulBasis,
+        &'a PiiTypes,
+        &'a Timestamp,
+        &'a AiActTier,
+        &'a ComplianceStatus,
+        &'a RiskAssessment,
+    );
+    let mut query = world.query::<Audited>();
+    for (_id, (event_id, provenance, service, usage, user, purpose, region, basis, pii, timestamp, tier, status, risk)) in &mut query {
+        let record = AuditRecord::new(*event_id, policy, timestamp.0, service, usage, status, risk);
+        let record = record.with_provenance(*provenance).with_user(*user).with_ai_act_tier(*tier);
+        let record = record.with_purpose(*purpose).with_region(*region);
+        records.push(record.with_lawful_basis(*basis).with_pii_types(*pii));
+    }
+}
+
+/// Decodes the evaluated entities at the batch positions chosen by an [`EventSampler`].
 ///
-/// # Returns
+/// Entities are visited in the order they were loaded, so an entity's position in the query is
+/// its position in the batch.
 ///
-/// A `ComplianceMetrics` structure with aggregated values.
-pub fn collect_metrics(world: &World) -> ComplianceMetrics {
-    let mut metrics = ComplianceMetrics::default();
-    for (_id, (service, usage, status, risk_opt)) in &mut world.query::<(&AIService, &Usage, &ComplianceStatus, Option<&RiskAssessment>)>() {
-        metrics.total_events += 1;
-        metrics.service_counts[service.name_idx as usize] += 1;
-        metrics.vendor_counts[service.vendor_idx as usize] += 1;
-        metrics.department_counts[usage.department_idx as usize] += 1;
-        metrics.total_data_sensitivity += usage.data_sensitivity as u64;
-        metrics.data_sensitivity_samples += 1;
-        if status.flags & EU_ACT_COMPLIANT == 0 {
-            metrics.eu_act_violations += 1;
-        }
-        if status.flags & GDPR_COMPLIANT == 0 {
-            metrics.gdpr_violations += 1;
-        }
-        if status.flags & INTERNAL_POLICY_COMPLIANT == 0 {
-            metrics.internal_violations += 1;
-        }
-        if let Some(risk) = risk_opt {
-            if risk.factor_flags & RISK_EU_ACT != 0 { metrics.risk_factor_counts[0] += 1; }
-            if risk.factor_flags & RISK_GDPR != 0 { metrics.risk_factor_counts[1] += 1; }
-            if risk.factor_flags & RISK_INTERNAL != 0 { metrics.risk_factor_counts[2] += 1; }
-            if risk.factor_flags & RISK_SENSITIVE_DATA != 0 { metrics.risk_factor_counts[3] += 1; }
-            if risk.factor_flags & RISK_PUBLIC_MODEL != 0 { metrics.risk_factor_counts[4] += 1; }
-            if risk.score > 70 {
-                metrics.high_risk_count += 1;
-            } else if risk.score > 30 {
-                metrics.medium_risk_count += 1;
-            } else {
-                metrics.low_risk_count += 1;
+/// # Arguments
+///
+/// * `world` - A reference to the ECS world, holding only the evaluated batch.
+/// * `policy` - The active policy, used to decode the verdicts.
+/// * `chosen` - Batch positions to decode, in ascending order, each with its reservoir slot.
+/// * `worker` - Index of the worker that evaluated the batch.
+pub fn collect_sampled_events(
+    world: &World,
+    policy: &Policy,
+    chosen: &[(usize, usize)],
+    worker: usize,
+) -> Vec<SampledEvent> {
+    type Sampled<'a> = (
+        Option<&'a EventId>,
+        &'a AIService,
+        &'a Usage,
+        &'a UserId,
+        &'a Purpose,
+        &'a Region,
+        &'a LawfulBasis,
+        &'a PiiTypes,
+        &'a Timestamp,
+        &'a AiActTier,
+        &'a ComplianceStatus,
+        &'a RiskAssessment,
+    );
+    let mut events = Vec::with_capacity(chosen.len());
+    let mut wanted = chosen.iter().map(|&(index, _)| index).peekable();
+    let mut query = world.query::<Sampled>();
+    for (index, (_id, (event_id, service, usage, user, purpose, region, basis, pii, timestamp, tier, status, risk))) in query.iter().enumerate() {
+        if wanted.peek() != Some(&index) {
+            continue;
+        }
+        wanted.next();
+        let record = AuditRecord::new(event_id.copied().unwrap_or(EventId(0)), policy, timestamp.0, service, usage, status, risk);
+        let record = record.with_user(*user).with_purpose(*purpose).with_region(*region).with_ai_act_tier(*tier);
+        let record = record.with_lawful_basis(*basis).with_pii_types(*pii);
+        events.push(SampledEvent::new(record, event_id.map(|id| id.0), worker));
+        if wanted.peek().is_none() {
+            break;
+        }
+    }
+    events
+}
+
+/// Optional per-worker outputs for generated and evaluated events.
+#[derive(Default)]
+pub struct WorkerSinks {
+    /// Channel for recording generated events to an event log.
+    pub record: Option<Sender<Vec<EventRecord>>>,
+    /// Bounded channel for per-event audit records.
+    pub audit: Option<Sender<Vec<AuditRecord>>>,
+    /// This worker's Parquet writer for evaluated events.
+    #[cfg(feature = "parquet")]
+    pub parquet: Option<ParquetEventWriter>,
+    /// Bounded channel for the records of events with violations, forwarded to a SIEM.
+    #[cfg(feature = "siem")]
+    pub siem: Option<Sender<Vec<AuditRecord>>>,
+    /// Reservoir of evaluated events forwarded to the aggregator for the event inspector.
+    pub samples: Option<EventSampler>,
+}
+
+impl WorkerSinks {
+    /// Returns true if any sink needs per-event records of the evaluated events.
+    fn wants_evaluated_events(&self) -> bool {
+        #[cfg(feature = "parquet")]
+        if self.parquet.is_some() {
+            return true;
+        }
+        #[cfg(feature = "siem")]
+        if self.siem.is_some() {
+            return true;
+        }
+        self.audit.is_some()
+    }
+
+    /// Hands a batch of evaluated events to every sink that is still active.
+    ///
+    /// Sinks that fail are dropped; the Parquet writer reports its own failure first.
+    fn write_evaluated_events(&mut self, records: Vec<AuditRecord>, errors: &Sender<ErrorReport>) {
+        #[cfg(feature = "parquet")]
+        if let Some(writer) = &mut self.parquet
+            && let Err(e) = writer.write(&records)
+        {
+            report(errors, e, writer.failure_action());
+            self.parquet = None;
+        }
+        #[cfg(not(feature = "parquet"))]
+        let _ = errors;
+        #[cfg(feature = "siem")]
+        if let Some(sender) = &self.siem {
+            let violations: Vec<AuditRecord> = records.iter().filter(|record| !record.violations.is_empty()).cloned().collect();
+            if !violations.is_empty() && sender.send(violations).is_err() {
+                self.siem = None;
             }
         }
+        // Blocks while the audit writer is behind, throttling this worker to its pace.
+        if let Some(sender) = &self.audit
+            && sender.send(records).is_err()
+        {
+            self.audit = None;
+        }
     }
-    if metrics.data_sensitivity_samples > 0 {
-        metrics.avg_data_sensitivity = metrics.total_data_sensitivity as f64 / metrics.data_sensitivity_samples as f64;
+
+    /// Returns the number of batches waiting in the event recorder and audit log channels.
+    fn queue_depths(&self) -> (usize, usize) {
+        let record = self.record.as_ref().map_or(0, |sender| sender.len());
+        let audit = self.audit.as_ref().map_or(0, |sender| sender.len());
+        (record, audit)
+    }
+
+    /// Finalizes sinks that buffer output inside the worker.
+    fn finish(&mut self, errors: &Sender<ErrorReport>) {
+        #[cfg(feature = "parquet")]
+        if let Some(writer) = &mut self.parquet
+            && let Err(e) = writer.close()
+        {
+            report(errors, e, writer.failure_action());
+        }
+        #[cfg(not(feature = "parquet"))]
+        let _ = errors;
     }
-    metrics
 }
 
-/// Worker function that generates events, processes them, and sends metrics through a channel.
+/// Worker function that takes events from its source, processes them, and adds the metrics to
+/// its shard.
 ///
-/// Runs continuously until a stop signal is set.
+/// Runs until a stop signal is set or the source is exhausted, pacing itself on the clock to the
+/// controller's target rate. A reloaded policy takes effect from the next batch. A source that
+/// cannot be opened or read is reported with the shutdown action.
 ///
 /// # Arguments
 ///
-/// * `events_per_batch` - Number of events to process in each batch.
+/// * `controller` - Chooses the size of each batch from the target rate and measured latency.
+/// * `live_policy` - The active policy.
+/// * `config` - The event source, how the systems iterate over each batch, and the clock
+///   timestamping events.
 /// * `stop_signal` - Atomic flag indicating when to stop processing.
-/// * `metrics_sender` - Channel sender for reporting metrics.
+/// * `shard` - This worker's metric counters, read by the aggregator.
+/// * `sinks` - Optional outputs for generated and evaluated events.
+/// * `errors` - Channel sender for error reports to the supervisor.
 pub fn worker_thread(
-    events_per_batch: usize,
+    mut controller: BatchController,
+    live_policy: Arc<LivePolicy>,
+    config: PipelineConfig,
     stop_signal: Arc<AtomicBool>,
-    metrics_sender: Sender<ComplianceMetrics>,
+    shard: Arc<MetricsShard>,
+    mut sinks: WorkerSinks,
+    errors: Sender<ErrorReport>,
 ) {
+    let mut policy = live_policy.current();
+    let mut source = match config.source.open(&config, &policy.content_scan) {
+        Ok(source) => source,
+        Err(e) => {
+            report(&errors, e, ErrorPolicy::Shutdown);
+            return;
+        }
+    };
+    let PipelineConfig { parallelism, clock, traffic, shadow, .. } = config;
     let mut world = World::new();
-    let mut thread_metrics = ComplianceMetrics::default();
-    let mut batch_count = 0;
+    let mut shadow_world = World::new();
+    debug!("worker started");
     while !stop_signal.load(Ordering::Relaxed) {
-        let events = generate_ai_events(events_per_batch);
-        for (ai_service, usage) in events {
-            let compliance = ComplianceStatus {
-                flags: EU_ACT_COMPLIANT | GDPR_COMPLIANT | INTERNAL_POLICY_COMPLIANT,
-            };
-            world.spawn((ai_service, usage, compliance));
-        }
-        eu_ai_act_system(&mut world);
-        gdpr_system(&mut world);
-        internal_policy_system(&mut world);
-        risk_assessment_system(&mut world);
-        let batch_metrics = collect_metrics(&world);
-        thread_metrics.merge(&batch_metrics);
-        batch_count += 1;
-        if batch_count % 10 == 0 {
-            if let Err(e) = metrics_sender.send(thread_metrics.clone()) {
-                eprintln!("Error sending metrics: {:?}", e);
+        if live_policy.refresh(&mut policy) {
+            debug!("worker picked up the reloaded policy");
+        }
+        let unix_ms = clock.unix_millis();
+        controller.set_scale(traffic.load(unix_ms));
+        let events_per_batch = match controller.next_batch(clock.now()) {
+            BatchPlan::Run(events) => events,
+            BatchPlan::Wait(duration) => {
+                clock.sleep(duration);
+                continue;
+            }
+        };
+        let batch_started = Instant::now();
+        let events = match time_stage(Stage::Generate, || source.next_batch(events_per_batch)) {
+            Ok(Some(events)) => events,
+            Ok(None) => break,
+            Err(e) => {
+                report(&errors, e, ErrorPolicy::Shutdown);
+                break;
+            }
+        };
+        if let Some(sender) = &sinks.record {
+            let started = Instant::now();
+            let records = events.iter().map(EventRecord::from_components).collect();
+            if sender.send(records).is_err() {
+                // The recorder has given up and reported why; stop producing records for it.
+                sinks.record = None;
+            }
+            record_stage(Stage::Sinks, started.elapsed());
+        }
+        let batch_metrics = if sinks.wants_evaluated_events() {
+            let mut records = Vec::with_capacity(events.len());
+            let batch_metrics = time_stage(Stage::Evaluate, || {
+                process_batch(&mut world, &events, &policy, parallelism, Some(&mut records))
+            });
+            time_stage(Stage::Sinks, || sinks.write_evaluated_events(records, &errors));
+            batch_metrics
+        } else {
+            time_stage(Stage::Evaluate, || process_batch(&mut world, &events, &policy, parallelism, None))
+        };
+        if let Some(sampler) = &mut sinks.samples {
+            let chosen = sampler.choose(events.len());
+            if !chosen.is_empty() {
+                let sampled = collect_sampled_events(&world, &policy, &chosen, sampler.worker());
+                sampler.fill(&chosen, sampled);
             }
-            thread_metrics = ComplianceMetrics::default();
+            sampler.forward_if_due(clock.now());
+        }
+        if let Some(shadow) = &shadow {
+            time_stage(Stage::Shadow, || shadow.evaluate(&mut shadow_world, &events, &batch_metrics, parallelism));
         }
-        world.clear();
+        shard.add(&batch_metrics);
+        source.ack(events.len());
+        let batch_latency = batch_started.elapsed();
+        controller.observe(events.len(), batch_latency);
+        shard.record_batch_latency(batch_latency);
+        let (record_depth, audit_depth) = sinks.queue_depths();
+        shard.set_sink_queue_depths(record_depth, audit_depth);
+        shard.set_world_size(world.len() as usize, world.archetypes().len());
+    }
+    source.shutdown();
+    sinks.finish(&errors);
+    debug!("worker stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MODELS_PER_SERVICE;
+    use crate::vendors::VendorProfile;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn chunked_systems_match_sequential() {
+        let policy = Policy::builtin();
+        let events = generate_ai_events(5_000);
+        let sequential = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        let chunked = process_batch(&mut World::new(), &events, &policy, Parallelism::Chunked(64), None);
+        assert_eq!(chunked.total_events, 5_000);
+        assert_eq!(chunked.violation_counts, sequential.violation_counts);
+        assert_eq!(chunked.risk_factor_counts, sequential.risk_factor_counts);
+        assert_eq!(chunked.service_counts, sequential.service_counts);
+        assert_eq!(
+            (chunked.high_risk_count, chunked.medium_risk_count, chunked.low_risk_count),
+            (sequential.high_risk_count, sequential.medium_risk_count, sequential.low_risk_count)
+        );
+        assert_eq!(chunked.total_data_sensitivity, sequential.total_data_sensitivity);
+    }
+
+    #[test]
+    fn breakdowns_and_user_metrics_add_up_to_the_totals() {
+        let policy = Policy::builtin();
+        let events = generate_ai_events(2_000);
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Chunked(64), None);
+        for breakdown in [&metrics.departments, &metrics.services, &metrics.models] {
+            let mut sum = ComplianceMetrics::default();
+            breakdown.iter().for_each(|entry| sum.merge(entry));
+            assert_eq!(sum.total_events, metrics.total_events);
+            assert_eq!(sum.violation_counts, metrics.violation_counts);
+            assert_eq!(sum.high_risk_count, metrics.high_risk_count);
+        }
+        assert_eq!(metrics.users.values().map(|user| user.events).sum::<u64>(), metrics.total_events);
+        for (index, service) in metrics.services.iter().enumerate() {
+            assert_eq!(service.total_events, metrics.service_counts[index]);
+            assert_eq!(service.department_counts.iter().sum::<u64>(), service.total_events);
+        }
+    }
+
+    #[test]
+    fn events_are_classified_into_ai_act_tiers() {
+        let policy = Policy::builtin();
+        let event = |name_idx, vendor_idx, data_sensitivity, purpose| {
+            let service = AIService { name_idx, vendor_idx, model_idx: name_idx * MODELS_PER_SERVICE as u8 };
+            (
+                service,
+                Usage { department_idx: 3, data_sensitivity },
+                UserId::UNKNOWN,
+                purpose,
+                Region::UNKNOWN,
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let (hr_screening, code_generation) = (Purpose(1), Purpose(0));
+        let events = [
+            event(1, 1, 95, hr_screening),
+            event(1, 1, 40, hr_screening),
+            event(0, 0, 40, hr_screening),
+            event(4, 4, 40, Purpose::UNSPECIFIED),
+            event(1, 1, 40, code_generation),
+        ];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        assert_eq!(metrics.ai_act_tier_counts, [1, 2, 1, 1]);
+        // The prohibited use, and the high-risk use of a vendor without a conformity assessment.
+        assert_eq!(metrics.violations(EU_AI_ACT), 2);
     }
-    if thread_metrics.total_events > 0 {
-        let _ = metrics_sender.send(thread_metrics);
+
+    #[test]
+    fn personal_data_only_goes_to_vendors_with_a_dpa() {
+        let mut policy = Policy::builtin();
+        let profiles = BTreeMap::from([("Anthropic".to_string(), VendorProfile { dpa_signed: true, ..VendorProfile::default() })]);
+        policy.vendors = VendorRegistry::from_profiles(profiles).unwrap();
+        let event = |vendor_idx, data_sensitivity| {
+            let service = AIService { name_idx: vendor_idx, vendor_idx, model_idx: vendor_idx * MODELS_PER_SERVICE as u8 };
+            (
+                service,
+                Usage { department_idx: 0, data_sensitivity },
+                UserId::UNKNOWN,
+                Purpose::UNSPECIFIED,
+                Region::UNKNOWN,
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let events = [event(1, 80), event(0, 80), event(0, 20)];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        // Only the personal data sent to a vendor without a DPA breaks GDPR.
+        assert_eq!(metrics.violations(GDPR), 1);
+        assert_eq!(metrics.vendor_counts, [2, 1, 0, 0, 0]);
+        assert_eq!(metrics.vendor_violations[0], 1);
+        assert_eq!(metrics.vendor_violations.iter().sum::<u64>(), metrics.violating_events);
+    }
+
+    #[test]
+    fn personal_data_needs_a_lawful_basis() {
+        let mut policy = Policy::builtin();
+        let profiles = BTreeMap::from([("Anthropic".to_string(), VendorProfile { dpa_signed: true, ..VendorProfile::default() })]);
+        policy.vendors = VendorRegistry::from_profiles(profiles).unwrap();
+        let event = |data_sensitivity, basis| {
+            let service = AIService { name_idx: 1, vendor_idx: 1, model_idx: 2 };
+            (
+                service,
+                Usage { department_idx: 0, data_sensitivity },
+                UserId::UNKNOWN,
+                Purpose::UNSPECIFIED,
+                Region::UNKNOWN,
+                basis,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let events = [
+            event(80, LawfulBasis::CONSENT),
+            event(80, LawfulBasis::NONE),
+            event(20, LawfulBasis::NONE),
+            event(80, LawfulBasis::UNKNOWN),
+        ];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        // Only personal data without a lawful basis breaks GDPR; events without a recorded basis are not checked.
+        assert_eq!(metrics.violations(GDPR), 1);
+        assert_eq!(metrics.lawful_basis_counts, [1, 0, 0, 2]);
+        assert_eq!(metrics.lawful_basis_personal, [1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn special_category_data_needs_consent_and_biometrics_are_high_risk() {
+        let mut policy = Policy::builtin();
+        let profiles = BTreeMap::from([("Anthropic".to_string(), VendorProfile { dpa_signed: true, ..VendorProfile::default() })]);
+        policy.vendors = VendorRegistry::from_profiles(profiles).unwrap();
+        let event = |basis, pii| {
+            let service = AIService { name_idx: 1, vendor_idx: 1, model_idx: 2 };
+            (service, Usage { department_idx: 0, data_sensitivity: 20 }, UserId::UNKNOWN, Purpose(0), Region::UNKNOWN, basis, pii, Timestamp::default())
+        };
+        let contract = LawfulBasis(1);
+        let events = [
+            event(LawfulBasis::CONSENT, PiiTypes::HEALTH),
+            event(contract, PiiTypes::HEALTH),
+            event(contract, PiiTypes::FINANCIAL),
+            event(LawfulBasis::CONSENT, PiiTypes::BIOMETRIC),
+        ];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        // Health data is personal data despite its low sensitivity, and a contract is not explicit consent.
+        assert_eq!(metrics.violations(GDPR), 1);
+        assert_eq!(metrics.lawful_basis_personal, [2, 1, 0, 0]);
+        assert_eq!(metrics.pii_type_counts, [0, 1, 2, 1, 0]);
+        // Only the biometric event is high-risk, and Anthropic has a conformity assessment.
+        assert_eq!(metrics.ai_act_tier_counts[AiActTier::High as usize], 1);
+        assert_eq!(metrics.violations(EU_AI_ACT), 0);
+    }
+
+    #[test]
+    fn personal_data_is_limited_to_approved_purposes() {
+        let policy = Policy::builtin();
+        let event = |department_idx, data_sensitivity, purpose| {
+            let service = AIService { name_idx: 1, vendor_idx: 1, model_idx: 2 };
+            (
+                service,
+                Usage { department_idx, data_sensitivity },
+                UserId::UNKNOWN,
+                purpose,
+                Region::UNKNOWN,
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let hr_screening = Purpose(1);
+        let events = [
+            event(3, 80, hr_screening),
+            event(0, 80, hr_screening),
+            event(0, 20, hr_screening),
+            event(0, 80, Purpose::UNSPECIFIED),
+        ];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        // Only Engineering screening candidates with personal data breaks purpose limitation.
+        assert_eq!(metrics.violations(PURPOSE_LIMITATION), 1);
+        assert_eq!(metrics.purpose_counts, [0, 3, 0, 0, 0, 0]);
+        assert_eq!(metrics.purpose_violations, [0, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn personal_data_is_only_processed_in_adequate_regions() {
+        let policy = Policy::builtin();
+        let event = |data_sensitivity, region| {
+            let service = AIService { name_idx: 0, vendor_idx: 0, model_idx: 0 };
+            (
+                service,
+                Usage { department_idx: 0, data_sensitivity },
+                UserId::UNKNOWN,
+                Purpose(0),
+                region,
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let (uk, us) = (Region(1), Region(3));
+        let events = [event(80, uk), event(80, us), event(20, us), event(80, Region::UNKNOWN)];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        assert_eq!(metrics.violations(DATA_RESIDENCY), 1);
+        assert_eq!(metrics.region_counts, [0, 1, 0, 2, 0, 0]);
+        assert_eq!(metrics.region_violations, [0, 0, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn hipaa_flags_phi_sent_to_vendors_without_a_baa() {
+        let mut policy = Policy::builtin();
+        let flag = policy.compliance.claim("hipaa", "HIPAA").unwrap();
+        policy.hipaa = Some(HipaaRules { flag, baa_vendors: 0b0_1111 });
+        let event = |vendor_idx, department_idx, data_sensitivity| {
+            let service = AIService { name_idx: vendor_idx, vendor_idx, model_idx: vendor_idx * MODELS_PER_SERVICE as u8 };
+            (
+                service,
+                Usage { department_idx, data_sensitivity },
+                UserId::UNKNOWN,
+                Purpose(5),
+                Region(0),
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let stability = 4;
+        let events = [
+            event(stability, HEALTHCARE_DEPARTMENT, 80),
+            event(1, HEALTHCARE_DEPARTMENT, 80),
+            event(stability, HEALTHCARE_DEPARTMENT, 40),
+            event(stability, 2, 80),
+        ];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        assert_eq!(metrics.phi_events, 2);
+        assert_eq!(metrics.violations(flag), 1);
+    }
+
+    #[test]
+    fn waived_violations_are_counted_separately_until_expiry() {
+        let mut policy = Policy::builtin();
+        let (finance, chatgpt, claude) = (2, 0, 1);
+        policy.waivers = vec![Waiver {
+            department: finance,
+            service: Some(chatgpt),
+            checks: INTERNAL_POLICY.bit(),
+            expires: "1970-01-01".to_string(),
+            expires_at_ms: 86_400_000,
+            ticket: "RISK-123".to_string(),
+        }];
+        let event = |name_idx| {
+            let service = AIService { name_idx, vendor_idx: name_idx, model_idx: name_idx * MODELS_PER_SERVICE as u8 };
+            (
+                service,
+                Usage { department_idx: finance, data_sensitivity: 10 },
+                UserId::UNKNOWN,
+                Purpose(5),
+                Region(0),
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let events = [event(chatgpt), event(chatgpt), event(claude)];
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        assert_eq!(metrics.violations(INTERNAL_POLICY), 0);
+        assert_eq!((metrics.waived_events, metrics.waived_counts[INTERNAL_POLICY.0 as usize]), (2, 2));
+
+        // Events from the day the waiver expires are no longer exempt.
+        let expired_events = events.map(|mut event| {
+            event.7 = Timestamp(86_400_000);
+            event
+        });
+        let expired = process_batch(&mut World::new(), &expired_events, &policy, Parallelism::Sequential, None);
+        assert_eq!((expired.violations(INTERNAL_POLICY), expired.waived_events), (2, 0));
+        // The waived events failed no other check, so they only count as violating once it expires.
+        assert_eq!(expired.violating_events, metrics.violating_events + 2);
+        // Waived or not, ChatGPT is not on Finance's allowlist.
+        assert_eq!((metrics.shadow_ai_events, expired.shadow_ai_events), (2, 2));
+    }
+
+    #[test]
+    fn reused_world_evaluates_each_batch_afresh() {
+        let policy = Policy::builtin();
+        let events = generate_ai_events(1_000);
+        let mut world = World::new();
+        let first = process_batch(&mut world, &events, &policy, Parallelism::Sequential, None);
+        let second = process_batch(&mut world, &events, &policy, Parallelism::Sequential, None);
+        assert_eq!(world.len(), 1_000);
+        assert_eq!(second.total_events, 1_000);
+        assert_eq!(second.violation_counts, first.violation_counts);
+        assert_eq!(second.risk_factor_counts, first.risk_factor_counts);
+    }
+
+    #[test]
+    fn audit_records_carry_the_steps_in_order() {
+        let policy = Policy::builtin();
+        let events = generate_ai_events(100);
+        let mut world = World::new();
+        let mut records = Vec::new();
+        // The second batch reuses the first batch's entities, whose provenance must start over.
+        for _ in 0..2 {
+            records.clear();
+            process_batch(&mut world, &events, &policy, Parallelism::Chunked(16), Some(&mut records));
+        }
+        let expected = [
+            EU_AI_ACT_BIOMETRIC_STEP.id,
+            GDPR_SPECIAL_CATEGORY_STEP.id,
+            INTERNAL_POLICY_ALLOWLIST_STEP.id,
+            PURPOSE_LIMITATION_STEP.id,
+            DATA_RESIDENCY_STEP.id,
+            RISK_ASSESSMENT_STEP.id,
+        ];
+        assert_eq!(records.len(), 100);
+        assert!(records.iter().all(|r| r.provenance.ids() == expected));
     }
 }
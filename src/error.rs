@@ -0,0 +1,219 @@
+use crate::chaos;
+use clap::ValueEnum;
+use crossbeam_channel::Sender;
+use std::io;
+use std::net::SocketAddr;
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Number of attempts made for a failing sink operation under the `retry` policy.
+const RETRY_ATTEMPTS: u32 = 5;
+
+/// Initial backoff between retries; doubled after each failed attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Crate-wide error type covering every failure the monitor can encounter.
+#[derive(Debug, Error)]
+pub enum MonitorError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("dashboard terminal error: {0}")]
+    Terminal(io::Error),
+
+    #[error("failed to install Ctrl+C handler: {0}")]
+    CtrlC(#[from] ctrlc::Error),
+
+    #[error("failed to write to {sink}: {source}")]
+    Sink {
+        sink: &'static str,
+        source: io::Error,
+    },
+
+    #[error("failed to read event log: {0}")]
+    EventLog(io::Error),
+
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    #[error("{0}")]
+    Usage(clap::Error),
+
+    #[error("failed to restore metrics snapshot: {0}")]
+    Snapshot(String),
+
+    #[error("failed to read benchmark baseline: {0}")]
+    Baseline(String),
+
+    #[error("performance regressed beyond the {threshold}% noise threshold in {regressed} of {workloads} workloads")]
+    BenchRegression {
+        regressed: usize,
+        workloads: usize,
+        threshold: f64,
+    },
+
+    #[error("memory grew by {slope:.2} MiB/hour during the soak test, beyond the {limit} MiB/hour limit")]
+    MemoryGrowth {
+        slope: f64,
+        limit: f64,
+    },
+
+    #[error("audit log failed verification: {0}")]
+    AuditLog(String),
+
+    #[error("failed to listen for {purpose} on {addr}: {source}")]
+    Listen {
+        purpose: &'static str,
+        addr: String,
+        source: io::Error,
+    },
+
+    #[error("connection from remote worker {peer} failed: {source}")]
+    RemoteWorker {
+        peer: SocketAddr,
+        source: io::Error,
+    },
+
+    #[error("connection to aggregator {addr} failed: {source}")]
+    Aggregator {
+        addr: String,
+        source: io::Error,
+    },
+
+    #[error("connection to monitor {addr} failed: {source}")]
+    RemoteMonitor {
+        addr: String,
+        source: io::Error,
+    },
+
+    #[error("failed to pin {thread} to core {core}")]
+    PinCore {
+        thread: String,
+        core: usize,
+    },
+
+    #[error("{0} thread panicked")]
+    ThreadPanicked(&'static str),
+
+    #[error("{thread} panicked: {message}")]
+    WorkerPanicked {
+        thread: String,
+        message: String,
+    },
+}
+
+/// Convenience alias for results using [`MonitorError`].
+pub type Result<T> = std::result::Result<T, MonitorError>;
+
+/// How the monitor reacts when a component reports an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ErrorPolicy {
+    /// Retry the failing operation with exponential backoff, degrading if it keeps failing.
+    Retry,
+    /// Disable the failing component and keep monitoring without it.
+    Degrade,
+    /// Stop the whole monitor.
+    Shutdown,
+}
+
+/// An error raised by a thread, sent to the supervisor together with the action taken.
+#[derive(Debug)]
+pub struct ErrorReport {
+    pub error: MonitorError,
+    pub action: ErrorPolicy,
+}
+
+/// Sends an error report to the supervisor.
+///
+/// If the supervisor is already gone the monitor is shutting down, so the report is dropped.
+pub fn report(sender: &Sender<ErrorReport>, error: MonitorError, action: ErrorPolicy) {
+    let _ = sender.send(ErrorReport { error, action });
+}
+
+/// Runs a sink operation according to `policy`, retrying with backoff under [`ErrorPolicy::Retry`].
+///
+/// Each failed attempt that will be retried is reported to the supervisor; the final error is
+/// returned to the caller, which decides how to degrade. When `--chaos` is enabled, attempts
+/// may fail before `op` runs.
+///
+/// # Arguments
+///
+/// * `policy` - The error policy configured for the sink.
+/// * `sink` - Name of the sink, used in error reports.
+/// * `errors` - Channel sender for error reports.
+/// * `op` - The operation to attempt.
+pub fn with_retry<T>(
+    policy: ErrorPolicy,
+    sink: &'static str,
+    errors: &Sender<ErrorReport>,
+    mut op: impl FnMut() -> io::Result<T>,
+) -> Result<T> {
+    let attempts = if policy == ErrorPolicy::Retry { RETRY_ATTEMPTS } else { 1 };
+    let mut backoff = RETRY_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match chaos::inject(sink).and_then(|()| op()) {
+            Ok(value) => return Ok(value),
+            Err(source) if attempt < attempts => {
+                report(errors, MonitorError::Sink { sink, source }, ErrorPolicy::Retry);
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(source) => return Err(MonitorError::Sink { sink, source }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+
+    /// Returns an operation that fails `failures` times before returning its attempt count.
+    fn failing(failures: u32) -> impl FnMut() -> io::Result<u32> {
+        let mut attempts = 0;
+        move || {
+            attempts += 1;
+            if attempts <= failures { Err(io::Error::other(format!("attempt {} failed", attempts))) } else { Ok(attempts) }
+        }
+    }
+
+    #[test]
+    fn transient_failures_are_retried_and_reported() {
+        let (errors, reports) = unbounded();
+        let attempts = with_retry(ErrorPolicy::Retry, "test sink", &errors, failing(2)).unwrap();
+        assert_eq!(attempts, 3);
+        let reported: Vec<_> = reports.try_iter().map(|r| (r.error.to_string(), r.action)).collect();
+        assert_eq!(
+            reported,
+            [
+                ("failed to write to test sink: attempt 1 failed".to_string(), ErrorPolicy::Retry),
+                ("failed to write to test sink: attempt 2 failed".to_string(), ErrorPolicy::Retry),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_last_error_is_returned_once_the_attempts_run_out() {
+        let (errors, reports) = unbounded();
+        let Err(e) = with_retry(ErrorPolicy::Retry, "test sink", &errors, failing(RETRY_ATTEMPTS)) else {
+            panic!("the operation succeeded after every attempt failed");
+        };
+        assert_eq!(e.to_string(), format!("failed to write to test sink: attempt {} failed", RETRY_ATTEMPTS));
+        assert_eq!(reports.try_iter().count(), RETRY_ATTEMPTS as usize - 1);
+    }
+
+    #[test]
+    fn other_policies_do_not_retry() {
+        for policy in [ErrorPolicy::Degrade, ErrorPolicy::Shutdown] {
+            let (errors, reports) = unbounded();
+            let Err(e) = with_retry(policy, "test sink", &errors, failing(1)) else {
+                panic!("the operation was retried under {:?}", policy);
+            };
+            assert_eq!(e.to_string(), "failed to write to test sink: attempt 1 failed");
+            assert_eq!(reports.try_iter().count(), 0);
+        }
+    }
+}
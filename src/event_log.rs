@@ -0,0 +1,389 @@
+use crate::clock::parse_utc_timestamp;
+use crate::components::{Event, UserId};
+use crate::content_scan::ContentScanner;
+use crate::error::{MonitorError, Result};
+use crate::replay::EventRecord;
+use crate::run_report::{record_stage, time_stage, Stage};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, Lines};
+use std::path::Path;
+use std::time::Instant;
+
+/// Skipped rows described in a [`SkippedRows`] summary; the rest are only counted.
+const MAX_SKIPPED_EXAMPLES: usize = 5;
+
+/// How a field of an [`EventRecord`] is read from a CSV cell.
+#[derive(Clone, Copy)]
+enum FieldKind {
+    Number,
+    /// Milliseconds since the Unix epoch, or a timestamp read by [`parse_utc_timestamp`].
+    Timestamp,
+    /// A numeric user id, or a user name mapped to one by [`user_id_from_name`].
+    UserId,
+    Text,
+    /// Names separated by `;`.
+    List,
+}
+
+/// The fields of an [`EventRecord`], as named in event logs.
+const FIELDS: [(&str, FieldKind); 12] = [
+    ("timestamp_ms", FieldKind::Timestamp),
+    ("service", FieldKind::Text),
+    ("model", FieldKind::Text),
+    ("vendor", FieldKind::Text),
+    ("department", FieldKind::Text),
+    ("data_sensitivity", FieldKind::Number),
+    ("user_id", FieldKind::UserId),
+    ("purpose", FieldKind::Text),
+    ("region", FieldKind::Text),
+    ("lawful_basis", FieldKind::Text),
+    ("pii_types", FieldKind::List),
+    ("prompt", FieldKind::Text),
+];
+
+/// Formats of event logs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON object per line.
+    Ndjson,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+impl LogFormat {
+    /// Returns the format of a log by its extension: `.csv` for CSV, `.jsonl` or `.ndjson` for
+    /// NDJSON, and `None` for anything else.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(LogFormat::Csv),
+            "jsonl" | "ndjson" => Some(LogFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// Which column of an event log holds each event field. Fields without an entry are read from
+/// the column named like the field.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnMapping {
+    columns: BTreeMap<String, String>,
+}
+
+impl ColumnMapping {
+    /// Loads a mapping from a JSON object of event field names to column names, e.g.
+    /// `{"service": "tool", "timestamp_ms": "ts"}`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The mapping file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+        let text = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let columns = serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+        ColumnMapping::from_columns(columns).map_err(invalid)
+    }
+
+    /// Builds a mapping from event field names to column names.
+    pub fn from_columns(columns: BTreeMap<String, String>) -> std::result::Result<Self, String> {
+        if let Some(field) = columns.keys().find(|field| !FIELDS.iter().any(|(name, _)| name == field)) {
+            return Err(format!("unknown event field '{}' in column mapping", field));
+        }
+        Ok(ColumnMapping { columns })
+    }
+
+    /// Returns the column holding `field`.
+    fn column<'a>(&'a self, field: &'a str) -> &'a str {
+        self.columns.get(field).map_or(field, String::as_str)
+    }
+
+    /// Picks the event fields out of a JSON object written with the mapped column names.
+    ///
+    /// Timestamps and user ids may be given as strings, as in CSV cells.
+    fn record_from_object(&self, object: &Map<String, Value>) -> std::result::Result<EventRecord, String> {
+        let mut fields = Map::new();
+        for (field, kind) in FIELDS {
+            let column = self.column(field);
+            let Some(value) = object.get(column) else {
+                continue;
+            };
+            let value = match (kind, value) {
+                (FieldKind::Timestamp | FieldKind::UserId, Value::String(text)) => cell_value(kind, text.trim(), column)?,
+                _ => value.clone(),
+            };
+            fields.insert(field.to_string(), value);
+        }
+        serde_json::from_value(Value::Object(fields)).map_err(|e| e.to_string())
+    }
+
+    /// Reads the event fields from a CSV row; empty cells are left out.
+    fn record_from_row(&self, headers: &csv::StringRecord, row: &csv::StringRecord) -> std::result::Result<EventRecord, String> {
+        let mut fields = Map::new();
+        for (field, kind) in FIELDS {
+            let column = self.column(field);
+            let Some(cell) = headers.iter().position(|header| header == column).and_then(|index| row.get(index)) else {
+                continue;
+            };
+            let cell = cell.trim();
+            if cell.is_empty() {
+                continue;
+            }
+            fields.insert(field.to_string(), cell_value(kind, cell, column)?);
+        }
+        serde_json::from_value(Value::Object(fields)).map_err(|e| e.to_string())
+    }
+}
+
+/// Reads a non-empty cell holding a field of the given kind.
+fn cell_value(kind: FieldKind, cell: &str, column: &str) -> std::result::Result<Value, String> {
+    Ok(match kind {
+        FieldKind::Number => {
+            Value::from(cell.parse::<u64>().map_err(|_| format!("invalid number '{}' in column '{}'", cell, column))?)
+        }
+        FieldKind::Timestamp => Value::from(
+            cell.parse::<u64>()
+                .ok()
+                .or_else(|| parse_utc_timestamp(cell))
+                .ok_or_else(|| format!("invalid timestamp '{}' in column '{}'", cell, column))?,
+        ),
+        FieldKind::UserId => Value::from(cell.parse::<u32>().unwrap_or_else(|_| user_id_from_name(cell))),
+        FieldKind::Text => Value::from(cell),
+        FieldKind::List => cell.split(';').map(str::trim).filter(|name| !name.is_empty()).collect(),
+    })
+}
+
+/// Maps a user name, such as an email address, to a numeric user id.
+///
+/// The id is the 32-bit FNV-1a hash of the name, so a user gets the same id in every log and
+/// every run without keeping a table of names. It never collides with [`UserId::UNKNOWN`].
+fn user_id_from_name(name: &str) -> u32 {
+    let hash = name.bytes().fold(0x811c_9dc5, |hash: u32, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193));
+    hash.min(UserId::UNKNOWN.0 - 1)
+}
+
+/// A row of an event log that was skipped, and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedRow {
+    /// Line of the log the row starts on, from 1.
+    pub line: u64,
+    pub reason: String,
+}
+
+/// A row read from an event log.
+pub enum LogRow {
+    /// An event, classified by its prompt, at its timestamp.
+    Event { timestamp_ms: u64, event: Event },
+    /// A row that could not be parsed or names something unknown to this build.
+    Skipped(SkippedRow),
+}
+
+/// Rows of the underlying reader, by format.
+enum Rows<R> {
+    Ndjson { lines: Lines<R>, line: u64 },
+    Csv { reader: csv::Reader<R>, headers: csv::StringRecord },
+}
+
+/// Reads the events of an NDJSON or CSV event log, with fields in the columns named by a
+/// [`ColumnMapping`].
+///
+/// Malformed rows are handed out as [`LogRow::Skipped`] rather than failing the read; only
+/// I/O errors do.
+pub struct EventLogReader<R> {
+    rows: Rows<R>,
+    mapping: ColumnMapping,
+    content_scan: ContentScanner,
+}
+
+impl<R: BufRead> EventLogReader<R> {
+    /// Creates the reader, reading the header row of a CSV log.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader over the log.
+    /// * `format` - Format of the log.
+    /// * `mapping` - Which column holds each event field.
+    /// * `content_scan` - Scanner classifying the prompts of the log's events.
+    pub fn new(reader: R, format: LogFormat, mapping: ColumnMapping, content_scan: ContentScanner) -> Result<Self> {
+        let rows = match format {
+            LogFormat::Ndjson => Rows::Ndjson { lines: reader.lines(), line: 0 },
+            LogFormat::Csv => {
+                let mut reader = csv::Reader::from_reader(reader);
+                let headers = reader.headers().map_err(|e| MonitorError::EventLog(e.into()))?.clone();
+                Rows::Csv { reader, headers }
+            }
+        };
+        Ok(EventLogReader { rows, mapping, content_scan })
+    }
+
+    /// Reads the next row, or `None` at the end of the log.
+    pub fn next_row(&mut self) -> Result<Option<LogRow>> {
+        let parse_started = Instant::now();
+        let (line, record) = match &mut self.rows {
+            Rows::Ndjson { lines, line } => loop {
+                let Some(text) = lines.next() else {
+                    return Ok(None);
+                };
+                let text = text.map_err(MonitorError::EventLog)?;
+                *line += 1;
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let record = serde_json::from_str::<Map<String, Value>>(&text)
+                    .map_err(|e| format!("invalid JSON: {}", e))
+                    .and_then(|object| self.mapping.record_from_object(&object));
+                break (*line, record);
+            },
+            Rows::Csv { reader, headers } => {
+                let mut row = csv::StringRecord::new();
+                match reader.read_record(&mut row) {
+                    Ok(false) => return Ok(None),
+                    Ok(true) => {
+                        let line = row.position().map_or(0, |position| position.line());
+                        (line, self.mapping.record_from_row(headers, &row))
+                    }
+                    Err(e) if e.is_io_error() => return Err(MonitorError::EventLog(e.into())),
+                    Err(e) => {
+                        let line = e.position().map_or(0, |position| position.line());
+                        let reason = match e.kind() {
+                            csv::ErrorKind::UnequalLengths { expected_len, len, .. } => {
+                                format!("malformed CSV row of {} fields, expected {}", len, expected_len)
+                            }
+                            _ => format!("malformed CSV row: {}", e),
+                        };
+                        (line, Err(reason))
+                    }
+                }
+            }
+        };
+        let mut record = match record {
+            Ok(record) => record,
+            Err(reason) => return Ok(Some(LogRow::Skipped(SkippedRow { line, reason }))),
+        };
+        record_stage(Stage::Parse, parse_started.elapsed());
+        if record.prompt.is_some() {
+            time_stage(Stage::Classify, || self.content_scan.classify(&mut record));
+        }
+        Ok(Some(match record.to_components() {
+            Some(event) => LogRow::Event { timestamp_ms: record.timestamp_ms, event },
+            None => LogRow::Skipped(SkippedRow { line, reason: "names a service, model, or other value unknown to this build".to_string() }),
+        }))
+    }
+}
+
+/// Summary of the rows skipped while reading event logs: how many, and where the first few were.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SkippedRows {
+    pub count: u64,
+    /// The first skipped rows, as `log:line: reason`.
+    pub examples: Vec<String>,
+}
+
+impl SkippedRows {
+    /// Counts a skipped row of the given log.
+    pub fn add(&mut self, log: &str, row: &SkippedRow) {
+        self.count += 1;
+        if self.examples.len() < MAX_SKIPPED_EXAMPLES {
+            self.examples.push(format!("{}:{}: {}", log, row.line, row.reason));
+        }
+    }
+
+    /// Renders the summary as indented lines, ending with how many rows were not described.
+    pub fn render(&self) -> String {
+        let mut out = format!("Skipped {} malformed rows:\n", self.count);
+        for example in &self.examples {
+            out.push_str(&format!("  {}\n", example));
+        }
+        let undescribed = self.count - self.examples.len() as u64;
+        if undescribed > 0 {
+            out.push_str(&format!("  ... and {} more\n", undescribed));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read_all(log: &str, format: LogFormat, mapping: ColumnMapping) -> (Vec<u64>, SkippedRows) {
+        let mut reader = EventLogReader::new(Cursor::new(log.to_string()), format, mapping, ContentScanner::builtin()).unwrap();
+        let (mut timestamps, mut skipped) = (Vec::new(), SkippedRows::default());
+        while let Some(row) = reader.next_row().unwrap() {
+            match row {
+                LogRow::Event { timestamp_ms, .. } => timestamps.push(timestamp_ms),
+                LogRow::Skipped(row) => skipped.add("log", &row),
+            }
+        }
+        (timestamps, skipped)
+    }
+
+    #[test]
+    fn malformed_rows_are_skipped_with_their_line_and_reason() {
+        let columns = [("timestamp_ms", "ts"), ("service", "tool"), ("vendor", "provider"), ("department", "team")];
+        let mapping = ColumnMapping::from_columns(columns.iter().map(|(field, column)| (field.to_string(), column.to_string())).collect()).unwrap();
+        let csv = "ts,tool,provider,team,pii_types\n\
+                   1000,Claude,Anthropic,HR,Contact details;Health\n\
+                   soon,Claude,Anthropic,HR,\n\
+                   2000,Bard,Google,HR,\n\
+                   3000,Claude\n\
+                   4000,Copilot,Microsoft,Finance,\n";
+        let (timestamps, skipped) = read_all(csv, LogFormat::Csv, mapping.clone());
+        assert_eq!(timestamps, [1_000, 4_000]);
+        assert_eq!(skipped.count, 3);
+        assert_eq!(skipped.examples[0], "log:3: invalid timestamp 'soon' in column 'ts'");
+        assert!(skipped.examples[1].starts_with("log:4: names a service"), "{}", skipped.examples[1]);
+        assert_eq!(skipped.examples[2], "log:5: malformed CSV row of 2 fields, expected 5");
+
+        let ndjson = "{\"ts\": 5000, \"tool\": \"Copilot\", \"provider\": \"Microsoft\", \"team\": \"Finance\"}\n\nnot json\n{\"ts\": 6000}\n";
+        let (timestamps, skipped) = read_all(ndjson, LogFormat::Ndjson, mapping);
+        assert_eq!(timestamps, [5_000]);
+        assert_eq!(skipped.count, 2);
+        assert!(skipped.examples[0].starts_with("log:3: invalid JSON"), "{}", skipped.examples[0]);
+        assert!(skipped.examples[1].starts_with("log:4: missing field"), "{}", skipped.examples[1]);
+
+        let many = SkippedRows { count: 7, examples: vec!["log:1: x".to_string()] };
+        assert_eq!(many.render(), "Skipped 7 malformed rows:\n  log:1: x\n  ... and 6 more\n");
+        let unknown = BTreeMap::from([("tool".to_string(), "service".to_string())]);
+        assert_eq!(ColumnMapping::from_columns(unknown).unwrap_err(), "unknown event field 'tool' in column mapping");
+        assert_eq!(LogFormat::from_path(Path::new("a/b.ndjson")), Some(LogFormat::Ndjson));
+        assert_eq!(LogFormat::from_path(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn timestamps_may_be_dates_and_times() {
+        let csv = "timestamp_ms,service,vendor,department\n\
+                   2025-01-31T13:05:09.250Z,Claude,Anthropic,HR\n\
+                   2025-01-31T14:05:10+01:00,Claude,Anthropic,HR\n\
+                   2025-01-31 13:05:11,Claude,Anthropic,HR\n\
+                   31/01/2025 13:05,Claude,Anthropic,HR\n";
+        let (timestamps, skipped) = read_all(csv, LogFormat::Csv, ColumnMapping::default());
+        assert_eq!(timestamps, [1_738_328_709_250, 1_738_328_710_000, 1_738_328_711_000]);
+        assert_eq!(skipped.examples, ["log:5: invalid timestamp '31/01/2025 13:05' in column 'timestamp_ms'"]);
+
+        let ndjson = "{\"timestamp_ms\": \"2025-01-31T13:05:09Z\", \"service\": \"Claude\", \"vendor\": \"Anthropic\", \"department\": \"HR\"}\n";
+        let (timestamps, _) = read_all(ndjson, LogFormat::Ndjson, ColumnMapping::default());
+        assert_eq!(timestamps, [1_738_328_709_000]);
+    }
+
+    #[test]
+    fn user_names_map_to_stable_user_ids() {
+        let csv = "timestamp_ms,service,vendor,department,user\n\
+                   1000,Claude,Anthropic,HR,42\n\
+                   2000,Claude,Anthropic,HR,alice@example.com\n\
+                   3000,Claude,Anthropic,HR,bob@example.com\n\
+                   4000,Claude,Anthropic,HR,alice@example.com\n";
+        let mapping = ColumnMapping::from_columns(BTreeMap::from([("user_id".to_string(), "user".to_string())])).unwrap();
+        let mut reader = EventLogReader::new(Cursor::new(csv), LogFormat::Csv, mapping, ContentScanner::builtin()).unwrap();
+        let mut users = Vec::new();
+        while let Some(LogRow::Event { event, .. }) = reader.next_row().unwrap() {
+            users.push(event.2.0);
+        }
+        let (alice, bob) = (user_id_from_name("alice@example.com"), user_id_from_name("bob@example.com"));
+        assert_ne!(alice, bob);
+        assert_eq!(users, [42, alice, bob, alice]);
+        // FNV-1a test vector, so ids stay the same across builds.
+        assert_eq!(user_id_from_name("a"), 0xe40c_292c);
+    }
+}
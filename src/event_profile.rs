@@ -0,0 +1,345 @@
+use crate::components::{AIService, Event, LawfulBasis, PiiTypes, Purpose, Region, Timestamp, Usage, UserId};
+use crate::constants::{
+    DEPARTMENT_NAMES, LAWFUL_BASIS_WEIGHTS, MODELS_PER_SERVICE, MODEL_DEPLOYMENTS, PII_TYPE_WEIGHTS, PURPOSE_NAMES,
+    REGION_NAMES, SERVICE_NAMES, SYNTHETIC_USERS, VENDOR_NAMES,
+};
+use crate::error::{MonitorError, Result};
+use rand::distr::weighted::WeightedIndex;
+use rand::{rng, Rng};
+use rand_distr::{Beta, Distribution, Zipf};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Names of the built-in event profiles accepted by `--event-profile`.
+pub const EVENT_PROFILE_NAMES: [&str; 2] = ["uniform", "enterprise"];
+
+/// Shape of a beta distribution, which data sensitivity is drawn from and scaled to `0..100`.
+///
+/// `alpha` above `beta` leans towards sensitive data, `beta` above `alpha` towards harmless data,
+/// and both at 1 draw every sensitivity equally often.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BetaShape {
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+/// The parameters of an event profile, before they are checked and turned into samplers.
+#[derive(Clone, Debug)]
+struct ProfileParams {
+    service_weights: [f64; SERVICE_NAMES.len()],
+    vendor_weights: [f64; VENDOR_NAMES.len()],
+    department_weights: [f64; DEPARTMENT_NAMES.len()],
+    /// Zipf exponent of user activity; 0 makes every user equally active.
+    user_zipf: f64,
+    /// Sensitivity of the data each department works with.
+    sensitivity: [BetaShape; DEPARTMENT_NAMES.len()],
+    /// Weights of the purposes each department uses AI for.
+    purpose_weights: [[f64; PURPOSE_NAMES.len()]; DEPARTMENT_NAMES.len()],
+}
+
+impl ProfileParams {
+    /// Every attribute drawn uniformly, as the generator always did.
+    fn uniform() -> Self {
+        ProfileParams {
+            service_weights: [1.0; SERVICE_NAMES.len()],
+            vendor_weights: [1.0; VENDOR_NAMES.len()],
+            department_weights: [1.0; DEPARTMENT_NAMES.len()],
+            user_zipf: 0.0,
+            sensitivity: [BetaShape { alpha: 1.0, beta: 1.0 }; DEPARTMENT_NAMES.len()],
+            purpose_weights: [[1.0; PURPOSE_NAMES.len()]; DEPARTMENT_NAMES.len()],
+        }
+    }
+
+    /// A few popular services and a long tail, engineering-heavy usage, a core of power users,
+    /// and departments whose sensitivity and purposes follow their line of work.
+    fn enterprise() -> Self {
+        let beta = |alpha, beta| BetaShape { alpha, beta };
+        ProfileParams {
+            service_weights: zipf_weights(1.0),
+            vendor_weights: zipf_weights(1.0),
+            // Engineering, Marketing, Finance, HR, Legal, Healthcare.
+            department_weights: [40.0, 15.0, 12.0, 8.0, 5.0, 20.0],
+            user_zipf: 1.1,
+            sensitivity: [beta(2.0, 6.0), beta(2.0, 5.0), beta(4.0, 3.0), beta(4.0, 3.0), beta(6.0, 2.0), beta(5.0, 2.0)],
+            // Code generation, HR screening, marketing copy, legal review, customer support, data analysis.
+            purpose_weights: [
+                [8.0, 0.0, 0.5, 0.0, 1.0, 3.0],
+                [0.5, 0.0, 8.0, 0.5, 3.0, 2.0],
+                [1.0, 0.0, 0.5, 1.0, 1.0, 8.0],
+                [0.5, 6.0, 1.0, 1.0, 1.0, 2.0],
+                [0.5, 0.5, 0.5, 8.0, 0.5, 2.0],
+                [0.5, 0.0, 0.5, 0.5, 3.0, 6.0],
+            ],
+        }
+    }
+}
+
+/// An event profile as written in a profile file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    /// Built-in profile the file starts from; `uniform` if omitted.
+    base: Option<String>,
+    /// Weights to override, keyed by name, or `{"zipf": s}` to rank them in the order they
+    /// are listed in the dashboard.
+    services: Option<BTreeMap<String, f64>>,
+    vendors: Option<BTreeMap<String, f64>>,
+    departments: Option<BTreeMap<String, f64>>,
+    users_zipf: Option<f64>,
+    /// Sensitivity of every department's data, before `department_sensitivity` is applied.
+    sensitivity: Option<BetaShape>,
+    #[serde(default)]
+    department_sensitivity: BTreeMap<String, BetaShape>,
+    /// Purpose weights to override, keyed by department and then by purpose.
+    #[serde(default)]
+    department_purposes: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// Distributions the synthetic events are drawn from, selected by `--event-profile`.
+#[derive(Clone, Debug)]
+pub struct EventProfile {
+    services: WeightedIndex<f64>,
+    vendors: WeightedIndex<f64>,
+    departments: WeightedIndex<f64>,
+    users: Zipf<f64>,
+    sensitivity: Vec<Beta<f64>>,
+    purposes: Vec<WeightedIndex<f64>>,
+    lawful_bases: WeightedIndex<f64>,
+    pii_types: WeightedIndex<f64>,
+}
+
+impl Default for EventProfile {
+    fn default() -> Self {
+        Self::from_params(&ProfileParams::uniform()).expect("the uniform profile is valid")
+    }
+}
+
+impl EventProfile {
+    /// Returns the built-in profile with the given name, if there is one.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let params = Self::builtin_params(name)?;
+        Some(Self::from_params(&params).expect("built-in profiles are valid"))
+    }
+
+    fn builtin_params(name: &str) -> Option<ProfileParams> {
+        match name {
+            "uniform" => Some(ProfileParams::uniform()),
+            "enterprise" => Some(ProfileParams::enterprise()),
+            _ => None,
+        }
+    }
+
+    /// Returns the profile selected by `--event-profile`: a built-in profile name or the path of
+    /// a profile file.
+    ///
+    /// A profile file is a JSON object with an optional `base` built-in profile and overrides of
+    /// its distributions, e.g. `{"base": "enterprise", "services": {"zipf": 1.5},
+    /// "department_sensitivity": {"Finance": {"alpha": 6, "beta": 2}}}`.
+    pub fn load(spec: &str) -> Result<Self> {
+        if let Some(profile) = Self::builtin(spec) {
+            return Ok(profile);
+        }
+        let path = Path::new(spec);
+        let invalid = |message: String| MonitorError::Config(format!("event profile {}: {}", path.display(), message));
+        let contents = fs::read_to_string(path).map_err(|e| {
+            invalid(format!("{} (built-in profiles are {})", e, EVENT_PROFILE_NAMES.join(", ")))
+        })?;
+        Self::from_json(&contents).map_err(invalid)
+    }
+
+    /// Parses the contents of a profile file.
+    fn from_json(contents: &str) -> std::result::Result<Self, String> {
+        let file: ProfileFile = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        let base = file.base.as_deref().unwrap_or("uniform");
+        let mut params = Self::builtin_params(base).ok_or_else(|| format!("unknown base profile '{}'", base))?;
+        if let Some(weights) = &file.services {
+            override_weights("service", &SERVICE_NAMES, &mut params.service_weights, weights)?;
+        }
+        if let Some(weights) = &file.vendors {
+            override_weights("vendor", &VENDOR_NAMES, &mut params.vendor_weights, weights)?;
+        }
+        if let Some(weights) = &file.departments {
+            override_weights("department", &DEPARTMENT_NAMES, &mut params.department_weights, weights)?;
+        }
+        if let Some(user_zipf) = file.users_zipf {
+            params.user_zipf = user_zipf;
+        }
+        if let Some(shape) = file.sensitivity {
+            params.sensitivity = [shape; DEPARTMENT_NAMES.len()];
+        }
+        for (department, shape) in &file.department_sensitivity {
+            params.sensitivity[index_of("department", &DEPARTMENT_NAMES, department)?] = *shape;
+        }
+        for (department, weights) in &file.department_purposes {
+            let index = index_of("department", &DEPARTMENT_NAMES, department)?;
+            override_weights("purpose", &PURPOSE_NAMES, &mut params.purpose_weights[index], weights)?;
+        }
+        Self::from_params(&params)
+    }
+
+    /// Checks the parameters and builds a sampler for each distribution.
+    fn from_params(params: &ProfileParams) -> std::result::Result<Self, String> {
+        let weighted = |what: &str, weights: &[f64]| {
+            WeightedIndex::new(weights).map_err(|e| format!("invalid {} weights: {}", what, e))
+        };
+        let sensitivity = params
+            .sensitivity
+            .iter()
+            .zip(DEPARTMENT_NAMES)
+            .map(|(shape, department)| {
+                Beta::new(shape.alpha, shape.beta).map_err(|e| format!("invalid sensitivity of {}: {}", department, e))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        let purposes = params
+            .purpose_weights
+            .iter()
+            .zip(DEPARTMENT_NAMES)
+            .map(|(weights, department)| weighted(&format!("purpose of {}", department), weights))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(EventProfile {
+            services: weighted("service", &params.service_weights)?,
+            vendors: weighted("vendor", &params.vendor_weights)?,
+            departments: weighted("department", &params.department_weights)?,
+            users: Zipf::new(SYNTHETIC_USERS as f64, params.user_zipf).map_err(|e| format!("invalid users_zipf: {}", e))?,
+            sensitivity,
+            purposes,
+            lawful_bases: weighted("lawful basis", &LAWFUL_BASIS_WEIGHTS)?,
+            pii_types: weighted("PII type", &PII_TYPE_WEIGHTS)?,
+        })
+    }
+
+    /// Generates AI events happening at `timestamp_ms`. Events with personal data, i.e. data
+    /// sensitivity of 50 or more, carry one PII type.
+    ///
+    /// On-prem models process their requests in the EU; API models in any region.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of events to generate.
+    /// * `region_weights` - Relative weights of the regions in `REGION_NAMES` API models process
+    ///   requests in, such as those of a traffic pattern; `None` weighs them equally.
+    /// * `timestamp_ms` - Time the events happen, in milliseconds since the Unix epoch.
+    pub fn generate(
+        &self,
+        count: usize,
+        region_weights: Option<&[f64; REGION_NAMES.len()]>,
+        timestamp_ms: u64,
+    ) -> Vec<Event> {
+        let mut events = Vec::with_capacity(count);
+        let mut rng = rng();
+        let regions = region_weights.and_then(|weights| WeightedIndex::new(weights).ok());
+        for _ in 0..count {
+            let name_idx = self.services.sample(&mut rng) as u8;
+            let ai_service = AIService {
+                name_idx,
+                vendor_idx: self.vendors.sample(&mut rng) as u8,
+                model_idx: name_idx * MODELS_PER_SERVICE as u8 + rng.random_range(0..MODELS_PER_SERVICE) as u8,
+            };
+            let department = self.departments.sample(&mut rng);
+            let usage = Usage {
+                department_idx: department as u8,
+                data_sensitivity: (self.sensitivity[department].sample(&mut rng) * 100.0).min(99.0) as u8,
+            };
+            // Zipf draws ranks from 1, so the most active user is user 0.
+            let user = UserId(self.users.sample(&mut rng) as u32 - 1);
+            let purpose = Purpose(self.purposes[department].sample(&mut rng) as u8);
+            let region = if MODEL_DEPLOYMENTS[ai_service.model_idx as usize] == 1 {
+                Region(0)
+            } else {
+                let region = match &regions {
+                    Some(regions) => regions.sample(&mut rng),
+                    None => rng.random_range(0..REGION_NAMES.len()),
+                };
+                Region(region as u8)
+            };
+            let basis = LawfulBasis(self.lawful_bases.sample(&mut rng) as u8);
+            let pii = if usage.data_sensitivity >= 50 {
+                PiiTypes(1 << self.pii_types.sample(&mut rng))
+            } else {
+                PiiTypes::NONE
+            };
+            events.push((ai_service, usage, user, purpose, region, basis, pii, Timestamp(timestamp_ms)));
+        }
+        events
+    }
+}
+
+/// Returns weights falling off with the `exponent`-th power of the rank, the first name ranked 1.
+fn zipf_weights<const N: usize>(exponent: f64) -> [f64; N] {
+    std::array::from_fn(|rank| 1.0 / ((rank + 1) as f64).powf(exponent))
+}
+
+/// Returns the index of `name` in `names`.
+fn index_of(what: &str, names: &[&str], name: &str) -> std::result::Result<usize, String> {
+    names
+        .iter()
+        .position(|candidate| *candidate == name)
+        .ok_or_else(|| format!("unknown {} '{}' (expected one of {})", what, name, names.join(", ")))
+}
+
+/// Replaces `weights` by Zipf weights if `overrides` is `{"zipf": s}`, and otherwise overrides
+/// the weights of the names it lists.
+fn override_weights<const N: usize>(
+    what: &str,
+    names: &[&str; N],
+    weights: &mut [f64; N],
+    overrides: &BTreeMap<String, f64>,
+) -> std::result::Result<(), String> {
+    if let Some(&exponent) = overrides.get("zipf") {
+        if overrides.len() > 1 {
+            return Err(format!("{} weights take either a zipf exponent or weights by name, not both", what));
+        }
+        *weights = zipf_weights(exponent);
+        return Ok(());
+    }
+    for (name, &weight) in overrides {
+        weights[index_of(what, names, name)?] = weight;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_skew_services_and_correlate_departments_with_sensitivity() {
+        let mean_sensitivity = |events: &[Event], department: u8| {
+            let sensitivities: Vec<_> = events
+                .iter()
+                .filter(|(_, usage, ..)| usage.department_idx == department)
+                .map(|(_, usage, ..)| usage.data_sensitivity as f64)
+                .collect();
+            sensitivities.iter().sum::<f64>() / sensitivities.len() as f64
+        };
+        let count = |events: &[Event], service: u8| {
+            events.iter().filter(|(ai_service, ..)| ai_service.name_idx == service).count()
+        };
+
+        let enterprise = EventProfile::load("enterprise").unwrap().generate(20_000, None, 0);
+        assert!(count(&enterprise, 0) > 2 * count(&enterprise, 4));
+        // Engineering works with harmless data, Legal with sensitive data.
+        assert!(mean_sensitivity(&enterprise, 0) < 40.0);
+        assert!(mean_sensitivity(&enterprise, 4) > 60.0);
+        assert!(enterprise.iter().all(|(_, usage, user, ..)| usage.data_sensitivity < 100 && user.0 < SYNTHETIC_USERS));
+
+        let uniform = EventProfile::default().generate(20_000, None, 0);
+        assert!(count(&uniform, 0).abs_diff(count(&uniform, 4)) < 800);
+
+        let custom = EventProfile::from_json(
+            r#"{"base": "enterprise", "services": {"Stable Diffusion": 1000}, "department_sensitivity": {"Engineering": {"alpha": 8, "beta": 1}}}"#,
+        )
+        .unwrap()
+        .generate(20_000, None, 0);
+        assert!(count(&custom, 4) > count(&custom, 0));
+        assert!(mean_sensitivity(&custom, 0) > 80.0);
+
+        assert!(EventProfile::from_json(r#"{"departments": {"Sales": 1}}"#).unwrap_err().contains("unknown department"));
+        assert!(EventProfile::from_json(r#"{"services": {"zipf": 1, "Claude": 2}}"#).is_err());
+        assert!(EventProfile::from_json(r#"{"sensitivity": {"alpha": 0, "beta": 1}}"#).is_err());
+        assert!(EventProfile::load("no-such-profile.json").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::audit::AuditRecord;
+use crossbeam_channel::{Sender, TrySendError};
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// How often each worker forwards its reservoir of sampled events to the aggregator.
+pub const SAMPLE_PERIOD: Duration = Duration::from_secs(1);
+
+/// Most sampled events the aggregator keeps for the event inspector and exports.
+pub const MAX_RETAINED_SAMPLED_EVENTS: usize = 1000;
+
+/// Reservoirs of sampled events buffered between the workers and the aggregator; a worker drops
+/// its reservoir rather than wait while the channel is full.
+pub const SAMPLE_CHANNEL_CAPACITY: usize = 64;
+
+/// An evaluated event drawn uniformly from those its worker evaluated in one sampling period.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SampledEvent {
+    /// Id of the event in the audit log, if the audit log is enabled.
+    pub event_id: Option<u64>,
+    pub timestamp_ms: u64,
+    /// Index of the worker that evaluated the event.
+    pub worker: usize,
+    /// Events this one stands for: those its worker evaluated in the sampling period, divided by
+    /// the events sampled from them.
+    pub weight: f64,
+    pub service: Cow<'static, str>,
+    pub model: Cow<'static, str>,
+    pub vendor: Cow<'static, str>,
+    pub department: Cow<'static, str>,
+    pub data_sensitivity: u8,
+    pub user_id: Option<u32>,
+    pub purpose: Option<Cow<'static, str>>,
+    pub region: Option<Cow<'static, str>>,
+    pub lawful_basis: Option<Cow<'static, str>>,
+    pub pii_types: Vec<Cow<'static, str>>,
+    pub ai_act_tier: Option<Cow<'static, str>>,
+    /// Keys of the compliance checks the event failed.
+    pub violations: Vec<Cow<'static, str>>,
+    /// Keys of the failed checks exempted by a waiver; they are not listed in `violations`.
+    pub waived: Vec<Cow<'static, str>>,
+    pub risk_score: u8,
+}
+
+impl SampledEvent {
+    /// Builds a sampled event from the decision record of an evaluated event.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The event's decision record, built as for the audit log.
+    /// * `event_id` - Id of the event if it was tagged with one for the audit log.
+    /// * `worker` - Index of the worker that evaluated the event.
+    pub fn new(record: AuditRecord, event_id: Option<u64>, worker: usize) -> Self {
+        SampledEvent {
+            event_id,
+            timestamp_ms: record.timestamp_ms,
+            worker,
+            weight: 1.0,
+            service: record.service.into(),
+            model: record.model.into(),
+            vendor: record.vendor.into(),
+            department: record.department.into(),
+            data_sensitivity: record.data_sensitivity,
+            user_id: record.user_id,
+            purpose: record.purpose.map(Cow::Borrowed),
+            region: record.region.map(Cow::Borrowed),
+            lawful_basis: record.lawful_basis.map(Cow::Borrowed),
+            pii_types: record.pii_types.into_iter().map(Cow::Borrowed).collect(),
+            ai_act_tier: record.ai_act_tier.map(Cow::Borrowed),
+            violations: record.violations.into_iter().map(Cow::Borrowed).collect(),
+            waived: record.waived.into_iter().map(Cow::Borrowed).collect(),
+            risk_score: record.risk_score,
+        }
+    }
+}
+
+/// Reservoir sampler of a single worker's evaluated events.
+///
+/// Every event of a sampling period has the same chance of being in the reservoir when it is
+/// forwarded, however the period's events were split into batches. Only the events that enter
+/// the reservoir are decoded, so the cost of sampling does not grow with the event rate.
+pub struct EventSampler {
+    worker: usize,
+    capacity: usize,
+    sender: Option<Sender<Vec<SampledEvent>>>,
+    reservoir: Vec<SampledEvent>,
+    /// Events evaluated since the reservoir was last forwarded.
+    seen: u64,
+    /// Monitor time at which the current sampling period started.
+    period_start: Option<Duration>,
+}
+
+impl EventSampler {
+    /// Creates an empty sampler.
+    ///
+    /// # Arguments
+    ///
+    /// * `worker` - Index of the worker owning the sampler.
+    /// * `capacity` - Most events forwarded per sampling period.
+    /// * `sender` - Channel to the aggregator.
+    pub fn new(worker: usize, capacity: usize, sender: Sender<Vec<SampledEvent>>) -> Self {
+        EventSampler {
+            worker,
+            capacity: capacity.max(1),
+            sender: Some(sender),
+            reservoir: Vec::with_capacity(capacity.max(1)),
+            seen: 0,
+            period_start: None,
+        }
+    }
+
+    /// Returns the index of the worker owning the sampler.
+    pub fn worker(&self) -> usize {
+        self.worker
+    }
+
+    /// Counts a batch of `len` events and draws which of them enter the reservoir.
+    ///
+    /// Returns the batch position of each event to keep with the reservoir slot it takes,
+    /// ordered by batch position; decode those events and pass them to [`EventSampler::fill`].
+    pub fn choose(&mut self, len: usize) -> Vec<(usize, usize)> {
+        if self.sender.is_none() {
+            return Vec::new();
+        }
+        let mut rng = rng();
+        let mut slots: HashMap<usize, usize> = HashMap::new();
+        for index in 0..len {
+            self.seen += 1;
+            let slot = if self.seen <= self.capacity as u64 {
+                (self.seen - 1) as usize
+            } else {
+                match rng.random_range(0..self.seen) as usize {
+                    slot if slot < self.capacity => slot,
+                    _ => continue,
+                }
+            };
+            // A later event drawn into the same slot replaces the earlier one.
+            slots.insert(slot, index);
+        }
+        let mut chosen: Vec<(usize, usize)> = slots.into_iter().map(|(slot, index)| (index, slot)).collect();
+        chosen.sort_unstable();
+        chosen
+    }
+
+    /// Puts the events drawn by [`EventSampler::choose`] in their slots.
+    ///
+    /// # Arguments
+    ///
+    /// * `chosen` - Batch positions and slots returned by `choose`.
+    /// * `events` - The decoded events at those positions, in the same order.
+    pub fn fill(&mut self, chosen: &[(usize, usize)], events: Vec<SampledEvent>) {
+        let mut placed: Vec<(usize, SampledEvent)> = chosen.iter().map(|&(_, slot)| slot).zip(events).collect();
+        placed.sort_unstable_by_key(|&(slot, _)| slot);
+        for (slot, event) in placed {
+            match self.reservoir.get_mut(slot) {
+                Some(existing) => *existing = event,
+                None => self.reservoir.push(event),
+            }
+        }
+    }
+
+    /// Forwards the reservoir to the aggregator once a sampling period has passed since the
+    /// last one.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current monitor time.
+    pub fn forward_if_due(&mut self, now: Duration) {
+        let started = *self.period_start.get_or_insert(now);
+        if now.saturating_sub(started) >= SAMPLE_PERIOD {
+            self.forward();
+            self.period_start = Some(now);
+        }
+    }
+
+    /// Forwards the reservoir to the aggregator and starts a new sampling period.
+    pub fn forward(&mut self) {
+        if self.reservoir.is_empty() {
+            return;
+        }
+        let weight = self.seen as f64 / self.reservoir.len() as f64;
+        let mut events = std::mem::replace(&mut self.reservoir, Vec::with_capacity(self.capacity));
+        for event in &mut events {
+            event.weight = weight;
+        }
+        self.seen = 0;
+        if let Some(sender) = &self.sender
+            && let Err(TrySendError::Disconnected(_)) = sender.try_send(events)
+        {
+            self.sender = None;
+        }
+    }
+}
+
+/// Estimated events and violations of one service in one department, from the sampled events.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SampleGroup {
+    pub service: String,
+    pub department: String,
+    pub sampled: usize,
+    /// Sum of the weights of the sampled events.
+    pub estimated_events: f64,
+    /// Sum of the weights of the sampled events with at least one violation.
+    pub estimated_violations: f64,
+}
+
+/// The most recent sampled events, kept by the aggregator.
+#[derive(Debug, Default)]
+pub struct RecentSamples {
+    events: VecDeque<SampledEvent>,
+}
+
+impl RecentSamples {
+    /// Adds a forwarded reservoir, dropping the oldest events beyond
+    /// [`MAX_RETAINED_SAMPLED_EVENTS`].
+    pub fn add(&mut self, events: Vec<SampledEvent>) {
+        self.events.extend(events);
+        let excess = self.events.len().saturating_sub(MAX_RETAINED_SAMPLED_EVENTS);
+        self.events.drain(..excess);
+    }
+
+    /// Returns the number of retained events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if no events have been sampled yet.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the retained events, newest first.
+    pub fn newest_first(&self) -> Vec<SampledEvent> {
+        self.events.iter().rev().cloned().collect()
+    }
+}
+
+/// Groups sampled events by service and department and returns the `limit` groups with the most
+/// estimated violations, then the most estimated events.
+pub fn top_groups(events: &[SampledEvent], limit: usize) -> Vec<SampleGroup> {
+    let mut groups: HashMap<(&str, &str), SampleGroup> = HashMap::new();
+    for event in events {
+        let group = groups.entry((event.service.as_ref(), event.department.as_ref())).or_insert_with(|| SampleGroup {
+            service: event.service.to_string(),
+            department: event.department.to_string(),
+            sampled: 0,
+            estimated_events: 0.0,
+            estimated_violations: 0.0,
+        });
+        group.sampled += 1;
+        group.estimated_events += event.weight;
+        if !event.violations.is_empty() {
+            group.estimated_violations += event.weight;
+        }
+    }
+    let mut groups: Vec<SampleGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.estimated_violations
+            .total_cmp(&a.estimated_violations)
+            .then(b.estimated_events.total_cmp(&a.estimated_events))
+            .then_with(|| (&a.service, &a.department).cmp(&(&b.service, &b.department)))
+    });
+    groups.truncate(limit);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+
+    fn event(index: usize) -> SampledEvent {
+        SampledEvent {
+            event_id: Some(index as u64),
+            timestamp_ms: 0,
+            worker: 0,
+            weight: 1.0,
+            service: "ChatGPT".into(),
+            model: "GPT-4".into(),
+            vendor: "OpenAI".into(),
+            department: if index.is_multiple_of(2) { "Finance".into() } else { "Legal".into() },
+            data_sensitivity: 50,
+            user_id: None,
+            purpose: None,
+            region: None,
+            lawful_basis: None,
+            pii_types: Vec::new(),
+            ai_act_tier: None,
+            violations: if index.is_multiple_of(4) { vec!["gdpr".into()] } else { Vec::new() },
+            waived: Vec::new(),
+            risk_score: 0,
+        }
+    }
+
+    /// Samples `batches` batches of `len` events, decoding each chosen position into its event.
+    fn sample(sampler: &mut EventSampler, batches: usize, len: usize) {
+        for batch in 0..batches {
+            let chosen = sampler.choose(len);
+            assert!(chosen.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            let events = chosen.iter().map(|&(index, _)| event(batch * len + index)).collect();
+            sampler.fill(&chosen, events);
+        }
+    }
+
+    #[test]
+    fn reservoir_is_a_uniform_weighted_sample_of_the_period() {
+        let (sender, receiver) = unbounded();
+        let mut sampler = EventSampler::new(0, 10, sender);
+        // Fewer events than the reservoir holds are all kept, each standing for itself.
+        sample(&mut sampler, 1, 4);
+        sampler.forward();
+        let forwarded = receiver.try_recv().unwrap();
+        assert_eq!(forwarded.len(), 4);
+        assert!(forwarded.iter().all(|event| event.weight == 1.0));
+
+        // Over many periods, every position in a 1000-event period is kept about equally often.
+        let mut kept = vec![0u32; 1000];
+        for _ in 0..2000 {
+            sample(&mut sampler, 10, 100);
+            sampler.forward();
+            let forwarded = receiver.try_recv().unwrap();
+            assert_eq!(forwarded.len(), 10);
+            assert!(forwarded.iter().all(|event| event.weight == 100.0));
+            for event in forwarded {
+                kept[event.event_id.unwrap() as usize] += 1;
+            }
+        }
+        // Each position is expected 20 times; the first and last batches are no exception.
+        let first: u32 = kept[..100].iter().sum();
+        let last: u32 = kept[900..].iter().sum();
+        assert!((1600..2400).contains(&first), "first batch kept {} times", first);
+        assert!((1600..2400).contains(&last), "last batch kept {} times", last);
+    }
+
+    #[test]
+    fn top_groups_rank_estimated_violations() {
+        let mut recent = RecentSamples::default();
+        recent.add((0..MAX_RETAINED_SAMPLED_EVENTS + 8).map(event).collect());
+        assert_eq!(recent.len(), MAX_RETAINED_SAMPLED_EVENTS);
+        let newest = recent.newest_first();
+        assert_eq!(newest[0].event_id, Some(MAX_RETAINED_SAMPLED_EVENTS as u64 + 7));
+
+        let groups = top_groups(&newest, 5);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].department, "Finance");
+        assert_eq!(groups[0].sampled, 500);
+        assert_eq!(groups[0].estimated_violations, 250.0);
+        assert_eq!(groups[1].estimated_violations, 0.0);
+    }
+}
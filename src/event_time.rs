@@ -0,0 +1,224 @@
+use crate::components::Event;
+use crate::metrics::{ComplianceMetrics, IntervalMetrics};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Splits a batch into runs of consecutive events whose timestamps fall into the same window,
+/// each with the start of its window in milliseconds since the Unix epoch.
+///
+/// # Arguments
+///
+/// * `events` - The batch, normally in timestamp order.
+/// * `window` - Length of the windows, aligned to the Unix epoch.
+pub fn window_runs(events: &[Event], window: Duration) -> impl Iterator<Item = (u64, &[Event])> {
+    let window_ms = (window.as_millis() as u64).max(1);
+    events
+        .chunk_by(move |a, b| a.7.0 / window_ms == b.7.0 / window_ms)
+        .map(move |run| (run[0].7.0 / window_ms * window_ms, run))
+}
+
+/// How many events arrived out of timestamp order, and how late.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LateArrivals {
+    /// Events older than one before them but within the allowed lateness, counted in their own
+    /// window.
+    pub reordered: u64,
+    /// Events whose window had already closed, counted in the oldest open window instead.
+    pub late: u64,
+    /// Most an event's timestamp trailed the newest one before it, in milliseconds.
+    pub max_delay_ms: u64,
+}
+
+impl LateArrivals {
+    /// Returns the events that arrived out of timestamp order.
+    pub fn total(&self) -> u64 {
+        self.reordered.saturating_add(self.late)
+    }
+
+    /// Renders the statistics as a line for the log.
+    pub fn render(&self) -> String {
+        format!(
+            "{} events arrived out of order, up to {:.1}s late: {} within the allowed lateness, {} beyond it and counted in a later window",
+            self.total(),
+            self.max_delay_ms as f64 / 1000.0,
+            self.reordered,
+            self.late
+        )
+    }
+}
+
+/// Accumulates metrics into event-time windows: fixed windows of the time the events happened,
+/// rather than of when they were processed, so replayed and imported logs report the timeline
+/// they recorded.
+///
+/// Windows close by a watermark trailing the newest timestamp seen by the allowed lateness, so
+/// events that arrive out of order, such as those of machines with skewed clocks, still count in
+/// their own window as long as it is open. Events of a window that has already closed are late
+/// and are counted in the oldest open window. A stretch of windows without any events closes as
+/// a single empty interval, so the history keeps its timeline without a point per empty window.
+pub struct EventTimeWindows {
+    window: Duration,
+    allowed_lateness: Duration,
+    /// Open windows by their start in milliseconds since the Unix epoch.
+    open: BTreeMap<u64, ComplianceMetrics>,
+    /// End of the newest closed window in milliseconds since the Unix epoch, if any has closed.
+    closed_until: Option<u64>,
+    /// Newest event timestamp seen, in milliseconds since the Unix epoch.
+    newest: Option<u64>,
+    arrivals: LateArrivals,
+}
+
+impl EventTimeWindows {
+    /// Creates the accumulator with no window open.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Length of the windows, aligned to the Unix epoch.
+    /// * `allowed_lateness` - How far the watermark trails the newest timestamp seen.
+    pub fn new(window: Duration, allowed_lateness: Duration) -> Self {
+        EventTimeWindows {
+            window: window.max(Duration::from_millis(1)),
+            allowed_lateness,
+            open: BTreeMap::new(),
+            closed_until: None,
+            newest: None,
+            arrivals: LateArrivals::default(),
+        }
+    }
+
+    /// Length of the windows.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Events that arrived out of order so far.
+    pub fn arrivals(&self) -> LateArrivals {
+        self.arrivals
+    }
+
+    /// Adds the metrics of a run of events from one window, as given by [`window_runs`], and
+    /// returns the intervals this closes, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Start of the events' window.
+    /// * `run` - The events, whose timestamps advance the watermark.
+    /// * `metrics` - Metrics of the events.
+    pub fn add(&mut self, start: u64, run: &[Event], metrics: &ComplianceMetrics) -> Vec<IntervalMetrics> {
+        let window_ms = self.window.as_millis() as u64;
+        let late = self.closed_until.is_some_and(|closed_until| start + window_ms <= closed_until);
+        for (.., timestamp) in run {
+            let delay = self.newest.map_or(0, |newest| newest.saturating_sub(timestamp.0));
+            if delay > 0 {
+                if late {
+                    self.arrivals.late += 1;
+                } else {
+                    self.arrivals.reordered += 1;
+                }
+                self.arrivals.max_delay_ms = self.arrivals.max_delay_ms.max(delay);
+            }
+            self.newest = self.newest.max(Some(timestamp.0));
+        }
+        let start = match (late, self.open.keys().next()) {
+            (true, Some(&oldest)) => oldest,
+            (true, None) => self.closed_until.unwrap_or(start),
+            (false, _) => start,
+        };
+        self.open.entry(start).or_default().merge(metrics);
+
+        let watermark = self.newest.unwrap_or(0).saturating_sub(self.allowed_lateness.as_millis() as u64);
+        let mut closed = Vec::new();
+        while let Some(entry) = self.open.first_entry()
+            && entry.key() + window_ms <= watermark
+        {
+            let (start, counts) = entry.remove_entry();
+            self.close(start, counts, &mut closed);
+        }
+        closed
+    }
+
+    /// Closes every open window, returning their intervals oldest first.
+    pub fn finish(&mut self) -> Vec<IntervalMetrics> {
+        let mut closed = Vec::new();
+        while let Some((start, counts)) = self.open.pop_first() {
+            self.close(start, counts, &mut closed);
+        }
+        closed
+    }
+
+    /// Closes the window starting at `start`, preceded by an empty interval over any gap since
+    /// the previous one closed.
+    fn close(&mut self, start: u64, counts: ComplianceMetrics, closed: &mut Vec<IntervalMetrics>) {
+        if let Some(end) = self.closed_until
+            && end < start
+        {
+            let gap = Duration::from_millis(start - end);
+            closed.push(IntervalMetrics::new(ComplianceMetrics::default(), gap, start));
+        }
+        let end = start + self.window.as_millis() as u64;
+        closed.push(IntervalMetrics::new(counts, self.window, end));
+        self.closed_until = Some(end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Timestamp;
+    use crate::ecs::generate_ai_events;
+
+    fn events_counted(events: u64) -> ComplianceMetrics {
+        ComplianceMetrics { total_events: events, ..ComplianceMetrics::default() }
+    }
+
+    /// Windows the events by 5 seconds, returning the end, span, and events of each interval, and
+    /// the events that arrived out of order.
+    fn windowed(events: &[Event], allowed_lateness: Duration) -> (Vec<(u64, Duration, u64)>, LateArrivals) {
+        let mut windows = EventTimeWindows::new(Duration::from_secs(5), allowed_lateness);
+        let mut closed = Vec::new();
+        for (start, run) in window_runs(events, windows.window()) {
+            closed.extend(windows.add(start, run, &events_counted(run.len() as u64)));
+        }
+        closed.extend(windows.finish());
+        let intervals = closed.iter().map(|interval| (interval.unix_ms, interval.span, interval.counts.total_events)).collect();
+        (intervals, windows.arrivals())
+    }
+
+    #[test]
+    fn metrics_are_windowed_by_event_time() {
+        let mut events = generate_ai_events(6);
+        for (event, timestamp_ms) in events.iter_mut().zip([1_000, 4_999, 5_000, 7_000, 21_000, 2_000]) {
+            event.7 = Timestamp(timestamp_ms);
+        }
+        let runs: Vec<(u64, usize)> = window_runs(&events, Duration::from_secs(5)).map(|(start, run)| (start, run.len())).collect();
+        assert_eq!(runs, [(0, 2), (5_000, 2), (20_000, 1), (0, 1)]);
+
+        let (intervals, arrivals) = windowed(&events, Duration::ZERO);
+        assert_eq!(arrivals, LateArrivals { reordered: 0, late: 1, max_delay_ms: 19_000 });
+        assert_eq!(
+            intervals,
+            [
+                (5_000, Duration::from_secs(5), 2),
+                (10_000, Duration::from_secs(5), 2),
+                // The empty windows from 10s to 20s close as one interval.
+                (20_000, Duration::from_secs(10), 0),
+                // The late event from the first window is counted in the open one.
+                (25_000, Duration::from_secs(5), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_within_the_allowed_lateness_count_in_their_own_window() {
+        let mut events = generate_ai_events(5);
+        for (event, timestamp_ms) in events.iter_mut().zip([1_000, 6_000, 3_000, 12_000, 4_000]) {
+            event.7 = Timestamp(timestamp_ms);
+        }
+        let (intervals, arrivals) = windowed(&events, Duration::from_secs(4));
+        // The event 3 seconds late is within the allowed lateness; the one 8 seconds late is not.
+        assert_eq!(arrivals, LateArrivals { reordered: 1, late: 1, max_delay_ms: 8_000 });
+        let span = Duration::from_secs(5);
+        assert_eq!(intervals, [(5_000, span, 2), (10_000, span, 2), (15_000, span, 1)]);
+    }
+}
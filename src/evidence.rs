@@ -0,0 +1,327 @@
+use crate::audit::signature_path;
+use crate::clock::now_millis;
+use crate::components::EvidenceArgs;
+use crate::constants::{DEPARTMENT_NAMES, MODEL_NAMES, PII_TYPE_NAMES, SERVICE_NAMES, VENDOR_NAMES};
+use crate::error::{MonitorError, Result};
+use crate::nist;
+use crate::policy::Policy;
+use crate::signing::{load_signing_key, to_hex};
+use crate::vendors::VendorProfile;
+use ed25519_dalek::Signer;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Manifest format version; bumped whenever the bundle layout changes.
+const MANIFEST_VERSION: u32 = 1;
+
+/// Audit frameworks the bundle is laid out for, recorded in the manifest.
+const FRAMEWORKS: [&str; 2] = ["ISO/IEC 42001", "SOC 2"];
+
+/// A file packaged into an evidence bundle, as listed in the manifest.
+#[derive(Serialize, Debug)]
+pub struct ManifestEntry {
+    /// Path of the file inside the bundle.
+    pub path: String,
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 digest of the file contents.
+    pub sha256: String,
+}
+
+/// Index of an evidence bundle, signed so auditors can detect tampering.
+#[derive(Serialize, Debug)]
+pub struct Manifest {
+    pub version: u32,
+    pub created_at_ms: u64,
+    /// Version of the monitor that built the bundle.
+    pub generator: String,
+    pub frameworks: [&'static str; 2],
+    pub policy_version: &'static str,
+    pub files: Vec<ManifestEntry>,
+    pub signature_algorithm: &'static str,
+    /// Hex-encoded Ed25519 public key verifying `manifest.sig`.
+    pub public_key: String,
+}
+
+/// The active policy configuration as recorded in the bundle.
+#[derive(Serialize)]
+struct PolicyRecord<'a> {
+    version: &'static str,
+    compliance_checks: Vec<CheckRecord>,
+    risk_factors: Vec<RiskFactorRecord<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hipaa: Option<HipaaRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slo: Option<SloRecord>,
+    /// The vendor registry, keyed by vendor name as in a policy file.
+    vendors: BTreeMap<&'static str, &'a VendorProfile>,
+    /// Approved uses of each department restricted by the allowlist.
+    allowlist: BTreeMap<&'static str, Vec<ApprovedUseRecord>>,
+    /// Rules of the content scanner, in the order they are applied.
+    content_scan: Vec<ScanRuleRecord<'a>>,
+}
+
+/// A compliance check with the NIST AI RMF categories it addresses.
+#[derive(Serialize)]
+struct CheckRecord {
+    key: &'static str,
+    name: &'static str,
+    nist_categories: &'static [&'static str],
+}
+
+/// A risk factor and its weight.
+#[derive(Serialize)]
+struct RiskFactorRecord<'a> {
+    key: &'a str,
+    name: &'a str,
+    weight: u8,
+}
+
+/// Settings of the HIPAA rule pack.
+#[derive(Serialize)]
+struct HipaaRecord {
+    baa_vendors: Vec<&'static str>,
+}
+
+/// A service, vendor, and model combination approved by the allowlist; missing parts match anything.
+#[derive(Serialize)]
+struct ApprovedUseRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vendor: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'static str>,
+}
+
+/// A content scanning rule, with its pattern as a regular expression.
+#[derive(Serialize)]
+struct ScanRuleRecord<'a> {
+    name: &'a str,
+    pattern: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pii_type: Option<&'static str>,
+    sensitivity: u8,
+}
+
+/// Per-department compliance SLOs.
+#[derive(Serialize)]
+struct SloRecord {
+    window_secs: u64,
+    burn_rate_alert: f64,
+    targets: BTreeMap<&'static str, f64>,
+}
+
+impl<'a> PolicyRecord<'a> {
+    fn new(policy: &'a Policy) -> Self {
+        PolicyRecord {
+            version: policy.version,
+            compliance_checks: policy
+                .compliance
+                .iter()
+                .map(|(_, info)| CheckRecord { key: info.key, name: info.name, nist_categories: nist::categories_for(info.key) })
+                .collect(),
+            risk_factors: policy
+                .risk_factors
+                .iter()
+                .map(|(_, factor)| RiskFactorRecord { key: &factor.key, name: &factor.name, weight: factor.weight })
+                .collect(),
+            hipaa: policy.hipaa.map(|rules| HipaaRecord {
+                baa_vendors: VENDOR_NAMES
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| rules.baa_vendors & (1 << i) != 0)
+                    .map(|(_, name)| *name)
+                    .collect(),
+            }),
+            slo: policy.slo.as_ref().map(|slo| SloRecord {
+                window_secs: slo.window.as_secs(),
+                burn_rate_alert: slo.burn_rate_alert,
+                targets: slo
+                    .targets
+                    .iter()
+                    .map(|target| (DEPARTMENT_NAMES[target.department as usize], target.target))
+                    .collect(),
+            }),
+            vendors: policy.vendors.iter().collect(),
+            allowlist: policy
+                .allowlist
+                .iter()
+                .map(|(department, approved)| {
+                    let records = approved
+                        .iter()
+                        .map(|approved_use| ApprovedUseRecord {
+                            service: approved_use.service.map(|index| SERVICE_NAMES[index as usize]),
+                            vendor: approved_use.vendor.map(|index| VENDOR_NAMES[index as usize]),
+                            model: approved_use.model.map(|index| MODEL_NAMES[index as usize]),
+                        })
+                        .collect();
+                    (department, records)
+                })
+                .collect(),
+            content_scan: policy
+                .content_scan
+                .rules()
+                .iter()
+                .map(|rule| ScanRuleRecord {
+                    name: &rule.name,
+                    pattern: rule.pattern.as_str(),
+                    pii_type: rule.pii_type.map(|index| PII_TYPE_NAMES[index as usize]),
+                    sensitivity: rule.sensitivity,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Packages audit evidence into a zip archive with a signed manifest.
+///
+/// The bundle holds the audit log and its chain signature, if any, under `audit/`, metrics
+/// snapshots under `snapshots/`, the active policy as `policy/active_policy.json` next to the
+/// policy and risk factor files it was built from, and `manifest.json` listing the SHA-256
+/// digest of every other file. The manifest is signed with Ed25519 and the signature stored in
+/// `manifest.sig`.
+///
+/// # Arguments
+///
+/// * `args` - The `export-evidence` arguments naming the files to package and the signing key.
+/// * `policy` - The active policy.
+/// * `policy_files` - The policy and risk factor files the policy was built from, if any.
+///
+/// # Returns
+///
+/// The manifest written to the bundle.
+pub fn export_evidence(args: &EvidenceArgs, policy: &Policy, policy_files: &[&Path]) -> Result<Manifest> {
+    let signing_key = load_signing_key(&args.signing_key)?;
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(&args.output)?));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut files = Vec::new();
+
+    if let Some(audit_log) = &args.audit_log {
+        files.push(add_file(&mut zip, options, "audit", audit_log)?);
+        let signature = signature_path(audit_log);
+        if signature.exists() {
+            files.push(add_file(&mut zip, options, "audit", &signature)?);
+        }
+    }
+    for snapshot in &args.snapshots {
+        files.push(add_file(&mut zip, options, "snapshots", snapshot)?);
+    }
+    for path in policy_files {
+        files.push(add_file(&mut zip, options, "policy", path)?);
+    }
+    let active_policy = serde_json::to_vec_pretty(&PolicyRecord::new(policy)).map_err(io::Error::from)?;
+    files.push(add_bytes(&mut zip, options, "policy/active_policy.json".to_string(), &active_policy)?);
+
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        created_at_ms: now_millis(),
+        generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        frameworks: FRAMEWORKS,
+        policy_version: policy.version,
+        files,
+        signature_algorithm: "ed25519",
+        public_key: to_hex(signing_key.verifying_key().as_bytes()),
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest).map_err(io::Error::from)?;
+    let signature = signing_key.sign(&manifest_bytes);
+    add_bytes(&mut zip, options, "manifest.json".to_string(), &manifest_bytes)?;
+    add_bytes(&mut zip, options, "manifest.sig".to_string(), to_hex(&signature.to_bytes()).as_bytes())?;
+    zip.finish().map_err(zip_error)?.flush()?;
+    Ok(manifest)
+}
+
+/// Streams a file into the bundle under `dir`, hashing it on the way.
+fn add_file<W: Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    options: SimpleFileOptions,
+    dir: &str,
+    path: &Path,
+) -> Result<ManifestEntry> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| MonitorError::Config(format!("{} is not a file", path.display())))?;
+    let name = format!("{}/{}", dir, file_name.to_string_lossy());
+    let mut file = File::open(path)?;
+    zip.start_file(name.as_str(), options).map_err(zip_error)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut bytes = 0;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        zip.write_all(&buffer[..read])?;
+        bytes += read as u64;
+    }
+    Ok(ManifestEntry { path: name, bytes, sha256: to_hex(&hasher.finalize()) })
+}
+
+/// Writes generated contents into the bundle.
+fn add_bytes<W: Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    options: SimpleFileOptions,
+    name: String,
+    contents: &[u8],
+) -> Result<ManifestEntry> {
+    zip.start_file(name.as_str(), options).map_err(zip_error)?;
+    zip.write_all(contents)?;
+    Ok(ManifestEntry { path: name, bytes: contents.len() as u64, sha256: to_hex(&Sha256::digest(contents)) })
+}
+
+/// Wraps a zip error as an I/O error.
+fn zip_error(e: zip::result::ZipError) -> MonitorError {
+    MonitorError::Io(io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::from_hex;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use std::fs;
+    use std::path::PathBuf;
+    use zip::ZipArchive;
+
+    #[test]
+    fn bundle_manifest_is_signed_and_lists_every_file() {
+        let dir = std::env::temp_dir().join(format!("evidence-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let signing_key = dir.join("key.hex");
+        fs::write(&signing_key, format!("{}\n", "2a".repeat(32))).unwrap();
+        let audit_log = dir.join("audit.ndjson");
+        fs::write(&audit_log, "{\"event_id\":1}\n").unwrap();
+        let args = EvidenceArgs {
+            output: dir.join("evidence.zip"),
+            signing_key,
+            audit_log: Some(audit_log),
+            snapshots: Vec::<PathBuf>::new(),
+        };
+        let manifest = export_evidence(&args, &Policy::builtin(), &[]).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&args.output).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut contents = Vec::new();
+            archive.by_name(name).unwrap().read_to_end(&mut contents).unwrap();
+            contents
+        };
+        let audit = read("audit/audit.ndjson");
+        let manifest_bytes = read("manifest.json");
+        let signature = from_hex(std::str::from_utf8(&read("manifest.sig")).unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let paths: Vec<&str> = manifest.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["audit/audit.ndjson", "policy/active_policy.json"]);
+        assert_eq!(manifest.files[0].sha256, to_hex(&Sha256::digest(&audit)));
+        let public_key: [u8; 32] = from_hex(&manifest.public_key).unwrap().try_into().unwrap();
+        let signature = Signature::from_slice(&signature).unwrap();
+        assert!(VerifyingKey::from_bytes(&public_key).unwrap().verify(&manifest_bytes, &signature).is_ok());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::components::{AiActTier, AI_ACT_TIERS};
+use crate::constants::*;
+use crate::event_sample::SampledEvent;
+use crate::metrics::{CumulativeMetrics, IntervalMetrics};
+use crate::nist::{self, NistFunction, NIST_FUNCTIONS};
+use crate::policy::Policy;
+use crate::sink::Sink;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Sink name used in error reports from the CSV exporter.
+pub const CSV_SINK: &str = "CSV export";
+
+/// Maximum number of reporting intervals retained in memory for on-demand exports.
+pub const MAX_RETAINED_SAMPLES: usize = 10_000;
+
+/// Appends reporting intervals to the CSV time series files in `dir`, creating them as needed.
+///
+/// One file is written per series: `rates.csv`, `violations.csv`, `risk.csv`,
+/// `services.csv`, `vendors.csv`, `departments.csv`, `purposes.csv`, `purpose_violations.csv`,
+/// `regions.csv`, `region_violations.csv`, `ai_act_tiers.csv`, and `nist_functions.csv`, plus
+/// `waived.csv` if the policy has waivers. Headers are written when a file is new.
+///
+/// # Arguments
+///
+/// * `dir` - Directory holding the CSV files.
+/// * `samples` - The intervals to append, oldest first.
+/// * `policy` - The active policy; each compliance flag and risk factor gets its own column.
+pub fn append_csv_series(dir: &Path, samples: &[IntervalMetrics], policy: &Policy) -> io::Result<()> {
+    append_remaining_csv_series(dir, samples, policy, &mut Vec::new())
+}
+
+/// Appends reporting intervals to the CSV time series files in `dir` like
+/// [`append_csv_series`], except for the files in `appended`, and adds each file it appends to.
+///
+/// Retrying after one file fails thus appends the intervals to the remaining files only.
+fn append_remaining_csv_series(dir: &Path, samples: &[IntervalMetrics], policy: &Policy, appended: &mut Vec<&'static str>) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    append_series(dir, appended, "rates.csv", &["events", "events_per_sec", "avg_data_sensitivity"], samples, |s| {
+        vec![
+            s.counts.total_events.to_string(),
+            format!("{:.1}", s.rate()),
+            format!("{:.2}", s.counts.avg_data_sensitivity),
+        ]
+    })?;
+
+    let violation_columns: Vec<&str> = policy.compliance.iter().map(|(_, info)| info.key).collect();
+    append_series(dir, appended, "violations.csv", &violation_columns, samples, |s| {
+        policy.compliance.iter().map(|(flag, _)| s.counts.violations(flag).to_string()).collect()
+    })?;
+
+    if !policy.waivers.is_empty() {
+        let mut waived_columns = vec!["events"];
+        waived_columns.extend(&violation_columns);
+        append_series(dir, appended, "waived.csv", &waived_columns, samples, |s| {
+            let mut row = vec![s.counts.waived_events.to_string()];
+            row.extend(policy.compliance.iter().map(|(flag, _)| s.counts.waived(flag).to_string()));
+            row
+        })?;
+    }
+
+    let registry = &policy.risk_factors;
+    let mut risk_columns = vec!["high", "medium", "low"];
+    risk_columns.extend(registry.iter().map(|(_, factor)| factor.key.as_str()));
+    append_series(dir, appended, "risk.csv", &risk_columns, samples, |s| {
+        let mut row = vec![
+            s.counts.high_risk_count.to_string(),
+            s.counts.medium_risk_count.to_string(),
+            s.counts.low_risk_count.to_string(),
+        ];
+        row.extend(registry.iter().map(|(id, _)| {
+            s.counts.risk_factor_counts.get(id.0 as usize).copied().unwrap_or(0).to_string()
+        }));
+        row
+    })?;
+
+    append_series(dir, appended, "services.csv", &SERVICE_NAMES, samples, |s| {
+        s.counts.service_counts.iter().map(u64::to_string).collect()
+    })?;
+    append_series(dir, appended, "vendors.csv", &VENDOR_NAMES, samples, |s| {
+        s.counts.vendor_counts.iter().map(u64::to_string).collect()
+    })?;
+    append_series(dir, appended, "departments.csv", &DEPARTMENT_NAMES, samples, |s| {
+        s.counts.department_counts.iter().map(u64::to_string).collect()
+    })?;
+    append_series(dir, appended, "purposes.csv", &PURPOSE_NAMES, samples, |s| {
+        s.counts.purpose_counts.iter().map(u64::to_string).collect()
+    })?;
+    append_series(dir, appended, "purpose_violations.csv", &PURPOSE_NAMES, samples, |s| {
+        s.counts.purpose_violations.iter().map(u64::to_string).collect()
+    })?;
+    append_series(dir, appended, "regions.csv", &REGION_NAMES, samples, |s| {
+        s.counts.region_counts.iter().map(u64::to_string).collect()
+    })?;
+    append_series(dir, appended, "region_violations.csv", &REGION_NAMES, samples, |s| {
+        s.counts.region_violations.iter().map(u64::to_string).collect()
+    })?;
+    let tier_columns = AI_ACT_TIERS.map(AiActTier::name);
+    append_series(dir, appended, "ai_act_tiers.csv", &tier_columns, samples, |s| {
+        s.counts.ai_act_tier_counts.iter().map(u64::to_string).collect()
+    })?;
+    let function_columns = NIST_FUNCTIONS.map(NistFunction::name);
+    append_series(dir, appended, "nist_functions.csv", &function_columns, samples, |s| {
+        let coverage = nist::coverage(&policy.compliance, &s.counts.violation_counts);
+        coverage.iter().map(|function| function.violations.to_string()).collect()
+    })
+}
+
+/// Sink appending every reporting interval to the CSV time series files in a directory.
+pub struct CsvSeriesSink {
+    dir: PathBuf,
+    /// End of the interval written last, and the files it was appended to, so that a retried
+    /// write does not append it twice to the files that succeeded.
+    appended: Mutex<(u64, Vec<&'static str>)>,
+}
+
+impl CsvSeriesSink {
+    /// Creates the sink writing to the CSV files in `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        CsvSeriesSink { dir, appended: Mutex::default() }
+    }
+}
+
+impl Sink for CsvSeriesSink {
+    fn name(&self) -> &'static str {
+        CSV_SINK
+    }
+
+    fn write(&self, interval: &IntervalMetrics, _total: &CumulativeMetrics, policy: &Policy) -> io::Result<()> {
+        let mut appended = self.appended.lock().unwrap_or_else(|e| e.into_inner());
+        if appended.0 != interval.unix_ms {
+            *appended = (interval.unix_ms, Vec::new());
+        }
+        append_remaining_csv_series(&self.dir, std::slice::from_ref(interval), policy, &mut appended.1)
+    }
+}
+
+/// Appends sampled events to `sampled_events.csv` in `dir`, creating it with a header as needed.
+///
+/// Checks are joined with `;` within their column; the weight is the number of evaluated events
+/// each sampled event stands for.
+///
+/// # Arguments
+///
+/// * `dir` - Directory holding the CSV files.
+/// * `events` - The sampled events to append.
+pub fn append_sampled_events(dir: &Path, events: &[SampledEvent]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let file = OpenOptions::new().create(true).append(true).open(dir.join("sampled_events.csv"))?;
+    let is_new = file.metadata()?.len() == 0;
+    let mut writer = csv::Writer::from_writer(file);
+    if is_new {
+        writer.write_record([
+            "timestamp_ms",
+            "event_id",
+            "worker",
+            "weight",
+            "service",
+            "model",
+            "vendor",
+            "department",
+            "data_sensitivity",
+            "user_id",
+            "purpose",
+            "region",
+            "lawful_basis",
+            "pii_types",
+            "ai_act_tier",
+            "risk_score",
+            "violations",
+            "waived",
+        ])?;
+    }
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    for event in events {
+        writer.write_record([
+            event.timestamp_ms.to_string(),
+            optional(event.event_id.map(|id| id.to_string())),
+            event.worker.to_string(),
+            format!("{:.1}", event.weight),
+            event.service.to_string(),
+            event.model.to_string(),
+            event.vendor.to_string(),
+            event.department.to_string(),
+            event.data_sensitivity.to_string(),
+            optional(event.user_id.map(|id| id.to_string())),
+            optional(event.purpose.as_ref().map(|purpose| purpose.to_string())),
+            optional(event.region.as_ref().map(|region| region.to_string())),
+            optional(event.lawful_basis.as_ref().map(|basis| basis.to_string())),
+            event.pii_types.join(";"),
+            optional(event.ai_act_tier.as_ref().map(|tier| tier.to_string())),
+            event.risk_score.to_string(),
+            event.violations.join(";"),
+            event.waived.join(";"),
+        ])?;
+    }
+    writer.flush()
+}
+
+/// Appends one row per sample to a single series file, prefixed with the sample timestamp.
+fn append_series(
+    dir: &Path,
+    appended: &mut Vec<&'static str>,
+    file_name: &'static str,
+    columns: &[&str],
+    samples: &[IntervalMetrics],
+    row: impl Fn(&IntervalMetrics) -> Vec<String>,
+) -> io::Result<()> {
+    if appended.contains(&file_name) {
+        return Ok(());
+    }
+    let file = OpenOptions::new().create(true).append(true).open(dir.join(file_name))?;
+    let is_new = file.metadata()?.len() == 0;
+    let mut writer = csv::Writer::from_writer(file);
+    if is_new {
+        writer.write_record(std::iter::once("timestamp_ms").chain(columns.iter().copied()))?;
+    }
+    for sample in samples {
+        writer.write_record(std::iter::once(sample.unix_ms.to_string()).chain(row(sample)))?;
+    }
+    writer.flush()?;
+    appended.push(file_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retried_intervals_are_appended_once_to_every_file() {
+        let dir = std::env::temp_dir().join(format!("csv-series-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        // A directory in place of violations.csv fails the write after rates.csv is appended.
+        fs::create_dir_all(dir.join("violations.csv")).unwrap();
+        let sink = CsvSeriesSink::new(dir.clone());
+        let interval = IntervalMetrics { unix_ms: 1_000, ..IntervalMetrics::default() };
+        let total = CumulativeMetrics::default();
+        let policy = Policy::builtin();
+        assert!(sink.write(&interval, &total, &policy).is_err());
+        fs::remove_dir(dir.join("violations.csv")).unwrap();
+        sink.write(&interval, &total, &policy).unwrap();
+        sink.write(&IntervalMetrics { unix_ms: 2_000, ..IntervalMetrics::default() }, &total, &policy).unwrap();
+
+        let rows = |file: &str| fs::read_to_string(dir.join(file)).unwrap().lines().skip(1).map(|line| line.split(',').next().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(rows("rates.csv"), ["1000", "2000"]);
+        assert_eq!(rows("violations.csv"), ["1000", "2000"]);
+        assert_eq!(rows("nist_functions.csv"), ["1000", "2000"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError};
+use crate::frontend::InstanceStatus;
+use crate::metrics::{ComplianceMetrics, IntervalMetrics};
+use crate::remote::{hello_frame, MetricsEncoder};
+use crossbeam_channel::{Receiver, Sender};
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How long connecting to the leader or writing a frame to it may take before the attempt fails.
+const LEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the name this instance reports to a federation leader under if `--instance-name` is
+/// not given: the host name, or the process id if it cannot be determined.
+pub fn default_instance_name() -> String {
+    fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("instance-{}", std::process::id()))
+}
+
+/// Pushes each reporting interval's metrics to a federation leader's `--listen` socket.
+///
+/// The connection starts with a hello frame naming this instance, followed by one metrics frame
+/// per interval in the remote worker format. While the leader is unreachable, intervals are
+/// merged and sent once it accepts a connection again, so its totals catch up. Connection
+/// failures are reported once per outage with [`ErrorPolicy::Retry`] and never stop the monitor.
+/// Returns once the monitor drops its sender.
+///
+/// # Arguments
+///
+/// * `leader` - Address of the leader's `--listen` socket.
+/// * `instance` - Name this instance is shown under on the leader's Fleet tab.
+/// * `reports` - Channel receiver for the metrics of each interval.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn federation_sender(leader: &str, instance: &str, reports: Receiver<IntervalMetrics>, errors: Sender<ErrorReport>) {
+    let mut connection: Option<(TcpStream, MetricsEncoder)> = None;
+    let mut pending = IntervalMetrics::default();
+    let mut outage_reported = false;
+    for metrics in &reports {
+        pending.merge(&metrics);
+        if connection.is_none() {
+            match connect_leader(leader, instance) {
+                Ok(stream) => {
+                    if outage_reported {
+                        info!("reconnected to federation leader {}", leader);
+                    }
+                    connection = Some((stream, MetricsEncoder::new(true)));
+                }
+                Err(source) => {
+                    if !outage_reported {
+                        report(&errors, MonitorError::Aggregator { addr: leader.to_string(), source }, ErrorPolicy::Retry);
+                        outage_reported = true;
+                    }
+                    continue;
+                }
+            }
+        }
+        if let Some((stream, encoder)) = &mut connection {
+            match stream.write_all(&encoder.encode(&pending.counts)) {
+                Ok(()) => {
+                    pending = IntervalMetrics::default();
+                    outage_reported = false;
+                }
+                Err(source) => {
+                    report(&errors, MonitorError::Aggregator { addr: leader.to_string(), source }, ErrorPolicy::Retry);
+                    outage_reported = true;
+                    connection = None;
+                }
+            }
+        }
+    }
+    if pending.counts.total_events > 0 {
+        warn!("{} events were not delivered to federation leader {}", pending.counts.total_events, leader);
+    }
+}
+
+/// Connects to the leader and introduces this instance with a hello frame.
+fn connect_leader(leader: &str, instance: &str) -> io::Result<TcpStream> {
+    let addr = leader
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "address did not resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, LEADER_TIMEOUT)?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(LEADER_TIMEOUT))?;
+    stream.write_all(&hello_frame(instance))?;
+    Ok(stream)
+}
+
+/// The instances and remote workers that have reported to this monitor, with the counts shown on
+/// the Fleet tab.
+#[derive(Default)]
+pub struct Fleet {
+    instances: Mutex<Vec<FleetInstance>>,
+}
+
+/// Counts of one reporting instance, kept across reconnections under the same name.
+struct FleetInstance {
+    name: String,
+    address: SocketAddr,
+    /// Open connections reporting under this name.
+    connections: usize,
+    /// Events, violations, and risk counts since the instance first connected.
+    totals: ComplianceMetrics,
+    /// Events reported since the statuses were last taken.
+    interval_events: u64,
+}
+
+impl Fleet {
+    /// Registers a connection reporting under `name`, which stays connected until the returned
+    /// member is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name from the connection's hello frame, or its address if it sent none.
+    /// * `address` - Address the connection comes from.
+    pub fn join(&self, name: &str, address: SocketAddr) -> FleetMember<'_> {
+        let mut instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        let index = match instances.iter().position(|instance| instance.name == name) {
+            Some(index) => index,
+            None => {
+                instances.push(FleetInstance {
+                    name: name.to_string(),
+                    address,
+                    connections: 0,
+                    totals: ComplianceMetrics::default(),
+                    interval_events: 0,
+                });
+                instances.len() - 1
+            }
+        };
+        instances[index].address = address;
+        instances[index].connections += 1;
+        FleetMember { fleet: self, index }
+    }
+
+    /// Returns the status of every instance that has reported, in the order they first
+    /// connected, and starts a new interval for their rates.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - Length of the interval since the statuses were last taken.
+    pub fn statuses(&self, elapsed: Duration) -> Vec<InstanceStatus> {
+        let mut instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        instances
+            .iter_mut()
+            .map(|instance| InstanceStatus {
+                name: instance.name.clone(),
+                address: instance.address.to_string(),
+                connected: instance.connections > 0,
+                events: instance.totals.total_events,
+                rate: std::mem::take(&mut instance.interval_events) as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+                compliance_percentage: instance.totals.compliance_percentage(),
+                violations: instance.totals.total_violations(),
+                high_risk_count: instance.totals.high_risk_count,
+            })
+            .collect()
+    }
+}
+
+/// A connection registered with a [`Fleet`]; dropping it marks the connection closed.
+pub struct FleetMember<'a> {
+    fleet: &'a Fleet,
+    index: usize,
+}
+
+impl FleetMember<'_> {
+    /// Adds a report received on this connection to its instance's counts.
+    pub fn record(&self, metrics: &ComplianceMetrics) {
+        // Only the counts shown per instance are kept; the breakdowns go to the fleet totals.
+        let counts = ComplianceMetrics {
+            total_events: metrics.total_events,
+            violation_counts: metrics.violation_counts.clone(),
+            high_risk_count: metrics.high_risk_count,
+            ..ComplianceMetrics::default()
+        };
+        let mut instances = self.fleet.instances.lock().unwrap_or_else(|e| e.into_inner());
+        let instance = &mut instances[self.index];
+        instance.totals.merge(&counts);
+        instance.interval_events = instance.interval_events.saturating_add(metrics.total_events);
+    }
+}
+
+impl Drop for FleetMember<'_> {
+    fn drop(&mut self) {
+        let mut instances = self.fleet.instances.lock().unwrap_or_else(|e| e.into_inner());
+        instances[self.index].connections -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::serve_remote_workers;
+    use crossbeam_channel::{bounded, unbounded};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn leaders_count_each_named_instance_and_merge_their_reports() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let leader = listener.local_addr().unwrap().to_string();
+        let fleet = Arc::new(Fleet::default());
+        let stop_signal = Arc::new(AtomicBool::new(false));
+        let (metrics_sender, metrics_receiver) = bounded(16);
+        let (errors, error_receiver) = unbounded();
+        let server = {
+            let (fleet, stop_signal, errors) = (fleet.clone(), stop_signal.clone(), errors.clone());
+            thread::spawn(move || serve_remote_workers(listener, stop_signal, metrics_sender, errors, fleet))
+        };
+
+        let interval = |events: u64| {
+            let counts = ComplianceMetrics {
+                total_events: events,
+                violation_counts: vec![0, events / 2, 0, 0, 0],
+                ..ComplianceMetrics::default()
+            };
+            IntervalMetrics::new(counts, Duration::from_secs(1), 0)
+        };
+        for (name, events) in [("gateway-eu", 100), ("gateway-us", 40)] {
+            let (reports, receiver) = unbounded();
+            reports.send(interval(events)).unwrap();
+            reports.send(interval(events)).unwrap();
+            drop(reports);
+            federation_sender(&leader, name, receiver, errors.clone());
+        }
+        let mut fleet_total = ComplianceMetrics::default();
+        for _ in 0..4 {
+            fleet_total.merge(&metrics_receiver.recv_timeout(Duration::from_secs(5)).unwrap());
+        }
+        assert_eq!(fleet_total.total_events, 280);
+        stop_signal.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+        assert!(error_receiver.try_recv().is_err());
+
+        let statuses = fleet.statuses(Duration::from_secs(2));
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].name, "gateway-eu");
+        assert_eq!(statuses[0].events, 200);
+        assert_eq!(statuses[0].rate, 100.0);
+        assert_eq!(statuses[0].violations, 100);
+        assert_eq!(statuses[0].compliance_percentage, 90.0);
+        assert_eq!(statuses[1].events, 80);
+        assert!(statuses.iter().all(|status| !status.connected));
+        assert_eq!(fleet.statuses(Duration::from_secs(2))[0].rate, 0.0);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::frontend::{Frontend, FrontendCommand, FrontendContext, InstanceStatus, PipelineHealth, ReplayStatus};
+use crate::metrics::ComplianceMetrics;
+use crate::policy::Policy;
+use crate::scoring::ScoringMode;
+use crate::shadow::PolicyComparison;
+use crossbeam_channel::RecvTimeoutError;
+use serde_json::{json, Map, Value};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// How the log frontend formats its lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineFormat {
+    /// Human-readable `key=value` lines.
+    Text,
+    /// One JSON object per line, tagged with a `type` field.
+    Json,
+}
+
+/// Headless frontend that prints one line per reporting interval to stdout.
+///
+/// It has no interactive controls; the monitor is stopped with Ctrl+C.
+pub struct LogFrontend {
+    format: LineFormat,
+    replay: Option<ReplayStatus>,
+    health: Option<PipelineHealth>,
+    /// Instances reporting to this monitor, if it accepts them.
+    fleet: Option<Vec<InstanceStatus>>,
+    /// Violations under the active and the candidate policy, if shadow mode is enabled.
+    comparison: Option<PolicyComparison>,
+}
+
+impl Frontend for LogFrontend {
+    fn name(&self) -> &'static str {
+        match self.format {
+            LineFormat::Text => "log frontend",
+            LineFormat::Json => "JSON log frontend",
+        }
+    }
+
+    fn run(mut self: Box<Self>, mut context: FrontendContext) {
+        let started = context.clock.now();
+        while !context.stop_signal.load(Ordering::Relaxed) {
+            match context.updates.recv_timeout(Duration::from_millis(100)) {
+                Ok(FrontendCommand::UpdateMetrics(metrics)) => {
+                    let elapsed = context.clock.now().saturating_sub(started);
+                    let line = match self.format {
+                        LineFormat::Text => self.format_interval(elapsed, &metrics, &context.policy),
+                        LineFormat::Json => self.interval_json(elapsed, &metrics, &context.policy).to_string(),
+                    };
+                    println!("{}", line);
+                }
+                Ok(FrontendCommand::UpdateReplay(status)) => {
+                    if status.finished && !self.replay.as_ref().is_some_and(|s| s.finished) {
+                        match self.format {
+                            LineFormat::Text => println!(
+                                "Replay of {} complete: {} events, {} records skipped, {} late",
+                                status.source, status.events_replayed, status.skipped_records, status.arrivals.late
+                            ),
+                            LineFormat::Json => println!(
+                                "{}",
+                                json!({
+                                    "type": "replay_complete",
+                                    "source": status.source,
+                                    "events_replayed": status.events_replayed,
+                                    "skipped_records": status.skipped_records,
+                                    "reordered_events": status.arrivals.reordered,
+                                    "late_events": status.arrivals.late,
+                                })
+                            ),
+                        }
+                    }
+                    self.replay = Some(status);
+                }
+                Ok(FrontendCommand::UpdateHealth(health)) => self.health = Some(health),
+                // Per-thread throughput is only shown on the dashboard's System tab.
+                Ok(FrontendCommand::UpdateThreads(_)) => {}
+                Ok(FrontendCommand::UpdateFleet(instances)) => self.fleet = Some(instances),
+                Ok(FrontendCommand::UpdateComparison(comparison)) => self.comparison = Some(comparison),
+                // Errors are already logged to stderr by the monitor.
+                Ok(FrontendCommand::ReportError(_)) => {}
+                // Cases are reviewed on the dashboard's Cases tab and persisted to the case file.
+                Ok(FrontendCommand::UpdateCases(_)) => {}
+                // Alerts are logged as warnings when raised.
+                Ok(FrontendCommand::UpdateAlerts(_)) => {}
+                // Sampled events are inspected on the dashboard's Events tab and exported with the metrics.
+                Ok(FrontendCommand::UpdateSampledEvents(_)) => {}
+                Ok(FrontendCommand::UpdatePolicy(policy)) => context.policy = policy,
+                Ok(FrontendCommand::ShowNotice(message)) => match self.format {
+                    LineFormat::Text => println!("{}", message),
+                    LineFormat::Json => println!("{}", json!({ "type": "notice", "message": message })),
+                },
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+}
+
+impl LogFrontend {
+    /// Creates a log frontend printing lines in the given format.
+    pub fn new(format: LineFormat) -> Self {
+        LogFrontend {
+            format,
+            replay: None,
+            health: None,
+            fleet: None,
+            comparison: None,
+        }
+    }
+
+    /// Formats the cumulative metrics as a single log line.
+    fn format_interval(&self, elapsed: Duration, metrics: &ComplianceMetrics, policy: &Policy) -> String {
+        let mut line = format!(
+            "[{:>6}s] events={} rate={:.1}/s compliance={:.1}%",
+            elapsed.as_secs(),
+            metrics.total_events,
+            metrics.processing_rate(),
+            policy.scoring.score(metrics),
+        );
+        if policy.scoring.mode != ScoringMode::default() {
+            line.push_str(&format!(" scoring={}", policy.scoring.mode.name()));
+        }
+        for (flag, info) in policy.compliance.iter() {
+            line.push_str(&format!(" {}={}", info.key, metrics.violations(flag)));
+        }
+        if !policy.waivers.is_empty() {
+            line.push_str(&format!(" waived={}", metrics.waived_events));
+        }
+        line.push_str(&format!(" shadow_ai={}", metrics.shadow_ai_events));
+        line.push_str(&format!(
+            " risk={}/{}/{}",
+            metrics.high_risk_count, metrics.medium_risk_count, metrics.low_risk_count
+        ));
+        if let Some(replay) = &self.replay {
+            line.push_str(&format!(" replayed={}", replay.events_replayed));
+        }
+        if let Some(comparison) = &self.comparison {
+            line.push_str(&format!(" candidate_delta={:+}", comparison.total_delta()));
+        }
+        if let Some(fleet) = &self.fleet {
+            let connected = fleet.iter().filter(|instance| instance.connected).count();
+            line.push_str(&format!(" instances={}/{}", connected, fleet.len()));
+        }
+        if let Some(health) = &self.health {
+            line.push_str(&format!(
+                " queue={}/{} peak={}",
+                health.queue_depth, health.queue_capacity, health.peak_queue_depth
+            ));
+            line.push_str(&format!(" lag={}ms", health.aggregator_lag.as_millis()));
+            if let Some(latency) = &health.batch_latency {
+                line.push_str(&format!(" batch_p999={}us", latency.p999.as_micros()));
+            }
+            if let Some(bytes) = health.rss_bytes {
+                line.push_str(&format!(" rss={:.1}MiB", bytes as f64 / (1024.0 * 1024.0)));
+            }
+            if let Some(injected) = health.injected_sink_failures {
+                line.push_str(&format!(" chaos_injected={}", injected));
+            }
+        }
+        line
+    }
+
+    /// Builds the JSON object for a reporting interval, with the same fields as the text line.
+    fn interval_json(&self, elapsed: Duration, metrics: &ComplianceMetrics, policy: &Policy) -> Value {
+        let violations: Map<String, Value> = policy
+            .compliance
+            .iter()
+            .map(|(flag, info)| (info.key.to_string(), metrics.violations(flag).into()))
+            .collect();
+        let mut line = json!({
+            "type": "interval",
+            "elapsed_secs": elapsed.as_secs(),
+            "events": metrics.total_events,
+            "rate": metrics.processing_rate(),
+            "compliance_percentage": policy.scoring.score(metrics),
+            "compliance_scoring": policy.scoring.mode.name(),
+            "violations": violations,
+            "shadow_ai_events": metrics.shadow_ai_events,
+            "risk": {
+                "high": metrics.high_risk_count,
+                "medium": metrics.medium_risk_count,
+                "low": metrics.low_risk_count,
+            },
+        });
+        if !policy.waivers.is_empty() {
+            line["waived_events"] = metrics.waived_events.into();
+        }
+        if let Some(replay) = &self.replay {
+            line["replayed"] = replay.events_replayed.into();
+        }
+        if let Some(comparison) = &self.comparison {
+            line["candidate"] = json!(comparison);
+        }
+        if let Some(fleet) = &self.fleet {
+            line["instances"] = json!(fleet);
+        }
+        if let Some(health) = &self.health {
+            line["queue"] = json!({
+                "depth": health.queue_depth,
+                "capacity": health.queue_capacity,
+                "peak": health.peak_queue_depth,
+            });
+            line["aggregator_lag_ms"] = (health.aggregator_lag.as_millis() as u64).into();
+            if let Some(latency) = &health.batch_latency {
+                line["batch_latency_us"] = json!({
+                    "p50": latency.p50.as_micros() as u64,
+                    "p95": latency.p95.as_micros() as u64,
+                    "p999": latency.p999.as_micros() as u64,
+                    "max": latency.max.as_micros() as u64,
+                });
+            }
+            if let Some(bytes) = health.rss_bytes {
+                line["rss_bytes"] = bytes.into();
+            }
+            if let Some(injected) = health.injected_sink_failures {
+                line["chaos_injected"] = injected.into();
+            }
+        }
+        line
+    }
+}
@@ -0,0 +1,291 @@
+pub mod log;
+pub mod remote;
+#[cfg(feature = "web")]
+pub mod web;
+
+use crate::alerts::AlertLog;
+use crate::cases::{CaseBook, CaseState};
+use crate::clock::SharedClock;
+use crate::error::{ErrorReport, MonitorError, Result};
+use crate::event_sample::SampledEvent;
+use crate::event_time::LateArrivals;
+use crate::metrics::CumulativeMetrics;
+use crate::policy::Policy;
+use crate::shadow::PolicyComparison;
+use crate::tail_latency;
+use crate::ui::keys::Keymap;
+use crate::ui::layout::DashboardLayout;
+use crate::ui::theme::Theme;
+use crate::ui::tui::TuiFrontend;
+use clap::ValueEnum;
+use crossbeam_channel::{Receiver, Sender};
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Updates sent from the monitor loop to the active frontend.
+pub enum FrontendCommand {
+    /// Update the displayed metrics.
+    UpdateMetrics(Box<CumulativeMetrics>),
+    /// Update the progress of an event log replay.
+    UpdateReplay(ReplayStatus),
+    /// Update the internal health of the metrics pipeline.
+    UpdateHealth(PipelineHealth),
+    /// Update the throughput of the local worker threads.
+    UpdateThreads(Vec<ThreadThroughput>),
+    /// Update the federated instances and remote workers reporting to this monitor.
+    UpdateFleet(Vec<InstanceStatus>),
+    /// Show a non-fatal error reported by one of the monitor's components.
+    ReportError(String),
+    /// Show an informational notice, e.g. the result of an export.
+    ShowNotice(String),
+    /// Update the violation cases.
+    UpdateCases(CaseBook),
+    /// Update the alert history, including the active alerts shown in the banner.
+    UpdateAlerts(AlertLog),
+    /// Label checks and show SLO targets and rule pack settings from a reloaded policy.
+    UpdatePolicy(Arc<Policy>),
+    /// Update the violations per check under the active and the candidate policy of shadow mode.
+    UpdateComparison(PolicyComparison),
+    /// Update the most recent events sampled by the local workers, newest first.
+    UpdateSampledEvents(Vec<SampledEvent>),
+}
+
+/// Requests sent from a frontend or the admin API back to the monitor loop.
+#[derive(Debug)]
+pub enum ControlCommand {
+    /// Export the retained per-interval metrics as CSV files.
+    ExportCsv,
+    /// Move a case, by its index in the case book, to a new state.
+    SetCaseState { case: usize, state: CaseState },
+    /// Acknowledge an alert, by its id, so the banner stops flashing for it.
+    AcknowledgeAlert { alert: u64 },
+    /// Record alerts without dispatching them for this long; a zero duration ends such a silence
+    /// early.
+    SilenceAlerts { duration: Duration },
+    /// Reload the policy from the files it was loaded from at startup and send back the result.
+    ReloadPolicy { reply: Sender<Result<()>> },
+    /// Queue a burst of non-compliant events for the local workers; `--spike-size` of them if
+    /// `events` is not given.
+    InjectSpike { events: Option<usize> },
+}
+
+/// Progress of an event log replay.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReplayStatus {
+    pub source: String,
+    pub speed: f64,
+    /// Position in the replayed timeline, relative to the first event in the log.
+    pub log_elapsed_ms: u64,
+    pub events_replayed: u64,
+    pub skipped_records: u64,
+    /// Events that arrived out of timestamp order, by how far.
+    pub arrivals: LateArrivals,
+    pub finished: bool,
+}
+
+/// Internal health of the pipeline: the bounded metrics channel from the replay thread and
+/// remote workers, the sink channels, and the aggregator itself.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PipelineHealth {
+    /// Metrics reports waiting in the channel when last sampled.
+    pub queue_depth: usize,
+    /// Reports the channel holds before senders block, set by `--channel-capacity`.
+    pub queue_capacity: usize,
+    /// Highest depth sampled since startup.
+    pub peak_queue_depth: usize,
+    /// Sink failures injected by `--chaos` so far, if it is enabled.
+    pub injected_sink_failures: Option<u64>,
+    /// Depths of the channels feeding the enabled sinks and the frontend.
+    pub channels: Vec<ChannelDepth>,
+    /// Resident memory of the process, where it can be measured.
+    pub rss_bytes: Option<u64>,
+    /// Longest delay of the aggregator loop beyond its regular tick during the last interval.
+    pub aggregator_lag: Duration,
+    /// Per-batch latency of each ECS system over the last interval, for systems that ran.
+    pub system_latencies: Vec<SystemLatency>,
+    /// Batch latency of all local workers over the last interval; `None` if no batch completed.
+    pub batch_latency: Option<TailLatency>,
+    /// Batch latency of all local workers since startup.
+    pub run_batch_latency: Option<TailLatency>,
+}
+
+/// Per-batch latency of a single ECS system over the last reporting interval.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemLatency {
+    pub name: Cow<'static, str>,
+    pub latency: TailLatency,
+}
+
+/// Depth of a single internal channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChannelDepth {
+    pub name: Cow<'static, str>,
+    pub depth: usize,
+    /// Capacity of a bounded channel; `None` if it is unbounded.
+    pub capacity: Option<usize>,
+}
+
+/// Batch latency percentiles read from an HDR histogram, precise to three significant digits.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TailLatency {
+    pub batches: u64,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+}
+
+impl TailLatency {
+    /// Reads the percentiles from an HDR histogram of batch latencies; `None` if it is empty.
+    pub fn from_histogram(histogram: &Histogram<u64>) -> Option<Self> {
+        (!histogram.is_empty()).then(|| TailLatency {
+            batches: histogram.len(),
+            p50: tail_latency::quantile(histogram, 0.5),
+            p95: tail_latency::quantile(histogram, 0.95),
+            p999: tail_latency::quantile(histogram, 0.999),
+            max: Duration::from_micros(histogram.max()),
+        })
+    }
+}
+
+/// Throughput of a single local worker thread.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThreadThroughput {
+    pub name: String,
+    /// Core the thread is pinned to, if any.
+    pub core: Option<usize>,
+    pub events: u64,
+    /// Events per second over the last reporting interval.
+    pub rate: f64,
+    /// Batch latency over the last reporting interval; `None` if no batch completed.
+    pub batch_latency: Option<TailLatency>,
+    /// Whether the thread is running; false while a panicked worker waits to be restarted.
+    pub alive: bool,
+    /// Times the worker was restarted after a panic.
+    pub restarts: u64,
+}
+
+/// Counts of a federated instance or remote worker reporting to this monitor.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstanceStatus {
+    /// Name from `--instance-name`, or the address of a sender that did not give one.
+    pub name: String,
+    /// Address the instance last connected from.
+    pub address: String,
+    pub connected: bool,
+    pub events: u64,
+    /// Events per second over the last reporting interval.
+    pub rate: f64,
+    pub compliance_percentage: f64,
+    pub violations: u64,
+    pub high_risk_count: u64,
+}
+
+/// Everything a frontend needs to present the monitor and control it.
+pub struct FrontendContext {
+    /// The active policy, used to label compliance flags and risk factors.
+    pub policy: Arc<Policy>,
+    /// Updates from the monitor loop; disconnected once the monitor shuts down.
+    pub updates: Receiver<FrontendCommand>,
+    /// Requests back to the monitor loop.
+    pub control: Sender<ControlCommand>,
+    /// Set by the monitor on shutdown; a frontend sets it itself when the user quits.
+    pub stop_signal: Arc<AtomicBool>,
+    /// Channel for reporting the frontend's own failures to the supervisor.
+    pub errors: Sender<ErrorReport>,
+    /// The monitor's clock, for showing elapsed time consistently with the reporting interval.
+    pub clock: SharedClock,
+}
+
+/// A user interface for the monitor, run on its own thread.
+pub trait Frontend: Send {
+    /// Short name shown in startup messages.
+    fn name(&self) -> &'static str;
+
+    /// Presents updates until the stop signal is set or the user quits.
+    fn run(self: Box<Self>, context: FrontendContext);
+}
+
+/// The frontends that can be selected at startup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FrontendKind {
+    /// Interactive terminal dashboard.
+    Tui,
+    /// Plain-text log of each reporting interval, for headless runs.
+    Log,
+    /// One JSON object per reporting interval, for log collectors and scripts.
+    Json,
+    /// Stream the dashboard to `connect` clients on `--dashboard-listen`, for headless servers.
+    Remote,
+}
+
+impl FrontendKind {
+    /// Returns the frontend to use given whether the process is attached to a terminal.
+    ///
+    /// The TUI needs a terminal for both input and output; without one it falls back to the
+    /// plain-text log. Other frontends are used as requested.
+    pub fn for_terminal(self, is_terminal: bool) -> Self {
+        match self {
+            FrontendKind::Tui if !is_terminal => FrontendKind::Log,
+            kind => kind,
+        }
+    }
+}
+
+/// Reference lines drawn across the dashboards' history charts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct ChartThresholds {
+    /// Compliance the organization aims for, in percent of checks passed.
+    pub compliance_target: Option<f64>,
+    /// Most events per second the deployment is sized for.
+    pub max_rate: Option<f64>,
+}
+
+impl ChartThresholds {
+    /// Creates the thresholds from `--compliance-target` and `--max-rate`.
+    pub fn new(compliance_target: Option<f64>, max_rate: Option<f64>) -> Result<Self> {
+        if let Some(target) = compliance_target
+            && !(0.0..=100.0).contains(&target)
+        {
+            return Err(MonitorError::Config(format!("compliance target must be between 0 and 100, got {}", target)));
+        }
+        if let Some(rate) = max_rate
+            && !(rate > 0.0 && rate.is_finite())
+        {
+            return Err(MonitorError::Config(format!("max rate must be a positive number of events per second, got {}", rate)));
+        }
+        Ok(ChartThresholds { compliance_target, max_rate })
+    }
+}
+
+/// Creates the frontend selected on the command line.
+///
+/// # Arguments
+///
+/// * `kind` - The frontend to create.
+/// * `theme` - Colors for the TUI dashboard; ignored by the other frontends.
+/// * `thresholds` - Reference lines for the TUI dashboard's charts; ignored by the other
+///   frontends.
+/// * `layout` - Custom layouts of the TUI dashboard's tabs; ignored by the other frontends.
+/// * `keymap` - Keys bound to the TUI dashboard's actions; ignored by the other frontends.
+/// * `dashboard_listen` - Address the remote frontend serves dashboards on; ignored by the
+///   other frontends.
+pub fn create_frontend(
+    kind: FrontendKind,
+    theme: Theme,
+    thresholds: ChartThresholds,
+    layout: DashboardLayout,
+    keymap: Keymap,
+    dashboard_listen: &str,
+) -> Result<Box<dyn Frontend>> {
+    Ok(match kind {
+        FrontendKind::Tui => Box::new(TuiFrontend { theme, thresholds, layout, keymap }),
+        FrontendKind::Log => Box::new(log::LogFrontend::new(log::LineFormat::Text)),
+        FrontendKind::Json => Box::new(log::LogFrontend::new(log::LineFormat::Json)),
+        FrontendKind::Remote => Box::new(remote::RemoteFrontend::bind(dashboard_listen)?),
+    })
+}
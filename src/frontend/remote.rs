@@ -0,0 +1,498 @@
+use crate::alerts::AlertLog;
+use crate::cases::{CaseBook, CaseState};
+use crate::clock::SharedClock;
+use crate::error::{report, ErrorPolicy, MonitorError, Result};
+use crate::event_sample::SampledEvent;
+use crate::frontend::{
+    ControlCommand, Frontend, FrontendCommand, FrontendContext, InstanceStatus, PipelineHealth, ReplayStatus, ThreadThroughput,
+};
+use crate::metrics::CumulativeMetrics;
+use crate::policy::Policy;
+use crate::remote::{decompress, read_frame};
+use crate::shadow::PolicyComparison;
+use crate::threads::spawn_named;
+use crossbeam_channel::{unbounded, RecvTimeoutError, Sender};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, ErrorKind, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Version of the dashboard stream, checked when a dashboard connects.
+const PROTOCOL_VERSION: u8 = 15;
+
+/// How often the server accepts dashboards and the client checks the stop signal.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a write to a dashboard may block before the dashboard is dropped, so a stalled
+/// connection cannot hold up the monitor's updates.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of kinds of update whose latest value is sent to a newly connected dashboard.
+const SNAPSHOT_SLOTS: usize = 9;
+
+/// Messages from a monitor to its remote dashboards.
+#[derive(Serialize, Deserialize)]
+enum ServerMessage {
+    /// First message on every connection, naming the checks and risk factors the metrics are
+    /// indexed by.
+    Hello {
+        version: u8,
+        checks: Vec<String>,
+        risk_factors: Vec<String>,
+    },
+    Metrics(Box<CumulativeMetrics>),
+    Replay(ReplayStatus),
+    Health(PipelineHealth),
+    Threads(Vec<ThreadThroughput>),
+    Fleet(Vec<InstanceStatus>),
+    Cases(CaseBook),
+    Alerts(AlertLog),
+    Comparison(PolicyComparison),
+    SampledEvents(Vec<SampledEvent>),
+    Error(String),
+    Notice(String),
+    /// The monitor is shutting down; the connection closes after this message.
+    Shutdown,
+}
+
+impl ServerMessage {
+    /// Converts an update for the frontend; `None` for updates not sent to dashboards.
+    fn from_command(command: FrontendCommand) -> Option<Self> {
+        Some(match command {
+            FrontendCommand::UpdateMetrics(metrics) => ServerMessage::Metrics(metrics),
+            FrontendCommand::UpdateReplay(status) => ServerMessage::Replay(status),
+            FrontendCommand::UpdateHealth(health) => ServerMessage::Health(health),
+            FrontendCommand::UpdateThreads(threads) => ServerMessage::Threads(threads),
+            FrontendCommand::UpdateFleet(instances) => ServerMessage::Fleet(instances),
+            FrontendCommand::UpdateCases(cases) => ServerMessage::Cases(cases),
+            FrontendCommand::UpdateAlerts(alerts) => ServerMessage::Alerts(alerts),
+            FrontendCommand::UpdateComparison(comparison) => ServerMessage::Comparison(comparison),
+            FrontendCommand::UpdateSampledEvents(events) => ServerMessage::SampledEvents(events),
+            FrontendCommand::ReportError(message) => ServerMessage::Error(message),
+            FrontendCommand::ShowNotice(message) => ServerMessage::Notice(message),
+            // Dashboards label checks with their own policy, and a reload keeps the checks.
+            FrontendCommand::UpdatePolicy(_) => return None,
+        })
+    }
+
+    /// Converts a received message into an update for the local frontend.
+    fn into_command(self) -> Option<FrontendCommand> {
+        Some(match self {
+            ServerMessage::Metrics(metrics) => FrontendCommand::UpdateMetrics(metrics),
+            ServerMessage::Replay(status) => FrontendCommand::UpdateReplay(status),
+            ServerMessage::Health(health) => FrontendCommand::UpdateHealth(health),
+            ServerMessage::Threads(threads) => FrontendCommand::UpdateThreads(threads),
+            ServerMessage::Fleet(instances) => FrontendCommand::UpdateFleet(instances),
+            ServerMessage::Cases(cases) => FrontendCommand::UpdateCases(cases),
+            ServerMessage::Alerts(alerts) => FrontendCommand::UpdateAlerts(alerts),
+            ServerMessage::Comparison(comparison) => FrontendCommand::UpdateComparison(comparison),
+            ServerMessage::SampledEvents(events) => FrontendCommand::UpdateSampledEvents(events),
+            ServerMessage::Error(message) => FrontendCommand::ReportError(message),
+            ServerMessage::Notice(message) => FrontendCommand::ShowNotice(message),
+            ServerMessage::Hello { .. } | ServerMessage::Shutdown => return None,
+        })
+    }
+
+    /// Returns the snapshot slot of updates that replace the previous one of their kind.
+    fn snapshot_slot(&self) -> Option<usize> {
+        match self {
+            ServerMessage::Metrics(_) => Some(0),
+            ServerMessage::Replay(_) => Some(1),
+            ServerMessage::Health(_) => Some(2),
+            ServerMessage::Threads(_) => Some(3),
+            ServerMessage::Cases(_) => Some(4),
+            ServerMessage::Fleet(_) => Some(5),
+            ServerMessage::Comparison(_) => Some(6),
+            ServerMessage::SampledEvents(_) => Some(7),
+            ServerMessage::Alerts(_) => Some(8),
+            _ => None,
+        }
+    }
+}
+
+/// Requests from a remote dashboard back to the monitor.
+#[derive(Serialize, Deserialize)]
+enum ClientMessage {
+    ExportCsv,
+    SetCaseState { case: usize, state: CaseState },
+    AcknowledgeAlert { alert: u64 },
+    InjectSpike { events: Option<usize> },
+}
+
+impl ClientMessage {
+    /// Converts a request from the local frontend; `None` for requests that stay local.
+    fn from_command(command: ControlCommand) -> Option<Self> {
+        match command {
+            ControlCommand::ExportCsv => Some(ClientMessage::ExportCsv),
+            ControlCommand::SetCaseState { case, state } => Some(ClientMessage::SetCaseState { case, state }),
+            ControlCommand::AcknowledgeAlert { alert } => Some(ClientMessage::AcknowledgeAlert { alert }),
+            ControlCommand::InjectSpike { events } => Some(ClientMessage::InjectSpike { events }),
+            ControlCommand::ReloadPolicy { .. } | ControlCommand::SilenceAlerts { .. } => None,
+        }
+    }
+
+    /// Converts a received request into a command for the monitor loop.
+    fn into_command(self) -> ControlCommand {
+        match self {
+            ClientMessage::ExportCsv => ControlCommand::ExportCsv,
+            ClientMessage::SetCaseState { case, state } => ControlCommand::SetCaseState { case, state },
+            ClientMessage::AcknowledgeAlert { alert } => ControlCommand::AcknowledgeAlert { alert },
+            ClientMessage::InjectSpike { events } => ControlCommand::InjectSpike { events },
+        }
+    }
+}
+
+/// Encodes a message as a length-prefixed frame holding LZ4-compressed JSON.
+fn encode_frame(message: &impl Serialize) -> io::Result<Vec<u8>> {
+    let payload = lz4_flex::compress_prepend_size(&serde_json::to_vec(message)?);
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Decodes the body of a frame read with [`read_frame`].
+fn decode_frame<T: DeserializeOwned>(body: &[u8]) -> io::Result<T> {
+    let json = decompress(body)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Headless frontend that streams the dashboard's updates to remote `connect` clients over TCP.
+///
+/// Each dashboard first receives the latest metrics, health, threads, cases, alerts, and replay
+/// progress, then every update as the monitor sends it. Dashboards can export CSV files, review
+/// cases, and acknowledge alerts as if they were local.
+pub struct RemoteFrontend {
+    listener: TcpListener,
+}
+
+impl RemoteFrontend {
+    /// Binds the dashboard socket, so that a bad address fails before any work starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The `--dashboard-listen` address.
+    pub fn bind(addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr).map_err(|source| MonitorError::Listen { purpose: "remote dashboards", addr: addr.to_string(), source })?;
+        Ok(RemoteFrontend { listener })
+    }
+
+    /// Returns the address the dashboard socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+impl Frontend for RemoteFrontend {
+    fn name(&self) -> &'static str {
+        "remote dashboard server"
+    }
+
+    fn run(self: Box<Self>, context: FrontendContext) {
+        let FrontendContext { policy, updates, control, stop_signal, errors, .. } = context;
+        if let Err(e) = self.listener.set_nonblocking(true) {
+            report(&errors, MonitorError::Io(e), ErrorPolicy::Shutdown);
+            return;
+        }
+        let hello = ServerMessage::Hello {
+            version: PROTOCOL_VERSION,
+            checks: policy.check_keys().into_iter().map(String::from).collect(),
+            risk_factors: policy.risk_factor_keys().into_iter().map(String::from).collect(),
+        };
+        let hello = match encode_frame(&hello) {
+            Ok(frame) => frame,
+            Err(e) => {
+                report(&errors, MonitorError::Io(e), ErrorPolicy::Shutdown);
+                return;
+            }
+        };
+        let mut snapshot: [Option<Vec<u8>>; SNAPSHOT_SLOTS] = Default::default();
+        let mut dashboards: Vec<RemoteDashboard> = Vec::new();
+        while !stop_signal.load(Ordering::Relaxed) {
+            loop {
+                match self.listener.accept() {
+                    Ok((stream, peer)) => {
+                        let frames = std::iter::once(&hello).chain(snapshot.iter().flatten());
+                        match RemoteDashboard::start(stream, peer, frames, &control) {
+                            Ok(dashboard) => {
+                                info!("remote dashboard {} connected", peer);
+                                dashboards.push(dashboard);
+                            }
+                            Err(e) => info!("remote dashboard {} failed to connect: {}", peer, e),
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => {
+                        report(&errors, MonitorError::Io(e), ErrorPolicy::Retry);
+                        break;
+                    }
+                }
+            }
+            dashboards.retain(RemoteDashboard::is_connected);
+            let message = match updates.recv_timeout(POLL_INTERVAL) {
+                Ok(command) => ServerMessage::from_command(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let Some(message) = message else {
+                continue;
+            };
+            match encode_frame(&message) {
+                Ok(frame) => {
+                    dashboards.retain(|dashboard| dashboard.send(&frame));
+                    if let Some(slot) = message.snapshot_slot() {
+                        snapshot[slot] = Some(frame);
+                    }
+                }
+                Err(e) => warn!("failed to encode a dashboard update: {}", e),
+            }
+        }
+        if let Ok(goodbye) = encode_frame(&ServerMessage::Shutdown) {
+            for dashboard in &dashboards {
+                dashboard.send(&goodbye);
+            }
+        }
+    }
+}
+
+/// A connected dashboard: the stream updates are written to, and the thread forwarding its
+/// requests to the monitor loop.
+struct RemoteDashboard {
+    stream: TcpStream,
+    peer: SocketAddr,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl RemoteDashboard {
+    /// Sends the initial frames and starts reading the dashboard's requests.
+    fn start<'a>(
+        stream: TcpStream,
+        peer: SocketAddr,
+        frames: impl Iterator<Item = &'a Vec<u8>>,
+        control: &Sender<ControlCommand>,
+    ) -> io::Result<Self> {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        for frame in frames {
+            (&stream).write_all(frame)?;
+        }
+        let mut reader = stream.try_clone()?;
+        let control = control.clone();
+        let reader = spawn_named("dashboard-peer", move || {
+            // Ends when the dashboard disconnects or the server shuts the stream down.
+            while let Ok(Some(body)) = read_frame(&mut reader) {
+                let Ok(message) = decode_frame::<ClientMessage>(&body) else {
+                    break;
+                };
+                if control.send(message.into_command()).is_err() {
+                    break;
+                }
+            }
+        })?;
+        Ok(RemoteDashboard { stream, peer, reader: Some(reader) })
+    }
+
+    /// Returns false once the dashboard has disconnected.
+    fn is_connected(&self) -> bool {
+        self.reader.as_ref().is_some_and(|reader| !reader.is_finished())
+    }
+
+    /// Writes a frame, returning false if the dashboard has gone or stalled.
+    fn send(&self, frame: &[u8]) -> bool {
+        (&self.stream).write_all(frame).is_ok()
+    }
+}
+
+impl Drop for RemoteDashboard {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+        info!("remote dashboard {} disconnected", self.peer);
+    }
+}
+
+/// Runs a frontend against the dashboard stream of a remote monitor until either side stops.
+///
+/// Prints a message and returns normally when the remote monitor shuts down; a lost connection
+/// is an error.
+///
+/// # Arguments
+///
+/// * `addr` - Address of the remote monitor's `--dashboard-listen` socket.
+/// * `policy` - The policy to label checks and risk factors with; it must have the same checks
+///   and risk factors as the remote monitor's.
+/// * `frontend` - The frontend to present the remote monitor with.
+/// * `clock` - Clock for the elapsed times shown by the frontend.
+/// * `stop_signal` - Atomic flag indicating when to disconnect; set when the user quits.
+pub fn connect(addr: &str, policy: Arc<Policy>, frontend: Box<dyn Frontend>, clock: SharedClock, stop_signal: Arc<AtomicBool>) -> Result<()> {
+    let connection_error = |source| MonitorError::RemoteMonitor { addr: addr.to_string(), source };
+    let stream = TcpStream::connect(addr).map_err(connection_error)?;
+    stream.set_nodelay(true).map_err(connection_error)?;
+    let mut reader = stream.try_clone().map_err(connection_error)?;
+    let hello = read_frame(&mut reader)
+        .and_then(|body| decode_frame(&body.ok_or(ErrorKind::UnexpectedEof)?))
+        .map_err(connection_error)?;
+    check_hello(addr, &hello, &policy)?;
+
+    let (update_sender, update_receiver) = unbounded();
+    let (control_sender, control_receiver) = unbounded();
+    let (error_sender, error_receiver) = unbounded();
+    // Returns true if the monitor said it was shutting down before the stream ended.
+    let stream_handle = spawn_named("dashboard-stream", move || -> io::Result<bool> {
+        while let Some(body) = read_frame(&mut reader)? {
+            match decode_frame(&body)? {
+                ServerMessage::Shutdown => return Ok(true),
+                message => {
+                    if let Some(command) = message.into_command()
+                        && update_sender.send(command).is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(false)
+    })?;
+    let context = FrontendContext {
+        policy,
+        updates: update_receiver,
+        control: control_sender,
+        stop_signal: stop_signal.clone(),
+        errors: error_sender,
+        clock,
+    };
+    let frontend_handle = spawn_named("dashboard", move || frontend.run(context))?;
+
+    let mut result = Ok(());
+    while !frontend_handle.is_finished() && !stream_handle.is_finished() && !stop_signal.load(Ordering::Relaxed) {
+        while let Ok(command) = control_receiver.try_recv() {
+            let Some(message) = ClientMessage::from_command(command) else {
+                continue;
+            };
+            if let Err(e) = encode_frame(&message).and_then(|frame| (&stream).write_all(&frame)) {
+                result = Err(connection_error(e));
+                stop_signal.store(true, Ordering::Relaxed);
+            }
+        }
+        while let Ok(error_report) = error_receiver.try_recv() {
+            if error_report.action == ErrorPolicy::Shutdown {
+                stop_signal.store(true, Ordering::Relaxed);
+                result = Err(error_report.error);
+            } else {
+                warn!("{}", error_report.error);
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    // The frontend also stops on its own once the stream reader has ended and dropped its sender.
+    let stream_ended = stream_handle.is_finished();
+    // Stop the frontend and unblock the stream reader.
+    stop_signal.store(true, Ordering::Relaxed);
+    let _ = stream.shutdown(Shutdown::Both);
+    if frontend_handle.join().is_err() && result.is_ok() {
+        result = Err(MonitorError::ThreadPanicked("frontend"));
+    }
+    let stream_result = stream_handle.join();
+    if stream_ended && result.is_ok() {
+        result = match stream_result {
+            Ok(Ok(true)) => {
+                println!("The monitor at {} has shut down.", addr);
+                Ok(())
+            }
+            Ok(Ok(false)) => Err(connection_error(ErrorKind::UnexpectedEof.into())),
+            Ok(Err(e)) => Err(connection_error(e)),
+            Err(_) => Err(MonitorError::ThreadPanicked("dashboard stream")),
+        };
+    }
+    result
+}
+
+/// Checks that a remote monitor speaks this dashboard's protocol and indexes its metrics by the
+/// same checks and risk factors as the local policy.
+fn check_hello(addr: &str, hello: &ServerMessage, policy: &Policy) -> Result<()> {
+    let ServerMessage::Hello { version, checks, risk_factors } = hello else {
+        return Err(MonitorError::Config(format!("{} did not start a dashboard stream", addr)));
+    };
+    if *version != PROTOCOL_VERSION {
+        return Err(MonitorError::Config(format!(
+            "{} streams dashboard protocol version {}, but this build supports version {}",
+            addr, version, PROTOCOL_VERSION
+        )));
+    }
+    for (what, remote, local) in [
+        ("checks", checks, policy.check_keys()),
+        ("risk factors", risk_factors, policy.risk_factor_keys()),
+    ] {
+        if !remote.iter().map(String::as_str).eq(local.iter().copied()) {
+            return Err(MonitorError::Config(format!(
+                "the monitor at {} has {} [{}] but the local policy has [{}]; pass the monitor's --policy and --risk-factors files",
+                addr,
+                what,
+                remote.join(", "),
+                local.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock;
+    use crate::metrics::ComplianceMetrics;
+
+    #[test]
+    fn dashboards_get_the_latest_state_and_send_requests_back() {
+        let policy = Arc::new(Policy::builtin());
+        let frontend = Box::new(RemoteFrontend::bind("127.0.0.1:0").unwrap());
+        let addr = frontend.local_addr().unwrap();
+        let (update_sender, updates) = unbounded();
+        let (control, control_receiver) = unbounded();
+        let (errors, _error_receiver) = unbounded();
+        let stop_signal = Arc::new(AtomicBool::new(false));
+        let context = FrontendContext {
+            policy: policy.clone(),
+            updates,
+            control,
+            stop_signal: stop_signal.clone(),
+            errors,
+            clock: clock::from_time_scale(1.0).unwrap(),
+        };
+        let metrics = CumulativeMetrics::resume(ComplianceMetrics { total_events: 42, ..ComplianceMetrics::default() });
+        update_sender.send(FrontendCommand::UpdateMetrics(Box::new(metrics))).unwrap();
+        let server = thread::spawn(move || frontend.run(context));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut next = || decode_frame::<ServerMessage>(&read_frame(&mut stream).unwrap().unwrap()).unwrap();
+        let hello = next();
+        check_hello("monitor", &hello, &policy).unwrap();
+        let mut hipaa = Policy::builtin();
+        hipaa.compliance.claim("hipaa", "HIPAA").unwrap();
+        assert!(check_hello("monitor", &hello, &hipaa).is_err());
+        assert!(matches!(next(), ServerMessage::Metrics(metrics) if metrics.total_events == 42));
+
+        let request = ClientMessage::SetCaseState { case: 3, state: CaseState::Remediated };
+        writer.write_all(&encode_frame(&request).unwrap()).unwrap();
+        let command = control_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(command, ControlCommand::SetCaseState { case: 3, state: CaseState::Remediated }));
+
+        update_sender.send(FrontendCommand::ShowNotice("hello".to_string())).unwrap();
+        assert!(matches!(next(), ServerMessage::Notice(message) if message == "hello"));
+        stop_signal.store(true, Ordering::Relaxed);
+        assert!(matches!(next(), ServerMessage::Shutdown));
+        server.join().unwrap();
+        // A message claiming to decompress to 4 GiB is rejected before allocating.
+        assert!(decode_frame::<ServerMessage>(&[0xff, 0xff, 0xff, 0xff, 0]).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::admin::metrics_json;
+use crate::alerts::AlertLog;
+use crate::cases::{CaseBook, CASE_STATES};
+use crate::constants::{LAWFUL_BASIS_NAMES, PURPOSE_NAMES};
+use crate::error::{report, ErrorPolicy, MonitorError, Result};
+use crate::frontend::{ChartThresholds, Frontend, FrontendCommand, FrontendContext, InstanceStatus, PipelineHealth, ReplayStatus, TailLatency, ThreadThroughput};
+use crate::event_sample::{top_groups, SampledEvent};
+use crate::logging;
+use crate::metrics::{CumulativeMetrics, LatestMetrics};
+use crate::policy::Policy;
+use crate::shadow::PolicyComparison;
+use crate::shadow_ai::shadow_ai_uses;
+use crate::threads::spawn_named;
+use crate::vendors::VendorRiskTier;
+use crossbeam_channel::{unbounded, RecvTimeoutError};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The dashboard page, which polls `/api/snapshot` and renders it.
+const PAGE: &str = include_str!("web.html");
+
+/// How often the web server accepts requests while no update arrives.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long the web server waits for a request before dropping the connection.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest request head the web server reads.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// Points of the rate and compliance history sent to the page.
+const HISTORY_POINTS: usize = 120;
+
+/// Users listed on the page's Risk tab.
+const TOP_USERS: usize = 10;
+
+/// Log lines sent to the page's Logs tab.
+const LOG_LINES: usize = 200;
+
+/// Errors kept for the page, newest last.
+const RECENT_ERRORS: usize = 20;
+
+/// Sampled events sent to the page's Events tab.
+const SAMPLED_EVENTS: usize = 100;
+
+/// Service and department groups listed on the page's Events tab.
+const TOP_SAMPLE_GROUPS: usize = 10;
+
+/// Frontend that serves a read-only web dashboard alongside another frontend.
+///
+/// Every update sent to the wrapped frontend is also kept by a `web-server` thread, which
+/// answers `GET /` with the dashboard page and `GET /api/snapshot` with the latest updates as
+/// JSON. The page polls the snapshot and shows the same tabs as the TUI dashboard.
+pub struct WebFrontend {
+    inner: Box<dyn Frontend>,
+    listener: TcpListener,
+    thresholds: ChartThresholds,
+}
+
+impl WebFrontend {
+    /// Binds the web socket, so that a bad address fails before any work starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The frontend that runs alongside the web dashboard.
+    /// * `addr` - The `--web` address.
+    /// * `thresholds` - Reference lines drawn across the page's history charts.
+    pub fn wrap(inner: Box<dyn Frontend>, addr: &str, thresholds: ChartThresholds) -> Result<Self> {
+        let listener = TcpListener::bind(addr).map_err(|source| MonitorError::Listen { purpose: "the web dashboard", addr: addr.to_string(), source })?;
+        Ok(WebFrontend { inner, listener, thresholds })
+    }
+
+    /// Returns the address the web socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+impl Frontend for WebFrontend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn run(self: Box<Self>, context: FrontendContext) {
+        let WebFrontend { inner, listener, thresholds } = *self;
+        let FrontendContext { policy, updates, control, stop_signal, errors, clock } = context;
+        let (inner_sender, inner_updates) = unbounded();
+        let server = {
+            let (updates, stop_signal, errors) = (updates.clone(), stop_signal.clone(), errors.clone());
+            let mut view = WebView::new(policy.clone(), thresholds);
+            spawn_named("web-server", move || {
+                if let Err(e) = listener.set_nonblocking(true) {
+                    report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade);
+                    // Keep the wrapped frontend updated even though the page cannot be served.
+                    for command in &updates {
+                        if inner_sender.send(command).is_err() {
+                            break;
+                        }
+                    }
+                    return;
+                }
+                while !stop_signal.load(Ordering::Relaxed) {
+                    loop {
+                        match listener.accept() {
+                            // A client that disconnects or stalls only affects its own request.
+                            Ok((stream, _peer)) => {
+                                let _ = respond(stream, &view);
+                            }
+                            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                            Err(e) => {
+                                report(&errors, MonitorError::Io(e), ErrorPolicy::Retry);
+                                break;
+                            }
+                        }
+                    }
+                    match updates.recv_timeout(POLL_INTERVAL) {
+                        Ok(command) => {
+                            view.update(&command);
+                            // The wrapped frontend may have quit; the page is served until shutdown.
+                            let _ = inner_sender.send(command);
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+        };
+        let (server, updates) = match server {
+            Ok(server) => (Some(server), inner_updates),
+            Err(e) => {
+                report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade);
+                (None, updates)
+            }
+        };
+        inner.run(FrontendContext { policy, updates, control, stop_signal, errors, clock });
+        if let Some(server) = server {
+            let _ = server.join();
+        }
+    }
+}
+
+/// The latest update of each kind, as shown on the web dashboard.
+pub struct WebView {
+    policy: Arc<Policy>,
+    thresholds: ChartThresholds,
+    latest: LatestMetrics,
+    replay: Option<ReplayStatus>,
+    health: Option<PipelineHealth>,
+    threads: Vec<ThreadThroughput>,
+    cases: CaseBook,
+    alerts: AlertLog,
+    fleet: Vec<InstanceStatus>,
+    comparison: Option<PolicyComparison>,
+    /// Most recent sampled events, newest first; `None` until the monitor sends any.
+    sampled_events: Option<Vec<SampledEvent>>,
+    notice: Option<String>,
+    errors: VecDeque<String>,
+    error_count: u64,
+}
+
+impl WebView {
+    /// Creates an empty view labelled with the given policy, with the given reference lines.
+    pub fn new(policy: Arc<Policy>, thresholds: ChartThresholds) -> Self {
+        WebView {
+            policy,
+            thresholds,
+            latest: LatestMetrics::default(),
+            replay: None,
+            health: None,
+            threads: Vec::new(),
+            cases: CaseBook::default(),
+            alerts: AlertLog::default(),
+            fleet: Vec::new(),
+            comparison: None,
+            sampled_events: None,
+            notice: None,
+            errors: VecDeque::new(),
+            error_count: 0,
+        }
+    }
+
+    /// Keeps an update sent to the frontend.
+    pub fn update(&mut self, command: &FrontendCommand) {
+        match command {
+            FrontendCommand::UpdateMetrics(metrics) => {
+                let updated_at_ms = metrics.history.last().map_or(self.latest.updated_at_ms, |point| point.unix_ms);
+                self.latest = LatestMetrics { metrics: CumulativeMetrics::clone(metrics), updated_at_ms };
+            }
+            FrontendCommand::UpdateReplay(status) => self.replay = Some(status.clone()),
+            FrontendCommand::UpdateHealth(health) => self.health = Some(health.clone()),
+            FrontendCommand::UpdateThreads(threads) => self.threads = threads.clone(),
+            FrontendCommand::UpdateFleet(instances) => self.fleet = instances.clone(),
+            FrontendCommand::UpdateComparison(comparison) => self.comparison = Some(comparison.clone()),
+            FrontendCommand::UpdateCases(cases) => self.cases = cases.clone(),
+            FrontendCommand::UpdateAlerts(alerts) => self.alerts = alerts.clone(),
+            FrontendCommand::ShowNotice(message) => self.notice = Some(message.clone()),
+            FrontendCommand::ReportError(message) => {
+                if self.errors.len() == RECENT_ERRORS {
+                    self.errors.pop_front();
+                }
+                self.errors.push_back(message.clone());
+                self.error_count += 1;
+            }
+            FrontendCommand::UpdatePolicy(policy) => self.policy = policy.clone(),
+            FrontendCommand::UpdateSampledEvents(events) => self.sampled_events = Some(events.clone()),
+        }
+    }
+
+    /// Builds the `/api/snapshot` response: the metrics as served by the admin API's
+    /// `/metrics.json`, plus everything else the dashboard tabs show.
+    pub fn snapshot_json(&self) -> Value {
+        let metrics = &self.latest.metrics;
+        let rate_history: Vec<Value> = metrics
+            .history_window(None, HISTORY_POINTS)
+            .iter()
+            .map(|point| json!({ "unix_ms": point.unix_ms, "rate": point.rate(), "compliance": 100.0 - point.violation_percentage() }))
+            .collect();
+        let checks: Vec<Value> = self
+            .policy
+            .compliance
+            .iter()
+            .map(|(flag, info)| {
+                json!({
+                    "key": info.key,
+                    "name": info.name,
+                    "violations": metrics.violations(flag),
+                    "waived": metrics.waived_counts.get(flag.0 as usize).copied().unwrap_or(0),
+                })
+            })
+            .collect();
+        let purposes: Vec<Value> = PURPOSE_NAMES
+            .iter()
+            .enumerate()
+            .map(|(index, name)| json!({ "name": name, "events": metrics.purpose_counts[index], "violations": metrics.purpose_violations[index] }))
+            .collect();
+        let lawful_bases: Vec<Value> = LAWFUL_BASIS_NAMES
+            .iter()
+            .enumerate()
+            .map(|(index, name)| json!({ "name": name, "events": metrics.lawful_basis_counts[index], "personal": metrics.lawful_basis_personal[index] }))
+            .collect();
+        let risk_factors: Vec<Value> = self
+            .policy
+            .risk_factors
+            .iter()
+            .map(|(id, factor)| {
+                let events = metrics.risk_factor_counts.get(id.0 as usize).copied().unwrap_or(0);
+                json!({ "key": factor.key, "name": factor.name, "weight": factor.weight, "events": events })
+            })
+            .collect();
+        let top_users: Vec<Value> = metrics
+            .top_users(TOP_USERS)
+            .into_iter()
+            .map(|(user, counts)| {
+                json!({
+                    "user": user,
+                    "events": counts.events,
+                    "violations": counts.violations,
+                    "high_risk_events": counts.high_risk_events,
+                    "total_risk_score": counts.total_risk_score,
+                })
+            })
+            .collect();
+        let vendors: Vec<Value> = self
+            .policy
+            .vendors
+            .iter()
+            .enumerate()
+            .map(|(index, (name, profile))| {
+                json!({
+                    "name": name,
+                    "risk_tier": profile.risk_tier.map(VendorRiskTier::name),
+                    "dpa_signed": profile.dpa_signed,
+                    "retention_days": profile.retention_days,
+                    "eu_hosting": profile.eu_hosting,
+                    "events": metrics.vendor_counts[index],
+                    "violating_events": metrics.vendor_violations[index],
+                })
+            })
+            .collect();
+        let violations_by_state = self.cases.violations_by_state();
+        let cases: Vec<Value> = self
+            .cases
+            .cases()
+            .iter()
+            .map(|case| {
+                json!({
+                    "check": case.check,
+                    "department": case.department,
+                    "state": case.state.name(),
+                    "violations": case.violations,
+                    "opened_at_ms": case.opened_at_ms,
+                    "updated_at_ms": case.updated_at_ms,
+                })
+            })
+            .collect();
+        json!({
+            "policy_version": self.policy.version,
+            "metrics": metrics_json(&self.latest, &self.policy),
+            "rate_history": rate_history,
+            "thresholds": self.thresholds,
+            "checks": checks,
+            "purposes": purposes,
+            "lawful_bases": lawful_bases,
+            "risk_factors": risk_factors,
+            "top_users": top_users,
+            "vendors": vendors,
+            "shadow_ai": shadow_ai_uses(metrics)
+                .iter()
+                .map(|shadow_ai_use| {
+                    json!({
+                        "service": shadow_ai_use.service,
+                        "model": shadow_ai_use.model,
+                        "events": shadow_ai_use.events,
+                        "avg_sensitivity": shadow_ai_use.avg_sensitivity,
+                        "departments": shadow_ai_use
+                            .departments()
+                            .into_iter()
+                            .map(|(name, events)| json!({ "name": name, "events": events }))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "health": self.health.as_ref().map(health_json),
+            "threads": self.threads.iter().map(thread_json).collect::<Vec<_>>(),
+            "cases": {
+                "violations_by_state": CASE_STATES
+                    .iter()
+                    .map(|state| json!({ "state": state.name(), "violations": violations_by_state[*state as usize] }))
+                    .collect::<Vec<_>>(),
+                "cases": cases,
+            },
+            "alerts": {
+                "banner": self.alerts.most_severe_active(),
+                "active": self.alerts.active_count(),
+                "silence": self.alerts.silence(),
+                // Newest first, as listed on the Alerts tab.
+                "history": self.alerts.alerts().iter().rev().collect::<Vec<_>>(),
+            },
+            "fleet": self.fleet,
+            "candidate": self.comparison,
+            "sampled_events": self.sampled_events_json(),
+            "replay": self.replay,
+            "notice": self.notice,
+            "errors": { "count": self.error_count, "recent": self.errors },
+            "logs": logging::recent_lines(LOG_LINES),
+        })
+    }
+}
+
+impl WebView {
+    /// Formats the newest sampled events and the groups with the most estimated violations.
+    fn sampled_events_json(&self) -> Value {
+        let events = self.sampled_events.as_deref().unwrap_or_default();
+        json!({
+            "enabled": self.sampled_events.is_some(),
+            "retained": events.len(),
+            "top_groups": top_groups(events, TOP_SAMPLE_GROUPS),
+            "events": &events[..events.len().min(SAMPLED_EVENTS)],
+        })
+    }
+}
+
+/// Formats the pipeline health with durations in microseconds.
+fn health_json(health: &PipelineHealth) -> Value {
+    json!({
+        "queue_depth": health.queue_depth,
+        "queue_capacity": health.queue_capacity,
+        "peak_queue_depth": health.peak_queue_depth,
+        "injected_sink_failures": health.injected_sink_failures,
+        "channels": health
+            .channels
+            .iter()
+            .map(|channel| json!({ "name": channel.name, "depth": channel.depth, "capacity": channel.capacity }))
+            .collect::<Vec<_>>(),
+        "rss_bytes": health.rss_bytes,
+        "aggregator_lag_us": health.aggregator_lag.as_micros() as u64,
+        "system_latencies": health
+            .system_latencies
+            .iter()
+            .map(|system| json!({ "name": system.name, "latency": tail_latency_json(&system.latency) }))
+            .collect::<Vec<_>>(),
+        "batch_latency": health.batch_latency.as_ref().map(tail_latency_json),
+        "run_batch_latency": health.run_batch_latency.as_ref().map(tail_latency_json),
+    })
+}
+
+/// Formats the throughput of a worker thread with its batch latency in microseconds.
+fn thread_json(thread: &ThreadThroughput) -> Value {
+    json!({
+        "name": thread.name,
+        "core": thread.core,
+        "events": thread.events,
+        "rate": thread.rate,
+        "batch_latency": thread.batch_latency.as_ref().map(tail_latency_json),
+        "alive": thread.alive,
+        "restarts": thread.restarts,
+    })
+}
+
+/// Formats HDR batch latency percentiles in microseconds.
+fn tail_latency_json(latency: &TailLatency) -> Value {
+    json!({
+        "batches": latency.batches,
+        "p50_us": latency.p50.as_micros() as u64,
+        "p95_us": latency.p95.as_micros() as u64,
+        "p999_us": latency.p999.as_micros() as u64,
+        "max_us": latency.max.as_micros() as u64,
+    })
+}
+
+/// Reads a single request and answers it.
+fn respond(mut stream: TcpStream, view: &WebView) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    // The headers are read so that closing the connection does not reset it before the browser
+    // has read the response.
+    let mut head = BufReader::new(&stream).take(MAX_REQUEST_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut header = String::new();
+    while head.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split_once('?').map_or(target, |(path, _query)| path);
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/" | "/index.html") => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+        ("GET", "/api/snapshot") => ("200 OK", "application/json", view.snapshot_json().to_string()),
+        (_, "/" | "/index.html" | "/api/snapshot") => ("405 Method Not Allowed", "text/plain", "only GET is supported\n".to_string()),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertSeverity;
+    use crate::cases::CaseState;
+    use crate::constants::DEPARTMENT_NAMES;
+    use crate::metrics::{ComplianceMetrics, IntervalMetrics};
+
+    #[test]
+    fn snapshots_hold_the_latest_update_of_each_kind() {
+        let policy = Arc::new(Policy::builtin());
+        let thresholds = ChartThresholds { compliance_target: Some(95.0), max_rate: None };
+        let mut view = WebView::new(policy, thresholds);
+        let interval = ComplianceMetrics {
+            total_events: 100,
+            violation_counts: vec![0, 5, 0, 0, 0],
+            purpose_counts: [10, 0, 0, 0, 0, 0],
+            ..ComplianceMetrics::default()
+        };
+        let mut metrics = CumulativeMetrics::default();
+        metrics.record(&IntervalMetrics::new(interval, Duration::from_secs(1), 7_000));
+        view.update(&FrontendCommand::UpdateMetrics(Box::new(metrics)));
+        let mut delta = ComplianceMetrics {
+            departments: vec![ComplianceMetrics::default(); DEPARTMENT_NAMES.len()],
+            ..ComplianceMetrics::default()
+        };
+        delta.departments[1].high_risk_violations = vec![0, 3, 0, 0, 0];
+        let mut cases = CaseBook::default();
+        cases.record(&delta, &view.policy.compliance, 1_000);
+        cases.set_state(0, CaseState::Waived, 2_000);
+        view.update(&FrontendCommand::UpdateCases(cases));
+        let mut alerts = AlertLog::default();
+        alerts.raise("slo_burn", "HR", AlertSeverity::Critical, "HR is burning", "4.0x".to_string(), 3_000);
+        view.update(&FrontendCommand::UpdateAlerts(alerts));
+        view.update(&FrontendCommand::ReportError("sink failed".to_string()));
+        view.update(&FrontendCommand::ReportError("sink failed again".to_string()));
+        view.update(&FrontendCommand::ShowNotice("exported".to_string()));
+
+        let snapshot = view.snapshot_json();
+        assert_eq!(snapshot["metrics"]["events"], 100);
+        assert_eq!(snapshot["metrics"]["updated_at_ms"], 7_000);
+        assert_eq!(snapshot["metrics"]["violations"]["gdpr"], 5);
+        assert_eq!(snapshot["checks"][1]["key"], "gdpr");
+        assert_eq!(snapshot["checks"][1]["violations"], 5);
+        assert_eq!(snapshot["purposes"][0]["events"], 10);
+        assert_eq!(snapshot["rate_history"][0]["unix_ms"], 7_000);
+        assert_eq!(snapshot["rate_history"][0]["rate"], 100.0);
+        assert_eq!(snapshot["rate_history"][0]["compliance"], 99.0);
+        assert_eq!(snapshot["thresholds"]["compliance_target"], 95.0);
+        assert!(snapshot["thresholds"]["max_rate"].is_null());
+        assert_eq!(snapshot["cases"]["cases"][0]["state"], "Waived");
+        assert_eq!(snapshot["cases"]["violations_by_state"][2]["violations"], 3);
+        assert_eq!(snapshot["alerts"]["banner"]["severity"], "critical");
+        assert_eq!(snapshot["alerts"]["history"][0]["value"], "4.0x");
+        assert_eq!(snapshot["errors"]["count"], 2);
+        assert_eq!(snapshot["errors"]["recent"][1], "sink failed again");
+        assert_eq!(snapshot["notice"], "exported");
+        assert!(snapshot["health"].is_null());
+        assert!(snapshot["replay"].is_null());
+    }
+}
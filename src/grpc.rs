@@ -0,0 +1,317 @@
+use crate::components::{Event, AI_ACT_TIERS};
+use crate::constants::{DEPARTMENT_NAMES, LAWFUL_BASIS_NAMES, PII_TYPE_NAMES, PURPOSE_NAMES, REGION_NAMES, SERVICE_NAMES, VENDOR_NAMES};
+use crate::ecs::{process_batch, PipelineConfig};
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError};
+use crate::metrics::{ComplianceMetrics, LatestMetrics, SharedLatestMetrics};
+use crate::policy::{LivePolicy, Policy};
+use crate::replay::EventRecord;
+use crossbeam_channel::Sender;
+use hecs::World;
+use proto::ingestion_server::{Ingestion, IngestionServer};
+use proto::metrics_query_server::{MetricsQuery, MetricsQueryServer};
+use std::io;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+
+/// Types and services generated from `proto/compliance.proto`.
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("ecs_ai_compliance.v1");
+}
+
+/// Most ingested events evaluated in one batch.
+const INGEST_BATCH_SIZE: usize = 1_000;
+
+/// How long an ingestion stream may be idle before the events received so far are evaluated,
+/// and how often the server checks the stop signal.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Threads of the runtime serving gRPC requests.
+const RUNTIME_THREADS: usize = 2;
+
+/// Everything the gRPC services need from the monitor.
+pub struct GrpcContext {
+    /// The active policy, applied to ingested events and used to name checks.
+    pub policy: Arc<LivePolicy>,
+    /// How ingested batches are evaluated and timestamped.
+    pub config: PipelineConfig,
+    /// Channel sender into the aggregator for the metrics of ingested events.
+    pub metrics_sender: Sender<ComplianceMetrics>,
+    /// Metrics returned by queries, updated by the aggregator every reporting interval.
+    pub latest: SharedLatestMetrics,
+}
+
+/// Serves the ingestion and metrics query services until the stop signal is set.
+///
+/// Requests run on a small Tokio runtime owned by this thread. Ingested events are evaluated by
+/// the same systems as generated ones, each stream in its own world, and their metrics are sent
+/// to the aggregator like those of a remote worker. Streams still open at shutdown are ended with
+/// `UNAVAILABLE` after their pending events are evaluated.
+///
+/// # Arguments
+///
+/// * `listener` - Socket bound to the `--grpc-listen` address.
+/// * `context` - Policy, pipeline settings, and channels shared with the monitor.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn serve_grpc(listener: TcpListener, context: GrpcContext, stop_signal: Arc<AtomicBool>, errors: Sender<ErrorReport>) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(RUNTIME_THREADS)
+        .thread_name("grpc-runtime")
+        .enable_all()
+        .build();
+    match runtime {
+        Ok(runtime) => runtime.block_on(serve_grpc_async(listener, context, stop_signal, errors)),
+        Err(e) => report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade),
+    }
+}
+
+/// Serves the ingestion and metrics query services on the current Tokio runtime until the stop
+/// signal is set; [`serve_grpc`] without a runtime of its own, for the `grpc-shared-runtime` feature.
+///
+/// # Arguments
+///
+/// * `listener` - Socket bound to the `--grpc-listen` address.
+/// * `context` - Policy, pipeline settings, and channels shared with the monitor.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub async fn serve_grpc_async(listener: TcpListener, context: GrpcContext, stop_signal: Arc<AtomicBool>, errors: Sender<ErrorReport>) {
+    let serve = async {
+        listener.set_nonblocking(true)?;
+        let incoming = TcpListenerStream::new(tokio::net::TcpListener::from_std(listener)?);
+        let ingestion = IngestionService {
+            policy: context.policy.clone(),
+            config: context.config,
+            metrics_sender: context.metrics_sender,
+            stop_signal: stop_signal.clone(),
+        };
+        let query = MetricsQueryService {
+            policy: context.policy,
+            latest: context.latest,
+        };
+        let shutdown = async {
+            while !stop_signal.load(Ordering::Relaxed) {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        };
+        Server::builder()
+            .add_service(IngestionServer::new(ingestion))
+            .add_service(MetricsQueryServer::new(query))
+            .serve_with_incoming_shutdown(incoming, shutdown)
+            .await
+            .map_err(io::Error::other)
+    };
+    if let Err(e) = serve.await {
+        report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade);
+    }
+}
+
+/// Evaluates streamed events and forwards their metrics to the aggregator.
+struct IngestionService {
+    policy: Arc<LivePolicy>,
+    config: PipelineConfig,
+    metrics_sender: Sender<ComplianceMetrics>,
+    stop_signal: Arc<AtomicBool>,
+}
+
+impl IngestionService {
+    /// Evaluates a batch of ingested events and sends its metrics to the aggregator.
+    ///
+    /// Returns false if the aggregator has stopped.
+    fn evaluate(&self, world: &mut World, batch: &mut Vec<Event>) -> bool {
+        if batch.is_empty() {
+            return true;
+        }
+        let parallelism = self.config.parallelism;
+        // Evaluation is CPU-bound and sending may wait for the aggregator, so neither may hold
+        // up the runtime's other tasks.
+        let sent = tokio::task::block_in_place(|| {
+            let policy = self.policy.current();
+            let metrics = process_batch(world, batch, &policy, parallelism, None);
+            self.metrics_sender.send(metrics).is_ok()
+        });
+        batch.clear();
+        sent
+    }
+}
+
+#[tonic::async_trait]
+impl Ingestion for IngestionService {
+    async fn stream_events(
+        &self,
+        request: Request<Streaming<proto::AiUsageEvent>>,
+    ) -> Result<Response<proto::IngestSummary>, Status> {
+        let mut stream = request.into_inner();
+        let mut world = World::new();
+        let mut batch = Vec::with_capacity(INGEST_BATCH_SIZE);
+        let mut summary = proto::IngestSummary::default();
+        loop {
+            if self.stop_signal.load(Ordering::Relaxed) {
+                self.evaluate(&mut world, &mut batch);
+                return Err(shutting_down());
+            }
+            let event = match tokio::time::timeout(POLL_INTERVAL, stream.message()).await {
+                Ok(message) => message?,
+                Err(_idle) if self.evaluate(&mut world, &mut batch) => continue,
+                Err(_idle) => return Err(shutting_down()),
+            };
+            let Some(event) = event else {
+                break;
+            };
+            let mut record = event_record(event, self.config.clock.unix_millis());
+            if record.prompt.is_some() {
+                self.policy.current().content_scan.classify(&mut record);
+            }
+            match record.to_components() {
+                Some(components) => {
+                    batch.push(components);
+                    summary.accepted += 1;
+                }
+                None => summary.rejected += 1,
+            }
+            if batch.len() >= INGEST_BATCH_SIZE && !self.evaluate(&mut world, &mut batch) {
+                return Err(shutting_down());
+            }
+        }
+        if !self.evaluate(&mut world, &mut batch) {
+            return Err(shutting_down());
+        }
+        Ok(Response::new(summary))
+    }
+}
+
+/// The status ending streams that are still open when the monitor stops.
+fn shutting_down() -> Status {
+    Status::unavailable("the monitor is shutting down")
+}
+
+/// Answers metric queries from the latest metrics published by the aggregator.
+struct MetricsQueryService {
+    policy: Arc<LivePolicy>,
+    latest: SharedLatestMetrics,
+}
+
+#[tonic::async_trait]
+impl MetricsQuery for MetricsQueryService {
+    async fn get_metrics(&self, _request: Request<proto::GetMetricsRequest>) -> Result<Response<proto::ComplianceMetrics>, Status> {
+        let latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(Response::new(metrics_message(&latest, &self.policy.current())))
+    }
+}
+
+/// Converts an ingested event into the record format of event logs, whose names it shares.
+///
+/// # Arguments
+///
+/// * `event` - The ingested event.
+/// * `timestamp_ms` - Time the event was ingested, in milliseconds since the Unix epoch.
+fn event_record(event: proto::AiUsageEvent, timestamp_ms: u64) -> EventRecord {
+    EventRecord {
+        timestamp_ms,
+        service: event.service,
+        model: event.model,
+        vendor: event.vendor,
+        department: event.department,
+        data_sensitivity: event.data_sensitivity.min(100) as u8,
+        user_id: event.user_id,
+        purpose: event.purpose,
+        region: event.region,
+        lawful_basis: event.lawful_basis,
+        pii_types: event.pii_types,
+        prompt: event.prompt,
+    }
+}
+
+/// Builds the query response for the latest metrics.
+///
+/// # Arguments
+///
+/// * `latest` - The latest cumulative metrics and when they were updated.
+/// * `policy` - The active policy, used to name the compliance checks and score compliance.
+pub fn metrics_message(latest: &LatestMetrics, policy: &Policy) -> proto::ComplianceMetrics {
+    let metrics = &latest.metrics;
+    let counts = |names: &[&str], counts: &[u64]| {
+        names
+            .iter()
+            .zip(counts)
+            .map(|(name, count)| proto::NamedCount { name: name.to_string(), count: *count })
+            .collect()
+    };
+    let tier_names: Vec<&str> = AI_ACT_TIERS.iter().map(|tier| tier.name()).collect();
+    proto::ComplianceMetrics {
+        updated_at_ms: latest.updated_at_ms,
+        total_events: metrics.total_events,
+        compliance_percentage: policy.scoring.score(metrics),
+        processing_rate: metrics.processing_rate(),
+        violations: policy
+            .compliance
+            .iter()
+            .map(|(flag, info)| proto::CheckViolations {
+                check: info.key.to_string(),
+                name: info.name.to_string(),
+                violations: metrics.violations(flag),
+                waived: metrics.waived_counts.get(flag.0 as usize).copied().unwrap_or(0),
+            })
+            .collect(),
+        high_risk_count: metrics.high_risk_count,
+        medium_risk_count: metrics.medium_risk_count,
+        low_risk_count: metrics.low_risk_count,
+        waived_events: metrics.waived_events,
+        avg_data_sensitivity: metrics.avg_data_sensitivity,
+        services: counts(&SERVICE_NAMES, &metrics.service_counts),
+        vendors: counts(&VENDOR_NAMES, &metrics.vendor_counts),
+        departments: counts(&DEPARTMENT_NAMES, &metrics.department_counts),
+        purposes: counts(&PURPOSE_NAMES, &metrics.purpose_counts),
+        regions: counts(&REGION_NAMES, &metrics.region_counts),
+        ai_act_tiers: counts(&tier_names, &metrics.ai_act_tier_counts),
+        lawful_bases: counts(&LAWFUL_BASIS_NAMES, &metrics.lawful_basis_counts),
+        pii_types: counts(&PII_TYPE_NAMES, &metrics.pii_type_counts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Parallelism;
+    use crate::metrics::CumulativeMetrics;
+
+    #[test]
+    fn ingested_events_resolve_like_event_log_records_and_metrics_are_named() {
+        let event = proto::AiUsageEvent {
+            service: "Claude".to_string(),
+            model: None,
+            vendor: "Anthropic".to_string(),
+            department: "Legal".to_string(),
+            data_sensitivity: 250,
+            user_id: Some(7),
+            purpose: Some("Data analysis".to_string()),
+            region: Some("EU".to_string()),
+            lawful_basis: Some("Contract".to_string()),
+            pii_types: vec!["Contact details".to_string()],
+            prompt: None,
+        };
+        let (service, usage, user, ..) = event_record(event.clone(), 0).to_components().unwrap();
+        assert_eq!(SERVICE_NAMES[service.name_idx as usize], "Claude");
+        assert_eq!(usage.data_sensitivity, 100);
+        assert_eq!(user.known(), Some(7));
+        let unknown = proto::AiUsageEvent { vendor: "Acme".to_string(), ..event.clone() };
+        assert!(event_record(unknown, 0).to_components().is_none());
+
+        let policy = Policy::builtin();
+        let components = vec![event_record(event, 0).to_components().unwrap(); 4];
+        let metrics = process_batch(&mut World::new(), &components, &policy, Parallelism::Sequential, None);
+        let message = metrics_message(&LatestMetrics { metrics: CumulativeMetrics::resume(metrics), updated_at_ms: 42 }, &policy);
+        assert_eq!(message.updated_at_ms, 42);
+        assert_eq!(message.total_events, 4);
+        assert_eq!(message.violations.len(), policy.compliance.len());
+        assert_eq!(message.violations[1].check, "gdpr");
+        let legal = message.departments.iter().find(|department| department.name == "Legal").unwrap();
+        assert_eq!(legal.count, 4);
+        assert_eq!(message.ai_act_tiers.len(), 4);
+    }
+}
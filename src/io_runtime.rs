@@ -0,0 +1,126 @@
+use crate::threads::spawn_named;
+use std::io;
+use std::thread;
+use std::thread::JoinHandle;
+
+#[cfg(feature = "grpc-shared-runtime")]
+use std::future::Future;
+#[cfg(feature = "grpc-shared-runtime")]
+use std::sync::OnceLock;
+#[cfg(feature = "grpc-shared-runtime")]
+use tokio::runtime::Runtime;
+
+/// Threads of the shared runtime driving asynchronous I/O, currently only the gRPC server.
+#[cfg(feature = "grpc-shared-runtime")]
+const IO_RUNTIME_THREADS: usize = 2;
+
+/// The runtime hosting the asynchronous I/O subsystems, built on first use.
+#[cfg(feature = "grpc-shared-runtime")]
+static IO_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Returns the shared runtime hosting the asynchronous I/O subsystems, building it on first use.
+#[cfg(feature = "grpc-shared-runtime")]
+pub fn io_runtime() -> io::Result<&'static Runtime> {
+    if let Some(runtime) = IO_RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(IO_RUNTIME_THREADS)
+        .thread_name("io-runtime")
+        .enable_all()
+        .build()?;
+    Ok(IO_RUNTIME.get_or_init(|| runtime))
+}
+
+/// A running I/O subsystem: a thread of its own, or a task on the shared runtime, as the gRPC
+/// server is with the `grpc-shared-runtime` feature.
+pub struct IoHandle(Running);
+
+enum Running {
+    Thread(JoinHandle<()>),
+    #[cfg(feature = "grpc-shared-runtime")]
+    Task(tokio::task::JoinHandle<()>),
+}
+
+impl IoHandle {
+    /// Returns true once the subsystem has returned.
+    pub fn is_finished(&self) -> bool {
+        match &self.0 {
+            Running::Thread(thread) => thread.is_finished(),
+            #[cfg(feature = "grpc-shared-runtime")]
+            Running::Task(task) => task.is_finished(),
+        }
+    }
+
+    /// Waits for the subsystem to return, like [`std::thread::JoinHandle::join`]: an error carries its panic.
+    pub fn join(self) -> thread::Result<()> {
+        match self.0 {
+            Running::Thread(thread) => thread.join(),
+            #[cfg(feature = "grpc-shared-runtime")]
+            Running::Task(task) => {
+                let runtime = IO_RUNTIME.get().expect("tasks run on the shared runtime");
+                match runtime.block_on(task) {
+                    Ok(()) => Ok(()),
+                    Err(e) if e.is_panic() => Err(e.into_panic()),
+                    Err(e) => Err(Box::new(e.to_string())),
+                }
+            }
+        }
+    }
+}
+
+/// Starts a blocking I/O subsystem, such as an HTTP server or a webhook sender fed by a channel,
+/// on a thread with the given name.
+///
+/// Blocking subsystems keep a thread each even with the `grpc-shared-runtime` feature; only
+/// asynchronous ones, started with `spawn_io_task`, share the runtime, and the gRPC server is the
+/// only one of those.
+///
+/// # Arguments
+///
+/// * `name` - Name of the thread, shown in debuggers, profilers, and `top -H`.
+/// * `f` - The subsystem, returning when it stops.
+pub fn spawn_io(name: &str, f: impl FnOnce() + Send + 'static) -> io::Result<IoHandle> {
+    Ok(IoHandle(Running::Thread(spawn_named(name, f)?)))
+}
+
+/// Starts an asynchronous I/O subsystem as a task on the shared runtime.
+///
+/// # Arguments
+///
+/// * `future` - The subsystem, completing when it stops.
+#[cfg(feature = "grpc-shared-runtime")]
+pub fn spawn_io_task(future: impl Future<Output = ()> + Send + 'static) -> io::Result<IoHandle> {
+    Ok(IoHandle(Running::Task(io_runtime()?.spawn(future))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+
+    #[test]
+    fn blocking_subsystems_run_on_named_threads() {
+        let (sender, receiver) = unbounded();
+        let handle = spawn_io("io-runtime-test", move || {
+            sender.send(thread::current().name().map(str::to_string)).unwrap();
+        })
+        .unwrap();
+        assert_eq!(receiver.recv().unwrap().as_deref(), Some("io-runtime-test"));
+        assert!(handle.join().is_ok());
+        assert!(spawn_io("io-runtime-panic", || panic!("subsystem failed")).unwrap().join().is_err());
+    }
+
+    #[cfg(feature = "grpc-shared-runtime")]
+    #[test]
+    fn asynchronous_subsystems_run_on_the_shared_runtime() {
+        let (sender, receiver) = unbounded();
+        let handle = spawn_io_task(async move {
+            sender.send(thread::current().name().map(str::to_string)).unwrap();
+        })
+        .unwrap();
+        assert!(handle.join().is_ok());
+        assert_eq!(receiver.recv().unwrap().as_deref(), Some("io-runtime"));
+        assert!(spawn_io_task(async { panic!("subsystem failed") }).unwrap().join().is_err());
+    }
+}
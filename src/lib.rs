@@ -0,0 +1,73 @@
+//! ECS pipeline, sinks, and frontends of the AI compliance monitor.
+//!
+//! The `ecs_ai_compliance` binary wires these together; they are exposed as a library so that
+//! benchmarks can drive the pipeline directly.
+
+pub mod admin;
+pub mod alerts;
+pub mod allowlist;
+pub mod analyze;
+pub mod audit;
+pub mod badge;
+pub mod batching;
+pub mod bench;
+pub mod cases;
+pub mod chaos;
+pub mod clock;
+pub mod compliance;
+pub mod compliance_report;
+pub mod components;
+pub mod config;
+pub mod constants;
+pub mod content_scan;
+pub mod ecs;
+pub mod error;
+pub mod event_log;
+pub mod event_profile;
+pub mod event_sample;
+pub mod event_time;
+pub mod evidence;
+pub mod export;
+pub mod federation;
+pub mod frontend;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod import;
+pub mod io_runtime;
+pub mod logging;
+pub mod metrics;
+pub mod nist;
+#[cfg(feature = "pagerduty")]
+pub mod pagerduty;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
+pub mod policy;
+pub mod profile;
+pub mod provenance;
+pub mod remote;
+pub mod replay;
+pub mod risk;
+pub mod scoring;
+#[cfg(feature = "siem")]
+pub mod siem;
+pub mod shadow;
+pub mod shadow_ai;
+pub mod signing;
+pub mod silence;
+pub mod sink;
+pub mod run_report;
+#[cfg(feature = "slack")]
+pub mod slack;
+pub mod slo;
+pub mod snapshot;
+pub mod source;
+pub mod soak;
+pub mod spike;
+pub mod statsd;
+pub mod supervisor;
+pub mod tail_latency;
+pub mod threads;
+pub mod traffic;
+pub mod ui;
+pub mod vendors;
+pub mod waivers;
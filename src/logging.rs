@@ -0,0 +1,178 @@
+use crate::error::{MonitorError, Result};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Write};
+use std::panic;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::{self, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Layer;
+
+/// Prefix of the daily log files, e.g. `ecs_ai_compliance.2025-01-31.log`.
+const LOG_FILE_PREFIX: &str = "ecs_ai_compliance";
+
+/// Number of daily log files kept before the oldest is deleted.
+const MAX_LOG_FILES: usize = 7;
+
+/// Number of recent log lines kept in memory for the dashboard's Logs tab.
+const MAX_RECENT_LINES: usize = 500;
+
+/// The most recent log lines, oldest first.
+static RECENT_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Set while the dashboard owns the terminal, so log output does not corrupt it.
+static CONSOLE_SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Returns the log level selected by the number of `-v` flags.
+pub fn level_for_verbosity(verbose: u8) -> LevelFilter {
+    match verbose {
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Installs the process-wide log subscriber and routes panic messages through it.
+///
+/// Events at the selected level are written to a daily rotating log file and kept in memory for
+/// the Logs tab. Warnings and errors are also printed to stderr unless the dashboard owns the
+/// terminal.
+///
+/// # Arguments
+///
+/// * `dir` - Directory for the log files; created if missing.
+/// * `verbose` - Number of `-v` flags given.
+///
+/// # Returns
+///
+/// A guard that flushes the log file when dropped; keep it alive until shutdown.
+pub fn init(dir: &Path, verbose: u8) -> Result<WorkerGuard> {
+    let log_dir_error = |e: &dyn std::fmt::Display| MonitorError::Config(format!("cannot write logs to {}: {}", dir.display(), e));
+    // The appender prunes old files on startup, which fails if the directory does not exist yet.
+    fs::create_dir_all(dir).map_err(|e| log_dir_error(&e))?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| log_dir_error(&e))?;
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+    let level = level_for_verbosity(verbose);
+    let subscriber = tracing_subscriber::registry()
+        .with(fmt::layer().with_ansi(false).with_thread_names(true).with_writer(file_writer).with_filter(level))
+        .with(fmt::layer().with_ansi(false).with_target(false).with_writer(RecentLines).with_filter(level))
+        .with(fmt::layer().with_ansi(false).without_time().with_target(false).with_writer(Console).with_filter(LevelFilter::WARN));
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| MonitorError::Config(format!("cannot install logger: {}", e)))?;
+    panic::set_hook(Box::new(|info| {
+        let thread = thread::current();
+        tracing::error!("thread '{}' {}", thread.name().unwrap_or("unnamed"), info);
+    }));
+    Ok(guard)
+}
+
+/// Stops or resumes printing warnings and errors to stderr.
+pub fn suspend_console(suspended: bool) {
+    CONSOLE_SUSPENDED.store(suspended, Ordering::Relaxed);
+}
+
+/// Returns up to `limit` of the most recent log lines, oldest first.
+pub fn recent_lines(limit: usize) -> Vec<String> {
+    let lines = RECENT_LINES.lock().unwrap_or_else(|e| e.into_inner());
+    lines.iter().skip(lines.len().saturating_sub(limit)).cloned().collect()
+}
+
+/// Returns the number of log lines kept in memory.
+pub fn recent_line_count() -> usize {
+    RECENT_LINES.lock().unwrap_or_else(|e| e.into_inner()).len()
+}
+
+/// Appends a formatted line to the in-memory log.
+fn push_recent_line(line: &str) {
+    let mut lines = RECENT_LINES.lock().unwrap_or_else(|e| e.into_inner());
+    if lines.len() == MAX_RECENT_LINES {
+        lines.pop_front();
+    }
+    lines.push_back(line.to_string());
+}
+
+/// Writer factory for the in-memory log.
+struct RecentLines;
+
+impl<'a> MakeWriter<'a> for RecentLines {
+    type Writer = RecentLineWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentLineWriter(Vec::new())
+    }
+}
+
+/// Buffers one formatted event and stores its lines when dropped.
+struct RecentLineWriter(Vec<u8>);
+
+impl Write for RecentLineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentLineWriter {
+    fn drop(&mut self) {
+        String::from_utf8_lossy(&self.0).lines().for_each(push_recent_line);
+    }
+}
+
+/// Writer factory for stderr that discards output while the console is suspended.
+struct Console;
+
+impl<'a> MakeWriter<'a> for Console {
+    type Writer = ConsoleWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        ConsoleWriter
+    }
+}
+
+struct ConsoleWriter;
+
+impl Write for ConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if CONSOLE_SUSPENDED.load(Ordering::Relaxed) {
+            Ok(buf.len())
+        } else {
+            io::stderr().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_lines_keep_the_newest_lines() {
+        for i in 0..MAX_RECENT_LINES + 2 {
+            let mut writer = RecentLines.make_writer();
+            writeln!(writer, "line {}", i).unwrap();
+        }
+        let lines = recent_lines(2);
+        assert_eq!(lines, vec![format!("line {}", MAX_RECENT_LINES), format!("line {}", MAX_RECENT_LINES + 1)]);
+        assert_eq!(recent_lines(usize::MAX).len(), MAX_RECENT_LINES);
+    }
+}
@@ -1,130 +1,2007 @@
-mod components;
-mod constants;
-mod ecs;
-mod metrics;
-mod ui;
-
-use crate::components::Args;
-use crate::ecs::*;
-use crate::metrics::*;
-use crate::ui::dashboard::Dashboard;
-use crate::ui::tui::{setup_terminal, restore_terminal};
-
-use clap::Parser;
-use crossbeam_channel::unbounded;
-use std::io;
+use ecs_ai_compliance::admin::{serve_admin, AdminContext};
+use ecs_ai_compliance::alerts::{AlertLog, AlertSeverity};
+use ecs_ai_compliance::analyze::{analyze_events, NamedPolicy};
+use ecs_ai_compliance::audit::{audit_writer, verify_audit_log, AUDIT_SINK};
+use ecs_ai_compliance::badge::{serve_badge, Badge, BadgeFileSink, SharedBadge};
+use ecs_ai_compliance::batching::{default_worker_count, BatchController, RateControl};
+use ecs_ai_compliance::bench::{self, compare, load_baseline, measure, render, Verdict, Workload, BASELINE_SINK};
+use ecs_ai_compliance::cases::{CaseBook, CASES_SINK};
+use ecs_ai_compliance::chaos::{self, ChaosConfig};
+use ecs_ai_compliance::clock::{self, SharedClock};
+use ecs_ai_compliance::compliance_report::ComplianceReport;
+use ecs_ai_compliance::components::{AnalyzeArgs, Args, BenchArgs, Command, EvidenceArgs, ExportArgs, ImportArgs, LiveOutputArgs, ReplayArgs, ReportArgs, RunArgs, VerifyAuditArgs, WorkerArgs};
+use ecs_ai_compliance::config::{parse_layered, ConfigLayers};
+use ecs_ai_compliance::constants::DEPARTMENT_NAMES;
+use ecs_ai_compliance::ecs::*;
+use ecs_ai_compliance::error::{report, with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use ecs_ai_compliance::event_profile::EventProfile;
+use ecs_ai_compliance::event_sample::{EventSampler, RecentSamples, SampledEvent, SAMPLE_CHANNEL_CAPACITY};
+use ecs_ai_compliance::event_time::EventTimeWindows;
+use ecs_ai_compliance::evidence::export_evidence;
+use ecs_ai_compliance::export::{append_csv_series, append_sampled_events, CsvSeriesSink, CSV_SINK, MAX_RETAINED_SAMPLES};
+use ecs_ai_compliance::federation::{default_instance_name, federation_sender, Fleet};
+use ecs_ai_compliance::frontend::remote::connect;
+#[cfg(feature = "web")]
+use ecs_ai_compliance::frontend::web::WebFrontend;
+use ecs_ai_compliance::frontend::{
+    create_frontend, ChannelDepth, ChartThresholds, ControlCommand, Frontend, FrontendCommand, FrontendContext,
+    FrontendKind, PipelineHealth, SystemLatency, TailLatency, ThreadThroughput,
+};
+#[cfg(all(feature = "grpc", not(feature = "grpc-shared-runtime")))]
+use ecs_ai_compliance::grpc::serve_grpc;
+#[cfg(feature = "grpc-shared-runtime")]
+use ecs_ai_compliance::grpc::serve_grpc_async;
+#[cfg(feature = "grpc")]
+use ecs_ai_compliance::grpc::GrpcContext;
+#[cfg(feature = "grpc-shared-runtime")]
+use ecs_ai_compliance::io_runtime::spawn_io_task;
+use ecs_ai_compliance::event_log::ColumnMapping;
+use ecs_ai_compliance::import::import_logs;
+use ecs_ai_compliance::io_runtime::{spawn_io, IoHandle};
+use ecs_ai_compliance::logging;
+use ecs_ai_compliance::metrics::*;
+#[cfg(feature = "pagerduty")]
+use ecs_ai_compliance::pagerduty::{pagerduty_sender, AlertCondition, EventAction, IncidentTracker, PagerDutyConfig, PagerDutyEvent, Severity};
+#[cfg(feature = "parquet")]
+use ecs_ai_compliance::parquet_export::ParquetEventWriter;
+use ecs_ai_compliance::policy::{LivePolicy, Policy};
+use ecs_ai_compliance::profile::system_latencies;
+use ecs_ai_compliance::remote::{forward_reports, serve_remote_workers};
+use ecs_ai_compliance::replay::{record_events, replay_thread, RECORDER_SINK};
+use ecs_ai_compliance::run_report::{current_rss_bytes, RunReport, RUN_RESULT_SINK};
+#[cfg(feature = "siem")]
+use ecs_ai_compliance::siem::{siem_forwarder, SiemConfig, SIEM_BUFFER};
+use ecs_ai_compliance::signing::{load_signing_key, parse_public_key};
+use ecs_ai_compliance::silence::{load_silence_windows, Silences};
+use ecs_ai_compliance::sink::{write_to_sinks, Sink};
+#[cfg(feature = "slack")]
+use ecs_ai_compliance::slack::{slack_notifier, AlertSummary, SlackAlert, SlackConfig};
+use ecs_ai_compliance::slo::SloPolicy;
+use ecs_ai_compliance::snapshot::{load_snapshot, save_snapshot, SNAPSHOT_SINK};
+use ecs_ai_compliance::shadow::ShadowPolicy;
+use ecs_ai_compliance::shadow_ai::ShadowAiReportSink;
+use ecs_ai_compliance::soak::{MemorySample, SoakTest};
+use ecs_ai_compliance::source::SourceSpec;
+use ecs_ai_compliance::spike::ViolationSpikes;
+use ecs_ai_compliance::statsd::StatsdEmitter;
+use ecs_ai_compliance::supervisor::WorkerSupervisor;
+use ecs_ai_compliance::tail_latency;
+use ecs_ai_compliance::threads::{pin_current_thread, spawn_named, worker_cores};
+use ecs_ai_compliance::traffic::TrafficPattern;
+use ecs_ai_compliance::ui::keys::Keymap;
+use ecs_ai_compliance::ui::layout::DashboardLayout;
+use ecs_ai_compliance::ui::theme::Theme;
+use ecs_ai_compliance::waivers::{Waiver, WaiverStatus};
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, IsTerminal};
+use std::net::TcpListener;
 use std::num::NonZeroUsize;
+use std::path::Path;
+use std::process::ExitCode;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Time the aggregator loop sleeps between polls of its channels.
+const MONITOR_TICK: Duration = Duration::from_millis(50);
+
+/// Keys of the alert rules evaluated by the monitor loop, listed on the dashboard's Alerts tab.
+const WAIVER_EXPIRING_RULE: &str = "waiver_expiring";
+const WAIVER_EXPIRED_RULE: &str = "waiver_expired";
+const SLO_BURN_RULE: &str = "slo_burn";
+
+/// Channel endpoints and shared counters linking the monitor loop and the threads it supervises.
+struct MonitorChannels {
+    metrics_sender: Sender<ComplianceMetrics>,
+    metrics_receiver: Receiver<ComplianceMetrics>,
+    cmd_sender: Sender<FrontendCommand>,
+    cmd_receiver: Receiver<FrontendCommand>,
+    error_sender: Sender<ErrorReport>,
+    error_receiver: Receiver<ErrorReport>,
+    control_sender: Sender<ControlCommand>,
+    control_receiver: Receiver<ControlCommand>,
+    /// Event-time windows closed by a replay, reported in place of wall-clock intervals.
+    event_windows: Option<Receiver<IntervalMetrics>>,
+    /// Metric shards updated in place by the local workers.
+    worker_metrics: Arc<SharedMetrics>,
+    /// Core each local worker is pinned to, if any.
+    worker_cores: Vec<Option<usize>>,
+    /// Clock measuring reporting and snapshot intervals.
+    clock: SharedClock,
+    /// Latest compliance badge, served by `--badge-listen`.
+    badge: SharedBadge,
+    /// Outputs written every reporting interval: `--csv-export`, `--statsd`, `--badge-file`, and
+    /// `--shadow-ai-report`, whichever are given.
+    sinks: Vec<Box<dyn Sink>>,
+    /// The active policy, replaced when the admin API reloads it.
+    live_policy: Arc<LivePolicy>,
+    /// Metrics answering admin and gRPC queries, if `--admin-listen` or `--grpc-listen` is given.
+    latest_metrics: Option<SharedLatestMetrics>,
+    /// Interval metrics to push to a federation leader, if `--federate` is given.
+    federation: Option<Sender<IntervalMetrics>>,
+    /// Instances and remote workers reporting to this monitor, if `--listen` is given.
+    fleet: Option<Arc<Fleet>>,
+    /// Non-compliant events queued for the local workers by `i` and the admin API.
+    spikes: Arc<ViolationSpikes>,
+    /// Candidate policy evaluated next to the active one, if `--candidate-policy` or
+    /// `--candidate-waivers` is given.
+    shadow: Option<Arc<ShadowPolicy>>,
+    /// Reservoirs of evaluated events sampled by the local workers, unless `--event-sample-rate`
+    /// is 0.
+    sampled_events: Option<Receiver<Vec<SampledEvent>>>,
+    /// Alerts to post to Slack, if `--slack-webhook` is given.
+    #[cfg(feature = "slack")]
+    slack_alerts: Option<Sender<SlackAlert>>,
+    /// Events to send to PagerDuty and the configuration of their rules, if `--pagerduty` is given.
+    #[cfg(feature = "pagerduty")]
+    pagerduty: Option<(Sender<PagerDutyEvent>, PagerDutyConfig)>,
+}
+
+impl MonitorChannels {
+    /// Creates the channels for metrics reporting, dashboard commands, and error reports.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics_capacity` - Number of metrics reports the bounded metrics channel can hold.
+    /// * `worker_cores` - Core of each local worker that needs a metric shard.
+    /// * `policy` - The policy loaded at startup, used to size the shards.
+    /// * `clock` - Clock measuring reporting and snapshot intervals.
+    fn new(metrics_capacity: usize, worker_cores: Vec<Option<usize>>, policy: &Arc<Policy>, clock: SharedClock) -> Self {
+        let (metrics_sender, metrics_receiver) = bounded(metrics_capacity.max(1));
+        let (cmd_sender, cmd_receiver) = unbounded();
+        let (error_sender, error_receiver) = unbounded();
+        let (control_sender, control_receiver) = unbounded();
+        MonitorChannels {
+            metrics_sender,
+            metrics_receiver,
+            cmd_sender,
+            cmd_receiver,
+            error_sender,
+            error_receiver,
+            control_sender,
+            control_receiver,
+            event_windows: None,
+            worker_metrics: Arc::new(SharedMetrics::new(worker_cores.len(), policy)),
+            worker_cores,
+            clock,
+            badge: Arc::new(Mutex::new(Badge::new(&ComplianceMetrics::default()))),
+            sinks: Vec::new(),
+            live_policy: Arc::new(LivePolicy::new(policy.clone())),
+            latest_metrics: None,
+            federation: None,
+            fleet: None,
+            spikes: Arc::default(),
+            shadow: None,
+            sampled_events: None,
+            #[cfg(feature = "slack")]
+            slack_alerts: None,
+            #[cfg(feature = "pagerduty")]
+            pagerduty: None,
+        }
+    }
+}
 
 /// Main entry point for the AI Compliance ECS Demo application.
-fn main() -> io::Result<()> {
-    // Parse command line arguments.
-    let args = Args::parse();
+fn main() -> ExitCode {
+    // Parse command line arguments, filling in the environment and the config file.
+    let (args, layers) = match parse_layered(std::env::args_os().collect()) {
+        Ok(parsed) => parsed,
+        Err(MonitorError::Usage(e)) => e.exit(),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&args, &layers) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    // Determine optimal number of worker threads.
-    let thread_count = args.threads.unwrap_or_else(|| {
-        std::thread::available_parallelism()
-            .map(NonZeroUsize::get)
-            .unwrap_or(1)
-    });
+/// Installs the shutdown handler and dispatches to the selected mode.
+fn run(args: &Args, layers: &ConfigLayers) -> Result<()> {
+    if let Some(Command::Check(run_args)) = &args.command {
+        return run_check(args, run_args, layers);
+    }
+    // Log to a file from the start; the guard flushes it on return.
+    let _log_guard = logging::init(&args.log_dir, args.verbose)?;
+    if let Some(path) = &layers.config_file {
+        info!("read options from config file {}", path.display());
+    }
+    debug!("command line: {:?}", args);
+
+    // Set up a stop signal for graceful shutdown, triggered by Ctrl+C.
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let ctrl_c_stop = stop_signal.clone();
+    ctrlc::set_handler(move || {
+        ctrl_c_stop.store(true, Ordering::Relaxed);
+    })?;
+
+    // Build the policy shared by the workers and the frontend.
+    let policy = Arc::new(Policy::from_args(args)?);
+    let clock = clock::from_time_scale(args.time_scale)?;
+    match &args.command {
+        Some(Command::ExportEvidence(evidence_args)) => return run_export_evidence(args, evidence_args, &policy),
+        Some(Command::VerifyAudit(verify_args)) => return run_verify_audit(verify_args),
+        Some(Command::Report(report_args)) => return run_compliance_report(report_args, &policy),
+        Some(Command::Bench(bench_args)) => return run_bench(args, bench_args, &policy, &stop_signal),
+        Some(Command::Analyze(analyze_args)) => return run_analyze(args, analyze_args, &policy),
+        Some(Command::Import(import_args)) => return run_import(args, import_args, &policy),
+        _ => {}
+    }
+    // The dashboard needs a terminal; when piped or run as a service, report intervals as text.
+    let is_terminal = io::stdin().is_terminal() && io::stdout().is_terminal();
+    let frontend_kind = args.frontend.for_terminal(is_terminal);
+    if frontend_kind == FrontendKind::Remote && matches!(args.command, Some(Command::Connect(_))) {
+        return Err(MonitorError::Config("connect shows the remote dashboard itself; use --frontend tui, log, or json".to_string()));
+    }
+    let thresholds = ChartThresholds::new(args.compliance_target, args.max_rate)?;
+    let layout = args.layout.as_deref().map(DashboardLayout::load).transpose()?.unwrap_or_default();
+    let mut theme = Theme::load(&args.theme)?;
+    theme.ascii = args.ascii.enabled();
+    let keymap = Keymap::new(args.keybindings, &args.bind)?;
+    let frontend = create_frontend(frontend_kind, theme, thresholds, layout, keymap, &args.dashboard_listen)?;
+    #[cfg(feature = "web")]
+    let frontend: Box<dyn Frontend> = match &args.web {
+        Some(addr) => {
+            let frontend = WebFrontend::wrap(frontend, addr, thresholds)?;
+            println!("Serving the web dashboard on http://{}", addr);
+            Box::new(frontend)
+        }
+        None => frontend,
+    };
+    if frontend_kind != args.frontend {
+        warn!("not running in a terminal; using the {} instead of the TUI dashboard", frontend.name());
+    }
+    if frontend_kind == FrontendKind::Remote {
+        println!("Serving remote dashboards on {}", args.dashboard_listen);
+    }
+    if args.time_scale != 1.0 {
+        println!("Time scale: {}x real time", args.time_scale);
+    }
+
+    match &args.command {
+        Some(Command::Replay(replay_args)) => run_replay(args, replay_args, policy, frontend, clock, stop_signal),
+        Some(Command::Worker(worker_args)) => run_worker(args, worker_args, policy, clock, stop_signal),
+        Some(Command::Connect(connect_args)) => connect(&connect_args.addr, policy, frontend, clock, stop_signal),
+        Some(Command::Run(run_args)) => run_live(args, run_args, policy, frontend, clock, stop_signal),
+        Some(Command::ExportEvidence(_) | Command::VerifyAudit(_) | Command::Report(_) | Command::Bench(_) | Command::Analyze(_) | Command::Import(_) | Command::Check(_)) => {
+            unreachable!("commands without a frontend run before it starts")
+        }
+        None => unreachable!("the command line is parsed as `run` without a subcommand"),
+    }
+}
+
+/// Generates synthetic events on worker threads and monitors them through the frontend.
+fn run_live(
+    args: &Args,
+    run_args: &RunArgs,
+    policy: Arc<Policy>,
+    frontend: Box<dyn Frontend>,
+    clock: SharedClock,
+    stop_signal: Arc<AtomicBool>,
+) -> Result<()> {
+    let thread_count = worker_thread_count(args);
+
+    // Restore cumulative metrics from a previous run before any work starts.
+    let snapshot_file = run_args.snapshot_file.as_deref();
+    let initial_metrics = match snapshot_file {
+        Some(path) => load_snapshot(path)?.unwrap_or_default(),
+        None => CumulativeMetrics::default(),
+    };
 
     println!("AI Compliance ECS Demo");
+    if initial_metrics.total_events > 0 {
+        println!("Restored {} events from snapshot", initial_metrics.total_events);
+    }
     println!("Target processing rate: {} events/second", args.rate);
     println!("Using {} worker threads", thread_count);
+    match &args.source {
+        SourceSpec::File { path, .. } => println!("Event source: {}", path.display()),
+        SourceSpec::Synthetic => println!("Event profile: {}, {:?} traffic", args.event_profile, args.traffic),
+    }
     println!("Reporting interval: {} seconds", args.interval);
-    println!("Starting TUI dashboard...");
+    if let Some(hours) = run_args.soak {
+        println!("Soak test: {} hours, failing above {} MiB/hour of memory growth", hours, run_args.soak_max_slope);
+    }
+    if let Some(failure_rate) = run_args.chaos {
+        chaos::enable(ChaosConfig::new(failure_rate, Duration::from_millis(run_args.chaos_max_outage))?);
+        println!("Chaos enabled: injecting sink failures");
+    }
 
-    // Calculate events per thread and per batch.
-    let events_per_thread = args.rate as usize / thread_count;
-    let events_per_batch = events_per_thread / 100;
+    // Bind the remote worker socket before starting any work, so a bad address fails fast.
+    let listener = match &run_args.listen {
+        Some(addr) => {
+            let listener = TcpListener::bind(addr).map_err(|source| MonitorError::Listen { purpose: "remote workers", addr: addr.clone(), source })?;
+            println!("Accepting remote workers on {}", addr);
+            Some(listener)
+        }
+        None => None,
+    };
+    println!("Starting {}...", frontend.name());
 
-    // Set up channels for metrics reporting and dashboard commands.
-    let (metrics_sender, metrics_receiver) = unbounded();
-    let (cmd_sender, cmd_receiver) = unbounded();
+    let rate_control = Arc::new(RateControl::new(args.rate, thread_count));
+    let controller = batch_controller(args, &rate_control);
+    let event_profile = Arc::new(EventProfile::load(&args.event_profile)?);
+    let worker_cores = worker_cores(args.pin_cores.as_deref(), thread_count)?;
+    let started = Instant::now();
+    let (sample_sender, sample_receiver) = match run_args.event_sample_rate {
+        0 => (None, None),
+        _ => {
+            let (sender, receiver) = bounded(SAMPLE_CHANNEL_CAPACITY);
+            (Some(sender), Some(receiver))
+        }
+    };
+    // Each worker samples its share of the rate, rounded up so every worker forwards some.
+    let samples_per_worker = (run_args.event_sample_rate as usize).div_ceil(thread_count);
+    let channels = MonitorChannels {
+        sinks: interval_sinks(&run_args.outputs.exports)?,
+        fleet: listener.is_some().then(|| Arc::new(Fleet::default())),
+        shadow: shadow_policy(args, &policy)?,
+        sampled_events: sample_receiver,
+        ..MonitorChannels::new(args.channel_capacity, worker_cores, &policy, clock.clone())
+    };
+    let config = PipelineConfig {
+        parallelism: Parallelism::from_chunk_size(args.chunk_size),
+        clock,
+        event_profile,
+        traffic: args.traffic,
+        spikes: channels.spikes.clone(),
+        source: args.source.clone(),
+        shadow: channels.shadow.clone(),
+    };
+    #[cfg(feature = "slack")]
+    let (channels, slack_handle) = start_slack_notifier(&run_args.outputs, args.sink_error_policy, channels, &stop_signal)?;
+    #[cfg(feature = "pagerduty")]
+    let (channels, pagerduty_handle) = start_pagerduty_sender(&run_args.outputs, args.sink_error_policy, channels, &stop_signal, false)?;
+    #[cfg(feature = "grpc")]
+    let (channels, grpc_handle) = start_grpc_server(run_args, channels, &config, &stop_signal)?;
+    let (channels, admin_handle) = start_admin_server(run_args, channels, &rate_control, &stop_signal, started)?;
+    let (mut channels, federation_handle) = start_federation(&run_args.outputs, run_args.listen.as_deref(), channels)?;
 
-    // Set up a stop signal for graceful shutdown.
-    let stop_signal = Arc::new(AtomicBool::new(false));
+    // Launch the remote worker listener if requested.
+    let remote_handle = listener.map(|listener| {
+        let remote_stop = stop_signal.clone();
+        let remote_sender = channels.metrics_sender.clone();
+        let remote_errors = channels.error_sender.clone();
+        let remote_fleet = channels.fleet.clone().unwrap_or_default();
+        spawn_io("remote-listener", move || {
+            serve_remote_workers(listener, remote_stop, remote_sender, remote_errors, remote_fleet)
+        })
+    });
+    let remote_handle = remote_handle.transpose()?;
+    let badge_handle = start_badge_server(&run_args.outputs, &channels, &stop_signal)?;
+
+    // Launch the event recorder if requested.
+    let mut record_sender = None;
+    let mut recorder_handle = None;
+    if let Some(path) = run_args.record.clone() {
+        let (sender, receiver) = unbounded();
+        let recorder_errors = channels.error_sender.clone();
+        let sink_policy = args.sink_error_policy;
+        record_sender = Some(sender);
+        recorder_handle = Some(spawn_named("recorder", move || record_events(&path, receiver, sink_policy, recorder_errors))?);
+    }
+
+    // Launch the audit log writer if requested.
+    let mut audit_sender = None;
+    let mut audit_handle = None;
+    if let Some(path) = run_args.audit_log.clone() {
+        let signing_key = run_args.audit_signing_key.as_deref().map(load_signing_key).transpose()?;
+        let (sender, receiver) = bounded(run_args.audit_buffer.max(1));
+        let audit_errors = channels.error_sender.clone();
+        let sink_policy = args.sink_error_policy;
+        audit_sender = Some(sender);
+        audit_handle = Some(spawn_named("audit-writer", move || {
+            audit_writer(&path, receiver, signing_key, sink_policy, audit_errors)
+        })?);
+    }
+
+    // Launch the SIEM forwarder if requested.
+    #[cfg(feature = "siem")]
+    let (siem_sender, siem_handle) = match &run_args.siem_url {
+        Some(url) => {
+            let config = SiemConfig::new(
+                run_args.siem_format,
+                url,
+                run_args.siem_token_file.as_deref(),
+                run_args.siem_index.as_deref(),
+                run_args.siem_batch_size,
+                &run_args.siem_dead_letter,
+            )?;
+            println!("Forwarding violations to {}", url);
+            let (sender, receiver) = bounded(SIEM_BUFFER);
+            let siem_errors = channels.error_sender.clone();
+            let sink_policy = args.sink_error_policy;
+            let handle = spawn_io("siem-forwarder", move || siem_forwarder(config, receiver, sink_policy, siem_errors))?;
+            (Some(sender), Some(handle))
+        }
+        None => (None, None),
+    };
+
+    // Launch worker threads under a supervisor that restarts any that panic.
+    let spawn_worker = {
+        #[cfg(feature = "parquet")]
+        let policy = policy.clone();
+        let live_policy = channels.live_policy.clone();
+        let stop_signal = stop_signal.clone();
+        let worker_metrics = channels.worker_metrics.clone();
+        let worker_cores = channels.worker_cores.clone();
+        let errors = channels.error_sender.clone();
+        #[cfg(feature = "parquet")]
+        let parquet_dir = run_args.parquet_dir.clone();
+        #[cfg(feature = "parquet")]
+        let sink_policy = args.sink_error_policy;
+        move |worker: usize| {
+            let thread_shard = worker_metrics.shard(worker);
+            let thread_policy = live_policy.clone();
+            let thread_stop = stop_signal.clone();
+            let thread_sinks = WorkerSinks {
+                record: record_sender.clone(),
+                audit: audit_sender.clone(),
+                #[cfg(feature = "parquet")]
+                parquet: parquet_dir
+                    .as_deref()
+                    .map(|dir| ParquetEventWriter::new(dir, worker, policy.clone(), sink_policy, errors.clone())),
+                #[cfg(feature = "siem")]
+                siem: siem_sender.clone(),
+                samples: sample_sender.clone().map(|sender| EventSampler::new(worker, samples_per_worker, sender)),
+            };
+            let thread_errors = errors.clone();
+            let thread_config = config.clone();
+            let thread_controller = controller.clone();
+            let core = worker_cores[worker];
+            spawn_named(&format!("worker-{}", worker), move || {
+                pin_current_thread(core, &thread_errors);
+                worker_thread(
+                    thread_controller,
+                    thread_policy,
+                    thread_config,
+                    thread_stop,
+                    thread_shard,
+                    thread_sinks,
+                    thread_errors,
+                );
+            })
+        }
+    };
+    // Only the supervisor holds record, audit, and SIEM senders now, so those writers finish once
+    // it has stopped every worker.
+    let supervisor = WorkerSupervisor::start(thread_count, spawn_worker, channels.worker_metrics.clone())?;
+    let supervisor_stop = stop_signal.clone();
+    let supervisor_errors = channels.error_sender.clone();
+    let supervisor_handle = spawn_named("supervisor", move || supervisor.run(&supervisor_stop, &supervisor_errors))?;
+
+    let initial_events = initial_metrics.total_events;
+    let (mut total_metrics, mut result) = run_monitor(args, MonitorArgs::Run(run_args), initial_metrics, &policy, frontend, &stop_signal, &channels);
+    // Dropping the sender lets the federation sender deliver the last interval and finish.
+    channels.federation = None;
+
+    // Wait for all worker threads and the recorder to finish.
+    if supervisor_handle.join().is_err() && result.is_ok() {
+        result = Err(MonitorError::ThreadPanicked("worker supervisor"));
+    }
+    if let Some(handle) = remote_handle {
+        drain_until_finished(|| handle.is_finished(), &channels, &mut total_metrics);
+        if handle.join().is_err() && result.is_ok() {
+            result = Err(MonitorError::ThreadPanicked("remote worker listener"));
+        }
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(handle) = grpc_handle {
+        drain_until_finished(|| handle.is_finished(), &channels, &mut total_metrics);
+        if handle.join().is_err() && result.is_ok() {
+            result = Err(MonitorError::ThreadPanicked("gRPC server"));
+        }
+    }
+    if let Some(handle) = recorder_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("event recorder"));
+    }
+    if let Some(handle) = badge_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("badge server"));
+    }
+    if let Some(handle) = admin_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("admin server"));
+    }
+    if let Some(handle) = federation_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("federation sender"));
+    }
+    #[cfg(feature = "slack")]
+    if let Some(handle) = slack_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("Slack notifier"));
+    }
+    #[cfg(feature = "pagerduty")]
+    if let Some(handle) = pagerduty_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("PagerDuty sender"));
+    }
+    if let Some(handle) = audit_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("audit writer"));
+    }
+    #[cfg(feature = "siem")]
+    if let Some(handle) = siem_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("SIEM forwarder"));
+    }
+
+    // Fold in the workers' final metrics and persist the cumulative state.
+    total_metrics.add_late(&channels.worker_metrics.collect_new());
+    while let Ok(metrics) = channels.metrics_receiver.try_recv() {
+        total_metrics.add_late(&metrics);
+    }
+    if let Some(path) = snapshot_file {
+        match save_snapshot(path, &total_metrics) {
+            Ok(()) => {
+                info!("saved metrics snapshot to {}", path.display());
+                println!("Saved metrics snapshot to {}", path.display());
+            }
+            Err(source) => warn!("{}", MonitorError::Sink { sink: SNAPSHOT_SINK, source }),
+        }
+    }
+    log_late_errors(&channels.error_receiver);
+    print_policy_comparison(&channels);
+    print_chaos_summary();
+    let events_processed = total_metrics.total_events.saturating_sub(initial_events);
+    report_run(args, "live", started.elapsed(), events_processed, Some(args.rate));
+
+    info!("shutdown complete");
+    println!("Shutdown complete.");
+    result
+}
+
+/// Loads the candidate policy of shadow mode, if `--candidate-policy` or `--candidate-waivers`
+/// is given, and prints a summary of it.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments naming the candidate's files.
+/// * `active` - The active policy the candidate is compared with.
+fn shadow_policy(args: &Args, active: &Policy) -> Result<Option<Arc<ShadowPolicy>>> {
+    let Some(candidate) = Policy::candidate_from_args(args)? else {
+        return Ok(None);
+    };
+    println!(
+        "Shadow mode: comparing a candidate policy with {} checks and {} waivers",
+        candidate.compliance.len(),
+        candidate.waivers.len()
+    );
+    Ok(Some(Arc::new(ShadowPolicy::new(active, Arc::new(candidate)))))
+}
 
-    // Launch worker threads.
-    let mut worker_handles = Vec::with_capacity(thread_count);
-    for _ in 0..thread_count {
-        let thread_sender = metrics_sender.clone();
-        let thread_stop = stop_signal.clone();
-        let handle = thread::spawn(move || {
-            worker_thread(events_per_batch, thread_stop, thread_sender);
+/// Prints the violations of each check under the active and the candidate policy, if shadow
+/// mode is enabled.
+fn print_policy_comparison(channels: &MonitorChannels) {
+    if let Some(shadow) = &channels.shadow {
+        print!("{}", shadow.comparison(&channels.live_policy.current()).render());
+    }
+}
+
+/// Generates synthetic events on worker threads and reports their metrics to a remote aggregator.
+fn run_worker(
+    args: &Args,
+    worker_args: &WorkerArgs,
+    policy: Arc<Policy>,
+    clock: SharedClock,
+    stop_signal: Arc<AtomicBool>,
+) -> Result<()> {
+    let thread_count = worker_thread_count(args).max(1);
+
+    println!("AI Compliance ECS Demo");
+    println!("Target processing rate: {} events/second", args.rate);
+    println!("Using {} worker threads", thread_count);
+    let instance = worker_args.instance_name.clone().unwrap_or_else(default_instance_name);
+    println!("Reporting to aggregator {} as {} every {} seconds", worker_args.aggregator, instance, args.interval);
+
+    let (error_sender, error_receiver) = unbounded();
+    let started = Instant::now();
+    let (worker_metrics, supervisor_handle) = start_headless_workers(args, thread_count, policy, clock.clone(), &stop_signal, &error_sender)?;
+
+    // Forwarding ends once every worker has stopped and its final counts have been sent.
+    let interval = Duration::from_secs(args.interval);
+    let workers_finished = || supervisor_handle.is_finished();
+    let mut result = forward_reports(
+        &worker_args.aggregator,
+        &instance,
+        worker_args.compress,
+        interval,
+        clock.as_ref(),
+        &worker_metrics,
+        workers_finished,
+    );
+    if result.is_err() {
+        stop_signal.store(true, Ordering::Relaxed);
+    }
+    if supervisor_handle.join().is_err() && result.is_ok() {
+        result = Err(MonitorError::ThreadPanicked("worker supervisor"));
+    }
+    log_late_errors(&error_receiver);
+    report_run(args, "worker", started.elapsed(), worker_metrics.load().total_events, Some(args.rate));
+
+    info!("shutdown complete");
+    println!("Shutdown complete.");
+    result
+}
+
+/// Runs every workload of the benchmark matrix and prints its statistics, compared with a
+/// saved baseline if one is given.
+///
+/// # Errors
+///
+/// [`MonitorError::BenchRegression`] if any workload regressed against the baseline, so that CI
+/// jobs running the benchmark fail.
+fn run_bench(args: &Args, bench_args: &BenchArgs, policy: &Policy, stop_signal: &AtomicBool) -> Result<()> {
+    let baseline = bench_args.baseline.as_deref().map(load_baseline).transpose()?;
+    let workloads = Workload::matrix(&bench_args.entities, &bench_args.thread_counts, &bench_args.batch_sizes);
+    let parallelism = Parallelism::from_chunk_size(args.chunk_size);
+    println!("Benchmarking {} workloads, {} samples each", workloads.len(), bench_args.samples);
+    if let Some(path) = &bench_args.baseline {
+        println!("Comparing with baseline {}", path.display());
+    }
+    println!();
+
+    let mut results = Vec::new();
+    let mut regressed = 0;
+    for workload in &workloads {
+        if stop_signal.load(Ordering::Relaxed) {
+            break;
+        }
+        let stats = measure(workload, policy, parallelism, bench_args.samples as usize);
+        let change = baseline
+            .as_ref()
+            .and_then(|baseline| baseline.iter().find(|saved| saved.id == stats.id))
+            .map(|saved| compare(&stats, saved, bench_args.noise_threshold));
+        if change.is_some_and(|change| change.verdict == Verdict::Regressed) {
+            regressed += 1;
+        }
+        println!("{}", render(&stats, change.as_ref()));
+        results.push(stats);
+    }
+    if let Some(path) = &bench_args.save_baseline {
+        bench::save_baseline(path, &results).map_err(|source| MonitorError::Sink { sink: BASELINE_SINK, source })?;
+        println!("Saved baseline to {}", path.display());
+    }
+    if regressed > 0 {
+        return Err(MonitorError::BenchRegression {
+            regressed,
+            workloads: results.len(),
+            threshold: bench_args.noise_threshold,
         });
-        worker_handles.push(handle);
     }
+    Ok(())
+}
+
+/// Starts supervised worker threads that generate and evaluate events at `--rate` without any
+/// sinks.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments.
+/// * `thread_count` - Number of worker threads.
+/// * `policy` - The policy events are evaluated against.
+/// * `clock` - Clock the events are timestamped and paced by.
+/// * `stop_signal` - Atomic flag indicating when the workers should stop.
+/// * `error_sender` - Channel sender for error reports from the workers.
+///
+/// # Returns
+///
+/// The workers' metric shards, and the supervisor thread, which finishes once every worker has
+/// stopped.
+fn start_headless_workers(
+    args: &Args,
+    thread_count: usize,
+    policy: Arc<Policy>,
+    clock: SharedClock,
+    stop_signal: &Arc<AtomicBool>,
+    error_sender: &Sender<ErrorReport>,
+) -> Result<(Arc<SharedMetrics>, thread::JoinHandle<()>)> {
+    let controller = batch_controller(args, &Arc::new(RateControl::new(args.rate, thread_count)));
+    let config = PipelineConfig {
+        parallelism: Parallelism::from_chunk_size(args.chunk_size),
+        clock,
+        event_profile: Arc::new(EventProfile::load(&args.event_profile)?),
+        traffic: args.traffic,
+        spikes: Arc::default(),
+        source: args.source.clone(),
+        shadow: None,
+    };
+    let worker_cores = worker_cores(args.pin_cores.as_deref(), thread_count)?;
+    let worker_metrics = Arc::new(SharedMetrics::new(thread_count, &policy));
 
+    let spawn_worker = {
+        let policy = Arc::new(LivePolicy::new(policy.clone()));
+        let stop_signal = stop_signal.clone();
+        let worker_metrics = worker_metrics.clone();
+        let errors = error_sender.clone();
+        move |worker: usize| {
+            let shard = worker_metrics.shard(worker);
+            let thread_policy = policy.clone();
+            let thread_stop = stop_signal.clone();
+            let thread_errors = errors.clone();
+            let thread_config = config.clone();
+            let thread_controller = controller.clone();
+            let core = worker_cores[worker];
+            spawn_named(&format!("worker-{}", worker), move || {
+                pin_current_thread(core, &thread_errors);
+                let sinks = WorkerSinks::default();
+                worker_thread(thread_controller, thread_policy, thread_config, thread_stop, shard, sinks, thread_errors);
+            })
+        }
+    };
+    let supervisor = WorkerSupervisor::start(thread_count, spawn_worker, worker_metrics.clone())?;
+    let supervisor_stop = stop_signal.clone();
+    let supervisor_errors = error_sender.clone();
+    let supervisor_handle = spawn_named("supervisor", move || supervisor.run(&supervisor_stop, &supervisor_errors))?;
+    Ok((worker_metrics, supervisor_handle))
+}
+
+/// Prints a compliance report of a metrics snapshot in the requested format.
+fn run_compliance_report(report_args: &ReportArgs, policy: &Policy) -> Result<()> {
+    let metrics = load_snapshot(&report_args.snapshot)?
+        .ok_or_else(|| MonitorError::Snapshot(format!("{} does not exist", report_args.snapshot.display())))?;
+    print!("{}", ComplianceReport::new(&metrics, policy).render(report_args.format));
+    Ok(())
+}
+
+/// Evaluates an event log under each policy to analyze and prints what their checks would flag.
+fn run_analyze(args: &Args, analyze_args: &AnalyzeArgs, policy: &Policy) -> Result<()> {
+    let policies = if analyze_args.policies.is_empty() {
+        let name = args.policy.as_ref().map_or_else(|| "built-in".to_string(), |path| path.display().to_string());
+        vec![NamedPolicy { name, policy: policy.clone() }]
+    } else {
+        analyze_args
+            .policies
+            .iter()
+            .map(|path| {
+                let policy = Policy::load(args.risk_factors.as_deref(), Some(path), args.waivers.as_deref())?;
+                Ok(NamedPolicy { name: path.display().to_string(), policy })
+            })
+            .collect::<Result<_>>()?
+    };
+    let reader = BufReader::new(File::open(&analyze_args.file).map_err(MonitorError::EventLog)?);
+    let source = analyze_args.file.display().to_string();
+    let report = analyze_events(&source, reader, policies, Parallelism::from_chunk_size(args.chunk_size))?;
+    info!("analyzed {} events of {} under {} policies", report.events, source, report.policies.len());
+    print!("{}", report.render(analyze_args.format));
+    Ok(())
+}
+
+/// Evaluates a directory of historical usage logs and writes the aggregate report and audit records.
+fn run_import(args: &Args, import_args: &ImportArgs, policy: &Policy) -> Result<()> {
+    let mapping = import_args.mapping.as_deref().map(ColumnMapping::load).transpose()?.unwrap_or_default();
+    let (error_sender, error_receiver) = unbounded();
+    let mut audit_sender = None;
+    let mut audit_handle = None;
+    if let Some(path) = import_args.audit_log.clone() {
+        let signing_key = import_args.audit_signing_key.as_deref().map(load_signing_key).transpose()?;
+        let (sender, receiver) = bounded(import_args.audit_buffer.max(1));
+        let audit_errors = error_sender.clone();
+        audit_sender = Some(sender);
+        audit_handle = Some(spawn_named("audit-writer", move || {
+            audit_writer(&path, receiver, signing_key, ErrorPolicy::Shutdown, audit_errors)
+        })?);
+    }
+    // Each event-time window goes to the interval sinks, as a live run's intervals do.
+    let interval_sinks = interval_sinks(&import_args.exports)?;
+    let mut sinks: Vec<&dyn Sink> = interval_sinks.iter().map(|sink| sink.as_ref()).collect();
+    let mut on_interval = |interval: &IntervalMetrics, total: &CumulativeMetrics| {
+        write_to_sinks(&mut sinks, interval, total, policy, args.sink_error_policy, &error_sender);
+    };
+    let parallelism = Parallelism::from_chunk_size(args.chunk_size);
+    let summary = import_logs(&import_args.dir, &mapping, policy, parallelism, event_time_windows(args), audit_sender.as_ref(), &mut on_interval);
+    // Dropping the sender lets the audit writer finish the log.
+    drop(audit_sender);
+    if let Some(handle) = audit_handle
+        && handle.join().is_err()
+    {
+        return Err(MonitorError::ThreadPanicked("audit writer"));
+    }
+    let summary = summary?;
+    for failure in error_receiver.try_iter() {
+        if failure.action == ErrorPolicy::Shutdown {
+            return Err(failure.error);
+        }
+        warn!("{}; continuing without it", failure.error);
+    }
+    info!("imported {} events from {} files in {}", summary.events, summary.files.len(), import_args.dir.display());
+    if summary.skipped.count > 0 {
+        warn!("{}", summary.skipped.render().trim_end());
+    }
+    if summary.arrivals.total() > 0 {
+        warn!("{}", summary.arrivals.render());
+    }
+    let report = ComplianceReport::new(&summary.metrics, policy).render(import_args.format);
+    match &import_args.output {
+        Some(path) => std::fs::write(path, report)?,
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
+/// Writes an evidence bundle of the given files and the active policy, then prints its contents.
+fn run_export_evidence(args: &Args, evidence_args: &EvidenceArgs, policy: &Policy) -> Result<()> {
+    let policy_files: Vec<&Path> = [&args.policy, &args.risk_factors].into_iter().flatten().map(|path| path.as_path()).collect();
+    let manifest = export_evidence(evidence_args, policy, &policy_files)?;
+    info!("evidence bundle written to {}", evidence_args.output.display());
+    println!("Evidence bundle written to {}:", evidence_args.output.display());
+    for file in &manifest.files {
+        println!("  {:<40} {:>12} bytes  sha256 {}", file.path, file.bytes, file.sha256);
+    }
+    println!("Manifest signed with Ed25519 public key {}", manifest.public_key);
+    Ok(())
+}
+
+/// Verifies an audit log and prints how much of it is chained and signed.
+fn run_verify_audit(verify_args: &VerifyAuditArgs) -> Result<()> {
+    let public_key = verify_args.public_key.as_deref().map(parse_public_key).transpose()?;
+    let verification = verify_audit_log(&verify_args.file, public_key.as_ref())?;
+    println!("{}: hash chain intact over {} records", verify_args.file.display(), verification.records);
+    println!("Chain head: {}", verification.head);
+    match &verification.signature {
+        Some(signature) => {
+            println!("Signature valid for the first {} records, signed by {}", signature.records, signature.public_key);
+            if public_key.is_none() {
+                println!("Pass --public-key to check the signer against a trusted key.");
+            }
+        }
+        None => println!("Not signed"),
+    }
+    Ok(())
+}
+
+/// Checks the options and the files they name without starting the monitor, and lists the
+/// options set on top of their defaults with where each came from.
+fn run_check(args: &Args, run_args: &RunArgs, layers: &ConfigLayers) -> Result<()> {
+    let policy = Policy::from_args(args)?;
+    Policy::candidate_from_args(args)?;
+    Theme::load(&args.theme)?;
+    if let Some(path) = &args.layout {
+        DashboardLayout::load(path)?;
+    }
+    Keymap::new(args.keybindings, &args.bind)?;
+    ChartThresholds::new(args.compliance_target, args.max_rate)?;
+    EventProfile::load(&args.event_profile)?;
+    clock::from_time_scale(args.time_scale)?;
+    if let Some(failure_rate) = run_args.chaos {
+        ChaosConfig::new(failure_rate, Duration::from_millis(run_args.chaos_max_outage))?;
+    }
+    if let Some(path) = &run_args.audit_signing_key {
+        load_signing_key(path)?;
+    }
+    if let Some(hours) = run_args.soak {
+        SoakTest::new(hours, run_args.soak_max_slope)?;
+    }
+    #[cfg(feature = "slack")]
+    if let Some(webhook) = &run_args.outputs.slack_webhook {
+        SlackConfig::new(webhook, run_args.outputs.slack_channel.as_deref(), run_args.outputs.slack_batch_secs)?;
+    }
+    if let Some(path) = &run_args.outputs.silence_windows {
+        load_silence_windows(path)?;
+    }
+    #[cfg(feature = "pagerduty")]
+    if let Some(path) = &run_args.outputs.pagerduty {
+        PagerDutyConfig::load(path)?;
+    }
+    match &layers.config_file {
+        Some(path) => println!("Config file: {}", path.display()),
+        None => println!("Config file: none"),
+    }
+    for (option, source) in &layers.settings {
+        println!("  {:<28} {}", option, source.name());
+    }
+    println!(
+        "Configuration is valid: {} compliance checks, {} risk factors, {} waivers",
+        policy.compliance.len(),
+        policy.risk_factors.len(),
+        policy.waivers.len()
+    );
+    Ok(())
+}
+
+/// Replays a recorded event log through the compliance systems and monitors it through the frontend.
+fn run_replay(
+    args: &Args,
+    replay_args: &ReplayArgs,
+    policy: Arc<Policy>,
+    frontend: Box<dyn Frontend>,
+    clock: SharedClock,
+    stop_signal: Arc<AtomicBool>,
+) -> Result<()> {
+    println!("AI Compliance ECS Demo");
+    println!("Replaying event log: {}", replay_args.file.display());
+    println!("Playback speed: {}", replay_args.speed);
+    println!("Reporting interval: {} seconds", args.interval);
+    println!("Starting {}...", frontend.name());
+
+    let started = Instant::now();
+    let (window_sender, window_receiver) = bounded(args.channel_capacity.max(1));
+    let channels = MonitorChannels {
+        event_windows: Some(window_receiver),
+        sinks: interval_sinks(&replay_args.outputs.exports)?,
+        shadow: shadow_policy(args, &policy)?,
+        ..MonitorChannels::new(args.channel_capacity, Vec::new(), &policy, clock.clone())
+    };
+    let mapping = replay_args.mapping.as_deref().map(ColumnMapping::load).transpose()?.unwrap_or_default();
+    let config = PipelineConfig {
+        parallelism: Parallelism::from_chunk_size(args.chunk_size),
+        clock,
+        event_profile: Arc::default(),
+        traffic: TrafficPattern::Flat,
+        spikes: Arc::default(),
+        source: SourceSpec::File { path: replay_args.file.clone(), speed: replay_args.speed, mapping },
+        shadow: channels.shadow.clone(),
+    };
+    let replay_source = config.source.open(&config, &policy.content_scan)?;
+    #[cfg(feature = "slack")]
+    let (channels, slack_handle) = start_slack_notifier(&replay_args.outputs, args.sink_error_policy, channels, &stop_signal)?;
+    #[cfg(feature = "pagerduty")]
+    let (channels, pagerduty_handle) = start_pagerduty_sender(&replay_args.outputs, args.sink_error_policy, channels, &stop_signal, true)?;
+    let (mut channels, federation_handle) = start_federation(&replay_args.outputs, None, channels)?;
+    let badge_handle = start_badge_server(&replay_args.outputs, &channels, &stop_signal)?;
+
+    // Launch the replay thread; the frontend stays open after the log is exhausted or fails.
+    let replay_stop = stop_signal.clone();
+    let windows = event_time_windows(args);
+    let replay_cmd_sender = channels.cmd_sender.clone();
+    let replay_errors = channels.error_sender.clone();
+    let replay_policy = policy.clone();
+    let replay_handle = spawn_named("replay", move || {
+        if let Err(e) = replay_thread(
+            replay_source,
+            &replay_policy,
+            config,
+            windows,
+            replay_stop,
+            window_sender,
+            replay_cmd_sender,
+        ) {
+            report(&replay_errors, e, ErrorPolicy::Degrade);
+        }
+    })?;
+
+    let (mut total_metrics, mut result) =
+        run_monitor(args, MonitorArgs::Replay(replay_args), CumulativeMetrics::default(), &policy, frontend, &stop_signal, &channels);
+    channels.federation = None;
+
+    drain_until_finished(|| replay_handle.is_finished(), &channels, &mut total_metrics);
+    if replay_handle.join().is_err() && result.is_ok() {
+        result = Err(MonitorError::ThreadPanicked("replay"));
+    }
+    if let Some(handle) = federation_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("federation sender"));
+    }
+    if let Some(handle) = badge_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("badge server"));
+    }
+    #[cfg(feature = "slack")]
+    if let Some(handle) = slack_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("Slack notifier"));
+    }
+    #[cfg(feature = "pagerduty")]
+    if let Some(handle) = pagerduty_handle
+        && handle.join().is_err()
+        && result.is_ok()
+    {
+        result = Err(MonitorError::ThreadPanicked("PagerDuty sender"));
+    }
+    for interval in channels.event_windows.iter().flat_map(Receiver::try_iter) {
+        total_metrics.record(&interval);
+    }
+    log_late_errors(&channels.error_receiver);
+    print_policy_comparison(&channels);
+    print_chaos_summary();
+    report_run(args, "replay", started.elapsed(), total_metrics.total_events, None);
+
+    info!("shutdown complete");
+    println!("Shutdown complete.");
+    result
+}
+
+/// Options of the subcommand a monitor with a frontend runs for.
+#[derive(Clone, Copy)]
+enum MonitorArgs<'a> {
+    Run(&'a RunArgs),
+    Replay(&'a ReplayArgs),
+}
+
+impl<'a> MonitorArgs<'a> {
+    /// Returns the options of a live run, or `None` for a replay.
+    fn run(self) -> Option<&'a RunArgs> {
+        match self {
+            MonitorArgs::Run(run_args) => Some(run_args),
+            MonitorArgs::Replay(_) => None,
+        }
+    }
+
+    /// Returns the options of the monitor's outputs.
+    fn outputs(self) -> &'a LiveOutputArgs {
+        match self {
+            MonitorArgs::Run(run_args) => &run_args.outputs,
+            MonitorArgs::Replay(replay_args) => &replay_args.outputs,
+        }
+    }
+}
+
+/// Runs the frontend, the metrics aggregation loop, and error supervision until shutdown.
+///
+/// Error reports are handled according to the action attached to them: retries and degraded
+/// components are shown by the frontend, while a shutdown stops the monitor and is returned.
+/// The cumulative metrics are returned alongside the result so they can be persisted.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments (reporting interval and sink settings).
+/// * `monitor_args` - Options of the live run or replay, whose outputs are written and, for a
+///   live run, whose `--snapshot-file` is persisted periodically and whose sinks are shown on the
+///   System tab.
+/// * `initial_metrics` - Cumulative metrics to start from, e.g. restored from a snapshot.
+/// * `policy` - The active policy, used by the frontend and exports to label flags and factors.
+/// * `frontend` - The user interface to present the monitor with.
+/// * `stop_signal` - Atomic flag indicating when to stop; set when the user quits.
+/// * `channels` - Channels connecting the monitor to the other threads.
+fn run_monitor(
+    args: &Args,
+    monitor_args: MonitorArgs,
+    initial_metrics: CumulativeMetrics,
+    policy: &Arc<Policy>,
+    frontend: Box<dyn Frontend>,
+    stop_signal: &Arc<AtomicBool>,
+    channels: &MonitorChannels,
+) -> (CumulativeMetrics, Result<()>) {
+    let initial_metrics = initial_metrics.with_history_points(args.history_points);
+    // The loop runs on its own named thread so it can be told apart from the workers in profilers.
+    let fallback_metrics = initial_metrics.clone();
+    thread::scope(|scope| {
+        let aggregator = thread::Builder::new().name("aggregator".to_string()).spawn_scoped(scope, move || {
+            monitor_loop(args, monitor_args, initial_metrics, policy, frontend, stop_signal, channels)
+        });
+        match aggregator.map(|handle| handle.join()) {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(_)) => (fallback_metrics, Err(MonitorError::ThreadPanicked("aggregator"))),
+            Err(e) => (fallback_metrics, Err(MonitorError::Io(e))),
+        }
+    })
+}
+
+/// The body of [`run_monitor`], run on the aggregator thread.
+fn monitor_loop(
+    args: &Args,
+    monitor_args: MonitorArgs,
+    initial_metrics: CumulativeMetrics,
+    policy: &Arc<Policy>,
+    frontend: Box<dyn Frontend>,
+    stop_signal: &Arc<AtomicBool>,
+    channels: &MonitorChannels,
+) -> (CumulativeMetrics, Result<()>) {
+    let MonitorChannels {
+        metrics_receiver,
+        cmd_sender,
+        error_sender,
+        error_receiver,
+        control_receiver,
+        event_windows,
+        worker_metrics,
+        clock,
+        badge,
+        sampled_events,
+        ..
+    } = channels;
+    let run_args = monitor_args.run();
+    let outputs = monitor_args.outputs();
+    let mut fatal_error = None;
     // Metrics aggregation variables.
-    let mut total_metrics = ComplianceMetrics::default();
-    let mut last_report_time = Instant::now();
+    let mut total_metrics = initial_metrics;
+    let mut last_report_time = clock.now();
     let mut metrics_since_last = ComplianceMetrics::default();
+    let snapshot_interval = Duration::from_secs(run_args.map_or(0, |run_args| run_args.snapshot_interval));
+    let mut snapshot_file = run_args.and_then(|run_args| run_args.snapshot_file.as_deref()).filter(|_| !snapshot_interval.is_zero());
+    let mut last_snapshot_time = clock.now();
+    let mut sinks: Vec<&dyn Sink> = channels.sinks.iter().map(Box::as_ref).collect();
+    let mut case_file = args.case_file.as_deref();
+    let mut cases = match case_file.map(CaseBook::load).transpose() {
+        Ok(cases) => cases.unwrap_or_default(),
+        Err(e) => return (total_metrics, Err(e)),
+    };
+    let soak_settings = run_args.and_then(|run_args| run_args.soak.map(|hours| (hours, run_args.soak_max_slope)));
+    let mut soak = match soak_settings.map(|(hours, max_slope)| SoakTest::new(hours, max_slope)).transpose() {
+        Ok(soak) => soak,
+        Err(e) => return (total_metrics, Err(e)),
+    };
+    let soak_started = Instant::now();
+    let mut policy = policy.clone();
+    let mut waiver_statuses = vec![WaiverStatus::Active; policy.waivers.len()];
+    let mut slo_burning = vec![false; policy.slo.as_ref().map_or(0, |slo| slo.targets.len())];
+    let mut alert_log = AlertLog::default();
+    let mut silences = match outputs.silence_windows.as_deref().map(load_silence_windows).transpose() {
+        Ok(windows) => Silences::new(windows.unwrap_or_default()),
+        Err(e) => return (total_metrics, Err(e)),
+    };
+    update_silence(&silences, &mut alert_log, clock.unix_millis(), cmd_sender);
+    // Set when the admin API starts or ends a silence, so the change applies before the next report.
+    let mut silence_requested = false;
+    // PagerDuty triggers held back by a silence, sent when it ends if their conditions still hold.
+    #[cfg(feature = "pagerduty")]
+    let mut held_incidents: Vec<PagerDutyEvent> = Vec::new();
+    #[cfg(feature = "pagerduty")]
+    let mut incidents = channels
+        .pagerduty
+        .as_ref()
+        .map(|(_, config)| IncidentTracker::new(config.rules.clone(), &config.source, clock.now()));
+    let mut interval_samples = VecDeque::new();
+    let mut recent_samples = RecentSamples::default();
+    let mut last_worker_events = worker_metrics.worker_events();
+    let mut last_system_latencies = system_latencies();
+    let mut last_tick = clock.now();
+    let mut max_loop_lag = Duration::ZERO;
+    let mut health = PipelineHealth {
+        queue_capacity: metrics_receiver.capacity().unwrap_or(0),
+        ..PipelineHealth::default()
+    };
 
-    // Set up Ctrl+C handler for graceful shutdown.
-    let ctrl_c_stop = stop_signal.clone();
-    ctrlc::set_handler(move || {
-        ctrl_c_stop.store(true, Ordering::Relaxed);
-    }).expect("Error setting Ctrl+C handler");
-
-    // Launch the TUI dashboard in a separate thread.
-    let dashboard_stop = stop_signal.clone();
-    let dashboard_handle = thread::spawn(move || {
-        let mut terminal = setup_terminal().expect("Failed to setup terminal");
-        let mut dashboard = Dashboard::new();
-        while !dashboard_stop.load(Ordering::Relaxed) && !dashboard.should_quit {
-            // Process incoming dashboard commands.
-            while let Ok(cmd) = cmd_receiver.try_recv() {
-                dashboard.handle_command(cmd);
-            }
-            // Render the dashboard UI.
-            if let Err(e) = dashboard.render(&mut terminal) {
-                eprintln!("Dashboard render error: {:?}", e);
-            }
-            // Poll for key events with a timeout.
-            if crossterm::event::poll(Duration::from_millis(100)).unwrap_or(false) {
-                if let crossterm::event::Event::Key(key) = crossterm::event::read().unwrap() {
-                    dashboard.handle_key_event(key);
-                    if dashboard.should_quit {
-                        dashboard_stop.store(true, Ordering::Relaxed);
-                    }
+    // Launch the frontend in a separate thread.
+    let context = FrontendContext {
+        policy: policy.clone(),
+        updates: channels.cmd_receiver.clone(),
+        control: channels.control_sender.clone(),
+        stop_signal: stop_signal.clone(),
+        errors: error_sender.clone(),
+        clock: clock.clone(),
+    };
+    let frontend_handle = match spawn_named("dashboard", move || frontend.run(context)) {
+        Ok(handle) => handle,
+        Err(e) => return (total_metrics, Err(MonitorError::Io(e))),
+    };
+
+    // Show restored metrics immediately rather than after the first interval.
+    let _ = cmd_sender.send(FrontendCommand::UpdateMetrics(Box::new(total_metrics.clone())));
+    let _ = cmd_sender.send(FrontendCommand::UpdateCases(cases.clone()));
+    let alerts = waiver_expiry_alerts(&policy.waivers, &mut waiver_statuses, &mut alert_log, clock.unix_millis());
+    raise_alerts(alerts, &alert_log, &total_metrics, &policy, channels);
+    let _ = cmd_sender.send(FrontendCommand::UpdateAlerts(alert_log.clone()));
+    *badge.lock().unwrap_or_else(|e| e.into_inner()) = Badge::new(&total_metrics);
+
+    // Main loop: supervise errors, aggregate metrics, and send frontend updates.
+    while !stop_signal.load(Ordering::Relaxed) {
+        // A tick that took longer than the sleep means the loop itself fell behind.
+        let tick = clock.now();
+        max_loop_lag = max_loop_lag.max(tick.saturating_sub(last_tick).saturating_sub(MONITOR_TICK));
+        last_tick = tick;
+        if frontend_handle.is_finished() {
+            // The frontend has exited, either because the user quit or after reporting why.
+            stop_signal.store(true, Ordering::Relaxed);
+            break;
+        }
+        while let Ok(error_report) = error_receiver.try_recv() {
+            let message = error_report.error.to_string();
+            match error_report.action {
+                ErrorPolicy::Retry => {
+                    warn!("{} (retrying)", message);
+                    let _ = cmd_sender.send(FrontendCommand::ReportError(format!("{} (retrying)", message)));
+                }
+                ErrorPolicy::Degrade => {
+                    error!("{} (disabled)", message);
+                    let _ = cmd_sender.send(FrontendCommand::ReportError(format!("{} (disabled)", message)));
+                }
+                ErrorPolicy::Shutdown => {
+                    error!("{} (shutting down)", message);
+                    stop_signal.store(true, Ordering::Relaxed);
+                    fatal_error.get_or_insert(error_report.error);
                 }
             }
         }
-        // Restore terminal settings upon exit.
-        if let Err(e) = restore_terminal(&mut terminal) {
-            eprintln!("Error restoring terminal: {:?}", e);
+        while let Ok(control) = control_receiver.try_recv() {
+            match control {
+                ControlCommand::ExportCsv => {
+                    // One-shot exports go to a fresh directory next to any continuous export.
+                    let samples: Vec<_> = interval_samples.iter().cloned().collect();
+                    let base_dir = outputs.exports.csv_export.as_deref().unwrap_or(Path::new("."));
+                    let dir = base_dir.join(format!("csv-export-{}", clock.unix_millis()));
+                    let sampled = recent_samples.newest_first();
+                    let export = append_csv_series(&dir, &samples, &policy)
+                        .and_then(|()| if sampled.is_empty() { Ok(()) } else { append_sampled_events(&dir, &sampled) });
+                    let command = match export {
+                        Ok(()) => FrontendCommand::ShowNotice(format!(
+                            "Exported {} intervals and {} sampled events to {}",
+                            samples.len(),
+                            sampled.len(),
+                            dir.display()
+                        )),
+                        Err(source) => {
+                            let message = MonitorError::Sink { sink: CSV_SINK, source }.to_string();
+                            warn!("{}", message);
+                            FrontendCommand::ReportError(message)
+                        }
+                    };
+                    let _ = cmd_sender.send(command);
+                }
+                ControlCommand::SetCaseState { case, state } => {
+                    if let Some(updated) = cases.set_state(case, state, clock.unix_millis()) {
+                        info!("case {} ({}, {}) marked {}", case, updated.check, updated.department, state.name());
+                        save_cases(args, &mut case_file, &cases, error_sender);
+                        let _ = cmd_sender.send(FrontendCommand::UpdateCases(cases.clone()));
+                    }
+                }
+                ControlCommand::AcknowledgeAlert { alert } => {
+                    if let Some(acknowledged) = alert_log.acknowledge(alert) {
+                        info!("alert {} ({}) acknowledged", alert, acknowledged.message);
+                        let _ = cmd_sender.send(FrontendCommand::UpdateAlerts(alert_log.clone()));
+                    }
+                }
+                ControlCommand::ReloadPolicy { reply } => {
+                    match Policy::from_args(args).and_then(|reloaded| channels.live_policy.replace(reloaded)) {
+                        Ok(reloaded) => {
+                            policy = reloaded;
+                            // The waivers and SLO targets may have changed, so their alerts start over.
+                            waiver_statuses = vec![WaiverStatus::Active; policy.waivers.len()];
+                            slo_burning = vec![false; policy.slo.as_ref().map_or(0, |slo| slo.targets.len())];
+                            alert_log.clear_rules(&[WAIVER_EXPIRING_RULE, WAIVER_EXPIRED_RULE, SLO_BURN_RULE], clock.unix_millis());
+                            info!("reloaded policy with {} waivers", policy.waivers.len());
+                            let _ = cmd_sender.send(FrontendCommand::UpdatePolicy(policy.clone()));
+                            let _ = cmd_sender.send(FrontendCommand::ShowNotice("Policy reloaded".to_string()));
+                            let _ = reply.send(Ok(()));
+                        }
+                        Err(e) => {
+                            warn!("policy reload failed: {}", e);
+                            let _ = reply.send(Err(e));
+                        }
+                    }
+                }
+                ControlCommand::SilenceAlerts { duration } => {
+                    if duration.is_zero() {
+                        if silences.end_one_shot() {
+                            info!("alert silence ended through the admin API");
+                        }
+                    } else {
+                        silences.silence_until(clock.unix_millis() + duration.as_millis() as u64, "Silenced through the admin API".to_string());
+                    }
+                    silence_requested = true;
+                }
+                ControlCommand::InjectSpike { events } => {
+                    // Replays evaluate recorded events only, so there are no workers to add the spike.
+                    let notice = match run_args {
+                        Some(run_args) => {
+                            let events = events.unwrap_or(run_args.spike_size as usize);
+                            channels.spikes.inject(events);
+                            info!("injecting a spike of {} non-compliant events", events);
+                            format!("Injecting {} non-compliant events", events)
+                        }
+                        None => "Violation spikes need generated events and are not available in replays".to_string(),
+                    };
+                    let _ = cmd_sender.send(FrontendCommand::ShowNotice(notice));
+                }
+            }
         }
-    });
-
-    // Main loop: aggregate metrics and send dashboard updates.
-    while !stop_signal.load(Ordering::Relaxed) {
+        health.queue_depth = metrics_receiver.len();
+        health.peak_queue_depth = health.peak_queue_depth.max(health.queue_depth);
         while let Ok(metrics) = metrics_receiver.try_recv() {
-            total_metrics.merge(&metrics);
             metrics_since_last.merge(&metrics);
         }
-        if last_report_time.elapsed() >= Duration::from_secs(args.interval) {
-            let elapsed = last_report_time.elapsed();
-            total_metrics.update_historical_data(metrics_since_last.total_events, elapsed);
-            if let Err(e) = cmd_sender.send(ui::dashboard::DashboardCommand::UpdateMetrics(total_metrics.clone())) {
-                eprintln!("Error sending dashboard command: {:?}", e);
+        metrics_since_last.merge(&worker_metrics.collect_new());
+        if let Some(receiver) = sampled_events {
+            while let Ok(events) = receiver.try_recv() {
+                recent_samples.add(events);
+            }
+        }
+        if let Some(path) = snapshot_file
+            && clock.now().saturating_sub(last_snapshot_time) >= snapshot_interval
+        {
+            let sink_policy = args.sink_error_policy;
+            if let Err(e) = with_retry(sink_policy, SNAPSHOT_SINK, error_sender, || save_snapshot(path, &total_metrics)) {
+                let action = if sink_policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+                report(error_sender, e, action);
+                // Periodic snapshots are disabled; a final one is still attempted on shutdown.
+                snapshot_file = None;
+            }
+            last_snapshot_time = clock.now();
+        }
+        if let Some(soak) = &mut soak {
+            let soak_elapsed = soak_started.elapsed();
+            if soak.sample_due(soak_elapsed) {
+                let (world_entities, world_archetypes) = worker_metrics.world_sizes();
+                let mut structures = vec![
+                    ("history points".into(), total_metrics.history.len() as u64),
+                    ("users".into(), total_metrics.users.len() as u64),
+                    ("retained intervals".into(), interval_samples.len() as u64),
+                    ("sampled events".into(), recent_samples.len() as u64),
+                    ("cases".into(), cases.cases().len() as u64),
+                    ("metrics queue".into(), metrics_receiver.len() as u64),
+                    ("world entities".into(), world_entities),
+                    ("world archetypes".into(), world_archetypes),
+                ];
+                let channels = channel_depths(run_args, worker_metrics, cmd_sender);
+                structures.extend(channels.into_iter().map(|channel| (channel.name, channel.depth as u64)));
+                soak.record(MemorySample { elapsed: soak_elapsed, rss_bytes: current_rss_bytes(), structures });
+            }
+            if soak_elapsed >= soak.duration() {
+                info!("soak test finished after {:.2} hours", soak_elapsed.as_secs_f64() / 3600.0);
+                stop_signal.store(true, Ordering::Relaxed);
+            }
+        }
+        let elapsed = clock.now().saturating_sub(last_report_time);
+        let report_due = elapsed >= Duration::from_secs(args.interval);
+        // A replay closes its intervals by the events' own time; otherwise one closes per report.
+        let closed = match event_windows {
+            Some(windows) => windows.try_iter().collect(),
+            None if report_due => vec![IntervalMetrics::new(std::mem::take(&mut metrics_since_last), elapsed, clock.unix_millis())],
+            None => Vec::new(),
+        };
+        if report_due || silence_requested {
+            silence_requested = false;
+            let ended = update_silence(&silences, &mut alert_log, clock.unix_millis(), cmd_sender);
+            #[cfg(feature = "pagerduty")]
+            if ended && let Some((pagerduty, _)) = &channels.pagerduty {
+                for event in held_incidents.drain(..) {
+                    info!("sending PagerDuty incident {} held back by the silence", event.dedup_key);
+                    let _ = pagerduty.send(event);
+                }
+            }
+            #[cfg(not(feature = "pagerduty"))]
+            let _ = ended;
+        }
+        for interval in closed {
+            total_metrics.record(&interval);
+            #[cfg(feature = "pagerduty")]
+            if let (Some(incidents), Some((pagerduty, config))) = (&mut incidents, &channels.pagerduty) {
+                let mut alerts = Vec::new();
+                for event in incidents.evaluate(&interval.counts, clock.now()) {
+                    match event.action {
+                        EventAction::Trigger => {
+                            let severity = match event.severity {
+                                Severity::Critical | Severity::Error => AlertSeverity::Critical,
+                                Severity::Warning => AlertSeverity::Warning,
+                                Severity::Info => AlertSeverity::Info,
+                            };
+                            alert_log.raise(event.rule, &config.source, severity, &event.summary, event.value.clone(), clock.unix_millis());
+                            alerts.push(event.summary.clone());
+                            if alert_log.silence().is_some() {
+                                held_incidents.push(event);
+                                continue;
+                            }
+                        }
+                        EventAction::Resolve => {
+                            alert_log.clear(event.rule, &config.source, clock.unix_millis());
+                            info!("resolved PagerDuty incident {}", event.dedup_key);
+                            // An incident whose trigger was held back was never opened.
+                            let held = held_incidents.len();
+                            held_incidents.retain(|held| held.dedup_key != event.dedup_key);
+                            if held_incidents.len() < held {
+                                continue;
+                            }
+                        }
+                    }
+                    let _ = pagerduty.send(event);
+                }
+                raise_alerts(alerts, &alert_log, &total_metrics, &policy, channels);
+            }
+            write_to_sinks(&mut sinks, &interval, &total_metrics, &policy, args.sink_error_policy, error_sender);
+            if let Some(federation) = &channels.federation {
+                let _ = federation.send(interval.clone());
+            }
+            if cases.record(&interval.counts, &policy.compliance, interval.unix_ms) {
+                save_cases(args, &mut case_file, &cases, error_sender);
+                let _ = cmd_sender.send(FrontendCommand::UpdateCases(cases.clone()));
+            }
+            if interval_samples.len() == MAX_RETAINED_SAMPLES {
+                interval_samples.pop_front();
+            }
+            interval_samples.push_back(interval);
+        }
+        if report_due {
+            let mut alerts = waiver_expiry_alerts(&policy.waivers, &mut waiver_statuses, &mut alert_log, clock.unix_millis());
+            if let Some(slo) = &policy.slo {
+                alerts.extend(slo_burn_alerts(slo, &total_metrics, &mut slo_burning, &mut alert_log, clock.unix_millis()));
+            }
+            raise_alerts(alerts, &alert_log, &total_metrics, &policy, channels);
+            let _ = cmd_sender.send(FrontendCommand::UpdateAlerts(alert_log.clone()));
+            *badge.lock().unwrap_or_else(|e| e.into_inner()) = Badge::new(&total_metrics);
+            if let Some(latest) = &channels.latest_metrics {
+                *latest.lock().unwrap_or_else(|e| e.into_inner()) = LatestMetrics {
+                    metrics: total_metrics.clone(),
+                    updated_at_ms: clock.unix_millis(),
+                };
+            }
+            if let Some(fleet) = &channels.fleet {
+                let _ = cmd_sender.send(FrontendCommand::UpdateFleet(fleet.statuses(elapsed)));
+            }
+            health.injected_sink_failures = chaos::is_enabled().then(chaos::total_injected);
+            health.channels = channel_depths(run_args, worker_metrics, cmd_sender);
+            health.rss_bytes = current_rss_bytes();
+            health.aggregator_lag = std::mem::take(&mut max_loop_lag);
+            let current_system_latencies = system_latencies();
+            health.system_latencies = current_system_latencies
+                .iter()
+                .zip(&last_system_latencies)
+                .filter_map(|((system, histogram), (_, last_histogram))| {
+                    let interval = tail_latency::delta_since(histogram, last_histogram);
+                    TailLatency::from_histogram(&interval).map(|latency| SystemLatency { name: system.name().into(), latency })
+                })
+                .collect();
+            last_system_latencies = current_system_latencies;
+            // Merge the workers' batch latencies of the interval, then into those since startup.
+            let worker_latencies = worker_metrics.take_worker_latencies();
+            let mut interval_latency = tail_latency::new_histogram();
+            for latency in &worker_latencies {
+                tail_latency::merge(&mut interval_latency, latency);
+            }
+            tail_latency::add_to_run(&interval_latency);
+            health.batch_latency = TailLatency::from_histogram(&interval_latency);
+            health.run_batch_latency = TailLatency::from_histogram(&tail_latency::run_batch_latency());
+            let _ = cmd_sender.send(FrontendCommand::UpdateHealth(health.clone()));
+            let worker_events = worker_metrics.worker_events();
+            let worker_status = worker_metrics.worker_status();
+            if !worker_events.is_empty() {
+                let threads = (0..worker_events.len())
+                    .map(|worker| {
+                        let interval_events = worker_events[worker].saturating_sub(last_worker_events[worker]);
+                        ThreadThroughput {
+                            name: format!("worker-{}", worker),
+                            core: channels.worker_cores[worker],
+                            events: worker_events[worker],
+                            rate: interval_events as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+                            batch_latency: TailLatency::from_histogram(&worker_latencies[worker]),
+                            alive: worker_status[worker].alive,
+                            restarts: worker_status[worker].restarts,
+                        }
+                    })
+                    .collect();
+                let _ = cmd_sender.send(FrontendCommand::UpdateThreads(threads));
+            }
+            last_worker_events = worker_events;
+            if let Some(shadow) = &channels.shadow {
+                let _ = cmd_sender.send(FrontendCommand::UpdateComparison(shadow.comparison(&policy)));
+            }
+            if sampled_events.is_some() {
+                let _ = cmd_sender.send(FrontendCommand::UpdateSampledEvents(recent_samples.newest_first()));
+            }
+            if cmd_sender.send(FrontendCommand::UpdateMetrics(Box::new(total_metrics.clone()))).is_err() {
+                // The dashboard thread has exited and reported why; stop monitoring.
+                stop_signal.store(true, Ordering::Relaxed);
+            }
+            last_report_time = clock.now();
+        }
+        clock.sleep(MONITOR_TICK);
+    }
+
+    // Wait for the frontend thread to finish.
+    if frontend_handle.join().is_err() {
+        fatal_error.get_or_insert(MonitorError::ThreadPanicked("frontend"));
+    }
+    // Judge the memory growth of a soak run, even one stopped early, now the terminal is restored.
+    if let Some(soak) = &soak {
+        let soak_result = soak.finish();
+        print!("{}", soak_result.render());
+        if let Err(e) = soak_result.check() {
+            fatal_error.get_or_insert(e);
+        }
+    }
+    // Close the last partial interval, push it to the federation leader, count its violations,
+    // and persist the final case states.
+    for latency in worker_metrics.take_worker_latencies() {
+        tail_latency::add_to_run(&latency);
+    }
+    if let Some(windows) = event_windows {
+        for interval in windows.try_iter() {
+            total_metrics.record(&interval);
+            write_to_sinks(&mut sinks, &interval, &total_metrics, &policy, args.sink_error_policy, error_sender);
+            if let Some(federation) = &channels.federation {
+                let _ = federation.send(interval.clone());
+            }
+            cases.record(&interval.counts, &policy.compliance, interval.unix_ms);
+        }
+    }
+    let interval = IntervalMetrics::new(metrics_since_last, clock.now().saturating_sub(last_report_time), clock.unix_millis());
+    if interval.counts.total_events > 0 {
+        total_metrics.record(&interval);
+    }
+    if let Some(federation) = &channels.federation {
+        let _ = federation.send(interval.clone());
+    }
+    cases.record(&interval.counts, &policy.compliance, clock.unix_millis());
+    if let Some(path) = args.case_file.as_deref() {
+        match cases.save(path) {
+            Ok(()) => info!("saved {} cases to {}", cases.cases().len(), path.display()),
+            Err(source) => warn!("{}", MonitorError::Sink { sink: CASES_SINK, source }),
+        }
+    }
+    // A fatal error may have been reported while the loop was exiting.
+    while let Ok(error_report) = error_receiver.try_recv() {
+        if error_report.action == ErrorPolicy::Shutdown {
+            fatal_error.get_or_insert(error_report.error);
+        } else {
+            warn!("{}", error_report.error);
+        }
+    }
+    let result = match fatal_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    };
+    (total_metrics, result)
+}
+
+/// Logs alerts as warnings, shows them on the frontend, and posts them to Slack if enabled and
+/// alerts are not silenced.
+///
+/// # Arguments
+///
+/// * `alerts` - The alert messages.
+/// * `alert_log` - The alert history, holding the silence in effect.
+/// * `metrics` - The cumulative metrics, summarized in Slack messages.
+/// * `policy` - The active policy, used to name the violated checks.
+/// * `channels` - Channels to the frontend and the Slack notifier.
+#[cfg_attr(not(feature = "slack"), allow(unused_variables))]
+fn raise_alerts(alerts: Vec<String>, alert_log: &AlertLog, metrics: &ComplianceMetrics, policy: &Policy, channels: &MonitorChannels) {
+    for message in alerts {
+        match alert_log.silence() {
+            Some(silence) => warn!("{} (silenced: {})", message, silence),
+            None => warn!("{}", message),
+        }
+        #[cfg(feature = "slack")]
+        if let Some(slack) = &channels.slack_alerts
+            && alert_log.silence().is_none()
+        {
+            let summary = AlertSummary::new(metrics, &policy.compliance);
+            let _ = slack.send(SlackAlert { message: message.clone(), summary });
+        }
+        let _ = channels.cmd_sender.send(FrontendCommand::ShowNotice(message));
+    }
+}
+
+/// Brings the silence of the alert log up to date with the silence windows and any one-shot
+/// silence, logging and showing a notice when a silence starts or ends.
+///
+/// # Arguments
+///
+/// * `silences` - The scheduled and one-shot silences.
+/// * `alert_log` - The alert history, whose silence is updated.
+/// * `now_ms` - Current time, in milliseconds since the Unix epoch.
+/// * `cmd_sender` - Channel sender for the frontend, sent the alert history if the silence changed.
+///
+/// # Returns
+///
+/// True if a silence ended, so that alerts held back during it can be dispatched.
+fn update_silence(silences: &Silences, alert_log: &mut AlertLog, now_ms: u64, cmd_sender: &Sender<FrontendCommand>) -> bool {
+    let silence = silences.active(now_ms);
+    let ended = alert_log.silence().is_some() && silence.is_none();
+    let notice = match &silence {
+        Some(silence) => format!("Alerts silenced: {}", silence),
+        None => "Alert silence ended".to_string(),
+    };
+    if alert_log.set_silence(silence) {
+        info!("{}", notice);
+        let _ = cmd_sender.send(FrontendCommand::ShowNotice(notice));
+        let _ = cmd_sender.send(FrontendCommand::UpdateAlerts(alert_log.clone()));
+    }
+    ended
+}
+
+/// Returns alerts for the waivers that started expiring within `EXPIRY_WARNING_DAYS`, or
+/// expired, since the last check, and records them in the alert log. An expiring waiver's alert
+/// clears when it expires.
+///
+/// # Arguments
+///
+/// * `waivers` - The waivers of the active policy.
+/// * `statuses` - The status of each waiver at the last check, updated in place.
+/// * `alert_log` - The alert history shown on the dashboard.
+/// * `now_ms` - Current time, in milliseconds since the Unix epoch.
+fn waiver_expiry_alerts(waivers: &[Waiver], statuses: &mut [WaiverStatus], alert_log: &mut AlertLog, now_ms: u64) -> Vec<String> {
+    let mut alerts = Vec::new();
+    for (waiver, last_status) in waivers.iter().zip(statuses.iter_mut()) {
+        let status = waiver.status(now_ms);
+        if status == *last_status {
+            continue;
+        }
+        *last_status = status;
+        let subject = waiver.describe();
+        let (rule, severity, message) = match status {
+            WaiverStatus::Active => continue,
+            WaiverStatus::Expiring => (WAIVER_EXPIRING_RULE, AlertSeverity::Info, format!("{} expires after {}", subject, waiver.expires)),
+            WaiverStatus::Expired => {
+                alert_log.clear(WAIVER_EXPIRING_RULE, &subject, now_ms);
+                let message = format!("{} expired after {}; its violations are counted again", subject, waiver.expires);
+                (WAIVER_EXPIRED_RULE, AlertSeverity::Warning, message)
             }
-            last_report_time = Instant::now();
-            metrics_since_last = ComplianceMetrics::default();
+        };
+        alert_log.raise(rule, &subject, severity, &message, waiver.expires.clone(), now_ms);
+        alerts.push(message);
+    }
+    alerts
+}
+
+/// Returns alerts for the departments whose SLO started burning its error budget faster than
+/// the alert threshold since the last check, and logs those that recovered. Both are recorded
+/// in the alert log: a burning SLO's alert clears when it recovers.
+///
+/// # Arguments
+///
+/// * `slo` - The SLOs of the active policy.
+/// * `metrics` - The cumulative metrics, including their history.
+/// * `burning` - Whether each SLO was burning at the last check, updated in place.
+/// * `alert_log` - The alert history shown on the dashboard.
+/// * `now_ms` - Current time, in milliseconds since the Unix epoch.
+fn slo_burn_alerts(slo: &SloPolicy, metrics: &ComplianceMetrics, burning: &mut [bool], alert_log: &mut AlertLog, now_ms: u64) -> Vec<String> {
+    let mut alerts = Vec::new();
+    for (status, was_burning) in slo.evaluate(metrics).iter().zip(burning.iter_mut()) {
+        let is_burning = status.is_burning(slo);
+        if is_burning == *was_burning {
+            continue;
+        }
+        *was_burning = is_burning;
+        let department = DEPARTMENT_NAMES[status.department as usize];
+        if is_burning {
+            let message = format!(
+                "{} is burning its compliance error budget at {:.1}x (target {:.1}%, {:.0}% of the {} budget left)",
+                department,
+                status.burn_rate,
+                status.target,
+                100.0 * status.budget_remaining.max(0.0),
+                slo.window_label()
+            );
+            let value = format!("{:.1}x", status.burn_rate);
+            alert_log.raise(SLO_BURN_RULE, department, AlertSeverity::Critical, &message, value, now_ms);
+            alerts.push(message);
+        } else {
+            alert_log.clear(SLO_BURN_RULE, department, now_ms);
+            info!("{} error budget burn rate is back to {:.1}x", department, status.burn_rate);
         }
-        thread::sleep(Duration::from_millis(50));
     }
+    alerts
+}
 
-    // Wait for the dashboard thread to finish.
-    dashboard_handle.join().expect("Dashboard thread panicked");
-    // Wait for all worker threads to finish.
-    for handle in worker_handles {
-        handle.join().expect("Worker thread panicked");
+/// Saves the case book to the case file, if one is still enabled.
+///
+/// A save that fails after retries is reported and disables further saves, except the final
+/// one attempted on shutdown.
+fn save_cases(args: &Args, case_file: &mut Option<&Path>, cases: &CaseBook, error_sender: &Sender<ErrorReport>) {
+    let Some(path) = *case_file else {
+        return;
+    };
+    let sink_policy = args.sink_error_policy;
+    if let Err(e) = with_retry(sink_policy, CASES_SINK, error_sender, || cases.save(path)) {
+        let action = if sink_policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+        report(error_sender, e, action);
+        *case_file = None;
     }
+}
 
-    println!("Shutdown complete.");
-    Ok(())
+/// Binds the `--badge-listen` address, if given, and serves the monitor's badge from a new thread.
+///
+/// # Arguments
+///
+/// * `outputs` - Options of the monitor's outputs.
+/// * `channels` - Channels of the monitor whose badge is served.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+fn start_badge_server(
+    outputs: &LiveOutputArgs,
+    channels: &MonitorChannels,
+    stop_signal: &Arc<AtomicBool>,
+) -> Result<Option<IoHandle>> {
+    let Some(addr) = &outputs.badge_listen else {
+        return Ok(None);
+    };
+    let listener = TcpListener::bind(addr).map_err(|source| MonitorError::Listen {
+        purpose: "badge requests",
+        addr: addr.clone(),
+        source,
+    })?;
+    println!("Serving compliance badge on http://{}/badge.json", addr);
+    let badge = channels.badge.clone();
+    let stop = stop_signal.clone();
+    let errors = channels.error_sender.clone();
+    Ok(Some(spawn_io("badge-server", move || serve_badge(listener, badge, stop, errors))?))
+}
+
+/// Starts the gRPC server if `--grpc-listen` is given, binding its address before any work
+/// starts so that a bad address fails fast.
+///
+/// # Arguments
+///
+/// * `run_args` - Options of the `run` subcommand.
+/// * `channels` - Channels of the monitor that ingested events report to.
+/// * `config` - How ingested batches are evaluated.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+#[cfg(feature = "grpc")]
+fn start_grpc_server(
+    run_args: &RunArgs,
+    channels: MonitorChannels,
+    config: &PipelineConfig,
+    stop_signal: &Arc<AtomicBool>,
+) -> Result<(MonitorChannels, Option<IoHandle>)> {
+    let Some(addr) = &run_args.grpc_listen else {
+        return Ok((channels, None));
+    };
+    let listener = TcpListener::bind(addr).map_err(|source| MonitorError::Listen { purpose: "gRPC requests", addr: addr.clone(), source })?;
+    println!("Serving gRPC ingestion and metric queries on {}", addr);
+    let latest = channels.latest_metrics.clone().unwrap_or_default();
+    let context = GrpcContext {
+        policy: channels.live_policy.clone(),
+        config: config.clone(),
+        metrics_sender: channels.metrics_sender.clone(),
+        latest: latest.clone(),
+    };
+    let stop = stop_signal.clone();
+    let errors = channels.error_sender.clone();
+    #[cfg(not(feature = "grpc-shared-runtime"))]
+    let handle = spawn_io("grpc-server", move || serve_grpc(listener, context, stop, errors))?;
+    #[cfg(feature = "grpc-shared-runtime")]
+    let handle = spawn_io_task(serve_grpc_async(listener, context, stop, errors))?;
+    Ok((MonitorChannels { latest_metrics: Some(latest), ..channels }, Some(handle)))
+}
+
+/// Starts the admin API if `--admin-listen` is given, binding its address before any work starts
+/// so that a bad address fails fast.
+///
+/// # Arguments
+///
+/// * `run_args` - Options of the `run` subcommand.
+/// * `channels` - Channels of the monitor that the admin API inspects and controls.
+/// * `rate_control` - Target rate and pause state shared with the local workers.
+/// * `stop_signal` - Atomic flag indicating when to stop serving.
+/// * `started` - When the monitor started.
+fn start_admin_server(
+    run_args: &RunArgs,
+    channels: MonitorChannels,
+    rate_control: &Arc<RateControl>,
+    stop_signal: &Arc<AtomicBool>,
+    started: Instant,
+) -> Result<(MonitorChannels, Option<IoHandle>)> {
+    let Some(addr) = &run_args.admin_listen else {
+        return Ok((channels, None));
+    };
+    let listener = TcpListener::bind(addr).map_err(|source| MonitorError::Listen { purpose: "admin requests", addr: addr.clone(), source })?;
+    println!("Serving the admin API on http://{}/status", addr);
+    let latest = channels.latest_metrics.clone().unwrap_or_default();
+    let context = AdminContext {
+        rate: rate_control.clone(),
+        policy: channels.live_policy.clone(),
+        latest: latest.clone(),
+        control: channels.control_sender.clone(),
+        notices: channels.cmd_sender.clone(),
+        started,
+    };
+    let stop = stop_signal.clone();
+    let errors = channels.error_sender.clone();
+    let handle = spawn_io("admin-server", move || serve_admin(listener, context, stop, errors))?;
+    Ok((MonitorChannels { latest_metrics: Some(latest), ..channels }, Some(handle)))
+}
+
+/// Creates the sinks written every reporting interval from their options. The StatsD socket is
+/// opened here, so that a bad address fails before any work starts.
+fn interval_sinks(exports: &ExportArgs) -> Result<Vec<Box<dyn Sink>>> {
+    let mut sinks: Vec<Box<dyn Sink>> = Vec::new();
+    if let Some(dir) = &exports.csv_export {
+        sinks.push(Box::new(CsvSeriesSink::new(dir.clone())));
+    }
+    if let Some(addr) = &exports.statsd {
+        sinks.push(Box::new(StatsdEmitter::connect(addr, &exports.statsd_prefix, exports.statsd_tags)?));
+        println!("Sending metrics to StatsD at {}", addr);
+    }
+    if let Some(path) = &exports.badge_file {
+        sinks.push(Box::new(BadgeFileSink { path: path.clone() }));
+    }
+    if let Some(path) = &exports.shadow_ai_report {
+        sinks.push(Box::new(ShadowAiReportSink { path: path.clone() }));
+    }
+    Ok(sinks)
+}
+
+/// Starts the Slack notifier if `--slack-webhook` is given, and routes the monitor's alerts to it.
+///
+/// # Arguments
+///
+/// * `outputs` - Options of the monitor's outputs.
+/// * `sink_policy` - How to handle failed posts.
+/// * `channels` - Channels of the monitor whose alerts are posted.
+/// * `stop_signal` - Atomic flag indicating when to stop posting.
+#[cfg(feature = "slack")]
+fn start_slack_notifier(
+    outputs: &LiveOutputArgs,
+    sink_policy: ErrorPolicy,
+    channels: MonitorChannels,
+    stop_signal: &Arc<AtomicBool>,
+) -> Result<(MonitorChannels, Option<IoHandle>)> {
+    let Some(webhook) = &outputs.slack_webhook else {
+        return Ok((channels, None));
+    };
+    let config = SlackConfig::new(webhook, outputs.slack_channel.as_deref(), outputs.slack_batch_secs)?;
+    let (sender, receiver) = unbounded();
+    let stop = stop_signal.clone();
+    let errors = channels.error_sender.clone();
+    let handle = spawn_io("slack-notifier", move || slack_notifier(config, receiver, stop, sink_policy, errors))?;
+    Ok((MonitorChannels { slack_alerts: Some(sender), ..channels }, Some(handle)))
+}
+
+/// Starts the PagerDuty sender if `--pagerduty` is given, for the monitor to send incident
+/// events to.
+///
+/// # Arguments
+///
+/// * `outputs` - Options of the monitor's outputs.
+/// * `sink_policy` - How to handle failed sends.
+/// * `channels` - Channels of the monitor whose incidents are sent.
+/// * `stop_signal` - Atomic flag indicating when to stop sending.
+/// * `ingestion_ends` - Whether events stop by design, as when a replay finishes; the ingestion
+///   stall rule is then disabled.
+#[cfg(feature = "pagerduty")]
+fn start_pagerduty_sender(
+    outputs: &LiveOutputArgs,
+    sink_policy: ErrorPolicy,
+    channels: MonitorChannels,
+    stop_signal: &Arc<AtomicBool>,
+    ingestion_ends: bool,
+) -> Result<(MonitorChannels, Option<IoHandle>)> {
+    let Some(path) = &outputs.pagerduty else {
+        return Ok((channels, None));
+    };
+    let mut config = PagerDutyConfig::load(path)?;
+    if ingestion_ends {
+        config.rules.retain(|rule| !matches!(rule.condition, AlertCondition::IngestionStalled(_)));
+    }
+    let (sender, receiver) = unbounded();
+    let thread_config = config.clone();
+    let stop = stop_signal.clone();
+    let errors = channels.error_sender.clone();
+    let handle = spawn_io("pagerduty-sender", move || pagerduty_sender(thread_config, receiver, stop, sink_policy, errors))?;
+    Ok((MonitorChannels { pagerduty: Some((sender, config)), ..channels }, Some(handle)))
+}
+
+/// Starts the federation sender if `--federate` is given, for the monitor to push each
+/// interval's metrics to the leader through.
+///
+/// # Arguments
+///
+/// * `outputs` - Options of the monitor's outputs.
+/// * `listen` - This instance's `--listen` address, which the leader must not be.
+/// * `channels` - Channels of the monitor whose metrics are pushed.
+fn start_federation(outputs: &LiveOutputArgs, listen: Option<&str>, channels: MonitorChannels) -> Result<(MonitorChannels, Option<IoHandle>)> {
+    let Some(leader) = outputs.federate.clone() else {
+        return Ok((channels, None));
+    };
+    if listen == Some(leader.as_str()) {
+        return Err(MonitorError::Config(format!(
+            "--federate {} is this instance's own --listen address; its metrics would be counted again every interval",
+            leader
+        )));
+    }
+    let instance = outputs.instance_name.clone().unwrap_or_else(default_instance_name);
+    println!("Federating to leader {} as {}", leader, instance);
+    let (sender, receiver) = unbounded();
+    let errors = channels.error_sender.clone();
+    let handle = spawn_io("federation-sender", move || federation_sender(&leader, &instance, receiver, errors))?;
+    Ok((MonitorChannels { federation: Some(sender), ..channels }, Some(handle)))
+}
+
+/// Reads the depths of the channels feeding the enabled sinks and the frontend.
+///
+/// # Arguments
+///
+/// * `run_args` - Options of a live run, selecting the sinks; `None` for replays.
+/// * `worker_metrics` - Metric shards holding the sink channel depths seen by the workers.
+/// * `cmd_sender` - Channel of updates to the frontend.
+fn channel_depths(run_args: Option<&RunArgs>, worker_metrics: &SharedMetrics, cmd_sender: &Sender<FrontendCommand>) -> Vec<ChannelDepth> {
+    let (record_depth, audit_depth) = worker_metrics.sink_queue_depths();
+    let mut channels = Vec::new();
+    if let Some(run_args) = run_args {
+        if run_args.record.is_some() {
+            channels.push(ChannelDepth { name: RECORDER_SINK.into(), depth: record_depth, capacity: None });
+        }
+        if run_args.audit_log.is_some() {
+            channels.push(ChannelDepth { name: AUDIT_SINK.into(), depth: audit_depth, capacity: Some(run_args.audit_buffer.max(1)) });
+        }
+    }
+    channels.push(ChannelDepth { name: "frontend updates".into(), depth: cmd_sender.len(), capacity: None });
+    channels
+}
+
+/// Returns the number of local worker threads.
+///
+/// Defaults to the number of logical cores, reduced for rates too low to keep them all busy. A
+/// file source is read by at most one.
+fn worker_thread_count(args: &Args) -> usize {
+    // Each worker would read the whole log, so a file source is read by one.
+    if let SourceSpec::File { .. } = args.source {
+        return args.threads.unwrap_or(1).min(1);
+    }
+    args.threads.unwrap_or_else(|| {
+        let cores = std::thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1);
+        default_worker_count(args.rate, cores)
+    })
+}
+
+/// Creates the event-time windows of a replay or import: one per `--interval`, kept open for
+/// `--allowed-lateness`.
+fn event_time_windows(args: &Args) -> EventTimeWindows {
+    EventTimeWindows::new(Duration::from_secs(args.interval), Duration::from_secs(args.allowed_lateness))
+}
+
+/// Creates a batch controller for one of the workers sharing a target rate.
+fn batch_controller(args: &Args, rate_control: &Arc<RateControl>) -> BatchController {
+    BatchController::controlled(rate_control.clone(), Duration::from_millis(args.max_batch_latency))
+}
+
+/// Merges incoming metrics into `total_metrics` until `is_finished` says the threads feeding them have finished.
+fn drain_until_finished(is_finished: impl Fn() -> bool, channels: &MonitorChannels, total_metrics: &mut CumulativeMetrics) {
+    while !is_finished() {
+        while let Ok(metrics) = channels.metrics_receiver.try_recv() {
+            total_metrics.add_late(&metrics);
+        }
+        for interval in channels.event_windows.iter().flat_map(Receiver::try_iter) {
+            total_metrics.record(&interval);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Prints the run summary and saves it to `--run-result` if requested.
+///
+/// # Arguments
+///
+/// * `args` - Command line arguments.
+/// * `mode` - Name of the mode that ran.
+/// * `duration` - Wall-clock time from the start of processing to shutdown.
+/// * `events_processed` - Events evaluated during this run.
+/// * `target_rate` - Requested rate in events per second, if there is one.
+fn report_run(args: &Args, mode: &'static str, duration: Duration, events_processed: u64, target_rate: Option<u32>) {
+    let run_report = RunReport::capture(mode, duration, events_processed, target_rate);
+    run_report.print();
+    if let Some(path) = &args.run_result {
+        match run_report.save(path) {
+            Ok(()) => println!("Saved run result to {}", path.display()),
+            Err(source) => warn!("{}", MonitorError::Sink { sink: RUN_RESULT_SINK, source }),
+        }
+    }
+}
+
+/// Prints how many sink failures `--chaos` injected, separately from the errors they caused.
+fn print_chaos_summary() {
+    for (sink, injected) in chaos::injected_failures() {
+        println!("Chaos: injected {} failures into {}", injected, sink);
+    }
+}
+
+/// Logs error reports that arrived after the dashboard closed, once the terminal is restored.
+fn log_late_errors(error_receiver: &Receiver<ErrorReport>) {
+    while let Ok(error_report) = error_receiver.try_recv() {
+        warn!("{}", error_report.error);
+    }
 }
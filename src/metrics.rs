@@ -1,90 +1,1292 @@
+use crate::compliance::ComplianceFlag;
+use crate::constants::{DEPARTMENT_NAMES, MODEL_NAMES, SERVICE_NAMES};
+use crate::policy::Policy;
+use crate::tail_latency::BatchLatencyRecorder;
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Default for the most points kept in each metrics history (`--history-points`); beyond the
+/// limit, the older half is merged pairwise, so old history loses resolution instead of being
+/// dropped.
+pub const DEFAULT_HISTORY_POINTS: usize = 4096;
+
+/// Largest count the dashboard's bar charts and sparklines can draw. tui scales each value by
+/// eight times the chart height in a `u64`, which overflows beyond this for the tallest chart.
+pub const CHART_VALUE_LIMIT: u64 = u64::MAX / (8 * u16::MAX as u64);
+
+/// Counter value above which the dashboard warns that charted counts are about to be capped at
+/// [`CHART_VALUE_LIMIT`]; about 10 years of a steady 100,000 events per second.
+pub const COUNTER_WARNING_THRESHOLD: u64 = CHART_VALUE_LIMIT / 10 * 9;
+
+/// Most users counted by one set of metrics. Beyond it, the lowest-ranked users are dropped, so
+/// a user first seen once the limit is reached is only kept if they outrank the lowest kept one.
+pub const MAX_TRACKED_USERS: usize = 1000;
+
 /// Enhanced metrics for compliance monitoring and reporting.
-#[derive(Default, Clone)]
+///
+/// The same counters describe a single batch, a reporting interval ([`IntervalMetrics`]), or the
+/// whole run ([`CumulativeMetrics`]); only the latter has a history. Counters saturate at `u64::MAX` instead of wrapping, so a monitor left running
+/// long enough to exhaust them keeps reporting its highest value rather than restarting at zero.
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct ComplianceMetrics {
-    pub total_events: usize,
-    pub eu_act_violations: usize,
-    pub gdpr_violations: usize,
-    pub internal_violations: usize,
-    pub high_risk_count: usize,
-    pub medium_risk_count: usize,
-    pub low_risk_count: usize,
-    pub service_counts: [usize; 5],
-    pub vendor_counts: [usize; 5],
-    pub department_counts: [usize; 5],
-    pub risk_factor_counts: [usize; 5],
+    pub total_events: u64,
+    pub violation_counts: Vec<u64>, // Indexed by compliance flag.
+    pub high_risk_violations: Vec<u64>, // Violations by high-risk events, indexed by compliance flag.
+    pub high_risk_count: u64,
+    pub medium_risk_count: u64,
+    pub low_risk_count: u64,
+    pub service_counts: [u64; 5],
+    pub vendor_counts: [u64; 5],
+    pub vendor_violations: [u64; 5], // Events that failed at least one check, indexed like `vendor_counts`.
+    pub department_counts: [u64; 6],
+    pub purpose_counts: [u64; 6], // Indexed like `PURPOSE_NAMES`, for events with a declared purpose.
+    pub purpose_violations: [u64; 6], // Purpose-limitation violations, indexed like `purpose_counts`.
+    pub region_counts: [u64; 6], // Indexed like `REGION_NAMES`, for events with a known region.
+    pub region_violations: [u64; 6], // Data residency violations, indexed like `region_counts`.
+    pub ai_act_tier_counts: [u64; 4], // Indexed like `AI_ACT_TIERS`.
+    pub phi_events: u64, // Events carrying protected health information.
+    pub waived_events: u64, // Events with at least one violation exempted by a waiver.
+    pub violating_events: u64, // Events that failed at least one compliance check.
+    pub shadow_ai_events: u64, // Events using a combination not on the department's allowlist, waived or not.
+    pub shadow_ai_department_counts: [u64; 6], // Shadow AI events, indexed like `department_counts`.
+    pub shadow_ai_sensitivity: u64, // Summed data sensitivity of the shadow AI events.
+    pub lawful_basis_counts: [u64; 4], // Indexed like `LAWFUL_BASIS_NAMES`, for events with a recorded basis.
+    pub lawful_basis_personal: [u64; 4], // Events with personal data per lawful basis, indexed like `lawful_basis_counts`.
+    pub pii_type_counts: [u64; 5], // Events containing each PII type, indexed like `PII_TYPE_NAMES`.
+    pub waived_counts: Vec<u64>, // Violations exempted by a waiver, indexed by compliance flag.
+    pub risk_factor_counts: Vec<u64>, // Indexed by risk factor id.
     pub avg_data_sensitivity: f64,
     pub total_data_sensitivity: u64,
-    pub data_sensitivity_samples: usize,
-    pub processing_rate: f64,
-    pub historical_rates: Vec<f64>,           // For time-series visualization
-    pub historical_violations: Vec<(usize, usize, usize)>, // (EU, GDPR, Internal)
+    pub data_sensitivity_samples: u64,
+    pub history: Vec<HistoryPoint>, // One point per reporting interval, oldest first.
+    /// The same metrics for the events of each department, indexed like `DEPARTMENT_NAMES`.
+    /// Empty until events are counted; the entries have no departments, services, or models of
+    /// their own.
+    pub departments: Vec<ComplianceMetrics>,
+    /// The same metrics for the events of each service, indexed like `SERVICE_NAMES`.
+    /// Empty until events are counted; the entries have no departments, services, models, or
+    /// users of their own.
+    pub services: Vec<ComplianceMetrics>,
+    /// The same metrics for the events of each model version, indexed like `MODEL_NAMES`.
+    /// Empty until events are counted; the entries have no departments, services, models, or
+    /// users of their own.
+    pub models: Vec<ComplianceMetrics>,
+    /// Counts per user id, for the events whose user is known; at most [`MAX_TRACKED_USERS`]
+    /// after merging.
+    pub users: BTreeMap<u32, UserCounts>,
+}
+
+/// Events, violations, and risk of a single user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserCounts {
+    pub events: u64,
+    /// Failed compliance checks, summed over the user's events.
+    pub violations: u64,
+    pub high_risk_events: u64,
+    /// Risk scores summed over the user's events; users are ranked by it.
+    pub total_risk_score: u64,
+}
+
+impl UserCounts {
+    /// Adds the counts of `other`, saturating on overflow.
+    fn add(&mut self, other: &UserCounts) {
+        self.events = self.events.saturating_add(other.events);
+        self.violations = self.violations.saturating_add(other.violations);
+        self.high_risk_events = self.high_risk_events.saturating_add(other.high_risk_events);
+        self.total_risk_score = self.total_risk_score.saturating_add(other.total_risk_score);
+    }
+
+    /// Returns the counts accumulated since `earlier`, an older reading of the same counts.
+    fn delta_since(&self, earlier: &UserCounts) -> UserCounts {
+        UserCounts {
+            events: self.events.saturating_sub(earlier.events),
+            violations: self.violations.saturating_sub(earlier.violations),
+            high_risk_events: self.high_risk_events.saturating_sub(earlier.high_risk_events),
+            total_risk_score: self.total_risk_score.saturating_sub(earlier.total_risk_score),
+        }
+    }
+
+    /// Returns the mean risk score of the user's events.
+    pub fn average_risk(&self) -> f64 {
+        self.total_risk_score as f64 / self.events.max(1) as f64
+    }
+}
+
+/// One reporting interval in the metrics history.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryPoint {
+    /// Monitor time at the end of the interval.
+    pub at: Duration,
+    /// Wall-clock time at the end of the interval, in milliseconds since the Unix epoch.
+    pub unix_ms: u64,
+    /// Length of the interval; spans several intervals once points are merged.
+    pub span: Duration,
+    /// Events processed within the interval.
+    pub events: u64,
+    /// Violations per compliance flag within the interval.
+    pub violations: Vec<u64>,
+    /// High-risk events within the interval; zero in snapshots saved before it was recorded.
+    #[serde(default)]
+    pub high_risk: u64,
+}
+
+impl HistoryPoint {
+    /// Returns the events per second over the interval.
+    pub fn rate(&self) -> f64 {
+        self.events as f64 / self.span.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Returns the percentage of compliance checks failed by the interval's events.
+    pub fn violation_percentage(&self) -> f64 {
+        let checks = self.events as f64 * self.violations.len() as f64;
+        if checks == 0.0 {
+            return 0.0;
+        }
+        100.0 * self.violations.iter().sum::<u64>() as f64 / checks
+    }
+
+    /// Returns the percentage of the interval's events that were high risk.
+    pub fn high_risk_percentage(&self) -> f64 {
+        if self.events == 0 {
+            return 0.0;
+        }
+        100.0 * self.high_risk as f64 / self.events as f64
+    }
+
+    /// Combines this point with the one following it into a point covering both intervals.
+    fn merge(&self, later: &HistoryPoint) -> HistoryPoint {
+        let mut violations = self.violations.clone();
+        merge_counts(&mut violations, &later.violations);
+        HistoryPoint {
+            at: later.at,
+            unix_ms: later.unix_ms,
+            span: self.span + later.span,
+            events: self.events.saturating_add(later.events),
+            violations,
+            high_risk: self.high_risk.saturating_add(later.high_risk),
+        }
+    }
+}
+
+/// The metrics counted over one reporting interval, or over several consecutive intervals once
+/// merged.
+///
+/// Rates are derived from the counts and the span, so merging intervals sums both rather than
+/// averaging their rates.
+#[derive(Clone, Default)]
+pub struct IntervalMetrics {
+    /// Wall-clock time at the end of the interval, in milliseconds since the Unix epoch.
+    pub unix_ms: u64,
+    /// Length of the interval.
+    pub span: Duration,
+    /// Events, violations, and breakdowns counted within the interval; they have no history.
+    pub counts: ComplianceMetrics,
+}
+
+impl IntervalMetrics {
+    /// Closes an interval over the metrics counted within it.
+    ///
+    /// # Arguments
+    ///
+    /// * `counts` - The metrics counted within the interval.
+    /// * `span` - Length of the interval.
+    /// * `unix_ms` - Wall-clock time at the end of the interval, in milliseconds since the Unix epoch.
+    pub fn new(counts: ComplianceMetrics, span: Duration, unix_ms: u64) -> Self {
+        IntervalMetrics { unix_ms, span, counts }
+    }
+
+    /// Returns the events per second over the interval.
+    pub fn rate(&self) -> f64 {
+        self.counts.total_events as f64 / self.span.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Extends this interval with the one following it, so it covers both.
+    pub fn merge(&mut self, later: &IntervalMetrics) {
+        self.counts.merge(&later.counts);
+        self.span += later.span;
+        self.unix_ms = self.unix_ms.max(later.unix_ms);
+    }
+}
+
+/// The metrics counted since the run started, or since the snapshot it resumed from, with one
+/// history point per reporting interval for the totals and for each department, service, and
+/// model.
+///
+/// It dereferences to the totals. They only grow by whole intervals, which also extend the
+/// history, or by counts arriving after the last interval closed.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CumulativeMetrics {
+    totals: ComplianceMetrics,
+    /// Most points kept in each history. It is not saved in snapshots, so a restored run keeps
+    /// as many points as it is configured to.
+    #[serde(skip, default = "default_history_points")]
+    history_points: usize,
+}
+
+fn default_history_points() -> usize {
+    DEFAULT_HISTORY_POINTS
+}
+
+impl Default for CumulativeMetrics {
+    fn default() -> Self {
+        CumulativeMetrics::resume(ComplianceMetrics::default())
+    }
+}
+
+impl CumulativeMetrics {
+    /// Resumes from totals and history counted earlier, such as those of a snapshot.
+    pub fn resume(totals: ComplianceMetrics) -> Self {
+        CumulativeMetrics { totals, history_points: DEFAULT_HISTORY_POINTS }
+    }
+
+    /// Limits each history to `points` points, merging older points of a longer restored
+    /// history right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - Most points kept per history, at least 2, as given by `--history-points`
+    pub fn with_history_points(mut self, points: usize) -> Self {
+        self.history_points = points.max(2);
+        self.totals.for_each_history(|metrics| metrics.compact_history(self.history_points));
+        self
+    }
+
+    /// Adds the counts of a closed interval and appends it to the history of the totals and of
+    /// each department, service, and model.
+    pub fn record(&mut self, interval: &IntervalMetrics) {
+        self.totals.merge(&interval.counts);
+        self.totals.update_historical_data(interval, self.history_points);
+    }
+
+    /// Adds counts that arrived after the last interval closed, such as those drained at
+    /// shutdown; they count towards the totals but not the history.
+    pub fn add_late(&mut self, counts: &ComplianceMetrics) {
+        self.totals.merge(counts);
+    }
+
+    /// Returns the totals.
+    pub fn into_totals(self) -> ComplianceMetrics {
+        self.totals
+    }
+}
+
+impl Deref for CumulativeMetrics {
+    type Target = ComplianceMetrics;
+
+    fn deref(&self) -> &ComplianceMetrics {
+        &self.totals
+    }
 }
 
 impl ComplianceMetrics {
-    /// Merges another `ComplianceMetrics` instance into self.
+    /// Merges another `ComplianceMetrics` instance into self, including its departments,
+    /// services, and models, saturating on overflow.
     pub fn merge(&mut self, other: &ComplianceMetrics) {
-        self.total_events += other.total_events;
-        self.eu_act_violations += other.eu_act_violations;
-        self.gdpr_violations += other.gdpr_violations;
-        self.internal_violations += other.internal_violations;
-        self.high_risk_count += other.high_risk_count;
-        self.medium_risk_count += other.medium_risk_count;
-        self.low_risk_count += other.low_risk_count;
-        for i in 0..5 {
-            self.service_counts[i] += other.service_counts[i];
-            self.vendor_counts[i] += other.vendor_counts[i];
-            self.department_counts[i] += other.department_counts[i];
-            self.risk_factor_counts[i] += other.risk_factor_counts[i];
-        }
-        self.total_data_sensitivity += other.total_data_sensitivity;
-        self.data_sensitivity_samples += other.data_sensitivity_samples;
+        self.merge_counters(other);
+        merge_breakdown(&mut self.departments, &other.departments);
+        merge_breakdown(&mut self.services, &other.services);
+        merge_breakdown(&mut self.models, &other.models);
+    }
+
+    /// Adds the counters of every department to the totals.
+    ///
+    /// Batches are tallied per department only, so the totals are summed once per batch
+    /// rather than counted twice per event.
+    pub fn add_department_totals(&mut self) {
+        let departments = std::mem::take(&mut self.departments);
+        for department in &departments {
+            self.merge_counters(department);
+        }
+        self.departments = departments;
+    }
+
+    /// Merges the counters of another instance into self, leaving the departments, services, and
+    /// models alone.
+    fn merge_counters(&mut self, other: &ComplianceMetrics) {
+        self.total_events = self.total_events.saturating_add(other.total_events);
+        merge_counts(&mut self.violation_counts, &other.violation_counts);
+        merge_counts(&mut self.high_risk_violations, &other.high_risk_violations);
+        self.high_risk_count = self.high_risk_count.saturating_add(other.high_risk_count);
+        self.medium_risk_count = self.medium_risk_count.saturating_add(other.medium_risk_count);
+        self.low_risk_count = self.low_risk_count.saturating_add(other.low_risk_count);
+        add_counts(&mut self.service_counts, &other.service_counts);
+        add_counts(&mut self.vendor_counts, &other.vendor_counts);
+        add_counts(&mut self.vendor_violations, &other.vendor_violations);
+        add_counts(&mut self.department_counts, &other.department_counts);
+        add_counts(&mut self.purpose_counts, &other.purpose_counts);
+        add_counts(&mut self.purpose_violations, &other.purpose_violations);
+        add_counts(&mut self.region_counts, &other.region_counts);
+        add_counts(&mut self.region_violations, &other.region_violations);
+        add_counts(&mut self.ai_act_tier_counts, &other.ai_act_tier_counts);
+        self.phi_events = self.phi_events.saturating_add(other.phi_events);
+        self.waived_events = self.waived_events.saturating_add(other.waived_events);
+        self.violating_events = self.violating_events.saturating_add(other.violating_events);
+        self.shadow_ai_events = self.shadow_ai_events.saturating_add(other.shadow_ai_events);
+        add_counts(&mut self.shadow_ai_department_counts, &other.shadow_ai_department_counts);
+        self.shadow_ai_sensitivity = self.shadow_ai_sensitivity.saturating_add(other.shadow_ai_sensitivity);
+        add_counts(&mut self.lawful_basis_counts, &other.lawful_basis_counts);
+        add_counts(&mut self.lawful_basis_personal, &other.lawful_basis_personal);
+        add_counts(&mut self.pii_type_counts, &other.pii_type_counts);
+        merge_counts(&mut self.waived_counts, &other.waived_counts);
+        merge_counts(&mut self.risk_factor_counts, &other.risk_factor_counts);
+        self.total_data_sensitivity = self.total_data_sensitivity.saturating_add(other.total_data_sensitivity);
+        self.data_sensitivity_samples = self.data_sensitivity_samples.saturating_add(other.data_sensitivity_samples);
         if self.data_sensitivity_samples > 0 {
             self.avg_data_sensitivity = self.total_data_sensitivity as f64 / self.data_sensitivity_samples as f64;
         }
+        merge_users(&mut self.users, &other.users);
     }
 
-    /// Updates historical data for processing rate and violations.
-    ///
-    /// # Arguments
-    ///
-    /// * `processed_since_last` - The number of events processed since the last update.
-    /// * `elapsed` - The duration since the last update.
-    pub fn update_historical_data(&mut self, processed_since_last: usize, elapsed: Duration) {
-        self.processing_rate = processed_since_last as f64 / elapsed.as_secs_f64();
-        self.historical_rates.push(self.processing_rate);
-        if self.historical_rates.len() > 30 {
-            self.historical_rates.remove(0);
+    /// Returns up to `limit` users, highest total risk score first, with ties broken by the
+    /// number of violations.
+    pub fn top_users(&self, limit: usize) -> Vec<(u32, UserCounts)> {
+        let mut users: Vec<(u32, UserCounts)> = self.users.iter().map(|(&user, &counts)| (user, counts)).collect();
+        users.sort_by_key(|(user, counts)| user_rank(*user, counts));
+        users.truncate(limit);
+        users
+    }
+
+    /// Appends an interval to the history of the totals and of each department, service, and
+    /// model.
+    fn update_historical_data(&mut self, interval: &IntervalMetrics, max_points: usize) {
+        let empty = ComplianceMetrics::default();
+        self.record_history(&interval.counts, interval.span, interval.unix_ms, max_points);
+        let breakdowns = [
+            (&mut self.departments, &interval.counts.departments),
+            (&mut self.services, &interval.counts.services),
+            (&mut self.models, &interval.counts.models),
+        ];
+        for (entries, counted) in breakdowns {
+            for (index, entry) in entries.iter_mut().enumerate() {
+                entry.record_history(counted.get(index).unwrap_or(&empty), interval.span, interval.unix_ms, max_points);
+            }
         }
-        self.historical_violations.push((
-            self.eu_act_violations,
-            self.gdpr_violations,
-            self.internal_violations,
-        ));
-        if self.historical_violations.len() > 30 {
-            self.historical_violations.remove(0);
+    }
+
+    /// Calls `f` on the totals and on each department, service, and model.
+    fn for_each_history(&mut self, mut f: impl FnMut(&mut ComplianceMetrics)) {
+        f(self);
+        for entry in self.departments.iter_mut().chain(&mut self.services).chain(&mut self.models) {
+            f(entry);
         }
     }
 
-    /// Calculates the overall compliance percentage.
-    pub fn compliance_percentage(&self) -> f64 {
+    /// Appends a history point for an interval of length `span` in which `counted` was counted.
+    fn record_history(&mut self, counted: &ComplianceMetrics, span: Duration, unix_ms: u64, max_points: usize) {
+        let at = self.history.last().map_or(Duration::ZERO, |point| point.at) + span;
+        self.history.push(HistoryPoint {
+            at,
+            unix_ms,
+            span,
+            events: counted.total_events,
+            violations: counted.violation_counts.clone(),
+            high_risk: counted.high_risk_count,
+        });
+        self.compact_history(max_points);
+    }
+
+    /// Merges the older half of the history pairwise until it has at most `max_points` points.
+    fn compact_history(&mut self, max_points: usize) {
+        while self.history.len() > max_points {
+            let older: Vec<HistoryPoint> = self.history.drain(..(self.history.len() / 2).max(2)).collect();
+            let merged = older.chunks(2).map(|pair| pair.iter().skip(1).fold(pair[0].clone(), |a, b| a.merge(b)));
+            self.history.splice(0..0, merged);
+        }
+    }
+
+    /// Returns the history within `window` of the latest point, or all of it for `None`, merged
+    /// into at most `max_points` points for display.
+    pub fn history_window(&self, window: Option<Duration>, max_points: usize) -> Vec<HistoryPoint> {
+        self.history_between(self.history_window_start(window), Duration::MAX, max_points)
+    }
+
+    /// Returns the history points that end after `start` and no later than `end`, in monitor
+    /// time, merged into at most `max_points` points for display.
+    pub fn history_between(&self, start: Duration, end: Duration, max_points: usize) -> Vec<HistoryPoint> {
+        let first = self.history.partition_point(|point| point.at <= start);
+        let last = self.history.partition_point(|point| point.at <= end).max(first);
+        let points = &self.history[first..last];
+        let group = points.len().div_ceil(max_points.max(1)).max(1);
+        points
+            .chunks(group)
+            .map(|chunk| chunk.iter().skip(1).fold(chunk[0].clone(), |a, b| a.merge(b)))
+            .collect()
+    }
+
+    /// Returns the monitor time at which `window` starts, or zero for the whole history.
+    pub fn history_window_start(&self, window: Option<Duration>) -> Duration {
+        match (window, self.history.last()) {
+            (Some(window), Some(latest)) => latest.at.saturating_sub(window),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns the violations per compliance flag within `window` of the latest history point,
+    /// or the cumulative totals for `None` or a window reaching back past the oldest point.
+    pub fn violations_in_window(&self, window: Option<Duration>) -> Vec<u64> {
+        match self.window_points(window) {
+            Some(points) => points.iter().fold(vec![0; self.violation_counts.len()], |mut violations, point| {
+                merge_counts(&mut violations, &point.violations);
+                violations
+            }),
+            None => self.violation_counts.clone(),
+        }
+    }
+
+    /// Returns the events processed within `window` of the latest history point, or the
+    /// cumulative total for `None` or a window reaching back past the oldest point.
+    pub fn events_in_window(&self, window: Option<Duration>) -> u64 {
+        match self.window_points(window) {
+            Some(points) => points.iter().fold(0, |events, point| events.saturating_add(point.events)),
+            None => self.total_events,
+        }
+    }
+
+    /// Returns the history points within `window` of the latest one, or `None` for no window or
+    /// one reaching back past the oldest point, which the totals cover instead.
+    fn window_points(&self, window: Option<Duration>) -> Option<&[HistoryPoint]> {
+        let start = self.history_window_start(window);
+        if window.is_none() || self.history.first().is_none_or(|point| point.at > start) {
+            return None;
+        }
+        Some(&self.history[self.history.partition_point(|point| point.at <= start)..])
+    }
+
+    /// Returns the events per second over the latest reporting interval.
+    pub fn processing_rate(&self) -> f64 {
+        self.history.last().map_or(0.0, HistoryPoint::rate)
+    }
+
+    /// Returns the number of events that failed the check behind `flag`.
+    pub fn violations(&self, flag: ComplianceFlag) -> u64 {
+        self.violation_counts.get(flag.0 as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the percentage of events that passed the check behind `flag`.
+    pub fn check_compliance_percentage(&self, flag: ComplianceFlag) -> f64 {
         if self.total_events == 0 {
             return 100.0;
         }
-        let violation_count = self.eu_act_violations + self.gdpr_violations + self.internal_violations;
-        100.0 * (1.0 - (violation_count as f64 / (self.total_events as f64 * 3.0)))
+        (100.0 * (1.0 - self.violations(flag) as f64 / self.total_events as f64)).clamp(0.0, 100.0)
     }
 
-    /// Returns the risk distribution as percentages for high, medium, and low risk events.
-    pub fn risk_distribution(&self) -> [f64; 3] {
-        if self.total_events == 0 {
-            return [0.0, 0.0, 0.0];
+    /// Returns the number of events whose violation of the check behind `flag` was waived.
+    pub fn waived(&self, flag: ComplianceFlag) -> u64 {
+        self.waived_counts.get(flag.0 as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the total number of violations across all compliance flags, saturating on overflow.
+    pub fn total_violations(&self) -> u64 {
+        self.violation_counts.iter().fold(0, |total, &count| total.saturating_add(count))
+    }
+
+    /// Calculates the overall compliance percentage, clamped to `0.0..=100.0`.
+    pub fn compliance_percentage(&self) -> f64 {
+        if self.total_events == 0 || self.violation_counts.is_empty() {
+            return 100.0;
+        }
+        let checks = self.total_events as f64 * self.violation_counts.len() as f64;
+        (100.0 * (1.0 - (self.total_violations() as f64 / checks))).clamp(0.0, 100.0)
+    }
+
+    /// Returns the largest cumulative counter drawn in a chart.
+    pub fn max_counter(&self) -> u64 {
+        [self.total_events, self.high_risk_count, self.medium_risk_count, self.low_risk_count]
+            .into_iter()
+            .chain(self.violation_counts.iter().copied())
+            .chain(self.risk_factor_counts.iter().copied())
+            .chain(self.purpose_violations)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns true once any cumulative counter passes [`COUNTER_WARNING_THRESHOLD`].
+    pub fn counters_near_limit(&self) -> bool {
+        self.max_counter() >= COUNTER_WARNING_THRESHOLD
+    }
+
+    /// Returns the counts accumulated since `earlier`, an older reading of the same counters,
+    /// including those of each department, service, and model.
+    pub fn delta_since(&self, earlier: &ComplianceMetrics) -> ComplianceMetrics {
+        let mut delta = ComplianceMetrics {
+            total_events: self.total_events.saturating_sub(earlier.total_events),
+            violation_counts: self.violation_counts.clone(),
+            high_risk_violations: self.high_risk_violations.clone(),
+            high_risk_count: self.high_risk_count.saturating_sub(earlier.high_risk_count),
+            medium_risk_count: self.medium_risk_count.saturating_sub(earlier.medium_risk_count),
+            low_risk_count: self.low_risk_count.saturating_sub(earlier.low_risk_count),
+            service_counts: self.service_counts,
+            vendor_counts: self.vendor_counts,
+            vendor_violations: self.vendor_violations,
+            department_counts: self.department_counts,
+            purpose_counts: self.purpose_counts,
+            purpose_violations: self.purpose_violations,
+            region_counts: self.region_counts,
+            region_violations: self.region_violations,
+            ai_act_tier_counts: self.ai_act_tier_counts,
+            phi_events: self.phi_events.saturating_sub(earlier.phi_events),
+            waived_events: self.waived_events.saturating_sub(earlier.waived_events),
+            violating_events: self.violating_events.saturating_sub(earlier.violating_events),
+            shadow_ai_events: self.shadow_ai_events.saturating_sub(earlier.shadow_ai_events),
+            shadow_ai_department_counts: self.shadow_ai_department_counts,
+            shadow_ai_sensitivity: self.shadow_ai_sensitivity.saturating_sub(earlier.shadow_ai_sensitivity),
+            lawful_basis_counts: self.lawful_basis_counts,
+            lawful_basis_personal: self.lawful_basis_personal,
+            pii_type_counts: self.pii_type_counts,
+            waived_counts: self.waived_counts.clone(),
+            risk_factor_counts: self.risk_factor_counts.clone(),
+            total_data_sensitivity: self.total_data_sensitivity.saturating_sub(earlier.total_data_sensitivity),
+            data_sensitivity_samples: self.data_sensitivity_samples.saturating_sub(earlier.data_sensitivity_samples),
+            departments: delta_breakdown(&self.departments, &earlier.departments),
+            services: delta_breakdown(&self.services, &earlier.services),
+            models: delta_breakdown(&self.models, &earlier.models),
+            users: self
+                .users
+                .iter()
+                .map(|(user, counts)| (*user, counts.delta_since(earlier.users.get(user).unwrap_or(&UserCounts::default()))))
+                .filter(|(_, delta)| delta.events > 0)
+                .collect(),
+            ..ComplianceMetrics::default()
+        };
+        sub_counts(&mut delta.violation_counts, &earlier.violation_counts);
+        sub_counts(&mut delta.high_risk_violations, &earlier.high_risk_violations);
+        sub_counts(&mut delta.waived_counts, &earlier.waived_counts);
+        sub_counts(&mut delta.service_counts, &earlier.service_counts);
+        sub_counts(&mut delta.vendor_counts, &earlier.vendor_counts);
+        sub_counts(&mut delta.vendor_violations, &earlier.vendor_violations);
+        sub_counts(&mut delta.shadow_ai_department_counts, &earlier.shadow_ai_department_counts);
+        sub_counts(&mut delta.lawful_basis_counts, &earlier.lawful_basis_counts);
+        sub_counts(&mut delta.lawful_basis_personal, &earlier.lawful_basis_personal);
+        sub_counts(&mut delta.pii_type_counts, &earlier.pii_type_counts);
+        sub_counts(&mut delta.department_counts, &earlier.department_counts);
+        sub_counts(&mut delta.purpose_counts, &earlier.purpose_counts);
+        sub_counts(&mut delta.purpose_violations, &earlier.purpose_violations);
+        sub_counts(&mut delta.region_counts, &earlier.region_counts);
+        sub_counts(&mut delta.region_violations, &earlier.region_violations);
+        sub_counts(&mut delta.ai_act_tier_counts, &earlier.ai_act_tier_counts);
+        sub_counts(&mut delta.risk_factor_counts, &earlier.risk_factor_counts);
+        if delta.data_sensitivity_samples > 0 {
+            delta.avg_data_sensitivity = delta.total_data_sensitivity as f64 / delta.data_sensitivity_samples as f64;
+        }
+        delta
+    }
+}
+
+/// Cumulative counters of a single worker, updated in place instead of sent over a channel.
+///
+/// Each shard has exactly one writer, so updates are a plain load and store per counter and
+/// keep the saturating semantics of [`ComplianceMetrics::merge`]. The aggregator reads shards
+/// concurrently; a reading may mix counters from adjacent batches, which evens out on the
+/// next read.
+#[repr(align(64))]
+pub struct MetricsShard {
+    /// Counters of all the worker's events.
+    totals: ShardCounters,
+    /// Counters of the worker's events per department.
+    departments: Vec<ShardCounters>,
+    /// Counters of the worker's events per service.
+    services: Vec<ShardCounters>,
+    /// Counters of the worker's events per model.
+    models: Vec<ShardCounters>,
+    /// The worker's batch processing times since the aggregator last took them.
+    batch_latency: BatchLatencyRecorder,
+    /// Depths of the event recorder and audit log channels, as last seen by the worker.
+    record_queue_depth: AtomicU64,
+    audit_queue_depth: AtomicU64,
+    /// Entities and archetypes in the worker's ECS world after its last batch.
+    world_entities: AtomicU64,
+    world_archetypes: AtomicU64,
+    /// Whether the worker's thread is running, and how often it was restarted after a panic.
+    alive: AtomicBool,
+    restarts: AtomicU64,
+}
+
+/// The atomic counterparts of the counters in [`ComplianceMetrics`].
+///
+/// Users are counted only by the counters of each department, whose users make up those of the
+/// totals. Their set is open-ended, so their counts sit behind a mutex instead, which the worker
+/// locks once per batch and the aggregator once per reading.
+struct ShardCounters {
+    total_events: AtomicU64,
+    high_risk_count: AtomicU64,
+    medium_risk_count: AtomicU64,
+    low_risk_count: AtomicU64,
+    service_counts: [AtomicU64; 5],
+    vendor_counts: [AtomicU64; 5],
+    vendor_violations: [AtomicU64; 5],
+    department_counts: [AtomicU64; 6],
+    purpose_counts: [AtomicU64; 6],
+    purpose_violations: [AtomicU64; 6],
+    region_counts: [AtomicU64; 6],
+    region_violations: [AtomicU64; 6],
+    ai_act_tier_counts: [AtomicU64; 4],
+    phi_events: AtomicU64,
+    waived_events: AtomicU64,
+    violating_events: AtomicU64,
+    shadow_ai_events: AtomicU64,
+    shadow_ai_department_counts: [AtomicU64; 6],
+    shadow_ai_sensitivity: AtomicU64,
+    lawful_basis_counts: [AtomicU64; 4],
+    lawful_basis_personal: [AtomicU64; 4],
+    pii_type_counts: [AtomicU64; 5],
+    total_data_sensitivity: AtomicU64,
+    data_sensitivity_samples: AtomicU64,
+    violation_counts: Vec<AtomicU64>,
+    high_risk_violations: Vec<AtomicU64>,
+    waived_counts: Vec<AtomicU64>,
+    risk_factor_counts: Vec<AtomicU64>,
+    users: Option<Mutex<BTreeMap<u32, UserCounts>>>,
+}
+
+impl ShardCounters {
+    /// Creates zeroed counters sized for the policy's compliance flags and risk factors, counting
+    /// users if `track_users` is set.
+    fn new(policy: &Policy, track_users: bool) -> Self {
+        ShardCounters {
+            total_events: AtomicU64::new(0),
+            high_risk_count: AtomicU64::new(0),
+            medium_risk_count: AtomicU64::new(0),
+            low_risk_count: AtomicU64::new(0),
+            service_counts: Default::default(),
+            vendor_counts: Default::default(),
+            vendor_violations: Default::default(),
+            department_counts: Default::default(),
+            purpose_counts: Default::default(),
+            purpose_violations: Default::default(),
+            region_counts: Default::default(),
+            region_violations: Default::default(),
+            ai_act_tier_counts: Default::default(),
+            phi_events: AtomicU64::new(0),
+            waived_events: AtomicU64::new(0),
+            violating_events: AtomicU64::new(0),
+            shadow_ai_events: AtomicU64::new(0),
+            shadow_ai_department_counts: Default::default(),
+            shadow_ai_sensitivity: AtomicU64::new(0),
+            lawful_basis_counts: Default::default(),
+            lawful_basis_personal: Default::default(),
+            pii_type_counts: Default::default(),
+            total_data_sensitivity: AtomicU64::new(0),
+            data_sensitivity_samples: AtomicU64::new(0),
+            violation_counts: (0..policy.compliance.len()).map(|_| AtomicU64::new(0)).collect(),
+            high_risk_violations: (0..policy.compliance.len()).map(|_| AtomicU64::new(0)).collect(),
+            waived_counts: (0..policy.compliance.len()).map(|_| AtomicU64::new(0)).collect(),
+            risk_factor_counts: (0..policy.risk_factors.len()).map(|_| AtomicU64::new(0)).collect(),
+            users: track_users.then(|| Mutex::new(BTreeMap::new())),
         }
-        [
-            self.high_risk_count as f64 / self.total_events as f64 * 100.0,
-            self.medium_risk_count as f64 / self.total_events as f64 * 100.0,
-            self.low_risk_count as f64 / self.total_events as f64 * 100.0,
-        ]
+    }
+
+    /// Adds the counters of `batch`, ignoring its departments, services, and models, and its users
+    /// unless these counters track them.
+    fn add(&self, batch: &ComplianceMetrics) {
+        add_atomic(&self.total_events, batch.total_events);
+        add_atomic(&self.high_risk_count, batch.high_risk_count);
+        add_atomic(&self.medium_risk_count, batch.medium_risk_count);
+        add_atomic(&self.low_risk_count, batch.low_risk_count);
+        add_atomic_counts(&self.service_counts, &batch.service_counts);
+        add_atomic_counts(&self.vendor_counts, &batch.vendor_counts);
+        add_atomic_counts(&self.vendor_violations, &batch.vendor_violations);
+        add_atomic_counts(&self.department_counts, &batch.department_counts);
+        add_atomic_counts(&self.purpose_counts, &batch.purpose_counts);
+        add_atomic_counts(&self.purpose_violations, &batch.purpose_violations);
+        add_atomic_counts(&self.region_counts, &batch.region_counts);
+        add_atomic_counts(&self.region_violations, &batch.region_violations);
+        add_atomic_counts(&self.ai_act_tier_counts, &batch.ai_act_tier_counts);
+        add_atomic(&self.phi_events, batch.phi_events);
+        add_atomic(&self.waived_events, batch.waived_events);
+        add_atomic(&self.violating_events, batch.violating_events);
+        add_atomic(&self.shadow_ai_events, batch.shadow_ai_events);
+        add_atomic_counts(&self.shadow_ai_department_counts, &batch.shadow_ai_department_counts);
+        add_atomic(&self.shadow_ai_sensitivity, batch.shadow_ai_sensitivity);
+        add_atomic_counts(&self.lawful_basis_counts, &batch.lawful_basis_counts);
+        add_atomic_counts(&self.lawful_basis_personal, &batch.lawful_basis_personal);
+        add_atomic_counts(&self.pii_type_counts, &batch.pii_type_counts);
+        add_atomic(&self.total_data_sensitivity, batch.total_data_sensitivity);
+        add_atomic(&self.data_sensitivity_samples, batch.data_sensitivity_samples);
+        add_atomic_counts(&self.violation_counts, &batch.violation_counts);
+        add_atomic_counts(&self.high_risk_violations, &batch.high_risk_violations);
+        add_atomic_counts(&self.waived_counts, &batch.waived_counts);
+        add_atomic_counts(&self.risk_factor_counts, &batch.risk_factor_counts);
+        if let Some(users) = &self.users
+            && !batch.users.is_empty()
+        {
+            merge_users(&mut users.lock().unwrap_or_else(|e| e.into_inner()), &batch.users);
+        }
+    }
+
+    /// Reads the counters.
+    fn load(&self) -> ComplianceMetrics {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        ComplianceMetrics {
+            total_events: load(&self.total_events),
+            high_risk_count: load(&self.high_risk_count),
+            medium_risk_count: load(&self.medium_risk_count),
+            low_risk_count: load(&self.low_risk_count),
+            service_counts: self.service_counts.each_ref().map(load),
+            vendor_counts: self.vendor_counts.each_ref().map(load),
+            vendor_violations: self.vendor_violations.each_ref().map(load),
+            department_counts: self.department_counts.each_ref().map(load),
+            purpose_counts: self.purpose_counts.each_ref().map(load),
+            purpose_violations: self.purpose_violations.each_ref().map(load),
+            region_counts: self.region_counts.each_ref().map(load),
+            region_violations: self.region_violations.each_ref().map(load),
+            ai_act_tier_counts: self.ai_act_tier_counts.each_ref().map(load),
+            phi_events: load(&self.phi_events),
+            waived_events: load(&self.waived_events),
+            violating_events: load(&self.violating_events),
+            shadow_ai_events: load(&self.shadow_ai_events),
+            shadow_ai_department_counts: self.shadow_ai_department_counts.each_ref().map(load),
+            shadow_ai_sensitivity: load(&self.shadow_ai_sensitivity),
+            lawful_basis_counts: self.lawful_basis_counts.each_ref().map(load),
+            lawful_basis_personal: self.lawful_basis_personal.each_ref().map(load),
+            pii_type_counts: self.pii_type_counts.each_ref().map(load),
+            total_data_sensitivity: load(&self.total_data_sensitivity),
+            data_sensitivity_samples: load(&self.data_sensitivity_samples),
+            violation_counts: self.violation_counts.iter().map(load).collect(),
+            high_risk_violations: self.high_risk_violations.iter().map(load).collect(),
+            waived_counts: self.waived_counts.iter().map(load).collect(),
+            risk_factor_counts: self.risk_factor_counts.iter().map(load).collect(),
+            users: self.users.as_ref().map(|users| users.lock().unwrap_or_else(|e| e.into_inner()).clone()).unwrap_or_default(),
+            ..ComplianceMetrics::default()
+        }
+    }
+}
+
+impl MetricsShard {
+    /// Creates a zeroed shard sized for the policy's compliance flags and risk factors.
+    fn new(policy: &Policy) -> Self {
+        MetricsShard {
+            totals: ShardCounters::new(policy, false),
+            departments: DEPARTMENT_NAMES.iter().map(|_| ShardCounters::new(policy, true)).collect(),
+            services: SERVICE_NAMES.iter().map(|_| ShardCounters::new(policy, false)).collect(),
+            models: MODEL_NAMES.iter().map(|_| ShardCounters::new(policy, false)).collect(),
+            batch_latency: Default::default(),
+            record_queue_depth: AtomicU64::new(0),
+            audit_queue_depth: AtomicU64::new(0),
+            world_entities: AtomicU64::new(0),
+            world_archetypes: AtomicU64::new(0),
+            alive: AtomicBool::new(false),
+            restarts: AtomicU64::new(0),
+        }
+    }
+
+    /// Adds a batch's metrics to the shard. Must only be called by the shard's owning worker.
+    pub fn add(&self, batch: &ComplianceMetrics) {
+        self.totals.add(batch);
+        for (counters, department) in self.departments.iter().zip(&batch.departments) {
+            counters.add(department);
+        }
+        for (counters, service) in self.services.iter().zip(&batch.services) {
+            counters.add(service);
+        }
+        for (counters, model) in self.models.iter().zip(&batch.models) {
+            counters.add(model);
+        }
+    }
+
+    /// Records how long a batch took to process. Must only be called by the shard's owning worker.
+    pub fn record_batch_latency(&self, latency: Duration) {
+        self.batch_latency.record(latency);
+    }
+
+    /// Stores the depths of the event recorder and audit log channels the worker last saw.
+    pub fn set_sink_queue_depths(&self, record: usize, audit: usize) {
+        self.record_queue_depth.store(record as u64, Ordering::Relaxed);
+        self.audit_queue_depth.store(audit as u64, Ordering::Relaxed);
+    }
+
+    /// Stores the number of entities and archetypes in the worker's ECS world.
+    pub fn set_world_size(&self, entities: usize, archetypes: usize) {
+        self.world_entities.store(entities as u64, Ordering::Relaxed);
+        self.world_archetypes.store(archetypes as u64, Ordering::Relaxed);
+    }
+
+    /// Marks the worker's thread as running or exited.
+    pub fn set_alive(&self, alive: bool) {
+        self.alive.store(alive, Ordering::Relaxed);
+    }
+
+    /// Counts a restart of the worker after a panic.
+    pub fn record_restart(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Reads the shard's cumulative counters.
+    fn load(&self) -> ComplianceMetrics {
+        let mut metrics = self.totals.load();
+        metrics.departments = self.departments.iter().map(ShardCounters::load).collect();
+        for department in &metrics.departments {
+            merge_users(&mut metrics.users, &department.users);
+        }
+        metrics.services = self.services.iter().map(ShardCounters::load).collect();
+        metrics.models = self.models.iter().map(ShardCounters::load).collect();
+        metrics
+    }
+}
+
+/// Whether a local worker is running and how often it was restarted after a panic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkerStatus {
+    pub alive: bool,
+    pub restarts: u64,
+}
+
+/// Per-worker metric shards, read and summed by the aggregator each interval.
+pub struct SharedMetrics {
+    shards: Vec<Arc<MetricsShard>>,
+    /// Cumulative counters as of the last [`SharedMetrics::collect_new`] call.
+    collected: Mutex<ComplianceMetrics>,
+}
+
+impl SharedMetrics {
+    /// Creates one zeroed shard per worker.
+    pub fn new(workers: usize, policy: &Policy) -> Self {
+        SharedMetrics {
+            shards: (0..workers).map(|_| Arc::new(MetricsShard::new(policy))).collect(),
+            collected: Mutex::new(ComplianceMetrics::default()),
+        }
+    }
+
+    /// Returns the shard owned by the given worker.
+    pub fn shard(&self, worker: usize) -> Arc<MetricsShard> {
+        self.shards[worker].clone()
+    }
+
+    /// Returns the cumulative number of events counted by each worker.
+    pub fn worker_events(&self) -> Vec<u64> {
+        self.shards.iter().map(|shard| shard.totals.total_events.load(Ordering::Relaxed)).collect()
+    }
+
+    /// Returns the batch latencies of each worker since the last call.
+    pub fn take_worker_latencies(&self) -> Vec<Histogram<u64>> {
+        self.shards.iter().map(|shard| shard.batch_latency.take()).collect()
+    }
+
+    /// Returns the liveness and restart count of each worker.
+    pub fn worker_status(&self) -> Vec<WorkerStatus> {
+        self.shards
+            .iter()
+            .map(|shard| WorkerStatus {
+                alive: shard.alive.load(Ordering::Relaxed),
+                restarts: shard.restarts.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Returns the deepest event recorder and audit log channel depths seen by any worker.
+    pub fn sink_queue_depths(&self) -> (usize, usize) {
+        self.shards.iter().fold((0, 0), |(record, audit), shard| {
+            (
+                record.max(shard.record_queue_depth.load(Ordering::Relaxed) as usize),
+                audit.max(shard.audit_queue_depth.load(Ordering::Relaxed) as usize),
+            )
+        })
+    }
+
+    /// Returns the entities and archetypes of all workers' ECS worlds, summed.
+    pub fn world_sizes(&self) -> (u64, u64) {
+        self.shards.iter().fold((0, 0), |(entities, archetypes), shard| {
+            (
+                entities + shard.world_entities.load(Ordering::Relaxed),
+                archetypes + shard.world_archetypes.load(Ordering::Relaxed),
+            )
+        })
+    }
+
+    /// Sums the cumulative counters of all shards.
+    pub fn load(&self) -> ComplianceMetrics {
+        let mut total = ComplianceMetrics::default();
+        for shard in &self.shards {
+            total.merge(&shard.load());
+        }
+        total
+    }
+
+    /// Returns the counts added by all workers since the previous call.
+    pub fn collect_new(&self) -> ComplianceMetrics {
+        let current = self.load();
+        let mut collected = self.collected.lock().unwrap_or_else(|e| e.into_inner());
+        let delta = current.delta_since(&collected);
+        *collected = current;
+        delta
+    }
+}
+
+/// The cumulative metrics as of the latest reporting interval.
+#[derive(Clone, Default)]
+pub struct LatestMetrics {
+    pub metrics: CumulativeMetrics,
+    /// Unix time in milliseconds at which the aggregator last updated the metrics.
+    pub updated_at_ms: u64,
+}
+
+/// Latest metrics shared between the aggregator and the servers answering queries about them.
+pub type SharedLatestMetrics = Arc<Mutex<LatestMetrics>>;
+
+/// Adds the counts of the users in `other` to `users`, then drops all but the
+/// [`MAX_TRACKED_USERS`] highest-ranked users.
+fn merge_users(users: &mut BTreeMap<u32, UserCounts>, other: &BTreeMap<u32, UserCounts>) {
+    for (user, counts) in other {
+        users.entry(*user).or_default().add(counts);
+    }
+    if users.len() <= MAX_TRACKED_USERS {
+        return;
+    }
+    let mut ranks: Vec<_> = users.iter().map(|(&user, counts)| user_rank(user, counts)).collect();
+    let (_, &mut lowest_kept, _) = ranks.select_nth_unstable(MAX_TRACKED_USERS - 1);
+    users.retain(|&user, counts| user_rank(user, counts) <= lowest_kept);
+}
+
+/// Returns the sort key ranking a user by [`ComplianceMetrics::top_users`]: highest total risk
+/// score first, then most violations, then lowest id.
+fn user_rank(user: u32, counts: &UserCounts) -> (std::cmp::Reverse<(u64, u64)>, u32) {
+    (std::cmp::Reverse((counts.total_risk_score, counts.violations)), user)
+}
+
+/// Merges the per-department, per-service, or per-model metrics in `other` into `entries`,
+/// growing `entries` if `other` has more.
+fn merge_breakdown(entries: &mut Vec<ComplianceMetrics>, other: &[ComplianceMetrics]) {
+    if entries.len() < other.len() {
+        entries.resize_with(other.len(), ComplianceMetrics::default);
+    }
+    for (entry, other) in entries.iter_mut().zip(other) {
+        entry.merge(other);
+    }
+}
+
+/// Returns the per-department, per-service, or per-model counts accumulated since `earlier`.
+fn delta_breakdown(entries: &[ComplianceMetrics], earlier: &[ComplianceMetrics]) -> Vec<ComplianceMetrics> {
+    let empty = ComplianceMetrics::default();
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| entry.delta_since(earlier.get(index).unwrap_or(&empty)))
+        .collect()
+}
+
+/// Adds `other` into `counts` element-wise, growing `counts` if `other` tracks more entries.
+fn merge_counts(counts: &mut Vec<u64>, other: &[u64]) {
+    if counts.len() < other.len() {
+        counts.resize(other.len(), 0);
+    }
+    add_counts(counts, other);
+}
+
+/// Adds `other` into `counts` element-wise, saturating on overflow.
+fn add_counts(counts: &mut [u64], other: &[u64]) {
+    for (count, &other_count) in counts.iter_mut().zip(other) {
+        *count = count.saturating_add(other_count);
+    }
+}
+
+/// Subtracts `other` from `counts` element-wise, saturating at zero.
+fn sub_counts(counts: &mut [u64], other: &[u64]) {
+    for (count, &other_count) in counts.iter_mut().zip(other) {
+        *count = count.saturating_sub(other_count);
+    }
+}
+
+/// Adds `value` to a single-writer counter, saturating on overflow.
+fn add_atomic(counter: &AtomicU64, value: u64) {
+    counter.store(counter.load(Ordering::Relaxed).saturating_add(value), Ordering::Relaxed);
+}
+
+/// Adds `values` into single-writer counters element-wise.
+fn add_atomic_counts(counters: &[AtomicU64], values: &[u64]) {
+    for (counter, &value) in counters.iter().zip(values) {
+        add_atomic(counter, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics_with_events(total_events: u64) -> ComplianceMetrics {
+        ComplianceMetrics {
+            total_events,
+            violation_counts: vec![total_events, 0, 0],
+            violating_events: total_events,
+            high_risk_count: total_events,
+            service_counts: [total_events, 0, 0, 0, 0],
+            risk_factor_counts: vec![total_events],
+            ..ComplianceMetrics::default()
+        }
+    }
+
+    #[test]
+    fn merge_adds_counters() {
+        let mut metrics = metrics_with_events(3);
+        metrics.merge(&metrics_with_events(4));
+        assert_eq!(metrics.total_events, 7);
+        assert_eq!(metrics.violation_counts, vec![7, 0, 0]);
+        assert_eq!(metrics.violating_events, 7);
+        assert_eq!(metrics.service_counts[0], 7);
+        assert_eq!(metrics.risk_factor_counts, vec![7]);
+    }
+
+    #[test]
+    fn merge_saturates_instead_of_wrapping() {
+        let mut metrics = metrics_with_events(u64::MAX - 1);
+        metrics.merge(&metrics_with_events(10));
+        assert_eq!(metrics.total_events, u64::MAX);
+        assert_eq!(metrics.violation_counts[0], u64::MAX);
+        assert_eq!(metrics.high_risk_count, u64::MAX);
+        assert_eq!(metrics.service_counts[0], u64::MAX);
+        assert_eq!(metrics.risk_factor_counts[0], u64::MAX);
+    }
+
+    #[test]
+    fn merge_grows_registry_indexed_counters() {
+        let mut metrics = ComplianceMetrics::default();
+        metrics.merge(&metrics_with_events(2));
+        assert_eq!(metrics.violation_counts, vec![2, 0, 0]);
+        assert_eq!(metrics.risk_factor_counts, vec![2]);
+    }
+
+    #[test]
+    fn total_violations_saturates() {
+        let metrics = ComplianceMetrics {
+            violation_counts: vec![u64::MAX, 1],
+            ..ComplianceMetrics::default()
+        };
+        assert_eq!(metrics.total_violations(), u64::MAX);
+    }
+
+    #[test]
+    fn compliance_percentage_stays_in_range() {
+        assert_eq!(ComplianceMetrics::default().compliance_percentage(), 100.0);
+
+        let mut metrics = metrics_with_events(10);
+        assert_eq!(metrics.check_compliance_percentage(ComplianceFlag(0)), 0.0);
+        assert_eq!(metrics.check_compliance_percentage(ComplianceFlag(1)), 100.0);
+        // Checks the metrics have not counted yet have no violations.
+        assert_eq!(metrics.check_compliance_percentage(ComplianceFlag(5)), 100.0);
+        metrics.violation_counts = vec![10, 10, 10];
+        assert_eq!(metrics.compliance_percentage(), 0.0);
+
+        // Saturated violation counts can exceed the number of checks.
+        metrics.violation_counts = vec![u64::MAX, u64::MAX, u64::MAX];
+        assert_eq!(metrics.compliance_percentage(), 0.0);
+        assert_eq!(metrics.check_compliance_percentage(ComplianceFlag(2)), 0.0);
+    }
+
+    #[test]
+    fn counters_near_limit_is_detected() {
+        assert!(!metrics_with_events(1_000_000).counters_near_limit());
+        assert!(metrics_with_events(COUNTER_WARNING_THRESHOLD).counters_near_limit());
+
+        // About a month of 100,000 events per second is far from the limit.
+        assert!(!metrics_with_events(100_000 * 86_400 * 30).counters_near_limit());
+        let mut metrics = ComplianceMetrics {
+            total_data_sensitivity: u64::MAX,
+            ..ComplianceMetrics::default()
+        };
+        assert!(!metrics.counters_near_limit());
+        metrics.purpose_violations[2] = COUNTER_WARNING_THRESHOLD;
+        assert!(metrics.counters_near_limit());
+    }
+
+    #[test]
+    fn old_history_is_merged_rather_than_dropped() {
+        let mut metrics = CumulativeMetrics::resume(metrics_with_events(1));
+        for _ in 0..DEFAULT_HISTORY_POINTS + 1 {
+            metrics.record(&IntervalMetrics::new(metrics_with_events(100), Duration::from_secs(1), 0));
+        }
+        assert_eq!(metrics.history.len(), DEFAULT_HISTORY_POINTS / 4 + DEFAULT_HISTORY_POINTS / 2 + 1);
+        assert_eq!(metrics.history[0].span, Duration::from_secs(2));
+        assert_eq!(metrics.history[0].events, 200);
+        assert_eq!(metrics.history[0].violations, vec![200, 0, 0]);
+        assert_eq!(metrics.history[0].rate(), 100.0);
+        assert_eq!(metrics.history[0].high_risk, 200);
+        assert_eq!(metrics.history[0].high_risk_percentage(), 100.0);
+        // Every event failed one of the three checks.
+        assert!((metrics.history[0].violation_percentage() - 100.0 / 3.0).abs() < 1e-9);
+        let latest = metrics.history.last().unwrap();
+        assert_eq!(latest.at, Duration::from_secs(DEFAULT_HISTORY_POINTS as u64 + 1));
+        assert_eq!(metrics.processing_rate(), 100.0);
+        assert_eq!(metrics.total_events, 100 * (DEFAULT_HISTORY_POINTS as u64 + 1) + 1);
+    }
+
+    #[test]
+    fn history_is_limited_to_the_configured_points() {
+        let mut metrics = CumulativeMetrics::default();
+        for _ in 0..100 {
+            metrics.record(&IntervalMetrics::new(metrics_with_events(10), Duration::from_secs(1), 0));
+        }
+        assert_eq!(metrics.history.len(), 100);
+
+        // A restored history longer than the limit is merged down straight away.
+        let mut metrics = metrics.with_history_points(10);
+        assert!(metrics.history.len() <= 10);
+        assert_eq!(metrics.history.iter().map(|point| point.events).sum::<u64>(), 1000);
+        for _ in 0..50 {
+            metrics.record(&IntervalMetrics::new(metrics_with_events(10), Duration::from_secs(1), 0));
+            assert!(metrics.history.len() <= 10);
+            assert!(metrics.departments.iter().all(|department| department.history.len() <= 10));
+        }
+        let latest = metrics.history.last().unwrap();
+        assert_eq!(latest.at, Duration::from_secs(150));
+        assert_eq!(latest.span, Duration::from_secs(1));
+        assert_eq!(metrics.history.iter().map(|point| point.events).sum::<u64>(), 1500);
+    }
+
+    #[test]
+    fn history_windows_are_downsampled() {
+        let mut metrics = CumulativeMetrics::default();
+        for second in 1..=120 {
+            metrics.record(&IntervalMetrics::new(metrics_with_events(second), Duration::from_secs(1), second * 1_000));
+        }
+        let minute = metrics.history_window(Some(Duration::from_secs(60)), 30);
+        assert_eq!(minute.len(), 30);
+        assert_eq!(minute[0].span, Duration::from_secs(2));
+        assert_eq!(minute[0].rate(), 61.5);
+        assert_eq!(minute[0].unix_ms, 62_000);
+        assert_eq!(metrics.history_window(None, 1000).len(), 120);
+        let zoomed = metrics.history_between(Duration::from_secs(30), Duration::from_secs(40), 5);
+        assert_eq!(zoomed.iter().map(|point| point.unix_ms).collect::<Vec<_>>(), [32_000, 34_000, 36_000, 38_000, 40_000]);
+        assert!(metrics.history_between(Duration::from_secs(40), Duration::from_secs(30), 5).is_empty());
+        // Seconds 61 to 120 each violated the first check once per event.
+        assert_eq!(metrics.violations_in_window(Some(Duration::from_secs(60)))[0], (61..=120).sum::<u64>());
+        assert_eq!(metrics.events_in_window(Some(Duration::from_secs(60))), (61..=120).sum::<u64>());
+        assert_eq!(metrics.violations_in_window(None)[0], (1..=120).sum::<u64>());
+    }
+
+    #[test]
+    fn merged_intervals_sum_their_counts_and_spans() {
+        let mut interval = IntervalMetrics::new(metrics_with_events(10), Duration::from_secs(1), 1_000);
+        interval.merge(&IntervalMetrics::new(metrics_with_events(50), Duration::from_secs(4), 5_000));
+        assert_eq!((interval.counts.total_events, interval.span, interval.unix_ms), (60, Duration::from_secs(5), 5_000));
+        // Not the mean of 10 and 12.5 events per second.
+        assert_eq!(interval.rate(), 12.0);
+
+        let mut metrics = CumulativeMetrics::default();
+        metrics.record(&interval);
+        metrics.add_late(&metrics_with_events(5));
+        assert_eq!(metrics.total_events, 65);
+        assert_eq!(metrics.history.len(), 1);
+        assert_eq!(metrics.processing_rate(), 12.0);
+    }
+
+    #[test]
+    fn delta_since_returns_interval_counts() {
+        let earlier = metrics_with_events(3);
+        let mut later = metrics_with_events(3);
+        later.merge(&metrics_with_events(4));
+        let delta = later.delta_since(&earlier);
+        assert_eq!(delta.total_events, 4);
+        assert_eq!(delta.violation_counts, vec![4, 0, 0]);
+        assert_eq!(delta.violating_events, 4);
+        assert_eq!(delta.service_counts[0], 4);
+        assert_eq!(delta.risk_factor_counts, vec![4]);
+    }
+
+    #[test]
+    fn users_are_merged_ranked_and_diffed() {
+        let user = |events, total_risk_score| UserCounts { events, total_risk_score, ..UserCounts::default() };
+        let mut earlier = ComplianceMetrics::default();
+        earlier.users.extend([(1, user(2, 100)), (2, user(1, 90))]);
+        let mut later = earlier.clone();
+        later.merge(&ComplianceMetrics {
+            users: [(2, user(1, 20)), (3, user(1, 80))].into(),
+            ..ComplianceMetrics::default()
+        });
+        let ranked: Vec<u32> = later.top_users(2).into_iter().map(|(user, _)| user).collect();
+        assert_eq!(ranked, vec![2, 1]);
+        assert_eq!(later.users[&2].average_risk(), 55.0);
+        let delta = later.delta_since(&earlier);
+        assert_eq!(delta.users, [(2, user(1, 20)), (3, user(1, 80))].into());
+    }
+
+    #[test]
+    fn tracked_users_are_capped_to_the_highest_ranked() {
+        let users = |first: u32| ComplianceMetrics {
+            users: (first..first + MAX_TRACKED_USERS as u32)
+                .map(|user| (user, UserCounts { events: 1, total_risk_score: user as u64, ..UserCounts::default() }))
+                .collect(),
+            ..ComplianceMetrics::default()
+        };
+        let mut metrics = users(0);
+        metrics.merge(&users(10));
+        // Users 0 to 9 were seen once with the lowest scores; the scores of users 10 to 999 doubled.
+        assert_eq!(metrics.users.len(), MAX_TRACKED_USERS);
+        assert_eq!(metrics.users.keys().next(), Some(&10));
+        assert_eq!(metrics.top_users(1)[0].0, 999);
+
+        // Shards count users per department only, and keep as many as the totals.
+        let policy = Policy::builtin();
+        let shared = SharedMetrics::new(1, &policy);
+        for first in [0, 2 * MAX_TRACKED_USERS as u32] {
+            let mut batch = ComplianceMetrics {
+                departments: vec![ComplianceMetrics::default(), users(first)],
+                ..ComplianceMetrics::default()
+            };
+            batch.add_department_totals();
+            shared.shard(0).add(&batch);
+        }
+        let total = shared.load();
+        assert_eq!(total.users.len(), MAX_TRACKED_USERS);
+        assert_eq!(total.users.keys().next(), Some(&(2 * MAX_TRACKED_USERS as u32)));
+        assert_eq!(total.departments[1].users, total.users);
+        assert!(total.services.iter().chain(&total.models).all(|metrics| metrics.users.is_empty()));
+    }
+
+    #[test]
+    fn shared_metrics_sum_all_shards() {
+        let policy = Policy::builtin();
+        let shared = SharedMetrics::new(2, &policy);
+        shared.shard(0).add(&metrics_with_events(3));
+        shared.shard(1).add(&metrics_with_events(4));
+        shared.shard(1).add(&metrics_with_events(5));
+        let total = shared.load();
+        assert_eq!(total.total_events, 12);
+        assert_eq!(total.violation_counts, vec![12, 0, 0, 0, 0]);
+        assert_eq!(total.risk_factor_counts, vec![12, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn departments_are_tracked_alongside_the_totals() {
+        let policy = Policy::builtin();
+        let shared = SharedMetrics::new(1, &policy);
+        let mut batch = ComplianceMetrics {
+            departments: vec![metrics_with_events(0), metrics_with_events(2)],
+            ..ComplianceMetrics::default()
+        };
+        batch.add_department_totals();
+        assert_eq!(batch.total_events, 2);
+        shared.shard(0).add(&batch);
+        let total = shared.load();
+        assert_eq!(total.total_events, 2);
+        assert_eq!(total.departments.len(), DEPARTMENT_NAMES.len());
+        assert_eq!(total.departments[1].total_events, 2);
+        assert_eq!(total.departments[1].violation_counts, vec![2, 0, 0, 0, 0]);
+
+        let mut history = CumulativeMetrics::default();
+        history.record(&IntervalMetrics::new(total, Duration::from_secs(1), 0));
+        assert_eq!(history.departments[1].processing_rate(), 2.0);
+        assert_eq!(history.departments[0].history.len(), 1);
+    }
+
+    #[test]
+    fn collect_new_returns_only_uncollected_counts() {
+        let policy = Policy::builtin();
+        let shared = SharedMetrics::new(1, &policy);
+        shared.shard(0).add(&metrics_with_events(3));
+        assert_eq!(shared.collect_new().total_events, 3);
+        shared.shard(0).add(&metrics_with_events(4));
+        assert_eq!(shared.collect_new().total_events, 4);
+        assert_eq!(shared.collect_new().total_events, 0);
+    }
+
+    #[test]
+    fn zero_elapsed_interval_does_not_produce_infinite_rate() {
+        let mut metrics = CumulativeMetrics::default();
+        metrics.record(&IntervalMetrics::new(metrics_with_events(100), Duration::ZERO, 0));
+        assert!(metrics.processing_rate().is_finite());
     }
 }
@@ -0,0 +1,153 @@
+use crate::compliance::ComplianceRegistry;
+
+/// The four core functions of the NIST AI Risk Management Framework.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NistFunction {
+    Govern,
+    Map,
+    Measure,
+    Manage,
+}
+
+/// All functions, in the order the framework lists them.
+pub const NIST_FUNCTIONS: [NistFunction; 4] = [NistFunction::Govern, NistFunction::Map, NistFunction::Measure, NistFunction::Manage];
+
+impl NistFunction {
+    /// Returns the display name of the function.
+    pub fn name(self) -> &'static str {
+        match self {
+            NistFunction::Govern => "Govern",
+            NistFunction::Map => "Map",
+            NistFunction::Measure => "Measure",
+            NistFunction::Manage => "Manage",
+        }
+    }
+
+    /// Returns the categories of the function.
+    pub fn categories(self) -> impl Iterator<Item = &'static NistCategory> {
+        NIST_CATEGORIES.iter().filter(move |category| category.function == self)
+    }
+}
+
+/// A category of the AI RMF core, such as `GOVERN 1`.
+#[derive(Clone, Copy, Debug)]
+pub struct NistCategory {
+    /// Identifier used in audit logs and exports.
+    pub id: &'static str,
+    pub function: NistFunction,
+    /// Short summary of the category's outcomes.
+    pub title: &'static str,
+}
+
+/// All categories of the AI RMF core.
+pub const NIST_CATEGORIES: [NistCategory; 19] = [
+    NistCategory { id: "GOVERN 1", function: NistFunction::Govern, title: "Policies, processes, and procedures" },
+    NistCategory { id: "GOVERN 2", function: NistFunction::Govern, title: "Accountability structures" },
+    NistCategory { id: "GOVERN 3", function: NistFunction::Govern, title: "Workforce diversity and inclusion" },
+    NistCategory { id: "GOVERN 4", function: NistFunction::Govern, title: "Risk culture" },
+    NistCategory { id: "GOVERN 5", function: NistFunction::Govern, title: "Stakeholder engagement" },
+    NistCategory { id: "GOVERN 6", function: NistFunction::Govern, title: "Third-party risks" },
+    NistCategory { id: "MAP 1", function: NistFunction::Map, title: "Context is established" },
+    NistCategory { id: "MAP 2", function: NistFunction::Map, title: "System is categorized" },
+    NistCategory { id: "MAP 3", function: NistFunction::Map, title: "Capabilities and targeted usage" },
+    NistCategory { id: "MAP 4", function: NistFunction::Map, title: "Risks of all components" },
+    NistCategory { id: "MAP 5", function: NistFunction::Map, title: "Impacts are characterized" },
+    NistCategory { id: "MEASURE 1", function: NistFunction::Measure, title: "Methods and metrics" },
+    NistCategory { id: "MEASURE 2", function: NistFunction::Measure, title: "Trustworthy characteristics" },
+    NistCategory { id: "MEASURE 3", function: NistFunction::Measure, title: "Risks are tracked over time" },
+    NistCategory { id: "MEASURE 4", function: NistFunction::Measure, title: "Measurement efficacy feedback" },
+    NistCategory { id: "MANAGE 1", function: NistFunction::Manage, title: "Risks are prioritized and responded to" },
+    NistCategory { id: "MANAGE 2", function: NistFunction::Manage, title: "Benefits are maximized, impacts minimized" },
+    NistCategory { id: "MANAGE 3", function: NistFunction::Manage, title: "Third-party risks are managed" },
+    NistCategory { id: "MANAGE 4", function: NistFunction::Manage, title: "Risk treatments are monitored" },
+];
+
+/// Categories addressed by each known compliance check, keyed by compliance flag key.
+const CHECK_CATEGORIES: [(&str, &[&str]); 6] = [
+    ("eu_ai_act", &["GOVERN 1", "MAP 2", "MAP 5", "MANAGE 1"]),
+    ("gdpr", &["GOVERN 1", "MEASURE 2"]),
+    ("internal_policy", &["GOVERN 1", "MANAGE 2"]),
+    ("purpose_limitation", &["MAP 1", "MAP 3"]),
+    ("data_residency", &["GOVERN 6", "MAP 4", "MANAGE 3"]),
+    ("hipaa", &["GOVERN 1", "GOVERN 6", "MEASURE 2", "MANAGE 3"]),
+];
+
+/// Returns the ids of the categories addressed by the compliance check with the given key.
+///
+/// Checks without a mapping, such as custom rule packs, address no categories.
+pub fn categories_for(key: &str) -> &'static [&'static str] {
+    CHECK_CATEGORIES
+        .iter()
+        .find(|(check, _)| *check == key)
+        .map_or(&[], |(_, categories)| *categories)
+}
+
+/// Returns the distinct category ids addressed by the failed checks, in framework order.
+pub fn violated_categories(violated_keys: &[&str]) -> Vec<&'static str> {
+    NIST_CATEGORIES
+        .iter()
+        .map(|category| category.id)
+        .filter(|id| violated_keys.iter().any(|key| categories_for(key).contains(id)))
+        .collect()
+}
+
+/// How well the enabled compliance checks cover a single AI RMF function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    pub function: NistFunction,
+    /// Ids of the function's categories addressed by at least one enabled check.
+    pub covered: Vec<&'static str>,
+    /// Number of categories in the function.
+    pub total: usize,
+    /// Violations of the checks mapped to the function, saturating on overflow.
+    ///
+    /// An event failing several checks mapped to the same function counts once per check.
+    pub violations: u64,
+}
+
+/// Summarizes the coverage of each AI RMF function by the enabled compliance checks.
+///
+/// # Arguments
+///
+/// * `compliance` - The compliance flags claimed by the enabled rule packs.
+/// * `violation_counts` - Violation counts indexed by compliance flag.
+pub fn coverage(compliance: &ComplianceRegistry, violation_counts: &[u64]) -> [FunctionCoverage; 4] {
+    NIST_FUNCTIONS.map(|function| {
+        let mut coverage = FunctionCoverage { function, covered: Vec::new(), total: 0, violations: 0 };
+        for category in function.categories() {
+            coverage.total += 1;
+            if compliance.iter().any(|(_, info)| categories_for(info.key).contains(&category.id)) {
+                coverage.covered.push(category.id);
+            }
+        }
+        for (flag, info) in compliance.iter() {
+            let mapped = categories_for(info.key)
+                .iter()
+                .any(|id| function.categories().any(|category| category.id == *id));
+            if mapped {
+                let count = violation_counts.get(flag.0 as usize).copied().unwrap_or(0);
+                coverage.violations = coverage.violations.saturating_add(count);
+            }
+        }
+        coverage
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn violations_are_mapped_to_categories_and_functions() {
+        assert_eq!(violated_categories(&["purpose_limitation", "gdpr"]), ["GOVERN 1", "MAP 1", "MAP 3", "MEASURE 2"]);
+        assert!(violated_categories(&["custom"]).is_empty());
+
+        let compliance = ComplianceRegistry::builtin();
+        let [govern, map, measure, manage] = coverage(&compliance, &[1, 2, 4, 8, 16]);
+        assert_eq!(govern.covered, ["GOVERN 1", "GOVERN 6"]);
+        assert_eq!((govern.total, govern.violations), (6, 1 + 2 + 4 + 16));
+        assert_eq!((map.covered.len(), map.violations), (5, 1 + 8 + 16));
+        assert_eq!((measure.covered, measure.violations), (vec!["MEASURE 2"], 2));
+        assert_eq!((manage.covered.len(), manage.violations), (3, 1 + 4 + 16));
+    }
+}
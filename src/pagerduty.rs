@@ -0,0 +1,380 @@
+use crate::error::{report, with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use crate::metrics::ComplianceMetrics;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Sink name used in error reports when sending events to PagerDuty.
+pub const PAGERDUTY_SINK: &str = "PagerDuty sender";
+
+/// Events API v2 endpoint used unless the configuration names another, such as the EU one.
+const DEFAULT_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Source reported with incidents unless the configuration names one.
+const DEFAULT_SOURCE: &str = "ecs_ai_compliance";
+
+/// How often the sender checks the stop signal while waiting for events.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Timeout of a single Events API request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// PagerDuty severity of the incidents a rule opens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Critical,
+    Error,
+    Warning,
+    Info,
+}
+
+/// A severe condition that opens a PagerDuty incident while it holds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlertCondition {
+    /// Compliance over a reporting interval fell below this percentage.
+    ComplianceFloor(f64),
+    /// No events were processed for this long.
+    IngestionStalled(Duration),
+}
+
+/// An alert rule and the severity of its incidents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AlertRule {
+    pub condition: AlertCondition,
+    pub severity: Severity,
+}
+
+/// Where PagerDuty events are sent and the rules that raise them.
+#[derive(Clone, Debug)]
+pub struct PagerDutyConfig {
+    /// Integration key of the Events API v2 integration of the PagerDuty service.
+    pub routing_key: String,
+    pub events_url: String,
+    /// Source of the incidents, also prefixed to their dedup keys.
+    pub source: String,
+    pub rules: Vec<AlertRule>,
+}
+
+/// Whether an event opens or resolves an incident.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventAction {
+    Trigger,
+    Resolve,
+}
+
+/// An event for the Events API, raised when a rule's condition starts or stops holding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PagerDutyEvent {
+    pub action: EventAction,
+    /// Identifies the incident, so that a resolve event closes the incident its trigger opened.
+    pub dedup_key: String,
+    pub summary: String,
+    pub severity: Severity,
+    /// Key of the rule that raised the event.
+    pub rule: &'static str,
+    /// Measurements behind the event, shown on the incident.
+    pub details: Value,
+    /// The measurement that tripped the rule, formatted for display.
+    pub value: String,
+}
+
+/// The PagerDuty configuration as written in a JSON file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PagerDutySpec {
+    routing_key: String,
+    events_url: Option<String>,
+    source: Option<String>,
+    rules: RulesSpec,
+}
+
+/// The alert rules as written in a JSON file; omitted rules are disabled.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct RulesSpec {
+    compliance_floor: Option<ComplianceFloorSpec>,
+    ingestion_stalled: Option<IngestionStalledSpec>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ComplianceFloorSpec {
+    floor: f64,
+    severity: Option<Severity>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct IngestionStalledSpec {
+    after_secs: u64,
+    severity: Option<Severity>,
+}
+
+impl AlertCondition {
+    /// Returns the key naming the rule in configuration files and dedup keys.
+    pub fn key(&self) -> &'static str {
+        match self {
+            AlertCondition::ComplianceFloor(_) => "compliance_floor",
+            AlertCondition::IngestionStalled(_) => "ingestion_stalled",
+        }
+    }
+}
+
+impl PagerDutyConfig {
+    /// Loads the routing key and alert rules from a JSON file.
+    ///
+    /// The file contains an object with the `routing_key` of the service's Events API v2
+    /// integration, optionally the `events_url` and the `source` reported with incidents, and
+    /// the `rules`: a `compliance_floor` with its `floor` percentage and an `ingestion_stalled`
+    /// rule with its `after_secs`, each with an optional `severity`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+        let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let spec: PagerDutySpec = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        if spec.routing_key.trim().is_empty() {
+            return Err(invalid("routing key must not be empty".to_string()));
+        }
+        let mut rules = Vec::new();
+        if let Some(rule) = spec.rules.compliance_floor {
+            if !(0.0..=100.0).contains(&rule.floor) {
+                return Err(invalid(format!("compliance floor must be between 0 and 100, got {}", rule.floor)));
+            }
+            rules.push(AlertRule {
+                condition: AlertCondition::ComplianceFloor(rule.floor),
+                severity: rule.severity.unwrap_or(Severity::Critical),
+            });
+        }
+        if let Some(rule) = spec.rules.ingestion_stalled {
+            if rule.after_secs == 0 {
+                return Err(invalid("ingestion stall must last at least one second".to_string()));
+            }
+            rules.push(AlertRule {
+                condition: AlertCondition::IngestionStalled(Duration::from_secs(rule.after_secs)),
+                severity: rule.severity.unwrap_or(Severity::Error),
+            });
+        }
+        if rules.is_empty() {
+            return Err(invalid("no alert rules configured".to_string()));
+        }
+        Ok(PagerDutyConfig {
+            routing_key: spec.routing_key,
+            events_url: spec.events_url.unwrap_or_else(|| DEFAULT_EVENTS_URL.to_string()),
+            source: spec.source.unwrap_or_else(|| DEFAULT_SOURCE.to_string()),
+            rules,
+        })
+    }
+}
+
+/// Tracks which rules have an open incident, raising events as their conditions change.
+pub struct IncidentTracker {
+    rules: Vec<AlertRule>,
+    source: String,
+    open: Vec<bool>,
+    /// Monitor time at which events were last processed.
+    last_events_at: Duration,
+}
+
+impl IncidentTracker {
+    /// Creates a tracker with no open incidents.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - The rules to evaluate.
+    /// * `source` - Source of the incidents, prefixed to their dedup keys.
+    /// * `now` - Current monitor time, from which a stall is measured until events arrive.
+    pub fn new(rules: Vec<AlertRule>, source: &str, now: Duration) -> Self {
+        IncidentTracker {
+            open: vec![false; rules.len()],
+            rules,
+            source: source.to_string(),
+            last_events_at: now,
+        }
+    }
+
+    /// Evaluates the rules at the end of a reporting interval.
+    ///
+    /// Returns a trigger event for each rule whose condition started holding and a resolve
+    /// event for each open incident whose condition cleared. An interval without events
+    /// leaves the compliance floor incident as it was.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The metrics counted during the interval.
+    /// * `now` - Current monitor time.
+    pub fn evaluate(&mut self, interval: &ComplianceMetrics, now: Duration) -> Vec<PagerDutyEvent> {
+        if interval.total_events > 0 {
+            self.last_events_at = now;
+        }
+        let mut events = Vec::new();
+        for (rule, open) in self.rules.iter().zip(self.open.iter_mut()) {
+            let (holds, summary, details, value) = match rule.condition {
+                AlertCondition::ComplianceFloor(_) if interval.total_events == 0 => continue,
+                AlertCondition::ComplianceFloor(floor) => {
+                    let compliance = interval.compliance_percentage();
+                    (
+                        compliance < floor,
+                        format!("AI compliance at {:.1}%, below the critical floor of {:.1}%", compliance, floor),
+                        json!({ "compliance_percentage": compliance, "floor": floor, "events": interval.total_events }),
+                        format!("{:.1}%", compliance),
+                    )
+                }
+                AlertCondition::IngestionStalled(after) => {
+                    let stalled = now.saturating_sub(self.last_events_at);
+                    (
+                        stalled >= after,
+                        format!("AI compliance monitor ingestion stalled: no events for {}s", stalled.as_secs()),
+                        json!({ "stalled_secs": stalled.as_secs(), "after_secs": after.as_secs() }),
+                        format!("{}s", stalled.as_secs()),
+                    )
+                }
+            };
+            if holds == *open {
+                continue;
+            }
+            *open = holds;
+            events.push(PagerDutyEvent {
+                action: if holds { EventAction::Trigger } else { EventAction::Resolve },
+                dedup_key: format!("{}/{}", self.source, rule.condition.key()),
+                summary,
+                severity: rule.severity,
+                rule: rule.condition.key(),
+                details,
+                value,
+            });
+        }
+        events
+    }
+}
+
+/// Builds the Events API v2 request body for an event.
+///
+/// # Arguments
+///
+/// * `event` - The event to send.
+/// * `config` - The routing key and source of the incidents.
+pub fn event_payload(event: &PagerDutyEvent, config: &PagerDutyConfig) -> Value {
+    let mut payload = json!({
+        "routing_key": config.routing_key,
+        "event_action": event.action,
+        "dedup_key": event.dedup_key,
+    });
+    if event.action == EventAction::Trigger {
+        payload["payload"] = json!({
+            "summary": event.summary,
+            "source": config.source,
+            "severity": event.severity,
+            "component": DEFAULT_SOURCE,
+            "class": event.rule,
+            "custom_details": event.details,
+        });
+    }
+    payload
+}
+
+/// Sends events to PagerDuty until the stop signal is set or every sender is dropped.
+///
+/// Events still waiting on shutdown are sent before returning. Failed requests are handled
+/// according to `policy`; if the sender gives up, it reports the error and exits, which
+/// disables PagerDuty incidents without affecting the monitor.
+///
+/// # Arguments
+///
+/// * `config` - Endpoint, routing key, and source of the incidents.
+/// * `receiver` - Channel receiving events from the monitor.
+/// * `stop` - Atomic flag indicating when to stop.
+/// * `policy` - How to handle failed requests.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn pagerduty_sender(
+    config: PagerDutyConfig,
+    receiver: Receiver<PagerDutyEvent>,
+    stop: Arc<AtomicBool>,
+    policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+) {
+    loop {
+        let stopping = stop.load(Ordering::Relaxed);
+        let events: Vec<PagerDutyEvent> = match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(event) => std::iter::once(event).chain(receiver.try_iter()).collect(),
+            Err(RecvTimeoutError::Timeout) => Vec::new(),
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        for event in events {
+            let body = event_payload(&event, &config).to_string();
+            if let Err(e) = with_retry(policy, PAGERDUTY_SINK, &errors, || post(&config.events_url, &body)) {
+                let action = if policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+                report(&errors, e, action);
+                return;
+            }
+        }
+        if stopping && receiver.is_empty() {
+            return;
+        }
+    }
+}
+
+/// Sends a JSON event to the Events API.
+fn post(events_url: &str, body: &str) -> io::Result<()> {
+    ureq::post(events_url)
+        .timeout(REQUEST_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+        .map(|_| ())
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => io::Error::other(format!("Events API returned HTTP {}", status)),
+            ureq::Error::Transport(transport) => io::Error::other(transport.to_string()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incidents_open_while_conditions_hold_and_resolve_when_they_clear() {
+        let config = PagerDutyConfig {
+            routing_key: "key".to_string(),
+            events_url: DEFAULT_EVENTS_URL.to_string(),
+            source: "monitor-1".to_string(),
+            rules: vec![
+                AlertRule { condition: AlertCondition::ComplianceFloor(90.0), severity: Severity::Critical },
+                AlertRule { condition: AlertCondition::IngestionStalled(Duration::from_secs(30)), severity: Severity::Error },
+            ],
+        };
+        let mut tracker = IncidentTracker::new(config.rules.clone(), &config.source, Duration::ZERO);
+        let interval = |events, violations| ComplianceMetrics {
+            total_events: events,
+            violation_counts: vec![violations, 0, 0, 0, 0],
+            ..ComplianceMetrics::default()
+        };
+        let at = Duration::from_secs;
+
+        // 60 failed checks out of 500 is 88% compliance.
+        let events = tracker.evaluate(&interval(100, 60), at(5));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, EventAction::Trigger);
+        assert_eq!(events[0].dedup_key, "monitor-1/compliance_floor");
+        let payload = event_payload(&events[0], &config);
+        assert_eq!(payload["routing_key"], "key");
+        assert_eq!(payload["payload"]["severity"], "critical");
+        assert_eq!(payload["payload"]["custom_details"]["floor"], 90.0);
+
+        assert!(tracker.evaluate(&interval(0, 0), at(30)).is_empty());
+        let events = tracker.evaluate(&interval(0, 0), at(35));
+        assert_eq!((events[0].rule, events[0].action), ("ingestion_stalled", EventAction::Trigger));
+
+        let events = tracker.evaluate(&interval(100, 0), at(40));
+        let actions: Vec<_> = events.iter().map(|event| (event.rule, event.action)).collect();
+        assert_eq!(actions, [("compliance_floor", EventAction::Resolve), ("ingestion_stalled", EventAction::Resolve)]);
+        let payload = event_payload(&events[0], &config);
+        assert_eq!(payload["event_action"], "resolve");
+        assert!(payload.get("payload").is_none());
+    }
+}
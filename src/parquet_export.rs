@@ -0,0 +1,262 @@
+use crate::audit::AuditRecord;
+use crate::clock::civil_from_days;
+use crate::error::{with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use crate::policy::Policy;
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, RecordBatch, StringArray, TimestampMillisecondArray, UInt32Array, UInt64Array,
+    UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use crossbeam_channel::Sender;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Sink name used in error reports from the Parquet exporter.
+pub const PARQUET_SINK: &str = "Parquet export";
+
+/// Rows buffered in memory before a row group is flushed to disk.
+const ROW_GROUP_ROWS: usize = 256 * 1024;
+
+/// Rows written to a single file before it is closed and a new one started.
+const MAX_FILE_ROWS: usize = 8 * 1024 * 1024;
+
+const MILLIS_PER_HOUR: u64 = 60 * 60 * 1000;
+
+/// An open Parquet file and the hourly partition it belongs to.
+struct PartitionFile {
+    hour: u64,
+    rows: usize,
+    writer: ArrowWriter<File>,
+}
+
+/// Streams one worker's evaluated events to hourly partitioned Parquet files.
+///
+/// Files are laid out as `<dir>/date=YYYY-MM-DD/hour=HH/worker-<n>-<start_ms>.parquet`, so the
+/// directory can be queried as a Hive-partitioned dataset. Each worker owns its own writer,
+/// avoiding any cross-thread coordination on the hot path.
+pub struct ParquetEventWriter {
+    dir: PathBuf,
+    worker: usize,
+    schema: SchemaRef,
+    policy: Arc<Policy>,
+    sink_policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+    /// Rows written to a file before rolling over; `MAX_FILE_ROWS` outside of tests.
+    max_file_rows: usize,
+    current: Option<PartitionFile>,
+}
+
+impl ParquetEventWriter {
+    /// Creates a writer for a single worker; files are created lazily on the first write.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Root directory of the partitioned dataset.
+    /// * `worker` - Index of the owning worker, used to keep file names unique.
+    /// * `policy` - The active policy; each compliance flag becomes a boolean column.
+    /// * `sink_policy` - How to handle file creation failures.
+    /// * `errors` - Channel sender for error reports to the supervisor.
+    pub fn new(
+        dir: &Path,
+        worker: usize,
+        policy: Arc<Policy>,
+        sink_policy: ErrorPolicy,
+        errors: Sender<ErrorReport>,
+    ) -> Self {
+        ParquetEventWriter {
+            dir: dir.to_path_buf(),
+            worker,
+            schema: event_schema(&policy),
+            policy,
+            sink_policy,
+            errors,
+            max_file_rows: MAX_FILE_ROWS,
+            current: None,
+        }
+    }
+
+    /// Returns the action to report when this writer gives up.
+    pub fn failure_action(&self) -> ErrorPolicy {
+        if self.sink_policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade }
+    }
+
+    /// Appends a batch of evaluated events, rolling over to a new file when needed.
+    pub fn write(&mut self, records: &[AuditRecord]) -> Result<()> {
+        let Some(first) = records.first() else {
+            return Ok(());
+        };
+        let hour = first.timestamp_ms / MILLIS_PER_HOUR;
+        if self.current.as_ref().is_some_and(|file| file.hour != hour || file.rows >= self.max_file_rows) {
+            self.close()?;
+        }
+        if self.current.is_none() {
+            self.current = Some(self.open(hour, first.timestamp_ms)?);
+        }
+        let batch = self.record_batch(records).map_err(sink_error)?;
+        let file = self.current.as_mut().expect("partition file was just opened");
+        file.writer.write(&batch).map_err(sink_error)?;
+        file.rows += records.len();
+        Ok(())
+    }
+
+    /// Flushes buffered rows and finalizes the current file, if any.
+    pub fn close(&mut self) -> Result<()> {
+        if let Some(file) = self.current.take() {
+            file.writer.close().map_err(sink_error)?;
+        }
+        Ok(())
+    }
+
+    /// Creates the file for the given hourly partition.
+    fn open(&self, hour: u64, start_ms: u64) -> Result<PartitionFile> {
+        let (year, month, day) = civil_from_days(hour / 24);
+        let partition = self
+            .dir
+            .join(format!("date={:04}-{:02}-{:02}", year, month, day))
+            .join(format!("hour={:02}", hour % 24));
+        let path = partition.join(format!("worker-{}-{}.parquet", self.worker, start_ms));
+        let file = with_retry(self.sink_policy, PARQUET_SINK, &self.errors, || {
+            fs::create_dir_all(&partition)?;
+            File::create(&path)
+        })?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(ROW_GROUP_ROWS)
+            .build();
+        let writer = ArrowWriter::try_new(file, self.schema.clone(), Some(properties)).map_err(sink_error)?;
+        Ok(PartitionFile { hour, rows: 0, writer })
+    }
+
+    /// Converts evaluated events into a columnar record batch.
+    fn record_batch(&self, records: &[AuditRecord]) -> std::result::Result<RecordBatch, arrow_schema::ArrowError> {
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.event_id))),
+            Arc::new(
+                TimestampMillisecondArray::from_iter_values(records.iter().map(|r| r.timestamp_ms as i64))
+                    .with_timezone("UTC"),
+            ),
+            Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.policy_version))),
+            Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.service))),
+            Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.model))),
+            Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.vendor))),
+            Arc::new(StringArray::from_iter_values(records.iter().map(|r| r.department))),
+            Arc::new(UInt32Array::from_iter(records.iter().map(|r| r.user_id))),
+            Arc::new(StringArray::from_iter(records.iter().map(|r| r.purpose))),
+            Arc::new(StringArray::from_iter(records.iter().map(|r| r.region))),
+            Arc::new(StringArray::from_iter(records.iter().map(|r| r.lawful_basis))),
+            Arc::new(StringArray::from_iter(records.iter().map(|r| (!r.pii_types.is_empty()).then(|| r.pii_types.join(";"))))),
+            Arc::new(StringArray::from_iter(records.iter().map(|r| r.ai_act_tier))),
+            Arc::new(UInt8Array::from_iter_values(records.iter().map(|r| r.data_sensitivity))),
+        ];
+        for (_, info) in self.policy.compliance.iter() {
+            let compliant = records.iter().map(|r| Some(!r.violations.contains(&info.key)));
+            columns.push(Arc::new(BooleanArray::from_iter(compliant)));
+        }
+        columns.push(Arc::new(UInt8Array::from_iter_values(records.iter().map(|r| r.risk_score))));
+        columns.push(Arc::new(UInt64Array::from_iter_values(records.iter().map(|r| r.risk_factors))));
+        columns.push(Arc::new(BinaryArray::from_iter_values(records.iter().map(|r| r.provenance.ids()))));
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+}
+
+/// Builds the Arrow schema for evaluated events, with one `<flag>_compliant` column per compliance flag.
+fn event_schema(policy: &Policy) -> SchemaRef {
+    let mut fields = vec![
+        Field::new("event_id", DataType::UInt64, false),
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+        Field::new("policy_version", DataType::Utf8, false),
+        Field::new("service", DataType::Utf8, false),
+        Field::new("model", DataType::Utf8, false),
+        Field::new("vendor", DataType::Utf8, false),
+        Field::new("department", DataType::Utf8, false),
+        Field::new("user_id", DataType::UInt32, true),
+        Field::new("purpose", DataType::Utf8, true),
+        Field::new("region", DataType::Utf8, true),
+        Field::new("lawful_basis", DataType::Utf8, true),
+        Field::new("pii_types", DataType::Utf8, true),
+        Field::new("ai_act_tier", DataType::Utf8, true),
+        Field::new("data_sensitivity", DataType::UInt8, false),
+    ];
+    for (_, info) in policy.compliance.iter() {
+        fields.push(Field::new(format!("{}_compliant", info.key), DataType::Boolean, false));
+    }
+    fields.push(Field::new("risk_score", DataType::UInt8, false));
+    fields.push(Field::new("risk_factors", DataType::UInt64, false));
+    // One byte per pipeline step id, in the order the steps ran.
+    fields.push(Field::new("provenance", DataType::Binary, false));
+    Arc::new(Schema::new(fields))
+}
+
+/// Wraps an Arrow or Parquet error as a sink failure.
+fn sink_error(e: impl std::error::Error + Send + Sync + 'static) -> MonitorError {
+    MonitorError::Sink {
+        sink: PARQUET_SINK,
+        source: io::Error::other(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::*;
+    use crossbeam_channel::unbounded;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    /// Returns the rows in each file under `dir`, keyed by the path relative to `dir`.
+    fn rows_per_file(dir: &Path) -> Vec<(String, i64)> {
+        let mut files = Vec::new();
+        for date in fs::read_dir(dir).unwrap() {
+            for hour in fs::read_dir(date.unwrap().path()).unwrap() {
+                for file in fs::read_dir(hour.unwrap().path()).unwrap() {
+                    let path = file.unwrap().path();
+                    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+                    let name = path.strip_prefix(dir).unwrap().to_string_lossy().into_owned();
+                    files.push((name, reader.metadata().file_metadata().num_rows()));
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn events_are_partitioned_by_hour_and_files_roll_over() {
+        let dir = std::env::temp_dir().join(format!("parquet-export-test-{}", std::process::id()));
+        let policy = Arc::new(Policy::builtin());
+        let service = AIService { name_idx: 0, vendor_idx: 0, model_idx: 0 };
+        let usage = Usage { department_idx: 0, data_sensitivity: 1 };
+        let status = ComplianceStatus { flags: policy.compliance.all_compliant(), waived: 0 };
+        let risk = RiskAssessment { score: 0, factor_flags: 0 };
+        let records = |start_ms: u64, count: u64| -> Vec<AuditRecord> {
+            (0..count)
+                .map(|i| AuditRecord::new(EventId(start_ms + i), &policy, start_ms + i, &service, &usage, &status, &risk))
+                .collect()
+        };
+        let (errors, _) = unbounded();
+        let mut writer = ParquetEventWriter::new(&dir, 3, policy.clone(), ErrorPolicy::Shutdown, errors);
+        writer.max_file_rows = 3;
+
+        // 2024-02-28T23:00:00Z, the last hour before a leap day.
+        let last_hour = 1_709_161_200_000;
+        writer.write(&records(last_hour, 2)).unwrap();
+        writer.write(&records(last_hour + 10, 2)).unwrap();
+        // The first file now holds 4 rows, past the limit of 3, so this batch starts a new one.
+        writer.write(&records(last_hour + 20, 1)).unwrap();
+        writer.write(&records(last_hour + MILLIS_PER_HOUR, 2)).unwrap();
+        writer.close().unwrap();
+
+        let files = rows_per_file(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let expected = [
+            (format!("date=2024-02-28/hour=23/worker-3-{}.parquet", last_hour), 4),
+            (format!("date=2024-02-28/hour=23/worker-3-{}.parquet", last_hour + 20), 1),
+            (format!("date=2024-02-29/hour=00/worker-3-{}.parquet", last_hour + MILLIS_PER_HOUR), 2),
+        ];
+        assert_eq!(files, expected);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::allowlist::{Allowlist, ApprovedUseSpec};
+use crate::compliance::{ComplianceFlag, ComplianceRegistry};
+use crate::components::Args;
+use crate::constants::{DEPARTMENT_NAMES, POLICY_VERSION, VENDOR_NAMES};
+use crate::content_scan::{ContentScanner, ScanRuleSpec};
+use crate::error::{MonitorError, Result};
+use crate::risk::RiskFactorRegistry;
+use crate::scoring::{ComplianceScoring, ScoringMode};
+use crate::slo::{SloPolicy, SloTarget, DEFAULT_BURN_RATE_ALERT, DEFAULT_SLO_WINDOW};
+use crate::vendors::{VendorProfile, VendorRegistry};
+use crate::waivers::{load_waivers, Waiver};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Vendors assumed to have signed a business associate agreement unless the policy file lists them.
+const DEFAULT_BAA_VENDORS: [&str; 4] = ["OpenAI", "Anthropic", "Google", "Microsoft"];
+
+/// The active rule configuration shared by the workers, the aggregator, and the dashboard.
+#[derive(Clone, Debug)]
+pub struct Policy {
+    /// Version recorded in audit logs.
+    pub version: &'static str,
+    /// Compliance flags claimed by the enabled rule packs.
+    pub compliance: ComplianceRegistry,
+    /// Risk factors evaluated by the risk assessment system.
+    pub risk_factors: RiskFactorRegistry,
+    /// The HIPAA rule pack, if enabled by the policy file.
+    pub hipaa: Option<HipaaRules>,
+    /// Per-department compliance SLOs, if configured by the policy file.
+    pub slo: Option<SloPolicy>,
+    /// How checks are combined into the headline compliance percentage.
+    pub scoring: ComplianceScoring,
+    /// Attributes of each vendor, consulted by the GDPR check.
+    pub vendors: VendorRegistry,
+    /// Service, vendor, and model combinations approved per department by the internal policy.
+    pub allowlist: Allowlist,
+    /// Rules deriving the sensitivity and PII types of ingested events from their prompt text.
+    pub content_scan: ContentScanner,
+    /// Exemptions from compliance checks listed in the waivers file.
+    pub waivers: Vec<Waiver>,
+}
+
+/// Settings of the optional HIPAA rule pack.
+#[derive(Clone, Copy, Debug)]
+pub struct HipaaRules {
+    /// Flag claimed for HIPAA compliance.
+    pub flag: ComplianceFlag,
+    /// Bit mask over `VENDOR_NAMES` of the vendors with a business associate agreement.
+    pub baa_vendors: u8,
+}
+
+/// Optional rule packs as written in a policy file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PolicySpec {
+    hipaa: Option<HipaaSpec>,
+    slo: Option<SloSpec>,
+    scoring: Option<ScoringSpec>,
+    /// Vendor profiles by vendor name.
+    vendors: Option<BTreeMap<String, VendorProfile>>,
+    /// Approved uses by department name.
+    allowlist: Option<BTreeMap<String, Vec<ApprovedUseSpec>>>,
+    /// Content scanning rules, in the order they are reported.
+    content_scan: Option<Vec<ScanRuleSpec>>,
+}
+
+/// HIPAA settings as written in a policy file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct HipaaSpec {
+    baa_vendors: Option<Vec<String>>,
+}
+
+/// Compliance SLOs as written in a policy file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct SloSpec {
+    window_secs: Option<u64>,
+    burn_rate_alert: Option<f64>,
+    /// Target compliance percentage by department name.
+    targets: BTreeMap<String, f64>,
+}
+
+/// Compliance scoring as written in a policy file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ScoringSpec {
+    mode: ScoringMode,
+    /// Weight by check key, for the weighted mode.
+    weights: Option<BTreeMap<String, f64>>,
+}
+
+impl Policy {
+    /// Creates a policy with only the built-in rule packs and risk factors.
+    pub fn builtin() -> Self {
+        Policy {
+            version: POLICY_VERSION,
+            compliance: ComplianceRegistry::builtin(),
+            risk_factors: RiskFactorRegistry::builtin(),
+            hipaa: None,
+            slo: None,
+            scoring: ComplianceScoring::default(),
+            vendors: VendorRegistry::default(),
+            allowlist: Allowlist::builtin(),
+            content_scan: ContentScanner::builtin(),
+            waivers: Vec::new(),
+        }
+    }
+
+    /// Builds the policy selected by the command line arguments.
+    pub fn from_args(args: &Args) -> Result<Self> {
+        Self::load(args.risk_factors.as_deref(), args.policy.as_deref(), args.waivers.as_deref())
+    }
+
+    /// Builds the candidate policy of shadow mode, or `None` if neither `--candidate-policy` nor
+    /// `--candidate-waivers` is given. Whichever of the two is missing is taken from the active
+    /// policy's `--policy` or `--waivers`, and the risk factors are always the active ones.
+    pub fn candidate_from_args(args: &Args) -> Result<Option<Self>> {
+        if args.candidate_policy.is_none() && args.candidate_waivers.is_none() {
+            return Ok(None);
+        }
+        let rule_packs = args.candidate_policy.as_deref().or(args.policy.as_deref());
+        let waivers = args.candidate_waivers.as_deref().or(args.waivers.as_deref());
+        Self::load(args.risk_factors.as_deref(), rule_packs, waivers).map(Some)
+    }
+
+    /// Builds a policy from its risk factor, rule pack, and waiver files, starting from the
+    /// built-in policy for any that is not given.
+    ///
+    /// # Arguments
+    ///
+    /// * `risk_factors` - File of the risk factors, as given to `--risk-factors`.
+    /// * `rule_packs` - File of the rule packs, as given to `--policy`.
+    /// * `waivers` - File of the waivers, as given to `--waivers`.
+    pub fn load(risk_factors: Option<&Path>, rule_packs: Option<&Path>, waivers: Option<&Path>) -> Result<Self> {
+        let mut policy = Self::builtin();
+        if let Some(path) = risk_factors {
+            policy.risk_factors = RiskFactorRegistry::load(path)?;
+        }
+        if let Some(path) = rule_packs {
+            policy.load_rule_packs(path)?;
+        }
+        // Waivers name checks by key, so they are loaded once every rule pack has claimed its flag.
+        if let Some(path) = waivers {
+            policy.waivers = load_waivers(path, &policy.compliance)?;
+        }
+        Ok(policy)
+    }
+
+    /// Returns the keys of the compliance checks, in flag order.
+    pub fn check_keys(&self) -> Vec<&'static str> {
+        self.compliance.iter().map(|(_, info)| info.key).collect()
+    }
+
+    /// Returns the keys of the risk factors, in id order.
+    pub fn risk_factor_keys(&self) -> Vec<&str> {
+        self.risk_factors.iter().map(|(_, factor)| factor.key.as_str()).collect()
+    }
+
+    /// Enables the optional rule packs configured in a JSON policy file.
+    ///
+    /// The file contains an object with an optional `hipaa` section, which enables the HIPAA
+    /// rule pack and may list the `baa_vendors` that signed a business associate agreement, an
+    /// optional `slo` section with target compliance percentages by department, an optional
+    /// `scoring` section choosing how checks are combined into the compliance percentage, an
+    /// optional `vendors` section with the vendor registry's profile of each vendor, an optional
+    /// `allowlist` section replacing the built-in approved uses of each department, and an
+    /// optional `content_scan` section replacing the built-in rules for scanning prompts.
+    pub fn load_rule_packs(&mut self, path: &Path) -> Result<()> {
+        let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+        let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let spec: PolicySpec = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        if let Some(hipaa) = spec.hipaa {
+            let mut baa_vendors = 0u8;
+            let listed = hipaa.baa_vendors.unwrap_or_else(|| DEFAULT_BAA_VENDORS.map(String::from).to_vec());
+            for vendor in listed {
+                let index = VENDOR_NAMES
+                    .iter()
+                    .position(|name| *name == vendor)
+                    .ok_or_else(|| invalid(format!("unknown BAA vendor '{}'", vendor)))?;
+                baa_vendors |= 1 << index;
+            }
+            let flag = self.compliance.claim("hipaa", "HIPAA").map_err(invalid)?;
+            self.hipaa = Some(HipaaRules { flag, baa_vendors });
+        }
+        if let Some(slo) = spec.slo {
+            let window = Duration::from_secs(slo.window_secs.unwrap_or(DEFAULT_SLO_WINDOW.as_secs()));
+            if window.is_zero() {
+                return Err(invalid("SLO window must be at least one second".to_string()));
+            }
+            let burn_rate_alert = slo.burn_rate_alert.unwrap_or(DEFAULT_BURN_RATE_ALERT);
+            if !(burn_rate_alert.is_finite() && burn_rate_alert > 0.0) {
+                return Err(invalid(format!("SLO burn rate alert must be a positive number, got {}", burn_rate_alert)));
+            }
+            let mut targets = Vec::new();
+            for (name, target) in slo.targets {
+                let department = DEPARTMENT_NAMES
+                    .iter()
+                    .position(|candidate| *candidate == name)
+                    .ok_or_else(|| invalid(format!("unknown SLO department '{}'", name)))?;
+                if !(target > 0.0 && target < 100.0) {
+                    return Err(invalid(format!("SLO target for {} must be between 0 and 100, got {}", name, target)));
+                }
+                targets.push(SloTarget { department: department as u8, target });
+            }
+            targets.sort_by_key(|target| target.department);
+            self.slo = Some(SloPolicy { window, burn_rate_alert, targets });
+        }
+        // Weights name checks by key, so they are read once the rule packs above have claimed theirs.
+        if let Some(scoring) = spec.scoring {
+            let mut weights = Vec::new();
+            if let Some(listed) = scoring.weights {
+                if scoring.mode != ScoringMode::Weighted {
+                    return Err(invalid(format!("scoring weights only apply to the weighted mode, not {}", scoring.mode.name())));
+                }
+                weights = vec![1.0; self.compliance.len()];
+                for (key, weight) in listed {
+                    let (flag, _) = self
+                        .compliance
+                        .iter()
+                        .find(|(_, info)| info.key == key)
+                        .ok_or_else(|| invalid(format!("unknown scoring check '{}'", key)))?;
+                    if !(weight.is_finite() && weight >= 0.0) {
+                        return Err(invalid(format!("scoring weight of {} must be zero or more, got {}", key, weight)));
+                    }
+                    weights[flag.0 as usize] = weight;
+                }
+                if weights.iter().all(|weight| *weight == 0.0) {
+                    return Err(invalid("scoring weights must not all be zero".to_string()));
+                }
+            }
+            self.scoring = ComplianceScoring { mode: scoring.mode, weights };
+        }
+        if let Some(vendors) = spec.vendors {
+            self.vendors = VendorRegistry::from_profiles(vendors).map_err(invalid)?;
+        }
+        if let Some(allowlist) = spec.allowlist {
+            self.allowlist = Allowlist::from_spec(allowlist).map_err(invalid)?;
+        }
+        if let Some(rules) = spec.content_scan {
+            self.content_scan = ContentScanner::from_specs(rules).map_err(invalid)?;
+        }
+        Ok(())
+    }
+}
+
+/// The active policy of a running monitor, which can be replaced by a reloaded one.
+///
+/// Readers keep their own `Arc<Policy>` and [`refresh`](LivePolicy::refresh) it between batches,
+/// so a reload never changes the policy in the middle of evaluating one.
+#[derive(Debug)]
+pub struct LivePolicy {
+    current: RwLock<Arc<Policy>>,
+    reloads: AtomicU64,
+}
+
+impl LivePolicy {
+    /// Starts with the policy loaded at startup.
+    pub fn new(policy: Arc<Policy>) -> Self {
+        LivePolicy {
+            current: RwLock::new(policy),
+            reloads: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the active policy.
+    pub fn current(&self) -> Arc<Policy> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns the number of times the policy has been replaced.
+    pub fn reloads(&self) -> u64 {
+        self.reloads.load(Ordering::Relaxed)
+    }
+
+    /// Replaces `policy` with the active policy if it has changed, returning true if it did.
+    pub fn refresh(&self, policy: &mut Arc<Policy>) -> bool {
+        let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+        if Arc::ptr_eq(&current, policy) {
+            return false;
+        }
+        *policy = current.clone();
+        true
+    }
+
+    /// Makes a reloaded policy the active one and returns it.
+    ///
+    /// Metrics are indexed by compliance flag and risk factor, so the reloaded policy must claim
+    /// the same checks and define the same risk factors, in the same order; rule pack settings,
+    /// risk factor conditions and weights, SLO targets, and waivers may all change. Adding or
+    /// removing a check or risk factor needs a restart.
+    pub fn replace(&self, policy: Policy) -> Result<Arc<Policy>> {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        let (checks, current_checks) = (policy.check_keys(), current.check_keys());
+        if checks != current_checks {
+            return Err(MonitorError::Config(format!(
+                "the reloaded policy has checks [{}] instead of [{}]; restart to change them",
+                checks.join(", "),
+                current_checks.join(", ")
+            )));
+        }
+        let (factors, current_factors) = (policy.risk_factor_keys(), current.risk_factor_keys());
+        if factors != current_factors {
+            return Err(MonitorError::Config(format!(
+                "the reloaded policy has risk factors [{}] instead of [{}]; restart to change them",
+                factors.join(", "),
+                current_factors.join(", ")
+            )));
+        }
+        *current = Arc::new(policy);
+        self.reloads.fetch_add(1, Ordering::Relaxed);
+        Ok(current.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waivers::Waiver;
+
+    #[test]
+    fn reloads_keep_the_checks_and_risk_factors() {
+        let live = LivePolicy::new(Arc::new(Policy::builtin()));
+        let mut seen = live.current();
+        assert!(!live.refresh(&mut seen));
+
+        let mut hipaa = Policy::builtin();
+        hipaa.compliance.claim("hipaa", "HIPAA").unwrap();
+        let error = live.replace(hipaa).unwrap_err().to_string();
+        assert!(error.contains("restart"), "{}", error);
+        assert_eq!(live.reloads(), 0);
+
+        let waiver = Waiver {
+            department: 0,
+            service: None,
+            checks: 0b10,
+            expires: "2030-01-01".to_string(),
+            expires_at_ms: 1_893_542_400_000,
+            ticket: "RISK-1".to_string(),
+        };
+        let mut waived = Policy::builtin();
+        waived.waivers = vec![waiver];
+        live.replace(waived).unwrap();
+        assert!(live.refresh(&mut seen));
+        assert_eq!(seen.waivers.len(), 1);
+        assert_eq!(live.reloads(), 1);
+    }
+
+    #[test]
+    fn scoring_weights_are_read_after_the_rule_packs() {
+        let path = std::env::temp_dir().join(format!("policy-scoring-test-{}.json", std::process::id()));
+        let load = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            let mut policy = Policy::builtin();
+            policy.load_rule_packs(&path).map(|()| policy)
+        };
+        let policy = load(r#"{"hipaa": {}, "scoring": {"mode": "weighted", "weights": {"hipaa": 3, "internal_policy": 0.5}}}"#).unwrap();
+        assert_eq!(policy.scoring.mode, ScoringMode::Weighted);
+        assert_eq!(policy.scoring.weights, vec![1.0, 1.0, 0.5, 1.0, 1.0, 3.0]);
+        assert_eq!(load(r#"{"scoring": {"mode": "any-violation"}}"#).unwrap().scoring.mode, ScoringMode::AnyViolation);
+
+        for (contents, expected) in [
+            (r#"{"scoring": {"mode": "weighted", "weights": {"hipaa": 3}}}"#, "unknown scoring check 'hipaa'"),
+            (r#"{"scoring": {"mode": "per-regulation", "weights": {"gdpr": 3}}}"#, "only apply to the weighted mode"),
+            (r#"{"scoring": {"mode": "weighted", "weights": {"gdpr": -1}}}"#, "must be zero or more"),
+            (r#"{"scoring": {"mode": "median"}}"#, "unknown variant"),
+        ] {
+            let error = load(contents).unwrap_err().to_string();
+            assert!(error.contains(expected), "{}", error);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+use crate::tail_latency;
+use hdrhistogram::Histogram;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+/// Steps of the batch pipeline whose latency is profiled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EcsSystem {
+    EuAiAct,
+    Gdpr,
+    InternalPolicy,
+    PurposeLimitation,
+    DataResidency,
+    /// The optional HIPAA rule pack; only timed when enabled.
+    Hipaa,
+    /// Clearing waived violations; only timed when the policy has waivers.
+    Waivers,
+    /// Risk assessment, including the batch metrics aggregated in the same pass.
+    RiskAssessment,
+    /// Collecting audit records from the evaluated entities.
+    AuditRecords,
+}
+
+/// Every profiled system, in pipeline order.
+pub const ECS_SYSTEMS: [EcsSystem; 9] = [
+    EcsSystem::EuAiAct,
+    EcsSystem::Gdpr,
+    EcsSystem::InternalPolicy,
+    EcsSystem::PurposeLimitation,
+    EcsSystem::DataResidency,
+    EcsSystem::Hipaa,
+    EcsSystem::Waivers,
+    EcsSystem::RiskAssessment,
+    EcsSystem::AuditRecords,
+];
+
+/// Per-batch latency of each system since startup, recorded by every thread that evaluates batches.
+static SYSTEM_LATENCY: LazyLock<[Mutex<Histogram<u64>>; ECS_SYSTEMS.len()]> =
+    LazyLock::new(|| ECS_SYSTEMS.map(|_| Mutex::new(tail_latency::new_histogram())));
+
+impl EcsSystem {
+    /// Returns the name used in reports and metric labels.
+    pub fn name(self) -> &'static str {
+        match self {
+            EcsSystem::EuAiAct => "eu_ai_act",
+            EcsSystem::Gdpr => "gdpr",
+            EcsSystem::InternalPolicy => "internal_policy",
+            EcsSystem::PurposeLimitation => "purpose_limitation",
+            EcsSystem::DataResidency => "data_residency",
+            EcsSystem::Hipaa => "hipaa",
+            EcsSystem::Waivers => "waivers",
+            EcsSystem::RiskAssessment => "risk_assessment",
+            EcsSystem::AuditRecords => "audit_records",
+        }
+    }
+
+    fn latency(self) -> &'static Mutex<Histogram<u64>> {
+        &SYSTEM_LATENCY[self as usize]
+    }
+}
+
+/// Runs `f`, a single system over a whole batch, and records how long it took.
+pub fn time_system<T>(system: EcsSystem, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let value = f();
+    let elapsed = started.elapsed();
+    tail_latency::record(&mut system.latency().lock().unwrap_or_else(|e| e.into_inner()), elapsed);
+    value
+}
+
+/// Returns the per-batch latency histogram of every system since startup.
+pub fn system_latencies() -> Vec<(EcsSystem, Histogram<u64>)> {
+    ECS_SYSTEMS
+        .iter()
+        .map(|&system| (system, system.latency().lock().unwrap_or_else(|e| e.into_inner()).clone()))
+        .collect()
+}
+
+/// Renders the per-system and worker batch latency summaries in the Prometheus text exposition
+/// format.
+pub fn prometheus_text() -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "# HELP ecs_system_batch_latency_seconds Time each ECS system takes per batch.");
+    let _ = writeln!(text, "# TYPE ecs_system_batch_latency_seconds summary");
+    for (system, histogram) in system_latencies() {
+        let labels = format!("system=\"{}\"", system.name());
+        tail_latency::summary_samples(&mut text, "ecs_system_batch_latency_seconds", &labels, &histogram);
+    }
+    tail_latency::prometheus_summary(&mut text);
+    text
+}
@@ -0,0 +1,116 @@
+/// A system at a specific rule version, recorded in an event's provenance by a compact id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PipelineStep {
+    /// Id stored in `Provenance` components and exports. Never reused for another step.
+    pub id: u8,
+    /// Name of the system.
+    pub system: &'static str,
+    /// Version of the system's rules; bumped, with a new id, whenever a verdict could change.
+    pub version: u16,
+}
+
+/// The first EU AI Act rule: high-risk vendors may not process highly sensitive data. Superseded
+/// by [`EU_AI_ACT_TIER_STEP`].
+pub const EU_AI_ACT_STEP: PipelineStep = PipelineStep { id: 0, system: "eu_ai_act", version: 1 };
+
+/// The first GDPR rule: only data sensitivity below 50 is compliant. Superseded by [`GDPR_DPA_STEP`].
+pub const GDPR_STEP: PipelineStep = PipelineStep { id: 1, system: "gdpr", version: 1 };
+
+/// The first internal policy rule: finance may only use approved services. Superseded by
+/// [`INTERNAL_POLICY_ALLOWLIST_STEP`].
+pub const INTERNAL_POLICY_STEP: PipelineStep = PipelineStep { id: 2, system: "internal_policy", version: 1 };
+
+/// The GDPR purpose-limitation rule: personal data may only be used for the department's approved purposes.
+pub const PURPOSE_LIMITATION_STEP: PipelineStep = PipelineStep { id: 4, system: "purpose_limitation", version: 1 };
+
+/// The data residency rule: EU personal data may only be processed in the EU or adequate countries.
+pub const DATA_RESIDENCY_STEP: PipelineStep = PipelineStep { id: 5, system: "data_residency", version: 1 };
+
+/// The EU AI Act rule by risk tier: prohibited uses, and high-risk uses of vendors without a
+/// conformity assessment, are violations. Superseded by [`EU_AI_ACT_BIOMETRIC_STEP`].
+pub const EU_AI_ACT_TIER_STEP: PipelineStep = PipelineStep { id: 6, system: "eu_ai_act", version: 2 };
+
+/// The optional HIPAA rule: protected health information may only go to vendors with a BAA.
+pub const HIPAA_STEP: PipelineStep = PipelineStep { id: 7, system: "hipaa", version: 1 };
+
+/// Waivers: violations exempted by an unexpired waiver for the event's department and service are cleared.
+pub const WAIVER_STEP: PipelineStep = PipelineStep { id: 8, system: "waivers", version: 1 };
+
+/// The GDPR rule with the vendor registry: personal data may only go to vendors with a signed DPA.
+/// Superseded by [`GDPR_LAWFUL_BASIS_STEP`].
+pub const GDPR_DPA_STEP: PipelineStep = PipelineStep { id: 9, system: "gdpr", version: 2 };
+
+/// The internal policy rule with the allowlist: departments restricted by the policy's allowlist
+/// may only use the service, vendor, and model combinations approved for them.
+pub const INTERNAL_POLICY_ALLOWLIST_STEP: PipelineStep = PipelineStep { id: 10, system: "internal_policy", version: 2 };
+
+/// The GDPR rule with lawful bases: personal data may only go to vendors with a signed DPA, and
+/// only on a lawful basis. Superseded by [`GDPR_SPECIAL_CATEGORY_STEP`].
+pub const GDPR_LAWFUL_BASIS_STEP: PipelineStep = PipelineStep { id: 11, system: "gdpr", version: 3 };
+
+/// The EU AI Act rule by risk tier, with any processing of biometric data classified as high-risk.
+pub const EU_AI_ACT_BIOMETRIC_STEP: PipelineStep = PipelineStep { id: 12, system: "eu_ai_act", version: 3 };
+
+/// The GDPR rule with special categories: health and biometric data is personal data whatever its
+/// sensitivity, and needs explicit consent on top of a signed DPA.
+pub const GDPR_SPECIAL_CATEGORY_STEP: PipelineStep = PipelineStep { id: 13, system: "gdpr", version: 4 };
+
+/// Risk assessment with the policy's risk factors.
+pub const RISK_ASSESSMENT_STEP: PipelineStep = PipelineStep { id: 3, system: "risk_assessment", version: 1 };
+
+/// Every step that has ever been recorded, indexed by id, so older exports stay decodable.
+pub const PIPELINE_STEPS: [PipelineStep; 14] = [
+    EU_AI_ACT_STEP,
+    GDPR_STEP,
+    INTERNAL_POLICY_STEP,
+    RISK_ASSESSMENT_STEP,
+    PURPOSE_LIMITATION_STEP,
+    DATA_RESIDENCY_STEP,
+    EU_AI_ACT_TIER_STEP,
+    HIPAA_STEP,
+    WAIVER_STEP,
+    GDPR_DPA_STEP,
+    INTERNAL_POLICY_ALLOWLIST_STEP,
+    GDPR_LAWFUL_BASIS_STEP,
+    EU_AI_ACT_BIOMETRIC_STEP,
+    GDPR_SPECIAL_CATEGORY_STEP,
+];
+
+/// Looks up the step recorded under `id`.
+pub fn pipeline_step(id: u8) -> Option<&'static PipelineStep> {
+    PIPELINE_STEPS.get(id as usize)
+}
+
+/// Maximum number of steps a single `Provenance` component can record.
+pub const MAX_PROVENANCE_STEPS: usize = 8;
+
+/// Component recording which pipeline steps evaluated an event, in the order they ran.
+///
+/// Attached together with [`crate::components::EventId`], only when per-event records are
+/// emitted. Serializes as the list of step ids.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Provenance {
+    ids: [u8; MAX_PROVENANCE_STEPS],
+    len: u8,
+}
+
+impl Provenance {
+    /// Appends `step` to the record. Steps beyond [`MAX_PROVENANCE_STEPS`] are dropped.
+    pub fn record(&mut self, step: PipelineStep) {
+        if let Some(slot) = self.ids.get_mut(self.len as usize) {
+            *slot = step.id;
+            self.len += 1;
+        }
+    }
+
+    /// Returns the ids of the recorded steps, in the order they ran.
+    pub fn ids(&self) -> &[u8] {
+        &self.ids[..self.len as usize]
+    }
+}
+
+impl serde::Serialize for Provenance {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.ids())
+    }
+}
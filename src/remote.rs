@@ -0,0 +1,647 @@
+use crate::clock::Clock;
+use crate::constants::{DEPARTMENT_NAMES, MODEL_NAMES, SERVICE_NAMES};
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError, Result};
+use crate::federation::Fleet;
+use crate::metrics::{ComplianceMetrics, SharedMetrics, UserCounts};
+use crossbeam_channel::Sender;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::info;
+
+/// Version of the metrics frame format.
+const FRAME_VERSION: u8 = 18;
+
+/// Frame flag marking an LZ4-compressed payload.
+const FLAG_LZ4: u8 = 1;
+
+/// Frame flag marking the first frame of a connection, whose payload names the sender.
+const FLAG_HELLO: u8 = 2;
+
+/// Longest instance name accepted in a hello frame.
+const MAX_INSTANCE_NAME: usize = 256;
+
+/// Largest frame accepted from a remote worker.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// How often blocked network calls wake up to check the stop signal.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Number of fixed counters at the start of the flattened metrics.
+const FIXED_COUNTERS: usize = 79;
+
+/// Number of values per user in the flattened metrics: the id and four counts.
+const USER_COUNTERS: usize = 5;
+
+/// Encodes the metrics a remote worker reports each interval into compact binary frames.
+///
+/// Every counter is written as a variable-length integer holding its difference from the
+/// previous report, so steady workloads encode to a few bytes per counter. The payload can
+/// additionally be LZ4-compressed.
+pub struct MetricsEncoder {
+    previous: Vec<u64>,
+    compress: bool,
+}
+
+impl MetricsEncoder {
+    /// Creates an encoder; `compress` enables LZ4 compression of each payload.
+    pub fn new(compress: bool) -> Self {
+        MetricsEncoder { previous: Vec::new(), compress }
+    }
+
+    /// Encodes a report as a length-prefixed frame.
+    pub fn encode(&mut self, metrics: &ComplianceMetrics) -> Vec<u8> {
+        let counters = flatten(metrics);
+        let mut payload = Vec::with_capacity(counters.len() * 2 + 2);
+        write_varint(&mut payload, counters.len() as u64);
+        for (i, &value) in counters.iter().enumerate() {
+            let previous = self.previous.get(i).copied().unwrap_or(0);
+            write_varint(&mut payload, zigzag(value.wrapping_sub(previous) as i64));
+        }
+        self.previous = counters;
+
+        let (flags, payload) = if self.compress {
+            (FLAG_LZ4, lz4_flex::compress_prepend_size(&payload))
+        } else {
+            (0, payload)
+        };
+        let mut frame = Vec::with_capacity(payload.len() + 6);
+        frame.extend_from_slice(&((payload.len() + 2) as u32).to_le_bytes());
+        frame.push(FRAME_VERSION);
+        frame.push(flags);
+        frame.extend_from_slice(&payload);
+        frame
+    }
+}
+
+/// Decodes frames produced by a single [`MetricsEncoder`], in order.
+#[derive(Default)]
+pub struct MetricsDecoder {
+    previous: Vec<u64>,
+}
+
+impl MetricsDecoder {
+    /// Decodes the body of a frame, i.e. everything after the length prefix.
+    pub fn decode(&mut self, body: &[u8]) -> io::Result<ComplianceMetrics> {
+        let [version, flags, payload @ ..] = body else {
+            return Err(invalid("truncated frame header"));
+        };
+        if *version != FRAME_VERSION {
+            return Err(invalid(format!("unsupported frame version {}", version)));
+        }
+        if flags & FLAG_HELLO != 0 {
+            return Err(invalid("unexpected hello frame"));
+        }
+        let decompressed;
+        let mut payload = if flags & FLAG_LZ4 != 0 {
+            decompressed = decompress(payload)?;
+            decompressed.as_slice()
+        } else {
+            payload
+        };
+
+        let len = read_varint(&mut payload)? as usize;
+        if len > payload.len() {
+            return Err(invalid("counter count exceeds payload"));
+        }
+        let mut counters = Vec::with_capacity(len);
+        for i in 0..len {
+            let previous = self.previous.get(i).copied().unwrap_or(0);
+            counters.push(previous.wrapping_add(unzigzag(read_varint(&mut payload)?) as u64));
+        }
+        let metrics = unflatten(&counters)?;
+        self.previous = counters;
+        Ok(metrics)
+    }
+}
+
+/// Decompresses an LZ4 payload prefixed with its uncompressed size. The size is written by the
+/// peer, so it is checked against [`MAX_FRAME_LEN`] before anything is allocated.
+pub fn decompress(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let (len, compressed) = lz4_flex::block::uncompressed_size(payload).map_err(invalid)?;
+    if len > MAX_FRAME_LEN {
+        return Err(invalid(format!("decompressed frame of {} bytes exceeds the limit", len)));
+    }
+    lz4_flex::block::decompress(compressed, len).map_err(invalid)
+}
+
+/// Encodes the hello frame that starts a connection, naming the instance or worker sending the
+/// frames that follow.
+pub fn hello_frame(instance: &str) -> Vec<u8> {
+    let name = &instance[..instance.floor_char_boundary(MAX_INSTANCE_NAME)];
+    let mut frame = Vec::with_capacity(name.len() + 6);
+    frame.extend_from_slice(&((name.len() + 2) as u32).to_le_bytes());
+    frame.push(FRAME_VERSION);
+    frame.push(FLAG_HELLO);
+    frame.extend_from_slice(name.as_bytes());
+    frame
+}
+
+/// Returns the instance name carried by a frame body, or `None` if it is not a hello frame.
+fn hello_instance(body: &[u8]) -> io::Result<Option<String>> {
+    match body {
+        [FRAME_VERSION, flags, name @ ..] if flags & FLAG_HELLO != 0 => {
+            if name.len() > MAX_INSTANCE_NAME {
+                return Err(invalid("instance name too long"));
+            }
+            let name = std::str::from_utf8(name).map_err(invalid)?;
+            Ok(Some(name.to_string()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Reads the next frame body from a stream, or `None` if the stream ended cleanly.
+pub fn read_frame(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(invalid(format!("frame of {} bytes exceeds the limit", len)));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// Forwards locally aggregated metrics to a remote aggregator, one frame per interval.
+///
+/// The connection starts with a hello frame naming the worker. Each frame after it carries the
+/// counts the local workers added to their shards since the previous frame. Returns once
+/// `workers_finished` reports that every local worker has stopped, after sending their final
+/// counts.
+///
+/// # Arguments
+///
+/// * `aggregator` - Address of the aggregator's `--listen` socket.
+/// * `instance` - Name the worker is shown under on the aggregator's Fleet tab.
+/// * `compress` - Whether to LZ4-compress frame payloads.
+/// * `interval` - Time between frames.
+/// * `clock` - Clock measuring the interval.
+/// * `worker_metrics` - Metric shards of the local workers.
+/// * `workers_finished` - Returns true once every local worker has stopped.
+pub fn forward_reports(
+    aggregator: &str,
+    instance: &str,
+    compress: bool,
+    interval: Duration,
+    clock: &dyn Clock,
+    worker_metrics: &SharedMetrics,
+    workers_finished: impl Fn() -> bool,
+) -> Result<()> {
+    let connection_error = |source| MonitorError::Aggregator { addr: aggregator.to_string(), source };
+    let mut stream = TcpStream::connect(aggregator).map_err(connection_error)?;
+    stream.set_nodelay(true).map_err(connection_error)?;
+    stream.write_all(&hello_frame(instance)).map_err(connection_error)?;
+    let mut encoder = MetricsEncoder::new(compress);
+    let mut last_frame = clock.now();
+    loop {
+        clock.sleep(POLL_INTERVAL);
+        let finished = workers_finished();
+        if finished || clock.now().saturating_sub(last_frame) >= interval {
+            stream.write_all(&encoder.encode(&worker_metrics.collect_new())).map_err(connection_error)?;
+            last_frame = clock.now();
+        }
+        if finished {
+            return Ok(());
+        }
+    }
+}
+
+/// Accepts remote worker connections and forwards their reports into the metrics channel.
+///
+/// Each connection is served on its own thread; connection failures are reported and only
+/// drop that worker. Federated instances connect the same way. Every connection is registered
+/// with the fleet under the name from its hello frame, or its address if it sent none. Returns
+/// once the stop signal is set and all connections are closed.
+///
+/// # Arguments
+///
+/// * `listener` - Bound listener for remote workers.
+/// * `stop_signal` - Atomic flag indicating when to stop accepting and reading.
+/// * `metrics_sender` - Channel sender into the aggregator.
+/// * `errors` - Channel sender for error reports to the supervisor.
+/// * `fleet` - The instances reporting to this monitor, shown on the Fleet tab.
+pub fn serve_remote_workers(
+    listener: TcpListener,
+    stop_signal: Arc<AtomicBool>,
+    metrics_sender: Sender<ComplianceMetrics>,
+    errors: Sender<ErrorReport>,
+    fleet: Arc<Fleet>,
+) {
+    let mut connections = Vec::new();
+    if let Err(e) = listener.set_nonblocking(true) {
+        report(&errors, MonitorError::Io(e), ErrorPolicy::Degrade);
+        return;
+    }
+    while !stop_signal.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                info!("remote worker {} connected", peer);
+                let stop = stop_signal.clone();
+                let sender = metrics_sender.clone();
+                let connection_errors = errors.clone();
+                let connection_fleet = fleet.clone();
+                connections.push(thread::spawn(move || {
+                    match receive_reports(stream, peer, &stop, &sender, &connection_fleet) {
+                        Ok(()) => info!("remote worker {} disconnected", peer),
+                        Err(source) => {
+                            report(&connection_errors, MonitorError::RemoteWorker { peer, source }, ErrorPolicy::Degrade)
+                        }
+                    }
+                }));
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => report(&errors, MonitorError::Io(e), ErrorPolicy::Retry),
+        }
+        connections.retain(|handle| !handle.is_finished());
+    }
+    for handle in connections {
+        let _ = handle.join();
+    }
+}
+
+/// Reads frames from one remote worker until it disconnects or the monitor stops.
+fn receive_reports(
+    stream: TcpStream,
+    peer: SocketAddr,
+    stop_signal: &AtomicBool,
+    sender: &Sender<ComplianceMetrics>,
+    fleet: &Fleet,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut decoder = MetricsDecoder::default();
+    let mut reader = StopAwareReader { stream, stop_signal };
+    let Some(first) = read_frame(&mut reader)? else {
+        return Ok(());
+    };
+    let (member, mut body) = match hello_instance(&first)? {
+        Some(name) => {
+            info!("remote worker {} reports as {}", peer, name);
+            (fleet.join(&name, peer), read_frame(&mut reader)?)
+        }
+        None => (fleet.join(&peer.to_string(), peer), Some(first)),
+    };
+    while let Some(frame) = body {
+        let metrics = decoder.decode(&frame)?;
+        member.record(&metrics);
+        // Blocking here pushes back on the remote worker through TCP flow control.
+        if sender.send(metrics).is_err() {
+            break;
+        }
+        body = read_frame(&mut reader)?;
+    }
+    Ok(())
+}
+
+/// Reader that retries timed-out reads until the stop signal is set, then reports end of stream.
+struct StopAwareReader<'a> {
+    stream: TcpStream,
+    stop_signal: &'a AtomicBool,
+}
+
+impl Read for StopAwareReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.stream.read(buf) {
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    if self.stop_signal.load(Ordering::Relaxed) {
+                        return Ok(0);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Flattens the counters of a report: the totals, then the number of departments followed by
+/// the counters of each, then the same for the services and the models.
+fn flatten(metrics: &ComplianceMetrics) -> Vec<u64> {
+    let series_len = FIXED_COUNTERS
+        + 4
+        + metrics.violation_counts.len()
+        + metrics.high_risk_violations.len()
+        + metrics.waived_counts.len()
+        + metrics.risk_factor_counts.len();
+    let entries = metrics.departments.len() + metrics.services.len() + metrics.models.len();
+    let mut counters = Vec::with_capacity(series_len * (1 + entries) + 3);
+    flatten_counters(metrics, &mut counters);
+    for breakdown in [&metrics.departments, &metrics.services, &metrics.models] {
+        counters.push(breakdown.len() as u64);
+        for entry in breakdown {
+            flatten_counters(entry, &mut counters);
+        }
+    }
+    counters
+}
+
+/// Appends the counters of a report, without its departments, services, and models: the fixed
+/// counters, then each length-prefixed registry-indexed series, then the number of users
+/// followed by the id and counts of each.
+fn flatten_counters(metrics: &ComplianceMetrics, counters: &mut Vec<u64>) {
+    counters.extend([
+        metrics.total_events,
+        metrics.high_risk_count,
+        metrics.medium_risk_count,
+        metrics.low_risk_count,
+    ]);
+    counters.extend(metrics.service_counts);
+    counters.extend(metrics.vendor_counts);
+    counters.extend(metrics.vendor_violations);
+    counters.extend(metrics.department_counts);
+    counters.extend([metrics.total_data_sensitivity, metrics.data_sensitivity_samples]);
+    counters.extend(metrics.purpose_counts);
+    counters.extend(metrics.purpose_violations);
+    counters.extend(metrics.region_counts);
+    counters.extend(metrics.region_violations);
+    counters.extend(metrics.ai_act_tier_counts);
+    counters.push(metrics.phi_events);
+    counters.push(metrics.waived_events);
+    counters.push(metrics.violating_events);
+    counters.push(metrics.shadow_ai_events);
+    counters.extend(metrics.shadow_ai_department_counts);
+    counters.push(metrics.shadow_ai_sensitivity);
+    counters.extend(metrics.lawful_basis_counts);
+    counters.extend(metrics.lawful_basis_personal);
+    counters.extend(metrics.pii_type_counts);
+    counters.push(metrics.violation_counts.len() as u64);
+    counters.extend(&metrics.violation_counts);
+    counters.push(metrics.high_risk_violations.len() as u64);
+    counters.extend(&metrics.high_risk_violations);
+    counters.push(metrics.waived_counts.len() as u64);
+    counters.extend(&metrics.waived_counts);
+    counters.push(metrics.risk_factor_counts.len() as u64);
+    counters.extend(&metrics.risk_factor_counts);
+    counters.push(metrics.users.len() as u64);
+    for (&user, counts) in &metrics.users {
+        counters.extend([user as u64, counts.events, counts.violations, counts.high_risk_events, counts.total_risk_score]);
+    }
+}
+
+/// Rebuilds a report from counters produced by [`flatten`].
+fn unflatten(counters: &[u64]) -> io::Result<ComplianceMetrics> {
+    let mut rest = counters;
+    let mut metrics = unflatten_counters(&mut rest)?;
+    metrics.departments = unflatten_breakdown(&mut rest, "departments", DEPARTMENT_NAMES.len())?;
+    metrics.services = unflatten_breakdown(&mut rest, "services", SERVICE_NAMES.len())?;
+    metrics.models = unflatten_breakdown(&mut rest, "models", MODEL_NAMES.len())?;
+    Ok(metrics)
+}
+
+/// Reads a count of at most `known` entries, then the counters of each, from the front of `counters`.
+fn unflatten_breakdown(counters: &mut &[u64], name: &str, known: usize) -> io::Result<Vec<ComplianceMetrics>> {
+    let (&len, tail) = counters.split_first().ok_or_else(|| invalid(format!("missing number of {}", name)))?;
+    if len as usize > known {
+        return Err(invalid(format!("{} {} exceed the {} known", len, name, known)));
+    }
+    *counters = tail;
+    (0..len).map(|_| unflatten_counters(counters)).collect()
+}
+
+/// Reads the counters written by [`flatten_counters`] from the front of `counters`.
+fn unflatten_counters(counters: &mut &[u64]) -> io::Result<ComplianceMetrics> {
+    let (fixed, mut rest) = counters
+        .split_at_checked(FIXED_COUNTERS)
+        .ok_or_else(|| invalid("too few counters"))?;
+    let mut series = || -> io::Result<Vec<u64>> {
+        let (&len, tail) = rest.split_first().ok_or_else(|| invalid("missing series length"))?;
+        let (values, tail) = tail.split_at_checked(len as usize).ok_or_else(|| invalid("truncated series"))?;
+        rest = tail;
+        Ok(values.to_vec())
+    };
+    let violation_counts = series()?;
+    let high_risk_violations = series()?;
+    let waived_counts = series()?;
+    let risk_factor_counts = series()?;
+    let (&user_count, tail) = rest.split_first().ok_or_else(|| invalid("missing number of users"))?;
+    let user_values = (user_count as usize).checked_mul(USER_COUNTERS).ok_or_else(|| invalid("too many users"))?;
+    let (user_values, tail) = tail.split_at_checked(user_values).ok_or_else(|| invalid("truncated users"))?;
+    *counters = tail;
+    let users = user_values
+        .chunks_exact(USER_COUNTERS)
+        .map(|user| {
+            let id = u32::try_from(user[0]).map_err(|_| invalid(format!("invalid user id {}", user[0])))?;
+            let counts = UserCounts {
+                events: user[1],
+                violations: user[2],
+                high_risk_events: user[3],
+                total_risk_score: user[4],
+            };
+            Ok((id, counts))
+        })
+        .collect::<io::Result<_>>()?;
+    // Fields are read in the order `flatten_counters` writes them.
+    let mut fixed = fixed.iter().copied();
+    let mut next = || fixed.next().expect("FIXED_COUNTERS counters");
+    let mut metrics = ComplianceMetrics {
+        total_events: next(),
+        high_risk_count: next(),
+        medium_risk_count: next(),
+        low_risk_count: next(),
+        service_counts: std::array::from_fn(|_| next()),
+        vendor_counts: std::array::from_fn(|_| next()),
+        vendor_violations: std::array::from_fn(|_| next()),
+        department_counts: std::array::from_fn(|_| next()),
+        total_data_sensitivity: next(),
+        data_sensitivity_samples: next(),
+        purpose_counts: std::array::from_fn(|_| next()),
+        purpose_violations: std::array::from_fn(|_| next()),
+        region_counts: std::array::from_fn(|_| next()),
+        region_violations: std::array::from_fn(|_| next()),
+        ai_act_tier_counts: std::array::from_fn(|_| next()),
+        phi_events: next(),
+        waived_events: next(),
+        violating_events: next(),
+        shadow_ai_events: next(),
+        shadow_ai_department_counts: std::array::from_fn(|_| next()),
+        shadow_ai_sensitivity: next(),
+        lawful_basis_counts: std::array::from_fn(|_| next()),
+        lawful_basis_personal: std::array::from_fn(|_| next()),
+        pii_type_counts: std::array::from_fn(|_| next()),
+        violation_counts,
+        high_risk_violations,
+        waived_counts,
+        risk_factor_counts,
+        users,
+        ..ComplianceMetrics::default()
+    };
+    if metrics.data_sensitivity_samples > 0 {
+        metrics.avg_data_sensitivity = metrics.total_data_sensitivity as f64 / metrics.data_sensitivity_samples as f64;
+    }
+    Ok(metrics)
+}
+
+/// Maps signed deltas to unsigned values so small magnitudes of either sign stay small.
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Appends `value` as a LEB128 variable-length integer.
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Reads a LEB128 variable-length integer from the front of `input`.
+fn read_varint(input: &mut &[u8]) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input.split_first().ok_or_else(|| invalid("truncated varint"))?;
+        *input = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn invalid(message: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_metrics(scale: u64) -> ComplianceMetrics {
+        ComplianceMetrics {
+            total_events: 1000 * scale,
+            violation_counts: vec![60 * scale, 500 * scale, 120 * scale, 150 * scale, 180 * scale],
+            high_risk_violations: vec![20 * scale, 90 * scale, 40 * scale, 10 * scale, 30 * scale],
+            high_risk_count: 60 * scale,
+            medium_risk_count: 240 * scale,
+            low_risk_count: 700 * scale,
+            service_counts: [200 * scale; 5],
+            vendor_counts: [200 * scale; 5],
+            vendor_violations: [90 * scale, 110 * scale, 120 * scale, 130 * scale, 190 * scale],
+            department_counts: [200 * scale, 200 * scale, 200 * scale, 150 * scale, 150 * scale, 100 * scale],
+            purpose_counts: [150 * scale, 200 * scale, 150 * scale, 100 * scale, 200 * scale, 200 * scale],
+            purpose_violations: [30 * scale, 10 * scale, 50 * scale, 20 * scale, 0, 40 * scale],
+            region_counts: [400 * scale, 100 * scale, 100 * scale, 200 * scale, 100 * scale, 100 * scale],
+            region_violations: [0, 0, 0, 90 * scale, 40 * scale, 50 * scale],
+            ai_act_tier_counts: [10 * scale, 150 * scale, 400 * scale, 440 * scale],
+            phi_events: 30 * scale,
+            waived_events: 25 * scale,
+            violating_events: 640 * scale,
+            shadow_ai_events: 130 * scale,
+            shadow_ai_department_counts: [10 * scale, 20 * scale, 40 * scale, 30 * scale, 30 * scale, 0],
+            shadow_ai_sensitivity: 7_800 * scale,
+            lawful_basis_counts: [300 * scale, 250 * scale, 150 * scale, 60 * scale],
+            lawful_basis_personal: [120 * scale, 90 * scale, 40 * scale, 25 * scale],
+            pii_type_counts: [110 * scale, 70 * scale, 45 * scale, 20 * scale, 15 * scale],
+            waived_counts: vec![0, 0, 25 * scale, 0, 0],
+            risk_factor_counts: vec![60 * scale, 500 * scale, 120 * scale, 190 * scale, 200 * scale],
+            total_data_sensitivity: 50_000 * scale,
+            data_sensitivity_samples: 1000 * scale,
+            users: [(7, 3), (42, 11)]
+                .into_iter()
+                .map(|(user, events)| {
+                    let counts = UserCounts {
+                        events: events * scale,
+                        violations: scale,
+                        high_risk_events: 0,
+                        total_risk_score: 40 * scale,
+                    };
+                    (user, counts)
+                })
+                .collect(),
+            departments: vec![
+                ComplianceMetrics {
+                    total_events: 200 * scale,
+                    violation_counts: vec![12 * scale, 100 * scale, 24 * scale, 30 * scale, 36 * scale],
+                    high_risk_violations: vec![4 * scale, 18 * scale, 8 * scale, 2 * scale, 6 * scale],
+                    ..ComplianceMetrics::default()
+                };
+                6
+            ],
+            services: vec![
+                ComplianceMetrics {
+                    total_events: 200 * scale,
+                    high_risk_count: 20 * scale,
+                    department_counts: [40 * scale, 60 * scale, 100 * scale, 0, 0, 0],
+                    ..ComplianceMetrics::default()
+                };
+                5
+            ],
+            ..ComplianceMetrics::default()
+        }
+    }
+
+    fn round_trip(compress: bool) {
+        let mut encoder = MetricsEncoder::new(compress);
+        let mut decoder = MetricsDecoder::default();
+        for metrics in [ComplianceMetrics::default(), sample_metrics(3), sample_metrics(2), sample_metrics(2)] {
+            let frame = encoder.encode(&metrics);
+            let body = read_frame(&mut frame.as_slice()).unwrap().unwrap();
+            let decoded = decoder.decode(&body).unwrap();
+            assert_eq!(flatten(&decoded), flatten(&metrics));
+            assert_eq!(decoded.avg_data_sensitivity, if metrics.data_sensitivity_samples > 0 { 50.0 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        round_trip(false);
+    }
+
+    #[test]
+    fn compressed_frames_round_trip() {
+        round_trip(true);
+    }
+
+    #[test]
+    fn unchanged_reports_encode_to_one_byte_per_counter() {
+        let mut encoder = MetricsEncoder::new(false);
+        encoder.encode(&sample_metrics(1));
+        let frame = encoder.encode(&sample_metrics(1));
+        let counters = flatten(&sample_metrics(1)).len();
+        let mut count = Vec::new();
+        write_varint(&mut count, counters as u64);
+        assert_eq!(frame.len(), 4 + 2 + count.len() + counters);
+    }
+
+    #[test]
+    fn clean_end_of_stream_is_not_an_error() {
+        assert!(read_frame(&mut [].as_slice()).unwrap().is_none());
+    }
+
+    #[test]
+    fn corrupt_frames_are_rejected() {
+        let mut decoder = MetricsDecoder::default();
+        assert!(decoder.decode(&[]).is_err());
+        assert!(decoder.decode(&[FRAME_VERSION + 1, 0, 0]).is_err());
+        assert!(decoder.decode(&[FRAME_VERSION, 0, 5, 0]).is_err());
+        // A compressed payload claiming to decompress to 4 GiB is rejected before allocating.
+        let Err(error) = decoder.decode(&[FRAME_VERSION, FLAG_LZ4, 0xff, 0xff, 0xff, 0xff, 0]) else {
+            panic!("a frame claiming 4 GiB was decoded");
+        };
+        assert!(error.to_string().contains("exceeds the limit"));
+    }
+
+    #[test]
+    fn hello_frames_name_the_sender() {
+        let frame = hello_frame("gateway-eu");
+        let body = read_frame(&mut frame.as_slice()).unwrap().unwrap();
+        assert_eq!(hello_instance(&body).unwrap().as_deref(), Some("gateway-eu"));
+        assert!(MetricsDecoder::default().decode(&body).is_err());
+        let metrics = MetricsEncoder::new(false).encode(&sample_metrics(1));
+        assert!(hello_instance(&metrics[4..]).unwrap().is_none());
+    }
+}
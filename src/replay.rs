@@ -0,0 +1,324 @@
+use crate::components::{AIService, Event, LawfulBasis, PiiTypes, Purpose, Region, Timestamp, Usage, UserId};
+use crate::constants::*;
+use crate::ecs::{process_batch, PipelineConfig};
+use crate::error::{report, with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use crate::event_time::{window_runs, EventTimeWindows, LateArrivals};
+use crate::metrics::IntervalMetrics;
+use crate::policy::Policy;
+use crate::run_report::{time_stage, Stage};
+use crate::source::EventSource;
+use crate::frontend::{FrontendCommand, ReplayStatus};
+use crossbeam_channel::{Receiver, Sender};
+use hecs::World;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Maximum number of events evaluated in a single replay batch.
+const REPLAY_BATCH_SIZE: usize = 10_000;
+
+/// Sink name used in error reports from the event recorder.
+pub const RECORDER_SINK: &str = "event recorder";
+
+/// Minimum time between replay progress updates sent to the frontend.
+const REPLAY_STATUS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A single AI usage event as stored in an NDJSON event log, one record per line.
+///
+/// Names are stored instead of indices so logs stay readable and survive reordering of the
+/// constant tables. Unknown fields are ignored, so richer audit records can be replayed too.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EventRecord {
+    pub timestamp_ms: u64,
+    pub service: String,
+    /// Model version of the service; logs recorded before models were tracked lack it and are
+    /// replayed with the service's first model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub vendor: String,
+    pub department: String,
+    /// Sensitivity of the data from 0 to 100; may be left out of events with a `prompt`, which
+    /// the content scanner scores instead.
+    #[serde(default)]
+    pub data_sensitivity: u8,
+    /// Id of the user behind the event; absent in logs recorded before users were tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u32>,
+    /// What the prompt is used for; events from logs recorded before purposes were tracked are
+    /// not checked for purpose limitation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    /// Region the request is processed in; events from logs recorded before regions were
+    /// tracked are not checked for data residency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Lawful basis the personal data is processed under; events from logs recorded before
+    /// lawful bases were tracked are not checked for one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lawful_basis: Option<String>,
+    /// Types of personally identifiable information in the data; absent if there are none or
+    /// the log was recorded before PII types were tracked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pii_types: Vec<String>,
+    /// Raw prompt text, scanned by the policy's content scanner for personal data. Recorded logs
+    /// never contain it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+impl EventRecord {
+    /// Builds a record from the ECS components of an event.
+    pub fn from_components((service, usage, user, purpose, region, basis, pii, timestamp): &Event) -> Self {
+        EventRecord {
+            timestamp_ms: timestamp.0,
+            service: SERVICE_NAMES[service.name_idx as usize].to_string(),
+            model: Some(MODEL_NAMES[service.model_idx as usize].to_string()),
+            vendor: VENDOR_NAMES[service.vendor_idx as usize].to_string(),
+            department: DEPARTMENT_NAMES[usage.department_idx as usize].to_string(),
+            data_sensitivity: usage.data_sensitivity,
+            user_id: user.known(),
+            purpose: purpose.known().map(|purpose| PURPOSE_NAMES[purpose].to_string()),
+            region: region.known().map(|region| REGION_NAMES[region].to_string()),
+            lawful_basis: basis.known().map(|basis| LAWFUL_BASIS_NAMES[basis].to_string()),
+            pii_types: pii.indices().map(|index| PII_TYPE_NAMES[index].to_string()).collect(),
+            prompt: None,
+        }
+    }
+
+    /// Resolves the record back into ECS components.
+    ///
+    /// Returns `None` if any name is not known to this build, or the model is not one of the
+    /// service's.
+    pub fn to_components(&self) -> Option<Event> {
+        let name_idx = lookup_index(&SERVICE_NAMES, &self.service)?;
+        let first_model = name_idx as usize * MODELS_PER_SERVICE;
+        let service_models = &MODEL_NAMES[first_model..first_model + MODELS_PER_SERVICE];
+        let model_offset = match &self.model {
+            Some(model) => lookup_index(service_models, model)?,
+            None => 0,
+        };
+        let ai_service = AIService {
+            name_idx,
+            vendor_idx: lookup_index(&VENDOR_NAMES, &self.vendor)?,
+            model_idx: first_model as u8 + model_offset,
+        };
+        let usage = Usage {
+            department_idx: lookup_index(&DEPARTMENT_NAMES, &self.department)?,
+            data_sensitivity: self.data_sensitivity.min(100),
+        };
+        let purpose = match &self.purpose {
+            Some(purpose) => Purpose(lookup_index(&PURPOSE_NAMES, purpose)?),
+            None => Purpose::UNSPECIFIED,
+        };
+        let region = match &self.region {
+            Some(region) => Region(lookup_index(&REGION_NAMES, region)?),
+            None => Region::UNKNOWN,
+        };
+        let basis = match &self.lawful_basis {
+            Some(basis) => LawfulBasis(lookup_index(&LAWFUL_BASIS_NAMES, basis)?),
+            None => LawfulBasis::UNKNOWN,
+        };
+        let mut pii = PiiTypes::NONE;
+        for pii_type in &self.pii_types {
+            pii.0 |= 1 << lookup_index(&PII_TYPE_NAMES, pii_type)?;
+        }
+        Some((ai_service, usage, self.user_id.map_or(UserId::UNKNOWN, UserId), purpose, region, basis, pii, Timestamp(self.timestamp_ms)))
+    }
+}
+
+/// Finds the index of `name` in one of the static name tables.
+fn lookup_index(names: &[&str], name: &str) -> Option<u8> {
+    names.iter().position(|n| *n == name).map(|i| i as u8)
+}
+
+/// Writes recorded event batches to an NDJSON log until all senders are dropped.
+///
+/// Write failures are handled according to `policy`; if the recorder gives up it reports the
+/// error and exits, which disables recording without affecting the rest of the monitor.
+///
+/// # Arguments
+///
+/// * `path` - The file to write the event log to.
+/// * `receiver` - Channel receiving batches of event records from the workers.
+/// * `policy` - How to handle write failures.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn record_events(
+    path: &Path,
+    receiver: Receiver<Vec<EventRecord>>,
+    policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+) {
+    if let Err(e) = write_event_log(path, receiver, policy, &errors) {
+        let action = if policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+        report(&errors, e, action);
+    }
+}
+
+/// Serializes each batch into a buffer and appends it to the log, retrying per `policy`.
+fn write_event_log(
+    path: &Path,
+    receiver: Receiver<Vec<EventRecord>>,
+    policy: ErrorPolicy,
+    errors: &Sender<ErrorReport>,
+) -> Result<()> {
+    let mut file = with_retry(policy, RECORDER_SINK, errors, || File::create(path))?;
+    let mut buffer = Vec::new();
+    for batch in receiver {
+        buffer.clear();
+        for record in batch {
+            serde_json::to_writer(&mut buffer, &record).map_err(|e| MonitorError::Sink {
+                sink: RECORDER_SINK,
+                source: e.into(),
+            })?;
+            buffer.push(b'\n');
+        }
+        with_retry(policy, RECORDER_SINK, errors, || file.write_all(&buffer))?;
+    }
+    with_retry(policy, RECORDER_SINK, errors, || file.flush())
+}
+
+/// Replays an event source, normally an NDJSON event log, through the ECS systems.
+///
+/// Runs until the source is exhausted or the stop signal is set. The metrics are reported in
+/// event-time windows, each closed once the replay reaches events of a later window, and the
+/// last when the replay ends.
+///
+/// # Arguments
+///
+/// * `source` - The source to replay, pacing its events; a [`FileSource`](crate::source::FileSource) reports its progress
+///   to the frontend.
+/// * `policy` - The active policy.
+/// * `config` - How the systems iterate over each replayed batch.
+/// * `windows` - The event-time windows the metrics are counted in, none of them open yet.
+/// * `stop_signal` - Atomic flag indicating when to stop replaying.
+/// * `window_sender` - Channel sender for the metrics of each closed window.
+/// * `cmd_sender` - Channel sender for replay progress updates to the frontend.
+pub fn replay_thread(
+    mut source: Box<dyn EventSource>,
+    policy: &Policy,
+    config: PipelineConfig,
+    mut windows: EventTimeWindows,
+    stop_signal: Arc<AtomicBool>,
+    window_sender: Sender<IntervalMetrics>,
+    cmd_sender: Sender<FrontendCommand>,
+) -> Result<()> {
+    let PipelineConfig { parallelism, shadow, .. } = config;
+    let mut world = World::new();
+    let mut shadow_world = World::new();
+    let mut last_status = Instant::now();
+    let send_status = |source: &dyn EventSource, arrivals: LateArrivals| {
+        if let Some(status) = source.replay_status() {
+            let _ = cmd_sender.send(FrontendCommand::UpdateReplay(ReplayStatus { arrivals, ..status.clone() }));
+        }
+    };
+
+    while !stop_signal.load(Ordering::Relaxed) {
+        let Some(batch) = source.next_batch(REPLAY_BATCH_SIZE)? else {
+            break;
+        };
+        if batch.is_empty() {
+            continue;
+        }
+        for (start, run) in window_runs(&batch, windows.window()) {
+            let metrics = time_stage(Stage::Evaluate, || process_batch(&mut world, run, policy, parallelism, None));
+            if let Some(shadow) = &shadow {
+                time_stage(Stage::Shadow, || shadow.evaluate(&mut shadow_world, run, &metrics, parallelism));
+            }
+            for interval in windows.add(start, run, &metrics) {
+                let _ = window_sender.send(interval);
+            }
+        }
+        source.ack(batch.len());
+        if last_status.elapsed() >= REPLAY_STATUS_INTERVAL {
+            send_status(source.as_ref(), windows.arrivals());
+            last_status = Instant::now();
+        }
+    }
+    for interval in windows.finish() {
+        let _ = window_sender.send(interval);
+    }
+    if windows.arrivals().total() > 0 {
+        warn!("{}", windows.arrivals().render());
+    }
+    source.shutdown();
+    send_status(source.as_ref(), windows.arrivals());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, ManualClock};
+    use crate::ecs::{generate_ai_events, Parallelism};
+    use crate::event_log::{ColumnMapping, EventLogReader, LogFormat};
+    use crate::source::{FileSource, SourceSpec};
+    use crate::traffic::TrafficPattern;
+    use crossbeam_channel::unbounded;
+    use std::io::Cursor;
+
+    #[test]
+    fn replay_paces_events_on_the_given_clock() {
+        let offsets_ms = [0, 1_000, 3_000];
+        let mut log = String::new();
+        for (offset, mut event) in offsets_ms.iter().zip(generate_ai_events(offsets_ms.len())) {
+            event.7 = Timestamp(1_000_000 + offset);
+            let record = EventRecord::from_components(&event);
+            log.push_str(&serde_json::to_string(&record).unwrap());
+            log.push('\n');
+        }
+        let clock = Arc::new(ManualClock::new(0));
+        let config = PipelineConfig {
+            parallelism: Parallelism::Sequential,
+            clock: clock.clone(),
+            event_profile: Arc::default(),
+            traffic: TrafficPattern::Flat,
+            spikes: Arc::default(),
+            source: SourceSpec::Synthetic,
+            shadow: None,
+        };
+        let status = ReplayStatus { speed: 2.0, ..ReplayStatus::default() };
+        let policy = Policy::builtin();
+        let reader = EventLogReader::new(Cursor::new(log), LogFormat::Ndjson, ColumnMapping::default(), policy.content_scan.clone()).unwrap();
+        let source = FileSource::new(reader, status, clock.clone());
+        let (window_sender, window_receiver) = unbounded();
+        let (cmd_sender, _cmd_receiver) = unbounded();
+        let stop_signal = Arc::new(AtomicBool::new(false));
+
+        let windows = EventTimeWindows::new(Duration::from_secs(2), Duration::ZERO);
+        replay_thread(Box::new(source), &policy, config, windows, stop_signal, window_sender, cmd_sender).unwrap();
+
+        // At double speed, the last event is due 1.5 simulated seconds in.
+        assert_eq!(clock.now(), Duration::from_millis(1_500));
+        // The metrics are windowed by the log's timeline, not the replay's.
+        let windows: Vec<(u64, u64)> = window_receiver.try_iter().map(|interval| (interval.unix_ms, interval.counts.total_events)).collect();
+        assert_eq!(windows, [(1_002_000, 2), (1_004_000, 1)]);
+    }
+
+    #[test]
+    fn records_resolve_models_of_their_service() {
+        let record = |model: Option<&str>| EventRecord {
+            timestamp_ms: 0,
+            service: "Claude".to_string(),
+            model: model.map(str::to_string),
+            vendor: "Anthropic".to_string(),
+            department: "Legal".to_string(),
+            data_sensitivity: 50,
+            user_id: None,
+            purpose: None,
+            region: None,
+            lawful_basis: None,
+            pii_types: Vec::new(),
+            prompt: None,
+        };
+        let model_of = |model| record(model).to_components().map(|(service, ..)| MODEL_NAMES[service.model_idx as usize]);
+        assert_eq!(model_of(Some("Claude Opus")), Some("Claude Opus"));
+        // Older logs have no model and get the service's first one.
+        assert_eq!(model_of(None), Some("Claude Sonnet"));
+        assert_eq!(model_of(Some("GPT-4o")), None);
+    }
+}
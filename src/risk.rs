@@ -0,0 +1,279 @@
+use crate::compliance::{ComplianceFlag, EU_AI_ACT, GDPR, INTERNAL_POLICY};
+use crate::components::{AIService, ComplianceStatus, Usage};
+use crate::constants::*;
+use crate::error::{MonitorError, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Maximum number of risk factors; each factor claims one bit of `RiskAssessment::factor_flags`.
+pub const MAX_RISK_FACTORS: usize = 64;
+
+/// Identifier of a registered risk factor, i.e. its bit position in the factor flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RiskFactorId(pub u8);
+
+impl RiskFactorId {
+    /// Returns the bit flag for this factor.
+    pub fn bit(self) -> u64 {
+        1 << self.0
+    }
+}
+
+/// Condition under which a risk factor applies to an event.
+#[derive(Clone, Debug)]
+pub enum RiskCondition {
+    /// The event failed the check behind the given compliance flag.
+    Violation(ComplianceFlag),
+    /// The event matches all of the given attributes.
+    Match(RiskMatch),
+}
+
+/// Attribute filters for a [`RiskCondition::Match`]; unset filters match everything.
+#[derive(Clone, Debug, Default)]
+pub struct RiskMatch {
+    pub service_idx: Option<u8>,
+    pub vendor_idx: Option<u8>,
+    pub model_idx: Option<u8>,
+    /// Index into `DEPLOYMENT_NAMES` of the deployment of the event's model.
+    pub deployment_idx: Option<u8>,
+    pub department_idx: Option<u8>,
+    pub min_sensitivity: Option<u8>,
+    pub max_sensitivity: Option<u8>,
+}
+
+impl RiskMatch {
+    /// Returns true if the event matches every configured filter.
+    fn matches(&self, service: &AIService, usage: &Usage) -> bool {
+        self.service_idx.is_none_or(|idx| idx == service.name_idx)
+            && self.vendor_idx.is_none_or(|idx| idx == service.vendor_idx)
+            && self.model_idx.is_none_or(|idx| idx == service.model_idx)
+            && self.deployment_idx.is_none_or(|idx| idx == MODEL_DEPLOYMENTS[service.model_idx as usize])
+            && self.department_idx.is_none_or(|idx| idx == usage.department_idx)
+            && self.min_sensitivity.is_none_or(|min| usage.data_sensitivity >= min)
+            && self.max_sensitivity.is_none_or(|max| usage.data_sensitivity <= max)
+    }
+}
+
+/// A named risk factor contributing `weight` points to the risk score when its condition holds.
+#[derive(Clone, Debug)]
+pub struct RiskFactor {
+    pub key: String,
+    /// Short label used in charts and reports.
+    pub name: String,
+    pub weight: u8,
+    pub condition: RiskCondition,
+}
+
+impl RiskFactor {
+    /// Returns true if this factor applies to the given event.
+    pub fn applies(&self, service: &AIService, usage: &Usage, status: &ComplianceStatus) -> bool {
+        match &self.condition {
+            RiskCondition::Violation(flag) => status.flags & flag.bit() == 0,
+            RiskCondition::Match(filter) => filter.matches(service, usage),
+        }
+    }
+}
+
+/// A risk factor definition as written in a risk factor file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct RiskFactorSpec {
+    key: String,
+    name: Option<String>,
+    weight: u8,
+    service: Option<String>,
+    vendor: Option<String>,
+    model: Option<String>,
+    deployment: Option<String>,
+    department: Option<String>,
+    min_sensitivity: Option<u8>,
+    max_sensitivity: Option<u8>,
+}
+
+/// Registry of all risk factors known to this run: the built-ins plus any loaded from file.
+#[derive(Clone, Debug)]
+pub struct RiskFactorRegistry {
+    factors: Vec<RiskFactor>,
+}
+
+impl RiskFactorRegistry {
+    /// Creates a registry containing only the built-in risk factors.
+    pub fn builtin() -> Self {
+        let mut registry = RiskFactorRegistry { factors: Vec::new() };
+        let builtins = [
+            ("eu_act", "EU Act", 40, RiskCondition::Violation(EU_AI_ACT)),
+            ("gdpr", "GDPR", 30, RiskCondition::Violation(GDPR)),
+            ("internal", "Internal", 20, RiskCondition::Violation(INTERNAL_POLICY)),
+            (
+                "sensitive_data",
+                "Sensitive",
+                10,
+                RiskCondition::Match(RiskMatch { min_sensitivity: Some(81), ..RiskMatch::default() }),
+            ),
+            (
+                "public_model",
+                "Public Model",
+                5,
+                // Assume vendor at index 0 serves public models.
+                RiskCondition::Match(RiskMatch { vendor_idx: Some(0), ..RiskMatch::default() }),
+            ),
+        ];
+        for (key, name, weight, condition) in builtins {
+            registry.factors.push(RiskFactor {
+                key: key.to_string(),
+                name: name.to_string(),
+                weight,
+                condition,
+            });
+        }
+        registry
+    }
+
+    /// Creates a registry with the built-ins plus the factors defined in a JSON file.
+    ///
+    /// The file contains an array of factor definitions, each with a `key`, `weight`,
+    /// optional display `name`, and optional `service`, `vendor`, `model`, `deployment`,
+    /// `department`, `min_sensitivity`, and `max_sensitivity` filters.
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+        let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let specs: Vec<RiskFactorSpec> = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        let mut registry = Self::builtin();
+        for spec in specs {
+            let lookup = |names: &[&str], value: Option<String>, field: &str| match value {
+                None => Ok(None),
+                Some(value) => names
+                    .iter()
+                    .position(|n| *n == value)
+                    .map(|i| Some(i as u8))
+                    .ok_or_else(|| invalid(format!("unknown {} '{}' in risk factor '{}'", field, value, spec.key))),
+            };
+            let condition = RiskCondition::Match(RiskMatch {
+                service_idx: lookup(&SERVICE_NAMES, spec.service.clone(), "service")?,
+                vendor_idx: lookup(&VENDOR_NAMES, spec.vendor.clone(), "vendor")?,
+                model_idx: lookup(&MODEL_NAMES, spec.model.clone(), "model")?,
+                deployment_idx: lookup(&DEPLOYMENT_NAMES, spec.deployment.clone(), "deployment")?,
+                department_idx: lookup(&DEPARTMENT_NAMES, spec.department.clone(), "department")?,
+                min_sensitivity: spec.min_sensitivity,
+                max_sensitivity: spec.max_sensitivity,
+            });
+            registry
+                .register(RiskFactor {
+                    name: spec.name.unwrap_or_else(|| spec.key.clone()),
+                    key: spec.key,
+                    weight: spec.weight,
+                    condition,
+                })
+                .map_err(invalid)?;
+        }
+        Ok(registry)
+    }
+
+    /// Registers a new risk factor and returns its id.
+    pub fn register(&mut self, factor: RiskFactor) -> std::result::Result<RiskFactorId, String> {
+        if self.factors.len() >= MAX_RISK_FACTORS {
+            return Err(format!("at most {} risk factors are supported", MAX_RISK_FACTORS));
+        }
+        if self.id(&factor.key).is_some() {
+            return Err(format!("duplicate risk factor '{}'", factor.key));
+        }
+        self.factors.push(factor);
+        Ok(RiskFactorId((self.factors.len() - 1) as u8))
+    }
+
+    /// Looks up a factor id by its key.
+    pub fn id(&self, key: &str) -> Option<RiskFactorId> {
+        self.factors.iter().position(|f| f.key == key).map(|i| RiskFactorId(i as u8))
+    }
+
+    /// Iterates over all factors with their ids, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (RiskFactorId, &RiskFactor)> {
+        self.factors.iter().enumerate().map(|(i, f)| (RiskFactorId(i as u8), f))
+    }
+
+    /// Returns the number of registered factors.
+    pub fn len(&self) -> usize {
+        self.factors.len()
+    }
+
+    /// Returns true if there are no registered factors.
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a factor matching every event.
+    fn factor(key: &str) -> RiskFactor {
+        RiskFactor {
+            key: key.to_string(),
+            name: key.to_string(),
+            weight: 1,
+            condition: RiskCondition::Match(RiskMatch::default()),
+        }
+    }
+
+    /// Loads a risk factor file with the given contents.
+    fn load(name: &str, contents: &str) -> Result<RiskFactorRegistry> {
+        let path = std::env::temp_dir().join(format!("risk-{}-{}.json", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        let registry = RiskFactorRegistry::load(&path);
+        fs::remove_file(&path).unwrap();
+        registry
+    }
+
+    #[test]
+    fn factors_claim_consecutive_bits_up_to_the_limit() {
+        let mut registry = RiskFactorRegistry::builtin();
+        let builtins = registry.len();
+        let id = registry.register(factor("first")).unwrap();
+        assert_eq!((id, id.bit()), (RiskFactorId(builtins as u8), 1 << builtins));
+        assert_eq!(registry.id("first"), Some(id));
+        assert_eq!(registry.register(factor("first")).unwrap_err(), "duplicate risk factor 'first'");
+
+        for i in registry.len()..MAX_RISK_FACTORS {
+            registry.register(factor(&format!("factor_{}", i))).unwrap();
+        }
+        let last = registry.id(&format!("factor_{}", MAX_RISK_FACTORS - 1)).unwrap();
+        assert_eq!(last.bit(), 1 << 63);
+        assert_eq!(registry.register(factor("one_too_many")).unwrap_err(), "at most 64 risk factors are supported");
+
+        let service = AIService { name_idx: 1, vendor_idx: 1, model_idx: 0 };
+        let usage = Usage { department_idx: 0, data_sensitivity: 90 };
+        let status = ComplianceStatus { flags: EU_AI_ACT.bit() | INTERNAL_POLICY.bit(), waived: 0 };
+        let flags = registry
+            .iter()
+            .filter(|(_, factor)| factor.applies(&service, &usage, &status))
+            .fold(0, |flags, (id, _)| flags | id.bit());
+        // Every factor but EU Act, Internal, and Public Model applies.
+        let excluded = ["eu_act", "internal", "public_model"].map(|key| registry.id(key).unwrap().bit());
+        assert_eq!(flags, !(excluded[0] | excluded[1] | excluded[2]));
+    }
+
+    #[test]
+    fn factor_files_extend_the_builtins_and_reject_invalid_definitions() {
+        let registry = load(
+            "valid",
+            r#"[{"key": "legal_claude", "weight": 15, "service": "Claude", "department": "Legal"}]"#,
+        )
+        .unwrap();
+        let id = registry.id("legal_claude").unwrap();
+        let (_, factor) = registry.iter().nth(id.0 as usize).unwrap();
+        assert_eq!((factor.name.as_str(), factor.weight), ("legal_claude", 15));
+        let service = AIService { name_idx: 1, vendor_idx: 1, model_idx: 0 };
+        let status = ComplianceStatus { flags: u64::MAX, waived: 0 };
+        assert!(factor.applies(&service, &Usage { department_idx: 4, data_sensitivity: 0 }, &status));
+        assert!(!factor.applies(&service, &Usage { department_idx: 3, data_sensitivity: 0 }, &status));
+
+        let error = |name: &str, contents: &str| load(name, contents).unwrap_err().to_string();
+        assert!(error("weight", r#"[{"key": "heavy", "weight": 300}]"#).contains("invalid value: integer `300`"));
+        assert!(error("field", r#"[{"key": "odd", "weight": 1, "colour": "red"}]"#).contains("unknown field `colour`"));
+        assert!(error("duplicate", r#"[{"key": "gdpr", "weight": 1}]"#).ends_with("duplicate risk factor 'gdpr'"));
+        assert!(error("service", r#"[{"key": "odd", "weight": 1, "service": "Bard"}]"#)
+            .ends_with("unknown service 'Bard' in risk factor 'odd'"));
+    }
+}
@@ -0,0 +1,280 @@
+use crate::frontend::TailLatency;
+use crate::profile::system_latencies;
+use crate::tail_latency::run_batch_latency;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Sink name used in error reports when saving the run result.
+pub const RUN_RESULT_SINK: &str = "run result";
+
+/// Pipeline stages whose time is measured for the final report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Generating synthetic events.
+    Generate,
+    /// Parsing the records of a replayed event log.
+    Parse,
+    /// Scanning the prompts of replayed events for personal data.
+    Classify,
+    /// Running the compliance systems over a batch.
+    Evaluate,
+    /// Handing generated and evaluated events to the event log, audit log, and Parquet sinks.
+    Sinks,
+    /// Evaluating a batch again under the candidate policy of shadow mode.
+    Shadow,
+}
+
+const STAGES: [Stage; 6] = [Stage::Generate, Stage::Parse, Stage::Classify, Stage::Evaluate, Stage::Sinks, Stage::Shadow];
+
+/// Time spent in each stage, summed across all threads, in nanoseconds.
+static STAGE_NANOS: [AtomicU64; STAGES.len()] = [const { AtomicU64::new(0) }; STAGES.len()];
+
+impl Stage {
+    /// Returns the name used in the report.
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Generate => "generate",
+            Stage::Parse => "parse",
+            Stage::Classify => "classify",
+            Stage::Evaluate => "evaluate",
+            Stage::Sinks => "sinks",
+            Stage::Shadow => "shadow",
+        }
+    }
+
+    fn nanos(self) -> &'static AtomicU64 {
+        &STAGE_NANOS[self as usize]
+    }
+}
+
+/// Runs `f` and adds its duration to `stage`.
+pub fn time_stage<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let value = f();
+    record_stage(stage, started.elapsed());
+    value
+}
+
+/// Adds `elapsed` to the time spent in `stage`.
+pub fn record_stage(stage: Stage, elapsed: Duration) {
+    stage.nanos().fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+}
+
+/// Time spent in a single stage.
+#[derive(Debug, Serialize)]
+pub struct StageTime {
+    pub stage: &'static str,
+    /// Summed across all threads, so it can exceed the wall-clock duration.
+    pub seconds: f64,
+    /// Share of the time spent in all measured stages.
+    pub share_percent: f64,
+}
+
+/// Per-batch latency of a single ECS system over the whole run.
+#[derive(Debug, Serialize)]
+pub struct SystemLatencyReport {
+    pub system: &'static str,
+    pub batches: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p999_ms: f64,
+}
+
+/// Batch latency of all local workers over the whole run, from their HDR histograms.
+#[derive(Debug, Serialize)]
+pub struct BatchLatencyReport {
+    pub batches: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p999_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Resource usage and throughput of a whole run, printed on shutdown and saved as `--run-result`.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    /// `live`, `replay`, or `worker`.
+    pub mode: &'static str,
+    pub duration_seconds: f64,
+    /// Events evaluated during this run, excluding any restored from a snapshot.
+    pub events_processed: u64,
+    /// Requested rate in events per second; absent for replays.
+    pub target_rate: Option<u32>,
+    pub achieved_rate: f64,
+    /// Achieved rate as a percentage of the target.
+    pub target_attainment_percent: Option<f64>,
+    /// Peak resident set size of the process; absent where it cannot be measured.
+    pub peak_rss_bytes: Option<u64>,
+    pub user_cpu_seconds: Option<f64>,
+    pub system_cpu_seconds: Option<f64>,
+    pub stages: Vec<StageTime>,
+    /// Systems that evaluated at least one batch in this process.
+    pub systems: Vec<SystemLatencyReport>,
+    /// Absent if the aggregator collected no batch latencies, as in worker mode.
+    pub batch_latency: Option<BatchLatencyReport>,
+}
+
+impl RunReport {
+    /// Captures the report at the end of a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Name of the mode that ran.
+    /// * `duration` - Wall-clock time from the start of processing to shutdown.
+    /// * `events_processed` - Events evaluated during this run.
+    /// * `target_rate` - Requested rate in events per second, if there is one.
+    pub fn capture(mode: &'static str, duration: Duration, events_processed: u64, target_rate: Option<u32>) -> Self {
+        let achieved_rate = events_processed as f64 / duration.as_secs_f64().max(f64::EPSILON);
+        let usage = resource_usage();
+        let stage_seconds: Vec<_> = STAGES
+            .iter()
+            .map(|stage| (stage.name(), Duration::from_nanos(stage.nanos().load(Ordering::Relaxed)).as_secs_f64()))
+            .filter(|&(_, seconds)| seconds > 0.0)
+            .collect();
+        let total_stage_seconds: f64 = stage_seconds.iter().map(|&(_, seconds)| seconds).sum();
+        RunReport {
+            mode,
+            duration_seconds: duration.as_secs_f64(),
+            events_processed,
+            target_rate,
+            achieved_rate,
+            target_attainment_percent: target_rate
+                .filter(|&rate| rate > 0)
+                .map(|rate| achieved_rate / rate as f64 * 100.0),
+            peak_rss_bytes: usage.map(|u| u.peak_rss_bytes),
+            user_cpu_seconds: usage.map(|u| u.user_cpu.as_secs_f64()),
+            system_cpu_seconds: usage.map(|u| u.system_cpu.as_secs_f64()),
+            stages: stage_seconds
+                .into_iter()
+                .map(|(stage, seconds)| StageTime {
+                    stage,
+                    seconds,
+                    share_percent: seconds / total_stage_seconds * 100.0,
+                })
+                .collect(),
+            systems: system_latencies()
+                .into_iter()
+                .filter_map(|(system, histogram)| {
+                    TailLatency::from_histogram(&histogram).map(|latency| SystemLatencyReport {
+                        system: system.name(),
+                        batches: latency.batches,
+                        p50_ms: latency.p50.as_secs_f64() * 1000.0,
+                        p95_ms: latency.p95.as_secs_f64() * 1000.0,
+                        p999_ms: latency.p999.as_secs_f64() * 1000.0,
+                    })
+                })
+                .collect(),
+            batch_latency: TailLatency::from_histogram(&run_batch_latency()).map(|latency| BatchLatencyReport {
+                batches: latency.batches,
+                p50_ms: latency.p50.as_secs_f64() * 1000.0,
+                p95_ms: latency.p95.as_secs_f64() * 1000.0,
+                p999_ms: latency.p999.as_secs_f64() * 1000.0,
+                max_ms: latency.max.as_secs_f64() * 1000.0,
+            }),
+        }
+    }
+
+    /// Prints the report as the final lines of the run.
+    pub fn print(&self) {
+        println!("Run summary ({}):", self.mode);
+        println!("  Duration: {:.1} s", self.duration_seconds);
+        let mut rate = format!("{:.1} events/s", self.achieved_rate);
+        if let (Some(target), Some(attainment)) = (self.target_rate, self.target_attainment_percent) {
+            rate.push_str(&format!(" (target {} events/s, {:.1}%)", target, attainment));
+        }
+        println!("  Events: {}, achieved {}", self.events_processed, rate);
+        if let Some(bytes) = self.peak_rss_bytes {
+            println!("  Peak RSS: {:.1} MiB", bytes as f64 / (1024.0 * 1024.0));
+        }
+        if let (Some(user), Some(system)) = (self.user_cpu_seconds, self.system_cpu_seconds) {
+            let cores = (user + system) / self.duration_seconds.max(f64::EPSILON);
+            println!("  CPU time: {:.1} s user, {:.1} s system ({:.2} cores on average)", user, system, cores);
+        }
+        if !self.stages.is_empty() {
+            let stages: Vec<_> = self
+                .stages
+                .iter()
+                .map(|s| format!("{} {:.1} s ({:.0}%)", s.stage, s.seconds, s.share_percent))
+                .collect();
+            println!("  Stage time (summed across threads): {}", stages.join(", "));
+        }
+        if !self.systems.is_empty() {
+            let systems: Vec<_> = self
+                .systems
+                .iter()
+                .map(|s| format!("{} {:.3}/{:.3}/{:.3} ms", s.system, s.p50_ms, s.p95_ms, s.p999_ms))
+                .collect();
+            println!("  System latency per batch (p50/p95/p99.9): {}", systems.join(", "));
+        }
+        if let Some(l) = &self.batch_latency {
+            println!(
+                "  Batch latency ({} batches): p50 {:.3} ms, p95 {:.3} ms, p99.9 {:.3} ms, max {:.3} ms",
+                l.batches, l.p50_ms, l.p95_ms, l.p999_ms, l.max_ms
+            );
+        }
+    }
+
+    /// Writes the report as pretty-printed JSON to `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+}
+
+/// Process-wide resource usage reported by the operating system.
+#[derive(Clone, Copy)]
+struct ResourceUsage {
+    peak_rss_bytes: u64,
+    user_cpu: Duration,
+    system_cpu: Duration,
+}
+
+/// Reads the peak RSS and CPU time of the process so far.
+#[cfg(unix)]
+fn resource_usage() -> Option<ResourceUsage> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: `getrusage` fully initializes `usage` when it returns 0.
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+        usage.assume_init()
+    };
+    let to_duration = |tv: libc::timeval| Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
+    // Linux reports the peak RSS in kilobytes, macOS in bytes.
+    let rss_unit = if cfg!(target_os = "macos") { 1 } else { 1024 };
+    Some(ResourceUsage {
+        peak_rss_bytes: usage.ru_maxrss as u64 * rss_unit,
+        user_cpu: to_duration(usage.ru_utime),
+        system_cpu: to_duration(usage.ru_stime),
+    })
+}
+
+/// Returns the current resident set size of the process.
+#[cfg(target_os = "linux")]
+pub fn current_rss_bytes() -> Option<u64> {
+    // The second field of statm is the number of resident pages.
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    // SAFETY: `sysconf` has no preconditions.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    u64::try_from(page_size).ok().map(|page_size| resident_pages * page_size)
+}
+
+/// The current resident set size is only measured on Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn current_rss_bytes() -> Option<u64> {
+    None
+}
+
+/// Resource usage is only measured on Unix.
+#[cfg(not(unix))]
+fn resource_usage() -> Option<ResourceUsage> {
+    None
+}
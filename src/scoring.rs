@@ -0,0 +1,105 @@
+use crate::compliance::ComplianceFlag;
+use crate::metrics::ComplianceMetrics;
+use serde::Deserialize;
+
+/// How the compliance checks of events are combined into a single compliance percentage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoringMode {
+    /// The share of checks passed, every check counting equally.
+    #[default]
+    CheckAverage,
+    /// The share of events that passed every check.
+    AnyViolation,
+    /// The share of checks passed, each check counting as much as its weight.
+    Weighted,
+    /// The share of events that passed the least compliant check.
+    PerRegulation,
+}
+
+impl ScoringMode {
+    /// Returns the name of the mode as written in a policy file.
+    pub fn name(self) -> &'static str {
+        match self {
+            ScoringMode::CheckAverage => "check-average",
+            ScoringMode::AnyViolation => "any-violation",
+            ScoringMode::Weighted => "weighted",
+            ScoringMode::PerRegulation => "per-regulation",
+        }
+    }
+
+    /// Returns a short description of the mode for the dashboard.
+    pub fn label(self) -> &'static str {
+        match self {
+            ScoringMode::CheckAverage => "check average",
+            ScoringMode::AnyViolation => "any violation",
+            ScoringMode::Weighted => "weighted",
+            ScoringMode::PerRegulation => "worst check",
+        }
+    }
+}
+
+/// The scoring mode of the policy, with the weight of each check for the weighted mode.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ComplianceScoring {
+    pub mode: ScoringMode,
+    /// Weight of each check, indexed by compliance flag; checks without one weigh 1.
+    pub weights: Vec<f64>,
+}
+
+impl ComplianceScoring {
+    /// Returns the compliance percentage of the metrics under this scoring mode.
+    pub fn score(&self, metrics: &ComplianceMetrics) -> f64 {
+        let checks = metrics.violation_counts.len();
+        match self.mode {
+            ScoringMode::CheckAverage => metrics.compliance_percentage(),
+            ScoringMode::AnyViolation => {
+                if metrics.total_events == 0 {
+                    return 100.0;
+                }
+                (100.0 * (1.0 - metrics.violating_events as f64 / metrics.total_events as f64)).clamp(0.0, 100.0)
+            }
+            ScoringMode::Weighted => {
+                let weight = |check: usize| self.weights.get(check).copied().unwrap_or(1.0);
+                let total_weight: f64 = (0..checks).map(weight).sum();
+                if metrics.total_events == 0 || total_weight == 0.0 {
+                    return 100.0;
+                }
+                let failed: f64 = (0..checks).map(|check| weight(check) * metrics.violation_counts[check] as f64).sum();
+                (100.0 * (1.0 - failed / (metrics.total_events as f64 * total_weight))).clamp(0.0, 100.0)
+            }
+            ScoringMode::PerRegulation => (0..checks)
+                .map(|check| metrics.check_compliance_percentage(ComplianceFlag(check as u8)))
+                .fold(100.0, f64::min),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_combine_the_checks_differently() {
+        // 100 events: 20 fail the first check, 10 of them also the second, and none the third.
+        let metrics = ComplianceMetrics {
+            total_events: 100,
+            violation_counts: vec![20, 10, 0],
+            violating_events: 20,
+            ..ComplianceMetrics::default()
+        };
+        let score = |mode, weights: Vec<f64>| ComplianceScoring { mode, weights }.score(&metrics);
+        assert_eq!(score(ScoringMode::CheckAverage, Vec::new()), 90.0);
+        assert_eq!(score(ScoringMode::AnyViolation, Vec::new()), 80.0);
+        assert_eq!(score(ScoringMode::PerRegulation, Vec::new()), 80.0);
+        // Without weights, every check weighs the same.
+        assert_eq!(score(ScoringMode::Weighted, Vec::new()), 90.0);
+        assert_eq!(score(ScoringMode::Weighted, vec![3.0, 1.0, 0.0]), 82.5);
+        assert_eq!(score(ScoringMode::Weighted, vec![0.0, 0.0, 0.0]), 100.0);
+
+        let empty = ComplianceMetrics::default();
+        for mode in [ScoringMode::CheckAverage, ScoringMode::AnyViolation, ScoringMode::Weighted, ScoringMode::PerRegulation] {
+            assert_eq!(ComplianceScoring { mode, weights: Vec::new() }.score(&empty), 100.0);
+        }
+    }
+}
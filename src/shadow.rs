@@ -0,0 +1,212 @@
+use crate::components::Event;
+use crate::ecs::{process_batch, Parallelism};
+use crate::metrics::ComplianceMetrics;
+use crate::policy::Policy;
+use hecs::World;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A candidate policy evaluated in shadow mode next to the active one, with the violations each
+/// of them found in the events both evaluated.
+///
+/// The candidate's verdicts are only counted: they never reach the metrics, sinks, or alerts.
+#[derive(Debug)]
+pub struct ShadowPolicy {
+    candidate: Arc<Policy>,
+    events: AtomicU64,
+    /// Violations under the active policy, indexed by its compliance flags.
+    active_violations: Vec<AtomicU64>,
+    /// Violations under the candidate policy, indexed by its compliance flags.
+    candidate_violations: Vec<AtomicU64>,
+}
+
+impl ShadowPolicy {
+    /// Starts counting with no events evaluated.
+    ///
+    /// # Arguments
+    ///
+    /// * `active` - The active policy, whose checks stay the same across reloads.
+    /// * `candidate` - The policy to compare with it.
+    pub fn new(active: &Policy, candidate: Arc<Policy>) -> Self {
+        let counters = |policy: &Policy| (0..policy.compliance.len()).map(|_| AtomicU64::new(0)).collect();
+        ShadowPolicy {
+            active_violations: counters(active),
+            candidate_violations: counters(&candidate),
+            candidate,
+            events: AtomicU64::new(0),
+        }
+    }
+
+    /// The candidate policy.
+    pub fn candidate(&self) -> &Arc<Policy> {
+        &self.candidate
+    }
+
+    /// Evaluates a batch, already evaluated under the active policy, under the candidate policy
+    /// and counts the violations of both.
+    ///
+    /// # Arguments
+    ///
+    /// * `world` - ECS world holding only entities evaluated under the candidate policy.
+    /// * `events` - The events of the batch.
+    /// * `active` - Metrics of the batch under the active policy.
+    /// * `parallelism` - How the systems iterate over the batch.
+    pub fn evaluate(&self, world: &mut World, events: &[Event], active: &ComplianceMetrics, parallelism: Parallelism) {
+        let candidate = process_batch(world, events, &self.candidate, parallelism, None);
+        self.events.fetch_add(events.len() as u64, Ordering::Relaxed);
+        for (counters, metrics) in [(&self.active_violations, active), (&self.candidate_violations, &candidate)] {
+            for (counter, violations) in counters.iter().zip(&metrics.violation_counts) {
+                counter.fetch_add(*violations, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Compares the violations of each check under both policies, listing the active policy's
+    /// checks first and then those only the candidate claims.
+    ///
+    /// # Arguments
+    ///
+    /// * `active` - The active policy, labelling its checks.
+    pub fn comparison(&self, active: &Policy) -> PolicyComparison {
+        let count = |counters: &[AtomicU64], index: usize| counters.get(index).map(|counter| counter.load(Ordering::Relaxed));
+        let candidate_flag = |key: &str| self.candidate.compliance.iter().find(|(_, info)| info.key == key).map(|(flag, _)| flag);
+        let mut rules: Vec<_> = active
+            .compliance
+            .iter()
+            .map(|(flag, info)| RuleComparison {
+                key: info.key.to_string(),
+                name: info.name.to_string(),
+                active: count(&self.active_violations, flag.0 as usize),
+                candidate: candidate_flag(info.key).and_then(|flag| count(&self.candidate_violations, flag.0 as usize)),
+            })
+            .collect();
+        for (flag, info) in self.candidate.compliance.iter() {
+            if !rules.iter().any(|rule| rule.key == info.key) {
+                rules.push(RuleComparison {
+                    key: info.key.to_string(),
+                    name: info.name.to_string(),
+                    active: None,
+                    candidate: count(&self.candidate_violations, flag.0 as usize),
+                });
+            }
+        }
+        PolicyComparison { events: self.events.load(Ordering::Relaxed), rules }
+    }
+}
+
+/// Violations of each check under the active and the candidate policy, over the same events.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyComparison {
+    /// Events evaluated under both policies.
+    pub events: u64,
+    pub rules: Vec<RuleComparison>,
+}
+
+/// Violations of one check under the active and the candidate policy.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuleComparison {
+    pub key: String,
+    pub name: String,
+    /// Violations under the active policy; `None` if it does not claim the check.
+    pub active: Option<u64>,
+    /// Violations under the candidate policy; `None` if it does not claim the check.
+    pub candidate: Option<u64>,
+}
+
+impl RuleComparison {
+    /// Change in violations if the candidate policy were enforced; a check one of the policies
+    /// does not claim counts as having no violations under it.
+    pub fn delta(&self) -> i64 {
+        self.candidate.unwrap_or(0) as i64 - self.active.unwrap_or(0) as i64
+    }
+}
+
+impl PolicyComparison {
+    /// Change in violations over all checks if the candidate policy were enforced.
+    pub fn total_delta(&self) -> i64 {
+        self.rules.iter().map(RuleComparison::delta).sum()
+    }
+
+    /// Renders the violations of each check under both policies, ending with a newline.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Candidate policy comparison over {} events:", self.events);
+        let _ = writeln!(out, "  {:<24} {:>12} {:>12} {:>12}", "Check", "Active", "Candidate", "Delta");
+        let count = |violations: Option<u64>| violations.map_or_else(|| "-".to_string(), |violations| violations.to_string());
+        for rule in &self.rules {
+            let _ = writeln!(
+                out,
+                "  {:<24} {:>12} {:>12} {:>+12}",
+                rule.name,
+                count(rule.active),
+                count(rule.candidate),
+                rule.delta()
+            );
+        }
+        let _ = writeln!(out, "  {:<24} {:>12} {:>12} {:>+12}", "Total", "", "", self.total_delta());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{AIService, LawfulBasis, PiiTypes, Purpose, Region, Timestamp, Usage, UserId};
+    use crate::compliance::{GDPR, INTERNAL_POLICY};
+    use crate::constants::{HEALTHCARE_DEPARTMENT, MODELS_PER_SERVICE};
+    use crate::policy::HipaaRules;
+    use crate::waivers::Waiver;
+
+    #[test]
+    fn candidate_policies_are_compared_check_by_check_on_the_same_events() {
+        let active = Policy::builtin();
+        // The candidate enables HIPAA and waives Finance's use of ChatGPT.
+        let mut candidate = Policy::builtin();
+        let hipaa = candidate.compliance.claim("hipaa", "HIPAA").unwrap();
+        candidate.hipaa = Some(HipaaRules { flag: hipaa, baa_vendors: 0b0_1111 });
+        let (finance, chatgpt, stability) = (2, 0, 4);
+        candidate.waivers = vec![Waiver {
+            department: finance,
+            service: Some(chatgpt),
+            checks: INTERNAL_POLICY.bit(),
+            expires: "2999-01-01".to_string(),
+            expires_at_ms: u64::MAX,
+            ticket: "RISK-123".to_string(),
+        }];
+        let shadow = ShadowPolicy::new(&active, Arc::new(candidate));
+
+        let event = |name_idx: u8, department_idx, data_sensitivity| {
+            let service = AIService { name_idx, vendor_idx: name_idx, model_idx: name_idx * MODELS_PER_SERVICE as u8 };
+            (
+                service,
+                Usage { department_idx, data_sensitivity },
+                UserId::UNKNOWN,
+                Purpose(5),
+                Region(0),
+                LawfulBasis::UNKNOWN,
+                PiiTypes::NONE,
+                Timestamp::default(),
+            )
+        };
+        let events = [event(stability, HEALTHCARE_DEPARTMENT, 80), event(chatgpt, finance, 30), event(1, 0, 60)];
+        let (mut active_world, mut shadow_world) = (World::new(), World::new());
+        for _ in 0..2 {
+            let metrics = process_batch(&mut active_world, &events, &active, Parallelism::Sequential, None);
+            shadow.evaluate(&mut shadow_world, &events, &metrics, Parallelism::Sequential);
+        }
+
+        let comparison = shadow.comparison(&active);
+        assert_eq!(comparison.events, 6);
+        assert_eq!(comparison.rules.len(), active.compliance.len() + 1);
+        let gdpr = &comparison.rules[GDPR.0 as usize];
+        assert_eq!((gdpr.active, gdpr.delta()), (Some(4), 0));
+        let internal = &comparison.rules[INTERNAL_POLICY.0 as usize];
+        assert_eq!((internal.active, internal.candidate), (Some(2), Some(0)));
+        let hipaa = comparison.rules.last().unwrap();
+        assert_eq!((hipaa.key.as_str(), hipaa.active, hipaa.candidate), ("hipaa", None, Some(2)));
+        assert_eq!(comparison.total_delta(), 0);
+        assert!(comparison.render().contains("HIPAA"), "{}", comparison.render());
+    }
+}
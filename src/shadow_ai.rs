@@ -0,0 +1,134 @@
+use crate::constants::{DEPARTMENT_NAMES, MODELS_PER_SERVICE, MODEL_NAMES, SERVICE_NAMES};
+use crate::metrics::{ComplianceMetrics, CumulativeMetrics, IntervalMetrics};
+use crate::policy::Policy;
+use crate::sink::Sink;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the shadow AI report file in error reports.
+pub const SHADOW_AI_SINK: &str = "shadow AI report";
+
+/// A model used without approval: how often, by which departments, and on how sensitive data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadowAiUse {
+    pub service: &'static str,
+    pub model: &'static str,
+    /// Shadow AI events using the model.
+    pub events: u64,
+    /// Average data sensitivity of those events.
+    pub avg_sensitivity: f64,
+    /// Shadow AI events by department, indexed like `DEPARTMENT_NAMES`.
+    pub department_counts: [u64; 6],
+}
+
+impl ShadowAiUse {
+    /// Returns the departments using the model without approval, with their events, most first.
+    pub fn departments(&self) -> Vec<(&'static str, u64)> {
+        let mut departments: Vec<(&'static str, u64)> = DEPARTMENT_NAMES
+            .into_iter()
+            .zip(self.department_counts)
+            .filter(|(_, events)| *events > 0)
+            .collect();
+        departments.sort_by_key(|(_, events)| std::cmp::Reverse(*events));
+        departments
+    }
+}
+
+/// Lists the models found in shadow AI events, most used first.
+///
+/// # Arguments
+///
+/// * `metrics` - Metrics with a breakdown by model, such as the cumulative metrics.
+pub fn shadow_ai_uses(metrics: &ComplianceMetrics) -> Vec<ShadowAiUse> {
+    let mut uses: Vec<ShadowAiUse> = metrics
+        .models
+        .iter()
+        .enumerate()
+        .filter(|(_, model)| model.shadow_ai_events > 0)
+        .map(|(index, model)| ShadowAiUse {
+            service: SERVICE_NAMES[index / MODELS_PER_SERVICE],
+            model: MODEL_NAMES[index],
+            events: model.shadow_ai_events,
+            avg_sensitivity: model.shadow_ai_sensitivity as f64 / model.shadow_ai_events as f64,
+            department_counts: model.shadow_ai_department_counts,
+        })
+        .collect();
+    uses.sort_by_key(|shadow_ai_use| std::cmp::Reverse(shadow_ai_use.events));
+    uses
+}
+
+/// Writes the shadow AI report as CSV to `path`, one row per model with its events, average
+/// sensitivity, and events per department. The file is replaced atomically, so readers never
+/// see a partial report.
+///
+/// # Arguments
+///
+/// * `path` - File to write.
+/// * `uses` - The report's rows, as returned by [`shadow_ai_uses`].
+pub fn write_shadow_ai_report(path: &Path, uses: &[ShadowAiUse]) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    writeln!(writer, "service,model,events,avg_sensitivity,{}", DEPARTMENT_NAMES.join(","))?;
+    for shadow_ai_use in uses {
+        let departments: Vec<String> = shadow_ai_use.department_counts.iter().map(u64::to_string).collect();
+        writeln!(
+            writer,
+            "{},{},{},{:.1},{}",
+            shadow_ai_use.service,
+            shadow_ai_use.model,
+            shadow_ai_use.events,
+            shadow_ai_use.avg_sensitivity,
+            departments.join(",")
+        )?;
+    }
+    writer.flush()?;
+    drop(writer);
+    fs::rename(&tmp_path, path)
+}
+
+/// Sink rewriting the shadow AI report of the whole run every reporting interval.
+pub struct ShadowAiReportSink {
+    pub path: PathBuf,
+}
+
+impl Sink for ShadowAiReportSink {
+    fn name(&self) -> &'static str {
+        SHADOW_AI_SINK
+    }
+
+    fn write(&self, _interval: &IntervalMetrics, total: &CumulativeMetrics, _policy: &Policy) -> io::Result<()> {
+        write_shadow_ai_report(&self.path, &shadow_ai_uses(total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn models_are_listed_by_shadow_ai_events() {
+        let model = |events, sensitivity, department_counts| ComplianceMetrics {
+            shadow_ai_events: events,
+            shadow_ai_sensitivity: sensitivity,
+            shadow_ai_department_counts: department_counts,
+            ..ComplianceMetrics::default()
+        };
+        let mut metrics = ComplianceMetrics { models: vec![ComplianceMetrics::default(); MODEL_NAMES.len()], ..ComplianceMetrics::default() };
+        metrics.models[0] = model(4, 200, [0, 0, 3, 0, 1, 0]);
+        metrics.models[5] = model(10, 300, [0, 0, 10, 0, 0, 0]);
+
+        let uses = shadow_ai_uses(&metrics);
+        assert_eq!(uses.len(), 2);
+        assert_eq!((uses[0].service, uses[0].model, uses[0].events, uses[0].avg_sensitivity), ("Gemini", "Gemini Pro", 10, 30.0));
+        assert_eq!(uses[1].departments(), [("Finance", 3), ("Legal", 1)]);
+
+        let path = std::env::temp_dir().join(format!("shadow-ai-test-{}.csv", std::process::id()));
+        write_shadow_ai_report(&path, &uses).unwrap();
+        let report = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "service,model,events,avg_sensitivity,Engineering,Marketing,Finance,HR,Legal,Healthcare");
+        assert_eq!(lines[2], "ChatGPT,GPT-4o,4,50.0,0,0,3,0,1,0");
+    }
+}
@@ -0,0 +1,409 @@
+use crate::audit::AuditRecord;
+use crate::clock::format_utc_timestamp;
+use crate::error::{report, with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use clap::ValueEnum;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde_json::{json, Value};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Sink name used in error reports when forwarding violations to a SIEM.
+pub const SIEM_SINK: &str = "SIEM forwarder";
+
+/// Sink name used in error reports when writing undeliverable violations to the dead-letter file.
+pub const DEAD_LETTER_SINK: &str = "SIEM dead-letter file";
+
+/// Elasticsearch index violations are written to unless `--siem-index` names another.
+pub const DEFAULT_ELASTICSEARCH_INDEX: &str = "ai-compliance-violations";
+
+/// Splunk source and sourcetype of forwarded violations.
+const SPLUNK_SOURCE: &str = "ecs_ai_compliance";
+const SPLUNK_SOURCETYPE: &str = "ai_compliance:violation";
+
+/// Violation batches buffered before workers block on the forwarder.
+pub const SIEM_BUFFER: usize = 64;
+
+/// Longest a violation waits to be forwarded when fewer than a full batch arrive.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Timeout of a single request to the SIEM.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The SIEM endpoint's ingestion API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SiemFormat {
+    /// Splunk HTTP Event Collector (`/services/collector/event`).
+    SplunkHec,
+    /// Elasticsearch or OpenSearch bulk API (`/_bulk`).
+    Elasticsearch,
+}
+
+/// Where and how violations are forwarded.
+#[derive(Clone, Debug)]
+pub struct SiemConfig {
+    pub format: SiemFormat,
+    /// Endpoint URL the batches are posted to.
+    pub url: String,
+    /// Splunk HEC token or Elasticsearch API key, if the endpoint requires one.
+    pub token: Option<String>,
+    /// Index the violations are written to; Splunk uses the token's default index if unset.
+    pub index: Option<String>,
+    /// Most violations posted in one request.
+    pub batch_size: usize,
+    /// NDJSON file that violations are appended to when they cannot be delivered.
+    pub dead_letter: PathBuf,
+}
+
+impl SiemConfig {
+    /// Builds the configuration from the `--siem-*` arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The endpoint's ingestion API.
+    /// * `url` - Endpoint URL; must use HTTP or HTTPS.
+    /// * `token_file` - File holding the HEC token or API key, if the endpoint requires one.
+    /// * `index` - Index to write to, overriding the default.
+    /// * `batch_size` - Most violations posted in one request.
+    /// * `dead_letter` - File that undeliverable violations are appended to.
+    pub fn new(
+        format: SiemFormat,
+        url: &str,
+        token_file: Option<&Path>,
+        index: Option<&str>,
+        batch_size: usize,
+        dead_letter: &Path,
+    ) -> Result<Self> {
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(MonitorError::Config("SIEM URL must be an http:// or https:// URL".to_string()));
+        }
+        if batch_size == 0 {
+            return Err(MonitorError::Config("SIEM batch size must be at least 1".to_string()));
+        }
+        let token = token_file
+            .map(|path| {
+                let token = fs::read_to_string(path)
+                    .map_err(|e| MonitorError::Config(format!("{}: {}", path.display(), e)))?;
+                match token.trim() {
+                    "" => Err(MonitorError::Config(format!("{}: SIEM token file is empty", path.display()))),
+                    token => Ok(token.to_string()),
+                }
+            })
+            .transpose()?;
+        let index = match (format, index) {
+            (_, Some(index)) => Some(index.to_string()),
+            (SiemFormat::Elasticsearch, None) => Some(DEFAULT_ELASTICSEARCH_INDEX.to_string()),
+            (SiemFormat::SplunkHec, None) => None,
+        };
+        Ok(SiemConfig {
+            format,
+            url: url.to_string(),
+            token,
+            index,
+            batch_size,
+            dead_letter: dead_letter.to_path_buf(),
+        })
+    }
+}
+
+/// Forwards violation records to the SIEM until every sender is dropped.
+///
+/// Records are posted in batches of up to `batch_size`, or after [`FLUSH_INTERVAL`] if fewer
+/// arrive. Failed requests are handled according to `policy`; a batch that still cannot be
+/// delivered is appended to the dead-letter file. Under [`ErrorPolicy::Degrade`] forwarding
+/// then stops and every later record goes straight to the dead-letter file, so workers are never
+/// held up by an unreachable SIEM. Records Elasticsearch rejects individually are dead-lettered
+/// without retrying. If the dead-letter file cannot be written either, the forwarder reports the
+/// error and exits.
+///
+/// # Arguments
+///
+/// * `config` - Endpoint, credentials, batch size, and dead-letter file.
+/// * `receiver` - Channel receiving violation records from the workers.
+/// * `policy` - How to handle failed requests.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn siem_forwarder(
+    config: SiemConfig,
+    receiver: Receiver<Vec<AuditRecord>>,
+    policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+) {
+    let mut forwarder = Forwarder {
+        config,
+        policy,
+        errors,
+        forwarding: true,
+        dead_letter: None,
+    };
+    if let Err(e) = forwarder.run(receiver) {
+        let action = if policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+        report(&forwarder.errors, e, action);
+    }
+}
+
+/// State of the forwarder thread.
+struct Forwarder {
+    config: SiemConfig,
+    policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+    /// False once the SIEM has been given up on under [`ErrorPolicy::Degrade`].
+    forwarding: bool,
+    /// The dead-letter file, opened for appending when first needed.
+    dead_letter: Option<BufWriter<File>>,
+}
+
+impl Forwarder {
+    /// Collects records into batches and delivers them until the channel disconnects.
+    fn run(&mut self, receiver: Receiver<Vec<AuditRecord>>) -> Result<()> {
+        let mut pending = Vec::new();
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let timeout = deadline.map_or(FLUSH_INTERVAL, |deadline| deadline.saturating_duration_since(Instant::now()));
+            let disconnected = match receiver.recv_timeout(timeout) {
+                Ok(records) => {
+                    deadline.get_or_insert_with(|| Instant::now() + FLUSH_INTERVAL);
+                    pending.extend(records);
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => true,
+            };
+            let due = disconnected || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            while pending.len() >= self.config.batch_size || (due && !pending.is_empty()) {
+                let rest = pending.split_off(pending.len().min(self.config.batch_size));
+                self.deliver(&pending)?;
+                pending = rest;
+            }
+            if pending.is_empty() {
+                deadline = None;
+            }
+            if disconnected {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Posts one batch, dead-lettering whatever the SIEM does not accept.
+    fn deliver(&mut self, batch: &[AuditRecord]) -> Result<()> {
+        if !self.forwarding {
+            return self.write_dead_letter(batch.iter());
+        }
+        let body = batch_payload(&self.config, batch).map_err(|e| MonitorError::Sink {
+            sink: SIEM_SINK,
+            source: e.into(),
+        })?;
+        match with_retry(self.policy, SIEM_SINK, &self.errors, || post(&self.config, &body)) {
+            Ok(rejected) if rejected.is_empty() => Ok(()),
+            Ok(rejected) => {
+                self.write_dead_letter(rejected.iter().map(|(item, _)| &batch[*item]))?;
+                let reason = format!(
+                    "Elasticsearch rejected {} of {} violations ({}); appended them to {}",
+                    rejected.len(),
+                    batch.len(),
+                    rejected[0].1,
+                    self.config.dead_letter.display()
+                );
+                // The rest of the batch was indexed, so the SIEM itself is still usable.
+                report(&self.errors, MonitorError::Sink { sink: SIEM_SINK, source: io::Error::other(reason) }, ErrorPolicy::Retry);
+                Ok(())
+            }
+            Err(e) => {
+                self.write_dead_letter(batch.iter())?;
+                let reason = match e {
+                    MonitorError::Sink { source, .. } => source.to_string(),
+                    other => other.to_string(),
+                };
+                let source = io::Error::other(format!(
+                    "{}; appended {} violations to {}",
+                    reason,
+                    batch.len(),
+                    self.config.dead_letter.display()
+                ));
+                let action = match self.policy {
+                    // Later batches are tried again, so a passing outage only dead-letters the
+                    // batches that fell into it.
+                    ErrorPolicy::Retry => ErrorPolicy::Retry,
+                    ErrorPolicy::Degrade => {
+                        self.forwarding = false;
+                        ErrorPolicy::Degrade
+                    }
+                    ErrorPolicy::Shutdown => ErrorPolicy::Shutdown,
+                };
+                report(&self.errors, MonitorError::Sink { sink: SIEM_SINK, source }, action);
+                Ok(())
+            }
+        }
+    }
+
+    /// Appends records to the dead-letter file, one JSON object per line.
+    fn write_dead_letter<'a>(&mut self, records: impl Iterator<Item = &'a AuditRecord>) -> Result<()> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record).map_err(|e| MonitorError::Sink {
+                sink: DEAD_LETTER_SINK,
+                source: e.into(),
+            })?;
+            lines.push(b'\n');
+        }
+        let path = &self.config.dead_letter;
+        let writer = match &mut self.dead_letter {
+            Some(writer) => writer,
+            None => {
+                let file = with_retry(self.policy, DEAD_LETTER_SINK, &self.errors, || {
+                    OpenOptions::new().create(true).append(true).open(path)
+                })?;
+                self.dead_letter.insert(BufWriter::new(file))
+            }
+        };
+        with_retry(self.policy, DEAD_LETTER_SINK, &self.errors, || {
+            writer.write_all(&lines)?;
+            writer.flush()
+        })
+    }
+}
+
+/// Builds the request body for a batch of violations.
+///
+/// For Splunk HEC this is one event object per line, carrying the record as its `event`, the
+/// record's time in seconds, and the index if one is configured. For Elasticsearch it is a bulk
+/// request of an `index` action and the record with an added `@timestamp` per violation.
+///
+/// # Arguments
+///
+/// * `config` - The endpoint's format and index.
+/// * `records` - The violation records to send.
+pub fn batch_payload(config: &SiemConfig, records: &[AuditRecord]) -> serde_json::Result<String> {
+    let mut body = String::new();
+    for record in records {
+        match config.format {
+            SiemFormat::SplunkHec => {
+                let mut event = json!({
+                    "time": record.timestamp_ms as f64 / 1000.0,
+                    "source": SPLUNK_SOURCE,
+                    "sourcetype": SPLUNK_SOURCETYPE,
+                    "event": record,
+                });
+                if let Some(index) = &config.index {
+                    event["index"] = index.as_str().into();
+                }
+                body.push_str(&event.to_string());
+            }
+            SiemFormat::Elasticsearch => {
+                let mut document = serde_json::to_value(record)?;
+                document["@timestamp"] = format_utc_timestamp(record.timestamp_ms).into();
+                body.push_str(&json!({ "index": { "_index": config.index } }).to_string());
+                body.push('\n');
+                body.push_str(&document.to_string());
+            }
+        }
+        body.push('\n');
+    }
+    Ok(body)
+}
+
+/// Posts a batch and returns the items the endpoint rejected, with the reason for the first.
+fn post(config: &SiemConfig, body: &str) -> io::Result<Vec<(usize, String)>> {
+    let mut request = ureq::post(&config.url).timeout(REQUEST_TIMEOUT);
+    request = match config.format {
+        SiemFormat::SplunkHec => request.set("Content-Type", "application/json"),
+        SiemFormat::Elasticsearch => request.set("Content-Type", "application/x-ndjson"),
+    };
+    if let Some(token) = &config.token {
+        let scheme = match config.format {
+            SiemFormat::SplunkHec => "Splunk",
+            SiemFormat::Elasticsearch => "ApiKey",
+        };
+        request = request.set("Authorization", &format!("{} {}", scheme, token));
+    }
+    let response = request.send_string(body).map_err(|e| match e {
+        ureq::Error::Status(status, response) => {
+            let detail = response.into_string().unwrap_or_default();
+            io::Error::other(format!("SIEM returned HTTP {}: {}", status, detail.trim()))
+        }
+        ureq::Error::Transport(transport) => io::Error::other(transport.to_string()),
+    })?;
+    match config.format {
+        SiemFormat::SplunkHec => Ok(Vec::new()),
+        SiemFormat::Elasticsearch => rejected_items(&response.into_string()?),
+    }
+}
+
+/// Returns the items of an Elasticsearch bulk response that failed, with their error reasons.
+fn rejected_items(response: &str) -> io::Result<Vec<(usize, String)>> {
+    let response: Value = serde_json::from_str(response)?;
+    if response["errors"] != true {
+        return Ok(Vec::new());
+    }
+    let items = response["items"]
+        .as_array()
+        .ok_or_else(|| io::Error::other("bulk response reported errors without items"))?;
+    Ok(items
+        .iter()
+        .enumerate()
+        .filter_map(|(item, result)| {
+            let result = result.as_object()?.values().next()?;
+            let error = result.get("error")?;
+            let reason = error["reason"].as_str().or(error["type"].as_str()).unwrap_or("unknown error");
+            Some((item, reason.to_string()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::Provenance;
+
+    #[test]
+    fn formats_batches_and_reads_bulk_rejections() {
+        let record = AuditRecord {
+            event_id: 7,
+            timestamp_ms: 1_738_328_709_250,
+            policy_version: "test",
+            service: "ChatGPT",
+            model: "GPT-4",
+            vendor: "OpenAI",
+            department: "Finance",
+            data_sensitivity: 3,
+            user_id: None,
+            purpose: None,
+            region: None,
+            lawful_basis: None,
+            pii_types: Vec::new(),
+            ai_act_tier: None,
+            ai_act_article: None,
+            violations: vec!["gdpr"],
+            nist_categories: vec!["MAP 1.1"],
+            waived: Vec::new(),
+            risk_score: 80,
+            risk_factors: 0,
+            provenance: Provenance::default(),
+        };
+        let records = [record.clone(), record];
+        let splunk = SiemConfig::new(SiemFormat::SplunkHec, "https://splunk:8088/services/collector/event", None, None, 10, Path::new("dead.ndjson")).unwrap();
+        let body = batch_payload(&splunk, &records).unwrap();
+        let events: Vec<Value> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["time"], 1_738_328_709.25);
+        assert_eq!(events[0]["sourcetype"], SPLUNK_SOURCETYPE);
+        assert_eq!(events[0]["event"]["violations"][0], "gdpr");
+        assert!(events[0].get("index").is_none());
+
+        let elastic = SiemConfig::new(SiemFormat::Elasticsearch, "http://es:9200/_bulk", None, None, 10, Path::new("dead.ndjson")).unwrap();
+        let body = batch_payload(&elastic, &records).unwrap();
+        let lines: Vec<Value> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["index"]["_index"], DEFAULT_ELASTICSEARCH_INDEX);
+        assert_eq!(lines[1]["@timestamp"], "2025-01-31T13:05:09.250Z");
+        assert_eq!(lines[1]["event_id"], 7);
+
+        let response = r#"{"errors": true, "items": [
+            {"index": {"status": 201}},
+            {"index": {"status": 400, "error": {"type": "mapper_parsing_exception", "reason": "failed to parse"}}}
+        ]}"#;
+        assert_eq!(rejected_items(response).unwrap(), vec![(1, "failed to parse".to_string())]);
+        assert!(rejected_items(r#"{"errors": false, "items": []}"#).unwrap().is_empty());
+        assert!(SiemConfig::new(SiemFormat::SplunkHec, "splunk:8088", None, None, 10, Path::new("dead.ndjson")).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::error::{MonitorError, Result};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use std::fs;
+use std::path::Path;
+
+/// Reads an Ed25519 signing key stored as 64 hex digits (the 32-byte secret seed).
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let invalid = |message: &str| MonitorError::Config(format!("{}: {}", path.display(), message));
+    let contents = fs::read_to_string(path).map_err(|e| invalid(&e.to_string()))?;
+    let seed = from_hex(contents.trim())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| invalid("signing key must be 64 hex digits"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Parses an Ed25519 public key written as 64 hex digits.
+pub fn parse_public_key(hex: &str) -> Result<VerifyingKey> {
+    from_hex(hex)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| MonitorError::Config(format!("'{}' is not an Ed25519 public key", hex)))
+}
+
+/// Encodes bytes as lowercase hex.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes hex digits into bytes, or returns `None` if the input is not valid hex.
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect()
+}
@@ -0,0 +1,223 @@
+use crate::clock::{civil_from_days, format_utc_timestamp};
+use crate::error::{MonitorError, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Longest silence window a schedule may open, so checking whether one is open stays cheap.
+pub const MAX_SILENCE_MINUTES: u64 = 7 * 24 * 60;
+
+/// A cron schedule of five fields, minute, hour, day of month, month, and day of week, each a
+/// `*`, a value, a range `a-b`, or a comma-separated list of them, optionally with a `/step`.
+/// Times are in UTC. As in cron, a time matches when both day fields match, or either one if
+/// both are restricted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of month and day of week fields were both restricted.
+    either_day: bool,
+}
+
+impl CronSchedule {
+    /// Parses a cron expression such as `0 2 * * 6` (02:00 every Saturday).
+    pub fn parse(expression: &str) -> std::result::Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(format!("cron schedule '{}' must have five fields", expression));
+        };
+        let weekdays_bits = parse_field(weekdays, 0, 7, "day of week")?;
+        Ok(CronSchedule {
+            minutes: parse_field(minutes, 0, 59, "minute")?,
+            hours: parse_field(hours, 0, 23, "hour")?,
+            days: parse_field(days, 1, 31, "day of month")?,
+            months: parse_field(months, 1, 12, "month")?,
+            // Both 0 and 7 stand for Sunday.
+            weekdays: (weekdays_bits | weekdays_bits >> 7) & 0x7f,
+            either_day: !days.starts_with('*') && !weekdays.starts_with('*'),
+        })
+    }
+
+    /// Returns true if the schedule fires at the start of the given minute since the Unix epoch.
+    pub fn matches(&self, unix_minute: u64) -> bool {
+        let days = unix_minute / (24 * 60);
+        let (_, month, day) = civil_from_days(days);
+        // The Unix epoch fell on a Thursday.
+        let weekday = (days + 4) % 7;
+        let bit = |bits: u64, value: u64| bits & (1 << value) != 0;
+        let day_of_month = bit(self.days, day as u64);
+        let day_of_week = bit(self.weekdays, weekday);
+        let day_matches = if self.either_day { day_of_month || day_of_week } else { day_of_month && day_of_week };
+        bit(self.minutes, unix_minute % 60) && bit(self.hours, unix_minute / 60 % 24) && bit(self.months, month as u64) && day_matches
+    }
+}
+
+/// Parses one field of a cron expression into a bit per matching value.
+fn parse_field(field: &str, min: u64, max: u64, name: &str) -> std::result::Result<u64, String> {
+    let invalid = || format!("invalid {} '{}' in cron schedule", name, field);
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u64>().ok().filter(|step| *step > 0).ok_or_else(invalid)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (start.parse().map_err(|_| invalid())?, end.parse().map_err(|_| invalid())?),
+                None => {
+                    let value = range.parse().map_err(|_| invalid())?;
+                    // A single value with a step runs to the end of the field, as in `5/15`.
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// A recurring window during which alerts are recorded but not dispatched.
+#[derive(Clone, Debug)]
+pub struct SilenceWindow {
+    /// The cron expression the window opens on, as written in the file.
+    pub cron: String,
+    pub schedule: CronSchedule,
+    pub duration: Duration,
+    /// Why alerts are silenced, e.g. the planned load test.
+    pub reason: String,
+}
+
+/// A silence window as written in the silence windows file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct SilenceWindowSpec {
+    schedule: String,
+    duration_minutes: u64,
+    reason: String,
+}
+
+/// Loads the silence windows listed in a JSON file.
+///
+/// The file contains an array of windows, each with the cron `schedule` that opens it, how long
+/// it stays open in `duration_minutes`, and the `reason` alerts are silenced.
+pub fn load_silence_windows(path: &Path) -> Result<Vec<SilenceWindow>> {
+    let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+    let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let specs: Vec<SilenceWindowSpec> = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    specs
+        .into_iter()
+        .map(|spec| {
+            let schedule = CronSchedule::parse(&spec.schedule).map_err(&invalid)?;
+            if !(1..=MAX_SILENCE_MINUTES).contains(&spec.duration_minutes) {
+                return Err(invalid(format!(
+                    "silence window '{}' must last between 1 and {} minutes",
+                    spec.reason, MAX_SILENCE_MINUTES
+                )));
+            }
+            Ok(SilenceWindow {
+                cron: spec.schedule,
+                schedule,
+                duration: Duration::from_secs(spec.duration_minutes * 60),
+                reason: spec.reason,
+            })
+        })
+        .collect()
+}
+
+/// The scheduled silence windows and any one-shot silence requested through the admin API.
+#[derive(Debug, Default)]
+pub struct Silences {
+    windows: Vec<SilenceWindow>,
+    /// End of the one-shot silence in milliseconds since the Unix epoch, and its reason.
+    one_shot: Option<(u64, String)>,
+}
+
+impl Silences {
+    /// Creates the silences with the scheduled windows and no one-shot silence.
+    pub fn new(windows: Vec<SilenceWindow>) -> Self {
+        Silences { windows, one_shot: None }
+    }
+
+    /// Silences alerts until `until_ms`, replacing any earlier one-shot silence.
+    pub fn silence_until(&mut self, until_ms: u64, reason: String) {
+        self.one_shot = Some((until_ms, reason));
+    }
+
+    /// Ends the one-shot silence early; scheduled windows are unaffected.
+    ///
+    /// # Returns
+    ///
+    /// True if a one-shot silence was set.
+    pub fn end_one_shot(&mut self) -> bool {
+        self.one_shot.take().is_some()
+    }
+
+    /// Returns a description of the silence in effect at `now_ms`, or `None` if alerts are
+    /// dispatched.
+    pub fn active(&self, now_ms: u64) -> Option<String> {
+        if let Some((until_ms, reason)) = &self.one_shot
+            && now_ms < *until_ms
+        {
+            return Some(format!("{} until {}", reason, format_utc_timestamp(*until_ms)));
+        }
+        let now_minute = now_ms / 60_000;
+        self.windows.iter().find_map(|window| {
+            let minutes = window.duration.as_secs() / 60;
+            (0..minutes.min(now_minute + 1))
+                .map(|ago| now_minute - ago)
+                .find(|start| window.schedule.matches(*start))
+                .map(|start| format!("{} until {}", window.reason, format_utc_timestamp((start + minutes) * 60_000)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_open_on_their_cron_schedule_for_their_duration() {
+        // 02:00 on Saturdays and the first of the month, and every 15 minutes past the hour.
+        let weekly = CronSchedule::parse("0 2 1 * 6").unwrap();
+        let quarter = CronSchedule::parse("*/15 * * * *").unwrap();
+        // 2025-02-01 at 02:00, a Saturday.
+        let saturday = 1_738_375_200_000 / 60_000;
+        assert!(weekly.matches(saturday));
+        assert!(weekly.matches(saturday + 7 * 24 * 60));
+        assert!(!weekly.matches(saturday + 24 * 60));
+        assert!(!weekly.matches(saturday + 1));
+        assert!(quarter.matches(saturday + 45) && !quarter.matches(saturday + 50));
+        assert_eq!(CronSchedule::parse("0 2 * * 7").unwrap(), CronSchedule::parse("0 2 * * 0").unwrap());
+        for invalid in ["0 2 * *", "60 * * * *", "0 2 * * 8", "*/0 * * * *", "5-1 * * * *"] {
+            assert!(CronSchedule::parse(invalid).is_err(), "{}", invalid);
+        }
+
+        let window = SilenceWindow {
+            cron: "0 2 1 * 6".to_string(),
+            schedule: weekly,
+            duration: Duration::from_secs(2 * 3600),
+            reason: "Load test".to_string(),
+        };
+        let mut silences = Silences::new(vec![window]);
+        let at = |minute: u64| minute * 60_000;
+        assert_eq!(silences.active(at(saturday + 119)).as_deref(), Some("Load test until 2025-02-01T04:00:00.000Z"));
+        assert!(silences.active(at(saturday + 120)).is_none());
+        assert!(silences.active(at(saturday - 1)).is_none());
+
+        silences.silence_until(at(saturday - 1), "Manual".to_string());
+        assert!(silences.active(at(saturday - 2)).unwrap().starts_with("Manual"));
+        assert!(silences.active(at(saturday - 1)).is_none());
+        assert!(silences.end_one_shot());
+        assert!(!silences.end_one_shot());
+    }
+}
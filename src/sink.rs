@@ -0,0 +1,95 @@
+use crate::error::{report, with_retry, ErrorPolicy, ErrorReport};
+use crate::metrics::{CumulativeMetrics, IntervalMetrics};
+use crate::policy::Policy;
+use crossbeam_channel::Sender;
+use std::io;
+
+/// An output the aggregator writes the metrics of every reporting interval to.
+pub trait Sink: Send + Sync {
+    /// Name of the sink in error reports.
+    fn name(&self) -> &'static str;
+
+    /// Writes the metrics of a finished reporting interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Metrics of the interval.
+    /// * `total` - Cumulative metrics, including the interval.
+    /// * `policy` - The active policy, naming the compliance flags and risk factors.
+    fn write(&self, interval: &IntervalMetrics, total: &CumulativeMetrics, policy: &Policy) -> io::Result<()>;
+}
+
+/// Writes a finished reporting interval to every sink, retrying per `sink_policy`.
+///
+/// A sink that still fails is reported and removed, so later intervals skip it, unless the
+/// policy shuts the monitor down.
+///
+/// # Arguments
+///
+/// * `sinks` - The sinks still enabled.
+/// * `interval` - Metrics of the interval.
+/// * `total` - Cumulative metrics, including the interval.
+/// * `policy` - The active policy.
+/// * `sink_policy` - How to handle write failures.
+/// * `errors` - Channel sender for error reports.
+pub fn write_to_sinks(
+    sinks: &mut Vec<&dyn Sink>,
+    interval: &IntervalMetrics,
+    total: &CumulativeMetrics,
+    policy: &Policy,
+    sink_policy: ErrorPolicy,
+    errors: &Sender<ErrorReport>,
+) {
+    sinks.retain(|sink| match with_retry(sink_policy, sink.name(), errors, || sink.write(interval, total, policy)) {
+        Ok(()) => true,
+        Err(e) => {
+            let action = if sink_policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+            report(errors, e, action);
+            false
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingSink {
+        fails: bool,
+        writes: AtomicUsize,
+    }
+
+    impl Sink for CountingSink {
+        fn name(&self) -> &'static str {
+            if self.fails { "failing sink" } else { "counting sink" }
+        }
+
+        fn write(&self, _interval: &IntervalMetrics, _total: &CumulativeMetrics, _policy: &Policy) -> io::Result<()> {
+            self.writes.fetch_add(1, Ordering::Relaxed);
+            if self.fails { Err(io::Error::other("disk full")) } else { Ok(()) }
+        }
+    }
+
+    #[test]
+    fn failing_sinks_are_reported_and_dropped() {
+        let working = CountingSink { fails: false, writes: AtomicUsize::new(0) };
+        let failing = CountingSink { fails: true, writes: AtomicUsize::new(0) };
+        let mut sinks: Vec<&dyn Sink> = vec![&working, &failing];
+        let (errors, reports) = unbounded();
+        let interval = IntervalMetrics::default();
+        let total = CumulativeMetrics::default();
+        let policy = Policy::builtin();
+
+        for _ in 0..2 {
+            write_to_sinks(&mut sinks, &interval, &total, &policy, ErrorPolicy::Degrade, &errors);
+        }
+        assert_eq!(sinks.len(), 1);
+        assert_eq!((working.writes.load(Ordering::Relaxed), failing.writes.load(Ordering::Relaxed)), (2, 1));
+        let report = reports.try_recv().unwrap();
+        assert_eq!(report.action, ErrorPolicy::Degrade);
+        assert!(report.error.to_string().contains("failing sink"), "{}", report.error);
+        assert!(reports.try_recv().is_err());
+    }
+}
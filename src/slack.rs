@@ -0,0 +1,260 @@
+use crate::compliance::ComplianceRegistry;
+use crate::error::{report, with_retry, ErrorPolicy, ErrorReport, MonitorError, Result};
+use crate::metrics::ComplianceMetrics;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde_json::{json, Value};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Sink name used in error reports when posting to Slack.
+pub const SLACK_SINK: &str = "Slack notifier";
+
+/// Most alerts listed in one message; the rest are only counted.
+const MAX_LISTED_ALERTS: usize = 10;
+
+/// Most violated checks named in a message's summary.
+const TOP_VIOLATIONS: usize = 3;
+
+/// How often the notifier checks the stop signal while waiting for alerts.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Timeout of a single webhook request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where and how often alerts are posted.
+#[derive(Clone, Debug)]
+pub struct SlackConfig {
+    /// Incoming webhook URL.
+    pub webhook: String,
+    /// Channel overriding the webhook's default channel, if any.
+    pub channel: Option<String>,
+    /// How long alerts are collected after the first one before they are posted together.
+    pub batch_window: Duration,
+}
+
+/// An alert raised by the monitor, with the metrics at the time it was raised.
+#[derive(Clone, Debug)]
+pub struct SlackAlert {
+    pub message: String,
+    pub summary: AlertSummary,
+}
+
+/// Key metrics included with an alert.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertSummary {
+    pub events: u64,
+    pub compliance_percentage: f64,
+    pub processing_rate: f64,
+    pub high_risk_count: u64,
+    /// The most violated checks by name, most violations first.
+    pub top_violations: Vec<(&'static str, u64)>,
+}
+
+impl SlackConfig {
+    /// Builds the configuration from the `--slack-*` arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - Incoming webhook URL; must use HTTP or HTTPS.
+    /// * `channel` - Channel overriding the webhook's default one.
+    /// * `batch_secs` - Seconds to collect alerts for before posting them.
+    pub fn new(webhook: &str, channel: Option<&str>, batch_secs: u64) -> Result<Self> {
+        if !(webhook.starts_with("https://") || webhook.starts_with("http://")) {
+            return Err(MonitorError::Config("Slack webhook must be an http:// or https:// URL".to_string()));
+        }
+        Ok(SlackConfig {
+            webhook: webhook.to_string(),
+            channel: channel.map(str::to_string),
+            batch_window: Duration::from_secs(batch_secs),
+        })
+    }
+}
+
+impl AlertSummary {
+    /// Summarizes the cumulative metrics.
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - The cumulative metrics.
+    /// * `compliance` - The compliance flags, used to name the violated checks.
+    pub fn new(metrics: &ComplianceMetrics, compliance: &ComplianceRegistry) -> Self {
+        let mut top_violations: Vec<(&'static str, u64)> = compliance
+            .iter()
+            .map(|(flag, info)| (info.name, metrics.violations(flag)))
+            .filter(|(_, violations)| *violations > 0)
+            .collect();
+        top_violations.sort_by_key(|(_, violations)| std::cmp::Reverse(*violations));
+        top_violations.truncate(TOP_VIOLATIONS);
+        AlertSummary {
+            events: metrics.total_events,
+            compliance_percentage: metrics.compliance_percentage(),
+            processing_rate: metrics.processing_rate(),
+            high_risk_count: metrics.high_risk_count,
+            top_violations,
+        }
+    }
+}
+
+/// Posts alerts to Slack until the stop signal is set or every sender is dropped.
+///
+/// The first alert starts a batch window; every alert raised within it goes out in the same
+/// message, with the metrics of the latest one. Alerts still waiting on shutdown are posted
+/// before returning. Failed posts are handled according to `policy`; if the notifier gives up,
+/// it reports the error and exits, which disables Slack alerts without affecting the monitor.
+///
+/// # Arguments
+///
+/// * `config` - Webhook, channel, and batch window.
+/// * `receiver` - Channel receiving alerts from the monitor.
+/// * `stop` - Atomic flag indicating when to stop.
+/// * `policy` - How to handle failed posts.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn slack_notifier(
+    config: SlackConfig,
+    receiver: Receiver<SlackAlert>,
+    stop: Arc<AtomicBool>,
+    policy: ErrorPolicy,
+    errors: Sender<ErrorReport>,
+) {
+    let mut batch = Vec::new();
+    let mut deadline = None;
+    loop {
+        let mut stopping = stop.load(Ordering::Relaxed);
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(alert) => {
+                deadline.get_or_insert_with(|| Instant::now() + config.batch_window);
+                batch.push(alert);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => stopping = true,
+        }
+        if stopping {
+            batch.extend(receiver.try_iter());
+        }
+        let due = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if !batch.is_empty() && (due || stopping) {
+            let body = message_payload(&batch, config.channel.as_deref()).to_string();
+            if let Err(e) = with_retry(policy, SLACK_SINK, &errors, || post(&config.webhook, &body)) {
+                let action = if policy == ErrorPolicy::Shutdown { ErrorPolicy::Shutdown } else { ErrorPolicy::Degrade };
+                report(&errors, e, action);
+                return;
+            }
+            batch.clear();
+            deadline = None;
+        }
+        if stopping {
+            return;
+        }
+    }
+}
+
+/// Sends a JSON message to the webhook.
+fn post(webhook: &str, body: &str) -> io::Result<()> {
+    ureq::post(webhook)
+        .timeout(REQUEST_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+        .map(|_| ())
+        .map_err(|e| match e {
+            ureq::Error::Status(status, _) => io::Error::other(format!("webhook returned HTTP {}", status)),
+            // The transport error's own message names the URL, which holds the webhook's secret.
+            ureq::Error::Transport(transport) => match transport.message() {
+                Some(message) => io::Error::other(format!("{}: {}", transport.kind(), message)),
+                None => io::Error::other(transport.kind().to_string()),
+            },
+        })
+}
+
+/// Builds the Slack message for a batch of alerts: a header, the alerts as a list, and the
+/// key metrics of the latest alert.
+///
+/// # Arguments
+///
+/// * `alerts` - The alerts to post, oldest first; must not be empty.
+/// * `channel` - Channel overriding the webhook's default one.
+pub fn message_payload(alerts: &[SlackAlert], channel: Option<&str>) -> Value {
+    let title = match alerts.len() {
+        1 => "AI compliance alert".to_string(),
+        count => format!("{} AI compliance alerts", count),
+    };
+    let mut list: Vec<String> = alerts
+        .iter()
+        .take(MAX_LISTED_ALERTS)
+        .map(|alert| format!("\u{2022} {}", escape(&alert.message)))
+        .collect();
+    if alerts.len() > MAX_LISTED_ALERTS {
+        list.push(format!("_\u{2026}and {} more_", alerts.len() - MAX_LISTED_ALERTS));
+    }
+    let summary = &alerts[alerts.len() - 1].summary;
+    let field = |name: &str, value: String| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", name, value) });
+    let mut blocks = vec![
+        json!({ "type": "header", "text": { "type": "plain_text", "text": title } }),
+        json!({ "type": "section", "text": { "type": "mrkdwn", "text": list.join("\n") } }),
+        json!({
+            "type": "section",
+            "fields": [
+                field("Events", summary.events.to_string()),
+                field("Compliance", format!("{:.1}%", summary.compliance_percentage)),
+                field("Rate", format!("{:.0}/s", summary.processing_rate)),
+                field("High risk", summary.high_risk_count.to_string()),
+            ],
+        }),
+    ];
+    if !summary.top_violations.is_empty() {
+        let violations: Vec<String> = summary
+            .top_violations
+            .iter()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect();
+        blocks.push(json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": format!("Most violated: {}", escape(&violations.join(", "))) }],
+        }));
+    }
+    let mut payload = json!({ "text": format!("{}: {}", title, escape(&alerts[0].message)), "blocks": blocks });
+    if let Some(channel) = channel {
+        payload["channel"] = channel.into();
+    }
+    payload
+}
+
+/// Escapes the characters Slack's mrkdwn reserves for links and mentions.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_alerts_into_one_message_with_the_latest_summary() {
+        let alert = |message: &str, events| SlackAlert {
+            message: message.to_string(),
+            summary: AlertSummary {
+                events,
+                compliance_percentage: 97.26,
+                processing_rate: 1_000.0,
+                high_risk_count: 12,
+                top_violations: vec![("GDPR", 40), ("Data Residency", 7)],
+            },
+        };
+        let alerts: Vec<SlackAlert> = (0..12).map(|i| alert(&format!("alert {} <R&D>", i), 100 * i)).collect();
+        let payload = message_payload(&alerts, Some("#compliance"));
+
+        assert_eq!(payload["channel"], "#compliance");
+        assert_eq!(payload["blocks"][0]["text"]["text"], "12 AI compliance alerts");
+        let list = payload["blocks"][1]["text"]["text"].as_str().unwrap();
+        assert!(list.starts_with("\u{2022} alert 0 &lt;R&amp;D&gt;\n"));
+        assert!(list.ends_with("_\u{2026}and 2 more_"));
+        assert_eq!(payload["blocks"][2]["fields"][0]["text"], "*Events*\n1100");
+        assert_eq!(payload["blocks"][2]["fields"][1]["text"], "*Compliance*\n97.3%");
+        assert_eq!(payload["blocks"][3]["elements"][0]["text"], "Most violated: GDPR 40, Data Residency 7");
+
+        let single = message_payload(&alerts[..1], None);
+        assert_eq!(single["blocks"][0]["text"]["text"], "AI compliance alert");
+        assert!(single.get("channel").is_none());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::metrics::ComplianceMetrics;
+use std::time::Duration;
+
+/// Rolling window over which error budgets are measured unless the policy file sets one.
+pub const DEFAULT_SLO_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Burn rate above which an alert is raised unless the policy file sets one.
+pub const DEFAULT_BURN_RATE_ALERT: f64 = 2.0;
+
+/// The burn rate is measured over this fraction of the budget window, so that a sudden rise in
+/// violations shows up before it has used much of the budget.
+const BURN_WINDOW_DIVISOR: u32 = 12;
+
+/// Compliance service level objectives for departments, as configured in the policy file.
+#[derive(Clone, Debug)]
+pub struct SloPolicy {
+    /// Rolling window over which the error budget is measured.
+    pub window: Duration,
+    /// Burn rate above which a department's SLO raises an alert.
+    pub burn_rate_alert: f64,
+    /// Targets in department order.
+    pub targets: Vec<SloTarget>,
+}
+
+/// A department's target compliance percentage.
+#[derive(Clone, Copy, Debug)]
+pub struct SloTarget {
+    /// Index into `DEPARTMENT_NAMES`.
+    pub department: u8,
+    /// Target compliance percentage, below 100.
+    pub target: f64,
+}
+
+/// How a department is doing against its SLO over the rolling window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SloStatus {
+    /// Index into `DEPARTMENT_NAMES`.
+    pub department: u8,
+    /// Target compliance percentage.
+    pub target: f64,
+    /// Compliance percentage over the window.
+    pub compliance: f64,
+    /// Fraction of the error budget left over the window; negative once it is overspent.
+    pub budget_remaining: f64,
+    /// Rate at which the budget was used over the burn window, relative to the rate that would
+    /// use it up exactly by the end of the window.
+    pub burn_rate: f64,
+}
+
+impl SloPolicy {
+    /// Returns the window over which burn rates are measured.
+    pub fn burn_window(&self) -> Duration {
+        self.window / BURN_WINDOW_DIVISOR
+    }
+
+    /// Returns a short label for the budget window, such as `1h` or `90s`.
+    pub fn window_label(&self) -> String {
+        let secs = self.window.as_secs();
+        if secs.is_multiple_of(3600) {
+            format!("{}h", secs / 3600)
+        } else if secs.is_multiple_of(60) {
+            format!("{}m", secs / 60)
+        } else {
+            format!("{}s", secs)
+        }
+    }
+
+    /// Evaluates every department's SLO against the history of the cumulative metrics.
+    ///
+    /// Compliance is measured per check, like the overall compliance percentage: the error
+    /// budget allows `100 - target` percent of the checks run on the department's events to fail.
+    pub fn evaluate(&self, metrics: &ComplianceMetrics) -> Vec<SloStatus> {
+        let empty = ComplianceMetrics::default();
+        self.targets
+            .iter()
+            .map(|target| {
+                let department = metrics.departments.get(target.department as usize).unwrap_or(&empty);
+                let allowed = 1.0 - target.target / 100.0;
+                let window_errors = error_rate(department, self.window);
+                SloStatus {
+                    department: target.department,
+                    target: target.target,
+                    compliance: 100.0 * (1.0 - window_errors),
+                    budget_remaining: 1.0 - window_errors / allowed,
+                    burn_rate: error_rate(department, self.burn_window()) / allowed,
+                }
+            })
+            .collect()
+    }
+}
+
+impl SloStatus {
+    /// Returns whether the budget is being used faster than `policy` allows.
+    pub fn is_burning(&self, policy: &SloPolicy) -> bool {
+        self.burn_rate > policy.burn_rate_alert
+    }
+}
+
+/// Returns the fraction of the checks run on a department's events within `window` that failed.
+fn error_rate(department: &ComplianceMetrics, window: Duration) -> f64 {
+    let checks = department.events_in_window(Some(window)) as f64 * department.violation_counts.len() as f64;
+    if checks == 0.0 {
+        return 0.0;
+    }
+    let violations: u64 = department.violations_in_window(Some(window)).iter().sum();
+    (violations as f64 / checks).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{CumulativeMetrics, IntervalMetrics};
+
+    #[test]
+    fn budget_and_burn_rate_follow_the_rolling_window() {
+        let (finance, legal) = (2, 4);
+        let slos = SloPolicy {
+            window: Duration::from_secs(3600),
+            burn_rate_alert: DEFAULT_BURN_RATE_ALERT,
+            targets: vec![SloTarget { department: finance, target: 99.5 }, SloTarget { department: legal, target: 99.0 }],
+        };
+        let mut metrics = CumulativeMetrics::default();
+        // One interval of violations that falls out of the window, eleven clean intervals, and
+        // a last one that fails 1% of its checks.
+        for (interval, violations) in [100, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 50].into_iter().enumerate() {
+            let mut delta = ComplianceMetrics {
+                departments: vec![ComplianceMetrics::default(); 6],
+                ..ComplianceMetrics::default()
+            };
+            delta.departments[finance as usize] = ComplianceMetrics {
+                total_events: 1000,
+                violation_counts: vec![violations, 0, 0, 0, 0],
+                ..ComplianceMetrics::default()
+            };
+            metrics.record(&IntervalMetrics::new(delta, Duration::from_secs(300), interval as u64 * 300_000));
+        }
+
+        let statuses = slos.evaluate(&metrics);
+        let finance_slo = statuses[0];
+        assert!((finance_slo.budget_remaining - 5.0 / 6.0).abs() < 1e-9);
+        assert!((finance_slo.burn_rate - 2.0).abs() < 1e-9);
+        assert!(!finance_slo.is_burning(&slos));
+        assert_eq!(statuses[1].budget_remaining, 1.0);
+        assert_eq!(statuses[1].burn_rate, 0.0);
+        assert_eq!(slos.window_label(), "1h");
+    }
+}
@@ -0,0 +1,96 @@
+use crate::clock::now_millis;
+use crate::error::{MonitorError, Result};
+use crate::metrics::CumulativeMetrics;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Snapshot format version; bumped whenever `CumulativeMetrics` changes incompatibly.
+const SNAPSHOT_VERSION: u32 = 21;
+
+/// Sink name used in error reports when saving snapshots.
+pub const SNAPSHOT_SINK: &str = "metrics snapshot";
+
+/// Aggregated metrics state persisted across restarts.
+#[derive(Serialize, Deserialize)]
+struct MetricsSnapshot {
+    version: u32,
+    saved_at_ms: u64,
+    metrics: CumulativeMetrics,
+}
+
+/// The version of a snapshot, read before the rest so that a snapshot of another version is
+/// reported as such rather than as a missing or mistyped field.
+#[derive(Deserialize)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+/// Writes the cumulative metrics, including history, to `path`.
+///
+/// The snapshot is written to a temporary file first and renamed into place, so a crash
+/// mid-write never leaves a truncated snapshot behind.
+pub fn save_snapshot(path: &Path, metrics: &CumulativeMetrics) -> io::Result<()> {
+    let snapshot = MetricsSnapshot {
+        version: SNAPSHOT_VERSION,
+        saved_at_ms: now_millis(),
+        metrics: metrics.clone(),
+    };
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer(&mut writer, &snapshot)?;
+    writer.flush()?;
+    drop(writer);
+    fs::rename(&tmp_path, path)
+}
+
+/// Loads previously saved metrics from `path`.
+///
+/// Returns `Ok(None)` if no snapshot exists yet. An unreadable or incompatible snapshot is an
+/// error rather than being silently discarded, so cumulative figures are never overwritten.
+pub fn load_snapshot(path: &Path) -> Result<Option<CumulativeMetrics>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(MonitorError::Snapshot(e.to_string())),
+    };
+    let probe: SnapshotVersion = serde_json::from_slice(&contents).map_err(|e| MonitorError::Snapshot(e.to_string()))?;
+    if probe.version != SNAPSHOT_VERSION {
+        return Err(MonitorError::Snapshot(format!(
+            "unsupported snapshot version {} (expected {})",
+            probe.version, SNAPSHOT_VERSION
+        )));
+    }
+    let snapshot: MetricsSnapshot = serde_json::from_slice(&contents).map_err(|e| MonitorError::Snapshot(e.to_string()))?;
+    Ok(Some(snapshot.metrics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::ComplianceMetrics;
+
+    #[test]
+    fn snapshots_round_trip_and_other_versions_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("snapshot-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("metrics.json");
+        assert!(load_snapshot(&path).unwrap().is_none());
+
+        let metrics = CumulativeMetrics::resume(ComplianceMetrics { total_events: 42, violating_events: 7, ..ComplianceMetrics::default() });
+        save_snapshot(&path, &metrics).unwrap();
+        let restored = load_snapshot(&path).unwrap().unwrap();
+        assert_eq!((restored.total_events, restored.violating_events), (42, 7));
+
+        // An older snapshot fails on its version before any field it lacks.
+        fs::write(&path, r#"{"version": 3, "saved_at_ms": 0, "metrics": {"total_events": 42}}"#).unwrap();
+        let result = load_snapshot(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        let Err(error) = result else {
+            panic!("a snapshot of another version was loaded");
+        };
+        let error = error.to_string();
+        assert!(error.contains(&format!("unsupported snapshot version 3 (expected {})", SNAPSHOT_VERSION)), "{}", error);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::error::{MonitorError, Result};
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Share of a soak run, from its start, left out of the growth fit while caches and buffers fill.
+const WARMUP_FRACTION: f64 = 0.1;
+
+/// Fewest samples after the warm-up needed to fit a growth trend.
+const MIN_FIT_SAMPLES: usize = 3;
+
+/// Samples taken over a soak run, unless that would sample more often than once a second.
+const TARGET_SAMPLES: u32 = 120;
+
+/// Longest time between two samples of a long soak run.
+const MAX_SAMPLE_PERIOD: Duration = Duration::from_secs(60);
+
+const MIB: f64 = 1024.0 * 1024.0;
+const SECS_PER_HOUR: f64 = 3600.0;
+
+/// Memory of the process and sizes of its long-lived structures at one point of a soak run.
+#[derive(Clone, Debug)]
+pub struct MemorySample {
+    /// Time since the soak run started.
+    pub elapsed: Duration,
+    /// Resident memory of the process, where it can be measured.
+    pub rss_bytes: Option<u64>,
+    /// Named sizes of structures that could grow without bound, such as histories and channels.
+    pub structures: Vec<(Cow<'static, str>, u64)>,
+}
+
+/// A soak run of `--soak`, collecting memory samples until its duration has passed.
+#[derive(Debug)]
+pub struct SoakTest {
+    duration: Duration,
+    /// Largest tolerated growth of the resident memory, in MiB per hour.
+    max_slope: f64,
+    sample_period: Duration,
+    samples: Vec<MemorySample>,
+}
+
+impl SoakTest {
+    /// Validates the soak settings given on the command line.
+    ///
+    /// # Arguments
+    ///
+    /// * `hours` - How long the run lasts.
+    /// * `max_slope` - Largest tolerated growth of the resident memory, in MiB per hour.
+    pub fn new(hours: f64, max_slope: f64) -> Result<Self> {
+        if !(hours.is_finite() && hours > 0.0) {
+            return Err(MonitorError::Config(format!("soak duration must be a positive number of hours, got {}", hours)));
+        }
+        if !(max_slope.is_finite() && max_slope >= 0.0) {
+            return Err(MonitorError::Config(format!("soak memory slope limit must not be negative, got {}", max_slope)));
+        }
+        let duration = Duration::from_secs_f64(hours * SECS_PER_HOUR);
+        Ok(SoakTest {
+            duration,
+            max_slope,
+            sample_period: (duration / TARGET_SAMPLES).clamp(Duration::from_secs(1), MAX_SAMPLE_PERIOD),
+            samples: Vec::new(),
+        })
+    }
+
+    /// How long the run lasts.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns true if a sample is due `elapsed` after the run started.
+    pub fn sample_due(&self, elapsed: Duration) -> bool {
+        self.samples.last().is_none_or(|last| elapsed >= last.elapsed + self.sample_period)
+    }
+
+    /// Adds a sample, taken after every earlier one.
+    pub fn record(&mut self, sample: MemorySample) {
+        self.samples.push(sample);
+    }
+
+    /// Fits the growth of the resident memory and of every structure over the samples taken
+    /// after the warm-up.
+    pub fn finish(&self) -> SoakResult {
+        let elapsed = self.samples.last().map_or(Duration::ZERO, |sample| sample.elapsed);
+        let warmup = elapsed.mul_f64(WARMUP_FRACTION);
+        let fitted: Vec<_> = self.samples.iter().filter(|sample| sample.elapsed >= warmup).collect();
+        let fit = |value: &dyn Fn(&MemorySample) -> Option<f64>| -> Option<f64> {
+            let points: Vec<_> = fitted
+                .iter()
+                .filter_map(|sample| value(sample).map(|y| (sample.elapsed.as_secs_f64() / SECS_PER_HOUR, y)))
+                .collect();
+            (points.len() >= MIN_FIT_SAMPLES).then(|| least_squares_slope(&points)).flatten()
+        };
+        let rss = |sample: &MemorySample| sample.rss_bytes.map(|bytes| bytes as f64 / MIB);
+        let structures = self.samples.first().map_or_else(Vec::new, |first| {
+            first
+                .structures
+                .iter()
+                .enumerate()
+                .map(|(index, (name, first_size))| StructureGrowth {
+                    name: name.clone(),
+                    first: *first_size,
+                    last: self.samples.last().and_then(|last| last.structures.get(index)).map_or(0, |(_, size)| *size),
+                    slope_per_hour: fit(&|sample| sample.structures.get(index).map(|(_, size)| *size as f64)),
+                })
+                .collect()
+        });
+        SoakResult {
+            elapsed,
+            samples: self.samples.len(),
+            first_rss_bytes: self.samples.first().and_then(|sample| sample.rss_bytes),
+            last_rss_bytes: self.samples.last().and_then(|sample| sample.rss_bytes),
+            rss_slope: fit(&rss),
+            max_slope: self.max_slope,
+            structures,
+        }
+    }
+}
+
+/// Growth of one structure over a soak run.
+#[derive(Clone, Debug)]
+pub struct StructureGrowth {
+    pub name: Cow<'static, str>,
+    pub first: u64,
+    pub last: u64,
+    /// Fitted growth in entries per hour, if there were enough samples.
+    pub slope_per_hour: Option<f64>,
+}
+
+/// Outcome of a soak run.
+#[derive(Clone, Debug)]
+pub struct SoakResult {
+    pub elapsed: Duration,
+    pub samples: usize,
+    pub first_rss_bytes: Option<u64>,
+    pub last_rss_bytes: Option<u64>,
+    /// Fitted growth of the resident memory in MiB per hour, if it was measured often enough.
+    pub rss_slope: Option<f64>,
+    pub max_slope: f64,
+    pub structures: Vec<StructureGrowth>,
+}
+
+impl SoakResult {
+    /// Fails if the resident memory grew faster than the limit.
+    pub fn check(&self) -> Result<()> {
+        match self.rss_slope {
+            Some(slope) if slope > self.max_slope => Err(MonitorError::MemoryGrowth { slope, limit: self.max_slope }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Renders the growth of the resident memory and every structure, ending with a newline.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Soak test: {:.2} hours, {} samples", self.elapsed.as_secs_f64() / SECS_PER_HOUR, self.samples);
+        let mib = |bytes: Option<u64>| bytes.map_or_else(|| "n/a".to_string(), |bytes| format!("{:.1} MiB", bytes as f64 / MIB));
+        let slope = match self.rss_slope {
+            Some(slope) => format!("{:+.2} MiB/hour", slope),
+            None => "too few samples to fit".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "  {:<24} {:>12} -> {:<12} {} (limit {} MiB/hour)",
+            "RSS",
+            mib(self.first_rss_bytes),
+            mib(self.last_rss_bytes),
+            slope,
+            self.max_slope
+        );
+        for structure in &self.structures {
+            let slope = structure.slope_per_hour.map_or_else(String::new, |slope| format!("{:+.1}/hour", slope));
+            let _ = writeln!(out, "  {:<24} {:>12} -> {:<12} {}", structure.name, structure.first, structure.last, slope);
+        }
+        out
+    }
+}
+
+/// Returns the slope of the least-squares line through `points`, or `None` if all of them share
+/// one x value.
+fn least_squares_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soak_runs_fail_when_memory_grows_faster_than_the_limit() {
+        assert!(SoakTest::new(0.0, 8.0).is_err());
+        assert!(SoakTest::new(1.0, -1.0).is_err());
+
+        // One hour with a 2 MiB warm-up spike, then 16 MiB/hour of growth and a history that
+        // stops growing.
+        let mut soak = SoakTest::new(1.0, 8.0).unwrap();
+        assert_eq!(soak.duration(), Duration::from_secs(3600));
+        assert!(soak.sample_due(Duration::ZERO));
+        for minute in 0..=60u64 {
+            let elapsed = Duration::from_secs(minute * 60);
+            let spike = if minute < 3 { 2.0 } else { 0.0 };
+            soak.record(MemorySample {
+                elapsed,
+                rss_bytes: Some(((100.0 + spike + 16.0 * minute as f64 / 60.0) * MIB) as u64),
+                structures: vec![("history points".into(), minute.min(5))],
+            });
+        }
+        assert!(!soak.sample_due(Duration::from_secs(3601)));
+
+        let result = soak.finish();
+        assert_eq!(result.samples, 61);
+        let slope = result.rss_slope.unwrap();
+        assert!((slope - 16.0).abs() < 0.01, "{}", slope);
+        assert!(matches!(result.check(), Err(MonitorError::MemoryGrowth { .. })));
+        let history = &result.structures[0];
+        assert_eq!((history.first, history.last), (0, 5));
+        assert_eq!(history.slope_per_hour, Some(0.0));
+        assert!(result.render().contains("+16.00 MiB/hour"), "{}", result.render());
+
+        let lenient = SoakResult { max_slope: 20.0, ..result };
+        assert!(lenient.check().is_ok());
+    }
+}
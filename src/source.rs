@@ -0,0 +1,264 @@
+use crate::clock::SharedClock;
+use crate::components::Event;
+use crate::content_scan::ContentScanner;
+use crate::ecs::PipelineConfig;
+use crate::error::{MonitorError, Result};
+use crate::event_log::{ColumnMapping, EventLogReader, LogFormat, LogRow, SkippedRows};
+use crate::event_profile::EventProfile;
+use crate::frontend::ReplayStatus;
+use crate::spike::{generate_violations, ViolationSpikes, MAX_SPIKE_EVENTS_PER_BATCH};
+use crate::traffic::TrafficPattern;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Longest a file source waits for its next event before handing back an empty batch, so the
+/// thread reading it notices the stop signal.
+const MAX_PACING_WAIT: Duration = Duration::from_millis(100);
+
+/// Where a pipeline thread takes the events it evaluates from.
+///
+/// The thread asks for a batch, evaluates it, and acknowledges it, until the source is
+/// exhausted or the thread is stopped, and then shuts the source down.
+pub trait EventSource: Send {
+    /// Returns up to `max_events` events to evaluate next. The batch is empty if no event is
+    /// due yet, and `None` once the source is exhausted.
+    fn next_batch(&mut self, max_events: usize) -> Result<Option<Vec<Event>>>;
+
+    /// Acknowledges that the `events` of the last batch were evaluated.
+    fn ack(&mut self, events: usize);
+
+    /// Stops the source once no more batches will be taken.
+    fn shutdown(&mut self);
+
+    /// Returns the progress through a replayed log, for sources that replay one.
+    fn replay_status(&self) -> Option<&ReplayStatus> {
+        None
+    }
+}
+
+/// The event sources a pipeline can be configured with, selected by `--source`.
+///
+/// Adding a source means implementing [`EventSource`] for it, opening it here, and naming it in
+/// [`SourceSpec::parse`]. There are no HTTP or Kafka sources; clients push events over gRPC
+/// instead.
+#[derive(Clone, Debug, Default)]
+pub enum SourceSpec {
+    /// Events drawn from the event profile, shaped by the traffic pattern, plus queued violation spikes.
+    #[default]
+    Synthetic,
+    /// An NDJSON or CSV event log, paced by its timestamps. Logs with a `.csv` extension are
+    /// read as CSV, anything else as NDJSON.
+    File {
+        path: PathBuf,
+        /// Playback speed multiplier; `1.0` is original timing, `0` replays as fast as possible.
+        speed: f64,
+        /// Which column of the log holds each event field.
+        mapping: ColumnMapping,
+    },
+}
+
+impl SourceSpec {
+    /// Parses `--source`: `synthetic`, or `file:<PATH>` for an event log paced at its original
+    /// timing with the default column mapping.
+    pub fn parse(spec: &str) -> std::result::Result<Self, String> {
+        match spec.split_once(':') {
+            None if spec == "synthetic" => Ok(SourceSpec::Synthetic),
+            Some(("file", path)) if !path.is_empty() => {
+                Ok(SourceSpec::File { path: PathBuf::from(path), speed: 1.0, mapping: ColumnMapping::default() })
+            }
+            Some((kind @ ("http" | "kafka"), _)) => Err(format!("{} sources are not supported; push events over gRPC instead", kind)),
+            _ => Err(format!("unknown event source `{}`; expected `synthetic` or `file:<PATH>`", spec)),
+        }
+    }
+
+    /// Opens the source for one pipeline thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The pipeline's configuration, with the event profile, traffic pattern,
+    ///   spikes, and clock the source draws on.
+    /// * `content_scan` - Scanner classifying the prompts of ingested events.
+    pub fn open(&self, config: &PipelineConfig, content_scan: &ContentScanner) -> Result<Box<dyn EventSource>> {
+        match self {
+            SourceSpec::Synthetic => Ok(Box::new(SyntheticSource::new(config))),
+            SourceSpec::File { path, speed, mapping } => {
+                let file = BufReader::new(File::open(path).map_err(MonitorError::EventLog)?);
+                let format = LogFormat::from_path(path).unwrap_or(LogFormat::Ndjson);
+                let reader = EventLogReader::new(file, format, mapping.clone(), content_scan.clone())?;
+                let status = ReplayStatus { source: path.display().to_string(), speed: *speed, ..ReplayStatus::default() };
+                Ok(Box::new(FileSource::new(reader, status, config.clock.clone())))
+            }
+        }
+    }
+}
+
+/// Generates events from an event profile, adding the violation spikes queued by `i` or the admin API.
+pub struct SyntheticSource {
+    event_profile: Arc<EventProfile>,
+    traffic: TrafficPattern,
+    spikes: Arc<ViolationSpikes>,
+    clock: SharedClock,
+}
+
+impl SyntheticSource {
+    /// Creates the source from the pipeline's event profile, traffic pattern, spikes, and clock.
+    pub fn new(config: &PipelineConfig) -> Self {
+        SyntheticSource {
+            event_profile: config.event_profile.clone(),
+            traffic: config.traffic,
+            spikes: config.spikes.clone(),
+            clock: config.clock.clone(),
+        }
+    }
+}
+
+impl EventSource for SyntheticSource {
+    fn next_batch(&mut self, max_events: usize) -> Result<Option<Vec<Event>>> {
+        let unix_ms = self.clock.unix_millis();
+        let region_weights = self.traffic.region_weights(unix_ms);
+        let mut events = self.event_profile.generate(max_events, region_weights.as_ref(), unix_ms);
+        events.extend(generate_violations(self.spikes.take(MAX_SPIKE_EVENTS_PER_BATCH), unix_ms));
+        Ok(Some(events))
+    }
+
+    fn ack(&mut self, _events: usize) {}
+
+    fn shutdown(&mut self) {}
+}
+
+/// Reads events from an event log, handing each out once it is due by its timestamp.
+///
+/// Rows that cannot be parsed or name something unknown are skipped, counted, and summarized
+/// in the log on shutdown.
+pub struct FileSource<R> {
+    reader: EventLogReader<R>,
+    status: ReplayStatus,
+    skipped: SkippedRows,
+    clock: SharedClock,
+    /// Clock reading when the replay started.
+    start: Duration,
+    first_timestamp: Option<u64>,
+    /// The next event and its offset into the log, held back because it was not due yet.
+    pending: Option<(Duration, Event)>,
+}
+
+impl<R: BufRead> FileSource<R> {
+    /// Creates the source, starting the replay's timeline now.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Reader over the event log.
+    /// * `status` - Initial progress, naming the log and the playback speed multiplier (`1.0` is
+    ///   original timing, `0` replays as fast as possible).
+    /// * `clock` - The clock pacing playback.
+    pub fn new(reader: EventLogReader<R>, status: ReplayStatus, clock: SharedClock) -> Self {
+        let start = clock.now();
+        FileSource { reader, status, skipped: SkippedRows::default(), clock, start, first_timestamp: None, pending: None }
+    }
+
+    /// Reads the next event the log holds and its offset from the log's first event.
+    fn read_event(&mut self) -> Result<Option<(Duration, Event)>> {
+        while let Some(row) = self.reader.next_row()? {
+            match row {
+                LogRow::Event { timestamp_ms, event } => {
+                    let first = *self.first_timestamp.get_or_insert(timestamp_ms);
+                    return Ok(Some((Duration::from_millis(timestamp_ms.saturating_sub(first)), event)));
+                }
+                LogRow::Skipped(row) => {
+                    self.status.skipped_records += 1;
+                    self.skipped.add(&self.status.source, &row);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead + Send> EventSource for FileSource<R> {
+    fn next_batch(&mut self, max_events: usize) -> Result<Option<Vec<Event>>> {
+        let mut batch = Vec::new();
+        while batch.len() < max_events {
+            let (log_offset, event) = match self.pending.take() {
+                Some(pending) => pending,
+                None => match self.read_event()? {
+                    Some(next) => next,
+                    None if batch.is_empty() => return Ok(None),
+                    None => break,
+                },
+            };
+            if self.status.speed > 0.0 {
+                let target = log_offset.div_f64(self.status.speed);
+                let played = self.clock.now().saturating_sub(self.start);
+                if target > played {
+                    // The event is in the future: hand out what is due, or wait for it a little.
+                    self.pending = Some((log_offset, event));
+                    if batch.is_empty() {
+                        self.clock.sleep(target.saturating_sub(played).min(MAX_PACING_WAIT));
+                    }
+                    break;
+                }
+            }
+            self.status.log_elapsed_ms = log_offset.as_millis() as u64;
+            batch.push(event);
+        }
+        Ok(Some(batch))
+    }
+
+    fn ack(&mut self, events: usize) {
+        self.status.events_replayed += events as u64;
+    }
+
+    fn shutdown(&mut self) {
+        self.status.finished = true;
+        if self.skipped.count > 0 {
+            warn!("{}", self.skipped.render().trim_end());
+        }
+    }
+
+    fn replay_status(&self) -> Option<&ReplayStatus> {
+        Some(&self.status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::ecs::generate_ai_events;
+    use crate::replay::EventRecord;
+    use std::io::Cursor;
+
+    #[test]
+    fn file_sources_batch_their_events_and_count_skipped_records() {
+        let mut log = String::new();
+        for event in generate_ai_events(5) {
+            log.push_str(&serde_json::to_string(&EventRecord::from_components(&event)).unwrap());
+            log.push('\n');
+        }
+        log.push_str("not json\n\n{\"timestamp_ms\": 0, \"service\": \"Bard\", \"vendor\": \"Google\", \"department\": \"HR\"}\n");
+        let reader = EventLogReader::new(Cursor::new(log), LogFormat::Ndjson, ColumnMapping::default(), ContentScanner::builtin()).unwrap();
+        let status = ReplayStatus { speed: 0.0, ..ReplayStatus::default() };
+        let mut source = FileSource::new(reader, status, Arc::new(ManualClock::new(0)));
+
+        assert_eq!(source.next_batch(3).unwrap().map(|batch| batch.len()), Some(3));
+        source.ack(3);
+        assert_eq!(source.next_batch(3).unwrap().map(|batch| batch.len()), Some(2));
+        source.ack(2);
+        assert!(source.next_batch(3).unwrap().is_none());
+        source.shutdown();
+        let status = source.replay_status().unwrap();
+        assert_eq!((status.events_replayed, status.skipped_records, status.finished), (5, 2, true));
+
+        assert!(matches!(SourceSpec::parse("synthetic"), Ok(SourceSpec::Synthetic)));
+        let Ok(SourceSpec::File { path, speed, .. }) = SourceSpec::parse("file:logs/events.csv") else {
+            panic!("expected a file source");
+        };
+        assert_eq!((path, speed), (PathBuf::from("logs/events.csv"), 1.0));
+        assert!(SourceSpec::parse("file:").is_err());
+        assert!(SourceSpec::parse("kafka:broker:9092").unwrap_err().contains("not supported"));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::components::{AIService, Event, LawfulBasis, PiiTypes, Purpose, Region, Timestamp, Usage, UserId};
+use crate::constants::{MODELS_PER_SERVICE, SYNTHETIC_USERS};
+use rand::{rng, Rng};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Most spike events a worker adds to one batch, so a large spike spreads over several batches
+/// instead of stalling a worker.
+pub const MAX_SPIKE_EVENTS_PER_BATCH: usize = 10_000;
+
+/// ChatGPT, which Finance may not use.
+const SPIKE_SERVICE: u8 = 0;
+
+/// OpenAI, the vendor without a conformity assessment.
+const SPIKE_VENDOR: u8 = 0;
+
+/// Finance, which may only use personal data for data analysis.
+const SPIKE_DEPARTMENT: u8 = 2;
+
+/// HR screening, prohibited by the EU AI Act on highly sensitive data.
+const SPIKE_PURPOSE: u8 = 1;
+
+/// The US, which has no EU adequacy decision.
+const SPIKE_REGION: u8 = 3;
+
+/// Lowest data sensitivity of a spike event, at which HR screening becomes prohibited.
+const SPIKE_MIN_SENSITIVITY: u8 = 90;
+
+/// Spike events requested from the TUI or the admin API and not yet generated by a worker.
+#[derive(Debug, Default)]
+pub struct ViolationSpikes {
+    pending: AtomicUsize,
+}
+
+impl ViolationSpikes {
+    /// Queues `count` non-compliant events for the workers to add to their next batches.
+    pub fn inject(&self, count: usize) {
+        self.pending.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Number of queued events no worker has taken yet.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Takes up to `max` queued events, returning how many were taken.
+    pub fn take(&self, max: usize) -> usize {
+        let taken = self.pending.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| {
+            (pending > 0).then(|| pending - pending.min(max))
+        });
+        taken.map_or(0, |pending| pending.min(max))
+    }
+}
+
+/// Generates events that break every built-in compliance check: Finance screening job
+/// candidates with ChatGPT on highly sensitive data, processed in the US by a vendor without a
+/// conformity assessment.
+///
+/// # Arguments
+///
+/// * `count` - The number of events to generate.
+/// * `timestamp_ms` - Time the events happen, in milliseconds since the Unix epoch.
+pub fn generate_violations(count: usize, timestamp_ms: u64) -> Vec<Event> {
+    let mut rng = rng();
+    (0..count)
+        .map(|_| {
+            let ai_service = AIService {
+                name_idx: SPIKE_SERVICE,
+                vendor_idx: SPIKE_VENDOR,
+                // Both ChatGPT models are reached over the API, so the request leaves the EU.
+                model_idx: SPIKE_SERVICE * MODELS_PER_SERVICE as u8 + rng.random_range(0..MODELS_PER_SERVICE) as u8,
+            };
+            let usage = Usage {
+                department_idx: SPIKE_DEPARTMENT,
+                data_sensitivity: rng.random_range(SPIKE_MIN_SENSITIVITY..100),
+            };
+            let user = UserId(rng.random_range(0..SYNTHETIC_USERS));
+            (ai_service, usage, user, Purpose(SPIKE_PURPOSE), Region(SPIKE_REGION), LawfulBasis::NONE, PiiTypes::BIOMETRIC, Timestamp(timestamp_ms))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::{DATA_RESIDENCY, EU_AI_ACT, GDPR, INTERNAL_POLICY, PURPOSE_LIMITATION};
+    use crate::ecs::{process_batch, Parallelism};
+    use crate::policy::Policy;
+    use hecs::World;
+
+    #[test]
+    fn spikes_are_drained_in_bounded_chunks_and_break_every_check() {
+        let spikes = ViolationSpikes::default();
+        assert_eq!(spikes.take(10), 0);
+        spikes.inject(25);
+        spikes.inject(5);
+        assert_eq!(spikes.pending(), 30);
+        assert_eq!(spikes.take(20), 20);
+        assert_eq!(spikes.take(20), 10);
+        assert_eq!(spikes.take(20), 0);
+
+        let policy = Policy::builtin();
+        let events = generate_violations(200, 0);
+        let metrics = process_batch(&mut World::new(), &events, &policy, Parallelism::Sequential, None);
+        assert_eq!(metrics.total_events, 200);
+        for check in [EU_AI_ACT, GDPR, INTERNAL_POLICY, PURPOSE_LIMITATION, DATA_RESIDENCY] {
+            assert_eq!(metrics.violations(check), 200);
+        }
+        assert_eq!(metrics.compliance_percentage(), 0.0);
+    }
+}
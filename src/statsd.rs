@@ -0,0 +1,194 @@
+use crate::compliance::ComplianceRegistry;
+use crate::constants::DEPARTMENT_NAMES;
+use crate::error::{MonitorError, Result};
+use crate::policy::Policy;
+use crate::sink::Sink;
+use crate::metrics::{ComplianceMetrics, CumulativeMetrics, IntervalMetrics};
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// Sink name used in error reports when sending metrics to StatsD.
+pub const STATSD_SINK: &str = "StatsD emitter";
+
+/// Largest datagram sent, small enough to avoid fragmentation on a standard Ethernet MTU.
+const MAX_PACKET: usize = 1432;
+
+/// Pushes the monitor's metrics to a StatsD or DogStatsD server over UDP.
+#[derive(Debug)]
+pub struct StatsdEmitter {
+    socket: UdpSocket,
+    prefix: String,
+    tags: bool,
+}
+
+impl StatsdEmitter {
+    /// Resolves the server address and opens a UDP socket connected to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The server's `host:port`.
+    /// * `prefix` - Prefix of every metric name.
+    /// * `tags` - Whether to send checks, departments, and risk levels as DogStatsD tags instead
+    ///   of encoding them in the metric names.
+    pub fn connect(addr: &str, prefix: &str, tags: bool) -> Result<Self> {
+        let invalid = |message: String| MonitorError::Config(format!("--statsd {}: {}", addr, message));
+        let server = addr
+            .to_socket_addrs()
+            .map_err(|e| invalid(e.to_string()))?
+            .next()
+            .ok_or_else(|| invalid("address did not resolve".to_string()))?;
+        let local = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local).and_then(|socket| socket.connect(server).map(|()| socket));
+        Ok(StatsdEmitter {
+            socket: socket.map_err(|e| invalid(e.to_string()))?,
+            prefix: prefix.trim_end_matches('.').to_string(),
+            tags,
+        })
+    }
+
+    /// Sends one reporting interval's metrics, packing as many lines into each datagram as fit.
+    ///
+    /// A refused datagram only means that no server is listening at the moment; like any StatsD
+    /// client, the emitter keeps sending rather than treating that as a failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Metrics of the interval, sent as counters and its rate as a gauge.
+    /// * `total` - Cumulative metrics, whose compliance is sent as a gauge.
+    /// * `compliance` - The compliance flags, used to name the checks.
+    pub fn emit(&self, interval: &IntervalMetrics, total: &CumulativeMetrics, compliance: &ComplianceRegistry) -> io::Result<()> {
+        let mut packet = String::with_capacity(MAX_PACKET);
+        for line in statsd_lines(&self.prefix, self.tags, interval, total, compliance) {
+            if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET {
+                self.send(&packet)?;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        if !packet.is_empty() {
+            self.send(&packet)?;
+        }
+        Ok(())
+    }
+
+    /// Sends one datagram.
+    fn send(&self, packet: &str) -> io::Result<()> {
+        match self.socket.send(packet.as_bytes()) {
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+}
+
+impl Sink for StatsdEmitter {
+    fn name(&self) -> &'static str {
+        STATSD_SINK
+    }
+
+    fn write(&self, interval: &IntervalMetrics, total: &CumulativeMetrics, policy: &Policy) -> io::Result<()> {
+        self.emit(interval, total, &policy.compliance)
+    }
+}
+
+/// Formats one reporting interval's metrics as StatsD lines.
+///
+/// Counters hold the interval's events, violations per check and per department, events per
+/// risk level, and waived violations. Gauges hold the cumulative compliance percentage and the
+/// interval's processing rate. Without tags, checks, departments, and risk levels are part of the
+/// metric name, such as `violations.gdpr`; with tags they are sent as `#check:gdpr`.
+///
+/// # Arguments
+///
+/// * `prefix` - Prefix of every metric name, without a trailing dot.
+/// * `tags` - Whether to use DogStatsD tags.
+/// * `interval` - Metrics of the interval.
+/// * `total` - Cumulative metrics.
+/// * `compliance` - The compliance flags, used to name the checks.
+pub fn statsd_lines(
+    prefix: &str,
+    tags: bool,
+    interval: &IntervalMetrics,
+    total: &CumulativeMetrics,
+    compliance: &ComplianceRegistry,
+) -> Vec<String> {
+    let rate = interval.rate();
+    let interval = &interval.counts;
+    let line = |name: &str, tag: Option<(&str, &str)>, value: String, kind: &str| match tag {
+        Some((key, tag_value)) if tags => format!("{}.{}:{}|{}|#{}:{}", prefix, name, value, kind, key, metric_part(tag_value)),
+        Some((_, tag_value)) => format!("{}.{}.{}:{}|{}", prefix, name, metric_part(tag_value), value, kind),
+        None => format!("{}.{}:{}|{}", prefix, name, value, kind),
+    };
+    let mut lines = vec![
+        line("events", None, interval.total_events.to_string(), "c"),
+        line("waived_violations", None, interval.waived_counts.iter().sum::<u64>().to_string(), "c"),
+    ];
+    for (flag, info) in compliance.iter() {
+        lines.push(line("violations", Some(("check", info.key)), interval.violations(flag).to_string(), "c"));
+    }
+    for (level, count) in [
+        ("high", interval.high_risk_count),
+        ("medium", interval.medium_risk_count),
+        ("low", interval.low_risk_count),
+    ] {
+        lines.push(line("risk", Some(("level", level)), count.to_string(), "c"));
+    }
+    let empty = ComplianceMetrics::default();
+    for (index, name) in DEPARTMENT_NAMES.iter().enumerate() {
+        let department = interval.departments.get(index).unwrap_or(&empty);
+        lines.push(line("department_events", Some(("department", name)), department.total_events.to_string(), "c"));
+        lines.push(line("department_violations", Some(("department", name)), department.total_violations().to_string(), "c"));
+    }
+    lines.push(line("compliance_percentage", None, format!("{:.3}", total.compliance_percentage()), "g"));
+    lines.push(line("processing_rate", None, format!("{:.1}", rate), "g"));
+    lines
+}
+
+/// Lowercases a name and replaces everything but letters and digits with underscores, so it is
+/// safe in a metric name or tag.
+fn metric_part(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn names_or_tags_checks_departments_and_risk_levels() {
+        let compliance = ComplianceRegistry::builtin();
+        let mut interval = ComplianceMetrics {
+            total_events: 100,
+            violation_counts: vec![0, 7, 0, 0, 2],
+            high_risk_count: 3,
+            departments: vec![ComplianceMetrics::default(); DEPARTMENT_NAMES.len()],
+            ..ComplianceMetrics::default()
+        };
+        interval.departments[3] = ComplianceMetrics {
+            total_events: 40,
+            violation_counts: vec![0, 7, 0, 0, 0],
+            ..ComplianceMetrics::default()
+        };
+        let total = CumulativeMetrics::resume(interval.clone());
+        let interval = IntervalMetrics::new(interval, Duration::from_secs(2), 0);
+
+        let plain = statsd_lines("ai", false, &interval, &total, &compliance);
+        assert_eq!(plain[0], "ai.events:100|c");
+        assert!(plain.contains(&"ai.violations.gdpr:7|c".to_string()));
+        assert!(plain.contains(&"ai.risk.high:3|c".to_string()));
+        assert!(plain.contains(&"ai.department_violations.hr:7|c".to_string()));
+        assert!(plain.contains(&"ai.compliance_percentage:98.200|g".to_string()));
+        assert!(plain.contains(&"ai.processing_rate:50.0|g".to_string()));
+
+        let tagged = statsd_lines("ai", true, &interval, &total, &compliance);
+        assert_eq!(tagged.len(), plain.len());
+        assert!(tagged.contains(&"ai.violations:2|c|#check:data_residency".to_string()));
+        assert!(tagged.contains(&"ai.department_events:40|c|#department:hr".to_string()));
+        assert!(tagged.contains(&"ai.events:100|c".to_string()));
+    }
+}
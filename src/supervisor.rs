@@ -0,0 +1,165 @@
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError};
+use crate::metrics::SharedMetrics;
+use crossbeam_channel::Sender;
+use std::any::Any;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// How often the supervisor checks its workers.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Delay before restarting a worker after its first panic; doubled after each further panic.
+const RESTART_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Longest delay between restarts of a worker that keeps panicking.
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(5);
+
+/// A supervised worker slot.
+struct Supervised {
+    /// The running thread, or `None` while it is waiting to be restarted or has exited.
+    handle: Option<JoinHandle<()>>,
+    /// When to restart the worker after a panic.
+    restart_at: Option<Instant>,
+    backoff: Duration,
+}
+
+/// Runs the local worker threads and restarts any that panic.
+///
+/// Each worker's liveness and restart count are kept in its metric shard, where the aggregator
+/// picks them up for the System tab.
+pub struct WorkerSupervisor<F> {
+    spawn: F,
+    workers: Vec<Supervised>,
+    worker_metrics: Arc<SharedMetrics>,
+}
+
+impl<F> WorkerSupervisor<F>
+where
+    F: Fn(usize) -> io::Result<JoinHandle<()>>,
+{
+    /// Spawns every worker.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - Number of workers; worker `n` owns shard `n` of `worker_metrics`.
+    /// * `spawn` - Spawns worker `n` on a new thread. Called again to restart it after a panic.
+    /// * `worker_metrics` - The workers' metric shards.
+    pub fn start(count: usize, spawn: F, worker_metrics: Arc<SharedMetrics>) -> io::Result<Self> {
+        let mut workers = Vec::with_capacity(count);
+        for worker in 0..count {
+            workers.push(Supervised {
+                handle: Some(spawn(worker)?),
+                restart_at: None,
+                backoff: RESTART_BACKOFF,
+            });
+            worker_metrics.shard(worker).set_alive(true);
+        }
+        Ok(WorkerSupervisor { spawn, workers, worker_metrics })
+    }
+
+    /// Returns true once no worker is running or waiting to be restarted.
+    pub fn is_finished(&self) -> bool {
+        self.workers.iter().all(|slot| slot.handle.is_none() && slot.restart_at.is_none())
+    }
+
+    /// Supervises the workers until the stop signal is set and all of them have exited.
+    ///
+    /// A panicking worker is reported to the supervisor with its panic message and restarted
+    /// after a backoff, so a worker that panics on every batch does not spin.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_signal` - Atomic flag indicating when the workers stop; no restarts happen after it is set.
+    /// * `errors` - Channel sender for error reports to the supervisor.
+    pub fn run(mut self, stop_signal: &AtomicBool, errors: &Sender<ErrorReport>) {
+        while !self.is_finished() {
+            self.poll(stop_signal.load(Ordering::Relaxed), errors);
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Reaps exited workers and restarts those that are due.
+    fn poll(&mut self, stopping: bool, errors: &Sender<ErrorReport>) {
+        for (worker, slot) in self.workers.iter_mut().enumerate() {
+            let shard = self.worker_metrics.shard(worker);
+            if let Some(handle) = slot.handle.take_if(|handle| handle.is_finished()) {
+                shard.set_alive(false);
+                if let Err(payload) = handle.join() {
+                    let thread = format!("worker-{}", worker);
+                    let message = panic_message(payload.as_ref());
+                    report(errors, MonitorError::WorkerPanicked { thread, message }, ErrorPolicy::Retry);
+                    slot.restart_at = Some(Instant::now() + slot.backoff);
+                    slot.backoff = (slot.backoff * 2).min(MAX_RESTART_BACKOFF);
+                }
+            }
+            if stopping {
+                slot.restart_at = None;
+                continue;
+            }
+            if slot.restart_at.is_some_and(|at| Instant::now() >= at) {
+                slot.restart_at = None;
+                match (self.spawn)(worker) {
+                    Ok(handle) => {
+                        info!("restarted worker-{}", worker);
+                        slot.handle = Some(handle);
+                        shard.record_restart();
+                        shard.set_alive(true);
+                    }
+                    Err(e) => report(errors, MonitorError::Io(e), ErrorPolicy::Degrade),
+                }
+            }
+        }
+    }
+}
+
+/// Extracts the message a thread panicked with.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Policy;
+    use crossbeam_channel::unbounded;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn panicking_worker_is_restarted() {
+        let metrics = Arc::new(SharedMetrics::new(1, &Policy::builtin()));
+        let stop_signal = Arc::new(AtomicBool::new(false));
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let spawn = {
+            let (stop_signal, attempts) = (stop_signal.clone(), attempts.clone());
+            move |_worker| {
+                let (stop_signal, attempts) = (stop_signal.clone(), attempts.clone());
+                thread::Builder::new().spawn(move || {
+                    if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        panic!("first attempt fails");
+                    }
+                    stop_signal.store(true, Ordering::SeqCst);
+                })
+            }
+        };
+        let (errors, error_receiver) = unbounded();
+        WorkerSupervisor::start(1, spawn, metrics.clone()).unwrap().run(&stop_signal, &errors);
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        let status = metrics.worker_status()[0];
+        assert_eq!(status.restarts, 1);
+        assert!(!status.alive);
+        let report = error_receiver.try_recv().unwrap();
+        assert_eq!(report.error.to_string(), "worker-0 panicked: first attempt fails");
+        assert_eq!(report.action, ErrorPolicy::Retry);
+    }
+}
@@ -0,0 +1,157 @@
+use hdrhistogram::Histogram;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+/// Longest batch latency tracked precisely, in microseconds; longer batches are recorded as this long.
+const MAX_TRACKED_MICROS: u64 = 60_000_000;
+/// Significant decimal digits kept for every recorded latency.
+const SIGNIFICANT_DIGITS: u8 = 3;
+/// Quantiles shown on the dashboard and exported to Prometheus.
+pub const TAIL_QUANTILES: [f64; 3] = [0.5, 0.95, 0.999];
+
+/// Batch latencies of every local worker since startup, merged in by the aggregator each interval.
+static RUN_BATCH_LATENCY: LazyLock<Mutex<Histogram<u64>>> = LazyLock::new(|| Mutex::new(new_histogram()));
+
+/// Returns an empty HDR histogram of batch latencies in microseconds.
+pub fn new_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, MAX_TRACKED_MICROS, SIGNIFICANT_DIGITS).expect("batch latency histogram bounds are valid")
+}
+
+/// Adds the latencies of one histogram to another. Both must come from [`new_histogram`].
+pub fn merge(into: &mut Histogram<u64>, from: &Histogram<u64>) {
+    into.add(from).expect("batch latency histograms share their bounds");
+}
+
+/// Returns the latencies recorded in `current` since `earlier`, an older copy of the same histogram.
+pub fn delta_since(current: &Histogram<u64>, earlier: &Histogram<u64>) -> Histogram<u64> {
+    let mut delta = current.clone();
+    delta.subtract(earlier).expect("an older copy of a histogram holds none of its later latencies");
+    delta
+}
+
+/// Records a latency in a histogram from [`new_histogram`], capping it at the tracked range.
+pub fn record(histogram: &mut Histogram<u64>, latency: Duration) {
+    let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX).max(1);
+    histogram.saturating_record(micros);
+}
+
+/// Returns the latency at a quantile of a histogram of microseconds.
+pub fn quantile(histogram: &Histogram<u64>, quantile: f64) -> Duration {
+    Duration::from_micros(histogram.value_at_quantile(quantile))
+}
+
+/// A worker's batch latencies since the aggregator last took them.
+pub struct BatchLatencyRecorder {
+    histogram: Mutex<Histogram<u64>>,
+}
+
+impl Default for BatchLatencyRecorder {
+    fn default() -> Self {
+        BatchLatencyRecorder { histogram: Mutex::new(new_histogram()) }
+    }
+}
+
+impl BatchLatencyRecorder {
+    /// Records how long a batch took to process.
+    ///
+    /// # Arguments
+    ///
+    /// * `latency` - Wall-clock time from taking the batch to finishing its metrics
+    pub fn record(&self, latency: Duration) {
+        record(&mut self.histogram.lock().unwrap_or_else(|e| e.into_inner()), latency);
+    }
+
+    /// Returns the latencies recorded since the last call and starts a new interval.
+    pub fn take(&self) -> Histogram<u64> {
+        std::mem::replace(&mut *self.histogram.lock().unwrap_or_else(|e| e.into_inner()), new_histogram())
+    }
+}
+
+/// Adds an interval's batch latencies to those since startup.
+pub fn add_to_run(interval: &Histogram<u64>) {
+    merge(&mut RUN_BATCH_LATENCY.lock().unwrap_or_else(|e| e.into_inner()), interval);
+}
+
+/// Returns the batch latencies of every local worker since startup.
+pub fn run_batch_latency() -> Histogram<u64> {
+    RUN_BATCH_LATENCY.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Renders the batch latencies since startup as a Prometheus summary.
+pub fn prometheus_summary(text: &mut String) {
+    let _ = writeln!(text, "# HELP ecs_batch_latency_seconds Wall-clock time a worker takes per batch.");
+    let _ = writeln!(text, "# TYPE ecs_batch_latency_seconds summary");
+    summary_samples(text, "ecs_batch_latency_seconds", "", &run_batch_latency());
+}
+
+/// Writes the quantile, sum, and count samples of a Prometheus summary of a latency histogram.
+///
+/// # Arguments
+///
+/// * `text` - The exposition text to append to.
+/// * `metric` - Name of the summary.
+/// * `labels` - Labels identifying the series, such as `system="gdpr"`, or an empty string.
+/// * `histogram` - Latencies in microseconds.
+pub fn summary_samples(text: &mut String, metric: &str, labels: &str, histogram: &Histogram<u64>) {
+    let separator = if labels.is_empty() { "" } else { "," };
+    if !histogram.is_empty() {
+        for q in TAIL_QUANTILES {
+            let _ = writeln!(
+                text,
+                "{}{{{}{}quantile=\"{}\"}} {}",
+                metric,
+                labels,
+                separator,
+                q,
+                quantile(histogram, q).as_secs_f64()
+            );
+        }
+    }
+    let labels = if labels.is_empty() { String::new() } else { format!("{{{}}}", labels) };
+    let sum_micros = histogram.mean() * histogram.len() as f64;
+    let _ = writeln!(text, "{}_sum{} {}", metric, labels, sum_micros / 1_000_000.0);
+    let _ = writeln!(text, "{}_count{} {}", metric, labels, histogram.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorder_tracks_the_tail_and_merges_intervals() {
+        let recorder = BatchLatencyRecorder::default();
+        for _ in 0..999 {
+            recorder.record(Duration::from_micros(100));
+        }
+        recorder.record(Duration::from_millis(250));
+        let first = recorder.take();
+        assert_eq!(first.len(), 1000);
+        assert_eq!(quantile(&first, 0.5), Duration::from_micros(100));
+        // Only the single slow batch is above the 99.9th percentile.
+        assert!(quantile(&first, 0.999) < Duration::from_micros(101));
+        assert!(quantile(&first, 1.0) >= Duration::from_micros(249_900));
+        assert!(recorder.take().is_empty());
+
+        recorder.record(Duration::from_secs(600));
+        let mut merged = new_histogram();
+        merge(&mut merged, &first);
+        merge(&mut merged, &recorder.take());
+        assert_eq!(merged.len(), 1001);
+        assert!(quantile(&merged, 1.0) >= Duration::from_secs(59));
+        assert_eq!(delta_since(&merged, &first).len(), 1);
+    }
+
+    #[test]
+    fn summaries_label_every_sample() {
+        let mut histogram = new_histogram();
+        record(&mut histogram, Duration::from_millis(2));
+        let mut text = String::new();
+        summary_samples(&mut text, "latency_seconds", "system=\"gdpr\"", &histogram);
+        assert!(text.starts_with("latency_seconds{system=\"gdpr\",quantile=\"0.5\"} 0.002\n"), "{}", text);
+        assert!(text.ends_with("latency_seconds_sum{system=\"gdpr\"} 0.002\nlatency_seconds_count{system=\"gdpr\"} 1\n"), "{}", text);
+        let mut text = String::new();
+        summary_samples(&mut text, "latency_seconds", "", &new_histogram());
+        assert_eq!(text, "latency_seconds_sum 0\nlatency_seconds_count 0\n");
+    }
+}
@@ -0,0 +1,59 @@
+use crate::error::{report, ErrorPolicy, ErrorReport, MonitorError, Result};
+use core_affinity::CoreId;
+use crossbeam_channel::Sender;
+use std::io;
+use std::thread::{self, JoinHandle};
+
+/// Assigns each local worker the core it should be pinned to, as requested by `--pin-cores`.
+///
+/// An empty list spreads the workers over all available cores. Workers are assigned to the
+/// cores round-robin, so there may be more workers than cores.
+///
+/// # Arguments
+///
+/// * `pin_cores` - Cores given with `--pin-cores`, or `None` if pinning is disabled.
+/// * `workers` - Number of local workers.
+///
+/// # Returns
+///
+/// One entry per worker, `None` if that worker is not pinned.
+pub fn worker_cores(pin_cores: Option<&[usize]>, workers: usize) -> Result<Vec<Option<usize>>> {
+    let Some(requested) = pin_cores else {
+        return Ok(vec![None; workers]);
+    };
+    let available: Vec<usize> = core_affinity::get_core_ids()
+        .ok_or_else(|| MonitorError::Config("--pin-cores is not supported on this platform".to_string()))?
+        .into_iter()
+        .map(|core| core.id)
+        .collect();
+    if let Some(core) = requested.iter().find(|core| !available.contains(core)) {
+        return Err(MonitorError::Config(format!(
+            "core {} is not available for pinning (available: {:?})",
+            core, available
+        )));
+    }
+    let cores = if requested.is_empty() { &available } else { requested };
+    Ok((0..workers).map(|worker| Some(cores[worker % cores.len()])).collect())
+}
+
+/// Pins the calling thread to `core`, if one is given.
+///
+/// A failure is reported to the supervisor; the thread keeps running unpinned.
+///
+/// # Arguments
+///
+/// * `core` - Core to pin to, or `None` to leave the thread unpinned.
+/// * `errors` - Channel sender for error reports to the supervisor.
+pub fn pin_current_thread(core: Option<usize>, errors: &Sender<ErrorReport>) {
+    if let Some(core) = core
+        && !core_affinity::set_for_current(CoreId { id: core })
+    {
+        let thread = thread::current().name().unwrap_or("unnamed thread").to_string();
+        report(errors, MonitorError::PinCore { thread, core }, ErrorPolicy::Degrade);
+    }
+}
+
+/// Spawns a thread with the given name, which shows up in debuggers, profilers, and `top -H`.
+pub fn spawn_named<T: Send + 'static>(name: &str, f: impl FnOnce() -> T + Send + 'static) -> io::Result<JoinHandle<T>> {
+    thread::Builder::new().name(name.to_string()).spawn(f)
+}
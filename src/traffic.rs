@@ -0,0 +1,117 @@
+use crate::constants::REGION_NAMES;
+use clap::ValueEnum;
+use std::sync::LazyLock;
+
+/// Share of the users in each region in `REGION_NAMES`, and the region's offset from UTC in hours.
+const REGION_TRAFFIC: [(f64, f64); REGION_NAMES.len()] =
+    [(30.0, 1.0), (10.0, 0.0), (10.0, 9.0), (30.0, -5.0), (12.0, 5.5), (8.0, -3.0)];
+
+/// Local hour around which a region's traffic peaks.
+const PEAK_HOUR: f64 = 13.0;
+
+/// Spread of a region's traffic around its peak, in hours.
+const PEAK_SPREAD: f64 = 3.0;
+
+/// Load of a region in the middle of its night, relative to its peak.
+const NIGHT_LOAD: f64 = 0.1;
+
+/// Load of a region on a Saturday or Sunday, relative to the same hour on a weekday.
+const WEEKEND_LOAD: f64 = 0.3;
+
+/// Step in which [`PEAK_LOAD`] scans a week.
+const SCAN_STEP_MS: u64 = 10 * 60 * 1000;
+
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// Highest summed load of all regions over a week, which `--rate` corresponds to.
+static PEAK_LOAD: LazyLock<f64> = LazyLock::new(|| {
+    let week_ms = 7 * 24 * 3_600_000;
+    (0..week_ms)
+        .step_by(SCAN_STEP_MS as usize)
+        .map(|unix_ms| region_loads(unix_ms).iter().sum::<f64>())
+        .fold(0.0, f64::max)
+});
+
+/// How the rate and the regional mix of generated events vary over time, selected by `--traffic`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TrafficPattern {
+    /// A constant rate, with requests processed in every region equally often.
+    #[default]
+    Flat,
+    /// Business-hours peaks in each region's time zone and quiet weekends; `--rate` is the
+    /// busiest hour of the week, and requests are processed where users are awake.
+    Diurnal,
+}
+
+impl TrafficPattern {
+    /// Returns the share of the peak rate generated at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `unix_ms` - Time on the run's clock, in milliseconds since the Unix epoch.
+    pub fn load(self, unix_ms: u64) -> f64 {
+        match self {
+            TrafficPattern::Flat => 1.0,
+            TrafficPattern::Diurnal => region_loads(unix_ms).iter().sum::<f64>() / *PEAK_LOAD,
+        }
+    }
+
+    /// Returns the relative weight of each region in `REGION_NAMES` at a time, or `None` if every
+    /// region is equally likely.
+    ///
+    /// # Arguments
+    ///
+    /// * `unix_ms` - Time on the run's clock, in milliseconds since the Unix epoch.
+    pub fn region_weights(self, unix_ms: u64) -> Option<[f64; REGION_NAMES.len()]> {
+        match self {
+            TrafficPattern::Flat => None,
+            TrafficPattern::Diurnal => Some(region_loads(unix_ms)),
+        }
+    }
+}
+
+/// Returns the load of each region at a time: its share of the users, scaled by how busy its
+/// local hour and weekday are.
+fn region_loads(unix_ms: u64) -> [f64; REGION_NAMES.len()] {
+    REGION_TRAFFIC.map(|(share, utc_offset)| {
+        let local_hours = unix_ms as f64 / MS_PER_HOUR + utc_offset;
+        // The Unix epoch was a Thursday, so Monday is day 0 of the week after shifting by 3.
+        let weekday = ((local_hours / 24.0).floor() as i64 + 3).rem_euclid(7);
+        let hour = local_hours.rem_euclid(24.0);
+        // Distance from the peak hour around the clock, so late evenings wrap to early mornings.
+        let from_peak = (hour - PEAK_HOUR).abs().min(24.0 - (hour - PEAK_HOUR).abs());
+        let daytime = (-0.5 * (from_peak / PEAK_SPREAD).powi(2)).exp();
+        let load = NIGHT_LOAD + (1.0 - NIGHT_LOAD) * daytime;
+        let day_load = if weekday >= 5 { WEEKEND_LOAD } else { 1.0 };
+        share * load * day_load
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diurnal_traffic_peaks_on_weekday_business_hours_and_follows_the_sun() {
+        // Monday 2025-01-06 and Sunday 2025-01-12, at midnight UTC.
+        let monday_ms = 1_736_121_600_000;
+        let sunday_ms = monday_ms + 6 * 24 * 3_600_000;
+        let at = |day_ms: u64, hour: u64| day_ms + hour * 3_600_000;
+
+        assert_eq!(TrafficPattern::Flat.load(at(monday_ms, 3)), 1.0);
+        assert_eq!(TrafficPattern::Flat.region_weights(monday_ms), None);
+
+        let diurnal = TrafficPattern::Diurnal;
+        let afternoon = diurnal.load(at(monday_ms, 14));
+        assert!(afternoon > 0.8 && afternoon <= 1.0, "{}", afternoon);
+        assert!(diurnal.load(at(monday_ms, 5)) < afternoon / 2.0);
+        assert!(diurnal.load(at(sunday_ms, 14)) < afternoon / 2.0);
+
+        // Japan is busiest in the UTC morning, the US in the UTC evening.
+        let (japan, us) = (2, 3);
+        let morning = diurnal.region_weights(at(monday_ms, 4)).unwrap();
+        let evening = diurnal.region_weights(at(monday_ms, 19)).unwrap();
+        assert!(morning[japan] > morning[us]);
+        assert!(evening[us] > evening[japan]);
+    }
+}
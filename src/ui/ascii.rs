@@ -0,0 +1,135 @@
+use clap::ValueEnum;
+use std::env;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::widgets::Widget;
+
+/// When the dashboard draws with ASCII characters only, selected with `--ascii`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum AsciiMode {
+    /// When the terminal looks unable to draw Braille and box drawing characters.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl AsciiMode {
+    /// Returns true if the dashboard should draw with ASCII characters only.
+    pub fn enabled(self) -> bool {
+        match self {
+            AsciiMode::Auto => legacy_terminal(|name| env::var(name).ok(), cfg!(windows)),
+            AsciiMode::Always => true,
+            AsciiMode::Never => false,
+        }
+    }
+}
+
+/// Returns true if the environment points to a terminal that mangles Braille and box drawing:
+/// a locale without UTF-8, the Linux console or another terminal type without Unicode fonts, or
+/// a Windows console outside Windows Terminal.
+///
+/// # Arguments
+///
+/// * `var` - Looks up an environment variable.
+/// * `windows` - Whether the monitor runs on Windows.
+pub fn legacy_terminal(var: impl Fn(&str) -> Option<String>, windows: bool) -> bool {
+    if windows {
+        return var("WT_SESSION").is_none() && var("TERM_PROGRAM").is_none();
+    }
+    if var("TERM").is_some_and(|term| matches!(term.as_str(), "linux" | "dumb" | "vt100" | "vt220")) {
+        return true;
+    }
+    // The first locale variable set decides, as in the C library.
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].into_iter().find_map(|name| var(name).filter(|value| !value.is_empty()));
+    locale.is_some_and(|locale| {
+        let locale = locale.to_lowercase();
+        !locale.contains("utf-8") && !locale.contains("utf8")
+    })
+}
+
+/// Returns the ASCII stand-in for a symbol drawn on the dashboard, or the symbol itself if it is
+/// ASCII already.
+pub fn ascii_symbol(symbol: &str) -> &str {
+    if symbol.is_ascii() {
+        return symbol;
+    }
+    let mut chars = symbol.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return "?";
+    };
+    match c {
+        '─' | '━' | '═' | '╌' | '┄' => "-",
+        '│' | '┃' | '║' | '╎' | '┆' => "|",
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╭' | '╮' | '╰' | '╯' | '╔' | '╗' | '╚' | '╝' | '┏' | '┓' | '┗'
+        | '┛' => "+",
+        '█' | '▓' | '▇' | '▉' | '▊' | '▋' => "#",
+        '▒' => "%",
+        '░' => ":",
+        '▄' | '▅' | '▆' => "=",
+        '▁' | '▂' | '▃' => "_",
+        '▌' | '▍' | '▎' | '▏' => "|",
+        '•' | '·' => "*",
+        '✓' => "v",
+        '✗' => "x",
+        'µ' => "u",
+        '…' => ".",
+        // The blank Braille pattern, then any dots.
+        '\u{2800}' => " ",
+        '\u{2801}'..='\u{28ff}' => ".",
+        _ => "?",
+    }
+}
+
+/// Replaces every symbol of a rendered frame with its ASCII stand-in, keeping the colors.
+pub struct AsciiFallback;
+
+impl Widget for AsciiFallback {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                if !cell.symbol.is_ascii() {
+                    let symbol = ascii_symbol(&cell.symbol).to_string();
+                    cell.set_symbol(&symbol);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::style::{Color, Style};
+    use tui::widgets::{Block, Borders};
+
+    #[test]
+    fn frames_are_redrawn_in_ascii() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 3));
+        Block::default().borders(Borders::ALL).render(Rect::new(0, 0, 8, 3), &mut buffer);
+        buffer.set_string(1, 1, "✓ 5µs", Style::default().fg(Color::Green));
+        buffer.set_string(6, 1, "\u{28ff}", Style::default());
+        AsciiFallback.render(buffer.area, &mut buffer);
+        let rows: Vec<String> = (0..3).map(|y| (0..8).map(|x| buffer.get(x, y).symbol.as_str()).collect()).collect();
+        assert_eq!(rows, ["+------+", "|v 5us.|", "+------+"]);
+        assert_eq!(buffer.get(1, 1).fg, Color::Green);
+        assert_eq!(ascii_symbol("日"), "?");
+    }
+
+    #[test]
+    fn legacy_terminals_are_detected_from_the_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+        };
+        assert!(!legacy_terminal(env(&[("LANG", "en_GB.UTF-8"), ("TERM", "xterm-256color")]), false));
+        assert!(legacy_terminal(env(&[("LANG", "C")]), false));
+        // LC_ALL overrides LANG, and an empty variable is skipped.
+        assert!(legacy_terminal(env(&[("LC_ALL", "POSIX"), ("LANG", "en_US.utf8")]), false));
+        assert!(!legacy_terminal(env(&[("LC_ALL", ""), ("LANG", "en_US.utf8")]), false));
+        assert!(legacy_terminal(env(&[("LANG", "en_US.UTF-8"), ("TERM", "linux")]), false));
+        assert!(!legacy_terminal(env(&[]), false));
+        assert!(legacy_terminal(env(&[]), true));
+        assert!(!legacy_terminal(env(&[("WT_SESSION", "1")]), true));
+    }
+}
@@ -1,27 +1,65 @@
-use crate::constants::TAB_NAMES;
+use crate::alerts::{Alert, AlertLog};
+use crate::cases::{Case, CaseBook, CaseState};
+use crate::clock::now_millis;
+use crate::constants::{DEPARTMENT_NAMES, SERVICE_NAMES, TAB_NAMES};
+use crate::event_sample::{top_groups, SampledEvent};
+use crate::frontend::{ChartThresholds, ControlCommand, FrontendCommand, InstanceStatus, PipelineHealth, ReplayStatus, ThreadThroughput};
+use crate::logging;
 use crate::metrics::ComplianceMetrics;
+use crate::policy::Policy;
+use crate::shadow::PolicyComparison;
+use crate::shadow_ai::shadow_ai_uses;
+use crate::ui::ascii::AsciiFallback;
+use crate::ui::keys::{Action, Keymap};
+use crate::ui::layout::{DashboardLayout, Panel};
+use crate::ui::search::{jump_to_match, Search};
+use crate::ui::snapshot::write_frame;
+use crate::ui::sort::{violation_rate, SortOrder};
+use crate::ui::theme::Theme;
 use crate::ui::widgets::*;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossbeam_channel::Sender;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use std::borrow::Cow;
 use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     Terminal,
 };
 
-/// Commands that can be sent to update the dashboard state.
-pub enum DashboardCommand {
-    /// Update the displayed metrics.
-    UpdateMetrics(ComplianceMetrics),
-}
+/// Number of log lines scrolled per mouse wheel step.
+const LOG_SCROLL_LINES: usize = 3;
+
+/// Service and department groups listed on the Events tab; five fit above the event table.
+const TOP_SAMPLE_GROUPS: usize = 5;
+
+/// Most times the history charts can be zoomed in, each halving the span shown.
+const MAX_CHART_ZOOM: u32 = 6;
+
+/// Smallest terminal the dashboard is drawn in; smaller ones show how far to enlarge it instead.
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 12;
+
+/// How long the alert banner stays in each phase of its flashing.
+const BANNER_FLASH: Duration = Duration::from_millis(500);
 
 /// Enumeration of dashboard tabs.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DashboardTab {
     Overview,
     Services,
     Compliance,
     Risk,
+    System,
+    Logs,
+    Cases,
+    Fleet,
+    Events,
+    Vendors,
+    Alerts,
 }
 
 impl DashboardTab {
@@ -32,148 +70,963 @@ impl DashboardTab {
             DashboardTab::Services => 1,
             DashboardTab::Compliance => 2,
             DashboardTab::Risk => 3,
+            DashboardTab::System => 4,
+            DashboardTab::Logs => 5,
+            DashboardTab::Cases => 6,
+            DashboardTab::Fleet => 7,
+            DashboardTab::Events => 8,
+            DashboardTab::Vendors => 9,
+            DashboardTab::Alerts => 10,
+        }
+    }
+
+    /// Returns the tab at the given index, the inverse of [`DashboardTab::index`].
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(DashboardTab::Overview),
+            1 => Some(DashboardTab::Services),
+            2 => Some(DashboardTab::Compliance),
+            3 => Some(DashboardTab::Risk),
+            4 => Some(DashboardTab::System),
+            5 => Some(DashboardTab::Logs),
+            6 => Some(DashboardTab::Cases),
+            7 => Some(DashboardTab::Fleet),
+            8 => Some(DashboardTab::Events),
+            9 => Some(DashboardTab::Vendors),
+            10 => Some(DashboardTab::Alerts),
+            _ => None,
+        }
+    }
+}
+
+/// Slice of the metrics history shown by the rate and violation charts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChartWindow {
+    OneMinute,
+    FiveMinutes,
+    ThirtyMinutes,
+    #[default]
+    All,
+}
+
+impl ChartWindow {
+    /// Returns how far back the window reaches, or `None` for the whole run.
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            ChartWindow::OneMinute => Some(Duration::from_secs(60)),
+            ChartWindow::FiveMinutes => Some(Duration::from_secs(5 * 60)),
+            ChartWindow::ThirtyMinutes => Some(Duration::from_secs(30 * 60)),
+            ChartWindow::All => None,
+        }
+    }
+
+    /// Returns the short label shown in chart titles.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartWindow::OneMinute => "1m",
+            ChartWindow::FiveMinutes => "5m",
+            ChartWindow::ThirtyMinutes => "30m",
+            ChartWindow::All => "all",
+        }
+    }
+
+    /// Returns the next window in the `w` key's cycle.
+    pub fn next(&self) -> Self {
+        match self {
+            ChartWindow::OneMinute => ChartWindow::FiveMinutes,
+            ChartWindow::FiveMinutes => ChartWindow::ThirtyMinutes,
+            ChartWindow::ThirtyMinutes => ChartWindow::All,
+            ChartWindow::All => ChartWindow::OneMinute,
         }
     }
 }
 
+/// The chart window, and how far the history charts are zoomed into and panned across it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChartView {
+    pub window: ChartWindow,
+    /// Times the span shown has been halved with `z`.
+    pub zoom: u32,
+    /// Half spans the charts end before the end of the window, panned with Left and Right.
+    pub pan: u32,
+    /// End of the reporting interval under the inspection cursor, in monitor time, or `None`
+    /// while the cursor is hidden.
+    pub cursor: Option<Duration>,
+}
+
+impl ChartView {
+    /// Shows the whole of a new window, keeping the inspection cursor if it is shown.
+    pub fn with_window(self, window: ChartWindow) -> Self {
+        ChartView { window, zoom: 0, pan: 0, cursor: self.cursor }
+    }
+
+    /// Halves the span shown, keeping its end in place.
+    pub fn zoom_in(self) -> Self {
+        if self.zoom == MAX_CHART_ZOOM {
+            return self;
+        }
+        ChartView { zoom: self.zoom + 1, pan: self.pan * 2, ..self }
+    }
+
+    /// Doubles the span shown, keeping it within the window.
+    pub fn zoom_out(self) -> Self {
+        let zoom = self.zoom.saturating_sub(1);
+        ChartView { zoom, pan: (self.pan / 2).min(Self::max_pan(zoom)), ..self }
+    }
+
+    /// Moves the span shown by half its length, back in time or towards the latest interval.
+    pub fn pan(self, later: bool) -> Self {
+        let pan = if later { self.pan.saturating_sub(1) } else { (self.pan + 1).min(Self::max_pan(self.zoom)) };
+        ChartView { pan, ..self }
+    }
+
+    /// Shows the inspection cursor on the latest interval shown, or hides it.
+    pub fn toggle_cursor(self, metrics: &ComplianceMetrics) -> Self {
+        let cursor = match self.cursor {
+            Some(_) => None,
+            None => self.range(metrics).map(|(_, end)| end),
+        };
+        ChartView { cursor, ..self }
+    }
+
+    /// Moves the inspection cursor to the previous or next reporting interval shown.
+    pub fn move_cursor(self, metrics: &ComplianceMetrics, later: bool) -> Self {
+        let (Some(cursor), Some((start, end))) = (self.cursor, self.range(metrics)) else {
+            return self;
+        };
+        let history = &metrics.history;
+        let first = history.partition_point(|point| point.at <= start);
+        let last = history.partition_point(|point| point.at <= end);
+        if first == last {
+            return self;
+        }
+        // The cursor may have been left outside the span shown by zooming or panning.
+        let current = history.partition_point(|point| point.at < cursor).clamp(first, last - 1);
+        let index = if later { (current + 1).min(last - 1) } else { current.saturating_sub(1).max(first) };
+        ChartView { cursor: Some(history[index].at), ..self }
+    }
+
+    /// Returns the furthest the charts can be panned back at a zoom level, which shows the start
+    /// of the window.
+    fn max_pan(zoom: u32) -> u32 {
+        2 * ((1 << zoom) - 1)
+    }
+
+    /// Returns the short label shown in chart titles, e.g. `5m` or `5m, 4x`.
+    pub fn label(&self) -> Cow<'static, str> {
+        match self.zoom {
+            0 => Cow::Borrowed(self.window.label()),
+            zoom => Cow::Owned(format!("{}, {}x", self.window.label(), 1u32 << zoom)),
+        }
+    }
+
+    /// Returns the start and end, in monitor time, of the history the charts show, or `None`
+    /// before the first reporting interval.
+    pub fn range(&self, metrics: &ComplianceMetrics) -> Option<(Duration, Duration)> {
+        let (first, latest) = (metrics.history.first()?, metrics.history.last()?);
+        let start = match self.window.duration() {
+            Some(window) => latest.at.saturating_sub(window),
+            None => first.at.saturating_sub(first.span),
+        };
+        let span = (latest.at - start) / (1 << self.zoom);
+        let end = latest.at.saturating_sub(span * self.pan / 2);
+        Some((end.saturating_sub(span).max(start), end))
+    }
+}
+
+/// The pane of a tab focused with `f`, by its position in the tab's layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaneFocus {
+    pub row: usize,
+    pub column: usize,
+    /// Whether the pane fills the tab, collapsing the others, as toggled with `m`.
+    pub maximized: bool,
+}
+
+/// A service or department selected by clicking its bar in a usage chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Drilldown {
+    Service(usize),
+    Department(usize),
+}
+
+/// Part of the last rendered frame that reacts to the mouse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MouseTarget {
+    Tab(usize),
+    Service(usize),
+    Department(usize),
+    /// The thread table, scrolled with the mouse wheel.
+    Threads,
+    /// The log view, scrolled with the mouse wheel.
+    Logs,
+}
+
 /// The main dashboard structure holding metrics and UI state.
 pub struct Dashboard {
     pub metrics: ComplianceMetrics,
+    pub policy: Arc<Policy>,
+    /// Colors used by every widget.
+    pub theme: Theme,
+    /// Reference lines drawn across the history charts.
+    pub thresholds: ChartThresholds,
+    /// Layouts of the tabs as configured, restored with `=`.
+    pub layout: DashboardLayout,
+    /// Layouts of the tabs with the panes resized at runtime.
+    pub panes: DashboardLayout,
+    /// Focused pane of each tab, by tab index.
+    pub pane_focus: [Option<PaneFocus>; TAB_NAMES.len()],
     pub active_tab: DashboardTab,
+    /// Time window of the rate and violation charts, and the zoom of the history charts.
+    pub chart_view: ChartView,
+    /// Index into `DEPARTMENT_NAMES` of the department every tab is limited to, or `None` for all.
+    pub department_filter: Option<usize>,
+    /// Search limiting the rows of the lists and tables, typed after `/`.
+    pub search: Search,
+    /// Order of the usage charts, the vendor registry, and the top users, changed with `O` and `R`.
+    pub sort: SortOrder,
+    pub replay: Option<ReplayStatus>,
+    pub health: Option<PipelineHealth>,
+    pub threads: Vec<ThreadThroughput>,
+    /// Federated instances and remote workers reporting to this monitor.
+    pub fleet: Vec<InstanceStatus>,
+    /// Violations under the active and the candidate policy, if shadow mode is enabled.
+    pub comparison: Option<PolicyComparison>,
+    pub last_error: Option<String>,
+    pub error_count: usize,
+    pub notice: Option<String>,
+    pub control: Sender<ControlCommand>,
     pub should_quit: bool,
+    /// Whether the help overlay is shown over the active tab.
+    pub show_help: bool,
+    /// Service or department whose details are shown on the Services tab.
+    pub drilldown: Option<Drilldown>,
+    /// Index into `SERVICE_NAMES` of the service whose compliance view replaces the Services tab.
+    pub service_view: Option<usize>,
+    /// Number of threads scrolled past at the top of the thread table.
+    pub thread_scroll: usize,
+    /// Number of lines the log view is scrolled back from the newest line.
+    pub log_scroll: usize,
+    pub cases: CaseBook,
+    /// Index of the case selected on the Cases tab.
+    pub case_selected: usize,
+    /// Most recent sampled events, newest first.
+    pub sampled_events: Vec<SampledEvent>,
+    /// Index of the event selected on the Events tab.
+    pub event_selected: usize,
+    pub alerts: AlertLog,
+    /// Index of the alert selected on the Alerts tab, which lists the newest first.
+    pub alert_selected: usize,
+    /// When the dashboard started, timing the flashing of the alert banner.
+    started: Instant,
+    /// Clickable and scrollable areas of the last rendered frame.
+    mouse_targets: Vec<(Rect, MouseTarget)>,
+    /// Row and column of each pane of the active tab shown in the last rendered frame, before
+    /// any pane was maximized, in the order `f` moves through them.
+    shown_panes: Vec<(usize, usize)>,
+    /// Whether the next rendered frame is saved to a file, as requested with `s`.
+    snapshot_requested: bool,
+    /// Keys bound to the dashboard's actions.
+    keymap: Keymap,
 }
 
 impl Dashboard {
     /// Creates a new instance of the Dashboard.
-    pub fn new() -> Self {
+    pub fn new(policy: Arc<Policy>, control: Sender<ControlCommand>, theme: Theme, thresholds: ChartThresholds, layout: DashboardLayout, keymap: Keymap) -> Self {
         Dashboard {
             metrics: ComplianceMetrics::default(),
+            policy,
+            theme,
+            thresholds,
+            panes: layout.clone(),
+            layout,
+            pane_focus: [None; TAB_NAMES.len()],
             active_tab: DashboardTab::Overview,
+            chart_view: ChartView::default(),
+            department_filter: None,
+            search: Search::default(),
+            sort: SortOrder::default(),
+            replay: None,
+            health: None,
+            threads: Vec::new(),
+            fleet: Vec::new(),
+            comparison: None,
+            last_error: None,
+            error_count: 0,
+            notice: None,
+            control,
             should_quit: false,
+            show_help: false,
+            drilldown: None,
+            service_view: None,
+            thread_scroll: 0,
+            log_scroll: 0,
+            cases: CaseBook::default(),
+            case_selected: 0,
+            sampled_events: Vec::new(),
+            event_selected: 0,
+            alerts: AlertLog::default(),
+            alert_selected: 0,
+            started: Instant::now(),
+            mouse_targets: Vec::new(),
+            shown_panes: Vec::new(),
+            snapshot_requested: false,
+            keymap,
         }
     }
 
     /// Handles an incoming command to update the dashboard.
-    pub fn handle_command(&mut self, cmd: DashboardCommand) {
+    pub fn handle_command(&mut self, cmd: FrontendCommand) {
         match cmd {
-            DashboardCommand::UpdateMetrics(metrics) => self.metrics = metrics,
+            FrontendCommand::UpdateMetrics(metrics) => {
+                self.metrics = metrics.into_totals();
+                // Reports sent before any events were counted carry no departments or services.
+                self.metrics.departments.resize_with(DEPARTMENT_NAMES.len(), ComplianceMetrics::default);
+                self.metrics.services.resize_with(SERVICE_NAMES.len(), ComplianceMetrics::default);
+            }
+            FrontendCommand::UpdateReplay(status) => self.replay = Some(status),
+            FrontendCommand::UpdateHealth(health) => self.health = Some(health),
+            FrontendCommand::UpdateThreads(threads) => self.threads = threads,
+            FrontendCommand::UpdateFleet(instances) => self.fleet = instances,
+            FrontendCommand::UpdateComparison(comparison) => self.comparison = Some(comparison),
+            FrontendCommand::ReportError(message) => {
+                self.last_error = Some(message);
+                self.error_count += 1;
+            }
+            FrontendCommand::ShowNotice(message) => self.notice = Some(message),
+            FrontendCommand::UpdateCases(cases) => self.cases = cases,
+            FrontendCommand::UpdateAlerts(alerts) => self.alerts = alerts,
+            FrontendCommand::UpdatePolicy(policy) => self.policy = policy,
+            FrontendCommand::UpdateSampledEvents(events) => self.sampled_events = events,
         }
     }
 
-    /// Processes a key event to update the UI (tab switching, quitting, etc.).
+    /// Processes a key event, running the action its key sequence is bound to.
     pub fn handle_key_event(&mut self, key: KeyEvent) {
+        if self.search.editing {
+            self.edit_search(key);
+            return;
+        }
+        let Some(action) = self.keymap.press(key) else {
+            return;
+        };
+        // The help overlay is modal: it swallows every action until it is dismissed.
+        if self.show_help {
+            if matches!(action, Action::Back | Action::Help) {
+                self.show_help = false;
+            }
+            return;
+        }
+        let tab = self.active_tab;
+        match action {
+            Action::Back if self.search.is_active() => self.search.clear(),
+            Action::Back if self.service_view.is_some() => self.service_view = None,
+            Action::Back if self.pane_focus[tab.index()].is_some_and(|focus| focus.maximized) => self.toggle_maximized(),
+            Action::Quit | Action::Back => self.should_quit = true,
+            Action::Tab(index) => {
+                if let Some(tab) = DashboardTab::from_index(index) {
+                    self.active_tab = tab;
+                }
+            }
+            Action::NextTab | Action::PreviousTab => {
+                let count = TAB_NAMES.len();
+                let step = if action == Action::NextTab { 1 } else { count - 1 };
+                self.active_tab = DashboardTab::from_index((tab.index() + step) % count).unwrap_or(tab);
+            }
+            Action::Export => {
+                let _ = self.control.send(ControlCommand::ExportCsv);
+            }
+            Action::Inject => {
+                let _ = self.control.send(ControlCommand::InjectSpike { events: None });
+            }
+            Action::Help => self.show_help = true,
+            Action::TimeWindow => self.chart_view = self.chart_view.with_window(self.chart_view.window.next()),
+            Action::ZoomIn => self.chart_view = self.chart_view.zoom_in(),
+            Action::ZoomOut => self.chart_view = self.chart_view.zoom_out(),
+            Action::Sort => self.sort = self.sort.next_key(),
+            Action::ReverseSort => self.sort = self.sort.reverse(),
+            Action::SaveScreen => self.snapshot_requested = true,
+            Action::FocusPane => self.focus_next_pane(),
+            Action::MaximizePane => self.toggle_maximized(),
+            Action::GrowPane | Action::ShrinkPane => {
+                if let Some(focus) = self.pane_focus[tab.index()] {
+                    self.panes.tab_mut(tab.index()).resize(focus.row, focus.column, action == Action::GrowPane);
+                }
+            }
+            Action::ResetLayout => {
+                *self.panes.tab_mut(tab.index()) = self.layout.tab(tab.index()).clone();
+                if let Some(focus) = &mut self.pane_focus[tab.index()] {
+                    focus.maximized = false;
+                }
+            }
+            Action::ChartCursor => self.chart_view = self.chart_view.toggle_cursor(self.visible_metrics()),
+            Action::Left | Action::Right if matches!(tab, DashboardTab::Overview | DashboardTab::System) => {
+                let later = action == Action::Right;
+                self.chart_view = match self.chart_view.cursor {
+                    Some(_) => self.chart_view.move_cursor(self.visible_metrics(), later),
+                    None => self.chart_view.pan(later),
+                };
+            }
+            Action::DepartmentFilter => {
+                self.department_filter = match self.department_filter {
+                    None => Some(0),
+                    Some(index) if index + 1 < DEPARTMENT_NAMES.len() => Some(index + 1),
+                    Some(_) => None,
+                };
+            }
+            Action::Left | Action::Right if tab == DashboardTab::Services => self.select_service(action == Action::Right),
+            Action::Select if tab == DashboardTab::Services => {
+                if let Some(Drilldown::Service(index)) = self.drilldown {
+                    self.service_view = Some(index);
+                }
+            }
+            Action::Select if tab == DashboardTab::Alerts => {
+                if let Some(alert) = self.matching_alerts().get(self.alert_selected) {
+                    let _ = self.control.send(ControlCommand::AcknowledgeAlert { alert: alert.id });
+                }
+            }
+            Action::Up | Action::Down | Action::Top | Action::Bottom => self.move_selection(action),
+            Action::Search => {
+                self.search.start();
+                self.reset_selections();
+            }
+            Action::NextMatch | Action::PreviousMatch => {
+                let forward = action == Action::NextMatch;
+                match tab {
+                    DashboardTab::Cases => self.case_selected = jump_to_match(self.case_selected, self.matching_cases().len(), forward),
+                    DashboardTab::Events => self.event_selected = jump_to_match(self.event_selected, self.matching_events().len(), forward),
+                    DashboardTab::Alerts => self.alert_selected = jump_to_match(self.alert_selected, self.matching_alerts().len(), forward),
+                    _ => {}
+                }
+            }
+            Action::Acknowledge | Action::Waive | Action::Remediate | Action::Reopen if tab == DashboardTab::Cases => {
+                let state = match action {
+                    Action::Acknowledge => CaseState::Acknowledged,
+                    Action::Waive => CaseState::Waived,
+                    Action::Remediate => CaseState::Remediated,
+                    _ => CaseState::Open,
+                };
+                if let Some(&case) = self.matching_case_indices().get(self.case_selected) {
+                    let _ = self.control.send(ControlCommand::SetCaseState { case, state });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Types a key into the search: characters extend it, Backspace removes the last one, Enter
+    /// keeps it, and Esc clears it. The lists are filtered as it is typed.
+    fn edit_search(&mut self, key: KeyEvent) {
         match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
-            KeyCode::Char('1') => self.active_tab = DashboardTab::Overview,
-            KeyCode::Char('2') => self.active_tab = DashboardTab::Services,
-            KeyCode::Char('3') => self.active_tab = DashboardTab::Compliance,
-            KeyCode::Char('4') => self.active_tab = DashboardTab::Risk,
-            KeyCode::Tab => {
-                // Cycle through tabs in order.
-                self.active_tab = match self.active_tab {
-                    DashboardTab::Overview => DashboardTab::Services,
-                    DashboardTab::Services => DashboardTab::Compliance,
-                    DashboardTab::Compliance => DashboardTab::Risk,
-                    DashboardTab::Risk => DashboardTab::Overview,
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.search.push(c),
+            KeyCode::Backspace => self.search.pop(),
+            KeyCode::Enter => {
+                self.search.editing = false;
+                if self.search.query().is_empty() {
+                    self.search.clear();
+                }
+                return;
+            }
+            KeyCode::Esc => self.search.clear(),
+            _ => return,
+        }
+        self.reset_selections();
+    }
+
+    /// Selects the first row of every list, e.g. when the search changes which rows are shown.
+    fn reset_selections(&mut self) {
+        self.case_selected = 0;
+        self.event_selected = 0;
+        self.alert_selected = 0;
+    }
+
+    /// Returns the indices of the cases matching the search, in the order they were opened.
+    fn matching_case_indices(&self) -> Vec<usize> {
+        let cases = self.cases.cases().iter().enumerate();
+        cases.filter(|(_, case)| self.search.matches_case(case)).map(|(index, _)| index).collect()
+    }
+
+    /// Returns the cases matching the search, in the order they were opened.
+    fn matching_cases(&self) -> Vec<&Case> {
+        self.cases.cases().iter().filter(|case| self.search.matches_case(case)).collect()
+    }
+
+    /// Returns the sampled events matching the search, newest first.
+    fn matching_events(&self) -> Vec<&SampledEvent> {
+        self.sampled_events.iter().filter(|event| self.search.matches_event(event)).collect()
+    }
+
+    /// Returns the alerts matching the search, newest first.
+    fn matching_alerts(&self) -> Vec<&Alert> {
+        self.alerts.alerts().iter().rev().filter(|alert| self.search.matches_alert(alert)).collect()
+    }
+
+    /// Returns the number of rows of the active tab's list matching the search, or `None` if the
+    /// tab has no list to select from.
+    fn search_matches(&self) -> Option<usize> {
+        match self.active_tab {
+            DashboardTab::Cases => Some(self.matching_cases().len()),
+            DashboardTab::Events => Some(self.matching_events().len()),
+            DashboardTab::Alerts => Some(self.matching_alerts().len()),
+            _ => None,
+        }
+    }
+
+    /// Moves through the active tab's list by a row, or to its first or last row: the selected
+    /// case, event, or alert, the first thread shown, or how far the logs are scrolled back.
+    fn move_selection(&mut self, action: Action) {
+        match self.active_tab {
+            DashboardTab::Cases => self.case_selected = move_row(self.case_selected, self.matching_cases().len(), action),
+            DashboardTab::Events => self.event_selected = move_row(self.event_selected, self.matching_events().len(), action),
+            DashboardTab::Alerts => self.alert_selected = move_row(self.alert_selected, self.matching_alerts().len(), action),
+            DashboardTab::System => self.thread_scroll = move_row(self.thread_scroll, self.threads.len(), action),
+            DashboardTab::Logs => {
+                // The logs are scrolled back from the newest line, so moving up scrolls further back.
+                let reversed = match action {
+                    Action::Up => Action::Down,
+                    Action::Down => Action::Up,
+                    Action::Top => Action::Bottom,
+                    _ => Action::Top,
                 };
+                self.log_scroll = move_row(self.log_scroll, logging::recent_line_count(), reversed);
+            }
+            _ => {}
+        }
+    }
+
+    /// Processes a mouse event: clicking a tab switches to it, clicking a bar in a usage chart
+    /// shows its details, and the wheel scrolls the thread table and the log view.
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        if self.show_help {
+            return;
+        }
+        let target = self
+            .mouse_targets
+            .iter()
+            .rev()
+            .find(|(area, _)| contains(area, mouse.column, mouse.row))
+            .map(|&(_, target)| target);
+        let Some(target) = target else {
+            return;
+        };
+        match (mouse.kind, target) {
+            (MouseEventKind::Down(MouseButton::Left), MouseTarget::Tab(index)) => {
+                if let Some(tab) = DashboardTab::from_index(index) {
+                    self.active_tab = tab;
+                }
+            }
+            (MouseEventKind::Down(MouseButton::Left), MouseTarget::Service(index)) => {
+                self.toggle_drilldown(Drilldown::Service(index));
+            }
+            (MouseEventKind::Down(MouseButton::Left), MouseTarget::Department(index)) => {
+                self.toggle_drilldown(Drilldown::Department(index));
+            }
+            (MouseEventKind::ScrollDown, MouseTarget::Threads) => {
+                self.thread_scroll = (self.thread_scroll + 1).min(self.threads.len().saturating_sub(1));
+            }
+            (MouseEventKind::ScrollUp, MouseTarget::Threads) => self.thread_scroll = self.thread_scroll.saturating_sub(1),
+            (MouseEventKind::ScrollUp, MouseTarget::Logs) => {
+                self.log_scroll = (self.log_scroll + LOG_SCROLL_LINES).min(logging::recent_line_count().saturating_sub(1));
             }
+            (MouseEventKind::ScrollDown, MouseTarget::Logs) => self.log_scroll = self.log_scroll.saturating_sub(LOG_SCROLL_LINES),
             _ => {}
         }
     }
 
-    /// Renders the dashboard UI.
+    /// Selects the next or previous service on the Services tab, wrapping around, and switches
+    /// an open service view to it.
+    fn select_service(&mut self, forward: bool) {
+        let count = SERVICE_NAMES.len();
+        let index = match (self.service_view, self.drilldown) {
+            (Some(index), _) | (None, Some(Drilldown::Service(index))) => {
+                if forward { (index + 1) % count } else { (index + count - 1) % count }
+            }
+            _ => if forward { 0 } else { count - 1 },
+        };
+        self.drilldown = Some(Drilldown::Service(index));
+        if self.service_view.is_some() {
+            self.service_view = Some(index);
+        }
+    }
+
+    /// Shows the details of a clicked bar on the Services tab, or hides them if it was already shown.
+    fn toggle_drilldown(&mut self, selection: Drilldown) {
+        if self.drilldown == Some(selection) {
+            self.drilldown = None;
+        } else {
+            self.drilldown = Some(selection);
+            self.active_tab = DashboardTab::Services;
+        }
+    }
+
+    /// Returns the metrics shown on every tab: those of the filtered department, or the totals.
+    fn visible_metrics(&self) -> &ComplianceMetrics {
+        self.department_filter
+            .and_then(|index| self.metrics.departments.get(index))
+            .unwrap_or(&self.metrics)
+    }
+
+    /// Renders the dashboard UI, saving the frame to a file in the current directory if a
+    /// snapshot was requested.
     pub fn render<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        terminal.draw(|f| {
+        let mut targets = Vec::new();
+        let mut shown_panes = Vec::new();
+        let frame = terminal.draw(|f| {
             let size = f.size();
-            // Layout: first row for tabs, remaining for content.
+            if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
+                render_terminal_too_small(f, size, (MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT), &self.theme);
+                return;
+            }
+            // Layout: the alert banner, a row for tabs, optional status lines, remaining for content.
+            let banner_height = if self.alerts.most_severe_active().is_some() || self.alerts.silence().is_some() { 1 } else { 0 };
+            let replay_height = if self.replay.is_some() { 1 } else { 0 };
+            let health_height = if self.health.is_some() { 1 } else { 0 };
+            let notice_height = if self.notice.is_some() { 1 } else { 0 };
+            let error_height = if self.last_error.is_some() { 1 } else { 0 };
+            let filter_height = if self.department_filter.is_some() { 1 } else { 0 };
+            let search_height = if self.search.is_active() { 1 } else { 0 };
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(1)
-                .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Length(banner_height),
+                        Constraint::Length(3),
+                        Constraint::Length(replay_height),
+                        Constraint::Length(health_height),
+                        Constraint::Length(notice_height),
+                        Constraint::Length(error_height),
+                        Constraint::Length(filter_height),
+                        Constraint::Length(search_height),
+                        Constraint::Min(0),
+                    ]
+                    .as_ref(),
+                )
                 .split(size);
 
-            // Render the tab bar.
-            render_tabs(f, chunks[0], &TAB_NAMES, self.active_tab.index());
+            // Render the alert banner, the tab bar, and status lines.
+            if banner_height > 0 {
+                // Unacknowledged alerts flash; acknowledged ones stay on screen until they clear.
+                let acknowledged = self.alerts.most_severe_active().is_none_or(|alert| alert.acknowledged);
+                let flash_on = acknowledged || (self.started.elapsed().as_millis() / BANNER_FLASH.as_millis()).is_multiple_of(2);
+                render_alert_banner(f, chunks[0], &self.alerts, flash_on, &self.theme);
+            }
+            let tabs = render_tabs(f, chunks[1], &TAB_NAMES, self.active_tab.index(), &self.theme);
+            targets.extend(tabs.into_iter().enumerate().map(|(index, area)| (area, MouseTarget::Tab(index))));
+            if let Some(replay) = &self.replay {
+                render_replay_status(f, chunks[2], replay, &self.theme);
+            }
+            if let Some(health) = &self.health {
+                render_pipeline_health(f, chunks[3], health, &self.theme);
+            }
+            if let Some(message) = &self.notice {
+                render_notice(f, chunks[4], message, &self.theme);
+            }
+            if let Some(message) = &self.last_error {
+                render_error_status(f, chunks[5], message, self.error_count, &self.theme);
+            }
+            if let Some(index) = self.department_filter {
+                render_department_filter(f, chunks[6], DEPARTMENT_NAMES[index], &self.theme);
+            }
+            if self.search.is_active() {
+                render_search(f, chunks[7], &self.search, self.search_matches(), &self.theme);
+            }
 
             // Render content based on the active tab.
-            match self.active_tab {
-                DashboardTab::Overview => self.render_overview_tab(f, chunks[1]),
-                DashboardTab::Services => self.render_services_tab(f, chunks[1]),
-                DashboardTab::Compliance => self.render_compliance_tab(f, chunks[1]),
-                DashboardTab::Risk => self.render_risk_tab(f, chunks[1]),
+            shown_panes = match self.active_tab {
+                DashboardTab::Services => self.render_services_tab(f, chunks[8], &mut targets),
+                _ => self.render_panes(f, chunks[8], &mut targets),
+            };
+            if self.show_help {
+                render_help(f, size, &self.keymap.help(), &self.theme);
+            }
+            // Last, so overlays are redrawn in ASCII too.
+            if self.theme.ascii {
+                f.render_widget(AsciiFallback, size);
             }
         })?;
+        self.mouse_targets = targets;
+        self.shown_panes = shown_panes;
+        if std::mem::take(&mut self.snapshot_requested) {
+            self.notice = Some(match write_frame(frame.buffer, Path::new(""), now_millis()) {
+                Ok(path) => format!("Saved the dashboard to {} and {}", path.display(), path.with_extension("ansi").display()),
+                Err(e) => format!("Could not save the dashboard: {}", e),
+            });
+        }
         Ok(())
     }
 
-    /// Renders the overview tab: gauge, stats, and charts.
-    fn render_overview_tab<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect) {
-        let chunks = Layout::default()
+    /// Renders the services tab with charts for service and department usage, the shadow AI
+    /// report, and the details of a clicked bar, or the compliance view of a single service. The
+    /// department filter does not apply to the shadow AI report, which lists every department.
+    fn render_services_tab<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect, targets: &mut Vec<(Rect, MouseTarget)>) -> Vec<(usize, usize)> {
+        if let Some(index) = self.service_view {
+            self.render_service_view(f, area, index);
+            return Vec::new();
+        }
+        let metrics = self.visible_metrics();
+        let details_height = if self.drilldown.is_some() { 5 } else { 0 };
+        let outer = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage(20),
-                    Constraint::Percentage(40),
-                    Constraint::Percentage(40),
-                ]
-                .as_ref(),
-            )
+            .constraints([Constraint::Min(0), Constraint::Length(details_height)].as_ref())
             .split(area);
+        let shown_panes = self.render_panes(f, outer[0], targets);
+        match self.drilldown {
+            Some(Drilldown::Service(index)) => {
+                let counts = &metrics.service_counts;
+                render_usage_details(f, outer[1], SERVICE_NAMES[index], counts[index], counts, metrics.total_events, &self.theme);
+            }
+            Some(Drilldown::Department(index)) => {
+                let counts = &metrics.department_counts;
+                render_usage_details(f, outer[1], DEPARTMENT_NAMES[index], counts[index], counts, metrics.total_events, &self.theme);
+            }
+            None => {}
+        }
+        shown_panes
+    }
 
-        // Top: overall compliance gauge.
-        render_compliance_gauge(f, chunks[0], &self.metrics);
-
-        // Middle: stats and service chart.
-        let middle_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(chunks[1]);
-
-        render_stats(f, middle_chunks[0], &self.metrics);
-        render_service_chart(f, middle_chunks[1], &self.metrics);
-
-        // Bottom: processing rate history.
-        render_rate_chart(f, chunks[2], &self.metrics);
+    /// Renders the panes of the active tab, leaving out the panels with nothing to show, or only
+    /// the focused pane if it is maximized.
+    ///
+    /// # Returns
+    ///
+    /// The row and column of each pane the tab's area fits, before any pane was maximized.
+    fn render_panes<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect, targets: &mut Vec<(Rect, MouseTarget)>) -> Vec<(usize, usize)> {
+        let focus = self.pane_focus[self.active_tab.index()];
+        let layout = self.panes.tab(self.active_tab.index());
+        let panes = layout.areas(area, |panel| self.panel_available(panel));
+        let shown_panes = panes.iter().map(|pane| (pane.row, pane.column)).collect();
+        // A maximized pane is shown even if the tab is too small to show it among the others.
+        let maximized = focus.filter(|focus| focus.maximized).map(|focus| layout.rows[focus.row].panels[focus.column].0);
+        if let Some(panel) = maximized.filter(|panel| self.panel_available(*panel)) {
+            self.render_panel(f, area, panel, targets);
+            render_focus(f, area, &self.theme);
+            return shown_panes;
+        }
+        for pane in panes {
+            self.render_panel(f, pane.area, pane.panel, targets);
+            if focus.is_some_and(|focus| (pane.row, pane.column) == (focus.row, focus.column)) {
+                render_focus(f, pane.area, &self.theme);
+            }
+        }
+        shown_panes
     }
 
-    /// Renders the services tab with charts for service and department usage.
-    fn render_services_tab<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(area);
+    /// Returns true if a panel has something to show: the HIPAA summary, SLOs, and candidate
+    /// policy only do when they are configured.
+    fn panel_available(&self, panel: Panel) -> bool {
+        match panel {
+            Panel::Hipaa => self.policy.hipaa.is_some(),
+            Panel::Slos => self.policy.slo.is_some(),
+            Panel::PolicyComparison => self.comparison.is_some(),
+            _ => true,
+        }
+    }
 
-        render_service_chart(f, chunks[0], &self.metrics);
-        render_department_chart(f, chunks[1], &self.metrics);
+    /// Moves the focus of the active tab to its next shown pane, keeping a maximized pane
+    /// maximized.
+    fn focus_next_pane(&mut self) {
+        let index = self.active_tab.index();
+        let focus = self.pane_focus[index];
+        let panes = &self.shown_panes;
+        let current = focus.and_then(|focus| panes.iter().position(|pane| *pane == (focus.row, focus.column)));
+        let next = current.map_or(0, |current| (current + 1) % panes.len().max(1));
+        self.pane_focus[index] = panes.get(next).map(|(row, column)| PaneFocus {
+            row: *row,
+            column: *column,
+            maximized: focus.is_some_and(|focus| focus.maximized),
+        });
     }
 
-    /// Renders the compliance tab with gauge and violations chart.
-    fn render_compliance_tab<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
-            .split(area);
+    /// Maximizes the focused pane of the active tab, or restores the others; focuses the first
+    /// pane first if none is.
+    fn toggle_maximized(&mut self) {
+        let index = self.active_tab.index();
+        if self.pane_focus[index].is_none() {
+            self.focus_next_pane();
+        }
+        if let Some(focus) = &mut self.pane_focus[index] {
+            focus.maximized = !focus.maximized;
+        }
+    }
 
-        render_compliance_gauge(f, chunks[0], &self.metrics);
-        render_violation_chart(f, chunks[1], &self.metrics);
+    /// Renders one panel of a tab, recording the parts of it that react to the mouse. Panels
+    /// showing data per department, per case, or per instance ignore the department filter, as
+    /// they do on their built-in tabs.
+    fn render_panel<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect, panel: Panel, targets: &mut Vec<(Rect, MouseTarget)>) {
+        let metrics = self.visible_metrics();
+        let compliance = &self.policy.compliance;
+        let theme = &self.theme;
+        match panel {
+            Panel::Gauges => render_compliance_gauges(f, area, metrics, &self.policy, theme),
+            Panel::Hipaa => {
+                if let Some(hipaa) = &self.policy.hipaa {
+                    render_hipaa(f, area, metrics, hipaa, theme);
+                }
+            }
+            Panel::Stats => render_stats(f, area, metrics, compliance, theme),
+            Panel::Services => {
+                let bars = render_service_chart(f, area, metrics, self.sort, theme);
+                targets.extend(bars.into_iter().map(|(area, index)| (area, MouseTarget::Service(index))));
+            }
+            Panel::Departments => {
+                let bars = render_department_chart(f, area, metrics, self.sort, theme);
+                targets.extend(bars.into_iter().map(|(area, index)| (area, MouseTarget::Department(index))));
+            }
+            Panel::ShadowAi => render_shadow_ai(f, area, &shadow_ai_uses(&self.metrics), theme),
+            Panel::Rate => render_rate_chart(f, area, metrics, self.chart_view, self.thresholds.max_rate, &self.alerts, theme),
+            Panel::ComplianceHistory => {
+                render_compliance_chart(f, area, metrics, self.chart_view, self.thresholds.compliance_target, &self.alerts, theme)
+            }
+            Panel::Violations => render_violation_chart(f, area, metrics, compliance, self.chart_view.window, theme),
+            Panel::Nist => render_nist_coverage(f, area, metrics, compliance, theme),
+            Panel::Regions => render_regions(f, area, metrics, theme),
+            Panel::Purposes => render_purpose_chart(f, area, metrics, theme),
+            Panel::LawfulBases => render_lawful_bases(f, area, metrics, theme),
+            Panel::Slos => {
+                if let Some(slo) = &self.policy.slo {
+                    render_slos(f, area, &self.metrics, slo, theme);
+                }
+            }
+            Panel::PolicyComparison => {
+                if let Some(comparison) = &self.comparison {
+                    render_policy_comparison(f, area, comparison, theme);
+                }
+            }
+            Panel::TopUsers => {
+                // One row per line inside the borders, below the header.
+                let visible_users = area.height.saturating_sub(3) as usize;
+                let sorted = self.sort != SortOrder::default();
+                let mut users = metrics.top_users(if self.search.is_active() || sorted { usize::MAX } else { visible_users });
+                users.retain(|(user, _)| self.search.matches([user.to_string().as_str()]));
+                if sorted {
+                    self.sort.sort(&mut users, |(user, counts)| {
+                        (*user, counts.total_risk_score as f64, violation_rate(counts.violations, counts.events))
+                    });
+                }
+                users.truncate(visible_users);
+                if users.is_empty() && self.search.is_active() {
+                    render_no_matches(f, area, "Top Users by Risk", &self.search, theme);
+                } else {
+                    render_top_users(f, area, &users, self.sort, theme);
+                }
+            }
+            Panel::RiskFactors => render_risk_factors(f, area, metrics, &self.policy.risk_factors, theme),
+            Panel::RiskDistribution => render_risk_distribution(f, area, metrics, theme),
+            Panel::AiActTiers => render_ai_act_tiers(f, area, metrics, theme),
+            Panel::PiiTypes => render_pii_types(f, area, metrics, theme),
+            Panel::SystemHealth => render_system_health(f, area, self.health.as_ref(), theme),
+            Panel::SystemLatencies => {
+                let systems = self.health.as_ref().map_or(&[][..], |health| &health.system_latencies);
+                render_system_latencies(f, area, systems, theme);
+            }
+            Panel::Threads => {
+                render_thread_throughput(f, area, &self.threads[self.thread_scroll.min(self.threads.len())..], theme);
+                targets.push((area, MouseTarget::Threads));
+            }
+            Panel::Logs => self.render_log_view(f, area, targets),
+            Panel::CaseSummary => render_case_summary(f, area, &self.cases, theme),
+            Panel::Cases => {
+                let cases = self.matching_cases();
+                if cases.is_empty() && self.search.is_active() && !self.cases.cases().is_empty() {
+                    render_no_matches(f, area, "Cases", &self.search, theme);
+                } else {
+                    render_cases(f, area, &cases, self.case_selected, compliance, theme);
+                }
+            }
+            Panel::FleetSummary => render_fleet_summary(f, area, &self.metrics, &self.fleet, theme),
+            Panel::Instances => render_instances(f, area, &self.fleet, theme),
+            Panel::SampleGroups => {
+                let mut groups = top_groups(&self.sampled_events, usize::MAX);
+                groups.retain(|group| self.search.matches_group(group));
+                groups.truncate(TOP_SAMPLE_GROUPS);
+                render_sample_groups(f, area, &groups, theme);
+            }
+            Panel::SampledEvents => {
+                let events = self.matching_events();
+                if events.is_empty() && self.search.is_active() && !self.sampled_events.is_empty() {
+                    render_no_matches(f, area, "Sampled Events", &self.search, theme);
+                } else {
+                    render_sampled_events(f, area, &events, self.event_selected, theme);
+                }
+            }
+            Panel::EventDetails => {
+                let events = self.matching_events();
+                let selected = events.get(self.event_selected.min(events.len().saturating_sub(1))).copied();
+                render_sampled_event_details(f, area, selected, compliance, theme);
+            }
+            Panel::Vendors => render_vendors(f, area, metrics, &self.policy.vendors, &self.search, self.sort, theme),
+            Panel::VendorChart => render_vendor_chart(f, area, metrics, self.sort, theme),
+            Panel::Alerts => {
+                let alerts = self.matching_alerts();
+                if alerts.is_empty() && self.search.is_active() && !self.alerts.alerts().is_empty() {
+                    render_no_matches(f, area, "Alerts", &self.search, theme);
+                } else {
+                    render_alerts(f, area, &self.alerts, &alerts, self.alert_selected, theme);
+                }
+            }
+        }
     }
 
-    /// Renders the risk tab with stats and risk charts.
-    fn render_risk_tab<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect) {
+    /// Renders the compliance view of one service: its statistics and department mix, violations
+    /// per compliance flag, its models, and risk distribution. The department filter does not
+    /// apply here.
+    fn render_service_view<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect, index: usize) {
+        let empty = ComplianceMetrics::default();
+        let metrics = self.metrics.services.get(index).unwrap_or(&empty);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
                 [
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(34),
+                    Constraint::Length(1),
+                    Constraint::Percentage(35),
+                    Constraint::Percentage(35),
+                    Constraint::Min(0),
                 ]
                 .as_ref(),
             )
             .split(area);
+        let top_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[1]);
+        let middle_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[2]);
+
+        render_service_header(f, chunks[0], SERVICE_NAMES[index], &self.theme);
+        render_stats(f, top_chunks[0], metrics, &self.policy.compliance, &self.theme);
+        render_department_chart(f, top_chunks[1], metrics, SortOrder::default(), &self.theme);
+        render_violation_chart(f, middle_chunks[0], metrics, &self.policy.compliance, self.chart_view.window, &self.theme);
+        render_models(f, middle_chunks[1], &self.metrics, index, &self.theme);
+        render_risk_distribution(f, chunks[3], metrics, &self.theme);
+    }
 
-        render_stats(f, chunks[0], &self.metrics);
-        render_risk_factors(f, chunks[1], &self.metrics);
-        render_risk_distribution(f, chunks[2], &self.metrics);
+    /// Renders the log view, scrolled back by [`Dashboard::log_scroll`] lines.
+    fn render_log_view<B: Backend>(&self, f: &mut tui::Frame<B>, area: Rect, targets: &mut Vec<(Rect, MouseTarget)>) {
+        let visible = area.height.saturating_sub(2) as usize;
+        let lines = logging::recent_lines(visible + self.log_scroll);
+        // Scrolling back past the oldest line keeps the oldest page on screen.
+        let end = lines.len().saturating_sub(self.log_scroll).max(visible.min(lines.len()));
+        render_logs(f, area, &lines[..end], &self.theme);
+        targets.push((area, MouseTarget::Logs));
     }
 }
+
+/// Returns the row a list of `len` rows moves to from `row` with one of the up, down, top, and
+/// bottom actions.
+fn move_row(row: usize, len: usize, action: Action) -> usize {
+    let last = len.saturating_sub(1);
+    match action {
+        Action::Up => row.saturating_sub(1),
+        Action::Down => (row + 1).min(last),
+        Action::Top => 0,
+        _ => last,
+    }
+}
+
+/// Returns true if the cell at `column`, `row` lies within `area`.
+fn contains(area: &Rect, column: u16, row: u16) -> bool {
+    (area.left()..area.right()).contains(&column) && (area.top()..area.bottom()).contains(&row)
+}
@@ -0,0 +1,466 @@
+use crate::constants::TAB_NAMES;
+use crate::error::{MonitorError, Result};
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+/// Something the dashboard does when its keys are pressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Switch to the tab with this index in `TAB_NAMES`.
+    Tab(usize),
+    NextTab,
+    PreviousTab,
+    Quit,
+    /// Close the help, clear the search, close a service view or a maximized pane, or else quit.
+    Back,
+    Help,
+    TimeWindow,
+    DepartmentFilter,
+    Export,
+    Inject,
+    SaveScreen,
+    FocusPane,
+    MaximizePane,
+    GrowPane,
+    ShrinkPane,
+    ResetLayout,
+    ZoomIn,
+    ZoomOut,
+    ChartCursor,
+    Left,
+    Right,
+    Up,
+    Down,
+    Top,
+    Bottom,
+    Select,
+    /// Start typing a search of the lists and tables.
+    Search,
+    NextMatch,
+    PreviousMatch,
+    /// Sort the usage charts and top tables by the next criterion.
+    Sort,
+    ReverseSort,
+    Acknowledge,
+    Waive,
+    Remediate,
+    Reopen,
+}
+
+/// Names of the actions other than switching to a tab, as written in keybindings.
+pub const ACTION_NAMES: [(&str, Action); 34] = [
+    ("next_tab", Action::NextTab),
+    ("previous_tab", Action::PreviousTab),
+    ("quit", Action::Quit),
+    ("back", Action::Back),
+    ("help", Action::Help),
+    ("time_window", Action::TimeWindow),
+    ("department_filter", Action::DepartmentFilter),
+    ("export", Action::Export),
+    ("inject", Action::Inject),
+    ("save_screen", Action::SaveScreen),
+    ("focus_pane", Action::FocusPane),
+    ("maximize_pane", Action::MaximizePane),
+    ("grow_pane", Action::GrowPane),
+    ("shrink_pane", Action::ShrinkPane),
+    ("reset_layout", Action::ResetLayout),
+    ("zoom_in", Action::ZoomIn),
+    ("zoom_out", Action::ZoomOut),
+    ("chart_cursor", Action::ChartCursor),
+    ("left", Action::Left),
+    ("right", Action::Right),
+    ("up", Action::Up),
+    ("down", Action::Down),
+    ("top", Action::Top),
+    ("bottom", Action::Bottom),
+    ("select", Action::Select),
+    ("search", Action::Search),
+    ("next_match", Action::NextMatch),
+    ("previous_match", Action::PreviousMatch),
+    ("sort", Action::Sort),
+    ("reverse_sort", Action::ReverseSort),
+    ("acknowledge", Action::Acknowledge),
+    ("waive", Action::Waive),
+    ("remediate", Action::Remediate),
+    ("reopen", Action::Reopen),
+];
+
+impl Action {
+    /// Returns the action with the given name: one of `ACTION_NAMES`, or a tab name followed by
+    /// `_tab`, such as `overview_tab`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(tab) = name.strip_suffix("_tab") {
+            return TAB_NAMES.iter().position(|tab_name| tab_name.eq_ignore_ascii_case(tab)).map(Action::Tab);
+        }
+        ACTION_NAMES.iter().find(|(action_name, _)| *action_name == name).map(|&(_, action)| action)
+    }
+
+    /// Returns the name of the action, as written in keybindings.
+    pub fn name(self) -> String {
+        match self {
+            Action::Tab(index) => format!("{}_tab", TAB_NAMES[index].to_lowercase()),
+            action => ACTION_NAMES.iter().find(|(_, named)| *named == action).map_or_else(String::new, |(name, _)| name.to_string()),
+        }
+    }
+}
+
+/// Built-in sets of keybindings, selected with `--keybindings`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum KeyPreset {
+    #[default]
+    Default,
+    /// The default keys, plus h/l to switch tabs, j/k to move through tables, and gg/G to jump
+    /// to the top or bottom of a list.
+    Vim,
+}
+
+impl KeyPreset {
+    /// Returns the keys of every action in the preset, in the notation of `--bind`, with the
+    /// key shown in the help first.
+    fn bindings(self) -> Vec<(Action, &'static [&'static str])> {
+        let mut bindings: Vec<(Action, &'static [&'static str])> = vec![
+            (Action::Tab(0), &["1"]),
+            (Action::Tab(1), &["2"]),
+            (Action::Tab(2), &["3"]),
+            (Action::Tab(3), &["4"]),
+            (Action::Tab(4), &["5"]),
+            (Action::Tab(5), &["6"]),
+            (Action::Tab(6), &["7"]),
+            (Action::Tab(7), &["8"]),
+            (Action::Tab(8), &["9"]),
+            (Action::Tab(9), &["0"]),
+            (Action::Tab(10), &["A"]),
+            (Action::NextTab, &["<Tab>"]),
+            (Action::PreviousTab, &["<S-Tab>"]),
+            (Action::Quit, &["q"]),
+            (Action::Back, &["<Esc>"]),
+            (Action::Help, &["?"]),
+            (Action::TimeWindow, &["w"]),
+            (Action::DepartmentFilter, &["d"]),
+            (Action::Export, &["e"]),
+            (Action::Inject, &["i"]),
+            (Action::SaveScreen, &["s"]),
+            (Action::FocusPane, &["f"]),
+            (Action::MaximizePane, &["m"]),
+            (Action::GrowPane, &["+"]),
+            (Action::ShrinkPane, &["-"]),
+            (Action::ResetLayout, &["="]),
+            (Action::ZoomIn, &["z"]),
+            (Action::ZoomOut, &["Z"]),
+            (Action::ChartCursor, &["c"]),
+            (Action::Left, &["<Left>"]),
+            (Action::Right, &["<Right>"]),
+            (Action::Up, &["<Up>"]),
+            (Action::Down, &["<Down>"]),
+            (Action::Top, &["<Home>"]),
+            (Action::Bottom, &["<End>"]),
+            (Action::Select, &["<Enter>"]),
+            (Action::Search, &["/"]),
+            (Action::NextMatch, &["n"]),
+            (Action::PreviousMatch, &["N"]),
+            (Action::Sort, &["O"]),
+            (Action::ReverseSort, &["R"]),
+            (Action::Acknowledge, &["a"]),
+            (Action::Waive, &["v"]),
+            (Action::Remediate, &["r"]),
+            (Action::Reopen, &["o"]),
+        ];
+        if self == KeyPreset::Vim {
+            for (action, keys) in &mut bindings {
+                *keys = match action {
+                    Action::NextTab => &["l", "<Tab>"],
+                    Action::PreviousTab => &["h", "<S-Tab>"],
+                    Action::Up => &["k", "<Up>"],
+                    Action::Down => &["j", "<Down>"],
+                    Action::Top => &["gg", "<Home>"],
+                    Action::Bottom => &["G", "<End>"],
+                    _ => continue,
+                };
+            }
+        }
+        bindings
+    }
+}
+
+/// A key press, ignoring Shift, which is part of the character typed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Key { code: event.code, ctrl: event.modifiers.contains(KeyModifiers::CONTROL) }
+    }
+}
+
+/// Names of the keys written in angle brackets, such as `<Esc>`.
+const KEY_NAMES: [(&str, KeyCode); 14] = [
+    ("Tab", KeyCode::Tab),
+    ("S-Tab", KeyCode::BackTab),
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Space", KeyCode::Char(' ')),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("lt", KeyCode::Char('<')),
+];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => f.write_str("Shift-Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => match KEY_NAMES.iter().find(|(_, named)| *named == code) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// Parses a key sequence in Vim's notation: characters stand for themselves, and named keys go
+/// in angle brackets, such as `gg`, `<Esc>`, `<F5>`, or `<C-c>`.
+pub fn parse_keys(spec: &str) -> std::result::Result<Vec<Key>, String> {
+    let mut keys = Vec::new();
+    let mut rest = spec;
+    while let Some(c) = rest.chars().next() {
+        if c != '<' {
+            keys.push(Key { code: KeyCode::Char(c), ctrl: false });
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let end = rest.find('>').ok_or_else(|| format!("unclosed `<` in `{}`; write `<lt>` for the key itself", spec))?;
+        let name = &rest[1..end];
+        rest = &rest[end + 1..];
+        let key = match name.strip_prefix("C-").or_else(|| name.strip_prefix("c-")) {
+            Some(ctrl) if ctrl.chars().count() == 1 => Key { code: KeyCode::Char(ctrl.chars().next().unwrap_or_default()), ctrl: true },
+            _ => {
+                let code = KEY_NAMES
+                    .iter()
+                    .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+                    .map(|&(_, code)| code)
+                    .or_else(|| name.strip_prefix(['F', 'f']).and_then(|n| n.parse().ok()).filter(|n| (1..=12).contains(n)).map(KeyCode::F))
+                    .ok_or_else(|| format!("unknown key `<{}>` in `{}`", name, spec))?;
+                Key { code, ctrl: false }
+            }
+        };
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+/// Writes a key sequence for display, such as `gg` or `Ctrl-w Left`.
+fn display_keys(keys: &[Key]) -> String {
+    let chars = keys.iter().all(|key| !key.ctrl && matches!(key.code, KeyCode::Char(c) if c != ' '));
+    let names: Vec<String> = keys.iter().map(Key::to_string).collect();
+    names.join(if chars { "" } else { " " })
+}
+
+/// A keybinding set with `--bind` or in the config file's `[keybindings]` table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub action: Action,
+    /// The key sequence, or `None` to leave the action unbound.
+    pub keys: Option<Vec<Key>>,
+}
+
+impl KeyBinding {
+    /// Parses `ACTION=KEYS`, such as `quit=<C-c>` or `top=gg`; an empty sequence unbinds the action.
+    pub fn parse(spec: &str) -> std::result::Result<Self, String> {
+        let (name, keys) = spec.split_once('=').ok_or_else(|| format!("expected ACTION=KEYS, not `{}`", spec))?;
+        let action = Action::from_name(name.trim()).ok_or_else(|| format!("unknown action `{}`", name.trim()))?;
+        let keys = if keys.is_empty() { None } else { Some(parse_keys(keys)?) };
+        Ok(KeyBinding { action, keys })
+    }
+}
+
+/// Rows of the help overlay: the actions whose keys are shown, and what they do.
+const HELP_ROWS: [(&[Action], &str); 21] = [
+    (&[Action::Tab(0), Action::Tab(8), Action::Tab(9)], "Switch to one of the first ten tabs"),
+    (&[Action::Tab(10)], "Switch to the Alerts tab"),
+    (&[Action::NextTab, Action::PreviousTab], "Cycle through tabs, forward or back"),
+    (&[Action::TimeWindow], "Cycle the chart time window: 1m, 5m, 30m, all"),
+    (&[Action::DepartmentFilter], "Cycle the department filter"),
+    (&[Action::Export], "Export the retained metrics as CSV"),
+    (&[Action::Inject], "Inject a burst of non-compliant events"),
+    (&[Action::SaveScreen], "Save the screen as text in the current directory"),
+    (&[Action::FocusPane, Action::MaximizePane], "Focus the next pane, or maximize or restore it"),
+    (&[Action::GrowPane, Action::ShrinkPane, Action::ResetLayout], "Grow or shrink the focused pane, or reset the tab"),
+    (&[Action::Help], "Show or hide this help"),
+    (&[Action::ZoomIn, Action::ZoomOut], "Zoom the rate and compliance history in or out"),
+    (&[Action::ChartCursor], "Show or hide the history charts' value cursor"),
+    (&[Action::Left, Action::Right], "Move the chart cursor or pan, or select a service"),
+    (&[Action::Select], "Open a service's view, or acknowledge an alert"),
+    (&[Action::Up, Action::Down], "Select a row, or scroll the threads and logs"),
+    (&[Action::Top, Action::Bottom], "Jump to the first or last row of a list"),
+    (&[Action::Search, Action::NextMatch, Action::PreviousMatch], "Filter lists by name, then jump between matches"),
+    (&[Action::Sort, Action::ReverseSort], "Sort by count, violation rate, or name, or reverse"),
+    (&[Action::Acknowledge, Action::Waive, Action::Remediate, Action::Reopen], "Acknowledge, waive, remediate, or reopen the case"),
+    (&[Action::Quit, Action::Back], "Quit, or back out of a service view or full pane"),
+];
+
+/// Mouse shortcuts, listed in the help after the keys.
+const MOUSE_HELP: [(&str, &str); 3] = [
+    ("Click tab", "Switch to the tab"),
+    ("Click bar", "Show or hide the service or department details"),
+    ("Wheel", "Scroll the thread table and the logs"),
+];
+
+/// The dashboard's keybindings, and the start of a key sequence being typed.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    /// Every key sequence bound, in the order the help shows them.
+    bindings: Vec<(Action, Vec<Key>)>,
+    pending: Vec<Key>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(KeyPreset::Default, &[]).expect("the default keybindings do not conflict")
+    }
+}
+
+impl Keymap {
+    /// Builds the keybindings of a preset with some actions remapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset` - The built-in keybindings to start from.
+    /// * `overrides` - Keybindings replacing the preset's keys of their actions; several for one
+    ///   action bind it to each of their keys.
+    ///
+    /// # Errors
+    ///
+    /// [`MonitorError::Config`] if a key sequence is bound to two actions, or one could never be
+    /// typed because the start of it is bound to something else.
+    pub fn new(preset: KeyPreset, overrides: &[KeyBinding]) -> Result<Self> {
+        let mut bindings = Vec::new();
+        for (action, specs) in preset.bindings() {
+            let remapped: Vec<&KeyBinding> = overrides.iter().filter(|binding| binding.action == action).collect();
+            if remapped.is_empty() {
+                for spec in specs {
+                    let keys = parse_keys(spec).map_err(|e| MonitorError::Config(format!("keybindings: {}", e)))?;
+                    bindings.push((action, keys));
+                }
+            } else {
+                bindings.extend(remapped.into_iter().filter_map(|binding| binding.keys.clone()).map(|keys| (action, keys)));
+            }
+        }
+        for (index, (action, keys)) in bindings.iter().enumerate() {
+            for (other_action, other_keys) in &bindings[index + 1..] {
+                let conflict = if keys == other_keys {
+                    (action != other_action).then(|| format!("`{}` is bound to both {} and {}", display_keys(keys), action.name(), other_action.name()))
+                } else {
+                    let (short, long) = if keys.len() < other_keys.len() { ((keys, action), (other_keys, other_action)) } else { ((other_keys, other_action), (keys, action)) };
+                    long.0.starts_with(short.0).then(|| {
+                        format!(
+                            "`{}` for {} could never be typed, since `{}` is bound to {}",
+                            display_keys(long.0),
+                            long.1.name(),
+                            display_keys(short.0),
+                            short.1.name()
+                        )
+                    })
+                };
+                if let Some(conflict) = conflict {
+                    return Err(MonitorError::Config(format!("keybindings: {}", conflict)));
+                }
+            }
+        }
+        Ok(Keymap { bindings, pending: Vec::new() })
+    }
+
+    /// Adds a key press to the sequence being typed.
+    ///
+    /// # Returns
+    ///
+    /// The action the sequence is bound to once it is complete, or `None` while it is the start
+    /// of a longer one or if nothing is bound to it. A key that cannot continue the sequence
+    /// starts a new one.
+    pub fn press(&mut self, key: KeyEvent) -> Option<Action> {
+        self.pending.push(Key::from(key));
+        loop {
+            if let Some((action, _)) = self.bindings.iter().find(|(_, keys)| *keys == self.pending) {
+                self.pending.clear();
+                return Some(*action);
+            }
+            if self.bindings.iter().any(|(_, keys)| keys.starts_with(&self.pending)) {
+                return None;
+            }
+            if self.pending.len() == 1 {
+                self.pending.clear();
+                return None;
+            }
+            self.pending.drain(..self.pending.len() - 1);
+        }
+    }
+
+    /// Returns the shortcuts listed in the help: the first key sequence of each action, and the
+    /// mouse shortcuts.
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        let first = |action: Action| self.bindings.iter().find(|(bound, _)| *bound == action).map(|(_, keys)| display_keys(keys));
+        let mut rows = Vec::new();
+        for (actions, description) in HELP_ROWS {
+            let keys: Vec<String> = actions.iter().filter_map(|&action| first(action)).collect();
+            let label = match actions {
+                [Action::Tab(_), _, _] if keys.len() == 3 => format!("{}-{}, {}", keys[0], keys[1], keys[2]),
+                _ => keys.join("/"),
+            };
+            if !label.is_empty() {
+                rows.push((label, description));
+            }
+        }
+        rows.extend(MOUSE_HELP.iter().map(|&(mouse, description)| (mouse.to_string(), description)));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keymap: &mut Keymap, keys: &str) -> Vec<Option<Action>> {
+        parse_keys(keys).unwrap().into_iter().map(|key| keymap.press(KeyEvent::new(key.code, if key.ctrl { KeyModifiers::CONTROL } else { KeyModifiers::NONE }))).collect()
+    }
+
+    #[test]
+    fn presets_and_remapped_keys_resolve_sequences() {
+        let mut keymap = Keymap::new(KeyPreset::Vim, &[]).unwrap();
+        assert_eq!(press(&mut keymap, "lgg<Tab>"), [Some(Action::NextTab), None, Some(Action::Top), Some(Action::NextTab)]);
+        // A key that cannot continue a sequence starts a new one.
+        assert_eq!(press(&mut keymap, "gjG"), [None, Some(Action::Down), Some(Action::Bottom)]);
+        assert_eq!(keymap.help()[0], ("1-9, 0".to_string(), "Switch to one of the first ten tabs"));
+        assert!(keymap.help().contains(&("gg/G".to_string(), "Jump to the first or last row of a list")));
+
+        let overrides = [
+            KeyBinding::parse("quit=<C-c>").unwrap(),
+            KeyBinding::parse("quit=Q").unwrap(),
+            KeyBinding::parse("inject=").unwrap(),
+            KeyBinding::parse("alerts_tab=<F10>").unwrap(),
+        ];
+        let mut keymap = Keymap::new(KeyPreset::Default, &overrides).unwrap();
+        assert_eq!(press(&mut keymap, "q<C-c>Qi<F10>h"), [None, Some(Action::Quit), Some(Action::Quit), None, Some(Action::Tab(10)), None]);
+        assert!(keymap.help().contains(&("Ctrl-c/Esc".to_string(), "Quit, or back out of a service view or full pane")));
+
+        assert_eq!(KeyBinding::parse("jump=x").unwrap_err(), "unknown action `jump`");
+        assert_eq!(KeyBinding::parse("top=<Hmoe>").unwrap_err(), "unknown key `<Hmoe>` in `<Hmoe>`");
+        let error = Keymap::new(KeyPreset::Vim, &[KeyBinding::parse("quit=l").unwrap()]).unwrap_err().to_string();
+        assert!(error.contains("`l` is bound to both next_tab and quit"), "{}", error);
+        let error = Keymap::new(KeyPreset::Vim, &[KeyBinding::parse("help=g").unwrap()]).unwrap_err().to_string();
+        assert!(error.contains("`gg` for top could never be typed, since `g` is bound to help"), "{}", error);
+    }
+}
@@ -0,0 +1,487 @@
+use crate::constants::TAB_NAMES;
+use crate::error::{MonitorError, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tui::layout::Rect;
+
+/// Narrowest a panel is drawn beside others; narrower rows stack their panels instead.
+pub const MIN_PANEL_WIDTH: u16 = 32;
+
+/// Fewest lines a panel is drawn in, its borders and three lines of content; tabs too short for
+/// every row leave out their last rows.
+pub const MIN_PANEL_HEIGHT: u16 = 5;
+
+/// A widget that can be placed in a custom tab layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Panel {
+    Gauges,
+    Hipaa,
+    Stats,
+    Services,
+    Departments,
+    ShadowAi,
+    Rate,
+    ComplianceHistory,
+    Violations,
+    Nist,
+    Regions,
+    Purposes,
+    LawfulBases,
+    Slos,
+    PolicyComparison,
+    TopUsers,
+    RiskFactors,
+    RiskDistribution,
+    AiActTiers,
+    PiiTypes,
+    SystemHealth,
+    SystemLatencies,
+    Threads,
+    Logs,
+    CaseSummary,
+    Cases,
+    FleetSummary,
+    Instances,
+    SampleGroups,
+    SampledEvents,
+    EventDetails,
+    Vendors,
+    VendorChart,
+    Alerts,
+}
+
+/// Panels by their name in layout files.
+pub const PANEL_NAMES: [(&str, Panel); 34] = [
+    ("gauges", Panel::Gauges),
+    ("hipaa", Panel::Hipaa),
+    ("stats", Panel::Stats),
+    ("services", Panel::Services),
+    ("departments", Panel::Departments),
+    ("shadow_ai", Panel::ShadowAi),
+    ("rate", Panel::Rate),
+    ("compliance_history", Panel::ComplianceHistory),
+    ("violations", Panel::Violations),
+    ("nist", Panel::Nist),
+    ("regions", Panel::Regions),
+    ("purposes", Panel::Purposes),
+    ("lawful_bases", Panel::LawfulBases),
+    ("slos", Panel::Slos),
+    ("policy_comparison", Panel::PolicyComparison),
+    ("top_users", Panel::TopUsers),
+    ("risk_factors", Panel::RiskFactors),
+    ("risk_distribution", Panel::RiskDistribution),
+    ("ai_act_tiers", Panel::AiActTiers),
+    ("pii_types", Panel::PiiTypes),
+    ("system_health", Panel::SystemHealth),
+    ("system_latencies", Panel::SystemLatencies),
+    ("threads", Panel::Threads),
+    ("logs", Panel::Logs),
+    ("case_summary", Panel::CaseSummary),
+    ("cases", Panel::Cases),
+    ("fleet_summary", Panel::FleetSummary),
+    ("instances", Panel::Instances),
+    ("sample_groups", Panel::SampleGroups),
+    ("sampled_events", Panel::SampledEvents),
+    ("event_details", Panel::EventDetails),
+    ("vendors", Panel::Vendors),
+    ("vendor_chart", Panel::VendorChart),
+    ("alerts", Panel::Alerts),
+];
+
+/// Built-in layouts of the tabs, in the order of `TAB_NAMES`, written as in layout files.
+pub const BUILTIN_LAYOUTS: [&[&str]; TAB_NAMES.len()] = [
+    &["20 gauges", "40 stats services", "40 rate:3 compliance_history:2"],
+    &["35 services", "35 departments", "30 shadow_ai"],
+    // One line per region inside the borders, below the header.
+    &["[9] gauges regions", "[4] hipaa", "violations:3 nist:2", "purposes lawful_bases slos policy_comparison"],
+    &["33 stats top_users", "33 risk_factors", "34 risk_distribution:4 ai_act_tiers:3 pii_types:3"],
+    &["[9] system_health system_latencies", "threads", "rate"],
+    &["logs"],
+    &["[3] case_summary", "cases"],
+    &["[4] fleet_summary", "instances"],
+    &["[8] sample_groups", "sampled_events", "[8] event_details"],
+    &["[8] vendors", "vendor_chart"],
+    &["alerts"],
+];
+
+/// Height of a row of panels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowHeight {
+    /// A share of what the fixed rows leave, relative to the other weighted rows.
+    Weight(u32),
+    /// A fixed number of lines.
+    Lines(u16),
+}
+
+/// One row of a tab layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutRow {
+    pub height: RowHeight,
+    /// Panels from left to right, each with its width relative to the others in the row.
+    pub panels: Vec<(Panel, u32)>,
+}
+
+impl LayoutRow {
+    /// Parses a row written as an optional height followed by the row's panels, each optionally
+    /// with a relative width, e.g. `2 stats rate:3`. A height is relative to the other rows, or
+    /// a fixed number of lines if written in brackets, e.g. `[8] vendors`. Heights and widths
+    /// default to 1.
+    pub fn parse(row: &str) -> std::result::Result<Self, String> {
+        let mut words = row.split_whitespace().peekable();
+        let height = match words.peek().copied() {
+            Some(word) if word.starts_with(|c: char| c.is_ascii_digit() || c == '[') => {
+                words.next();
+                let height = match word.strip_prefix('[').and_then(|word| word.strip_suffix(']')) {
+                    Some(lines) => lines.parse().ok().map(RowHeight::Lines),
+                    None => word.parse().ok().map(RowHeight::Weight),
+                };
+                match height {
+                    Some(height @ (RowHeight::Weight(1..) | RowHeight::Lines(1..))) => height,
+                    _ => return Err(format!("invalid height in row '{}'; heights are whole numbers from 1", row)),
+                }
+            }
+            _ => RowHeight::Weight(1),
+        };
+        let panels = words
+            .map(|word| {
+                let (name, width) = match word.split_once(':') {
+                    Some((name, width)) => {
+                        let width = width.parse::<u32>().ok().filter(|width| *width > 0);
+                        (name, width.ok_or_else(|| format!("invalid width in '{}'; widths are whole numbers from 1", word))?)
+                    }
+                    None => (word, 1),
+                };
+                let panel = PANEL_NAMES
+                    .iter()
+                    .find(|(panel_name, _)| *panel_name == name)
+                    .map(|(_, panel)| *panel)
+                    .ok_or_else(|| format!("unknown panel '{}'", name))?;
+                Ok((panel, width))
+            })
+            .collect::<std::result::Result<Vec<_>, String>>()?;
+        if panels.is_empty() {
+            return Err(format!("row '{}' has no panels", row));
+        }
+        Ok(LayoutRow { height, panels })
+    }
+}
+
+/// A panel placed on the screen, with its position in the tab layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PanelArea {
+    pub panel: Panel,
+    /// Index of the panel's row in the layout.
+    pub row: usize,
+    /// Index of the panel in its row.
+    pub column: usize,
+    pub area: Rect,
+}
+
+/// The rows of panels of a tab.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TabLayout {
+    pub rows: Vec<LayoutRow>,
+}
+
+impl TabLayout {
+    /// Parses the rows of a tab, top to bottom.
+    fn parse(rows: &[impl AsRef<str>]) -> std::result::Result<Self, String> {
+        let rows = rows.iter().map(|row| LayoutRow::parse(row.as_ref())).collect::<std::result::Result<_, _>>()?;
+        Ok(TabLayout { rows })
+    }
+
+    /// Divides an area between the tab's panels.
+    ///
+    /// Rows too narrow to give each of their panels [`MIN_PANEL_WIDTH`] columns stack them, each
+    /// as tall as the row. Rows that would then get fewer than [`MIN_PANEL_HEIGHT`] lines, or
+    /// fewer than their fixed height, are made room for by leaving out the last rows, which are
+    /// taken as the least important.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - The area of the tab.
+    /// * `available` - Whether a panel has anything to show; panels that do not, such as the
+    ///   SLOs without an SLO policy, are left out and the rest of their row takes their space.
+    ///
+    /// # Returns
+    ///
+    /// Each shown panel with its area, row by row from left to right.
+    pub fn areas(&self, area: Rect, available: impl Fn(Panel) -> bool) -> Vec<PanelArea> {
+        // The rows with a panel to show, and the columns of those panels.
+        let rows: Vec<(usize, Vec<usize>)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(row, layout_row)| (row, (0..layout_row.panels.len()).filter(|column| available(layout_row.panels[*column].0)).collect::<Vec<_>>()))
+            .filter(|(_, columns)| !columns.is_empty())
+            .collect();
+        let mut rows: Vec<(usize, Vec<usize>)> = rows
+            .into_iter()
+            .flat_map(|(row, columns)| {
+                let widths: Vec<RowHeight> = columns.iter().map(|column| RowHeight::Weight(self.rows[row].panels[*column].1)).collect();
+                if split(area.width, &widths).iter().all(|width| *width >= MIN_PANEL_WIDTH) {
+                    vec![(row, columns)]
+                } else {
+                    columns.into_iter().map(|column| (row, vec![column])).collect()
+                }
+            })
+            .collect();
+        while rows.len() > 1 {
+            let heights: Vec<RowHeight> = rows.iter().map(|(row, _)| self.rows[*row].height).collect();
+            let readable = heights.iter().zip(split(area.height, &heights)).all(|(height, lines)| match height {
+                RowHeight::Lines(fixed) => lines >= *fixed,
+                RowHeight::Weight(_) => lines >= MIN_PANEL_HEIGHT,
+            });
+            if readable {
+                break;
+            }
+            rows.pop();
+        }
+        let heights: Vec<RowHeight> = rows.iter().map(|(row, _)| self.rows[*row].height).collect();
+        let mut areas = Vec::new();
+        let mut y = area.y;
+        for ((row, columns), height) in rows.iter().zip(split(area.height, &heights)) {
+            let panels = &self.rows[*row].panels;
+            let widths: Vec<RowHeight> = columns.iter().map(|column| RowHeight::Weight(panels[*column].1)).collect();
+            let mut x = area.x;
+            for (column, width) in columns.iter().zip(split(area.width, &widths)) {
+                areas.push(PanelArea { panel: panels[*column].0, row: *row, column: *column, area: Rect::new(x, y, width, height) });
+                x += width;
+            }
+            y += height;
+        }
+        areas
+    }
+
+    /// Grows or shrinks a panel: its row by a line if the row has a fixed height, or by a tenth
+    /// of the weighted rows otherwise, and its width by a tenth of its row.
+    pub fn resize(&mut self, row: usize, column: usize, grow: bool) {
+        let Some(layout_row) = self.rows.get(row) else {
+            return;
+        };
+        if column >= layout_row.panels.len() {
+            return;
+        }
+        match layout_row.height {
+            RowHeight::Lines(lines) => {
+                let lines = if grow { lines.saturating_add(1) } else { lines.saturating_sub(1).max(1) };
+                self.rows[row].height = RowHeight::Lines(lines);
+            }
+            RowHeight::Weight(_) => {
+                let weighted: Vec<usize> = (0..self.rows.len()).filter(|index| matches!(self.rows[*index].height, RowHeight::Weight(_))).collect();
+                let mut weights: Vec<u32> = weighted
+                    .iter()
+                    .map(|index| match self.rows[*index].height {
+                        RowHeight::Weight(weight) => weight,
+                        RowHeight::Lines(_) => unreachable!("only weighted rows are collected"),
+                    })
+                    .collect();
+                let position = weighted.iter().position(|index| *index == row).expect("the row is weighted");
+                resize_weight(&mut weights, position, grow);
+                for (index, weight) in weighted.into_iter().zip(weights) {
+                    self.rows[index].height = RowHeight::Weight(weight);
+                }
+            }
+        }
+        let mut widths: Vec<u32> = self.rows[row].panels.iter().map(|(_, width)| *width).collect();
+        resize_weight(&mut widths, column, grow);
+        for ((_, width), resized) in self.rows[row].panels.iter_mut().zip(widths) {
+            *width = resized;
+        }
+    }
+}
+
+/// Grows or shrinks one of a set of weights by a tenth of their total, keeping it at least 1.
+fn resize_weight(weights: &mut [u32], index: usize, grow: bool) {
+    // A lone weight takes all the space whatever its value.
+    if weights.len() < 2 {
+        return;
+    }
+    // Small weights such as 1 and 1 are scaled up first, so a step is a tenth rather than double.
+    if weights.iter().sum::<u32>() < 20 {
+        weights.iter_mut().for_each(|weight| *weight *= 10);
+    }
+    let step = (weights.iter().sum::<u32>() / 10).max(1);
+    let weight = &mut weights[index];
+    *weight = if grow { weight.saturating_add(step) } else { weight.saturating_sub(step).max(1) };
+}
+
+/// Divides a length between rows or columns: fixed sizes first, as far as the length allows,
+/// then what is left in proportion to the weights, the last weighted size taking what rounding
+/// leaves over.
+fn split(length: u16, sizes: &[RowHeight]) -> Vec<u16> {
+    let mut left = length;
+    let mut lengths: Vec<u16> = sizes
+        .iter()
+        .map(|size| match size {
+            RowHeight::Lines(lines) => {
+                let lines = (*lines).min(left);
+                left -= lines;
+                lines
+            }
+            RowHeight::Weight(_) => 0,
+        })
+        .collect();
+    let total: u64 = sizes.iter().map(|size| if let RowHeight::Weight(weight) = size { *weight as u64 } else { 0 }).sum();
+    let weighted: Vec<usize> = (0..sizes.len()).filter(|index| matches!(sizes[*index], RowHeight::Weight(_))).collect();
+    let mut shared = 0;
+    for (position, index) in weighted.iter().enumerate() {
+        let RowHeight::Weight(weight) = sizes[*index] else {
+            continue;
+        };
+        lengths[*index] = if position + 1 == weighted.len() {
+            left - shared
+        } else {
+            (left as u64 * weight as u64 / total) as u16
+        };
+        shared += lengths[*index];
+    }
+    lengths
+}
+
+/// Layouts of the dashboard tabs: the built-in ones, or those replaced with `--layout`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DashboardLayout {
+    /// Layouts by tab index.
+    tabs: [TabLayout; TAB_NAMES.len()],
+}
+
+impl Default for DashboardLayout {
+    fn default() -> Self {
+        DashboardLayout {
+            tabs: BUILTIN_LAYOUTS.map(|rows| TabLayout::parse(rows).expect("built-in layouts are valid")),
+        }
+    }
+}
+
+impl DashboardLayout {
+    /// Loads a layout file, replacing the built-in layouts of the tabs it names.
+    ///
+    /// A layout file is a JSON object mapping tab names to their rows, top to bottom, e.g.
+    /// `{"overview": ["gauges", "2 stats rate:2"], "system": ["threads", "rate"]}`. Each row is
+    /// written as for [`LayoutRow::parse`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |message: String| MonitorError::Config(format!("layout {}: {}", path.display(), message));
+        let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        Self::from_json(&contents).map_err(invalid)
+    }
+
+    /// Parses the contents of a layout file.
+    fn from_json(contents: &str) -> std::result::Result<Self, String> {
+        let file: BTreeMap<String, Vec<String>> = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        let mut layout = DashboardLayout::default();
+        for (tab, rows) in file {
+            let index = TAB_NAMES
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(&tab))
+                .ok_or_else(|| format!("unknown tab '{}' (tabs are {})", tab, TAB_NAMES.join(", ")))?;
+            if rows.is_empty() {
+                return Err(format!("tab '{}' has no rows", tab));
+            }
+            layout.tabs[index] = TabLayout::parse(&rows)?;
+        }
+        Ok(layout)
+    }
+
+    /// Returns the layout of the tab at the given index.
+    pub fn tab(&self, index: usize) -> &TabLayout {
+        &self.tabs[index]
+    }
+
+    /// Returns the layout of the tab at the given index, to resize its panels.
+    pub fn tab_mut(&mut self, index: usize) -> &mut TabLayout {
+        &mut self.tabs[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::REGION_NAMES;
+
+    #[test]
+    fn layout_files_divide_tabs_into_weighted_rows_of_panels() {
+        let layout = DashboardLayout::from_json(r#"{"Overview": ["gauges", "3 stats:3 slos rate"], "system": ["logs"]}"#).unwrap();
+        let overview = layout.tab(0);
+        assert_eq!(overview.rows[1], LayoutRow { height: RowHeight::Weight(3), panels: vec![(Panel::Stats, 3), (Panel::Slos, 1), (Panel::Rate, 1)] });
+        assert_eq!(layout.tab(4).rows[0].panels, [(Panel::Logs, 1)]);
+        assert_eq!(layout.tab(1), DashboardLayout::default().tab(1));
+
+        let areas = overview.areas(Rect::new(0, 0, 160, 40), |panel| panel != Panel::Slos);
+        let placed: Vec<(Panel, Rect)> = areas.iter().map(|pane| (pane.panel, pane.area)).collect();
+        assert_eq!(
+            placed,
+            [
+                (Panel::Gauges, Rect::new(0, 0, 160, 10)),
+                (Panel::Stats, Rect::new(0, 10, 120, 30)),
+                (Panel::Rate, Rect::new(120, 10, 40, 30)),
+            ]
+        );
+        assert_eq!((areas[2].row, areas[2].column), (1, 2));
+        // A row left without panels gives its space to the others.
+        let gauges = overview.areas(Rect::new(0, 0, 80, 40), |panel| panel == Panel::Gauges);
+        assert_eq!(gauges.iter().map(|pane| pane.area).collect::<Vec<_>>(), [Rect::new(0, 0, 80, 40)]);
+
+        assert!(DashboardLayout::from_json(r#"{"Home": ["gauges"]}"#).unwrap_err().contains("unknown tab"));
+        assert!(DashboardLayout::from_json(r#"{"risk": []}"#).unwrap_err().contains("no rows"));
+        assert!(DashboardLayout::from_json(r#"{"risk": ["2"]}"#).unwrap_err().contains("no panels"));
+        assert!(DashboardLayout::from_json(r#"{"risk": ["0 stats"]}"#).unwrap_err().contains("height"));
+        assert!(DashboardLayout::from_json(r#"{"risk": ["[x] stats"]}"#).unwrap_err().contains("height"));
+        assert!(DashboardLayout::from_json(r#"{"risk": ["stats:0"]}"#).unwrap_err().contains("invalid width"));
+        assert!(DashboardLayout::from_json(r#"{"risk": ["radar"]}"#).unwrap_err().contains("unknown panel 'radar'"));
+    }
+
+    #[test]
+    fn narrow_tabs_stack_panels_and_short_tabs_leave_out_their_last_rows() {
+        let layout = TabLayout::parse(&["[3] case_summary", "stats:2 rate", "alerts"]).unwrap();
+        let placed = |width, height| {
+            let areas = layout.areas(Rect::new(0, 0, width, height), |_| true);
+            areas.iter().map(|pane| (pane.panel, pane.area)).collect::<Vec<_>>()
+        };
+        // 96 columns give the rate chart the narrowest width kept beside the statistics, 93 one less.
+        assert_eq!(placed(96, 23)[1..3], [(Panel::Stats, Rect::new(0, 3, 64, 10)), (Panel::Rate, Rect::new(64, 3, 32, 10))]);
+        assert_eq!(
+            placed(93, 23),
+            [
+                (Panel::CaseSummary, Rect::new(0, 0, 93, 3)),
+                (Panel::Stats, Rect::new(0, 3, 93, 6)),
+                (Panel::Rate, Rect::new(0, 9, 93, 6)),
+                (Panel::Alerts, Rect::new(0, 15, 93, 8)),
+            ]
+        );
+        // Too short for four rows of at least five lines, the alerts are left out, then the rate.
+        assert_eq!(placed(93, 17).iter().map(|(panel, _)| *panel).collect::<Vec<_>>(), [Panel::CaseSummary, Panel::Stats, Panel::Rate]);
+        assert_eq!(placed(93, 12), [(Panel::CaseSummary, Rect::new(0, 0, 93, 3)), (Panel::Stats, Rect::new(0, 3, 93, 9))]);
+        // The first row is always shown.
+        assert_eq!(placed(93, 2), [(Panel::CaseSummary, Rect::new(0, 0, 93, 2))]);
+    }
+
+    #[test]
+    fn panels_grow_and_shrink_around_fixed_rows() {
+        let mut layout = TabLayout::parse(&["[4] case_summary", "cases rate", "alerts"]).unwrap();
+        let heights = |layout: &TabLayout| layout.areas(Rect::new(0, 0, 100, 44), |_| true).iter().map(|pane| pane.area.height).collect::<Vec<_>>();
+        assert_eq!(heights(&layout), [4, 20, 20, 20]);
+
+        // Weights of 1 and 1 are scaled to 10 and 10 before growing by a tenth of their total.
+        layout.resize(1, 1, true);
+        assert_eq!(layout.rows[1], LayoutRow { height: RowHeight::Weight(12), panels: vec![(Panel::Cases, 10), (Panel::Rate, 12)] });
+        assert_eq!(heights(&layout), [4, 21, 21, 19]);
+        layout.resize(0, 0, false);
+        assert_eq!(layout.rows[0].height, RowHeight::Lines(3));
+        for _ in 0..20 {
+            layout.resize(2, 0, false);
+        }
+        assert_eq!(layout.rows[2].height, RowHeight::Weight(1));
+        // Out of range panels are ignored.
+        layout.resize(5, 0, true);
+        layout.resize(0, 3, true);
+
+        // The fixed rows take their lines first, as far as the area allows.
+        assert_eq!(split(5, &[RowHeight::Lines(4), RowHeight::Weight(1), RowHeight::Lines(4)]), [4, 0, 1]);
+        assert_eq!(DashboardLayout::default().tab(2).rows[0].height, RowHeight::Lines(REGION_NAMES.len() as u16 + 3));
+        for (index, rows) in BUILTIN_LAYOUTS.iter().enumerate() {
+            assert!(TabLayout::parse(rows).is_ok(), "{}", TAB_NAMES[index]);
+        }
+    }
+}
@@ -1,3 +1,10 @@
+pub mod ascii;
 pub mod dashboard;
+pub mod keys;
+pub mod layout;
+pub mod search;
+pub mod snapshot;
+pub mod sort;
+pub mod theme;
 pub mod tui;
 pub mod widgets;
@@ -0,0 +1,125 @@
+use crate::alerts::Alert;
+use crate::cases::Case;
+use crate::event_sample::{SampleGroup, SampledEvent};
+
+/// The search typed after `/`, filtering the rows of the dashboard's lists and tables to those
+/// naming a matching service, vendor, department, or user.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Search {
+    query: String,
+    /// Whether keys are typed into the query rather than running actions.
+    pub editing: bool,
+}
+
+impl Search {
+    /// Returns the query as typed.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns true while the query is being typed or filters the rows.
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+
+    /// Starts typing a new query.
+    pub fn start(&mut self) {
+        self.query.clear();
+        self.editing = true;
+    }
+
+    /// Adds a character to the query being typed.
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+    }
+
+    /// Removes the last character of the query being typed.
+    pub fn pop(&mut self) {
+        self.query.pop();
+    }
+
+    /// Stops the search, showing every row again.
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.editing = false;
+    }
+
+    /// Returns true if any of the fields contains the query, ignoring case; every row matches
+    /// an empty query.
+    pub fn matches<'a>(&self, fields: impl IntoIterator<Item = &'a str>) -> bool {
+        if self.query.is_empty() {
+            return true;
+        }
+        let query = self.query.to_lowercase();
+        fields.into_iter().any(|field| field.to_lowercase().contains(&query))
+    }
+
+    /// Returns true if the sampled event's service, vendor, department, or user matches.
+    pub fn matches_event(&self, event: &SampledEvent) -> bool {
+        let user = event.user_id.map(|user| user.to_string());
+        self.matches([&*event.service, &*event.vendor, &*event.department].into_iter().chain(user.as_deref()))
+    }
+
+    /// Returns true if the case's department or check matches.
+    pub fn matches_case(&self, case: &Case) -> bool {
+        self.matches([case.department.as_str(), case.check.as_str()])
+    }
+
+    /// Returns true if the alert's subject, such as a department or waiver, or its rule matches.
+    pub fn matches_alert(&self, alert: &Alert) -> bool {
+        self.matches([alert.subject.as_str(), alert.rule.as_str()])
+    }
+
+    /// Returns true if the group's service or department matches.
+    pub fn matches_group(&self, group: &SampleGroup) -> bool {
+        self.matches([group.service.as_str(), group.department.as_str()])
+    }
+}
+
+/// Returns the row `n` or `N` jumps to from `selected` among `len` matching rows: the next or
+/// the previous one, wrapping around at either end.
+pub fn jump_to_match(selected: usize, len: usize, forward: bool) -> usize {
+    match len {
+        0 => 0,
+        _ if forward => (selected + 1) % len,
+        _ => (selected.min(len - 1) + len - 1) % len,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cases::CaseState;
+
+    #[test]
+    fn rows_match_their_names_ignoring_case_and_matches_wrap_around() {
+        let mut search = Search::default();
+        let case = Case {
+            check: "gdpr".to_string(),
+            department: "Healthcare".to_string(),
+            state: CaseState::Open,
+            violations: 3,
+            opened_at_ms: 0,
+            updated_at_ms: 0,
+        };
+        assert!(search.matches_case(&case));
+        assert!(!search.is_active());
+        search.start();
+        for c in "HEALTHX".chars() {
+            search.push(c);
+        }
+        assert!(!search.matches_case(&case));
+        search.pop();
+        assert_eq!(search.query(), "HEALTH");
+        assert!(search.matches_case(&case));
+        assert!(!search.matches(["Engineering", "4217"]));
+        search.clear();
+        assert!(!search.is_active());
+
+        assert_eq!(jump_to_match(2, 3, true), 0);
+        assert_eq!(jump_to_match(0, 3, false), 2);
+        // A selection left past the end by a narrower search jumps from the last match.
+        assert_eq!(jump_to_match(7, 3, false), 1);
+        assert_eq!(jump_to_match(0, 0, true), 0);
+    }
+}
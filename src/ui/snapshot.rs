@@ -0,0 +1,139 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tui::buffer::Buffer;
+use tui::style::{Color, Modifier};
+
+/// Renders a frame as plain text, one line per row with trailing spaces trimmed.
+pub fn frame_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let row: String = (area.left()..area.right()).map(|x| buffer.get(x, y).symbol.as_str()).collect();
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Renders a frame with ANSI escape codes for its colors and text styles, for viewing with
+/// `cat` or `less -R` in a terminal.
+pub fn frame_ansi(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        // Every row starts from the default style, so rows can be viewed on their own.
+        let mut style = None;
+        for x in area.left()..area.right() {
+            let cell = buffer.get(x, y);
+            let cell_style = (cell.fg, cell.bg, cell.modifier);
+            if style != Some(cell_style) {
+                text.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+                style = Some(cell_style);
+            }
+            text.push_str(&cell.symbol);
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+/// Returns the escape sequence that resets the style and then selects the given one.
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let mut codes = String::from("0");
+    for (flag, code) in [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::SLOW_BLINK, 5),
+        (Modifier::REVERSED, 7),
+        (Modifier::CROSSED_OUT, 9),
+    ] {
+        if modifier.contains(flag) {
+            let _ = write!(codes, ";{}", code);
+        }
+    }
+    for code in [color_code(fg, false), color_code(bg, true)].into_iter().flatten() {
+        let _ = write!(codes, ";{}", code);
+    }
+    format!("\x1b[{}m", codes)
+}
+
+/// Returns the SGR parameters selecting a foreground or background color, or `None` for the
+/// terminal's default.
+fn color_code(color: Color, background: bool) -> Option<String> {
+    // Background codes are the foreground ones shifted by ten.
+    let offset = if background { 10 } else { 0 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", 38 + offset, r, g, b)),
+        Color::Indexed(index) => return Some(format!("{};5;{}", 38 + offset, index)),
+    };
+    Some((code + offset).to_string())
+}
+
+/// Writes a frame as `dashboard-<timestamp>.txt` in plain text and `dashboard-<timestamp>.ansi`
+/// with its colors.
+///
+/// # Arguments
+///
+/// * `buffer` - The rendered frame.
+/// * `dir` - Directory the files are written to.
+/// * `unix_ms` - Time of the frame, in milliseconds since the Unix epoch, naming the files.
+///
+/// # Returns
+///
+/// The path of the plain text file.
+pub fn write_frame(buffer: &Buffer, dir: &Path, unix_ms: u64) -> io::Result<PathBuf> {
+    let path = dir.join(format!("dashboard-{}.txt", unix_ms));
+    fs::write(&path, frame_text(buffer))?;
+    fs::write(path.with_extension("ansi"), frame_ansi(buffer))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::layout::Rect;
+    use tui::style::Style;
+
+    #[test]
+    fn frames_are_written_as_text_and_ansi() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 2));
+        buffer.set_string(0, 0, "Compliance", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
+        buffer.set_string(0, 1, "99%", Style::default().bg(Color::Rgb(1, 2, 3)));
+        assert_eq!(frame_text(&buffer), "Compliance\n99%\n");
+        assert_eq!(
+            frame_ansi(&buffer),
+            "\x1b[0;1;32mCompliance\x1b[0m  \x1b[0m\n\x1b[0;48;2;1;2;3m99%\x1b[0m         \x1b[0m\n"
+        );
+
+        let dir = std::env::temp_dir().join(format!("snapshot-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = write_frame(&buffer, &dir, 1_700_000_000_000).unwrap();
+        let ansi = fs::read_to_string(path.with_extension("ansi"));
+        let text = fs::read_to_string(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(path.file_name().unwrap(), "dashboard-1700000000000.txt");
+        assert_eq!(text.unwrap(), "Compliance\n99%\n");
+        assert!(ansi.unwrap().contains("\x1b[0;1;32m"));
+    }
+}
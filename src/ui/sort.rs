@@ -0,0 +1,107 @@
+use std::cmp::Ordering;
+
+/// What the usage charts and top tables are sorted by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Each row's count, highest first: events, or total risk score for the top users.
+    #[default]
+    Count,
+    /// Violations per event, highest first.
+    ViolationRate,
+    /// Names in alphabetical order, or user ids in ascending order.
+    Name,
+}
+
+/// How the usage charts and top tables order their rows, cycled with `O` and reversed with `R`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub reversed: bool,
+}
+
+impl SortOrder {
+    /// Returns the order by the next criterion, unreversed.
+    pub fn next_key(self) -> Self {
+        let key = match self.key {
+            SortKey::Count => SortKey::ViolationRate,
+            SortKey::ViolationRate => SortKey::Name,
+            SortKey::Name => SortKey::Count,
+        };
+        SortOrder { key, reversed: false }
+    }
+
+    /// Returns the order with its direction reversed.
+    pub fn reverse(self) -> Self {
+        SortOrder { reversed: !self.reversed, ..self }
+    }
+
+    /// Returns the order for chart and table titles, or `None` for the default order.
+    pub fn label(self) -> Option<String> {
+        if self == SortOrder::default() {
+            return None;
+        }
+        let key = match self.key {
+            SortKey::Count => "by count",
+            SortKey::ViolationRate => "by violation rate",
+            SortKey::Name => "by name",
+        };
+        Some(if self.reversed { format!("{}, reversed", key) } else { key.to_string() })
+    }
+
+    /// Sorts rows in this order; rows equal by the criterion are ordered by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows to sort.
+    /// * `fields` - Returns a row's name, count, and violation rate.
+    pub fn sort<T, N: Ord>(self, rows: &mut Vec<T>, fields: impl Fn(&T) -> (N, f64, f64)) {
+        let keys: Vec<(N, f64, f64)> = rows.iter().map(fields).collect();
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by(|&a, &b| self.compare(&keys[a], &keys[b]));
+        let mut unsorted: Vec<Option<T>> = rows.drain(..).map(Some).collect();
+        rows.extend(order.into_iter().filter_map(|index| unsorted[index].take()));
+    }
+
+    /// Compares two rows by their name, count, and violation rate.
+    fn compare<N: Ord>(self, a: &(N, f64, f64), b: &(N, f64, f64)) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Count => b.1.total_cmp(&a.1),
+            SortKey::ViolationRate => b.2.total_cmp(&a.2).then(b.1.total_cmp(&a.1)),
+            SortKey::Name => Ordering::Equal,
+        };
+        let ordering = ordering.then_with(|| a.0.cmp(&b.0));
+        if self.reversed { ordering.reverse() } else { ordering }
+    }
+}
+
+/// Returns the violations per event, or 0 without events.
+pub fn violation_rate(violations: u64, events: u64) -> f64 {
+    if events == 0 { 0.0 } else { violations as f64 / events as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_sort_by_count_violation_rate_or_name_either_way() {
+        // Name, events, violating events.
+        let rows = vec![("Legal", 50, 25), ("finance", 200, 20), ("HR", 200, 10), ("Engineering", 10, 0)];
+        let sorted = |order: SortOrder| {
+            let mut rows = rows.clone();
+            order.sort(&mut rows, |&(name, events, violating)| (name.to_lowercase(), events as f64, violation_rate(violating, events)));
+            rows.iter().map(|(name, _, _)| *name).collect::<Vec<_>>()
+        };
+        let order = SortOrder::default();
+        assert_eq!(order.label(), None);
+        assert_eq!(sorted(order), ["finance", "HR", "Legal", "Engineering"]);
+        let order = order.next_key();
+        assert_eq!(sorted(order), ["Legal", "finance", "HR", "Engineering"]);
+        let order = order.next_key();
+        assert_eq!(sorted(order), ["Engineering", "finance", "HR", "Legal"]);
+        let order = order.reverse();
+        assert_eq!(order.label().as_deref(), Some("by name, reversed"));
+        assert_eq!(sorted(order), ["Legal", "HR", "finance", "Engineering"]);
+        assert_eq!(order.next_key(), SortOrder::default());
+    }
+}
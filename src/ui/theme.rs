@@ -0,0 +1,301 @@
+use crate::error::{MonitorError, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tui::style::Color;
+use tui::symbols::{bar, Marker};
+
+/// Names of the built-in themes accepted by `--theme`.
+pub const THEME_NAMES: [&str; 3] = ["default", "high-contrast", "color-blind"];
+
+/// Colors used by the dashboard widgets, by the role they play.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// Block titles, the selected tab, and notices.
+    pub title: Color,
+    /// Values, labels, and axes.
+    pub text: Color,
+    /// Placeholders shown while there is no data.
+    pub muted: Color,
+    /// Table headers and shortcut keys.
+    pub heading: Color,
+    /// Healthy compliance and finished replays.
+    pub good: Color,
+    /// Warnings, full channels, and restarted workers.
+    pub warning: Color,
+    /// Errors, poor compliance, and workers that are down.
+    pub bad: Color,
+    /// Status lines with nothing to report.
+    pub inactive: Color,
+    /// A replay in progress.
+    pub accent: Color,
+    pub rate_line: Color,
+    pub service_bars: Color,
+    pub department_bars: Color,
+    pub risk_factor_bars: Color,
+    pub violation_bars: Color,
+    pub risk_high: Color,
+    pub risk_medium: Color,
+    pub risk_low: Color,
+    pub gauge_background: Color,
+    /// Fill the risk levels with distinct patterns and mark the compliance gauge with a symbol,
+    /// so neither relies on color alone.
+    pub patterns: bool,
+    /// Draw with ASCII characters only, for terminals that mangle Braille and box drawing; set
+    /// by `--ascii` rather than by theme files.
+    pub ascii: bool,
+}
+
+/// A theme as written in a theme file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// Built-in theme the file starts from; `default` if omitted.
+    base: Option<String>,
+    patterns: Option<bool>,
+    /// Colors to override, keyed by role.
+    #[serde(default)]
+    colors: BTreeMap<String, String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            title: Color::Cyan,
+            text: Color::White,
+            muted: Color::Gray,
+            heading: Color::Yellow,
+            good: Color::Green,
+            warning: Color::Yellow,
+            bad: Color::Red,
+            inactive: Color::DarkGray,
+            accent: Color::Magenta,
+            rate_line: Color::Cyan,
+            service_bars: Color::Yellow,
+            department_bars: Color::Green,
+            risk_factor_bars: Color::Yellow,
+            violation_bars: Color::Red,
+            risk_high: Color::Red,
+            risk_medium: Color::Yellow,
+            risk_low: Color::Green,
+            gauge_background: Color::Black,
+            patterns: false,
+            ascii: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Bright colors on black, for low-contrast terminals and projectors.
+    pub fn high_contrast() -> Self {
+        Theme {
+            title: Color::LightCyan,
+            text: Color::White,
+            muted: Color::White,
+            heading: Color::LightYellow,
+            good: Color::LightGreen,
+            warning: Color::LightYellow,
+            bad: Color::LightRed,
+            inactive: Color::Gray,
+            accent: Color::LightMagenta,
+            rate_line: Color::LightCyan,
+            service_bars: Color::LightYellow,
+            department_bars: Color::LightGreen,
+            risk_factor_bars: Color::LightYellow,
+            violation_bars: Color::LightRed,
+            risk_high: Color::LightRed,
+            risk_medium: Color::LightYellow,
+            risk_low: Color::LightGreen,
+            gauge_background: Color::Black,
+            patterns: false,
+            ascii: false,
+        }
+    }
+
+    /// The Okabe-Ito palette, which replaces red/green coding with blue, yellow, and vermillion
+    /// and adds fill patterns, so it stays readable with any form of color blindness.
+    pub fn color_blind() -> Self {
+        let blue = Color::Indexed(32);
+        let sky_blue = Color::Indexed(74);
+        let orange = Color::Indexed(214);
+        let yellow = Color::Indexed(227);
+        let vermillion = Color::Indexed(166);
+        Theme {
+            title: sky_blue,
+            text: Color::White,
+            muted: Color::Gray,
+            heading: orange,
+            good: blue,
+            warning: yellow,
+            bad: vermillion,
+            inactive: Color::DarkGray,
+            accent: Color::Indexed(175),
+            rate_line: sky_blue,
+            service_bars: orange,
+            department_bars: blue,
+            risk_factor_bars: orange,
+            violation_bars: vermillion,
+            risk_high: vermillion,
+            risk_medium: yellow,
+            risk_low: blue,
+            gauge_background: Color::Black,
+            patterns: true,
+            ascii: false,
+        }
+    }
+
+    /// Returns the built-in theme with the given name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "color-blind" => Some(Self::color_blind()),
+            _ => None,
+        }
+    }
+
+    /// Returns the theme selected by `--theme`: a built-in theme name or the path of a theme file.
+    ///
+    /// A theme file is a JSON object with an optional `base` built-in theme, an optional
+    /// `patterns` flag, and a `colors` object overriding individual roles, e.g.
+    /// `{"base": "color-blind", "colors": {"bad": "#d55e00", "title": "light-cyan"}}`.
+    pub fn load(spec: &str) -> Result<Self> {
+        if let Some(theme) = Self::builtin(spec) {
+            return Ok(theme);
+        }
+        let path = Path::new(spec);
+        let invalid = |message: String| MonitorError::Config(format!("theme {}: {}", path.display(), message));
+        let contents = fs::read_to_string(path).map_err(|e| {
+            invalid(format!("{} (built-in themes are {})", e, THEME_NAMES.join(", ")))
+        })?;
+        Self::from_json(&contents).map_err(invalid)
+    }
+
+    /// Parses the contents of a theme file.
+    fn from_json(contents: &str) -> std::result::Result<Self, String> {
+        let file: ThemeFile = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        let base = file.base.as_deref().unwrap_or("default");
+        let mut theme = Self::builtin(base).ok_or_else(|| format!("unknown base theme '{}'", base))?;
+        if let Some(patterns) = file.patterns {
+            theme.patterns = patterns;
+        }
+        for (role, value) in &file.colors {
+            let color = parse_color(value).ok_or_else(|| format!("invalid color '{}' for '{}'", value, role))?;
+            *theme.role_mut(role).ok_or_else(|| format!("unknown color role '{}'", role))? = color;
+        }
+        Ok(theme)
+    }
+
+    /// Returns the color of a role by its name in theme files.
+    fn role_mut(&mut self, role: &str) -> Option<&mut Color> {
+        Some(match role {
+            "title" => &mut self.title,
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "heading" => &mut self.heading,
+            "good" => &mut self.good,
+            "warning" => &mut self.warning,
+            "bad" => &mut self.bad,
+            "inactive" => &mut self.inactive,
+            "accent" => &mut self.accent,
+            "rate_line" => &mut self.rate_line,
+            "service_bars" => &mut self.service_bars,
+            "department_bars" => &mut self.department_bars,
+            "risk_factor_bars" => &mut self.risk_factor_bars,
+            "violation_bars" => &mut self.violation_bars,
+            "risk_high" => &mut self.risk_high,
+            "risk_medium" => &mut self.risk_medium,
+            "risk_low" => &mut self.risk_low,
+            "gauge_background" => &mut self.gauge_background,
+            _ => return None,
+        })
+    }
+
+    /// Returns the marker the charts plot with: Braille dots, or plain dots in ASCII mode.
+    pub fn chart_marker(&self) -> Marker {
+        if self.ascii { Marker::Dot } else { Marker::Braille }
+    }
+
+    /// Returns the symbols for a risk level's bar: solid by default, or one of three distinct
+    /// fill patterns when patterns are enabled.
+    pub fn risk_bar_set(&self, level: usize) -> bar::Set {
+        if !self.patterns {
+            return bar::NINE_LEVELS;
+        }
+        let fill = ["█", "▒", "░"][level.min(2)];
+        bar::Set {
+            full: fill,
+            seven_eighths: fill,
+            three_quarters: fill,
+            five_eighths: fill,
+            half: fill,
+            three_eighths: fill,
+            one_quarter: fill,
+            one_eighth: fill,
+            empty: " ",
+        }
+    }
+}
+
+/// Parses a color: a name such as `light-red` or `dark_gray`, a `#rrggbb` hex value, or a
+/// 256-color palette index.
+pub fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    if let Ok(index) = value.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+    let name: String = value.chars().filter(|c| !matches!(c, '-' | '_' | ' ')).collect::<String>().to_lowercase();
+    Some(match name.as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_color_names_hex_and_indexes() {
+        assert_eq!(parse_color("light-red"), Some(Color::LightRed));
+        assert_eq!(parse_color("Dark_Gray"), Some(Color::DarkGray));
+        assert_eq!(parse_color("#d55e00"), Some(Color::Rgb(0xd5, 0x5e, 0x00)));
+        assert_eq!(parse_color("208"), Some(Color::Indexed(208)));
+        assert_eq!(parse_color("#d55e0"), None);
+        assert_eq!(parse_color("mauve"), None);
+    }
+
+    #[test]
+    fn theme_file_overrides_its_base() {
+        let theme = Theme::from_json(r##"{"base": "color-blind", "colors": {"bad": "#ff0000"}}"##).unwrap();
+        assert_eq!(theme.bad, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.good, Theme::color_blind().good);
+        assert!(theme.patterns);
+
+        assert!(Theme::from_json(r#"{"colors": {"background": "red"}}"#).unwrap_err().contains("unknown color role"));
+        assert!(Theme::from_json(r#"{"base": "neon"}"#).unwrap_err().contains("unknown base theme"));
+    }
+}
@@ -1,11 +1,86 @@
+use crate::error::{report, ErrorPolicy, MonitorError};
+use crate::frontend::{ChartThresholds, Frontend, FrontendContext};
+use crate::logging;
+use crate::ui::dashboard::Dashboard;
+use crate::ui::keys::Keymap;
+use crate::ui::layout::DashboardLayout;
+use crate::ui::theme::Theme;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::io::{self, Stdout};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tui::{backend::CrosstermBackend, Terminal};
 
+/// Interactive terminal dashboard frontend.
+pub struct TuiFrontend {
+    /// Colors used by the dashboard.
+    pub theme: Theme,
+    /// Reference lines drawn across the history charts.
+    pub thresholds: ChartThresholds,
+    /// Custom layouts of the dashboard tabs.
+    pub layout: DashboardLayout,
+    /// Keys bound to the dashboard's actions.
+    pub keymap: Keymap,
+}
+
+impl Frontend for TuiFrontend {
+    fn name(&self) -> &'static str {
+        "TUI dashboard"
+    }
+
+    fn run(self: Box<Self>, context: FrontendContext) {
+        let TuiFrontend { theme, thresholds, layout, keymap } = *self;
+        let FrontendContext { policy, updates, control, stop_signal, errors, .. } = context;
+        let mut terminal = match setup_terminal() {
+            Ok(terminal) => terminal,
+            Err(e) => {
+                report(&errors, MonitorError::Terminal(e), ErrorPolicy::Shutdown);
+                return;
+            }
+        };
+        // Log output would corrupt the dashboard; it stays in the log file and the Logs tab.
+        logging::suspend_console(true);
+        let mut dashboard = Dashboard::new(policy, control, theme, thresholds, layout, keymap);
+        while !stop_signal.load(Ordering::Relaxed) && !dashboard.should_quit {
+            // Process incoming dashboard commands.
+            while let Ok(cmd) = updates.try_recv() {
+                dashboard.handle_command(cmd);
+            }
+            // Render the dashboard UI.
+            if let Err(e) = dashboard.render(&mut terminal) {
+                report(&errors, MonitorError::Terminal(e), ErrorPolicy::Shutdown);
+                break;
+            }
+            // Poll for key events with a timeout.
+            match event::poll(Duration::from_millis(100))
+                .and_then(|ready| if ready { event::read().map(Some) } else { Ok(None) })
+            {
+                Ok(Some(Event::Key(key))) => {
+                    dashboard.handle_key_event(key);
+                    if dashboard.should_quit {
+                        stop_signal.store(true, Ordering::Relaxed);
+                    }
+                }
+                Ok(Some(Event::Mouse(mouse))) => dashboard.handle_mouse_event(mouse),
+                Ok(_) => {}
+                Err(e) => {
+                    report(&errors, MonitorError::Terminal(e), ErrorPolicy::Shutdown);
+                    break;
+                }
+            }
+        }
+        // Restore terminal settings upon exit.
+        if let Err(e) = restore_terminal(&mut terminal) {
+            report(&errors, MonitorError::Terminal(e), ErrorPolicy::Degrade);
+        }
+        logging::suspend_console(false);
+    }
+}
+
 /// Sets up the terminal with raw mode, an alternate screen, and mouse capture enabled.
 pub fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
     enable_raw_mode()?;
@@ -1,62 +1,161 @@
+use crate::alerts::{Alert, AlertLog, AlertSeverity};
+use crate::clock::format_utc_time;
+use crate::cases::{Case, CaseBook, CaseState, CASE_STATES};
+use crate::compliance::ComplianceRegistry;
+use crate::components::{AiActTier, LawfulBasis, PiiTypes, AI_ACT_TIERS};
 use crate::constants::*;
-use crate::metrics::ComplianceMetrics;
+use crate::event_sample::{SampleGroup, SampledEvent};
+use crate::metrics::{ComplianceMetrics, HistoryPoint, UserCounts, CHART_VALUE_LIMIT};
+use crate::nist;
+use crate::policy::{HipaaRules, Policy};
+use crate::risk::RiskFactorRegistry;
+use crate::shadow::PolicyComparison;
+use crate::shadow_ai::ShadowAiUse;
+use crate::slo::SloPolicy;
+use crate::frontend::{InstanceStatus, PipelineHealth, ReplayStatus, SystemLatency, TailLatency, ThreadThroughput};
+use crate::ui::dashboard::{ChartView, ChartWindow};
+use crate::ui::search::Search;
+use crate::ui::sort::{violation_rate, SortKey, SortOrder};
+use crate::ui::theme::Theme;
+use crate::vendors::{VendorRegistry, VendorRiskTier};
+use std::borrow::Cow;
+use std::time::Duration;
 use tui::{
     backend::Backend,
-    layout::{Rect, Constraint, Direction, Layout},
+    buffer::Buffer,
+    layout::{Alignment, Rect, Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
-    symbols,
     text::{Span, Spans},
-    widgets::{Axis, BarChart, Block, Borders, Chart, Dataset, Gauge, Paragraph, Tabs, GraphType},
+    widgets::{Axis, BarChart, Block, Borders, Chart, Clear, Dataset, Gauge, Paragraph, Row, Sparkline, Table, TableState, Tabs, GraphType, Widget, Wrap},
     Frame,
 };
 
+/// Highlights the border of a pane in place, keeping the pane's title and contents.
+struct FocusBorder(Style);
+
+impl Widget for FocusBorder {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.area() == 0 {
+            return;
+        }
+        buf.set_style(Rect { height: 1, ..area }, self.0);
+        buf.set_style(Rect { y: area.bottom() - 1, height: 1, ..area }, self.0);
+        buf.set_style(Rect { width: 1, ..area }, self.0);
+        buf.set_style(Rect { x: area.right() - 1, width: 1, ..area }, self.0);
+    }
+}
+
+/// Marks the focused pane by drawing its border in the accent color.
+pub fn render_focus<B: Backend>(f: &mut Frame<B>, area: Rect, theme: &Theme) {
+    f.render_widget(FocusBorder(Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)), area);
+}
+
 /// Creates a styled block with the provided title.
-pub fn create_block(title: &str) -> Block {
+pub fn create_block<'a>(title: &'a str, theme: &Theme) -> Block<'a> {
     Block::default().borders(Borders::ALL).title(Span::styled(
         title,
-        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        Style::default().fg(theme.title).add_modifier(Modifier::BOLD),
     ))
 }
 
-/// Renders a gauge showing overall compliance percentage.
-pub fn render_compliance_gauge<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
-    let compliance_pct = metrics.compliance_percentage();
-    let gauge_color = if compliance_pct > 90.0 {
-        Color::Green
-    } else if compliance_pct > 70.0 {
-        Color::Yellow
-    } else {
-        Color::Red
-    };
-    let gauge = Gauge::default()
-        .block(create_block("Overall Compliance"))
-        .gauge_style(Style::default().fg(gauge_color).bg(Color::Black))
-        .percent(compliance_pct as u16)
-        .label(format!("{:.1}%", compliance_pct));
-    f.render_widget(gauge, area);
+/// Renders a row of gauges: the overall compliance under the policy's scoring mode, labeled with
+/// the mode, then one per compliance flag showing the percentage of events that passed that
+/// check, so a failing regulation is not hidden in a blended percentage. With patterns enabled,
+/// each label is marked with a symbol for the compliance level as well as colored.
+pub fn render_compliance_gauges<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    policy: &Policy,
+    theme: &Theme,
+) {
+    // The overall gauge is twice as wide, to fit the scoring mode in its label.
+    let shares = policy.compliance.len() as u32 + 2;
+    let mut constraints = vec![Constraint::Ratio(2, shares)];
+    constraints.extend(policy.compliance.iter().map(|_| Constraint::Ratio(1, shares)));
+    let chunks = Layout::default().direction(Direction::Horizontal).constraints(constraints).split(area);
+    let overall = (policy.scoring.score(metrics), Some(policy.scoring.mode.label()), "Overall Compliance");
+    let checks = policy
+        .compliance
+        .iter()
+        .map(|(flag, info)| (metrics.check_compliance_percentage(flag), None, info.name));
+    for ((compliance_pct, mode, title), chunk) in std::iter::once(overall).chain(checks).zip(chunks) {
+        let (gauge_color, marker) = if compliance_pct > 90.0 {
+            (theme.good, "✓")
+        } else if compliance_pct > 70.0 {
+            (theme.warning, "!")
+        } else {
+            (theme.bad, "✗")
+        };
+        let mut label = if theme.patterns { format!("{} {:.1}%", marker, compliance_pct) } else { format!("{:.1}%", compliance_pct) };
+        if let Some(mode) = mode {
+            label.push_str(&format!(" ({})", mode));
+        }
+        let gauge = Gauge::default()
+            .block(create_block(title, theme))
+            .gauge_style(Style::default().fg(gauge_color).bg(theme.gauge_background))
+            .percent(compliance_pct.clamp(0.0, 100.0).round() as u16)
+            .label(label);
+        f.render_widget(gauge, chunk);
+    }
 }
 
-/// Renders processing statistics as text.
-pub fn render_stats<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
-    let text = vec![
+/// Narrowest statistics panel, inside its borders, that has room for sparklines.
+const MIN_SPARKLINE_PANEL_WIDTH: u16 = 48;
+
+/// Renders processing statistics as text, with one violation line per compliance flag. If the
+/// panel is wide enough, sparklines of the latest intervals' events per second, violation rate,
+/// and high-risk rate are drawn next to those numbers.
+pub fn render_stats<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    compliance: &ComplianceRegistry,
+    theme: &Theme,
+) {
+    let mut text = Vec::new();
+    if metrics.counters_near_limit() {
+        text.push(Spans::from(Span::styled(
+            "Counters near the chart limit; bars will stop growing",
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )));
+    }
+    let rate_row = text.len() + 1;
+    let violation_row = text.len() + 2;
+    text.extend([
         Spans::from(Span::raw(format!("Total Events: {}", metrics.total_events))),
-        Spans::from(Span::raw(format!("Processing Rate: {:.1} events/s", metrics.processing_rate))),
+        Spans::from(Span::raw(format!("Processing Rate: {:.1} events/s", metrics.processing_rate()))),
+        Spans::from(Span::raw(format!("Violation Rate: {:.1}% of checks", 100.0 - metrics.compliance_percentage()))),
         Spans::from(Span::raw("")),
-        Spans::from(Span::raw(format!(
-            "EU AI Act Violations: {} ({:.1}%)",
-            metrics.eu_act_violations,
-            if metrics.total_events > 0 { (metrics.eu_act_violations as f64 / metrics.total_events as f64) * 100.0 } else { 0.0 }
-        ))),
-        Spans::from(Span::raw(format!(
-            "GDPR Violations: {} ({:.1}%)",
-            metrics.gdpr_violations,
-            if metrics.total_events > 0 { (metrics.gdpr_violations as f64 / metrics.total_events as f64) * 100.0 } else { 0.0 }
-        ))),
-        Spans::from(Span::raw(format!(
-            "Internal Policy Violations: {} ({:.1}%)",
-            metrics.internal_violations,
-            if metrics.total_events > 0 { (metrics.internal_violations as f64 / metrics.total_events as f64) * 100.0 } else { 0.0 }
-        ))),
+    ]);
+    for (flag, info) in compliance.iter() {
+        let violations = metrics.violations(flag);
+        let waived = match metrics.waived(flag) {
+            0 => String::new(),
+            waived => format!(", {} waived", waived),
+        };
+        text.push(Spans::from(Span::raw(format!(
+            "{} Violations: {} ({:.1}%){}",
+            info.name,
+            violations,
+            if metrics.total_events > 0 { (violations as f64 / metrics.total_events as f64) * 100.0 } else { 0.0 },
+            waived
+        ))));
+    }
+    if metrics.waived_events > 0 {
+        text.push(Spans::from(Span::raw(format!("Waived Events: {}", metrics.waived_events))));
+    }
+    let shadow_ai_style = if metrics.shadow_ai_events > 0 { Style::default().fg(theme.warning) } else { Style::default() };
+    text.push(Spans::from(Span::styled(
+        format!(
+            "Shadow AI Events: {} ({:.1}%)",
+            metrics.shadow_ai_events,
+            if metrics.total_events > 0 { (metrics.shadow_ai_events as f64 / metrics.total_events as f64) * 100.0 } else { 0.0 }
+        ),
+        shadow_ai_style,
+    )));
+    let high_risk_row = text.len() + 1;
+    text.extend([
         Spans::from(Span::raw("")),
         Spans::from(Span::raw(format!(
             "High Risk Events: {} ({:.1}%)",
@@ -73,193 +172,754 @@ pub fn render_stats<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &Complian
             metrics.low_risk_count,
             if metrics.total_events > 0 { (metrics.low_risk_count as f64 / metrics.total_events as f64) * 100.0 } else { 0.0 }
         ))),
+    ]);
+    let block = create_block("Processing Statistics", theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.width < MIN_SPARKLINE_PANEL_WIDTH {
+        f.render_widget(Paragraph::new(text).style(Style::default().fg(theme.text)), inner);
+        return;
+    }
+    let spark_width = inner.width / 3;
+    let text_area = Rect { width: inner.width - spark_width - 1, ..inner };
+    f.render_widget(Paragraph::new(text).style(Style::default().fg(theme.text)), text_area);
+
+    // One bar per interval, so the sparklines show the latest intervals at full resolution.
+    let history = &metrics.history[metrics.history.len().saturating_sub(spark_width as usize)..];
+    // Percentages are scaled to hundredths, since sparklines plot whole numbers.
+    let sparklines: [(usize, Vec<u64>, Color); 3] = [
+        (rate_row, history.iter().map(|point| chart_value(point.rate().round() as u64)).collect(), theme.rate_line),
+        (violation_row, history.iter().map(|point| (point.violation_percentage() * 100.0).round() as u64).collect(), theme.bad),
+        (high_risk_row, history.iter().map(|point| (point.high_risk_percentage() * 100.0).round() as u64).collect(), theme.risk_high),
     ];
-    let paragraph = Paragraph::new(text)
-        .block(create_block("Processing Statistics"))
-        .style(Style::default().fg(Color::White));
-    f.render_widget(paragraph, area);
+    for (row, data, color) in sparklines {
+        if row >= inner.height as usize {
+            continue;
+        }
+        let spark_area = Rect {
+            x: inner.right() - spark_width,
+            y: inner.y + row as u16,
+            width: spark_width,
+            height: 1,
+        };
+        f.render_widget(Sparkline::default().data(&data).style(Style::default().fg(color)), spark_area);
+    }
 }
 
+/// Caps a value drawn in a bar chart or sparkline at [`CHART_VALUE_LIMIT`], beyond which tui's
+/// scaling would overflow.
+fn chart_value(value: u64) -> u64 {
+    value.min(CHART_VALUE_LIMIT)
+}
+
+/// Width of each bar in the usage charts.
+const USAGE_BAR_WIDTH: u16 = 9;
+
 /// Renders a bar chart displaying service usage.
-pub fn render_service_chart<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
-    let mut data = Vec::new();
-    let total = metrics.total_events.max(1) as f64;
-    for i in 0..SERVICE_NAMES.len() {
-        if metrics.service_counts[i] > 0 {
-            let percentage = (metrics.service_counts[i] as f64 / total) * 100.0;
-            data.push((SERVICE_NAMES[i], percentage as u64));
-        }
-    }
-    data.sort_by(|a, b| b.1.cmp(&a.1));
-    let barchart = BarChart::default()
-        .block(create_block("Service Usage"))
-        .data(&data)
-        .bar_width(9)
-        .bar_style(Style::default().fg(Color::Yellow))
-        .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
-    f.render_widget(barchart, area);
+///
+/// # Returns
+///
+/// The screen area of each bar with the index of its service, for mouse hit testing.
+pub fn render_service_chart<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    sort: SortOrder,
+    theme: &Theme,
+) -> Vec<(Rect, usize)> {
+    let usage: Vec<(&str, u64, u64)> = SERVICE_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (*name, metrics.service_counts[index], metrics.services.get(index).map_or(0, |service| service.violating_events)))
+        .collect();
+    render_usage_chart(f, area, "Service Usage", &usage, metrics.total_events, sort, theme.service_bars, theme)
 }
 
 /// Renders a bar chart displaying department usage.
-pub fn render_department_chart<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
-    let mut data = Vec::new();
-    let total = metrics.total_events.max(1) as f64;
-    for i in 0..DEPARTMENT_NAMES.len() {
-        if metrics.department_counts[i] > 0 {
-            let percentage = (metrics.department_counts[i] as f64 / total) * 100.0;
-            data.push((DEPARTMENT_NAMES[i], percentage as u64));
-        }
-    }
-    data.sort_by(|a, b| b.1.cmp(&a.1));
+///
+/// # Returns
+///
+/// The screen area of each bar with the index of its department, for mouse hit testing.
+pub fn render_department_chart<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    sort: SortOrder,
+    theme: &Theme,
+) -> Vec<(Rect, usize)> {
+    let usage: Vec<(&str, u64, u64)> = DEPARTMENT_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let violating = metrics.departments.get(index).map_or(0, |department| department.violating_events);
+            (*name, metrics.department_counts[index], violating)
+        })
+        .collect();
+    render_usage_chart(f, area, "Department Usage", &usage, metrics.total_events, sort, theme.department_bars, theme)
+}
+
+/// Renders the share of events per category as bars, largest first unless sorted otherwise.
+/// Sorted by violation rate, the bars show the percentage of each category's events that
+/// violated a check instead.
+///
+/// # Arguments
+///
+/// * `usage` - Name, event count, and violating event count of each category.
+/// * `total_events` - All events processed.
+/// * `sort` - Order of the bars.
+/// * `color` - Color of the bars.
+#[allow(clippy::too_many_arguments)]
+fn render_usage_chart<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    title: &str,
+    usage: &[(&str, u64, u64)],
+    total_events: u64,
+    sort: SortOrder,
+    color: Color,
+    theme: &Theme,
+) -> Vec<(Rect, usize)> {
+    let total = total_events.max(1) as f64;
+    let by_rate = sort.key == SortKey::ViolationRate;
+    let mut bars: Vec<(usize, u64)> = usage
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, count, _))| *count > 0)
+        .map(|(index, &(_, count, violating))| {
+            let percentage = if by_rate { violation_rate(violating, count) * 100.0 } else { (count as f64 / total) * 100.0 };
+            (index, percentage as u64)
+        })
+        .collect();
+    sort.sort(&mut bars, |&(index, _)| {
+        let (name, count, violating) = usage[index];
+        (name.to_lowercase(), count as f64, violation_rate(violating, count))
+    });
+    let data: Vec<(&str, u64)> = bars.iter().map(|&(index, percentage)| (usage[index].0, percentage)).collect();
+    let title = match (by_rate, sort.label()) {
+        (true, Some(label)) => format!("{} (% violating, {})", title, label),
+        (false, Some(label)) => format!("{} ({})", title, label),
+        (_, None) => title.to_string(),
+    };
+    let block = create_block(&title, theme);
+    let inner = block.inner(area);
     let barchart = BarChart::default()
-        .block(create_block("Department Usage"))
+        .block(block)
         .data(&data)
-        .bar_width(9)
-        .bar_style(Style::default().fg(Color::Green))
-        .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+        .bar_width(USAGE_BAR_WIDTH)
+        .bar_style(Style::default().fg(color))
+        .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD));
     f.render_widget(barchart, area);
+    // Mirrors the bar chart's layout: bars separated by a one-column gap, as many as fit.
+    let visible = (inner.width / (USAGE_BAR_WIDTH + 1)) as usize;
+    bars.iter()
+        .take(visible)
+        .enumerate()
+        .map(|(position, &(index, _))| {
+            let x = inner.x + position as u16 * (USAGE_BAR_WIDTH + 1);
+            (Rect::new(x, inner.y, USAGE_BAR_WIDTH, inner.height), index)
+        })
+        .collect()
 }
 
-/// Renders a line chart showing historical processing rates.
-pub fn render_rate_chart<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
-    if metrics.historical_rates.is_empty() {
-        let message = Paragraph::new("Waiting for data...")
-            .block(create_block("Processing Rate History"))
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(message, area);
-        return;
-    }
-    let data: Vec<(f64, f64)> = metrics.historical_rates
+/// Renders details of the service or department selected by clicking its bar.
+///
+/// # Arguments
+///
+/// * `name` - Name of the selected service or department.
+/// * `count` - Events of the selected service or department.
+/// * `counts` - Events of every service or department, to rank the selection.
+/// * `total_events` - All events processed.
+pub fn render_usage_details<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    name: &str,
+    count: u64,
+    counts: &[u64],
+    total_events: u64,
+    theme: &Theme,
+) {
+    let share = 100.0 * count as f64 / total_events.max(1) as f64;
+    let rank = 1 + counts.iter().filter(|&&other| other > count).count();
+    let text = vec![
+        Spans::from(Span::styled(name.to_string(), Style::default().fg(theme.title).add_modifier(Modifier::BOLD))),
+        Spans::from(format!("Events: {} ({:.1}% of all events)", count, share)),
+        Spans::from(format!("Rank: {} of {}", rank, counts.len())),
+    ];
+    let paragraph = Paragraph::new(text).block(create_block("Details (click the bar again to close)", theme));
+    f.render_widget(paragraph, area);
+}
+
+/// Columns taken by one time axis label and the space after it.
+const TIME_LABEL_WIDTH: usize = 10;
+
+/// Round intervals between time axis labels, in seconds.
+const TIME_TICK_STEPS: [u64; 17] = [1, 2, 5, 10, 15, 30, 60, 120, 300, 600, 900, 1_800, 3_600, 7_200, 21_600, 43_200, 86_400];
+
+/// Returns time axis bounds widened to whole ticks and an `HH:MM:SS` label for each tick, using
+/// the shortest round tick interval that needs no more than `max_labels` labels.
+///
+/// # Arguments
+///
+/// * `start` - Start of the axis, in seconds since the Unix epoch.
+/// * `end` - End of the axis, in seconds since the Unix epoch.
+/// * `max_labels` - Most labels that fit along the axis.
+fn time_axis(start: f64, end: f64, max_labels: usize) -> ([f64; 2], Vec<String>) {
+    let max_labels = max_labels.max(2) as u64;
+    let (start, end) = (start.max(0.0).floor() as u64, end.max(0.0).ceil() as u64);
+    let ticks = |step: u64| (start / step, end.div_ceil(step).max(start / step + 1));
+    let step = TIME_TICK_STEPS
+        .into_iter()
+        .find(|&step| {
+            let (first, last) = ticks(step);
+            last - first < max_labels
+        })
+        .unwrap_or_else(|| (end - start).div_ceil(max_labels - 1).div_ceil(86_400) * 86_400);
+    let (first, last) = ticks(step);
+    let labels = (first..=last).map(|tick| format_utc_time(tick * step)).collect();
+    ([(first * step) as f64, (last * step) as f64], labels)
+}
+
+/// A history chart's plotted line, value axis, and reference line.
+struct TimeSeries<'a> {
+    title: String,
+    /// Legend name and color of the plotted line.
+    name: &'a str,
+    color: Color,
+    /// Points of seconds since the Unix epoch and value.
+    data: Vec<(f64, f64)>,
+    /// Start and end of the time axis, in seconds since the Unix epoch.
+    x_range: [f64; 2],
+    y_title: &'a str,
+    y_bounds: [f64; 2],
+    y_labels: [String; 3],
+    /// Legend name and value of the horizontal reference line.
+    threshold: Option<(String, f64)>,
+    /// Point under the inspection cursor, with its value formatted for the cursor's box.
+    cursor: Option<((f64, f64), String)>,
+}
+
+/// The history shown by a chart.
+struct VisibleHistory {
+    /// Points merged down for display.
+    points: Vec<HistoryPoint>,
+    /// Start and end of the time axis, in seconds since the Unix epoch.
+    x_range: [f64; 2],
+    /// Index of the point under the inspection cursor, if it is shown.
+    cursor: Option<usize>,
+}
+
+/// Returns the points of vertical lines marking when the alerts in view were raised, grouped by
+/// severity, most severe first, so that each severity is plotted as one dataset.
+///
+/// # Arguments
+///
+/// * `alerts` - The alert history.
+/// * `x_bounds` - Bounds of the time axis, in seconds since the Unix epoch.
+/// * `y_bounds` - Bounds of the value axis, which the lines span.
+/// * `rows` - Height of the plot in terminal rows; braille markers plot four dots per row.
+fn alert_markers(alerts: &AlertLog, x_bounds: [f64; 2], y_bounds: [f64; 2], rows: u16) -> Vec<(AlertSeverity, Vec<(f64, f64)>)> {
+    let dots = (rows as usize * 4).max(2);
+    let step = (y_bounds[1] - y_bounds[0]) / (dots - 1) as f64;
+    [AlertSeverity::Critical, AlertSeverity::Warning, AlertSeverity::Info]
+        .into_iter()
+        .map(|severity| {
+            let points: Vec<(f64, f64)> = alerts
+                .alerts()
+                .iter()
+                .filter(|alert| alert.severity == severity)
+                .map(|alert| alert.raised_at_ms as f64 / 1_000.0)
+                .filter(|time| (x_bounds[0]..=x_bounds[1]).contains(time))
+                .flat_map(|time| (0..dots).map(move |dot| (time, y_bounds[0] + dot as f64 * step)))
+                .collect();
+            (severity, points)
+        })
+        .filter(|(_, points)| !points.is_empty())
+        .collect()
+}
+
+/// Renders a line chart against wall-clock time, so spikes can be matched with other logs, with
+/// the reference line across it and a vertical marker where each alert in view was raised. A
+/// point under the inspection cursor is marked with crosshairs, and its time and value are
+/// shown in a box in the chart's top right corner, in place of the legend.
+fn render_time_series<B: Backend>(f: &mut Frame<B>, area: Rect, series: TimeSeries, alerts: &AlertLog, theme: &Theme) {
+    let [start, end] = series.x_range;
+    let (x_bounds, x_labels) = time_axis(start, end, area.width.saturating_sub(12) as usize / TIME_LABEL_WIDTH);
+    let markers = alert_markers(alerts, x_bounds, series.y_bounds, area.height.saturating_sub(3));
+    let threshold = series.threshold.map(|(name, value)| (name, [(x_bounds[0], value), (x_bounds[1], value)]));
+    let crosshairs = series.cursor.as_ref().map(|&((x, y), _)| {
+        [[(x, series.y_bounds[0]), (x, series.y_bounds[1])], [(x_bounds[0], y), (x_bounds[1], y)]]
+    });
+    // The plotted line is drawn last, so it stays visible where the overlays cross it.
+    let mut datasets: Vec<Dataset> = markers
         .iter()
-        .enumerate()
-        .map(|(i, &rate)| (i as f64, rate))
+        .map(|(severity, points)| {
+            Dataset::default()
+                .name(format!("{} alerts", severity.name()))
+                .marker(theme.chart_marker())
+                .style(Style::default().fg(severity_color(*severity, theme)))
+                .graph_type(GraphType::Scatter)
+                .data(points)
+        })
         .collect();
-    let max_rate = metrics.historical_rates.iter().cloned().fold(0.0, f64::max);
-    let datasets = vec![
+    if let Some((name, line)) = &threshold {
+        datasets.push(
+            Dataset::default()
+                .name(name.as_str())
+                .marker(theme.chart_marker())
+                .style(Style::default().fg(theme.muted))
+                .graph_type(GraphType::Line)
+                .data(line),
+        );
+    }
+    for line in crosshairs.iter().flatten() {
+        datasets.push(
+            Dataset::default()
+                .marker(theme.chart_marker())
+                .style(Style::default().fg(theme.accent))
+                .graph_type(GraphType::Line)
+                .data(line),
+        );
+    }
+    datasets.push(
         Dataset::default()
-            .name("Events/second")
-            .marker(symbols::Marker::Braille)
-            .style(Style::default().fg(Color::Cyan))
+            .name(series.name)
+            .marker(theme.chart_marker())
+            .style(Style::default().fg(series.color))
             .graph_type(GraphType::Line)
-            .data(&data),
-    ];
-    let mid_label = format!("{:.0}", max_rate / 2.0);
-    let max_label = format!("{:.0}", max_rate);
-    let chart = Chart::new(datasets)
-        .block(create_block("Processing Rate History"))
+            .data(&series.data),
+    );
+    let mut chart = Chart::new(datasets)
+        .block(create_block(&series.title, theme))
         .x_axis(
             Axis::default()
-                .title(Span::styled("Time", Style::default().fg(Color::White)))
-                .style(Style::default().fg(Color::White))
-                .bounds([0.0, data.len() as f64])
-                .labels(vec![
-                    Span::styled("Start", Style::default().fg(Color::White)),
-                    Span::styled("Now", Style::default().fg(Color::White)),
-                ]),
+                .title(Span::styled("UTC", Style::default().fg(theme.text)))
+                .style(Style::default().fg(theme.text))
+                .bounds(x_bounds)
+                .labels(x_labels.into_iter().map(|label| Span::styled(label, Style::default().fg(theme.text))).collect()),
         )
         .y_axis(
             Axis::default()
-                .title(Span::styled("Events/s", Style::default().fg(Color::White)))
-                .style(Style::default().fg(Color::White))
-                .bounds([0.0, max_rate * 1.1])
-                .labels(vec![
-                    Span::styled("0", Style::default().fg(Color::White)),
-                    Span::styled(&mid_label, Style::default().fg(Color::White)),
-                    Span::styled(&max_label, Style::default().fg(Color::White)),
-                ]),
+                .title(Span::styled(series.y_title, Style::default().fg(theme.text)))
+                .style(Style::default().fg(theme.text))
+                .bounds(series.y_bounds)
+                .labels(series.y_labels.into_iter().map(|label| Span::styled(label, Style::default().fg(theme.text))).collect()),
         );
+    let Some(((x, _), value)) = series.cursor else {
+        f.render_widget(chart, area);
+        return;
+    };
+    chart = chart.hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)));
     f.render_widget(chart, area);
+    let lines = vec![Spans::from(format!("{} UTC", format_utc_time(x.round() as u64))), Spans::from(value)];
+    let width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16 + 2;
+    if width + 2 > area.width || area.height < 6 {
+        return;
+    }
+    let cursor_box = Rect::new(area.right() - width - 1, area.y + 1, width, 4);
+    f.render_widget(Clear, cursor_box);
+    let block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme.accent));
+    f.render_widget(Paragraph::new(lines).block(block).style(Style::default().fg(theme.text)), cursor_box);
+}
+
+/// Renders a "Waiting for data" placeholder in place of a chart with no history yet.
+fn render_waiting<B: Backend>(f: &mut Frame<B>, area: Rect, title: &str, theme: &Theme) {
+    let message = Paragraph::new("Waiting for data...")
+        .block(create_block(title, theme))
+        .style(Style::default().fg(theme.muted));
+    f.render_widget(message, area);
 }
 
-/// Renders a bar chart showing breakdown of risk factors.
-pub fn render_risk_factors<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
+/// Returns the history shown by a chart, merged down to two points per column of `area` since
+/// braille markers plot two points per terminal column, or `None` before the first reporting
+/// interval.
+fn visible_history(metrics: &ComplianceMetrics, view: ChartView, area: Rect) -> Option<VisibleHistory> {
+    let (start, end) = view.range(metrics)?;
+    let points = metrics.history_between(start, end, area.width.saturating_sub(2) as usize * 2);
+    // Wall-clock times are counted back from the latest point, so the axis is labeled in UTC.
+    let latest = metrics.history.last()?;
+    let unix_secs = |at: Duration| latest.unix_ms as f64 / 1_000.0 - (latest.at - at).as_secs_f64();
+    // A merged point covers the intervals up to its end, so the cursor is on the first point
+    // ending at or after it.
+    let cursor = view
+        .cursor
+        .filter(|_| !points.is_empty())
+        .map(|cursor| points.partition_point(|point| point.at < cursor).min(points.len() - 1));
+    Some(VisibleHistory { points, x_range: [unix_secs(start), unix_secs(end)], cursor })
+}
+
+/// Renders a line chart of the processing rate over the selected time window, zoomed and panned.
+///
+/// # Arguments
+///
+/// * `view` - The time window and the part of it shown.
+/// * `max_rate` - Most events per second the deployment is sized for, drawn as a reference line.
+/// * `alerts` - The alert history, marked where each alert was raised.
+pub fn render_rate_chart<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    view: ChartView,
+    max_rate: Option<f64>,
+    alerts: &AlertLog,
+    theme: &Theme,
+) {
+    let title = format!("Processing Rate History ({})", view.label());
+    let Some(visible) = visible_history(metrics, view, area) else {
+        render_waiting(f, area, &title, theme);
+        return;
+    };
+    let data: Vec<(f64, f64)> = visible.points.iter().map(|point| (point.unix_ms as f64 / 1_000.0, point.rate())).collect();
+    // The axis reaches the reference line even while the rate stays well below it.
+    let top = visible.points.iter().map(|point| point.rate()).fold(max_rate.unwrap_or(0.0), f64::max);
+    let cursor = visible.cursor.map(|index| (data[index], format!("{:.1} events/s", data[index].1)));
+    let series = TimeSeries {
+        title,
+        name: "Events/second",
+        color: theme.rate_line,
+        data,
+        x_range: visible.x_range,
+        y_title: "Events/s",
+        y_bounds: [0.0, top * 1.1],
+        y_labels: ["0".to_string(), format!("{:.0}", top / 2.0), format!("{:.0}", top)],
+        threshold: max_rate.map(|rate| (format!("Max {:.0}/s", rate), rate)),
+        cursor,
+    };
+    render_time_series(f, area, series, alerts, theme);
+}
+
+/// Renders a line chart of the share of compliance checks passed in each interval of the
+/// selected time window, zoomed and panned.
+///
+/// # Arguments
+///
+/// * `view` - The time window and the part of it shown.
+/// * `target` - Compliance the organization aims for, in percent, drawn as a reference line.
+/// * `alerts` - The alert history, marked where each alert was raised.
+pub fn render_compliance_chart<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    view: ChartView,
+    target: Option<f64>,
+    alerts: &AlertLog,
+    theme: &Theme,
+) {
+    let title = format!("Compliance History ({})", view.label());
+    let Some(visible) = visible_history(metrics, view, area) else {
+        render_waiting(f, area, &title, theme);
+        return;
+    };
+    let data: Vec<(f64, f64)> = visible
+        .points
+        .iter()
+        .map(|point| (point.unix_ms as f64 / 1_000.0, 100.0 - point.violation_percentage()))
+        .collect();
+    // Start the axis at the ten below the lowest value, so small dips stay visible.
+    let lowest = data.iter().map(|&(_, compliance)| compliance).fold(target.unwrap_or(100.0), f64::min);
+    let bottom = ((lowest - 5.0) / 10.0).floor().clamp(0.0, 9.0) * 10.0;
+    let cursor = visible.cursor.map(|index| (data[index], format!("{:.2}% of checks passed", data[index].1)));
+    let series = TimeSeries {
+        title,
+        name: "Checks passed",
+        color: theme.good,
+        data,
+        x_range: visible.x_range,
+        y_title: "%",
+        y_bounds: [bottom, 100.0],
+        y_labels: [format!("{:.0}", bottom), format!("{:.0}", (bottom + 100.0) / 2.0), "100".to_string()],
+        threshold: target.map(|target| (format!("Target {:.1}%", target), target)),
+        cursor,
+    };
+    render_time_series(f, area, series, alerts, theme);
+}
+
+/// Renders a bar chart showing breakdown of the registered risk factors.
+pub fn render_risk_factors<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    registry: &RiskFactorRegistry,
+    theme: &Theme,
+) {
     if metrics.total_events == 0 {
         let message = Paragraph::new("Waiting for data...")
-            .block(create_block("Risk Factors"))
-            .style(Style::default().fg(Color::Gray));
+            .block(create_block("Risk Factors", theme))
+            .style(Style::default().fg(theme.muted));
         f.render_widget(message, area);
         return;
     }
     let mut risk_data = Vec::new();
-    for (i, (_, _name)) in crate::constants::RISK_FACTOR_NAMES.iter().enumerate() {
-        if metrics.risk_factor_counts[i] > 0 {
-            let short_name = match i {
-                0 => "EU Act",
-                1 => "GDPR",
-                2 => "Internal",
-                3 => "Sensitive",
-                4 => "Public Model",
-                _ => "Other",
-            };
-            risk_data.push((short_name, metrics.risk_factor_counts[i] as u64));
+    for (id, factor) in registry.iter() {
+        let count = metrics.risk_factor_counts.get(id.0 as usize).copied().unwrap_or(0);
+        if count > 0 {
+            risk_data.push((factor.name.as_str(), chart_value(count)));
         }
     }
-    risk_data.sort_by(|a, b| b.1.cmp(&a.1));
+    risk_data.sort_by_key(|b| std::cmp::Reverse(b.1));
     if !risk_data.is_empty() {
         let barchart = BarChart::default()
-            .block(create_block("Risk Factors"))
+            .block(create_block("Risk Factors", theme))
             .data(&risk_data)
             .bar_width(9)
-            .bar_style(Style::default().fg(Color::Yellow))
-            .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+            .bar_style(Style::default().fg(theme.risk_factor_bars))
+            .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
             .bar_gap(2);
         f.render_widget(barchart, area);
     } else {
         let message = Paragraph::new("No risk factors detected")
-            .block(create_block("Risk Factors"))
-            .style(Style::default().fg(Color::Gray));
+            .block(create_block("Risk Factors", theme))
+            .style(Style::default().fg(theme.muted));
         f.render_widget(message, area);
     }
 }
 
-/// Renders a bar chart displaying compliance violations.
-pub fn render_violation_chart<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
+/// Renders a bar chart displaying violations per compliance flag within the selected time window.
+pub fn render_violation_chart<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    compliance: &ComplianceRegistry,
+    window: ChartWindow,
+    theme: &Theme,
+) {
+    let title = format!("Compliance Violations ({})", window.label());
     if metrics.total_events == 0 {
         let message = Paragraph::new("Waiting for data...")
-            .block(create_block("Compliance Violations"))
-            .style(Style::default().fg(Color::Gray));
+            .block(create_block(&title, theme))
+            .style(Style::default().fg(theme.muted));
         f.render_widget(message, area);
         return;
     }
-    let violations = vec![
-        ("EU AI Act", metrics.eu_act_violations as u64),
-        ("GDPR", metrics.gdpr_violations as u64),
-        ("Internal", metrics.internal_violations as u64),
-    ];
+    let counts = metrics.violations_in_window(window.duration());
+    let violations: Vec<(&str, u64)> =
+        compliance.iter().map(|(flag, info)| (info.name, chart_value(counts.get(flag.0 as usize).copied().unwrap_or(0)))).collect();
     let barchart = BarChart::default()
-        .block(create_block("Compliance Violations"))
+        .block(create_block(&title, theme))
         .data(&violations)
         .bar_width(10)
-        .bar_style(Style::default().fg(Color::Red))
-        .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .bar_style(Style::default().fg(theme.violation_bars))
+        .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
         .bar_gap(3);
     f.render_widget(barchart, area);
 }
 
+/// Renders a bar chart of the purpose-limitation violations per declared purpose since the start.
+pub fn render_purpose_chart<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, theme: &Theme) {
+    let title = "Purpose Limitation Violations";
+    if metrics.purpose_counts.iter().all(|&count| count == 0) {
+        let message = Paragraph::new("Waiting for events with a declared purpose...")
+            .block(create_block(title, theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let violations: Vec<(&str, u64)> = PURPOSE_NAMES.into_iter().zip(metrics.purpose_violations.map(chart_value)).collect();
+    let barchart = BarChart::default()
+        .block(create_block(title, theme))
+        .data(&violations)
+        .bar_width(16)
+        .bar_style(Style::default().fg(theme.violation_bars))
+        .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
+        .bar_gap(1);
+    f.render_widget(barchart, area);
+}
+
+/// Renders the HIPAA summary: events carrying protected health information and those sent to
+/// vendors without a business associate agreement.
+pub fn render_hipaa<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, rules: &HipaaRules, theme: &Theme) {
+    let violations = metrics.violations(rules.flag);
+    let violation_style = if violations > 0 { Style::default().fg(theme.bad) } else { Style::default().fg(theme.good) };
+    let text = vec![
+        Spans::from(Span::raw(format!("PHI events: {}", metrics.phi_events))),
+        Spans::from(Span::styled(
+            format!(
+                "Sent to vendors without a BAA: {} ({:.1}% of PHI)",
+                violations,
+                100.0 * violations as f64 / metrics.phi_events.max(1) as f64
+            ),
+            violation_style,
+        )),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(create_block("HIPAA", theme))
+        .style(Style::default().fg(theme.text));
+    f.render_widget(paragraph, area);
+}
+
+/// Renders a table of the events processed in each region, with their share and data residency
+/// violations.
+pub fn render_regions<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, theme: &Theme) {
+    let located = metrics.region_counts.iter().sum::<u64>().max(1) as f64;
+    let rows = REGION_NAMES.iter().enumerate().map(|(region, name)| {
+        let violations = metrics.region_violations[region];
+        let style = if violations > 0 { Style::default().fg(theme.bad) } else { Style::default().fg(theme.text) };
+        Row::new(vec![
+            name.to_string(),
+            if REGION_ADEQUATE[region] { "yes" } else { "no" }.to_string(),
+            metrics.region_counts[region].to_string(),
+            format!("{:.1}%", 100.0 * metrics.region_counts[region] as f64 / located),
+            violations.to_string(),
+        ])
+        .style(style)
+    });
+    let header = Row::new(vec!["Region", "Adequate", "Events", "Share", "Violations"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Processing Regions", theme))
+        .widths(&[
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(10),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders a table of the events on each GDPR lawful basis, with their share and how many carry
+/// personal data. Personal data processed without a lawful basis is shown in red.
+pub fn render_lawful_bases<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, theme: &Theme) {
+    let recorded = metrics.lawful_basis_counts.iter().sum::<u64>().max(1) as f64;
+    let rows = LAWFUL_BASIS_NAMES.iter().enumerate().map(|(basis, name)| {
+        let personal = metrics.lawful_basis_personal[basis];
+        let unlawful = basis == LawfulBasis::NONE.0 as usize && personal > 0;
+        let style = if unlawful { Style::default().fg(theme.bad) } else { Style::default().fg(theme.text) };
+        Row::new(vec![
+            name.to_string(),
+            metrics.lawful_basis_counts[basis].to_string(),
+            format!("{:.1}%", 100.0 * metrics.lawful_basis_counts[basis] as f64 / recorded),
+            personal.to_string(),
+        ])
+        .style(style)
+    });
+    let header = Row::new(vec!["Basis", "Events", "Share", "Personal data"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Lawful Basis", theme))
+        .widths(&[
+            Constraint::Length(20),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(13),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders each department's compliance SLO over its rolling window, with the error budget
+/// left and the current burn rate. Departments burning their budget faster than the alert
+/// threshold, or that have used it up, are shown in red.
+pub fn render_slos<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, slo: &SloPolicy, theme: &Theme) {
+    let rows = slo.evaluate(metrics).into_iter().map(|status| {
+        let style = if status.is_burning(slo) || status.budget_remaining <= 0.0 {
+            Style::default().fg(theme.bad)
+        } else if status.burn_rate > 1.0 {
+            Style::default().fg(theme.warning)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        Row::new(vec![
+            DEPARTMENT_NAMES[status.department as usize].to_string(),
+            format!("{:.1}%", status.target),
+            format!("{:.2}%", status.compliance),
+            format!("{:.0}%", 100.0 * status.budget_remaining.max(0.0)),
+            format!("{:.1}x", status.burn_rate),
+        ])
+        .style(style)
+    });
+    let header = Row::new(vec!["Department", "Target", "Actual", "Budget", "Burn"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let title = format!("Compliance SLOs ({} window, alert above {:.1}x)", slo.window_label(), slo.burn_rate_alert);
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block(&title, theme))
+        .widths(&[
+            Constraint::Length(12),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Length(7),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the violations of each check under the active and the candidate policy of shadow
+/// mode. Checks the candidate would flag more often are colored as bad, less often as good.
+pub fn render_policy_comparison<B: Backend>(f: &mut Frame<B>, area: Rect, comparison: &PolicyComparison, theme: &Theme) {
+    let count = |violations: Option<u64>| violations.map_or_else(|| "-".to_string(), |violations| violations.to_string());
+    let delta_style = |delta: i64| match delta {
+        delta if delta > 0 => Style::default().fg(theme.bad),
+        delta if delta < 0 => Style::default().fg(theme.good),
+        _ => Style::default().fg(theme.text),
+    };
+    let rows = comparison
+        .rules
+        .iter()
+        .map(|rule| {
+            Row::new(vec![rule.name.clone(), count(rule.active), count(rule.candidate), format!("{:+}", rule.delta())])
+                .style(delta_style(rule.delta()))
+        })
+        .chain(std::iter::once(
+            Row::new(vec!["Total".to_string(), String::new(), String::new(), format!("{:+}", comparison.total_delta())])
+                .style(delta_style(comparison.total_delta()).add_modifier(Modifier::BOLD)),
+        ));
+    let header = Row::new(vec!["Check", "Active", "Candidate", "Delta"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let title = format!("Candidate Policy ({} events)", comparison.events);
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block(&title, theme))
+        .widths(&[
+            Constraint::Length(12),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+        ]);
+    f.render_widget(table, area);
+}
+
 /// Renders the tab selector.
-pub fn render_tabs<B: Backend>(f: &mut Frame<B>, area: Rect, titles: &[&str], active_tab: usize) {
+///
+/// # Returns
+///
+/// The screen area of each tab title, for mouse hit testing.
+pub fn render_tabs<B: Backend>(f: &mut Frame<B>, area: Rect, titles: &[&str], active_tab: usize, theme: &Theme) -> Vec<Rect> {
+    let block = Block::default().borders(tui::widgets::Borders::BOTTOM);
+    let inner = block.inner(area);
     let tabs = Tabs::new(titles.iter().map(|t| Spans::from(*t)).collect())
-        .block(Block::default().borders(tui::widgets::Borders::BOTTOM))
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .highlight_style(Style::default().fg(theme.title).add_modifier(Modifier::BOLD))
         .select(active_tab);
     f.render_widget(tabs, area);
+    // Mirrors the tabs widget's layout: each title is padded by a space on both sides and
+    // followed by a one-column divider.
+    let mut x = inner.x;
+    titles
+        .iter()
+        .map(|title| {
+            let width = (title.chars().count() as u16).min(inner.right().saturating_sub(x + 1));
+            let tab = Rect::new(x + 1, inner.y, width, 1);
+            x += width + 3;
+            tab
+        })
+        .collect()
 }
 
 /// Renders risk distribution across high, medium, and low risk levels.
-pub fn render_risk_distribution<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics) {
+pub fn render_risk_distribution<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, theme: &Theme) {
     if metrics.total_events == 0 {
         let message = Paragraph::new("Waiting for data...")
-            .block(create_block("Risk Distribution"))
-            .style(Style::default().fg(Color::Gray));
+            .block(create_block("Risk Distribution", theme))
+            .style(Style::default().fg(theme.muted));
         f.render_widget(message, area);
         return;
     }
-    let risk_dist = metrics.risk_distribution();
-    let high_count = (risk_dist[0] * metrics.total_events as f64 / 100.0) as u64;
-    let medium_count = (risk_dist[1] * metrics.total_events as f64 / 100.0) as u64;
-    let low_count = (risk_dist[2] * metrics.total_events as f64 / 100.0) as u64;
-    let block = create_block("Risk Distribution");
+    let high_count = chart_value(metrics.high_risk_count);
+    let medium_count = chart_value(metrics.medium_risk_count);
+    let low_count = chart_value(metrics.low_risk_count);
+    let block = create_block("Risk Distribution", theme);
     f.render_widget(block.clone(), area);
     let inner = block.inner(area);
     let chunks = Layout::default()
@@ -274,8 +934,9 @@ pub fn render_risk_distribution<B: Backend>(f: &mut Frame<B>, area: Rect, metric
     let high_chart = BarChart::default()
         .data(&high_data)
         .bar_width(15)
-        .bar_style(Style::default().fg(Color::Red))
-        .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .bar_set(theme.risk_bar_set(0))
+        .bar_style(Style::default().fg(theme.risk_high))
+        .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
         .max(high_count.max(medium_count).max(low_count))
         .bar_gap(0);
     f.render_widget(high_chart, chunks[0]);
@@ -283,8 +944,9 @@ pub fn render_risk_distribution<B: Backend>(f: &mut Frame<B>, area: Rect, metric
     let medium_chart = BarChart::default()
         .data(&medium_data)
         .bar_width(15)
-        .bar_style(Style::default().fg(Color::Yellow))
-        .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .bar_set(theme.risk_bar_set(1))
+        .bar_style(Style::default().fg(theme.risk_medium))
+        .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
         .max(high_count.max(medium_count).max(low_count))
         .bar_gap(0);
     f.render_widget(medium_chart, chunks[1]);
@@ -292,9 +954,980 @@ pub fn render_risk_distribution<B: Backend>(f: &mut Frame<B>, area: Rect, metric
     let low_chart = BarChart::default()
         .data(&low_data)
         .bar_width(15)
-        .bar_style(Style::default().fg(Color::Green))
-        .value_style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .bar_set(theme.risk_bar_set(2))
+        .bar_style(Style::default().fg(theme.risk_low))
+        .value_style(Style::default().fg(theme.text).add_modifier(Modifier::BOLD))
         .max(high_count.max(medium_count).max(low_count))
         .bar_gap(0);
     f.render_widget(low_chart, chunks[2]);
 }
+
+/// Renders a table of the events in each EU AI Act risk tier, with their share and the
+/// provisions defining the tier.
+pub fn render_ai_act_tiers<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, theme: &Theme) {
+    let total = metrics.ai_act_tier_counts.iter().sum::<u64>().max(1) as f64;
+    let rows = AI_ACT_TIERS.iter().map(|&tier| {
+        let count = metrics.ai_act_tier_counts[tier as usize];
+        let color = match tier {
+            AiActTier::Unacceptable => theme.bad,
+            AiActTier::High => theme.risk_high,
+            AiActTier::Limited => theme.risk_medium,
+            AiActTier::Minimal => theme.risk_low,
+        };
+        Row::new(vec![
+            tier.name().to_string(),
+            count.to_string(),
+            format!("{:.1}%", 100.0 * count as f64 / total),
+            tier.article().to_string(),
+        ])
+        .style(Style::default().fg(color))
+    });
+    let header = Row::new(vec!["Tier", "Events", "Share", "Reference"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("EU AI Act Risk Tiers", theme))
+        .widths(&[
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(17),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders a table of the events containing each type of personally identifiable information,
+/// with their share of all events. Special categories under GDPR Art. 9 are shown in red.
+pub fn render_pii_types<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, theme: &Theme) {
+    let total = metrics.total_events.max(1) as f64;
+    let rows = PII_TYPE_NAMES.iter().enumerate().map(|(index, name)| {
+        let count = metrics.pii_type_counts[index];
+        let special = PiiTypes(1 << index).is_special_category();
+        Row::new(vec![
+            name.to_string(),
+            count.to_string(),
+            format!("{:.1}%", 100.0 * count as f64 / total),
+            if special { "Art. 9" } else { "" }.to_string(),
+        ])
+        .style(Style::default().fg(if special { theme.bad } else { theme.text }))
+    });
+    let header = Row::new(vec!["PII type", "Events", "Share", "Special"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("PII Types", theme))
+        .widths(&[
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(8),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the coverage of each NIST AI RMF function by the enabled compliance checks, with
+/// the covered category numbers and the violations of the checks mapped to the function.
+pub fn render_nist_coverage<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    compliance: &ComplianceRegistry,
+    theme: &Theme,
+) {
+    let rows = nist::coverage(compliance, &metrics.violation_counts).map(|function| {
+        let style = if function.violations > 0 { Style::default().fg(theme.bad) } else { Style::default().fg(theme.text) };
+        // Category ids repeat the function name, so only their numbers are listed.
+        let numbers: Vec<&str> = function.covered.iter().filter_map(|id| id.split(' ').nth(1)).collect();
+        Row::new(vec![
+            function.function.name().to_string(),
+            format!("{}/{}", function.covered.len(), function.total),
+            function.violations.to_string(),
+            numbers.join(", "),
+        ])
+        .style(style)
+    });
+    let header = Row::new(vec!["Function", "Covered", "Violations", "Categories"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("NIST AI RMF Coverage", theme))
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            // Wide enough for the six Govern categories.
+            Constraint::Length(16),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the high-risk violations of all cases by state, so open violations can be told
+/// apart from those already acknowledged, waived, or remediated.
+pub fn render_case_summary<B: Backend>(f: &mut Frame<B>, area: Rect, cases: &CaseBook, theme: &Theme) {
+    let spans: Vec<Span> = CASE_STATES
+        .iter()
+        .zip(cases.violations_by_state())
+        .map(|(state, total)| {
+            let color = if *state == CaseState::Open && total > 0 { theme.bad } else { theme.text };
+            Span::styled(format!("{}: {}   ", state.name(), total), Style::default().fg(color))
+        })
+        .collect();
+    let paragraph = Paragraph::new(Spans::from(spans)).block(create_block("High-Risk Violations by Case State", theme));
+    f.render_widget(paragraph, area);
+}
+
+/// Renders the cases in the order they were opened, highlighting the selected one. Open cases
+/// are shown in red and acknowledged ones in yellow.
+///
+/// # Arguments
+///
+/// * `cases` - The cases to list, those matching the search.
+/// * `selected` - Index in `cases` of the case the state keys apply to.
+/// * `compliance` - The compliance flags, used to name the violated checks.
+pub fn render_cases<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    cases: &[&Case],
+    selected: usize,
+    compliance: &ComplianceRegistry,
+    theme: &Theme,
+) {
+    if cases.is_empty() {
+        let message = Paragraph::new("No high-risk violations yet")
+            .block(create_block("Cases", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = cases.iter().map(|case| {
+        // Cases of rule packs that are no longer enabled keep their flag key.
+        let check = compliance
+            .iter()
+            .find(|(_, info)| info.key == case.check)
+            .map_or(case.check.as_str(), |(_, info)| info.name);
+        let row = Row::new(vec![
+            check.to_string(),
+            case.department.clone(),
+            case.state.name().to_string(),
+            case.violations.to_string(),
+            format_utc_time(case.opened_at_ms / 1000),
+            format_utc_time(case.updated_at_ms / 1000),
+        ]);
+        match case.state {
+            CaseState::Open => row.style(Style::default().fg(theme.bad)),
+            CaseState::Acknowledged => row.style(Style::default().fg(theme.warning)),
+            CaseState::Waived | CaseState::Remediated => row.style(Style::default().fg(theme.muted)),
+        }
+    });
+    let header = Row::new(vec!["Check", "Department", "State", "Violations", "Opened", "Updated"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Cases (a: acknowledge, v: waive, r: remediated, o: reopen)", theme))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ")
+        .widths(&[
+            Constraint::Length(20),
+            Constraint::Length(12),
+            Constraint::Length(13),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ]);
+    let mut state = TableState::default();
+    state.select(Some(selected.min(cases.len() - 1)));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+/// Returns the color of an alert of the given severity.
+fn severity_color(severity: AlertSeverity, theme: &Theme) -> Color {
+    match severity {
+        AlertSeverity::Critical => theme.bad,
+        AlertSeverity::Warning => theme.warning,
+        AlertSeverity::Info => theme.title,
+    }
+}
+
+/// Renders the banner line with the most severe active alert, in reverse video while
+/// `highlighted` so that an unacknowledged alert flashes between redraws, followed by the
+/// silence in effect, if any.
+///
+/// # Arguments
+///
+/// * `alerts` - The alert history with the active alerts and the silence.
+/// * `highlighted` - Whether the banner is drawn in its highlighted phase.
+pub fn render_alert_banner<B: Backend>(f: &mut Frame<B>, area: Rect, alerts: &AlertLog, highlighted: bool, theme: &Theme) {
+    let mut spans = Vec::new();
+    if let Some(alert) = alerts.most_severe_active() {
+        let mut text = format!(" {}: {} ", alert.severity.name().to_uppercase(), alert.message);
+        let active = alerts.active_count();
+        if active > 1 {
+            text.push_str(&format!("(+{} more, A: alerts) ", active - 1));
+        }
+        let mut style = Style::default().fg(severity_color(alert.severity, theme)).add_modifier(Modifier::BOLD);
+        if highlighted {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        spans.push(Span::styled(text, style));
+    }
+    if let Some(silence) = alerts.silence() {
+        spans.push(Span::styled(format!(" Alerts silenced: {}", silence), Style::default().fg(theme.muted)));
+    }
+    f.render_widget(Paragraph::new(Spans::from(spans)), area);
+}
+
+/// Renders the alert history, newest first, highlighting the selected alert. Active alerts are
+/// shown in the color of their severity and cleared ones muted.
+///
+/// # Arguments
+///
+/// * `alerts` - The alert history, with the active alerts and the silence.
+/// * `shown` - The alerts to list, those matching the search, newest first.
+/// * `selected` - Index in `shown` of the alert Enter acknowledges.
+pub fn render_alerts<B: Backend>(f: &mut Frame<B>, area: Rect, alerts: &AlertLog, shown: &[&Alert], selected: usize, theme: &Theme) {
+    if alerts.alerts().is_empty() {
+        let message = Paragraph::new("No alerts raised yet")
+            .block(create_block("Alerts", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = shown.iter().map(|alert| {
+        let row = Row::new(vec![
+            format_utc_time(alert.raised_at_ms / 1000),
+            alert.severity.name().to_string(),
+            alert.rule.clone(),
+            alert.subject.clone(),
+            alert.value.clone(),
+            alert.cleared_at_ms.map_or_else(|| "active".to_string(), |cleared_at_ms| format_utc_time(cleared_at_ms / 1000)),
+            if alert.acknowledged { "yes" } else { "no" }.to_string(),
+            if alert.silenced { "yes" } else { "no" }.to_string(),
+        ]);
+        if alert.is_active() {
+            row.style(Style::default().fg(severity_color(alert.severity, theme)))
+        } else {
+            row.style(Style::default().fg(theme.muted))
+        }
+    });
+    let header = Row::new(vec!["Raised", "Severity", "Rule", "Subject", "Value", "Cleared", "Ack", "Silenced"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let title = match alerts.silence() {
+        Some(silence) => format!("Alerts: {} active, silenced: {} (Enter: acknowledge)", alerts.active_count(), silence),
+        None => format!("Alerts: {} active (Enter: acknowledge)", alerts.active_count()),
+    };
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block(&title, theme))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ")
+        .widths(&[
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(18),
+            Constraint::Length(36),
+            Constraint::Length(12),
+            Constraint::Length(9),
+            Constraint::Length(4),
+            Constraint::Length(8),
+        ]);
+    let mut state = TableState::default();
+    state.select(Some(selected.min(shown.len().saturating_sub(1))));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+/// Renders a table of the model versions of a service with their deployment, events,
+/// compliance, and share of high-risk events.
+///
+/// # Arguments
+///
+/// * `metrics` - Metrics holding the per-model breakdown.
+/// * `service` - Index of the service in `SERVICE_NAMES`.
+pub fn render_models<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, service: usize, theme: &Theme) {
+    let empty = ComplianceMetrics::default();
+    let first_model = service * MODELS_PER_SERVICE;
+    let rows = (first_model..first_model + MODELS_PER_SERVICE).map(|model| {
+        let counts = metrics.models.get(model).unwrap_or(&empty);
+        let high_risk = 100.0 * counts.high_risk_count as f64 / counts.total_events.max(1) as f64;
+        Row::new(vec![
+            MODEL_NAMES[model].to_string(),
+            DEPLOYMENT_NAMES[MODEL_DEPLOYMENTS[model] as usize].to_string(),
+            counts.total_events.to_string(),
+            format!("{:.1}%", counts.compliance_percentage()),
+            format!("{:.1}%", high_risk),
+        ])
+    });
+    let header = Row::new(vec!["Model", "Deploy", "Events", "Compliance", "High risk"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Models", theme))
+        .widths(&[
+            Constraint::Length(18),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(9),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders a table of the users with the highest total risk score, or the first users in
+/// another sort order.
+pub fn render_top_users<B: Backend>(f: &mut Frame<B>, area: Rect, users: &[(u32, UserCounts)], sort: SortOrder, theme: &Theme) {
+    let title = match sort.label() {
+        Some(label) => format!("Top Users ({})", label.replace("by count", "by risk")),
+        None => "Top Users by Risk".to_string(),
+    };
+    if users.is_empty() {
+        let message = Paragraph::new("No events with a user id yet")
+            .block(create_block(&title, theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = users.iter().map(|(user, counts)| {
+        Row::new(vec![
+            user.to_string(),
+            counts.events.to_string(),
+            counts.violations.to_string(),
+            counts.high_risk_events.to_string(),
+            format!("{:.1}", counts.average_risk()),
+        ])
+    });
+    let header = Row::new(vec!["User", "Events", "Violations", "High risk", "Avg risk"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block(&title, theme))
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(8),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders a single status line describing the progress of an event log replay.
+pub fn render_replay_status<B: Backend>(f: &mut Frame<B>, area: Rect, status: &ReplayStatus, theme: &Theme) {
+    let elapsed_secs = status.log_elapsed_ms / 1000;
+    let speed = if status.speed > 0.0 { format!("{:.1}x", status.speed) } else { "max speed".to_string() };
+    let state = if status.finished { "complete" } else { "replaying" };
+    let mut text = format!(
+        "Replay: {} @ {} | log time T+{:02}:{:02}:{:02} | {} events | {}",
+        status.source,
+        speed,
+        elapsed_secs / 3600,
+        (elapsed_secs / 60) % 60,
+        elapsed_secs % 60,
+        status.events_replayed,
+        state,
+    );
+    if status.skipped_records > 0 {
+        text.push_str(&format!(" | {} records skipped", status.skipped_records));
+    }
+    if status.arrivals.late > 0 {
+        text.push_str(&format!(" | {} late", status.arrivals.late));
+    }
+    let color = if status.finished { theme.good } else { theme.accent };
+    let paragraph = Paragraph::new(Span::styled(text, Style::default().fg(color).add_modifier(Modifier::BOLD)));
+    f.render_widget(paragraph, area);
+}
+
+/// Renders a single status line naming the department the dashboard is filtered to.
+pub fn render_department_filter<B: Backend>(f: &mut Frame<B>, area: Rect, department: &str, theme: &Theme) {
+    let text = format!("Department: {} (press d to change)", department);
+    let paragraph = Paragraph::new(Span::styled(text, Style::default().fg(theme.heading).add_modifier(Modifier::BOLD)));
+    f.render_widget(paragraph, area);
+}
+
+/// Renders a single status line with the search: the query being typed, or the query
+/// filtering the lists and how many rows of the tab's list match it.
+pub fn render_search<B: Backend>(f: &mut Frame<B>, area: Rect, search: &Search, matches: Option<usize>, theme: &Theme) {
+    let text = if search.editing {
+        format!("Search: {}_ (Enter to keep, Esc to clear)", search.query())
+    } else {
+        let matches = matches.map_or_else(String::new, |count| format!("{} {}, ", count, if count == 1 { "match" } else { "matches" }));
+        format!("Search: {} ({}n/N to jump, / to change, Esc to clear)", search.query(), matches)
+    };
+    let paragraph = Paragraph::new(Span::styled(text, Style::default().fg(theme.heading).add_modifier(Modifier::BOLD)));
+    f.render_widget(paragraph, area);
+}
+
+/// Renders the placeholder of a list with no rows matching the search.
+pub fn render_no_matches<B: Backend>(f: &mut Frame<B>, area: Rect, title: &str, search: &Search, theme: &Theme) {
+    let message = Paragraph::new(format!("Nothing matches \"{}\"", search.query()))
+        .block(create_block(title, theme))
+        .style(Style::default().fg(theme.muted));
+    f.render_widget(message, area);
+}
+
+/// Renders the heading line of a service's compliance view.
+pub fn render_service_header<B: Backend>(f: &mut Frame<B>, area: Rect, service: &str, theme: &Theme) {
+    let text = format!("Service: {} (left/right for other services, Esc to go back)", service);
+    let paragraph = Paragraph::new(Span::styled(text, Style::default().fg(theme.title).add_modifier(Modifier::BOLD)));
+    f.render_widget(paragraph, area);
+}
+
+/// Renders a single status line with the depth of the metrics channel and any injected failures.
+pub fn render_pipeline_health<B: Backend>(f: &mut Frame<B>, area: Rect, health: &PipelineHealth, theme: &Theme) {
+    let mut text = format!(
+        "Pipeline: metrics queue {}/{} (peak {})",
+        health.queue_depth, health.queue_capacity, health.peak_queue_depth,
+    );
+    if let Some(injected) = health.injected_sink_failures {
+        text.push_str(&format!(" | chaos: {} injected sink failures", injected));
+    }
+    // A full queue means the aggregator fell behind and held up the producers at some point.
+    let color = if health.queue_capacity > 0 && health.peak_queue_depth >= health.queue_capacity {
+        theme.warning
+    } else {
+        theme.inactive
+    };
+    f.render_widget(Paragraph::new(Span::styled(text, Style::default().fg(color))), area);
+}
+
+/// Renders a table of the local worker threads with their status, restarts, pinned core, throughput, and
+/// batch latency. Workers that are down are shown in red and restarted ones in yellow.
+pub fn render_thread_throughput<B: Backend>(f: &mut Frame<B>, area: Rect, threads: &[ThreadThroughput], theme: &Theme) {
+    if threads.is_empty() {
+        let message = Paragraph::new("No local worker threads")
+            .block(create_block("Threads", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = threads.iter().map(|thread| {
+        let latency = |pick: fn(&TailLatency) -> Duration| {
+            thread.batch_latency.as_ref().map_or_else(|| "-".to_string(), |l| format_duration(pick(l)))
+        };
+        let row = Row::new(vec![
+            thread.name.clone(),
+            if thread.alive { "alive" } else { "down" }.to_string(),
+            thread.restarts.to_string(),
+            thread.core.map_or_else(|| "-".to_string(), |core| core.to_string()),
+            thread.events.to_string(),
+            format!("{:.1}/s", thread.rate),
+            latency(|l| l.p50),
+            latency(|l| l.p95),
+            latency(|l| l.p999),
+        ]);
+        match (thread.alive, thread.restarts) {
+            (false, _) => row.style(Style::default().fg(theme.bad)),
+            (true, 0) => row,
+            (true, _) => row.style(Style::default().fg(theme.warning)),
+        }
+    });
+    let header = Row::new(vec!["Thread", "Status", "Restarts", "Core", "Events", "Rate", "Batch p50", "p95", "p99.9"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Threads", theme))
+        .widths(&[
+            Constraint::Percentage(12),
+            Constraint::Percentage(8),
+            Constraint::Percentage(9),
+            Constraint::Percentage(6),
+            Constraint::Percentage(15),
+            Constraint::Percentage(14),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the fleet-wide totals: how many instances are connected, and the events, rate, and
+/// compliance of the whole fleet, including this monitor's own workers.
+pub fn render_fleet_summary<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    instances: &[InstanceStatus],
+    theme: &Theme,
+) {
+    let connected = instances.iter().filter(|instance| instance.connected).count();
+    let color = if connected < instances.len() { theme.warning } else { theme.text };
+    let lines = vec![
+        Spans::from(Span::styled(
+            format!("Instances: {} connected of {} reporting", connected, instances.len()),
+            Style::default().fg(color),
+        )),
+        Spans::from(Span::raw(format!(
+            "Fleet: {} events | {:.1} events/s | {:.1}% compliant | {} high risk",
+            metrics.total_events,
+            metrics.processing_rate(),
+            metrics.compliance_percentage(),
+            metrics.high_risk_count
+        ))),
+    ];
+    f.render_widget(Paragraph::new(lines).block(create_block("Fleet", theme)), area);
+}
+
+/// Renders one row per federated instance or remote worker with its connection, events, rate,
+/// and compliance. Disconnected instances are shown in red with the counts they reported.
+pub fn render_instances<B: Backend>(f: &mut Frame<B>, area: Rect, instances: &[InstanceStatus], theme: &Theme) {
+    if instances.is_empty() {
+        let message = Paragraph::new("No instances have reported yet; point them here with --federate")
+            .block(create_block("Instances", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = instances.iter().map(|instance| {
+        let row = Row::new(vec![
+            instance.name.clone(),
+            instance.address.clone(),
+            if instance.connected { "connected" } else { "gone" }.to_string(),
+            instance.events.to_string(),
+            format!("{:.1}/s", instance.rate),
+            format!("{:.1}%", instance.compliance_percentage),
+            instance.violations.to_string(),
+            instance.high_risk_count.to_string(),
+        ]);
+        if instance.connected { row } else { row.style(Style::default().fg(theme.bad)) }
+    });
+    let header = Row::new(vec!["Instance", "Address", "Status", "Events", "Rate", "Compliance", "Violations", "High risk"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Instances", theme))
+        .widths(&[
+            Constraint::Percentage(18),
+            Constraint::Percentage(17),
+            Constraint::Percentage(10),
+            Constraint::Percentage(13),
+            Constraint::Percentage(11),
+            Constraint::Percentage(10),
+            Constraint::Percentage(11),
+            Constraint::Percentage(10),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the service and department groups of the sampled events with the most estimated
+/// violations.
+pub fn render_sample_groups<B: Backend>(f: &mut Frame<B>, area: Rect, groups: &[SampleGroup], theme: &Theme) {
+    let rows = groups.iter().map(|group| {
+        let row = Row::new(vec![
+            group.service.clone(),
+            group.department.clone(),
+            group.sampled.to_string(),
+            format!("{:.0}", group.estimated_events),
+            format!("{:.0}", group.estimated_violations),
+        ]);
+        if group.estimated_violations > 0.0 { row.style(Style::default().fg(theme.bad)) } else { row }
+    });
+    let header = Row::new(vec!["Service", "Department", "Sampled", "Est. events", "Est. violations"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Top Services and Departments (estimated from samples)", theme))
+        .widths(&[
+            Constraint::Percentage(24),
+            Constraint::Percentage(20),
+            Constraint::Percentage(14),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the sampled events, newest first, highlighting the selected one. Events with
+/// violations are shown in red.
+///
+/// # Arguments
+///
+/// * `events` - The retained sampled events matching the search, newest first.
+/// * `selected` - Index in `events` of the event whose details are shown below the table.
+pub fn render_sampled_events<B: Backend>(f: &mut Frame<B>, area: Rect, events: &[&SampledEvent], selected: usize, theme: &Theme) {
+    if events.is_empty() {
+        let message = Paragraph::new("No events sampled yet; sampling is set with --event-sample-rate")
+            .block(create_block("Sampled Events", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = events.iter().map(|event| {
+        let row = Row::new(vec![
+            format_utc_time(event.timestamp_ms / 1000),
+            event.service.to_string(),
+            event.department.to_string(),
+            event.user_id.map_or_else(|| "-".to_string(), |user| user.to_string()),
+            event.region.as_deref().unwrap_or("-").to_string(),
+            event.risk_score.to_string(),
+            event.violations.len().to_string(),
+        ]);
+        if event.violations.is_empty() { row } else { row.style(Style::default().fg(theme.bad)) }
+    });
+    let header = Row::new(vec!["Time", "Service", "Department", "User", "Region", "Risk", "Violations"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Sampled Events (Up/Down: select)", theme))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ")
+        .widths(&[
+            Constraint::Length(9),
+            Constraint::Length(16),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(5),
+            Constraint::Length(10),
+        ]);
+    let mut state = TableState::default();
+    state.select(Some(selected.min(events.len() - 1)));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+/// Renders every field of one sampled event, naming its violated and waived checks.
+///
+/// # Arguments
+///
+/// * `event` - The selected event, or `None` if no events have been sampled.
+/// * `compliance` - The compliance flags, used to name the checks.
+pub fn render_sampled_event_details<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    event: Option<&SampledEvent>,
+    compliance: &ComplianceRegistry,
+    theme: &Theme,
+) {
+    let Some(event) = event else {
+        f.render_widget(create_block("Event Details", theme), area);
+        return;
+    };
+    // Checks of rule packs that are no longer enabled keep their flag key.
+    let names = |keys: &[Cow<'static, str>]| -> String {
+        if keys.is_empty() {
+            return "none".to_string();
+        }
+        keys.iter()
+            .map(|key| compliance.iter().find(|(_, info)| info.key == key.as_ref()).map_or(key.as_ref(), |(_, info)| info.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let optional = |value: &Option<Cow<'static, str>>| value.as_deref().unwrap_or("-").to_string();
+    let id = event.event_id.map_or_else(|| "not audited".to_string(), |id| id.to_string());
+    let user = event.user_id.map_or_else(|| "-".to_string(), |user| user.to_string());
+    let violation_color = if event.violations.is_empty() { theme.text } else { theme.bad };
+    let lines = vec![
+        Spans::from(format!(
+            "Event {} at {} | worker {} | stands for {:.0} events",
+            id,
+            format_utc_time(event.timestamp_ms / 1000),
+            event.worker,
+            event.weight
+        )),
+        Spans::from(format!(
+            "Service: {} | model: {} | vendor: {} | department: {} | user: {}",
+            event.service, event.model, event.vendor, event.department, user
+        )),
+        Spans::from(format!(
+            "Purpose: {} | region: {} | lawful basis: {} | AI Act tier: {} | data sensitivity: {} | risk: {}",
+            optional(&event.purpose),
+            optional(&event.region),
+            optional(&event.lawful_basis),
+            optional(&event.ai_act_tier),
+            event.data_sensitivity,
+            event.risk_score
+        )),
+        Spans::from(format!("PII types: {}", if event.pii_types.is_empty() { "none".to_string() } else { event.pii_types.join(", ") })),
+        Spans::from(Span::styled(format!("Violations: {}", names(&event.violations)), Style::default().fg(violation_color))),
+        Spans::from(format!("Waived: {}", names(&event.waived))),
+    ];
+    f.render_widget(Paragraph::new(lines).block(create_block("Event Details", theme)), area);
+}
+
+/// Renders the vendor registry with each vendor's live usage. High-tier vendors are shown in
+/// red, and medium-tier ones in yellow. Vendors keep the registry's order until sorted.
+pub fn render_vendors<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    metrics: &ComplianceMetrics,
+    vendors: &VendorRegistry,
+    search: &Search,
+    sort: SortOrder,
+    theme: &Theme,
+) {
+    let total = metrics.total_events.max(1) as f64;
+    let mut matching: Vec<_> = vendors.iter().enumerate().filter(|(_, (name, _))| search.matches([*name])).collect();
+    if sort != SortOrder::default() {
+        sort.sort(&mut matching, |&(index, (name, _))| {
+            let (events, violations) = (metrics.vendor_counts[index], metrics.vendor_violations[index]);
+            (name.to_lowercase(), events as f64, violation_rate(violations, events))
+        });
+    }
+    let rows = matching.into_iter().map(|(index, (name, profile))| {
+        let (events, violations) = (metrics.vendor_counts[index], metrics.vendor_violations[index]);
+        let row = Row::new(vec![
+            name.to_string(),
+            profile.risk_tier.map_or("unassessed", VendorRiskTier::name).to_string(),
+            if profile.dpa_signed { "yes" } else { "no" }.to_string(),
+            profile.retention_days.map_or("unknown".to_string(), |days| format!("{} days", days)),
+            if profile.eu_hosting { "yes" } else { "no" }.to_string(),
+            events.to_string(),
+            format!("{:.1}%", 100.0 * events as f64 / total),
+            violations.to_string(),
+            format!("{:.1}%", 100.0 * violations as f64 / events.max(1) as f64),
+        ]);
+        match profile.risk_tier {
+            Some(VendorRiskTier::High) => row.style(Style::default().fg(theme.bad)),
+            Some(VendorRiskTier::Medium) => row.style(Style::default().fg(theme.warning)),
+            _ => row,
+        }
+    });
+    let header = Row::new(vec!["Vendor", "Risk tier", "DPA", "Retention", "EU hosting", "Events", "Share", "Violating", "Violating %"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let title = sort.label().map_or_else(|| "Vendor Registry".to_string(), |label| format!("Vendor Registry ({})", label));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block(&title, theme))
+        .widths(&[
+            Constraint::Percentage(14),
+            Constraint::Percentage(11),
+            Constraint::Percentage(6),
+            Constraint::Percentage(11),
+            Constraint::Percentage(10),
+            Constraint::Percentage(12),
+            Constraint::Percentage(8),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the shadow AI report: each model used without approval, with its events, average
+/// data sensitivity, and the departments using it, most used first.
+pub fn render_shadow_ai<B: Backend>(f: &mut Frame<B>, area: Rect, uses: &[ShadowAiUse], theme: &Theme) {
+    if uses.is_empty() {
+        let message = Paragraph::new("No shadow AI detected: every department uses only approved models")
+            .block(create_block("Shadow AI", theme))
+            .style(Style::default().fg(theme.good));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = uses.iter().map(|shadow_ai_use| {
+        let departments: Vec<String> =
+            shadow_ai_use.departments().iter().map(|(name, events)| format!("{} {}", name, events)).collect();
+        Row::new(vec![
+            shadow_ai_use.service.to_string(),
+            shadow_ai_use.model.to_string(),
+            shadow_ai_use.events.to_string(),
+            format!("{:.1}", shadow_ai_use.avg_sensitivity),
+            departments.join(", "),
+        ])
+    });
+    let header = Row::new(vec!["Service", "Model", "Events", "Avg sensitivity", "Departments"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("Shadow AI (unapproved models)", theme))
+        .widths(&[
+            Constraint::Percentage(15),
+            Constraint::Percentage(17),
+            Constraint::Percentage(11),
+            Constraint::Percentage(14),
+            Constraint::Percentage(43),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders a bar chart displaying vendor usage.
+pub fn render_vendor_chart<B: Backend>(f: &mut Frame<B>, area: Rect, metrics: &ComplianceMetrics, sort: SortOrder, theme: &Theme) {
+    let usage: Vec<(&str, u64, u64)> = VENDOR_NAMES
+        .iter()
+        .enumerate()
+        .map(|(index, name)| (*name, metrics.vendor_counts[index], metrics.vendor_violations[index]))
+        .collect();
+    render_usage_chart(f, area, "Vendor Usage", &usage, metrics.total_events, sort, theme.service_bars, theme);
+}
+
+/// Renders memory usage, aggregator loop lag, and the depth of every internal channel.
+pub fn render_system_health<B: Backend>(f: &mut Frame<B>, area: Rect, health: Option<&PipelineHealth>, theme: &Theme) {
+    let Some(health) = health else {
+        let message = Paragraph::new("Waiting for data...")
+            .block(create_block("System", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    };
+    let rss = health
+        .rss_bytes
+        .map_or_else(|| "n/a".to_string(), |bytes| format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0)));
+    let mut lines = vec![
+        Spans::from(format!("Memory (RSS): {}", rss)),
+        Spans::from(format!("Aggregator loop lag: {}", format_duration(health.aggregator_lag))),
+        Spans::from(format!("Batch latency (interval): {}", format_tail_latency(health.batch_latency.as_ref()))),
+        Spans::from(format!("Batch latency (since start): {}", format_tail_latency(health.run_batch_latency.as_ref()))),
+        Spans::from(format!(
+            "metrics queue: {}/{} (peak {})",
+            health.queue_depth, health.queue_capacity, health.peak_queue_depth
+        )),
+    ];
+    for channel in &health.channels {
+        let text = match channel.capacity {
+            Some(capacity) => format!("{}: {}/{}", channel.name, channel.depth, capacity),
+            None => format!("{}: {}", channel.name, channel.depth),
+        };
+        // A full bounded channel is holding up its producers.
+        let color = if channel.capacity.is_some_and(|capacity| channel.depth >= capacity) {
+            theme.warning
+        } else {
+            theme.text
+        };
+        lines.push(Spans::from(Span::styled(text, Style::default().fg(color))));
+    }
+    f.render_widget(Paragraph::new(lines).block(create_block("System", theme)), area);
+}
+
+/// Formats batch latency percentiles on one line, or a dash if no batch completed.
+fn format_tail_latency(latency: Option<&TailLatency>) -> String {
+    latency.map_or_else(
+        || "-".to_string(),
+        |l| {
+            format!(
+                "p50 {} | p95 {} | p99.9 {} | max {}",
+                format_duration(l.p50),
+                format_duration(l.p95),
+                format_duration(l.p999),
+                format_duration(l.max)
+            )
+        },
+    )
+}
+
+/// Renders a table of the per-batch latency of each ECS system over the last interval.
+pub fn render_system_latencies<B: Backend>(f: &mut Frame<B>, area: Rect, systems: &[SystemLatency], theme: &Theme) {
+    if systems.is_empty() {
+        let message = Paragraph::new("No batches evaluated in the last interval")
+            .block(create_block("ECS Systems", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    let rows = systems.iter().map(|system| {
+        Row::new(vec![
+            system.name.to_string(),
+            system.latency.batches.to_string(),
+            format_duration(system.latency.p50),
+            format_duration(system.latency.p95),
+            format_duration(system.latency.p999),
+        ])
+    });
+    let header = Row::new(vec!["System", "Batches", "p50", "p95", "p99.9"])
+        .style(Style::default().fg(theme.heading).add_modifier(Modifier::BOLD));
+    let table = Table::new(rows)
+        .header(header)
+        .block(create_block("ECS Systems", theme))
+        .widths(&[
+            Constraint::Percentage(36),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+        ]);
+    f.render_widget(table, area);
+}
+
+/// Renders the most recent log lines, with warnings and errors highlighted.
+pub fn render_logs<B: Backend>(f: &mut Frame<B>, area: Rect, lines: &[String], theme: &Theme) {
+    if lines.is_empty() {
+        let message = Paragraph::new("No log messages yet")
+            .block(create_block("Logs", theme))
+            .style(Style::default().fg(theme.muted));
+        f.render_widget(message, area);
+        return;
+    }
+    // Show the newest lines that fit inside the borders.
+    let visible = lines.iter().skip(lines.len().saturating_sub(area.height.saturating_sub(2) as usize));
+    let text: Vec<Spans> = visible
+        .map(|line| {
+            let color = if line.contains(" ERROR ") {
+                theme.bad
+            } else if line.contains(" WARN ") {
+                theme.warning
+            } else {
+                theme.text
+            };
+            Spans::from(Span::styled(line.clone(), Style::default().fg(color)))
+        })
+        .collect();
+    f.render_widget(Paragraph::new(text).block(create_block("Logs", theme)), area);
+}
+
+/// Renders the keyboard and mouse shortcuts in a box centered over `area`.
+///
+/// # Arguments
+///
+/// * `shortcuts` - The keys or mouse gesture of each shortcut, and what it does.
+pub fn render_help<B: Backend>(f: &mut Frame<B>, area: Rect, shortcuts: &[(String, &str)], theme: &Theme) {
+    // The key column fits the longest keys, up to a limit, and the descriptions the rest.
+    let key_width = shortcuts.iter().map(|(keys, _)| keys.chars().count() as u16).max().unwrap_or(0).clamp(10, 20);
+    let width = (key_width + 54).min(area.width);
+    let height = (shortcuts.len() as u16 + 2).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let rows = shortcuts.iter().map(|(keys, action)| {
+        Row::new(vec![
+            Span::styled(keys.as_str(), Style::default().fg(theme.heading).add_modifier(Modifier::BOLD)),
+            Span::raw(*action),
+        ])
+    });
+    let widths = [Constraint::Length(key_width), Constraint::Length(width.saturating_sub(key_width + 3))];
+    let table = Table::new(rows).block(create_block("Help (Esc to close)", theme)).widths(&widths);
+    f.render_widget(Clear, popup);
+    f.render_widget(table, popup);
+}
+
+/// Formats a duration with a unit suited to its magnitude.
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+/// Renders a single informational status line.
+pub fn render_notice<B: Backend>(f: &mut Frame<B>, area: Rect, message: &str, theme: &Theme) {
+    let paragraph = Paragraph::new(Span::styled(message, Style::default().fg(theme.title)));
+    f.render_widget(paragraph, area);
+}
+
+/// Fills the screen with a message saying how small the terminal is and how large it needs to
+/// be, in place of a dashboard too cramped to read.
+///
+/// # Arguments
+///
+/// * `area` - The whole terminal.
+/// * `minimum` - Smallest width and height the dashboard is drawn in.
+pub fn render_terminal_too_small<B: Backend>(f: &mut Frame<B>, area: Rect, minimum: (u16, u16), theme: &Theme) {
+    let lines = vec![
+        Spans::from(Span::styled(
+            format!("Terminal too small: {}x{}", area.width, area.height),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(Span::styled(format!("Enlarge it to at least {}x{}", minimum.0, minimum.1), Style::default().fg(theme.text))),
+    ];
+    // Centered vertically, wrapping onto the lines below on very narrow terminals.
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let message = Rect { y: area.y + top, height: area.height - top, ..area };
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).wrap(Wrap { trim: true }), message);
+}
+
+/// Renders a single status line with the most recent error reported by a component.
+pub fn render_error_status<B: Backend>(f: &mut Frame<B>, area: Rect, message: &str, error_count: usize, theme: &Theme) {
+    let text = format!("Errors: {} | last: {}", error_count, message);
+    let paragraph = Paragraph::new(Span::styled(text, Style::default().fg(theme.bad).add_modifier(Modifier::BOLD)));
+    f.render_widget(paragraph, area);
+}
@@ -0,0 +1,114 @@
+use crate::constants::VENDOR_NAMES;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Risk tier given to a vendor by the organisation's vendor assessment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VendorRiskTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl VendorRiskTier {
+    /// Returns the name of the tier as written in a policy file.
+    pub fn name(self) -> &'static str {
+        match self {
+            VendorRiskTier::Low => "low",
+            VendorRiskTier::Medium => "medium",
+            VendorRiskTier::High => "high",
+        }
+    }
+}
+
+/// Attributes of a vendor in the vendor registry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct VendorProfile {
+    /// Risk tier from the vendor assessment, or `None` if the vendor has not been assessed.
+    #[serde(default)]
+    pub risk_tier: Option<VendorRiskTier>,
+    /// Whether the vendor signed a data processing agreement (GDPR Art. 28).
+    #[serde(default)]
+    pub dpa_signed: bool,
+    /// Days the vendor retains prompts and outputs, or `None` if unknown.
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+    /// Whether the vendor offers hosting within the EU.
+    #[serde(default)]
+    pub eu_hosting: bool,
+}
+
+/// The vendor registry: the attributes of every vendor, indexed like `VENDOR_NAMES`.
+///
+/// Vendors not configured by the policy file are unassessed and have no DPA.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VendorRegistry {
+    profiles: [VendorProfile; VENDOR_NAMES.len()],
+}
+
+impl VendorRegistry {
+    /// Builds the registry from profiles keyed by vendor name.
+    ///
+    /// # Arguments
+    ///
+    /// * `profiles` - Profiles of the configured vendors, keyed by name as in `VENDOR_NAMES`.
+    pub fn from_profiles(profiles: BTreeMap<String, VendorProfile>) -> Result<Self, String> {
+        let mut registry = VendorRegistry::default();
+        for (name, profile) in profiles {
+            let index = VENDOR_NAMES
+                .iter()
+                .position(|candidate| *candidate == name)
+                .ok_or_else(|| format!("unknown vendor '{}'", name))?;
+            registry.profiles[index] = profile;
+        }
+        Ok(registry)
+    }
+
+    /// Returns the profile of the vendor at `vendor_idx`.
+    pub fn profile(&self, vendor_idx: u8) -> &VendorProfile {
+        &self.profiles[vendor_idx as usize]
+    }
+
+    /// Returns a bit mask over `VENDOR_NAMES` of the vendors that signed a DPA.
+    pub fn dpa_vendors(&self) -> u8 {
+        self.profiles
+            .iter()
+            .enumerate()
+            .filter(|(_, profile)| profile.dpa_signed)
+            .fold(0, |mask, (index, _)| mask | 1 << index)
+    }
+
+    /// Iterates over the vendors' names and profiles, in `VENDOR_NAMES` order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &VendorProfile)> {
+        VENDOR_NAMES.iter().copied().zip(self.profiles.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_are_read_by_vendor_name() {
+        let profiles: BTreeMap<String, VendorProfile> = serde_json::from_str(
+            r#"{"Anthropic": {"risk_tier": "low", "dpa_signed": true, "retention_days": 30, "eu_hosting": true},
+                "Stability AI": {"risk_tier": "high"}}"#,
+        )
+        .unwrap();
+        let registry = VendorRegistry::from_profiles(profiles).unwrap();
+        let anthropic = registry.profile(1);
+        assert_eq!(anthropic.risk_tier, Some(VendorRiskTier::Low));
+        assert_eq!(anthropic.retention_days, Some(30));
+        assert!(anthropic.eu_hosting);
+        assert_eq!(registry.profile(4).risk_tier, Some(VendorRiskTier::High));
+        assert!(!registry.profile(4).dpa_signed);
+        assert_eq!(*registry.profile(0), VendorProfile::default());
+        assert_eq!(registry.dpa_vendors(), 0b10);
+
+        let unknown = BTreeMap::from([("Acme".to_string(), VendorProfile::default())]);
+        assert_eq!(VendorRegistry::from_profiles(unknown).unwrap_err(), "unknown vendor 'Acme'");
+        assert!(serde_json::from_str::<VendorProfile>(r#"{"dpa": true}"#).is_err());
+    }
+}
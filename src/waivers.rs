@@ -0,0 +1,169 @@
+use crate::clock::parse_utc_date;
+use crate::compliance::ComplianceRegistry;
+use crate::constants::{DEPARTMENT_NAMES, SERVICE_NAMES};
+use crate::error::{MonitorError, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Days before its expiry from which a waiver is reported as expiring.
+pub const EXPIRY_WARNING_DAYS: u64 = 14;
+
+const DAY_MS: u64 = 86_400_000;
+
+/// An approved exemption from some compliance checks for a department's use of AI services.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Waiver {
+    /// Index into `DEPARTMENT_NAMES` of the exempted department.
+    pub department: u8,
+    /// Index into `SERVICE_NAMES` of the exempted service, or `None` for every service.
+    pub service: Option<u8>,
+    /// Bit flags of the waived compliance checks.
+    pub checks: u64,
+    /// Last day the waiver applies, as written in the waivers file (`YYYY-MM-DD`, UTC).
+    pub expires: String,
+    /// Start of the day after `expires`, in milliseconds since the Unix epoch.
+    pub expires_at_ms: u64,
+    /// Reference to the approval, e.g. a risk ticket.
+    pub ticket: String,
+}
+
+/// Whether a waiver still applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaiverStatus {
+    Active,
+    /// Active, but expiring within `EXPIRY_WARNING_DAYS`.
+    Expiring,
+    Expired,
+}
+
+/// A waiver as written in a waivers file.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct WaiverSpec {
+    department: String,
+    service: Option<String>,
+    /// Keys of the waived compliance checks; every check if omitted.
+    checks: Option<Vec<String>>,
+    expires: String,
+    ticket: String,
+}
+
+impl Waiver {
+    /// Returns whether the waiver applies at `now_ms` or is about to expire.
+    pub fn status(&self, now_ms: u64) -> WaiverStatus {
+        if now_ms >= self.expires_at_ms {
+            WaiverStatus::Expired
+        } else if now_ms + EXPIRY_WARNING_DAYS * DAY_MS >= self.expires_at_ms {
+            WaiverStatus::Expiring
+        } else {
+            WaiverStatus::Active
+        }
+    }
+
+    /// Returns a one-line description naming the ticket, department, and service.
+    pub fn describe(&self) -> String {
+        let service = self.service.map_or("all services", |index| SERVICE_NAMES[index as usize]);
+        format!("waiver {} ({}, {})", self.ticket, DEPARTMENT_NAMES[self.department as usize], service)
+    }
+}
+
+/// Loads the waivers listed in a JSON waivers file.
+///
+/// The file contains an array of waivers, each naming the `department`, optionally the
+/// `service`, the `checks` it waives by key (every check if omitted), the date it `expires`
+/// after, and the approving `ticket`.
+///
+/// # Arguments
+///
+/// * `path` - The waivers file.
+/// * `compliance` - The compliance flags claimed by the enabled rule packs.
+pub fn load_waivers(path: &Path, compliance: &ComplianceRegistry) -> Result<Vec<Waiver>> {
+    let invalid = |message: String| MonitorError::Config(format!("{}: {}", path.display(), message));
+    let contents = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let specs: Vec<WaiverSpec> = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    specs
+        .into_iter()
+        .map(|spec| {
+            let lookup = |names: &[&str], name: &str, kind: &str| {
+                names
+                    .iter()
+                    .position(|candidate| *candidate == name)
+                    .map(|index| index as u8)
+                    .ok_or_else(|| invalid(format!("waiver {}: unknown {} '{}'", spec.ticket, kind, name)))
+            };
+            let department = lookup(&DEPARTMENT_NAMES, &spec.department, "department")?;
+            let service = spec.service.as_deref().map(|name| lookup(&SERVICE_NAMES, name, "service")).transpose()?;
+            let checks = match &spec.checks {
+                Some(keys) => keys.iter().try_fold(0u64, |checks, key| -> Result<u64> {
+                    let (flag, _) = compliance
+                        .iter()
+                        .find(|(_, info)| info.key == key)
+                        .ok_or_else(|| invalid(format!("waiver {}: unknown compliance check '{}'", spec.ticket, key)))?;
+                    Ok(checks | flag.bit())
+                })?,
+                None => compliance.all_compliant(),
+            };
+            let days = parse_utc_date(&spec.expires)
+                .ok_or_else(|| invalid(format!("waiver {}: invalid expiry date '{}'", spec.ticket, spec.expires)))?;
+            Ok(Waiver {
+                department,
+                service,
+                checks,
+                expires: spec.expires,
+                expires_at_ms: (days + 1) * DAY_MS,
+                ticket: spec.ticket,
+            })
+        })
+        .collect()
+}
+
+/// Returns the bit flags of the checks waived for an event at `now_ms`.
+///
+/// # Arguments
+///
+/// * `waivers` - The waivers of the active policy.
+/// * `department` - Index into `DEPARTMENT_NAMES` of the event's department.
+/// * `service` - Index into `SERVICE_NAMES` of the event's service.
+/// * `now_ms` - Evaluation time, in milliseconds since the Unix epoch.
+pub fn waived_checks(waivers: &[Waiver], department: u8, service: u8, now_ms: u64) -> u64 {
+    waivers
+        .iter()
+        .filter(|waiver| waiver.department == department && waiver.service.is_none_or(|index| index == service))
+        .filter(|waiver| now_ms < waiver.expires_at_ms)
+        .fold(0, |checks, waiver| checks | waiver.checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compliance::{GDPR, INTERNAL_POLICY};
+
+    #[test]
+    fn waivers_apply_to_matching_events_until_they_expire() {
+        let path = std::env::temp_dir().join(format!("waivers-test-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"[
+                {"department": "Finance", "service": "ChatGPT", "checks": ["internal_policy"], "expires": "2025-12-31", "ticket": "RISK-123"},
+                {"department": "Legal", "expires": "2025-06-30", "ticket": "RISK-7"}
+            ]"#,
+        )
+        .unwrap();
+        let compliance = ComplianceRegistry::builtin();
+        let waivers = load_waivers(&path, &compliance).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let (finance, legal, chatgpt, claude) = (2, 4, 0, 1);
+        let before_expiry = parse_utc_date("2025-12-31").unwrap() * DAY_MS + DAY_MS - 1;
+        assert_eq!(waived_checks(&waivers, finance, chatgpt, before_expiry), INTERNAL_POLICY.bit());
+        assert_eq!(waived_checks(&waivers, finance, claude, before_expiry), 0);
+        assert_eq!(waived_checks(&waivers, finance, chatgpt, before_expiry + 1), 0);
+        let june = parse_utc_date("2025-06-20").unwrap() * DAY_MS;
+        assert_ne!(waived_checks(&waivers, legal, claude, june) & GDPR.bit(), 0);
+        assert_eq!(waivers[0].status(june), WaiverStatus::Active);
+        assert_eq!(waivers[1].status(june), WaiverStatus::Expiring);
+        assert_eq!(waivers[1].status(before_expiry), WaiverStatus::Expired);
+        assert_eq!(waivers[0].describe(), "waiver RISK-123 (Finance, ChatGPT)");
+    }
+}